@@ -0,0 +1,670 @@
+//! Sound cues for block interactions and footsteps.
+//!
+//! `AudioSystem` resolves which sample a gameplay event plays and remembers the most recent
+//! cue for the debug overlay. Built with the `audio` feature it also mixes those samples
+//! into the default output device. Every cue has a built-in synthesized sound; a 16-bit PCM
+//! WAV file named after the cue in `SOUND_DIR` replaces it. A missing device means silence.
+
+use std::collections::HashMap;
+use std::f32::consts::TAU;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Directory, relative to the working directory, that holds `<cue>.wav` samples.
+pub const SOUND_DIR: &str = "sounds";
+
+/// Voices beyond this steal the oldest slot, so rapid breaking can't pile up sounds.
+const MAX_VOICES: usize = 16;
+/// Ambience looped while the player's head is under water.
+pub const UNDERWATER_LOOP: &str = "underwater_loop";
+/// One-pole low-pass coefficient for sounds heard from under water; lower is duller.
+const MUFFLE_CUTOFF: f32 = 0.12;
+/// Level kept by sounds heard from under water.
+const MUFFLE_GAIN: f32 = 0.6;
+/// Sample rate of the built-in sounds.
+const SYNTH_RATE: u32 = 22_050;
+/// Length of the built-in underwater ambience, whose ends are cross-faded so it loops cleanly.
+const UNDERWATER_LOOP_SECONDS: f32 = 2.0;
+
+/// Material group shared by blocks that should sound alike.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SoundMaterial {
+    Stone,
+    Wood,
+    Grass,
+    Sand,
+    Glass,
+    Metal,
+    Water,
+}
+
+impl SoundMaterial {
+    const ALL: [SoundMaterial; 7] = [
+        SoundMaterial::Stone,
+        SoundMaterial::Wood,
+        SoundMaterial::Grass,
+        SoundMaterial::Sand,
+        SoundMaterial::Glass,
+        SoundMaterial::Metal,
+        SoundMaterial::Water,
+    ];
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SoundEvent {
+    Place,
+    Break,
+    Footstep,
+}
+
+impl SoundEvent {
+    const ALL: [SoundEvent; 3] = [SoundEvent::Place, SoundEvent::Break, SoundEvent::Footstep];
+}
+
+/// Sample names for one material, one per event.
+struct SampleSet {
+    place: &'static str,
+    break_: &'static str,
+    footstep: &'static str,
+}
+
+impl SampleSet {
+    fn get(&self, event: SoundEvent) -> &'static str {
+        match event {
+            SoundEvent::Place => self.place,
+            SoundEvent::Break => self.break_,
+            SoundEvent::Footstep => self.footstep,
+        }
+    }
+}
+
+fn sample_set(material: SoundMaterial) -> SampleSet {
+    match material {
+        SoundMaterial::Stone => SampleSet {
+            place: "stone_place",
+            break_: "stone_break",
+            footstep: "stone_step",
+        },
+        SoundMaterial::Wood => SampleSet {
+            place: "wood_place",
+            break_: "wood_break",
+            footstep: "wood_step",
+        },
+        SoundMaterial::Grass => SampleSet {
+            place: "grass_place",
+            break_: "grass_break",
+            footstep: "grass_step",
+        },
+        SoundMaterial::Sand => SampleSet {
+            place: "sand_place",
+            break_: "sand_break",
+            footstep: "sand_step",
+        },
+        SoundMaterial::Glass => SampleSet {
+            place: "glass_place",
+            break_: "glass_break",
+            footstep: "glass_step",
+        },
+        SoundMaterial::Metal => SampleSet {
+            place: "metal_place",
+            break_: "metal_break",
+            footstep: "metal_step",
+        },
+        SoundMaterial::Water => SampleSet {
+            place: "water_pour",
+            break_: "water_scoop",
+            footstep: "water_splash",
+        },
+    }
+}
+
+pub fn sample_name(material: SoundMaterial, event: SoundEvent) -> &'static str {
+    sample_set(material).get(event)
+}
+
+/// A decoded sample, mixed down to mono.
+#[derive(Debug, PartialEq)]
+struct Clip {
+    sample_rate: u32,
+    samples: Vec<f32>,
+}
+
+/// Reads a RIFF/WAVE file holding 16-bit PCM; anything else is rejected.
+fn decode_wav(bytes: &[u8]) -> Option<Clip> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return None;
+    }
+    let mut format = None;
+    let mut offset = 12;
+    while offset + 8 <= bytes.len() {
+        let id = &bytes[offset..offset + 4];
+        let len = u32::from_le_bytes(bytes[offset + 4..offset + 8].try_into().ok()?) as usize;
+        let body = bytes.get(offset + 8..offset + 8 + len)?;
+        match id {
+            b"fmt " if len >= 16 => {
+                let tag = u16::from_le_bytes([body[0], body[1]]);
+                let channels = u16::from_le_bytes([body[2], body[3]]) as usize;
+                let sample_rate = u32::from_le_bytes(body[4..8].try_into().ok()?);
+                let bits = u16::from_le_bytes([body[14], body[15]]);
+                if tag != 1 || bits != 16 || channels == 0 || sample_rate == 0 {
+                    return None;
+                }
+                format = Some((channels, sample_rate));
+            }
+            b"data" => {
+                let (channels, sample_rate) = format?;
+                let samples = body
+                    .chunks_exact(2 * channels)
+                    .map(|frame| {
+                        let sum: f32 = frame
+                            .chunks_exact(2)
+                            .map(|pair| i16::from_le_bytes([pair[0], pair[1]]) as f32)
+                            .sum();
+                        sum / (channels as f32 * 32768.0)
+                    })
+                    .collect();
+                return Some(Clip {
+                    sample_rate,
+                    samples,
+                });
+            }
+            _ => {}
+        }
+        // Chunks are padded to an even length.
+        offset += 8 + len + (len & 1);
+    }
+    None
+}
+
+/// Xorshift white noise in -1..1; seeded per cue so every run sounds the same.
+struct Noise(u32);
+
+impl Noise {
+    fn next(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0 as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+}
+
+/// How a material colours its built-in sounds: a decaying tone over low-passed noise.
+struct Timbre {
+    tone_hz: f32,
+    /// Share of the tone in the mix; the rest is noise.
+    tone_mix: f32,
+    /// One-pole low-pass coefficient for the noise; lower is duller.
+    noise_cutoff: f32,
+    /// Stretch applied to the event's length, so hard materials ring on.
+    ring: f32,
+}
+
+fn timbre(material: SoundMaterial) -> Timbre {
+    let (tone_hz, tone_mix, noise_cutoff, ring) = match material {
+        SoundMaterial::Stone => (140.0, 0.25, 0.45, 1.0),
+        SoundMaterial::Wood => (260.0, 0.5, 0.3, 1.1),
+        SoundMaterial::Grass => (0.0, 0.0, 0.12, 1.2),
+        SoundMaterial::Sand => (0.0, 0.0, 0.22, 1.4),
+        SoundMaterial::Glass => (2100.0, 0.6, 0.7, 1.6),
+        SoundMaterial::Metal => (880.0, 0.65, 0.5, 1.8),
+        SoundMaterial::Water => (420.0, 0.3, 0.08, 1.5),
+    };
+    Timbre {
+        tone_hz,
+        tone_mix,
+        noise_cutoff,
+        ring,
+    }
+}
+
+/// The cue played for `material` and `event` when no sample file replaces it.
+fn synthesize(material: SoundMaterial, event: SoundEvent) -> Clip {
+    let timbre = timbre(material);
+    let (seconds, level) = match event {
+        SoundEvent::Footstep => (0.09, 0.3),
+        SoundEvent::Place => (0.14, 0.55),
+        SoundEvent::Break => (0.28, 0.7),
+    };
+    let len = (seconds * timbre.ring * SYNTH_RATE as f32) as usize;
+    let mut noise = Noise(0x9E37_79B9 ^ ((material as u32) << 8 | event as u32));
+    // Low-passing white noise loses level; this puts it back roughly.
+    let noise_gain = timbre.noise_cutoff.sqrt().recip();
+    let mut filtered = 0.0;
+    let samples = (0..len)
+        .map(|i| {
+            let t = i as f32 / SYNTH_RATE as f32;
+            let attack = (i as f32 / 32.0).min(1.0);
+            let envelope = attack * (1.0 - i as f32 / len as f32).powi(2);
+            filtered += (noise.next() - filtered) * timbre.noise_cutoff;
+            let tone = (TAU * timbre.tone_hz * t).sin();
+            let body = filtered * noise_gain * (1.0 - timbre.tone_mix) + tone * timbre.tone_mix;
+            (body * envelope * level).clamp(-1.0, 1.0)
+        })
+        .collect();
+    Clip {
+        sample_rate: SYNTH_RATE,
+        samples,
+    }
+}
+
+/// A low, slowly swelling rumble for `UNDERWATER_LOOP`.
+fn synthesize_underwater_loop() -> Clip {
+    let len = (UNDERWATER_LOOP_SECONDS * SYNTH_RATE as f32) as usize;
+    let fade = len / 8;
+    let mut noise = Noise(0x5EA_F100);
+    let mut filtered = 0.0;
+    let mut samples: Vec<f32> = (0..len + fade)
+        .map(|i| {
+            let t = i as f32 / SYNTH_RATE as f32;
+            filtered += (noise.next() - filtered) * 0.01;
+            let swell = 0.75 + 0.25 * (TAU * 0.5 * t).sin();
+            (filtered * 10.0 * swell * 0.25).clamp(-1.0, 1.0)
+        })
+        .collect();
+    // Blend the overhang into the start so the wrap-around has no click.
+    for i in 0..fade {
+        let blend = i as f32 / fade as f32;
+        samples[i] = samples[i] * blend + samples[len + i] * (1.0 - blend);
+    }
+    samples.truncate(len);
+    Clip {
+        sample_rate: SYNTH_RATE,
+        samples,
+    }
+}
+
+/// A `<name>.wav` from `dir`, if one is there and decodes.
+fn load_sample(dir: &Path, name: &str) -> Option<Clip> {
+    let path = dir.join(format!("{name}.wav"));
+    let bytes = fs::read(&path).ok()?;
+    let clip = decode_wav(&bytes);
+    if clip.is_none() {
+        println!(
+            "[Audio] {} isn't 16-bit PCM WAV; using the built-in sound.",
+            path.display()
+        );
+    }
+    clip
+}
+
+/// A clip for every cue, built once when the audio system starts so playback never touches
+/// the disk. Sample files in the sound directory replace the synthesized ones.
+#[derive(Default)]
+struct SampleBank {
+    clips: HashMap<&'static str, Arc<Clip>>,
+}
+
+impl SampleBank {
+    fn load(dir: &Path) -> Self {
+        let mut clips = HashMap::new();
+        for material in SoundMaterial::ALL {
+            for event in SoundEvent::ALL {
+                let name = sample_name(material, event);
+                let clip = load_sample(dir, name).unwrap_or_else(|| synthesize(material, event));
+                clips.insert(name, Arc::new(clip));
+            }
+        }
+        let ambience =
+            load_sample(dir, UNDERWATER_LOOP).unwrap_or_else(synthesize_underwater_loop);
+        clips.insert(UNDERWATER_LOOP, Arc::new(ambience));
+        Self { clips }
+    }
+
+    fn get(&self, name: &str) -> Option<Arc<Clip>> {
+        self.clips.get(name).cloned()
+    }
+}
+
+struct Voice {
+    clip: Arc<Clip>,
+    position: f32,
+    step: f32,
+    /// Looping voices are ambience: they repeat until stopped and are never muffled.
+    looping: bool,
+}
+
+/// Sums the playing voices; shared with the output stream's callback.
+struct Mixer {
+    output_rate: u32,
+    gain: f32,
+    voices: Vec<Voice>,
+    muffled: bool,
+    lowpass: f32,
+}
+
+impl Mixer {
+    fn new(gain: f32) -> Self {
+        Self {
+            output_rate: 44_100,
+            gain,
+            voices: Vec::new(),
+            muffled: false,
+            lowpass: 0.0,
+        }
+    }
+
+    fn start(&mut self, clip: Arc<Clip>) {
+        self.push(clip, false);
+    }
+
+    fn start_loop(&mut self, clip: Arc<Clip>) {
+        self.push(clip, true);
+    }
+
+    fn stop_loops(&mut self) {
+        self.voices.retain(|voice| !voice.looping);
+    }
+
+    fn push(&mut self, clip: Arc<Clip>, looping: bool) {
+        if clip.samples.is_empty() {
+            return;
+        }
+        if self.voices.len() >= MAX_VOICES {
+            match self.voices.iter().position(|voice| !voice.looping) {
+                Some(oldest) => {
+                    self.voices.remove(oldest);
+                }
+                None => return,
+            }
+        }
+        let step = clip.sample_rate as f32 / self.output_rate as f32;
+        self.voices.push(Voice {
+            clip,
+            position: 0.0,
+            step,
+            looping,
+        });
+    }
+
+    /// Fills an interleaved buffer, resampling each voice by nearest neighbour.
+    #[cfg_attr(not(feature = "audio"), allow(dead_code))]
+    fn mix(&mut self, out: &mut [f32], channels: usize) {
+        for frame in out.chunks_mut(channels.max(1)) {
+            let mut effects = 0.0;
+            let mut ambience = 0.0;
+            for voice in &mut self.voices {
+                let len = voice.clip.samples.len();
+                let sample = voice
+                    .clip
+                    .samples
+                    .get(voice.position as usize)
+                    .copied()
+                    .unwrap_or(0.0);
+                voice.position += voice.step;
+                if voice.looping {
+                    ambience += sample;
+                    if voice.position as usize >= len {
+                        voice.position -= len as f32;
+                    }
+                } else {
+                    effects += sample;
+                }
+            }
+            if self.muffled {
+                self.lowpass += (effects - self.lowpass) * MUFFLE_CUTOFF;
+                effects = self.lowpass * MUFFLE_GAIN;
+            }
+            frame.fill(((effects + ambience) * self.gain).clamp(-1.0, 1.0));
+        }
+        self.voices
+            .retain(|voice| voice.looping || (voice.position as usize) < voice.clip.samples.len());
+    }
+}
+
+#[cfg(feature = "audio")]
+type Stream = cpal::Stream;
+#[cfg(not(feature = "audio"))]
+type Stream = ();
+
+#[cfg(feature = "audio")]
+fn open_output(mixer: &Arc<Mutex<Mixer>>) -> Option<Stream> {
+    use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+
+    fn build<T>(
+        device: &cpal::Device,
+        config: &cpal::StreamConfig,
+        mixer: Arc<Mutex<Mixer>>,
+    ) -> Result<cpal::Stream, cpal::BuildStreamError>
+    where
+        T: cpal::SizedSample + cpal::FromSample<f32>,
+    {
+        let channels = config.channels as usize;
+        let mut scratch = Vec::new();
+        device.build_output_stream(
+            config,
+            move |data: &mut [T], _| {
+                scratch.resize(data.len(), 0.0);
+                match mixer.lock() {
+                    Ok(mut mixer) => mixer.mix(&mut scratch, channels),
+                    Err(_) => scratch.fill(0.0),
+                }
+                for (out, sample) in data.iter_mut().zip(&scratch) {
+                    *out = T::from_sample(*sample);
+                }
+            },
+            |err| eprintln!("[Audio] Output stream error: {err}"),
+            None,
+        )
+    }
+
+    let Some(device) = cpal::default_host().default_output_device() else {
+        println!("[Audio] No output device; sound is disabled.");
+        return None;
+    };
+    let supported = device.default_output_config().ok()?;
+    let config: cpal::StreamConfig = supported.config();
+    if let Ok(mut mixer) = mixer.lock() {
+        mixer.output_rate = config.sample_rate.0;
+    }
+    let mixer = Arc::clone(mixer);
+    let stream = match supported.sample_format() {
+        cpal::SampleFormat::F32 => build::<f32>(&device, &config, mixer),
+        cpal::SampleFormat::I16 => build::<i16>(&device, &config, mixer),
+        cpal::SampleFormat::U16 => build::<u16>(&device, &config, mixer),
+        other => {
+            println!("[Audio] Unsupported sample format {other:?}; sound is disabled.");
+            return None;
+        }
+    };
+    match stream.map_err(|err| err.to_string()).and_then(|stream| {
+        stream.play().map_err(|err| err.to_string())?;
+        Ok(stream)
+    }) {
+        Ok(stream) => Some(stream),
+        Err(err) => {
+            println!("[Audio] Could not start output: {err}");
+            None
+        }
+    }
+}
+
+#[cfg(not(feature = "audio"))]
+fn open_output(_mixer: &Arc<Mutex<Mixer>>) -> Option<Stream> {
+    None
+}
+
+pub struct AudioSystem {
+    volume: f32,
+    last_cue: Option<&'static str>,
+    samples: SampleBank,
+    mixer: Arc<Mutex<Mixer>>,
+    output: Option<Stream>,
+    submerged: bool,
+}
+
+impl AudioSystem {
+    pub fn new(volume: f32) -> Self {
+        let volume = volume.clamp(0.0, 1.0);
+        let mixer = Arc::new(Mutex::new(Mixer::new(volume)));
+        let output = open_output(&mixer);
+        // Nothing is decoded or synthesized when there's no device to hear it.
+        let samples = if output.is_some() {
+            SampleBank::load(Path::new(SOUND_DIR))
+        } else {
+            SampleBank::default()
+        };
+        Self {
+            volume,
+            last_cue: None,
+            samples,
+            mixer,
+            output,
+            submerged: false,
+        }
+    }
+
+    /// True while samples are actually reaching an output device.
+    pub fn is_live(&self) -> bool {
+        self.output.is_some()
+    }
+
+    /// Changes the master gain, including for sounds that are already playing.
+    pub fn set_volume(&mut self, volume: f32) {
+        self.volume = volume.clamp(0.0, 1.0);
+        if let Ok(mut mixer) = self.mixer.lock() {
+            mixer.gain = self.volume;
+        }
+    }
+
+    /// Resolves and starts the sample for `event`; muted playback is dropped.
+    pub fn play(&mut self, material: SoundMaterial, event: SoundEvent) {
+        if self.volume <= 0.0 {
+            return;
+        }
+        let name = sample_name(material, event);
+        self.last_cue = Some(name);
+        if self.output.is_none() {
+            return;
+        }
+        if let Some(clip) = self.samples.get(name) {
+            if let Ok(mut mixer) = self.mixer.lock() {
+                mixer.start(clip);
+            }
+        }
+    }
+
+    /// Muffles every other sound and loops `UNDERWATER_LOOP` while the listener is submerged.
+    pub fn set_submerged(&mut self, submerged: bool) {
+        if submerged == self.submerged {
+            return;
+        }
+        self.submerged = submerged;
+        let ambience = if submerged && self.output.is_some() {
+            self.samples.get(UNDERWATER_LOOP)
+        } else {
+            None
+        };
+        if let Ok(mut mixer) = self.mixer.lock() {
+            mixer.muffled = submerged;
+            mixer.stop_loops();
+            if let Some(clip) = ambience {
+                mixer.start_loop(clip);
+            }
+        }
+    }
+
+    pub fn is_submerged(&self) -> bool {
+        self.submerged
+    }
+
+    pub fn last_cue(&self) -> Option<&'static str> {
+        self.last_cue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wav_bytes(channels: u16, sample_rate: u32, samples: &[i16]) -> Vec<u8> {
+        let data: Vec<u8> = samples
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect();
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&channels.to_le_bytes());
+        bytes.extend_from_slice(&sample_rate.to_le_bytes());
+        bytes.extend_from_slice(&(sample_rate * channels as u32 * 2).to_le_bytes());
+        bytes.extend_from_slice(&(channels * 2).to_le_bytes());
+        bytes.extend_from_slice(&16u16.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&data);
+        bytes
+    }
+
+    #[test]
+    fn stereo_wav_decodes_to_mono_and_mixes_at_the_master_gain() {
+        let bytes = wav_bytes(2, 22_050, &[16384, 16384, -32768, 0]);
+        let clip = decode_wav(&bytes).expect("valid wav");
+        assert_eq!(clip.sample_rate, 22_050);
+        assert_eq!(clip.samples, vec![0.5, -0.5]);
+        assert!(decode_wav(&bytes[..20]).is_none());
+
+        // A 22.05 kHz clip on a 44.1 kHz stereo output repeats each sample twice.
+        let mut mixer = Mixer::new(0.5);
+        mixer.start(Arc::new(clip));
+        let mut out = [0.0; 10];
+        mixer.mix(&mut out, 2);
+        assert_eq!(
+            out,
+            [0.25, 0.25, 0.25, 0.25, -0.25, -0.25, -0.25, -0.25, 0.0, 0.0]
+        );
+        assert!(mixer.voices.is_empty());
+    }
+
+    #[test]
+    fn ambience_loops_unmuffled_while_other_sounds_are_muffled() {
+        let clip = |samples: Vec<f32>| {
+            Arc::new(Clip {
+                sample_rate: 44_100,
+                samples,
+            })
+        };
+        let mut mixer = Mixer::new(1.0);
+        mixer.muffled = true;
+        mixer.start_loop(clip(vec![0.1, 0.2]));
+        mixer.start(clip(vec![1.0]));
+        let mut out = [0.0; 5];
+        mixer.mix(&mut out, 1);
+
+        let first_effect = MUFFLE_CUTOFF * MUFFLE_GAIN;
+        assert!((out[0] - (0.1 + first_effect)).abs() < 1e-6);
+        // The one-shot has ended, but its muffled tail decays instead of cutting off.
+        assert!(out[1] > 0.2 && out[1] < 0.2 + first_effect);
+        assert!(out[2] > 0.1 && out[2] < out[1]);
+        assert_eq!(mixer.voices.len(), 1);
+
+        mixer.stop_loops();
+        assert!(mixer.voices.is_empty());
+    }
+
+    #[test]
+    fn every_cue_has_an_audible_built_in_sound_without_sample_files() {
+        let bank = SampleBank::load(Path::new("no/such/sound/dir"));
+        let names = SoundMaterial::ALL
+            .iter()
+            .flat_map(|&material| {
+                SoundEvent::ALL
+                    .iter()
+                    .map(move |&event| sample_name(material, event))
+            })
+            .chain([UNDERWATER_LOOP]);
+        for name in names {
+            let clip = bank.get(name).expect(name);
+            let peak = clip.samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+            assert!(peak > 0.05 && peak <= 1.0, "{name} peaks at {peak}");
+        }
+
+        // The ambience wraps around without a jump.
+        let ambience = bank.get(UNDERWATER_LOOP).unwrap();
+        let (first, last) = (ambience.samples[0], *ambience.samples.last().unwrap());
+        assert!((first - last).abs() < 0.05);
+    }
+}
@@ -1,3 +1,5 @@
+use crate::audio::SoundMaterial;
+
 #[repr(u8)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum BlockType {
@@ -24,6 +26,50 @@ pub enum BlockType {
     VoltageSource,
     Ground,
     Torch,
+    Capacitor,
+    Switch,
+    Led,
+    Diode,
+    Potentiometer,
+    Lava,
+    Sponge,
+    /// A sponge that has soaked up all it can; it dries out when broken.
+    WetSponge,
+}
+
+/// Liquid held by a fluid cell. Every kind flows by the same level rules; they differ in
+/// how often they step and in how they look.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FluidKind {
+    #[default]
+    Water,
+    Lava,
+}
+
+impl FluidKind {
+    pub const ALL: [FluidKind; 2] = [FluidKind::Water, FluidKind::Lava];
+
+    /// Inverse of `kind as u8`; `None` for ids no kind uses.
+    pub fn from_id(id: u8) -> Option<Self> {
+        Self::ALL.get(id as usize).copied()
+    }
+
+    /// The block a cell of this fluid reads as, and that places it from the palette.
+    pub fn block_type(self) -> BlockType {
+        match self {
+            FluidKind::Water => BlockType::Water,
+            FluidKind::Lava => BlockType::Lava,
+        }
+    }
+
+    pub fn from_block(block: BlockType) -> Option<Self> {
+        match block {
+            BlockType::Water => Some(FluidKind::Water),
+            BlockType::Lava => Some(FluidKind::Lava),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -98,6 +144,16 @@ impl Axis {
 }
 
 impl BlockFace {
+    #[cfg(test)]
+    pub const ALL: [BlockFace; 6] = [
+        BlockFace::Top,
+        BlockFace::Bottom,
+        BlockFace::North,
+        BlockFace::South,
+        BlockFace::East,
+        BlockFace::West,
+    ];
+
     pub fn axis(self) -> Axis {
         match self {
             BlockFace::Top | BlockFace::Bottom => Axis::Y,
@@ -166,6 +222,11 @@ pub enum ElectricalKind {
     Resistor,
     VoltageSource,
     Ground,
+    Capacitor,
+    Switch,
+    Led,
+    Diode,
+    Potentiometer,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -214,6 +275,8 @@ impl TextureRule {
 #[derive(Clone, Copy, Debug)]
 pub struct BlockInfo {
     pub name: &'static str,
+    /// One line for tooltips on what the block is or does.
+    pub description: &'static str,
     pub is_solid: bool,
     pub occludes: bool,
     pub hardness: f32,
@@ -222,11 +285,12 @@ pub struct BlockInfo {
     pub render_kind: RenderKind,
 }
 
-pub const VARIANT_COUNT: usize = 23;
+pub const VARIANT_COUNT: usize = 31;
 
 const BLOCK_INFOS: [BlockInfo; VARIANT_COUNT] = [
     BlockInfo {
         name: "Air",
+        description: "Empty space",
         is_solid: false,
         occludes: false,
         hardness: 0.0,
@@ -236,6 +300,7 @@ const BLOCK_INFOS: [BlockInfo; VARIANT_COUNT] = [
     },
     BlockInfo {
         name: "Grass",
+        description: "Topsoil with a living green cover",
         is_solid: true,
         occludes: true,
         hardness: 0.6,
@@ -245,6 +310,7 @@ const BLOCK_INFOS: [BlockInfo; VARIANT_COUNT] = [
     },
     BlockInfo {
         name: "Dirt",
+        description: "Soft soil found under grass",
         is_solid: true,
         occludes: true,
         hardness: 0.5,
@@ -254,6 +320,7 @@ const BLOCK_INFOS: [BlockInfo; VARIANT_COUNT] = [
     },
     BlockInfo {
         name: "Stone",
+        description: "The solid rock most terrain is made of",
         is_solid: true,
         occludes: true,
         hardness: 1.5,
@@ -263,6 +330,7 @@ const BLOCK_INFOS: [BlockInfo; VARIANT_COUNT] = [
     },
     BlockInfo {
         name: "Wood",
+        description: "A log from a tree trunk",
         is_solid: true,
         occludes: true,
         hardness: 1.0,
@@ -272,6 +340,7 @@ const BLOCK_INFOS: [BlockInfo; VARIANT_COUNT] = [
     },
     BlockInfo {
         name: "Sand",
+        description: "Loose grains from beaches and deserts",
         is_solid: true,
         occludes: true,
         hardness: 0.5,
@@ -281,6 +350,7 @@ const BLOCK_INFOS: [BlockInfo; VARIANT_COUNT] = [
     },
     BlockInfo {
         name: "Leaves",
+        description: "Foliage that lets some light through",
         is_solid: true,
         occludes: false,
         hardness: 0.2,
@@ -290,6 +360,7 @@ const BLOCK_INFOS: [BlockInfo; VARIANT_COUNT] = [
     },
     BlockInfo {
         name: "Coal Ore",
+        description: "Stone streaked with coal",
         is_solid: true,
         occludes: true,
         hardness: 1.2,
@@ -299,6 +370,7 @@ const BLOCK_INFOS: [BlockInfo; VARIANT_COUNT] = [
     },
     BlockInfo {
         name: "Iron Ore",
+        description: "Stone flecked with iron",
         is_solid: true,
         occludes: true,
         hardness: 1.4,
@@ -308,6 +380,7 @@ const BLOCK_INFOS: [BlockInfo; VARIANT_COUNT] = [
     },
     BlockInfo {
         name: "Water",
+        description: "A liquid that flows downhill",
         is_solid: false,
         occludes: false,
         hardness: 0.0,
@@ -317,6 +390,7 @@ const BLOCK_INFOS: [BlockInfo; VARIANT_COUNT] = [
     },
     BlockInfo {
         name: "Rose",
+        description: "A red flower for decoration",
         is_solid: false,
         occludes: false,
         hardness: 0.0,
@@ -326,6 +400,7 @@ const BLOCK_INFOS: [BlockInfo; VARIANT_COUNT] = [
     },
     BlockInfo {
         name: "Tulip",
+        description: "A bright flower for decoration",
         is_solid: false,
         occludes: false,
         hardness: 0.0,
@@ -335,6 +410,7 @@ const BLOCK_INFOS: [BlockInfo; VARIANT_COUNT] = [
     },
     BlockInfo {
         name: "Glow Shroom",
+        description: "A cave fungus that gives off light",
         is_solid: false,
         occludes: false,
         hardness: 0.0,
@@ -344,6 +420,7 @@ const BLOCK_INFOS: [BlockInfo; VARIANT_COUNT] = [
     },
     BlockInfo {
         name: "Cave Crystal",
+        description: "A glowing crystal from deep caves",
         is_solid: true,
         occludes: true,
         hardness: 0.6,
@@ -353,6 +430,7 @@ const BLOCK_INFOS: [BlockInfo; VARIANT_COUNT] = [
     },
     BlockInfo {
         name: "Cave Moss",
+        description: "Damp moss that carpets cave floors",
         is_solid: true,
         occludes: true,
         hardness: 0.25,
@@ -362,6 +440,7 @@ const BLOCK_INFOS: [BlockInfo; VARIANT_COUNT] = [
     },
     BlockInfo {
         name: "Terracotta",
+        description: "Baked clay in warm earthy tones",
         is_solid: true,
         occludes: true,
         hardness: 1.4,
@@ -371,6 +450,7 @@ const BLOCK_INFOS: [BlockInfo; VARIANT_COUNT] = [
     },
     BlockInfo {
         name: "Lily Pad",
+        description: "A floating leaf that sits on water",
         is_solid: false,
         occludes: false,
         hardness: 0.0,
@@ -380,6 +460,7 @@ const BLOCK_INFOS: [BlockInfo; VARIANT_COUNT] = [
     },
     BlockInfo {
         name: "Snow",
+        description: "A cold layer from snowy peaks",
         is_solid: true,
         occludes: true,
         hardness: 0.2,
@@ -389,6 +470,7 @@ const BLOCK_INFOS: [BlockInfo; VARIANT_COUNT] = [
     },
     BlockInfo {
         name: "Copper Wire",
+        description: "Carries current between components",
         is_solid: false,
         occludes: false,
         hardness: 0.0,
@@ -398,6 +480,7 @@ const BLOCK_INFOS: [BlockInfo; VARIANT_COUNT] = [
     },
     BlockInfo {
         name: "Resistor",
+        description: "Limits the current through a circuit",
         is_solid: false,
         occludes: false,
         hardness: 0.0,
@@ -407,6 +490,7 @@ const BLOCK_INFOS: [BlockInfo; VARIANT_COUNT] = [
     },
     BlockInfo {
         name: "Voltage Source",
+        description: "Drives current around a circuit",
         is_solid: false,
         occludes: false,
         hardness: 0.0,
@@ -416,6 +500,7 @@ const BLOCK_INFOS: [BlockInfo; VARIANT_COUNT] = [
     },
     BlockInfo {
         name: "Ground Node",
+        description: "Ties a circuit to zero volts",
         is_solid: false,
         occludes: false,
         hardness: 0.0,
@@ -425,6 +510,7 @@ const BLOCK_INFOS: [BlockInfo; VARIANT_COUNT] = [
     },
     BlockInfo {
         name: "Torch",
+        description: "A small light to brighten dark places",
         is_solid: false,
         occludes: false,
         hardness: 0.0,
@@ -432,13 +518,139 @@ const BLOCK_INFOS: [BlockInfo; VARIANT_COUNT] = [
         textures: TextureRule::uniform((20, 0)),
         render_kind: RenderKind::Cross,
     },
+    BlockInfo {
+        name: "Capacitor",
+        description: "Stores charge and smooths changes",
+        is_solid: false,
+        occludes: false,
+        hardness: 0.0,
+        light_emission: 0.0,
+        textures: TextureRule::uniform((39, 0)),
+        render_kind: RenderKind::Electrical(ElectricalKind::Capacitor),
+    },
+    BlockInfo {
+        name: "Switch",
+        description: "Opens or closes a circuit by hand",
+        is_solid: false,
+        occludes: false,
+        hardness: 0.0,
+        light_emission: 0.0,
+        textures: TextureRule::uniform((40, 0)),
+        render_kind: RenderKind::Electrical(ElectricalKind::Switch),
+    },
+    BlockInfo {
+        name: "LED",
+        description: "Lights up when current flows through",
+        is_solid: false,
+        occludes: false,
+        hardness: 0.0,
+        light_emission: 0.0,
+        textures: TextureRule::uniform((41, 0)),
+        render_kind: RenderKind::Electrical(ElectricalKind::Led),
+    },
+    BlockInfo {
+        name: "Diode",
+        description: "Lets current flow one way only",
+        is_solid: false,
+        occludes: false,
+        hardness: 0.0,
+        light_emission: 0.0,
+        textures: TextureRule::uniform((42, 0)),
+        render_kind: RenderKind::Electrical(ElectricalKind::Diode),
+    },
+    BlockInfo {
+        name: "Potentiometer",
+        description: "A resistor you can adjust by hand",
+        is_solid: false,
+        occludes: false,
+        hardness: 0.0,
+        light_emission: 0.0,
+        textures: TextureRule::uniform((43, 0)),
+        render_kind: RenderKind::Electrical(ElectricalKind::Potentiometer),
+    },
+    BlockInfo {
+        name: "Lava",
+        description: "Molten rock that glows and burns",
+        is_solid: false,
+        occludes: false,
+        hardness: 0.0,
+        light_emission: 1.0,
+        textures: TextureRule::uniform((44, 0)),
+        render_kind: RenderKind::Solid,
+    },
+    BlockInfo {
+        name: "Sponge",
+        description: "Soaks up the water around it",
+        is_solid: true,
+        occludes: true,
+        hardness: 0.6,
+        light_emission: 0.0,
+        textures: TextureRule::uniform((45, 0)),
+        render_kind: RenderKind::Solid,
+    },
+    BlockInfo {
+        name: "Wet Sponge",
+        description: "A sponge full of water that dries when broken",
+        is_solid: true,
+        occludes: true,
+        hardness: 0.6,
+        light_emission: 0.0,
+        textures: TextureRule::uniform((46, 0)),
+        render_kind: RenderKind::Solid,
+    },
 ];
 
 impl BlockType {
+    /// Every variant in discriminant order, for exhaustive table checks and save files.
+    pub const ALL: [BlockType; VARIANT_COUNT] = [
+        BlockType::Air,
+        BlockType::Grass,
+        BlockType::Dirt,
+        BlockType::Stone,
+        BlockType::Wood,
+        BlockType::Sand,
+        BlockType::Leaves,
+        BlockType::CoalOre,
+        BlockType::IronOre,
+        BlockType::Water,
+        BlockType::FlowerRose,
+        BlockType::FlowerTulip,
+        BlockType::GlowShroom,
+        BlockType::CaveCrystal,
+        BlockType::CaveMoss,
+        BlockType::Terracotta,
+        BlockType::LilyPad,
+        BlockType::Snow,
+        BlockType::CopperWire,
+        BlockType::Resistor,
+        BlockType::VoltageSource,
+        BlockType::Ground,
+        BlockType::Torch,
+        BlockType::Capacitor,
+        BlockType::Switch,
+        BlockType::Led,
+        BlockType::Diode,
+        BlockType::Potentiometer,
+        BlockType::Lava,
+        BlockType::Sponge,
+        BlockType::WetSponge,
+    ];
+
+    /// Inverse of `block as u8`; `None` for ids no variant uses.
+    pub fn from_id(id: u8) -> Option<Self> {
+        Self::ALL.get(id as usize).copied()
+    }
+
     fn info(self) -> &'static BlockInfo {
         &BLOCK_INFOS[self as usize]
     }
 
+    /// Atlas tile for each of the six faces, in `BlockFace::ALL` order.
+    #[cfg(test)]
+    pub fn face_tiles(self) -> [(BlockFace, (u32, u32)); 6] {
+        BlockFace::ALL.map(|face| (face, self.atlas_coords(face)))
+    }
+
     pub fn is_solid(self) -> bool {
         self.info().is_solid
     }
@@ -451,6 +663,10 @@ impl BlockType {
         self.info().name
     }
 
+    pub fn description(self) -> &'static str {
+        self.info().description
+    }
+
     #[allow(dead_code)]
     pub fn hardness(self) -> f32 {
         self.info().hardness
@@ -475,19 +691,74 @@ impl BlockType {
             BlockType::Resistor => Some(ElectricalKind::Resistor),
             BlockType::VoltageSource => Some(ElectricalKind::VoltageSource),
             BlockType::Ground => Some(ElectricalKind::Ground),
+            BlockType::Capacitor => Some(ElectricalKind::Capacitor),
+            BlockType::Switch => Some(ElectricalKind::Switch),
+            BlockType::Led => Some(ElectricalKind::Led),
+            BlockType::Diode => Some(ElectricalKind::Diode),
+            BlockType::Potentiometer => Some(ElectricalKind::Potentiometer),
             _ => None,
         }
     }
 
+    /// What breaking the block hands back: a soaked sponge comes out dry.
+    pub fn dropped_as(self) -> BlockType {
+        match self {
+            BlockType::WetSponge => BlockType::Sponge,
+            other => other,
+        }
+    }
+
     pub fn is_electrical(self) -> bool {
         self.electrical_kind().is_some()
     }
 
+    /// Material group used for placement, break and footstep sounds; air makes none.
+    pub fn sound_material(self) -> Option<SoundMaterial> {
+        let material = match self {
+            BlockType::Stone
+            | BlockType::CoalOre
+            | BlockType::IronOre
+            | BlockType::Terracotta => SoundMaterial::Stone,
+            BlockType::Wood | BlockType::Torch => SoundMaterial::Wood,
+            BlockType::Grass
+            | BlockType::Dirt
+            | BlockType::Leaves
+            | BlockType::FlowerRose
+            | BlockType::FlowerTulip
+            | BlockType::GlowShroom
+            | BlockType::CaveMoss
+            | BlockType::LilyPad
+            | BlockType::Sponge
+            | BlockType::WetSponge => SoundMaterial::Grass,
+            BlockType::Sand | BlockType::Snow => SoundMaterial::Sand,
+            BlockType::CaveCrystal => SoundMaterial::Glass,
+            // Electrical components share one group so a circuit sounds consistent.
+            BlockType::CopperWire
+            | BlockType::Resistor
+            | BlockType::VoltageSource
+            | BlockType::Ground
+            | BlockType::Capacitor
+            | BlockType::Switch
+            | BlockType::Led
+            | BlockType::Diode
+            | BlockType::Potentiometer => SoundMaterial::Metal,
+            BlockType::Water | BlockType::Lava => SoundMaterial::Water,
+            BlockType::Air => return None,
+        };
+        Some(material)
+    }
+
     pub fn default_axis(self) -> Axis {
         match self.electrical_kind() {
             Some(ElectricalKind::VoltageSource) => Axis::X,
             Some(ElectricalKind::Ground) => Axis::Y,
-            Some(ElectricalKind::Wire) | Some(ElectricalKind::Resistor) => Axis::X,
+            Some(ElectricalKind::Wire)
+            | Some(ElectricalKind::Resistor)
+            | Some(ElectricalKind::Capacitor)
+            | Some(ElectricalKind::Switch)
+            | Some(ElectricalKind::Led)
+            | Some(ElectricalKind::Diode)
+            | Some(ElectricalKind::Potentiometer) => Axis::X,
             None => Axis::X,
         }
     }
@@ -509,3 +780,90 @@ impl Default for Block {
         Block::new(BlockType::Air)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::texture::{ATLAS_COLS, ATLAS_ROWS};
+
+    #[test]
+    fn block_table_matches_variant_order() {
+        for (index, block) in BlockType::ALL.into_iter().enumerate() {
+            assert_eq!(block as usize, index, "{block:?} is out of order in BlockType::ALL");
+        }
+    }
+
+    #[test]
+    fn every_block_has_a_plain_description() {
+        for block in BlockType::ALL {
+            let description = block.description();
+            assert!(!description.is_empty(), "{block:?} has no description");
+            // Tooltips draw with the bitmap font, which has no apostrophes or semicolons.
+            assert!(
+                description
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphanumeric() || ch == ' '),
+                "{block:?} description uses characters the UI font lacks"
+            );
+        }
+    }
+
+    #[test]
+    fn every_face_tile_is_inside_the_atlas() {
+        for block in BlockType::ALL {
+            for (face, (tile_x, tile_y)) in block.face_tiles() {
+                assert!(
+                    tile_x < ATLAS_COLS && tile_y < ATLAS_ROWS,
+                    "{block:?} {face:?} samples tile ({tile_x}, {tile_y}) outside the \
+                     {ATLAS_COLS}x{ATLAS_ROWS} atlas"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn electrical_blocks_share_the_metal_sound_group() {
+        for block in BlockType::ALL {
+            if block.is_electrical() {
+                assert_eq!(block.sound_material(), Some(SoundMaterial::Metal), "{block:?}");
+            } else {
+                assert_ne!(block.sound_material(), Some(SoundMaterial::Metal), "{block:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn solid_cubes_texture_all_six_faces() {
+        let air_tile = BlockType::Air.atlas_coords(BlockFace::North);
+        for block in BlockType::ALL {
+            if !block.is_solid() || !matches!(block.render_kind(), RenderKind::Solid) {
+                continue;
+            }
+            let tiles = block.face_tiles();
+            let sides: Vec<_> = tiles
+                .iter()
+                .filter(|(face, _)| !matches!(face, BlockFace::Top | BlockFace::Bottom))
+                .map(|&(_, tile)| tile)
+                .collect();
+            assert!(
+                sides.windows(2).all(|pair| pair[0] == pair[1]),
+                "{block:?} has mismatched side tiles"
+            );
+            if block != BlockType::Grass {
+                assert!(
+                    tiles.iter().all(|&(_, tile)| tile != air_tile),
+                    "{block:?} samples the grass-top placeholder tile"
+                );
+            }
+        }
+
+        assert_ne!(
+            BlockType::Grass.atlas_coords(BlockFace::Top),
+            BlockType::Grass.atlas_coords(BlockFace::North)
+        );
+        assert_ne!(
+            BlockType::Grass.atlas_coords(BlockFace::Bottom),
+            BlockType::Grass.atlas_coords(BlockFace::North)
+        );
+    }
+}
@@ -2,12 +2,48 @@ use cgmath::{perspective, vec3, InnerSpace, Matrix4, Point3, Rad, Vector3};
 use winit::event::{ElementState, MouseScrollDelta, WindowEvent};
 use winit::keyboard::{KeyCode, PhysicalKey};
 
+use crate::gamepad::{STICK_LOOK_SPEED, STICK_SENSITIVITY_MAX, STICK_SENSITIVITY_MIN};
+
 pub const PLAYER_HEIGHT: f32 = 1.8;
 pub const PLAYER_EYE_HEIGHT: f32 = 1.62;
 pub const PLAYER_RADIUS: f32 = 0.3;
+/// How far sneaking lowers the eye and the top of the player's box.
+pub const SNEAK_EYE_DROP: f32 = 0.3;
 
 const GRAVITY: f32 = -25.0;
 const JUMP_VELOCITY: f32 = 8.0;
+/// Height probed above a blocked move to decide whether auto-jump can clear the ledge.
+const AUTO_JUMP_STEP_HEIGHT: f32 = 1.05;
+/// Tallest ledge the player walks up without jumping; full blocks still need a jump.
+const STEP_UP_HEIGHT: f32 = 0.6;
+/// Spacing of the lifts tried when looking for the top of a ledge.
+const STEP_UP_PROBE: f32 = 0.05;
+/// How fast a step-up raises the player, so the view glides up instead of snapping.
+const STEP_UP_SPEED: f32 = 6.0;
+const SNEAK_SPEED_MULTIPLIER: f32 = 0.3;
+/// Drop below the feet that still counts as ground while sneaking; anything deeper is an
+/// edge the player won't walk off.
+const SNEAK_EDGE_DEPTH: f32 = 0.55;
+const SNEAK_SETTLE_LIFT: f32 = 0.002;
+const SWIM_SPEED_MULTIPLIER: f32 = 0.5;
+/// Gravity left over once buoyancy is taken off.
+const SWIM_GRAVITY: f32 = -4.0;
+/// Fastest the player sinks without holding jump.
+const SWIM_SINK_SPEED: f32 = 1.2;
+const SWIM_RISE_ACCELERATION: f32 = 12.0;
+const SWIM_RISE_SPEED: f32 = 3.0;
+/// Rate at which water bleeds off vertical speed.
+const SWIM_DRAG: f32 = 3.0;
+/// Ranges of the movement settings, in blocks per second except the sprint multiplier.
+pub const WALK_SPEED_MIN: f32 = 2.0;
+pub const WALK_SPEED_MAX: f32 = 30.0;
+pub const SPRINT_MULTIPLIER_MIN: f32 = 1.0;
+pub const SPRINT_MULTIPLIER_MAX: f32 = 3.0;
+pub const FLY_SPEED_MIN: f32 = 4.0;
+pub const FLY_SPEED_MAX: f32 = 60.0;
+/// One block per 60 Hz physics tick. Any faster and a single step could carry the
+/// player's box clean through a wall, so sprinting and flying never exceed it.
+const MAX_MOVE_SPEED: f32 = 60.0;
 
 pub struct Camera {
     pub position: Point3<f32>,
@@ -39,6 +75,17 @@ impl Camera {
         self.direction().cross(Self::UP).normalize()
     }
 
+    /// Turns the camera to face `target`, keeping the usual pitch limits.
+    pub fn look_at(&mut self, target: Point3<f32>) {
+        let offset = target - self.position;
+        let horizontal = (offset.x * offset.x + offset.z * offset.z).sqrt();
+        if horizontal > f32::EPSILON {
+            self.yaw = Rad(offset.z.atan2(offset.x));
+        }
+        let half_pi = std::f32::consts::FRAC_PI_2 - 0.01;
+        self.pitch = Rad(offset.y.atan2(horizontal).clamp(-half_pi, half_pi));
+    }
+
     const UP: Vector3<f32> = vec3(0.0, 1.0, 0.0);
 }
 
@@ -72,6 +119,15 @@ impl Projection {
         self.aspect
     }
 
+    pub fn set_zfar(&mut self, zfar: f32) {
+        self.zfar = zfar.max(self.znear + 1.0);
+    }
+
+    /// `(znear, zfar)`, needed to turn depth buffer values back into distances.
+    pub fn clip_planes(&self) -> (f32, f32) {
+        (self.znear, self.zfar)
+    }
+
     pub fn build_matrix(&self) -> Matrix4<f32> {
         perspective(self.fov_y, self.aspect, self.znear, self.zfar)
     }
@@ -80,6 +136,16 @@ impl Projection {
         self.base_fov
     }
 
+    /// Sets the resting FOV that sprint and zoom offsets are measured from.
+    pub fn set_base_fov(&mut self, fov: Rad<f32>) {
+        self.base_fov = fov;
+        self.target_fov = fov;
+    }
+
+    pub fn fov(&self) -> Rad<f32> {
+        self.fov_y
+    }
+
     pub fn set_target_fov(&mut self, fov: Rad<f32>) {
         self.target_fov = fov;
     }
@@ -88,6 +154,10 @@ impl Projection {
         let rate = 10.0;
         let lerp = 1.0 - (-rate * dt).exp();
         self.fov_y = Rad(self.fov_y.0 + (self.target_fov.0 - self.fov_y.0) * lerp);
+        // Snap the tail of the ease so a released zoom lands exactly on the target.
+        if (self.target_fov.0 - self.fov_y.0).abs() < 1e-4 {
+            self.fov_y = self.target_fov;
+        }
     }
 
     pub fn ray_direction(&self, camera: &Camera, screen: (f32, f32)) -> Vector3<f32> {
@@ -114,9 +184,63 @@ impl Projection {
     }
 }
 
+/// How walking velocity chases the input. Rates are per second; higher is snappier.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MovementModel {
+    /// Rate at which ground velocity closes on the input direction.
+    pub ground_acceleration: f32,
+    /// Share of the ground rates available while airborne.
+    pub air_control: f32,
+    /// Rate at which ground velocity bleeds off once the keys are released.
+    pub friction: f32,
+}
+
+/// Movement presets offered in the settings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MovementFeel {
+    /// Reaches full speed (or a stop) within a few ticks, with full air control.
+    #[default]
+    Instant,
+    /// Ramps up over about a second, coasts to a stop and steers weakly mid-air.
+    Smooth,
+}
+
+impl MovementFeel {
+    const ALL: [Self; 2] = [Self::Instant, Self::Smooth];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Instant => "INSTANT",
+            Self::Smooth => "SMOOTH",
+        }
+    }
+
+    pub fn cycle(self, step: i32) -> Self {
+        let index = Self::ALL.iter().position(|&feel| feel == self).unwrap_or(0) as i32;
+        Self::ALL[(index + step).rem_euclid(Self::ALL.len() as i32) as usize]
+    }
+
+    pub fn model(self) -> MovementModel {
+        match self {
+            Self::Instant => MovementModel {
+                ground_acceleration: 12.0,
+                air_control: 1.0,
+                friction: 12.0,
+            },
+            Self::Smooth => MovementModel {
+                ground_acceleration: 3.0,
+                air_control: 0.25,
+                friction: 2.5,
+            },
+        }
+    }
+}
+
 pub struct CameraController {
     base_speed: f32,
     sprint_multiplier: f32,
+    /// Speed in noclip and free-look flight.
+    fly_speed: f32,
     sensitivity: f32,
     is_forward_pressed: bool,
     is_backward_pressed: bool,
@@ -124,11 +248,24 @@ pub struct CameraController {
     is_right_pressed: bool,
     is_jump_pressed: bool,
     is_sprint_pressed: bool,
+    is_sneak_pressed: bool,
+    /// Eye and box are lowered; stays set under a low ceiling after the key is released.
+    sneaking: bool,
+    /// The body is in a liquid; set each tick by the caller.
+    swimming: bool,
     scroll: f32,
     velocity_y: f32,
     is_on_ground: bool,
     horizontal_velocity: Vector3<f32>,
+    movement: MovementModel,
     pub noclip: bool,
+    auto_jump: bool,
+    /// Multiplier on mouse look, lowered while zoomed in.
+    look_scale: f32,
+    /// Dead-zoned left stick: X strafes right, Y walks forward.
+    stick_move: (f32, f32),
+    is_pad_jump_pressed: bool,
+    stick_sensitivity: f32,
 }
 
 impl CameraController {
@@ -140,10 +277,43 @@ impl CameraController {
         self.sensitivity = value.clamp(0.0005, 0.02);
     }
 
+    pub fn stick_sensitivity(&self) -> f32 {
+        self.stick_sensitivity
+    }
+
+    pub fn set_stick_sensitivity(&mut self, value: f32) {
+        self.stick_sensitivity = value.clamp(STICK_SENSITIVITY_MIN, STICK_SENSITIVITY_MAX);
+    }
+
+    pub fn walk_speed(&self) -> f32 {
+        self.base_speed
+    }
+
+    pub fn set_walk_speed(&mut self, value: f32) {
+        self.base_speed = value.clamp(WALK_SPEED_MIN, WALK_SPEED_MAX);
+    }
+
+    pub fn sprint_multiplier(&self) -> f32 {
+        self.sprint_multiplier
+    }
+
+    pub fn set_sprint_multiplier(&mut self, value: f32) {
+        self.sprint_multiplier = value.clamp(SPRINT_MULTIPLIER_MIN, SPRINT_MULTIPLIER_MAX);
+    }
+
+    pub fn fly_speed(&self) -> f32 {
+        self.fly_speed
+    }
+
+    pub fn set_fly_speed(&mut self, value: f32) {
+        self.fly_speed = value.clamp(FLY_SPEED_MIN, FLY_SPEED_MAX);
+    }
+
     pub fn new(speed: f32, sensitivity: f32) -> Self {
         Self {
             base_speed: speed,
             sprint_multiplier: 1.6,
+            fly_speed: speed,
             sensitivity,
             is_forward_pressed: false,
             is_backward_pressed: false,
@@ -151,14 +321,35 @@ impl CameraController {
             is_right_pressed: false,
             is_jump_pressed: false,
             is_sprint_pressed: false,
+            is_sneak_pressed: false,
+            sneaking: false,
+            swimming: false,
             scroll: 0.0,
             velocity_y: 0.0,
             is_on_ground: true, // Start on ground
             horizontal_velocity: Vector3::new(0.0, 0.0, 0.0),
+            movement: MovementFeel::default().model(),
             noclip: false,
+            auto_jump: false,
+            look_scale: 1.0,
+            stick_move: (0.0, 0.0),
+            is_pad_jump_pressed: false,
+            stick_sensitivity: 1.0,
         }
     }
 
+    pub fn auto_jump(&self) -> bool {
+        self.auto_jump
+    }
+
+    pub fn set_auto_jump(&mut self, enabled: bool) {
+        self.auto_jump = enabled;
+    }
+
+    pub fn set_movement_model(&mut self, model: MovementModel) {
+        self.movement = model;
+    }
+
     pub fn toggle_noclip(&mut self) {
         self.noclip = !self.noclip;
     }
@@ -177,6 +368,9 @@ impl CameraController {
                         KeyCode::ControlLeft | KeyCode::ControlRight => {
                             self.is_sprint_pressed = is_pressed
                         }
+                        KeyCode::ShiftLeft | KeyCode::ShiftRight => {
+                            self.is_sneak_pressed = is_pressed
+                        }
                         _ => return false,
                     }
                     return true;
@@ -195,55 +389,150 @@ impl CameraController {
         }
     }
 
+    pub fn set_look_scale(&mut self, scale: f32) {
+        self.look_scale = scale.clamp(0.05, 1.5);
+    }
+
     pub fn process_mouse(&mut self, delta: (f64, f64), camera: &mut Camera) {
         let (dx, dy) = delta;
-        camera.yaw += Rad(dx as f32 * self.sensitivity);
-        camera.pitch += Rad(-dy as f32 * self.sensitivity);
+        let sensitivity = self.sensitivity * self.look_scale;
+        camera.yaw += Rad(dx as f32 * sensitivity);
+        camera.pitch += Rad(-dy as f32 * sensitivity);
+
+        let half_pi = std::f32::consts::FRAC_PI_2 - 0.01;
+        camera.pitch.0 = camera.pitch.0.clamp(-half_pi, half_pi);
+    }
+
+    /// Controller movement for the next ticks; adds to whatever the keyboard holds.
+    pub fn set_gamepad_motion(&mut self, stick_move: (f32, f32), jump: bool) {
+        self.stick_move = stick_move;
+        self.is_pad_jump_pressed = jump;
+    }
+
+    /// Turns the view by a dead-zoned right stick held for `dt` seconds.
+    pub fn process_stick_look(&mut self, stick: (f32, f32), dt: f32, camera: &mut Camera) {
+        let speed = STICK_LOOK_SPEED * self.stick_sensitivity * self.look_scale * dt;
+        camera.yaw += Rad(stick.0 * speed);
+        camera.pitch += Rad(stick.1 * speed);
 
         let half_pi = std::f32::consts::FRAC_PI_2 - 0.01;
         camera.pitch.0 = camera.pitch.0.clamp(-half_pi, half_pi);
     }
 
+    fn jump_held(&self) -> bool {
+        self.is_jump_pressed || self.is_pad_jump_pressed
+    }
+
+    pub fn is_on_ground(&self) -> bool {
+        self.is_on_ground
+    }
+
     pub fn is_sprinting(&self) -> bool {
-        !self.noclip && self.is_sprint_pressed && self.horizontal_velocity.magnitude2() > 0.05
+        !self.noclip
+            && !self.sneaking
+            && self.is_sprint_pressed
+            && self.horizontal_velocity.magnitude2() > 0.05
+    }
+
+    pub fn is_swimming(&self) -> bool {
+        self.swimming && !self.noclip
     }
 
+    pub fn set_swimming(&mut self, swimming: bool) {
+        self.swimming = swimming;
+    }
+
+    pub fn is_sneaking(&self) -> bool {
+        self.sneaking
+    }
+
+    /// Distance from the feet to the camera, lowered while sneaking.
+    pub fn eye_height(&self) -> f32 {
+        if self.sneaking {
+            PLAYER_EYE_HEIGHT - SNEAK_EYE_DROP
+        } else {
+            PLAYER_EYE_HEIGHT
+        }
+    }
+
+    /// Crouches or stands up to follow the sneak key, keeping the feet in place. Standing
+    /// waits until the full-height box fits.
+    fn update_sneak(
+        &mut self,
+        camera: &mut Camera,
+        check_collision: &impl Fn(Point3<f32>, f32) -> bool,
+    ) {
+        let want = self.is_sneak_pressed && !self.noclip;
+        if want == self.sneaking {
+            return;
+        }
+        let (drop, eye_height) = if want {
+            (-SNEAK_EYE_DROP, PLAYER_EYE_HEIGHT - SNEAK_EYE_DROP)
+        } else {
+            (SNEAK_EYE_DROP, PLAYER_EYE_HEIGHT)
+        };
+        let moved = camera.position + Vector3::new(0.0, drop, 0.0);
+        // Rounding can leave the moved feet a hair inside the floor; a tiny lift fixes that.
+        let fitted = [0.0, SNEAK_SETTLE_LIFT]
+            .into_iter()
+            .map(|lift| moved + Vector3::new(0.0, lift, 0.0))
+            .find(|&pos| self.noclip || !check_collision(pos, eye_height));
+        if let Some(pos) = fitted {
+            self.sneaking = want;
+            camera.position = pos;
+        }
+    }
+
+    /// Moves `camera` freely along the look direction with no gravity or collision.
+    /// Noclip and the detached free-look camera both fly this way.
+    pub fn fly_camera(&mut self, camera: &mut Camera, dt: f32) {
+        let speed_multiplier = if self.is_sprint_pressed {
+            self.sprint_multiplier
+        } else {
+            1.0
+        };
+        let mut direction = Vector3::new(0.0, 0.0, 0.0);
+        if self.is_forward_pressed {
+            direction += camera.direction();
+        }
+        if self.is_backward_pressed {
+            direction -= camera.direction();
+        }
+        if self.is_left_pressed {
+            direction -= camera.right();
+        }
+        if self.is_right_pressed {
+            direction += camera.right();
+        }
+        if self.jump_held() {
+            direction += Camera::UP;
+        }
+        direction += camera.direction() * self.stick_move.1 + camera.right() * self.stick_move.0;
+
+        // A half-tilted stick flies slower; only a combined push past full speed is capped.
+        if direction.magnitude2() > 1.0 {
+            direction = direction.normalize();
+        }
+
+        self.horizontal_velocity = Vector3::new(0.0, 0.0, 0.0);
+        let speed = (self.fly_speed * speed_multiplier).min(MAX_MOVE_SPEED);
+        camera.position += direction * speed * dt;
+    }
+
+    /// `check_collision` tests the player's box for an eye at the given point and the given
+    /// eye height above the feet.
     pub fn update_camera(
         &mut self,
         camera: &mut Camera,
         dt: f32,
-        check_collision: impl Fn(cgmath::Point3<f32>) -> bool,
+        check_collision: impl Fn(cgmath::Point3<f32>, f32) -> bool,
     ) {
+        self.update_sneak(camera, &check_collision);
+        let eye_height = self.eye_height();
+        let check_collision = |pos: Point3<f32>| check_collision(pos, eye_height);
         if self.noclip {
             // Noclip mode - free flight
-            let speed_multiplier = if self.is_sprint_pressed {
-                self.sprint_multiplier
-            } else {
-                1.0
-            };
-            let mut direction = Vector3::new(0.0, 0.0, 0.0);
-            if self.is_forward_pressed {
-                direction += camera.direction();
-            }
-            if self.is_backward_pressed {
-                direction -= camera.direction();
-            }
-            if self.is_left_pressed {
-                direction -= camera.right();
-            }
-            if self.is_right_pressed {
-                direction += camera.right();
-            }
-            if self.is_jump_pressed {
-                direction += Camera::UP;
-            }
-
-            if direction.magnitude2() > 0.0 {
-                direction = direction.normalize();
-            }
-
-            self.horizontal_velocity = Vector3::new(0.0, 0.0, 0.0);
-            camera.position += direction * self.base_speed * speed_multiplier * dt;
+            self.fly_camera(camera, dt);
         } else {
             // Normal mode - with gravity and collision
             // Handle horizontal movement
@@ -271,19 +560,33 @@ impl CameraController {
             if self.is_right_pressed {
                 horizontal += right;
             }
+            horizontal += forward * self.stick_move.1 + right * self.stick_move.0;
 
-            if horizontal.magnitude2() > 0.0 {
+            if horizontal.magnitude2() > 1.0 {
                 horizontal = horizontal.normalize();
             }
 
-            let speed_multiplier = if self.is_sprint_pressed {
+            let mut speed_multiplier = if self.sneaking {
+                SNEAK_SPEED_MULTIPLIER
+            } else if self.is_sprint_pressed {
                 self.sprint_multiplier
             } else {
                 1.0
             };
-            let target_velocity = horizontal * self.base_speed * speed_multiplier;
-            let accel = 12.0;
-            let lerp_factor = 1.0 - (-accel * dt).exp();
+            if self.swimming {
+                speed_multiplier *= SWIM_SPEED_MULTIPLIER;
+            }
+            let speed = (self.base_speed * speed_multiplier).min(MAX_MOVE_SPEED);
+            let target_velocity = horizontal * speed;
+            let mut rate = if horizontal.magnitude2() > 0.0 {
+                self.movement.ground_acceleration
+            } else {
+                self.movement.friction
+            };
+            if !self.is_on_ground {
+                rate *= self.movement.air_control;
+            }
+            let lerp_factor = 1.0 - (-rate * dt).exp();
             self.horizontal_velocity = self.horizontal_velocity
                 + (target_velocity - self.horizontal_velocity) * lerp_factor;
 
@@ -292,18 +595,42 @@ impl CameraController {
                 horizontal_movement = Vector3::new(0.0, 0.0, 0.0);
             }
 
+            // Sneaking on the ground refuses moves that would leave nothing underfoot.
+            let edge_probe = Vector3::new(0.0, -SNEAK_EDGE_DEPTH, 0.0);
+            let guard_edges = self.sneaking && self.is_on_ground;
+            let off_edge = |pos: Point3<f32>| guard_edges && !check_collision(pos + edge_probe);
+
             // Apply horizontal movement with collision
+            let step_up = Vector3::new(0.0, AUTO_JUMP_STEP_HEIGHT, 0.0);
+            let mut step_clear = false;
+            // A low ledge in the way lifts the player a little each tick, keeping the
+            // horizontal speed, until the move goes through.
+            let can_step = self.velocity_y <= 0.0;
+            let mut stepping = false;
             let new_pos_x = camera.position + Vector3::new(horizontal_movement.x, 0.0, 0.0);
-            if !check_collision(new_pos_x) {
+            if off_edge(new_pos_x) {
+                self.horizontal_velocity.x = 0.0;
+            } else if !check_collision(new_pos_x) {
                 camera.position = new_pos_x;
+            } else if can_step && self.step_up(camera, new_pos_x, dt, &check_collision) {
+                stepping = true;
             } else {
+                step_clear |= !check_collision(new_pos_x + step_up);
                 self.horizontal_velocity.x = 0.0;
             }
 
             let new_pos_z = camera.position + Vector3::new(0.0, 0.0, horizontal_movement.z);
-            if !check_collision(new_pos_z) {
+            if off_edge(new_pos_z) {
+                self.horizontal_velocity.z = 0.0;
+            } else if !check_collision(new_pos_z) {
                 camera.position = new_pos_z;
+            } else if can_step
+                && !stepping
+                && self.step_up(camera, new_pos_z, dt, &check_collision)
+            {
+                stepping = true;
             } else {
+                step_clear |= !check_collision(new_pos_z + step_up);
                 self.horizontal_velocity.z = 0.0;
             }
 
@@ -311,17 +638,46 @@ impl CameraController {
             let ground_check = camera.position + Vector3::new(0.0, -0.05, 0.0);
             self.is_on_ground = check_collision(ground_check);
 
-            // Jumping
-            if self.is_jump_pressed && self.is_on_ground {
-                self.velocity_y = JUMP_VELOCITY;
-                self.is_on_ground = false;
-            }
-
-            // Apply gravity
-            if !self.is_on_ground {
-                self.velocity_y += GRAVITY * dt;
-            } else {
+            // A blocked move with room one block up is a ledge; taller walls or a low
+            // ceiling fail one of the probes.
+            let auto_jump = self.auto_jump
+                && step_clear
+                && !check_collision(camera.position + step_up);
+
+            if self.swimming {
+                // Water holds the player up: a slow sink, or a steady climb while jump is
+                // held. Jumping against a ledge with room above hops out onto it.
+                if self.jump_held() && step_clear {
+                    self.velocity_y = JUMP_VELOCITY;
+                    self.is_on_ground = false;
+                } else if self.jump_held() {
+                    self.velocity_y =
+                        (self.velocity_y + SWIM_RISE_ACCELERATION * dt).min(SWIM_RISE_SPEED);
+                    self.is_on_ground = false;
+                } else if self.is_on_ground {
+                    self.velocity_y = 0.0;
+                } else {
+                    self.velocity_y += SWIM_GRAVITY * dt;
+                }
+                self.velocity_y *= (-SWIM_DRAG * dt).exp();
+                self.velocity_y = self.velocity_y.max(-SWIM_SINK_SPEED);
+            } else if stepping {
+                // Climbing a step holds the player up until the ledge is mounted.
                 self.velocity_y = 0.0;
+                self.is_on_ground = true;
+            } else {
+                // Jumping
+                if (self.jump_held() || auto_jump) && self.is_on_ground {
+                    self.velocity_y = JUMP_VELOCITY;
+                    self.is_on_ground = false;
+                }
+
+                // Apply gravity
+                if !self.is_on_ground {
+                    self.velocity_y += GRAVITY * dt;
+                } else {
+                    self.velocity_y = 0.0;
+                }
             }
 
             // Apply vertical movement
@@ -356,7 +712,37 @@ impl CameraController {
         self.scroll = 0.0;
     }
 
+    /// Raises the player toward the top of a ledge blocking the move to `blocked`. Only
+    /// ledges up to `STEP_UP_HEIGHT` with headroom above count. Returns whether it lifted.
+    fn step_up(
+        &self,
+        camera: &mut Camera,
+        blocked: Point3<f32>,
+        dt: f32,
+        check_collision: &impl Fn(Point3<f32>) -> bool,
+    ) -> bool {
+        let probes = (STEP_UP_HEIGHT / STEP_UP_PROBE).round() as usize;
+        // The first clear lift sits just above the ledge; the one below it still touched
+        // the ledge, so there is ground under the raised feet.
+        let Some(lift) = (1..=probes)
+            .map(|i| i as f32 * STEP_UP_PROBE)
+            .find(|&lift| !check_collision(blocked + Camera::UP * lift))
+        else {
+            return false;
+        };
+        let raised = camera.position + Camera::UP * lift.min(STEP_UP_SPEED * dt);
+        if check_collision(raised) {
+            return false;
+        }
+        camera.position = raised;
+        true
+    }
+
     pub fn reset_motion(&mut self) {
+        // Menus swallow the key release, so let go of sneak here.
+        self.is_sneak_pressed = false;
+        self.stick_move = (0.0, 0.0);
+        self.is_pad_jump_pressed = false;
         self.horizontal_velocity = Vector3::new(0.0, 0.0, 0.0);
         self.velocity_y = 0.0;
         self.scroll = 0.0;
@@ -380,6 +766,223 @@ mod tests {
             ray,
             view
         );
+    }
+
+    #[test]
+    fn look_at_points_the_view_at_the_target() {
+        let mut camera = Camera::new(point3(0.0, 70.0, 0.0), Rad(0.0), Rad(0.0));
+        let target = point3(-6.0, 64.0, 9.0);
+        camera.look_at(target);
+        let expected = (target - camera.position).normalize();
+        assert!((camera.direction() - expected).magnitude() < 1e-5);
+    }
+
+    /// Same block-centred AABB test as the game, in x and y only, against `solid`.
+    fn box_collides(solid: impl Fn(i32, i32) -> bool) -> impl Fn(cgmath::Point3<f32>, f32) -> bool {
+        move |pos, eye_height| {
+            let bottom = pos.y - eye_height;
+            let top = pos.y + PLAYER_HEIGHT - PLAYER_EYE_HEIGHT;
+            let min_x = (pos.x - PLAYER_RADIUS - 0.5).ceil() as i32;
+            let max_x = (pos.x + PLAYER_RADIUS + 0.5 - 0.001).floor() as i32;
+            let min_y = (bottom - 0.5).ceil() as i32;
+            let max_y = (top + 0.5 - 0.001).floor() as i32;
+            (min_x..=max_x).any(|x| (min_y..=max_y).any(|y| solid(x, y)))
+        }
+    }
+
+    /// Walks east for two seconds beside an obstacle `height` blocks tall; returns feet height.
+    fn walk_into_obstacle(height: i32, auto_jump: bool) -> f32 {
+        // A floor at y <= 0 and the obstacle from x = 2.
+        let collides = box_collides(|x, y| y <= 0 || (x >= 2 && y <= height));
+
+        let mut controller = CameraController::new(4.0, 0.004);
+        controller.set_auto_jump(auto_jump);
+        controller.is_forward_pressed = true;
+        let mut camera = Camera::new(point3(0.0, 0.5 + PLAYER_EYE_HEIGHT, 0.0), Rad(0.0), Rad(0.0));
+        for _ in 0..120 {
+            controller.update_camera(&mut camera, 1.0 / 60.0, &collides);
+        }
+        camera.position.y - PLAYER_EYE_HEIGHT
+    }
+
+    /// Walks east for four seconds along a platform ending at x = 2; returns `(x, feet y)`.
+    fn walk_off_ledge(sneak: bool) -> (f32, f32) {
+        let collides = box_collides(|x, y| y <= -5 || (y <= 0 && x <= 2));
+        let mut controller = CameraController::new(4.0, 0.004);
+        controller.is_forward_pressed = true;
+        controller.is_sneak_pressed = sneak;
+        // Drop in from just above the platform so rounding can't start the feet inside it.
+        let start = point3(0.0, 0.52 + PLAYER_EYE_HEIGHT, 0.0);
+        let mut camera = Camera::new(start, Rad(0.0), Rad(0.0));
+        for _ in 0..240 {
+            controller.update_camera(&mut camera, 1.0 / 60.0, &collides);
+        }
+        (camera.position.x, camera.position.y - controller.eye_height())
+    }
+
+    #[test]
+    fn half_tilted_stick_walks_at_half_speed() {
+        let floor = box_collides(|_, y| y <= 0);
+        let walk = |stick: (f32, f32)| {
+            let mut controller = CameraController::new(4.0, 0.004);
+            controller.set_gamepad_motion(stick, false);
+            let start = point3(0.0, 0.52 + PLAYER_EYE_HEIGHT, 0.0);
+            let mut camera = Camera::new(start, Rad(0.0), Rad(0.0));
+            for _ in 0..120 {
+                controller.update_camera(&mut camera, 1.0 / 60.0, &floor);
+            }
+            camera.position.x
+        };
+        let full = walk((0.0, 1.0));
+        let half = walk((0.0, 0.5));
+        assert!(full > 6.0, "full tilt only walked {full}");
+        assert!((half / full - 0.5).abs() < 0.02, "half tilt walked {half} of {full}");
+    }
+
+    #[test]
+    fn swimming_sinks_slowly_and_rises_while_jump_is_held() {
+        let open_water = |_: cgmath::Point3<f32>, _: f32| false;
+        let mut controller = CameraController::new(4.0, 0.004);
+        controller.set_swimming(true);
+        controller.is_on_ground = false;
+        let mut camera = Camera::new(point3(0.0, 10.0, 0.0), Rad(0.0), Rad(0.0));
+        for _ in 0..120 {
+            controller.update_camera(&mut camera, 1.0 / 60.0, open_water);
+        }
+        let sunk = 10.0 - camera.position.y;
+        assert!(sunk > 0.5 && sunk <= SWIM_SINK_SPEED * 2.0, "sank {sunk} in two seconds");
+
+        controller.is_jump_pressed = true;
+        let depth = camera.position.y;
+        for _ in 0..60 {
+            controller.update_camera(&mut camera, 1.0 / 60.0, open_water);
+        }
+        assert!(camera.position.y > depth + 1.0, "holding jump should swim upward");
+    }
+
+    /// Walks east for two seconds toward a ledge from x = 1.5 whose top is `rise` above the
+    /// floor; returns `(x, feet y)`.
+    fn walk_onto_ledge(rise: f32) -> (f32, f32) {
+        let collides = |pos: Point3<f32>, eye_height: f32| {
+            let feet = pos.y - eye_height;
+            feet < 0.5 || (pos.x + PLAYER_RADIUS > 1.5 && feet < 0.5 + rise)
+        };
+        let mut controller = CameraController::new(4.0, 0.004);
+        controller.is_forward_pressed = true;
+        let start = point3(0.0, 0.52 + PLAYER_EYE_HEIGHT, 0.0);
+        let mut camera = Camera::new(start, Rad(0.0), Rad(0.0));
+        for _ in 0..120 {
+            controller.update_camera(&mut camera, 1.0 / 60.0, collides);
+        }
+        (camera.position.x, camera.position.y - PLAYER_EYE_HEIGHT)
+    }
+
+    #[test]
+    fn low_ledges_are_stepped_up_and_taller_ones_block() {
+        let (x, feet) = walk_onto_ledge(0.5);
+        assert!(x > 2.5, "should walk onto a half-block ledge, x {x}");
+        assert!((feet - 1.0).abs() < 0.08, "feet should rest on the ledge, got {feet}");
+        let (x, feet) = walk_onto_ledge(0.75);
+        assert!(x < 1.5, "a ledge past the step height should block, x {x}");
+        assert!(feet < 0.6, "should stay on the floor, got {feet}");
+    }
+
+    #[test]
+    fn sneaking_stops_at_ledges_and_lowers_the_eye() {
+        let (x, feet) = walk_off_ledge(true);
+        assert!((feet - 0.5).abs() < 0.02, "sneaking should stay on the platform, feet {feet}");
+        assert!(x > 2.5 && x < 2.5 + PLAYER_RADIUS, "should stop hanging over the edge, x {x}");
+        let (x, feet) = walk_off_ledge(false);
+        assert!(feet < 0.0, "walking should drop off the end, x {x} feet {feet}");
+
+        let collides = box_collides(|_, y| y <= 0);
+        let mut controller = CameraController::new(4.0, 0.004);
+        let mut camera = Camera::new(point3(0.0, 0.5 + PLAYER_EYE_HEIGHT, 0.0), Rad(0.0), Rad(0.0));
+        controller.is_sneak_pressed = true;
+        controller.update_camera(&mut camera, 1.0 / 60.0, &collides);
+        assert!((camera.position.y - (0.5 + PLAYER_EYE_HEIGHT - SNEAK_EYE_DROP)).abs() < 0.01);
+        controller.is_sneak_pressed = false;
+        controller.update_camera(&mut camera, 1.0 / 60.0, &collides);
+        assert!(!controller.is_sneaking());
+        assert!((camera.position.y - (0.5 + PLAYER_EYE_HEIGHT)).abs() < 0.01);
+    }
+
+    /// Horizontal speed after walking forward on flat ground for each tick count.
+    fn walking_speeds(feel: MovementFeel, ticks: &[usize]) -> Vec<f32> {
+        let floor = |pos: cgmath::Point3<f32>, eye_height: f32| pos.y - eye_height < 0.49;
+        let mut controller = CameraController::new(4.0, 0.004);
+        controller.set_movement_model(feel.model());
+        controller.is_forward_pressed = true;
+        let mut camera = Camera::new(point3(0.0, 0.5 + PLAYER_EYE_HEIGHT, 0.0), Rad(0.0), Rad(0.0));
+        let mut speeds = Vec::new();
+        for tick in 1..=*ticks.iter().max().unwrap() {
+            controller.update_camera(&mut camera, 1.0 / 60.0, floor);
+            if ticks.contains(&tick) {
+                speeds.push(controller.horizontal_velocity.magnitude());
+            }
+        }
+        speeds
+    }
+
+    #[test]
+    fn smooth_movement_ramps_up_where_instant_snaps() {
+        let instant = walking_speeds(MovementFeel::Instant, &[15]);
+        assert!(instant[0] > 3.6, "instant should be near 4 m/s, got {}", instant[0]);
+
+        let smooth = walking_speeds(MovementFeel::Smooth, &[15, 60, 120]);
+        assert!(smooth[0] < 2.5, "smooth should still be ramping, got {}", smooth[0]);
+        assert!(smooth[0] < smooth[1] && smooth[1] < smooth[2]);
+        assert!(smooth[2] > 3.6, "smooth should approach 4 m/s, got {}", smooth[2]);
+    }
+
+    #[test]
+    fn auto_jump_climbs_single_steps_only() {
+        assert!(walk_into_obstacle(1, true) > 1.4, "auto-jump should mount a one-block step");
+        assert!(walk_into_obstacle(1, false) < 0.6, "auto-jump must stay off by default");
+        assert!(walk_into_obstacle(2, true) < 0.6, "auto-jump must not climb two-block walls");
+    }
+
+    #[test]
+    fn released_zoom_settles_exactly_on_the_base_fov() {
+        let mut projection = Projection::new(800, 600, 70f32.to_radians(), 0.1, 100.0);
+        projection.set_base_fov(Rad(85f32.to_radians()));
+        projection.set_target_fov(Rad(20f32.to_radians()));
+        for _ in 0..60 {
+            projection.animate(1.0 / 60.0);
+        }
+        projection.set_target_fov(projection.base_fov());
+        for _ in 0..240 {
+            projection.animate(1.0 / 60.0);
+        }
+        assert_eq!(projection.fov(), projection.base_fov());
+    }
+
+    #[test]
+    fn speed_settings_clamp_and_never_outrun_a_block_per_tick() {
+        let mut controller = CameraController::new(4.0, 0.004);
+        controller.set_walk_speed(500.0);
+        controller.set_sprint_multiplier(0.2);
+        controller.set_fly_speed(1.0);
+        assert_eq!(controller.walk_speed(), WALK_SPEED_MAX);
+        assert_eq!(controller.sprint_multiplier(), SPRINT_MULTIPLIER_MIN);
+        assert_eq!(controller.fly_speed(), FLY_SPEED_MIN);
+
+        controller.set_sprint_multiplier(SPRINT_MULTIPLIER_MAX);
+        controller.set_fly_speed(FLY_SPEED_MAX);
+        controller.is_forward_pressed = true;
+        controller.is_sprint_pressed = true;
+        let floor = box_collides(|_, y| y <= 0);
+        for noclip in [false, true] {
+            controller.noclip = noclip;
+            let start = point3(0.0, 0.5 + PLAYER_EYE_HEIGHT, 0.0);
+            let mut camera = Camera::new(start, Rad(0.0), Rad(0.0));
+            for _ in 0..120 {
+                let before = camera.position;
+                controller.update_camera(&mut camera, 1.0 / 60.0, &floor);
+                let step = (camera.position - before).magnitude();
+                assert!(step <= 1.0 + 1e-4, "moved {step} blocks in one tick");
+            }
+        }
     }
 
     #[test]
@@ -1,8 +1,15 @@
-use crate::block::{Block, BlockType, RenderKind};
+use anyhow::Context;
+
+use crate::block::{Block, BlockType, FluidKind, RenderKind};
+use crate::save::{SaveReader, SaveWriter, StateHasher};
 
 pub const CHUNK_SIZE: usize = 16;
+/// Default column height; worlds may opt into taller chunks via `Chunk::with_height`.
 pub const CHUNK_HEIGHT: usize = 256;
+/// Tallest column a world may request.
+pub const MAX_CHUNK_HEIGHT: usize = 1024;
 pub const CHUNK_AREA: usize = CHUNK_SIZE * CHUNK_SIZE;
+/// Cell count of a default-height chunk.
 pub const CHUNK_VOLUME: usize = CHUNK_AREA * CHUNK_HEIGHT;
 
 pub const fn index(x: usize, y: usize, z: usize) -> usize {
@@ -11,38 +18,73 @@ pub const fn index(x: usize, y: usize, z: usize) -> usize {
 
 #[derive(Clone)]
 pub struct Chunk {
-    blocks: [Block; CHUNK_VOLUME],
-    fluids: [u8; CHUNK_VOLUME],
+    height: usize,
+    blocks: Vec<Block>,
+    fluids: Vec<u8>,
+    /// Horizontal steps each fluid cell sits from the source it spread from (0 = source).
+    fluid_distance: Vec<u8>,
+    /// Liquid in each fluid cell; empty cells read as water.
+    fluid_kinds: Vec<FluidKind>,
     cell_state: Vec<i16>,
     /// Packed lighting: upper 4 bits = skylight (0-15), lower 4 bits = blocklight (0-15)
-    lighting: [u8; CHUNK_VOLUME],
+    lighting: Vec<u8>,
+    /// Running totals kept in sync by the setters so stats never need a full scan.
+    non_air_count: u32,
+    fluid_count: u32,
+    sponge_count: u32,
 }
 
 impl Chunk {
     pub fn new() -> Self {
+        Self::with_height(CHUNK_HEIGHT)
+    }
+
+    /// Creates an empty chunk `height` cells tall, clamped to `1..=MAX_CHUNK_HEIGHT`.
+    pub fn with_height(height: usize) -> Self {
+        let height = height.clamp(1, MAX_CHUNK_HEIGHT);
+        let volume = CHUNK_AREA * height;
         let mut chunk = Self {
-            blocks: [Block::default(); CHUNK_VOLUME],
-            fluids: [0; CHUNK_VOLUME],
-            cell_state: vec![0; CHUNK_VOLUME],
-            lighting: [0; CHUNK_VOLUME], // Initially dark, will be calculated
+            height,
+            blocks: vec![Block::default(); volume],
+            fluids: vec![0; volume],
+            fluid_distance: vec![0; volume],
+            fluid_kinds: vec![FluidKind::Water; volume],
+            cell_state: vec![0; volume],
+            lighting: vec![0; volume], // Initially dark, will be calculated
+            non_air_count: 0,
+            fluid_count: 0,
+            sponge_count: 0,
         };
         chunk.rebuild_cell_state();
         chunk
     }
 
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Number of cells in this chunk.
+    pub fn volume(&self) -> usize {
+        self.blocks.len()
+    }
+
+    fn contains(&self, x: usize, y: usize, z: usize) -> bool {
+        x < CHUNK_SIZE && y < self.height && z < CHUNK_SIZE
+    }
+
     pub fn set_block(&mut self, x: usize, y: usize, z: usize, block: BlockType) {
-        if x < CHUNK_SIZE && y < CHUNK_HEIGHT && z < CHUNK_SIZE {
+        if self.contains(x, y, z) {
             let idx = index(x, y, z);
-            self.blocks[idx] = Block::new(block);
+            self.replace_block(idx, block);
             if block != BlockType::Air {
-                self.fluids[idx] = 0;
+                self.replace_fluid(idx, 0);
             }
             self.update_cell_state(idx);
         }
     }
 
     pub fn get_block(&self, x: usize, y: usize, z: usize) -> BlockType {
-        if x < CHUNK_SIZE && y < CHUNK_HEIGHT && z < CHUNK_SIZE {
+        if self.contains(x, y, z) {
             self.blocks[index(x, y, z)].block_type
         } else {
             BlockType::Air
@@ -74,7 +116,7 @@ impl Chunk {
     }
 
     pub fn get_fluid(&self, x: usize, y: usize, z: usize) -> u8 {
-        if x < CHUNK_SIZE && y < CHUNK_HEIGHT && z < CHUNK_SIZE {
+        if self.contains(x, y, z) {
             self.fluids[index(x, y, z)]
         } else {
             0
@@ -82,16 +124,52 @@ impl Chunk {
     }
 
     pub fn set_fluid(&mut self, x: usize, y: usize, z: usize, amount: u8) {
-        if x < CHUNK_SIZE && y < CHUNK_HEIGHT && z < CHUNK_SIZE {
+        if self.contains(x, y, z) {
             let idx = index(x, y, z);
-            self.fluids[idx] = amount;
+            self.replace_fluid(idx, amount);
             if amount > 0 {
-                self.blocks[idx] = Block::new(BlockType::Air);
+                self.replace_block(idx, BlockType::Air);
             }
             self.update_cell_state(idx);
         }
     }
 
+    pub fn fluid_distance(&self, x: usize, y: usize, z: usize) -> u8 {
+        if self.contains(x, y, z) {
+            self.fluid_distance[index(x, y, z)]
+        } else {
+            0
+        }
+    }
+
+    pub fn set_fluid_with_distance(
+        &mut self,
+        x: usize,
+        y: usize,
+        z: usize,
+        kind: FluidKind,
+        amount: u8,
+        distance: u8,
+    ) {
+        if self.contains(x, y, z) {
+            self.set_fluid(x, y, z, amount);
+            if amount > 0 {
+                let idx = index(x, y, z);
+                self.fluid_distance[idx] = distance;
+                self.fluid_kinds[idx] = kind;
+                self.update_cell_state(idx);
+            }
+        }
+    }
+
+    pub fn fluid_kind(&self, x: usize, y: usize, z: usize) -> FluidKind {
+        if self.contains(x, y, z) {
+            self.fluid_kinds[index(x, y, z)]
+        } else {
+            FluidKind::Water
+        }
+    }
+
     pub fn fluids_iter(&self) -> impl Iterator<Item = (usize, usize, usize, u8)> + '_ {
         self.fluids
             .iter()
@@ -111,20 +189,128 @@ impl Chunk {
         &self.fluids
     }
 
+    /// Takes water levels computed off the main thread. Lava cells are walls to that
+    /// simulation, so they keep their own levels.
     pub fn apply_fluids(&mut self, new_fluids: &[u8]) {
-        if new_fluids.len() != CHUNK_VOLUME {
+        if new_fluids.len() != self.volume() {
             return;
         }
-        self.fluids.copy_from_slice(new_fluids);
-        for idx in 0..CHUNK_VOLUME {
+        self.fluid_count = 0;
+        for (idx, &level) in new_fluids.iter().enumerate() {
+            if self.fluid_kinds[idx] == FluidKind::Water {
+                self.fluids[idx] = level;
+            }
+            if self.fluids[idx] > 0 {
+                self.fluid_count += 1;
+            } else {
+                self.fluid_distance[idx] = 0;
+            }
             // Only clear block if fluid was added and block is not already air
             if self.fluids[idx] > 0 && self.blocks[idx].block_type != BlockType::Air {
-                self.blocks[idx] = Block::new(BlockType::Air);
+                self.replace_block(idx, BlockType::Air);
             }
             self.update_cell_state(idx);
         }
     }
 
+    /// Feeds every block type, fluid amount and fluid kind, in index order, into `state`.
+    pub fn hash_contents(&self, state: &mut StateHasher) {
+        for block in &self.blocks {
+            state.put_u8(block.block_type as u8);
+        }
+        state.put_bytes(&self.fluids);
+        for kind in &self.fluid_kinds {
+            state.put_u8(*kind as u8);
+        }
+    }
+
+    /// Writes the height, then blocks, fluid levels, fluid distances, fluid kinds and
+    /// lighting cell by cell.
+    pub fn write_save(&self, out: &mut SaveWriter) {
+        out.put_u32(self.height as u32);
+        for block in &self.blocks {
+            out.put_u8(block.block_type as u8);
+        }
+        out.put_bytes(&self.fluids);
+        out.put_bytes(&self.fluid_distance);
+        for kind in &self.fluid_kinds {
+            out.put_u8(*kind as u8);
+        }
+        out.put_bytes(&self.lighting);
+    }
+
+    pub fn read_save(input: &mut SaveReader) -> anyhow::Result<Self> {
+        let height = input.u32()? as usize;
+        anyhow::ensure!(
+            (1..=MAX_CHUNK_HEIGHT).contains(&height),
+            "saved chunk height {height} is out of range"
+        );
+        let mut chunk = Self::with_height(height);
+        let volume = chunk.volume();
+        for (idx, &id) in input.take(volume)?.iter().enumerate() {
+            let block = BlockType::from_id(id)
+                .with_context(|| format!("unknown block id {id} in saved chunk"))?;
+            chunk.replace_block(idx, block);
+        }
+        for (idx, &amount) in input.take(volume)?.iter().enumerate() {
+            chunk.replace_fluid(idx, amount);
+        }
+        chunk.fluid_distance.copy_from_slice(input.take(volume)?);
+        for (idx, &id) in input.take(volume)?.iter().enumerate() {
+            chunk.fluid_kinds[idx] = FluidKind::from_id(id)
+                .with_context(|| format!("unknown fluid id {id} in saved chunk"))?;
+        }
+        chunk.lighting.copy_from_slice(input.take(volume)?);
+        chunk.rebuild_cell_state();
+        Ok(chunk)
+    }
+
+    /// Number of cells holding a non-air block.
+    pub fn non_air_count(&self) -> u32 {
+        self.non_air_count
+    }
+
+    /// Number of cells holding any fluid.
+    pub fn fluid_count(&self) -> u32 {
+        self.fluid_count
+    }
+
+    /// Number of dry sponges, so the fluid update only looks for them where they exist.
+    pub fn sponge_count(&self) -> u32 {
+        self.sponge_count
+    }
+
+    fn replace_block(&mut self, idx: usize, block: BlockType) {
+        let was_sponge = self.blocks[idx].block_type == BlockType::Sponge;
+        if was_sponge && block != BlockType::Sponge {
+            self.sponge_count -= 1;
+        } else if !was_sponge && block == BlockType::Sponge {
+            self.sponge_count += 1;
+        }
+        let was_air = self.blocks[idx].block_type == BlockType::Air;
+        let is_air = block == BlockType::Air;
+        if was_air && !is_air {
+            self.non_air_count += 1;
+        } else if !was_air && is_air {
+            self.non_air_count -= 1;
+        }
+        self.blocks[idx] = Block::new(block);
+    }
+
+    fn replace_fluid(&mut self, idx: usize, amount: u8) {
+        let had_fluid = self.fluids[idx] > 0;
+        if !had_fluid && amount > 0 {
+            self.fluid_count += 1;
+        } else if had_fluid && amount == 0 {
+            self.fluid_count -= 1;
+        }
+        self.fluids[idx] = amount;
+        if amount == 0 {
+            self.fluid_distance[idx] = 0;
+            self.fluid_kinds[idx] = FluidKind::Water;
+        }
+    }
+
     pub fn cell_state(&self) -> &[i16] {
         &self.cell_state
     }
@@ -132,7 +318,10 @@ impl Chunk {
     fn update_cell_state(&mut self, idx: usize) {
         let block = self.blocks[idx].block_type;
         let fluid = self.fluids[idx];
-        self.cell_state[idx] = if fluid > 0 {
+        // Lava steps on its own schedule, so the water simulation sees it as a wall.
+        self.cell_state[idx] = if fluid > 0 && self.fluid_kinds[idx] == FluidKind::Lava {
+            -1
+        } else if fluid > 0 {
             fluid as i16
         } else if block.occludes() {
             -1
@@ -149,7 +338,7 @@ impl Chunk {
 
     /// Get skylight level (0-15) at position
     pub fn get_skylight(&self, x: usize, y: usize, z: usize) -> u8 {
-        if x < CHUNK_SIZE && y < CHUNK_HEIGHT && z < CHUNK_SIZE {
+        if self.contains(x, y, z) {
             let light = self.lighting[index(x, y, z)];
             (light >> 4) & 0xF
         } else {
@@ -159,7 +348,7 @@ impl Chunk {
 
     /// Get blocklight level (0-15) at position
     pub fn get_blocklight(&self, x: usize, y: usize, z: usize) -> u8 {
-        if x < CHUNK_SIZE && y < CHUNK_HEIGHT && z < CHUNK_SIZE {
+        if self.contains(x, y, z) {
             let light = self.lighting[index(x, y, z)];
             light & 0xF
         } else {
@@ -169,7 +358,7 @@ impl Chunk {
 
     /// Set skylight level (0-15) at position
     pub fn set_skylight(&mut self, x: usize, y: usize, z: usize, level: u8) {
-        if x < CHUNK_SIZE && y < CHUNK_HEIGHT && z < CHUNK_SIZE {
+        if self.contains(x, y, z) {
             let idx = index(x, y, z);
             let level = level.min(15);
             self.lighting[idx] = (self.lighting[idx] & 0x0F) | (level << 4);
@@ -178,7 +367,7 @@ impl Chunk {
 
     /// Set blocklight level (0-15) at position
     pub fn set_blocklight(&mut self, x: usize, y: usize, z: usize, level: u8) {
-        if x < CHUNK_SIZE && y < CHUNK_HEIGHT && z < CHUNK_SIZE {
+        if self.contains(x, y, z) {
             let idx = index(x, y, z);
             let level = level.min(15);
             self.lighting[idx] = (self.lighting[idx] & 0xF0) | level;
@@ -0,0 +1,126 @@
+struct Environment {
+    sky_zenith: vec4<f32>,
+    sky_horizon: vec4<f32>,
+    fog_color: vec4<f32>,
+    camera_position: vec4<f32>,
+    fog_params: vec4<f32>,
+    time_params: vec4<f32>,
+    screen_params: vec4<f32>,
+    fog_range: vec4<f32>,
+    light_direction: vec4<f32>,
+    animation: vec4<f32>,
+};
+
+struct Clouds {
+    inv_view_proj: mat4x4<f32>,
+    // params: [altitude, animation_time, coverage, fade_distance]
+    params: vec4<f32>,
+    view_proj: mat4x4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> environment: Environment;
+
+@group(1) @binding(0)
+var<uniform> clouds: Clouds;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) ndc: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var positions = array<vec2<f32>, 3>(
+        vec2<f32>(-1.0, -3.0),
+        vec2<f32>(3.0, 1.0),
+        vec2<f32>(-1.0, 1.0),
+    );
+
+    let pos = positions[vertex_index];
+    var output: VertexOutput;
+    output.position = vec4<f32>(pos, 0.0, 1.0);
+    output.ndc = pos;
+    return output;
+}
+
+fn hash(p: vec2<f32>) -> f32 {
+    return fract(sin(dot(p, vec2<f32>(127.1, 311.7))) * 43758.5453);
+}
+
+fn value_noise(p: vec2<f32>) -> f32 {
+    let cell = floor(p);
+    let f = fract(p);
+    let u = f * f * (3.0 - 2.0 * f);
+    let a = hash(cell);
+    let b = hash(cell + vec2<f32>(1.0, 0.0));
+    let c = hash(cell + vec2<f32>(0.0, 1.0));
+    let d = hash(cell + vec2<f32>(1.0, 1.0));
+    return mix(mix(a, b, u.x), mix(c, d, u.x), u.y);
+}
+
+fn fbm(p: vec2<f32>) -> f32 {
+    var value = 0.0;
+    var amplitude = 0.5;
+    var q = p;
+    for (var i = 0; i < 4; i = i + 1) {
+        value = value + value_noise(q) * amplitude;
+        q = q * 2.03 + vec2<f32>(17.0, 9.0);
+        amplitude = amplitude * 0.5;
+    }
+    return value;
+}
+
+struct FragmentOutput {
+    @location(0) color: vec4<f32>,
+    // Depth of the cloud plane, so terrain nearer than it hides it.
+    @builtin(frag_depth) depth: f32,
+};
+
+@fragment
+fn fs_main(input: VertexOutput) -> FragmentOutput {
+    let near = clouds.inv_view_proj * vec4<f32>(input.ndc, 0.0, 1.0);
+    let far = clouds.inv_view_proj * vec4<f32>(input.ndc, 1.0, 1.0);
+    let dir = normalize(far.xyz / far.w - near.xyz / near.w);
+    let camera = environment.camera_position.xyz;
+
+    let altitude = clouds.params.x;
+    if (abs(dir.y) < 0.0001) {
+        discard;
+    }
+    let t = (altitude - camera.y) / dir.y;
+    if (t <= 0.0) {
+        discard;
+    }
+
+    let hit = camera + dir * t;
+    let distance = length(hit.xz - camera.xz);
+    let fade_distance = clouds.params.w;
+    let fade = 1.0 - smoothstep(fade_distance * 0.35, fade_distance, distance);
+    if (fade <= 0.0) {
+        discard;
+    }
+
+    // Drift with the wind; animation_time keeps the scroll tied to the animation clock.
+    let wind = vec2<f32>(1.6, 0.7) * clouds.params.y;
+    let density = fbm((hit.xz + wind) * 0.012);
+    let coverage = clouds.params.z;
+    let cover = smoothstep(coverage, coverage + 0.18, density);
+    let alpha = cover * fade * 0.85;
+    if (alpha < 0.01) {
+        discard;
+    }
+
+    // time_params: [daylight, sun_elevation, twilight, time_of_day]
+    let daylight = environment.time_params.x;
+    let lit = mix(vec3<f32>(0.14, 0.16, 0.22), vec3<f32>(0.98, 0.98, 1.0), daylight);
+    let storm = clamp(environment.fog_params.x * 3.0, 0.0, 0.45);
+    var color = lit * (1.0 - storm) * (1.0 - 0.18 * cover);
+    color = mix(color, environment.fog_color.rgb, smoothstep(0.0, fade_distance, distance) * 0.7);
+
+    let clip = clouds.view_proj * vec4<f32>(hit, 1.0);
+    var output: FragmentOutput;
+    output.color = vec4<f32>(color, alpha);
+    output.depth = clamp(clip.z / clip.w, 0.0, 1.0);
+    return output;
+}
@@ -1,5 +1,5 @@
 use crate::block::BlockType;
-use crate::item::{ItemType, MaterialType, ToolType};
+use crate::item::{BucketState, ItemType, MaterialType, ToolType};
 
 /// Represents a crafting recipe
 #[derive(Clone, Debug)]
@@ -271,6 +271,16 @@ impl CraftingSystem {
             output: ItemType::Block(Torch),
             output_count: 4,
         });
+
+        // Bucket: 3 iron ore in a V
+        self.recipes.push(Recipe {
+            pattern: RecipePattern::Shaped(vec![
+                vec![Some(B(IronOre)), None, Some(B(IronOre))],
+                vec![None, Some(B(IronOre)), None],
+            ]),
+            output: ItemType::Bucket(BucketState::Empty),
+            output_count: 1,
+        });
     }
 
     pub fn recipe_count(&self) -> usize {
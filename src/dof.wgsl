@@ -0,0 +1,75 @@
+struct DepthOfField {
+    // x: focus distance, y: half-width of the sharp band, z: max blur radius in pixels
+    focus: vec4<f32>,
+    // x: near plane, y: far plane, zw: size of one pixel in uv
+    clip: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var scene_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var scene_sampler: sampler;
+@group(0) @binding(2)
+var depth_texture: texture_depth_2d;
+@group(0) @binding(3)
+var<uniform> dof: DepthOfField;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> @builtin(position) vec4<f32> {
+    var positions = array<vec2<f32>, 3>(
+        vec2<f32>(-1.0, -3.0),
+        vec2<f32>(3.0, 1.0),
+        vec2<f32>(-1.0, 1.0),
+    );
+    return vec4<f32>(positions[vertex_index], 0.0, 1.0);
+}
+
+fn linear_depth(uv: vec2<f32>) -> f32 {
+    let size = vec2<f32>(textureDimensions(depth_texture));
+    let texel = vec2<i32>(clamp(uv * size, vec2<f32>(0.0), size - 1.0));
+    let depth = textureLoad(depth_texture, texel, 0);
+    let near = dof.clip.x;
+    let far = dof.clip.y;
+    return 2.0 * near * far / ((far + near) - depth * (far - near));
+}
+
+// 0 inside the sharp band around the focus distance, rising to 1 a focus distance beyond it.
+fn circle_of_confusion(uv: vec2<f32>) -> f32 {
+    let distance = abs(linear_depth(uv) - dof.focus.x);
+    return clamp((distance - dof.focus.y) / max(dof.focus.x, 1.0), 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let uv = position.xy * dof.clip.zw;
+    let center = textureSampleLevel(scene_texture, scene_sampler, uv, 0.0);
+    let radius = circle_of_confusion(uv) * dof.focus.z;
+    if radius < 0.5 {
+        return center;
+    }
+
+    var taps = array<vec2<f32>, 12>(
+        vec2<f32>(-0.326, -0.406),
+        vec2<f32>(-0.840, -0.074),
+        vec2<f32>(-0.696, 0.457),
+        vec2<f32>(-0.203, 0.621),
+        vec2<f32>(0.962, -0.195),
+        vec2<f32>(0.473, -0.480),
+        vec2<f32>(0.519, 0.767),
+        vec2<f32>(0.185, -0.893),
+        vec2<f32>(0.507, 0.064),
+        vec2<f32>(0.896, 0.412),
+        vec2<f32>(-0.322, -0.933),
+        vec2<f32>(-0.792, -0.598),
+    );
+    var color = center.rgb;
+    var weight = 1.0;
+    for (var i = 0u; i < 12u; i = i + 1u) {
+        let tap_uv = uv + taps[i] * radius * dof.clip.zw;
+        // Sharp pixels keep to themselves so in-focus edges don't smear into the blur.
+        let tap_weight = circle_of_confusion(tap_uv);
+        color += textureSampleLevel(scene_texture, scene_sampler, tap_uv, 0.0).rgb * tap_weight;
+        weight += tap_weight;
+    }
+    return vec4<f32>(color / weight, center.a);
+}
@@ -1,10 +1,12 @@
 use std::collections::{HashMap, HashSet, VecDeque};
 
+use anyhow::Context;
 use cgmath::Vector3;
 
 use crate::{
     block::{Axis, BlockFace, BlockType, ElectricalKind},
     chunk::CHUNK_SIZE,
+    save::{SaveReader, SaveWriter, StateHasher},
     world::ChunkPos,
 };
 
@@ -18,6 +20,34 @@ const NEIGHBOR_DIRS: [Vector3<i32>; 6] = [
     Vector3::new(0, 0, -1),
 ];
 
+/// Resistance of the path through water from a wet component to ground.
+pub const WATER_LEAK_OHMS: f32 = 2.0;
+
+/// Once a network's capacitors sit this close to their final voltage they snap to it and
+/// `tick` stops integrating until the next edit.
+const CAPACITOR_SETTLED_VOLTS: f32 = 1e-3;
+
+/// Resistance of a reverse-biased diode; leaves a trickle of current rather than none.
+const DIODE_BLOCKING_OHMS: f32 = 1e9;
+/// Re-solves allowed for diode states to settle before the last guess is kept.
+const DIODE_SETTLE_PASSES: usize = 8;
+
+/// Resistance a potentiometer sweeps between, wiper fully down to fully up.
+pub const POTENTIOMETER_MIN_OHMS: f32 = 1.0;
+pub const POTENTIOMETER_MAX_OHMS: f32 = 1000.0;
+
+/// Forward current at which an LED reaches full brightness.
+pub const LED_FULL_BRIGHTNESS_AMPS: f32 = 0.5;
+/// Brightness steps an LED's mesh is rebuilt at, so small current changes don't remesh.
+pub const LED_BRIGHTNESS_LEVELS: u8 = 8;
+
+/// Quantized brightness of an LED carrying `current`, from 0 (dark) to
+/// `LED_BRIGHTNESS_LEVELS`.
+pub fn led_level(current: f32) -> u8 {
+    let fraction = (current / LED_FULL_BRIGHTNESS_AMPS).clamp(0.0, 1.0);
+    (fraction * LED_BRIGHTNESS_LEVELS as f32).round() as u8
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct BlockPos3 {
     pub x: i32,
@@ -38,8 +68,12 @@ impl BlockPos3 {
 #[derive(Debug, Clone, Copy, Default, PartialEq)]
 pub struct ComponentParams {
     pub resistance_ohms: Option<f32>,
+    /// A source's EMF, or the forward drop an LED or diode needs before it conducts.
     pub voltage_volts: Option<f32>,
     pub max_current_amps: Option<f32>,
+    pub capacitance_farads: Option<f32>,
+    /// Sources only: hold the loop at `max_current_amps` instead of tripping past it.
+    pub current_limit: bool,
 }
 
 #[derive(Debug, Clone, Copy, Default, PartialEq)]
@@ -61,6 +95,8 @@ impl ComponentParams {
             resistance_ohms: Some(resistance),
             voltage_volts: None,
             max_current_amps: Some(max_current),
+            capacitance_farads: None,
+            current_limit: false,
         }
     }
 
@@ -69,6 +105,8 @@ impl ComponentParams {
             resistance_ohms: Some(resistance),
             voltage_volts: None,
             max_current_amps: Some(max_current),
+            capacitance_farads: None,
+            current_limit: false,
         }
     }
 
@@ -77,6 +115,8 @@ impl ComponentParams {
             resistance_ohms: Some(internal_resistance),
             voltage_volts: Some(voltage),
             max_current_amps: Some(max_current),
+            capacitance_farads: None,
+            current_limit: false,
         }
     }
 
@@ -85,6 +125,68 @@ impl ComponentParams {
             resistance_ohms: Some(0.0),
             voltage_volts: Some(0.0),
             max_current_amps: None,
+            capacitance_farads: None,
+            current_limit: false,
+        }
+    }
+
+    /// `contact_resistance` applies while the switch is closed; open, it conducts nothing.
+    pub const fn switch(contact_resistance: f32, max_current: f32) -> Self {
+        Self {
+            resistance_ohms: Some(contact_resistance),
+            voltage_volts: None,
+            max_current_amps: Some(max_current),
+            capacitance_farads: None,
+            current_limit: false,
+        }
+    }
+
+    /// `forward_voltage` is dropped across the LED while it conducts; below it, no current.
+    pub const fn led(forward_voltage: f32, series_resistance: f32, max_current: f32) -> Self {
+        Self {
+            resistance_ohms: Some(series_resistance),
+            voltage_volts: Some(forward_voltage),
+            max_current_amps: Some(max_current),
+            capacitance_farads: None,
+            current_limit: false,
+        }
+    }
+
+    /// Conducts with `forward_voltage` dropped across `on_resistance`, and blocks reverse current.
+    pub const fn diode(forward_voltage: f32, on_resistance: f32, max_current: f32) -> Self {
+        Self {
+            resistance_ohms: Some(on_resistance),
+            voltage_volts: Some(forward_voltage),
+            max_current_amps: Some(max_current),
+            capacitance_farads: None,
+            current_limit: false,
+        }
+    }
+
+    /// A variable resistor with its wiper `wiper` (0 to 1) of the way up its range.
+    pub fn potentiometer(wiper: f32, max_current: f32) -> Self {
+        let span = POTENTIOMETER_MAX_OHMS - POTENTIOMETER_MIN_OHMS;
+        Self::resistor(
+            POTENTIOMETER_MIN_OHMS + wiper.clamp(0.0, 1.0) * span,
+            max_current,
+        )
+    }
+
+    /// How far up a potentiometer's range these params' resistance sits, from 0 to 1.
+    pub fn wiper(&self) -> f32 {
+        let resistance = self.resistance_ohms.unwrap_or(POTENTIOMETER_MIN_OHMS);
+        ((resistance - POTENTIOMETER_MIN_OHMS) / (POTENTIOMETER_MAX_OHMS - POTENTIOMETER_MIN_OHMS))
+            .clamp(0.0, 1.0)
+    }
+
+    /// `series_resistance` is the small ESR the charging current flows through.
+    pub const fn capacitor(capacitance: f32, series_resistance: f32, max_current: f32) -> Self {
+        Self {
+            resistance_ohms: Some(series_resistance),
+            voltage_volts: None,
+            max_current_amps: Some(max_current),
+            capacitance_farads: Some(capacitance),
+            current_limit: false,
         }
     }
 }
@@ -95,6 +197,11 @@ pub enum ElectricalComponent {
     Resistor,
     VoltageSource,
     Ground,
+    Capacitor,
+    Switch,
+    Led,
+    Diode,
+    Potentiometer,
 }
 
 impl ElectricalComponent {
@@ -104,6 +211,11 @@ impl ElectricalComponent {
             ElectricalKind::Resistor => Some(Self::Resistor),
             ElectricalKind::VoltageSource => Some(Self::VoltageSource),
             ElectricalKind::Ground => Some(Self::Ground),
+            ElectricalKind::Capacitor => Some(Self::Capacitor),
+            ElectricalKind::Switch => Some(Self::Switch),
+            ElectricalKind::Led => Some(Self::Led),
+            ElectricalKind::Diode => Some(Self::Diode),
+            ElectricalKind::Potentiometer => Some(Self::Potentiometer),
         }
     }
 
@@ -127,7 +239,12 @@ impl ElectricalComponent {
                 connectors[face_index(face)] = true;
                 connectors
             }
-            Self::VoltageSource => {
+            Self::VoltageSource
+            | Self::Capacitor
+            | Self::Switch
+            | Self::Led
+            | Self::Diode
+            | Self::Potentiometer => {
                 let mut connectors = axis_pair_connectors(axis);
                 // Also enable the mount face connector
                 connectors[face_index(face)] = true;
@@ -143,7 +260,14 @@ impl ElectricalComponent {
 
     pub fn default_axis(self) -> Axis {
         match self {
-            Self::Wire | Self::Resistor | Self::VoltageSource => Axis::X,
+            Self::Wire
+            | Self::Resistor
+            | Self::VoltageSource
+            | Self::Capacitor
+            | Self::Switch
+            | Self::Led
+            | Self::Diode
+            | Self::Potentiometer => Axis::X,
             Self::Ground => Axis::Y,
         }
     }
@@ -154,6 +278,13 @@ impl ElectricalComponent {
             Self::Resistor => ComponentParams::resistor(100.0, 2.0),
             Self::VoltageSource => ComponentParams::voltage_source(12.0, 0.1, 10.0),
             Self::Ground => ComponentParams::ground(),
+            // 20 mF behind the default resistor charges with a two second time constant.
+            Self::Capacitor => ComponentParams::capacitor(0.02, 0.1, 5.0),
+            Self::Switch => ComponentParams::switch(0.01, 10.0),
+            // Sturdier than a real LED so it survives being wired straight across a source.
+            Self::Led => ComponentParams::led(2.0, 50.0, 1.0),
+            Self::Diode => ComponentParams::diode(0.7, 0.05, 5.0),
+            Self::Potentiometer => ComponentParams::potentiometer(0.1, 2.0),
         }
     }
 
@@ -163,7 +294,12 @@ impl ElectricalComponent {
             ElectricalComponent::Ground => (mount_face, mount_face),
             ElectricalComponent::Wire
             | ElectricalComponent::Resistor
-            | ElectricalComponent::VoltageSource => (axis.positive_face(), axis.negative_face()),
+            | ElectricalComponent::VoltageSource
+            | ElectricalComponent::Capacitor
+            | ElectricalComponent::Switch
+            | ElectricalComponent::Led
+            | ElectricalComponent::Diode
+            | ElectricalComponent::Potentiometer => (axis.positive_face(), axis.negative_face()),
         }
     }
 
@@ -173,6 +309,27 @@ impl ElectricalComponent {
             Self::Resistor => BlockType::Resistor,
             Self::VoltageSource => BlockType::VoltageSource,
             Self::Ground => BlockType::Ground,
+            Self::Capacitor => BlockType::Capacitor,
+            Self::Switch => BlockType::Switch,
+            Self::Led => BlockType::Led,
+            Self::Diode => BlockType::Diode,
+            Self::Potentiometer => BlockType::Potentiometer,
+        }
+    }
+
+    /// Case-insensitive lookup for console commands; `"source"` is short for a voltage source.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "wire" => Some(Self::Wire),
+            "resistor" => Some(Self::Resistor),
+            "source" | "voltagesource" => Some(Self::VoltageSource),
+            "ground" => Some(Self::Ground),
+            "capacitor" | "cap" => Some(Self::Capacitor),
+            "switch" => Some(Self::Switch),
+            "led" => Some(Self::Led),
+            "diode" => Some(Self::Diode),
+            "potentiometer" | "pot" => Some(Self::Potentiometer),
+            _ => None,
         }
     }
 }
@@ -185,6 +342,11 @@ pub struct ElectricalNode {
     pub face: BlockFace,
     pub params: ComponentParams,
     pub telemetry: ComponentTelemetry,
+    /// Voltage across a capacitor's plates; it persists between ticks and stays 0 for
+    /// every other component.
+    pub charge_volts: f32,
+    /// Whether a switch conducts. Switches are placed open; other components ignore it.
+    pub closed: bool,
 }
 
 impl ElectricalNode {
@@ -206,6 +368,26 @@ pub struct NetworkElement {
     pub params: ComponentParams,
 }
 
+/// Whole-world circuit totals for the electrical summary panel.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ElectricalSummary {
+    pub wires: usize,
+    pub resistors: usize,
+    pub sources: usize,
+    pub grounds: usize,
+    pub capacitors: usize,
+    pub switches: usize,
+    pub leds: usize,
+    pub diodes: usize,
+    pub potentiometers: usize,
+    /// Connected networks, whether or not they form a closed loop.
+    pub circuits: usize,
+    /// Power delivered by every source after breakers have tripped.
+    pub power_watts: f32,
+    /// Components rated below the current their network tried to draw.
+    pub overloaded: usize,
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct ElectricalNetwork {
     pub elements: Vec<NetworkElement>,
@@ -264,6 +446,20 @@ pub struct ElectricalSystem {
     nodes: HashMap<BlockPos3, FaceNodes>,
     networks: Vec<ElectricalNetwork>,
     dirty_blocks: HashSet<BlockPos3>,
+    /// Times `tick` has rebuilt the networks, for the debug overlay and batching tests.
+    rebuild_count: u64,
+    /// Components whose rating the last tick's demanded current exceeded, with that current.
+    overloaded: HashMap<AttachmentKey, f32>,
+    delivered_watts: f32,
+    /// A capacitor was still charging or discharging on the last update, so `tick` keeps
+    /// integrating even without edits.
+    charging: bool,
+    /// LEDs whose brightness level changed since `take_relit_leds` last ran.
+    relit_leds: HashSet<BlockPos3>,
+    /// Opt-in: fluid touching a component leaks its network to ground.
+    water_leakage: bool,
+    /// Components whose cell, or the cell their mount face opens into, holds fluid.
+    wet: HashSet<AttachmentKey>,
 }
 
 impl ElectricalSystem {
@@ -272,9 +468,72 @@ impl ElectricalSystem {
             nodes: HashMap::new(),
             networks: Vec::new(),
             dirty_blocks: HashSet::new(),
+            rebuild_count: 0,
+            overloaded: HashMap::new(),
+            delivered_watts: 0.0,
+            charging: false,
+            relit_leds: HashSet::new(),
+            water_leakage: false,
+            wet: HashSet::new(),
+        }
+    }
+
+    pub fn water_leakage(&self) -> bool {
+        self.water_leakage
+    }
+
+    pub fn set_water_leakage(&mut self, enabled: bool) {
+        if self.water_leakage != enabled {
+            self.water_leakage = enabled;
+            if !enabled {
+                self.set_wet_cells(&HashSet::new());
+            }
         }
     }
 
+    /// Cells whose fluid would wet a component: each component's own cell and the
+    /// cell its mount face opens into.
+    pub fn exposed_cells(&self) -> HashSet<BlockPos3> {
+        self.nodes
+            .iter()
+            .flat_map(|(&pos, faces)| {
+                faces
+                    .iter()
+                    .flat_map(move |(face, _)| [pos, pos.offset(face.normal())])
+            })
+            .collect()
+    }
+
+    /// Marks the components touching `wet_cells`. A change schedules a rebuild so the
+    /// leakage paths follow the water.
+    pub fn set_wet_cells(&mut self, wet_cells: &HashSet<BlockPos3>) {
+        let wet: HashSet<AttachmentKey> = self
+            .nodes
+            .iter()
+            .flat_map(|(&pos, faces)| {
+                faces
+                    .iter()
+                    .map(move |(face, _)| AttachmentKey { pos, face })
+            })
+            .filter(|key| {
+                wet_cells.contains(&key.pos)
+                    || wet_cells.contains(&key.pos.offset(key.face.normal()))
+            })
+            .collect();
+        if wet != self.wet {
+            self.dirty_blocks
+                .extend(wet.symmetric_difference(&self.wet).map(|key| key.pos));
+            self.wet = wet;
+        }
+    }
+
+    pub fn is_wet(&self, world_pos: BlockPos3, face: BlockFace) -> bool {
+        self.wet.contains(&AttachmentKey {
+            pos: world_pos,
+            face,
+        })
+    }
+
     /// Called whenever a world block changes.
     pub fn update_block(
         &mut self,
@@ -320,6 +579,8 @@ impl ElectricalSystem {
                     face,
                     params,
                     telemetry: ComponentTelemetry::default(),
+                    charge_volts: 0.0,
+                    closed: false,
                 },
             );
             self.dirty_blocks.insert(world_pos);
@@ -363,6 +624,21 @@ impl ElectricalSystem {
         }
     }
 
+    /// Drops every component hosted in `chunk`. Networks reaching across its border are
+    /// rebuilt on the next tick, so neighbours lose the connection cleanly.
+    pub fn remove_chunk_components(&mut self, chunk: ChunkPos) -> usize {
+        let hosted: Vec<BlockPos3> = self
+            .nodes
+            .iter()
+            .filter(|(_, faces)| faces.iter().any(|(_, node)| node.chunk == chunk))
+            .map(|(&pos, _)| pos)
+            .collect();
+        for &pos in &hosted {
+            self.remove_all_components(pos);
+        }
+        hosted.len()
+    }
+
     pub fn set_axis(&mut self, world_pos: BlockPos3, face: BlockFace, axis: Axis) {
         if let Some(entry) = self.nodes.get_mut(&world_pos) {
             if let Some(node) = entry.get_mut(face) {
@@ -375,6 +651,18 @@ impl ElectricalSystem {
         }
     }
 
+    /// Flips the switch at `face` between open and closed and returns the new state, or
+    /// `None` when there is no switch there.
+    pub fn toggle_switch(&mut self, world_pos: BlockPos3, face: BlockFace) -> Option<bool> {
+        let node = self.nodes.get_mut(&world_pos)?.get_mut(face)?;
+        if node.component != ElectricalComponent::Switch {
+            return None;
+        }
+        node.closed = !node.closed;
+        self.dirty_blocks.insert(world_pos);
+        Some(node.closed)
+    }
+
     pub fn set_params(&mut self, world_pos: BlockPos3, face: BlockFace, params: ComponentParams) {
         if let Some(entry) = self.nodes.get_mut(&world_pos) {
             if let Some(node) = entry.get_mut(face) {
@@ -386,6 +674,46 @@ impl ElectricalSystem {
         }
     }
 
+    /// Applies `params` to every component of the same type in the network that
+    /// contains the given node. Returns how many components were updated.
+    pub fn set_params_in_network(
+        &mut self,
+        world_pos: BlockPos3,
+        face: BlockFace,
+        params: ComponentParams,
+    ) -> usize {
+        let Some(component) = self.component_at(world_pos, face) else {
+            return 0;
+        };
+        let targets: Vec<(BlockPos3, BlockFace)> = self
+            .networks
+            .iter()
+            .find(|network| {
+                network
+                    .elements
+                    .iter()
+                    .any(|element| element.position == world_pos && element.face == face)
+            })
+            .map(|network| {
+                network
+                    .elements
+                    .iter()
+                    .filter(|element| element.component == component)
+                    .map(|element| (element.position, element.face))
+                    .collect()
+            })
+            .unwrap_or_else(|| vec![(world_pos, face)]);
+
+        let mut updated = 0;
+        for (pos, target_face) in targets {
+            if self.component_at(pos, target_face) == Some(component) {
+                self.set_params(pos, target_face, params);
+                updated += 1;
+            }
+        }
+        updated
+    }
+
     pub fn axis_at(&self, world_pos: BlockPos3, face: BlockFace) -> Option<Axis> {
         self.nodes
             .get(&world_pos)
@@ -422,6 +750,87 @@ impl ElectricalSystem {
             .map(|node| node.telemetry)
     }
 
+    /// Plate voltage of the capacitor at `face`; 0 for other components.
+    pub fn charge_at(&self, world_pos: BlockPos3, face: BlockFace) -> Option<f32> {
+        self.nodes
+            .get(&world_pos)
+            .and_then(|entry| entry.get(face))
+            .map(|node| node.charge_volts)
+    }
+
+    /// Whether the switch at `face` is closed; `None` when there is no switch there.
+    pub fn switch_closed(&self, world_pos: BlockPos3, face: BlockFace) -> Option<bool> {
+        self.nodes
+            .get(&world_pos)
+            .and_then(|entry| entry.get(face))
+            .filter(|node| node.component == ElectricalComponent::Switch)
+            .map(|node| node.closed)
+    }
+
+    /// Closest block to `from` holding `component` on any face, by straight-line distance.
+    pub fn nearest_component(
+        &self,
+        from: BlockPos3,
+        component: ElectricalComponent,
+    ) -> Option<BlockPos3> {
+        let positions = self
+            .nodes
+            .iter()
+            .filter(|(_, faces)| faces.iter().any(|(_, node)| node.component == component))
+            .map(|(pos, _)| *pos);
+        nearest_to(from, positions)
+    }
+
+    /// Closest block to `from` holding a component that tripped its network.
+    pub fn nearest_overloaded(&self, from: BlockPos3) -> Option<BlockPos3> {
+        nearest_to(from, self.overloaded.keys().map(|key| key.pos))
+    }
+
+    /// Every component carrying more than its `max_current_amps` as of the last tick, in
+    /// position order. Their networks trip, so the live current through them reads 0.
+    pub fn overcurrent_nodes(&self) -> Vec<(BlockPos3, BlockFace)> {
+        let mut nodes: Vec<(BlockPos3, BlockFace)> =
+            self.overloaded.keys().map(|key| (key.pos, key.face)).collect();
+        nodes.sort_by_key(|(pos, face)| (pos.x, pos.y, pos.z, face_index(*face)));
+        nodes
+    }
+
+    /// Current the component on `face` of `world_pos` would have carried when its network
+    /// tripped, or `None` if it is within its rating.
+    pub fn overcurrent_at(&self, world_pos: BlockPos3, face: BlockFace) -> Option<f32> {
+        self.overloaded
+            .get(&AttachmentKey {
+                pos: world_pos,
+                face,
+            })
+            .copied()
+    }
+
+    pub fn summary(&self) -> ElectricalSummary {
+        let mut summary = ElectricalSummary {
+            circuits: self.networks.len(),
+            power_watts: self.delivered_watts,
+            overloaded: self.overloaded.len(),
+            ..ElectricalSummary::default()
+        };
+        for faces in self.nodes.values() {
+            for (_, node) in faces.iter() {
+                match node.component {
+                    ElectricalComponent::Wire => summary.wires += 1,
+                    ElectricalComponent::Resistor => summary.resistors += 1,
+                    ElectricalComponent::VoltageSource => summary.sources += 1,
+                    ElectricalComponent::Ground => summary.grounds += 1,
+                    ElectricalComponent::Capacitor => summary.capacitors += 1,
+                    ElectricalComponent::Switch => summary.switches += 1,
+                    ElectricalComponent::Led => summary.leds += 1,
+                    ElectricalComponent::Diode => summary.diodes += 1,
+                    ElectricalComponent::Potentiometer => summary.potentiometers += 1,
+                }
+            }
+        }
+        summary
+    }
+
     pub fn powered_nodes(
         &self,
         min_current: f32,
@@ -446,10 +855,43 @@ impl ElectricalSystem {
         powered
     }
 
+    /// Every LED carrying forward current, with the face it is mounted on.
+    pub fn lit_leds(&self) -> Vec<(BlockPos3, BlockFace, f32)> {
+        let mut lit = Vec::new();
+        for (pos, faces) in &self.nodes {
+            for (face, node) in faces.iter() {
+                if node.component == ElectricalComponent::Led && node.telemetry.current > 0.0 {
+                    lit.push((*pos, face, node.telemetry.current));
+                }
+            }
+        }
+        lit
+    }
+
     pub fn connection_mask(&self, world_pos: BlockPos3, face: BlockFace) -> Option<[bool; 6]> {
-        let faces = self.nodes.get(&world_pos)?;
-        let node = faces.get(face)?;
-        let connectors = node.connectors();
+        let node = self.nodes.get(&world_pos)?.get(face)?;
+        Some(self.mask_for_connectors(world_pos, face, node.connectors()))
+    }
+
+    /// Connections a `component` would form if it were placed on `face` of `world_pos`
+    /// with `axis`, using the same rules as `connection_mask`. Any component already
+    /// on that face is ignored since placement would replace it.
+    pub fn preview_connection_mask(
+        &self,
+        world_pos: BlockPos3,
+        face: BlockFace,
+        component: ElectricalComponent,
+        axis: Axis,
+    ) -> [bool; 6] {
+        self.mask_for_connectors(world_pos, face, component.connectors(axis, face))
+    }
+
+    fn mask_for_connectors(
+        &self,
+        world_pos: BlockPos3,
+        face: BlockFace,
+        connectors: [bool; 6],
+    ) -> [bool; 6] {
         let mut mask = [false; 6];
 
         for (idx, has_connector) in connectors.iter().enumerate() {
@@ -468,6 +910,9 @@ impl ElectricalSystem {
             }
         }
 
+        let Some(faces) = self.nodes.get(&world_pos) else {
+            return mask;
+        };
         for (other_face, other_node) in faces.iter() {
             if other_face == face {
                 continue;
@@ -480,21 +925,162 @@ impl ElectricalSystem {
             }
         }
 
-        Some(mask)
+        mask
+    }
+
+    /// Current state of every attached component, ordered by position and face
+    /// so successive snapshots line up row for row. Only allocates when called.
+    pub fn telemetry_snapshot(
+        &self,
+    ) -> Vec<(BlockPos3, BlockFace, ElectricalComponent, ComponentTelemetry)> {
+        let mut snapshot = Vec::with_capacity(self.node_count());
+        for (pos, faces) in &self.nodes {
+            for (face, node) in faces.iter() {
+                snapshot.push((*pos, face, node.component, node.telemetry));
+            }
+        }
+        snapshot.sort_by_key(|(pos, face, _, _)| (pos.x, pos.y, pos.z, face_index(*face)));
+        snapshot
+    }
+
+    /// Feeds every attached component, its parameters, capacitor charge and switch state into
+    /// `state` in position/face order. Telemetry is left out since `tick` derives it from the rest.
+    pub fn hash_state(&self, state: &mut StateHasher) {
+        let mut positions: Vec<BlockPos3> = self.nodes.keys().copied().collect();
+        positions.sort_by_key(|pos| (pos.x, pos.y, pos.z));
+        for pos in positions {
+            for (face, node) in self.nodes[&pos].iter() {
+                state.put_i32(pos.x);
+                state.put_i32(pos.y);
+                state.put_i32(pos.z);
+                state.put_u8(face_index(face) as u8);
+                state.put_u8(node.component as u8);
+                state.put_u8(node.axis as u8);
+                for value in [
+                    node.params.resistance_ohms,
+                    node.params.voltage_volts,
+                    node.params.max_current_amps,
+                    node.params.capacitance_farads,
+                ] {
+                    state.put_u32(value.map_or(u32::MAX, f32::to_bits));
+                }
+                state.put_u8(node.params.current_limit as u8);
+                state.put_u32(node.charge_volts.to_bits());
+                state.put_u8(node.closed as u8);
+            }
+        }
+    }
+
+    /// Writes every attached component with its axis, face, parameters, capacitor charge and
+    /// switch state in position/face order. Telemetry is left out; `read_save` marks
+    /// everything dirty so `tick` recomputes it.
+    pub fn write_save(&self, out: &mut SaveWriter) {
+        let mut positions: Vec<BlockPos3> = self.nodes.keys().copied().collect();
+        positions.sort_by_key(|pos| (pos.x, pos.y, pos.z));
+        out.put_u32(self.node_count() as u32);
+        for pos in positions {
+            for (face, node) in self.nodes[&pos].iter() {
+                out.put_i32(pos.x);
+                out.put_i32(pos.y);
+                out.put_i32(pos.z);
+                out.put_u8(face_index(face) as u8);
+                out.put_u8(node.component.block_type() as u8);
+                out.put_u8(node.axis.to_index() as u8);
+                out.put_opt_f32(node.params.resistance_ohms);
+                out.put_opt_f32(node.params.voltage_volts);
+                out.put_opt_f32(node.params.max_current_amps);
+                out.put_opt_f32(node.params.capacitance_farads);
+                out.put_bool(node.params.current_limit);
+                out.put_f32(node.charge_volts);
+                out.put_bool(node.closed);
+            }
+        }
+    }
+
+    /// Replaces every component with the ones in `input`, keeping the water leakage
+    /// setting. Networks and telemetry are rebuilt on the next `tick`.
+    pub fn read_save(&mut self, input: &mut SaveReader) -> anyhow::Result<()> {
+        let mut restored = Self::new();
+        restored.water_leakage = self.water_leakage;
+        for _ in 0..input.u32()? {
+            let pos = BlockPos3::new(input.i32()?, input.i32()?, input.i32()?);
+            let face_id = input.u8()? as usize;
+            anyhow::ensure!(face_id < 6, "invalid face {face_id} on saved component");
+            let face = face_from_index(face_id);
+            let block_id = input.u8()?;
+            let component = BlockType::from_id(block_id)
+                .and_then(ElectricalComponent::from_block)
+                .with_context(|| format!("block id {block_id} is not a component"))?;
+            let axis_id = input.u8()?;
+            let axis = Axis::all()
+                .get(axis_id as usize)
+                .copied()
+                .with_context(|| format!("invalid axis {axis_id} on saved component"))?;
+            let params = ComponentParams {
+                resistance_ohms: input.opt_f32()?,
+                voltage_volts: input.opt_f32()?,
+                max_current_amps: input.opt_f32()?,
+                capacitance_farads: input.opt_f32()?,
+                current_limit: input.bool()?,
+            };
+            let charge_volts = input.f32()?;
+            let closed = input.bool()?;
+            let chunk = ChunkPos {
+                x: pos.x.div_euclid(CHUNK_SIZE as i32),
+                z: pos.z.div_euclid(CHUNK_SIZE as i32),
+            };
+            restored.nodes.entry(pos).or_default().set(
+                face,
+                ElectricalNode {
+                    component,
+                    chunk,
+                    axis,
+                    face,
+                    params,
+                    telemetry: ComponentTelemetry::default(),
+                    charge_volts,
+                    closed,
+                },
+            );
+            restored.dirty_blocks.insert(pos);
+        }
+        *self = restored;
+        Ok(())
+    }
+
+    /// Total number of attached components across all blocks.
+    pub fn node_count(&self) -> usize {
+        self.nodes.values().map(|faces| faces.iter().count()).sum()
     }
 
     pub(crate) fn face_nodes(&self, world_pos: BlockPos3) -> Option<&FaceNodes> {
         self.nodes.get(&world_pos)
     }
 
-    pub fn tick(&mut self) {
-        if self.dirty_blocks.is_empty() {
+    /// Rebuilds edited networks and refreshes telemetry. `dt_seconds` is how much time the
+    /// tick covers, which capacitors integrate their charging current over; edits made
+    /// outside the simulation pass 0.
+    pub fn tick(&mut self, dt_seconds: f32) {
+        if self.dirty_blocks.is_empty() && !self.charging {
             return;
         }
 
-        self.rebuild_networks();
-        self.update_telemetry();
-        self.dirty_blocks.clear();
+        if !self.dirty_blocks.is_empty() {
+            self.rebuild_networks();
+            self.dirty_blocks.clear();
+            self.rebuild_count += 1;
+        }
+        self.update_telemetry(dt_seconds);
+    }
+
+    /// Positions of LEDs that got brighter or dimmer since the last call, so their meshes
+    /// can be rebuilt.
+    pub fn take_relit_leds(&mut self) -> Vec<BlockPos3> {
+        self.relit_leds.drain().collect()
+    }
+
+    pub fn rebuild_count(&self) -> u64 {
+        self.rebuild_count
     }
 
     #[allow(dead_code)]
@@ -643,7 +1229,13 @@ impl ElectricalSystem {
                     match current_node.component {
                         ElectricalComponent::VoltageSource => network.has_source = true,
                         ElectricalComponent::Ground => network.has_ground = true,
-                        ElectricalComponent::Wire | ElectricalComponent::Resistor => {}
+                        ElectricalComponent::Wire
+                        | ElectricalComponent::Resistor
+                        | ElectricalComponent::Capacitor
+                        | ElectricalComponent::Switch
+                        | ElectricalComponent::Led
+                        | ElectricalComponent::Diode
+                        | ElectricalComponent::Potentiometer => {}
                     }
 
                     network.elements.push(NetworkElement {
@@ -654,52 +1246,9 @@ impl ElectricalSystem {
                         params: current_node.params,
                     });
 
-                    let connectors = current_node.connectors();
-                    for (idx, dir) in NEIGHBOR_DIRS.iter().enumerate() {
-                        if !connectors[idx] {
-                            continue;
-                        }
-                        let neighbor_pos = current.pos.offset(*dir);
-                        let opposite = opposite_index(idx);
-                        if let Some(neighbors) = self.nodes.get(&neighbor_pos) {
-                            for (neighbor_face, neighbor_node) in neighbors.iter() {
-                                if !neighbor_node.connectors()[opposite] {
-                                    continue;
-                                }
-                                let neighbor_key = AttachmentKey {
-                                    pos: neighbor_pos,
-                                    face: neighbor_face,
-                                };
-                                if visited.contains(&neighbor_key) {
-                                    continue;
-                                }
-                                queue.push_back(neighbor_key);
-                            }
-                        }
-                    }
-
-                    if let Some(entry) = self.nodes.get(&current.pos) {
-                        for (other_face, other_node) in entry.iter() {
-                            if other_face == current.face {
-                                continue;
-                            }
-                            let other_connectors = other_node.connectors();
-                            let mut shared = false;
-                            for (idx, has_connector) in connectors.iter().enumerate() {
-                                if *has_connector && other_connectors[idx] {
-                                    shared = true;
-                                    break;
-                                }
-                            }
-                            if shared {
-                                let neighbor_key = AttachmentKey {
-                                    pos: current.pos,
-                                    face: other_face,
-                                };
-                                if !visited.contains(&neighbor_key) {
-                                    queue.push_back(neighbor_key);
-                                }
-                            }
+                    for (_, neighbor_key) in self.links(current) {
+                        if !visited.contains(&neighbor_key) {
+                            queue.push_back(neighbor_key);
                         }
                     }
                 }
@@ -711,6 +1260,128 @@ impl ElectricalSystem {
         }
     }
 
+    /// Attachments `key` is wired to, each tagged with the index of the connector it uses:
+    /// matching connectors on neighbouring blocks, and shared connectors on its own block.
+    fn links(&self, key: AttachmentKey) -> Vec<(usize, AttachmentKey)> {
+        let Some(node) = self.node_ref(key) else {
+            return Vec::new();
+        };
+        let connectors = node.connectors();
+        let mut links = Vec::new();
+        for (idx, dir) in NEIGHBOR_DIRS.iter().enumerate() {
+            if !connectors[idx] {
+                continue;
+            }
+            let neighbor_pos = key.pos.offset(*dir);
+            let opposite = opposite_index(idx);
+            if let Some(neighbors) = self.nodes.get(&neighbor_pos) {
+                for (neighbor_face, neighbor_node) in neighbors.iter() {
+                    if neighbor_node.connectors()[opposite] {
+                        let neighbor_key = AttachmentKey {
+                            pos: neighbor_pos,
+                            face: neighbor_face,
+                        };
+                        links.push((idx, neighbor_key));
+                    }
+                }
+            }
+        }
+
+        if let Some(entry) = self.nodes.get(&key.pos) {
+            for (other_face, other_node) in entry.iter() {
+                if other_face == key.face {
+                    continue;
+                }
+                let other_connectors = other_node.connectors();
+                for (idx, (&own, &other)) in connectors.iter().zip(&other_connectors).enumerate() {
+                    if own && other {
+                        let other_key = AttachmentKey {
+                            pos: key.pos,
+                            face: other_face,
+                        };
+                        links.push((idx, other_key));
+                    }
+                }
+            }
+        }
+        links
+    }
+
+    /// +1 when the loop current reaches `diode` at its positive terminal, -1 when it reaches
+    /// the negative one. Current leaves every source through its positive terminal, so the
+    /// diode terminal fewer hops from there wins, counting paths that avoid the diode itself
+    /// and never pass through a source.
+    fn diode_direction(&self, network: &ElectricalNetwork, diode: AttachmentKey) -> f32 {
+        let is_source = |key: AttachmentKey| {
+            self.node_ref(key)
+                .is_some_and(|node| node.component == ElectricalComponent::VoltageSource)
+        };
+        let positive_index =
+            |key: AttachmentKey| self.node_ref(key).map(|node| face_index(node.terminal_faces().0));
+
+        let mut hops: HashMap<AttachmentKey, usize> = HashMap::new();
+        let mut queue = VecDeque::new();
+        for element in &network.elements {
+            if element.component != ElectricalComponent::VoltageSource {
+                continue;
+            }
+            let source = AttachmentKey {
+                pos: element.position,
+                face: element.face,
+            };
+            for (idx, next) in self.links(source) {
+                if Some(idx) == positive_index(source)
+                    && next != diode
+                    && !is_source(next)
+                    && hops.insert(next, 1).is_none()
+                {
+                    queue.push_back(next);
+                }
+            }
+        }
+        while let Some(current) = queue.pop_front() {
+            let next_hops = hops[&current] + 1;
+            for (_, next) in self.links(current) {
+                if next == diode || is_source(next) || hops.contains_key(&next) {
+                    continue;
+                }
+                hops.insert(next, next_hops);
+                queue.push_back(next);
+            }
+        }
+
+        let Some(node) = self.node_ref(diode) else {
+            return 1.0;
+        };
+        let diode_links = self.links(diode);
+        let terminal_hops = |face: BlockFace| {
+            diode_links
+                .iter()
+                .filter(|(idx, _)| *idx == face_index(face))
+                .filter_map(|&(_, next)| {
+                    if is_source(next) {
+                        // Wired straight to a source: right at its positive terminal, or
+                        // unreachable at its negative one.
+                        let back = self
+                            .links(next)
+                            .into_iter()
+                            .find(|(_, key)| *key == diode)
+                            .map(|(idx, _)| idx);
+                        (back.is_some() && back == positive_index(next)).then_some(0)
+                    } else {
+                        hops.get(&next).copied()
+                    }
+                })
+                .min()
+        };
+        let (positive_face, negative_face) = node.terminal_faces();
+        match (terminal_hops(positive_face), terminal_hops(negative_face)) {
+            (Some(positive), Some(negative)) if negative < positive => -1.0,
+            (None, Some(_)) => -1.0,
+            _ => 1.0,
+        }
+    }
+
     fn node_ref(&self, key: AttachmentKey) -> Option<&ElectricalNode> {
         self.nodes
             .get(&key.pos)
@@ -723,17 +1394,61 @@ impl ElectricalSystem {
             .and_then(|entry| entry.get_mut(key.face))
     }
 
-    fn update_telemetry(&mut self) {
-        for faces in self.nodes.values_mut() {
-            for (_, node) in faces.iter_mut() {
+    fn update_telemetry(&mut self, dt_seconds: f32) {
+        let mut previous_levels = HashMap::new();
+        for (&pos, faces) in self.nodes.iter_mut() {
+            for (face, node) in faces.iter_mut() {
+                if node.component == ElectricalComponent::Led {
+                    let level = led_level(node.telemetry.current);
+                    previous_levels.insert(AttachmentKey { pos, face }, level);
+                }
                 node.telemetry = ComponentTelemetry::default();
             }
         }
 
         let mut telemetry_updates = Vec::new();
+        let mut charge_updates = Vec::new();
+        let mut still_charging = false;
+        self.overloaded.clear();
+        self.delivered_watts = 0.0;
 
         for network in &self.networks {
-            let has_loop = network.has_source && network.has_ground;
+            // Plate voltage of every capacitor, updated below once this tick's charge moves.
+            let mut plates: HashMap<AttachmentKey, (f32, f32)> = network
+                .elements
+                .iter()
+                .filter_map(|el| {
+                    let capacitance = el.params.capacitance_farads?.max(1e-6);
+                    let key = AttachmentKey {
+                        pos: el.position,
+                        face: el.face,
+                    };
+                    let volts = self.node_ref(key).map_or(0.0, |node| node.charge_volts);
+                    Some((key, (capacitance, volts)))
+                })
+                .collect();
+            let stored_voltage = plates.values().map(|(_, volts)| volts).sum::<f32>();
+            // An open switch is an infinite resistance in the series loop.
+            let broken = network.elements.iter().any(|el| {
+                el.component == ElectricalComponent::Switch
+                    && !self
+                        .node_ref(AttachmentKey {
+                            pos: el.position,
+                            face: el.face,
+                        })
+                        .is_some_and(|node| node.closed)
+            });
+            // A charged capacitor can drive current back to ground without a source.
+            let has_loop = !broken
+                && network.has_ground
+                && (network.has_source || !plates.is_empty());
+            let leaking = network.has_source
+                && network.elements.iter().any(|el| {
+                    self.wet.contains(&AttachmentKey {
+                        pos: el.position,
+                        face: el.face,
+                    })
+                });
 
             // Count voltage sources for validation
             let voltage_sources: Vec<_> = network
@@ -747,6 +1462,22 @@ impl ElectricalSystem {
                 .iter()
                 .filter_map(|el| el.params.voltage_volts)
                 .sum::<f32>();
+            // LEDs only conduct forward, and only once the rest of the loop clears their drop.
+            let forward_drop = network
+                .elements
+                .iter()
+                .filter(|el| el.component == ElectricalComponent::Led)
+                .filter_map(|el| el.params.voltage_volts)
+                .sum::<f32>();
+            let has_led = network
+                .elements
+                .iter()
+                .any(|el| el.component == ElectricalComponent::Led);
+            let driving = if has_led {
+                (source_voltage - forward_drop - stored_voltage).max(0.0)
+            } else {
+                source_voltage - stored_voltage
+            };
 
             // Calculate total resistance
             let total_resistance = network
@@ -755,20 +1486,69 @@ impl ElectricalSystem {
                 .filter_map(|el| el.params.resistance_ohms)
                 .sum::<f32>();
 
+            let diodes: Vec<(AttachmentKey, f32, ComponentParams)> = network
+                .elements
+                .iter()
+                .filter(|el| el.component == ElectricalComponent::Diode)
+                .map(|el| {
+                    let key = AttachmentKey {
+                        pos: el.position,
+                        face: el.face,
+                    };
+                    (key, self.diode_direction(network, key), el.params)
+                })
+                .collect();
+            let conducting = settle_diodes(&diodes, driving, total_resistance);
+            let (diode_drop, blocking_ohms) = diode_loop_terms(&diodes, &conducting);
+            let diode_states: HashMap<AttachmentKey, (f32, bool)> = diodes
+                .iter()
+                .zip(&conducting)
+                .map(|(&(key, direction, _), &on)| (key, (direction, on)))
+                .collect();
+            let driving = driving - diode_drop;
+            let total_resistance = total_resistance + blocking_ohms;
+
             // Ensure minimum resistance to avoid division by zero or unrealistic currents
             let effective_resistance = total_resistance.max(0.01);
 
             // Calculate theoretical current - only flows if we have a complete loop (source AND ground)
-            let mut current = if has_loop {
-                source_voltage / effective_resistance
+            let mut current = if leaking {
+                // Water is a conductance from the wet node to ground, in parallel with
+                // whatever the rest of the loop offers. Every element reports the source
+                // current, as in the plain series case.
+                let internal = voltage_sources
+                    .iter()
+                    .filter_map(|el| el.params.resistance_ohms)
+                    .sum::<f32>();
+                let shunt = if has_loop {
+                    let load = (total_resistance - internal).max(0.0);
+                    load * WATER_LEAK_OHMS / (load + WATER_LEAK_OHMS)
+                } else {
+                    WATER_LEAK_OHMS
+                };
+                source_voltage / (internal + shunt).max(0.01)
+            } else if has_loop {
+                driving / effective_resistance
             } else {
                 0.0
             };
 
+            // A current-limited source holds the loop at its rating rather than tripping.
+            let source_limit = voltage_sources
+                .iter()
+                .filter(|el| el.params.current_limit)
+                .filter_map(|el| el.params.max_current_amps)
+                .min_by(|a, b| a.total_cmp(b));
+            let limited = !leaking && source_limit.is_some_and(|limit| current.abs() > limit);
+            if let Some(limit) = source_limit.filter(|_| limited) {
+                current = limit.copysign(current);
+            }
+
             // Short circuit detection: Check if current exceeds any component's max_current
             // Find the most restrictive current limit in the network
             let mut is_short_circuit = false;
-            if current > 0.0 {
+            let demanded = current;
+            if current != 0.0 {
                 let min_max_current = network
                     .elements
                     .iter()
@@ -776,7 +1556,7 @@ impl ElectricalSystem {
                     .min_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
 
                 if let Some(max_current) = min_max_current {
-                    if current > max_current {
+                    if current.abs() > max_current {
                         // Short circuit detected! Limit current to max or cut it off entirely
                         // For realistic behavior, we'll cut the current to simulate a blown fuse/breaker
                         is_short_circuit = true;
@@ -786,12 +1566,55 @@ impl ElectricalSystem {
 
                 // Additional check: if resistance is extremely low (< 0.1 ohms) and current is very high
                 // This catches cases where max_current might not be set properly
-                if total_resistance < 0.1 && current > 100.0 {
+                if total_resistance < 0.1 && current.abs() > 100.0 {
                     is_short_circuit = true;
                     current = 0.0;
                 }
             }
 
+            if is_short_circuit {
+                for element in &network.elements {
+                    if element.params.max_current_amps.is_some_and(|max| demanded.abs() > max) {
+                        let key = AttachmentKey {
+                            pos: element.position,
+                            face: element.face,
+                        };
+                        self.overloaded.insert(key, demanded.abs());
+                    }
+                }
+            }
+            if has_loop && !leaking && !is_short_circuit && !plates.is_empty() {
+                // Capacitors in series charge as one whose capacitance is the reciprocal sum.
+                // Stepping the RC decay exactly keeps loops with almost no resistance stable.
+                let series_capacitance =
+                    1.0 / plates.values().map(|(capacitance, _)| 1.0 / capacitance).sum::<f32>();
+                if limited {
+                    // Held at the limit, the plates charge at a steady rate instead.
+                    let full = series_capacitance * driving;
+                    let moved_charge = (current * dt_seconds).clamp(-full.abs(), full.abs());
+                    for (key, (capacitance, volts)) in plates.iter_mut() {
+                        *volts += moved_charge / *capacitance;
+                        charge_updates.push((*key, *volts));
+                    }
+                    still_charging = true;
+                } else {
+                    let time_constant = effective_resistance * series_capacitance;
+                    let mut remaining = driving * (-dt_seconds / time_constant).exp();
+                    if remaining.abs() < CAPACITOR_SETTLED_VOLTS {
+                        remaining = 0.0;
+                    } else {
+                        still_charging = true;
+                    }
+                    let moved_charge = series_capacitance * (driving - remaining);
+                    for (key, (capacitance, volts)) in plates.iter_mut() {
+                        *volts += moved_charge / *capacitance;
+                        charge_updates.push((*key, *volts));
+                    }
+                    current = remaining / effective_resistance;
+                }
+            }
+            self.delivered_watts += source_voltage * current;
+
             // Calculate ground-relative voltages for components in this network
             // We'll trace through the circuit starting from ground (0V) and accumulate voltage changes
             let mut node_voltages: std::collections::HashMap<AttachmentKey, f32> = std::collections::HashMap::new();
@@ -832,6 +1655,17 @@ impl ElectricalSystem {
                         // Assuming negative terminal is connected towards ground
                         voltage_acc = source_voltage;
                         node_voltages.insert(key, voltage_acc);
+                    } else if element.component == ElectricalComponent::Led {
+                        node_voltages.insert(key, voltage_acc);
+                        voltage_acc -= led_drop(element.params, current);
+                    } else if let Some(&(direction, on)) = diode_states.get(&key) {
+                        node_voltages.insert(key, voltage_acc);
+                        let forward = diode_drop_across(element.params, direction * current, on);
+                        voltage_acc -= direction * forward;
+                    } else if let Some((_, volts)) = plates.get(&key) {
+                        node_voltages.insert(key, voltage_acc);
+                        let series = element.params.resistance_ohms.unwrap_or(0.0);
+                        voltage_acc -= volts + current * series;
                     } else if let Some(resistance) = element.params.resistance_ohms {
                         // Resistor/wire: voltage drops by I*R
                         // The positive terminal voltage depends on circuit position
@@ -851,9 +1685,16 @@ impl ElectricalSystem {
                     face: element.face,
                 };
 
-                let voltage_local = if is_short_circuit {
+                let voltage_local = if let Some((_, volts)) = plates.get(&key) {
+                    // Plates keep their charge even while a breaker holds the current at 0.
+                    volts + current * element.params.resistance_ohms.unwrap_or(0.0)
+                } else if is_short_circuit {
                     // In a short circuit, voltage drops to near zero
                     0.0
+                } else if element.component == ElectricalComponent::Led {
+                    led_drop(element.params, current)
+                } else if let Some(&(direction, on)) = diode_states.get(&key) {
+                    diode_drop_across(element.params, direction * current, on)
                 } else if element.component == ElectricalComponent::VoltageSource {
                     // Voltage source shows its source voltage
                     source_voltage
@@ -865,6 +1706,10 @@ impl ElectricalSystem {
                 };
 
                 let voltage_ground = node_voltages.get(&key).copied().unwrap_or(0.0);
+                // A diode reports the current through it in its own forward direction.
+                let current = diode_states
+                    .get(&key)
+                    .map_or(current, |&(direction, _)| direction * current);
 
                 telemetry_updates.push((key, ComponentTelemetry {
                     current,
@@ -879,9 +1724,106 @@ impl ElectricalSystem {
                 node.telemetry = telemetry;
             }
         }
+        for (key, volts) in charge_updates {
+            if let Some(node) = self.node_mut(key) {
+                node.charge_volts = volts;
+            }
+        }
+        self.charging = still_charging;
+
+        for (&pos, faces) in &self.nodes {
+            for (face, node) in faces.iter() {
+                if node.component != ElectricalComponent::Led {
+                    continue;
+                }
+                let before = previous_levels.get(&AttachmentKey { pos, face }).copied();
+                if before != Some(led_level(node.telemetry.current)) {
+                    self.relit_leds.insert(pos);
+                }
+            }
+        }
     }
 }
 
+/// Picks which diodes conduct by iterating a piecewise-linear model: a conducting diode is its
+/// forward drop plus on-resistance, a blocking one is `DIODE_BLOCKING_OHMS`. The loop is
+/// re-solved until every state agrees with the current it produces. `diodes` carries each
+/// diode's direction from `diode_direction`; `resistance` already counts their on-resistance.
+fn settle_diodes(
+    diodes: &[(AttachmentKey, f32, ComponentParams)],
+    driving: f32,
+    resistance: f32,
+) -> Vec<bool> {
+    let mut conducting = vec![true; diodes.len()];
+    for _ in 0..DIODE_SETTLE_PASSES {
+        let (drop, blocking_ohms) = diode_loop_terms(diodes, &conducting);
+        let current = (driving - drop) / (resistance + blocking_ohms).max(0.01);
+        let mut settled = true;
+        for (&(_, direction, params), on) in diodes.iter().zip(conducting.iter_mut()) {
+            let forward_current = direction * current;
+            let next = if *on {
+                forward_current > 0.0
+            } else {
+                forward_current * DIODE_BLOCKING_OHMS > params.voltage_volts.unwrap_or(0.0)
+            };
+            settled &= next == *on;
+            *on = next;
+        }
+        if settled {
+            break;
+        }
+    }
+    conducting
+}
+
+/// The loop-direction voltage the diodes drop while conducting, and the resistance the
+/// blocking ones add on top of the on-resistance already in the loop total.
+fn diode_loop_terms(
+    diodes: &[(AttachmentKey, f32, ComponentParams)],
+    conducting: &[bool],
+) -> (f32, f32) {
+    let mut drop = 0.0;
+    let mut blocking_ohms = 0.0;
+    for (&(_, direction, params), &on) in diodes.iter().zip(conducting) {
+        if on {
+            drop += direction * params.voltage_volts.unwrap_or(0.0);
+        } else {
+            blocking_ohms += DIODE_BLOCKING_OHMS - params.resistance_ohms.unwrap_or(0.0);
+        }
+    }
+    (drop, blocking_ohms)
+}
+
+/// Voltage across a diode in its forward direction while `forward_current` flows through it.
+fn diode_drop_across(params: ComponentParams, forward_current: f32, conducting: bool) -> f32 {
+    if conducting {
+        let on_resistance = params.resistance_ohms.unwrap_or(0.0);
+        params.voltage_volts.unwrap_or(0.0) + forward_current * on_resistance
+    } else {
+        forward_current * DIODE_BLOCKING_OHMS
+    }
+}
+
+/// Voltage across a conducting LED: its forward drop plus the series resistance.
+fn led_drop(params: ComponentParams, current: f32) -> f32 {
+    if current > 0.0 {
+        params.voltage_volts.unwrap_or(0.0) + current * params.resistance_ohms.unwrap_or(0.0)
+    } else {
+        0.0
+    }
+}
+
+fn nearest_to(from: BlockPos3, positions: impl Iterator<Item = BlockPos3>) -> Option<BlockPos3> {
+    positions.min_by_key(|pos| {
+        let (dx, dy, dz) = (
+            (pos.x - from.x) as i64,
+            (pos.y - from.y) as i64,
+            (pos.z - from.z) as i64,
+        );
+        (dx * dx + dy * dy + dz * dz, pos.x, pos.y, pos.z)
+    })
+}
+
 fn axis_pair_connectors(axis: Axis) -> [bool; 6] {
     let mut connectors = [false; 6];
     let (a, b) = axis.pair_indices();
@@ -894,7 +1836,12 @@ fn preferred_axes(component: ElectricalComponent) -> [Axis; 3] {
     match component {
         ElectricalComponent::Wire
         | ElectricalComponent::Resistor
-        | ElectricalComponent::VoltageSource => [Axis::X, Axis::Z, Axis::Y],
+        | ElectricalComponent::VoltageSource
+        | ElectricalComponent::Capacitor
+        | ElectricalComponent::Switch
+        | ElectricalComponent::Led
+        | ElectricalComponent::Diode
+        | ElectricalComponent::Potentiometer => [Axis::X, Axis::Z, Axis::Y],
         ElectricalComponent::Ground => [Axis::Y, Axis::X, Axis::Z],
     }
 }
@@ -953,3 +1900,350 @@ fn opposite_index(idx: usize) -> usize {
         _ => unreachable!(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn telemetry_snapshot_reports_series_circuit_current() {
+        let mut system = ElectricalSystem::new();
+        let chunk = ChunkPos { x: 0, z: 0 };
+        system.update_block(chunk, (2, 64, 2), BlockType::VoltageSource);
+        system.update_block(chunk, (3, 64, 2), BlockType::Resistor);
+        system.update_block(chunk, (4, 64, 2), BlockType::Ground);
+        system.tick(0.0);
+
+        let snapshot = system.telemetry_snapshot();
+        let components: Vec<_> = snapshot.iter().map(|entry| entry.2).collect();
+        assert_eq!(
+            components,
+            vec![
+                ElectricalComponent::VoltageSource,
+                ElectricalComponent::Resistor,
+                ElectricalComponent::Ground,
+            ]
+        );
+
+        let source = ElectricalComponent::VoltageSource.default_params();
+        let resistor = ElectricalComponent::Resistor.default_params();
+        let expected = source.voltage_volts.unwrap()
+            / (source.resistance_ohms.unwrap() + resistor.resistance_ohms.unwrap());
+        let (pos, face, _, telemetry) = snapshot[1];
+        assert_eq!(pos, BlockPos3::new(3, 64, 2));
+        assert_eq!(face, BlockFace::Top);
+        assert!(
+            (telemetry.current - expected).abs() < 1e-3,
+            "expected {expected} A through the resistor, got {}",
+            telemetry.current
+        );
+    }
+
+    #[test]
+    fn capacitor_charges_toward_the_source_then_blocks_current() {
+        let mut system = ElectricalSystem::new();
+        let chunk = ChunkPos { x: 0, z: 0 };
+        system.update_block(chunk, (2, 64, 2), BlockType::VoltageSource);
+        system.update_block(chunk, (3, 64, 2), BlockType::Resistor);
+        system.update_block(chunk, (4, 64, 2), BlockType::Capacitor);
+        system.update_block(chunk, (5, 64, 2), BlockType::Ground);
+        let capacitor = BlockPos3::new(4, 64, 2);
+        let source_volts = ElectricalComponent::VoltageSource
+            .default_params()
+            .voltage_volts
+            .unwrap();
+
+        let step = 1.0 / 20.0;
+        let mut readings = Vec::new();
+        for tick in 1..=400 {
+            system.tick(step);
+            if tick % 20 == 0 {
+                readings.push(system.telemetry_at(capacitor, BlockFace::Top).unwrap());
+            }
+        }
+        let plates = |second: usize| readings[second - 1].voltage_local;
+        assert!(plates(1) > 0.5 && plates(1) < source_volts * 0.6, "{}", plates(1));
+        assert!(plates(2) > plates(1) && plates(4) > plates(2));
+        assert!(plates(4) < source_volts - 0.1, "charged too fast: {}", plates(4));
+        assert!((plates(20) - source_volts).abs() < 0.01, "{}", plates(20));
+
+        let charged = readings.last().unwrap();
+        assert_eq!(charged.current, 0.0, "a charged capacitor blocks DC");
+        let rebuilds = system.rebuild_count();
+        system.tick(step);
+        assert_eq!(system.rebuild_count(), rebuilds);
+        assert_eq!(system.telemetry_at(capacitor, BlockFace::Top).unwrap(), *charged);
+    }
+
+    #[test]
+    fn charged_capacitor_discharges_once_the_source_is_removed() {
+        let mut system = ElectricalSystem::new();
+        let chunk = ChunkPos { x: 0, z: 0 };
+        system.update_block(chunk, (2, 64, 2), BlockType::VoltageSource);
+        system.update_block(chunk, (3, 64, 2), BlockType::Resistor);
+        system.update_block(chunk, (4, 64, 2), BlockType::Capacitor);
+        system.update_block(chunk, (5, 64, 2), BlockType::Ground);
+        for _ in 0..400 {
+            system.tick(1.0 / 20.0);
+        }
+
+        // Swap the source for a plain wire so the capacitor drains back through the loop.
+        system.update_block(chunk, (2, 64, 2), BlockType::CopperWire);
+        system.tick(1.0 / 20.0);
+        let capacitor = BlockPos3::new(4, 64, 2);
+        let telemetry = system.telemetry_at(capacitor, BlockFace::Top).unwrap();
+        assert!(telemetry.current < 0.0, "expected a discharge, got {}", telemetry.current);
+        for _ in 0..400 {
+            system.tick(1.0 / 20.0);
+        }
+        assert!(system.charge_at(capacitor, BlockFace::Top).unwrap().abs() < 0.01);
+    }
+
+    #[test]
+    fn opening_a_switch_stops_the_loop_current() {
+        let mut system = ElectricalSystem::new();
+        let chunk = ChunkPos { x: 0, z: 0 };
+        system.update_block(chunk, (2, 64, 2), BlockType::VoltageSource);
+        system.update_block(chunk, (3, 64, 2), BlockType::Switch);
+        system.update_block(chunk, (4, 64, 2), BlockType::Resistor);
+        system.update_block(chunk, (5, 64, 2), BlockType::Ground);
+        let switch = BlockPos3::new(3, 64, 2);
+        let resistor = BlockPos3::new(4, 64, 2);
+        system.tick(0.0);
+        assert_eq!(system.switch_closed(switch, BlockFace::Top), Some(false));
+        assert_eq!(system.telemetry_at(resistor, BlockFace::Top).unwrap().current, 0.0);
+
+        assert_eq!(system.toggle_switch(switch, BlockFace::Top), Some(true));
+        system.tick(0.0);
+        let closed = system.telemetry_at(resistor, BlockFace::Top).unwrap().current;
+        assert!(closed > 0.1, "expected current through the closed switch, got {closed}");
+
+        assert_eq!(system.toggle_switch(switch, BlockFace::Top), Some(false));
+        system.tick(0.0);
+        assert_eq!(system.telemetry_at(resistor, BlockFace::Top).unwrap().current, 0.0);
+        assert_eq!(system.toggle_switch(resistor, BlockFace::Top), None);
+    }
+
+    #[test]
+    fn shorted_source_reports_overcurrent_until_it_current_limits() {
+        let mut system = ElectricalSystem::new();
+        let chunk = ChunkPos { x: 0, z: 0 };
+        system.update_block(chunk, (2, 64, 2), BlockType::VoltageSource);
+        system.update_block(chunk, (3, 64, 2), BlockType::CopperWire);
+        system.update_block(chunk, (4, 64, 2), BlockType::Ground);
+        system.tick(0.0);
+
+        let source = BlockPos3::new(2, 64, 2);
+        let wire = BlockPos3::new(3, 64, 2);
+        assert_eq!(
+            system.overcurrent_nodes(),
+            vec![(source, BlockFace::Top), (wire, BlockFace::Top)]
+        );
+        let rated = system.params_at(source, BlockFace::Top).unwrap().max_current_amps.unwrap();
+        assert!(system.overcurrent_at(source, BlockFace::Top).unwrap() > rated);
+        assert_eq!(system.overcurrent_at(BlockPos3::new(4, 64, 2), BlockFace::Top), None);
+        assert_eq!(system.telemetry_at(wire, BlockFace::Top).unwrap().current, 0.0);
+
+        let mut params = system.params_at(source, BlockFace::Top).unwrap();
+        params.current_limit = true;
+        system.set_params(source, BlockFace::Top, params);
+        system.tick(0.0);
+        assert!(system.overcurrent_nodes().is_empty());
+        let held = system.telemetry_at(wire, BlockFace::Top).unwrap().current;
+        assert!((held - rated).abs() < 1e-4, "expected the source to hold {rated} A, got {held}");
+    }
+
+    #[test]
+    fn diode_conducts_forward_and_blocks_reversed() {
+        let mut system = ElectricalSystem::new();
+        let chunk = ChunkPos { x: 0, z: 0 };
+        // Reversed: the source's positive terminal feeds the diode's negative face.
+        system.update_block(chunk, (2, 64, 8), BlockType::VoltageSource);
+        system.update_block(chunk, (3, 64, 8), BlockType::Diode);
+        system.update_block(chunk, (4, 64, 8), BlockType::Resistor);
+        system.update_block(chunk, (5, 64, 8), BlockType::Ground);
+        // Forward: the loop doubles back so the current arrives at the diode's positive face.
+        system.update_block(chunk, (2, 64, 2), BlockType::VoltageSource);
+        system.update_block(chunk, (3, 64, 2), BlockType::CopperWire);
+        system.update_block(chunk, (3, 64, 3), BlockType::Resistor);
+        system.update_block(chunk, (3, 64, 4), BlockType::CopperWire);
+        system.update_block(chunk, (2, 64, 4), BlockType::Diode);
+        system.update_block(chunk, (1, 64, 4), BlockType::Ground);
+        system.tick(0.0);
+
+        let forward = system.telemetry_at(BlockPos3::new(2, 64, 4), BlockFace::Top).unwrap();
+        let resistor = system.telemetry_at(BlockPos3::new(3, 64, 3), BlockFace::Top).unwrap();
+        let drop = ElectricalComponent::Diode.default_params().voltage_volts.unwrap();
+        assert!(forward.current > 0.1, "forward diode should conduct, got {}", forward.current);
+        assert!((forward.current - resistor.current).abs() < 1e-6);
+        assert!(forward.voltage_local >= drop);
+
+        let reversed = system.telemetry_at(BlockPos3::new(3, 64, 8), BlockFace::Top).unwrap();
+        assert!(reversed.current.abs() < 1e-6, "reversed diode leaked {}", reversed.current);
+        assert!(reversed.current <= 0.0);
+        let load = system.telemetry_at(BlockPos3::new(4, 64, 8), BlockFace::Top).unwrap();
+        assert!(load.current.abs() < 1e-6);
+    }
+
+    #[test]
+    fn turning_a_potentiometer_down_raises_the_loop_current() {
+        let mut system = ElectricalSystem::new();
+        let chunk = ChunkPos { x: 0, z: 0 };
+        system.update_block(chunk, (2, 64, 2), BlockType::VoltageSource);
+        system.update_block(chunk, (3, 64, 2), BlockType::Potentiometer);
+        system.update_block(chunk, (4, 64, 2), BlockType::Ground);
+        let pot = BlockPos3::new(3, 64, 2);
+        let current_at = |system: &mut ElectricalSystem, wiper: f32| {
+            system.set_params(pot, BlockFace::Top, ComponentParams::potentiometer(wiper, 2.0));
+            system.tick(0.0);
+            system.telemetry_at(pot, BlockFace::Top).unwrap().current
+        };
+
+        let high = current_at(&mut system, 0.8);
+        let low = current_at(&mut system, 0.2);
+        assert!(low > high * 3.0, "expected {low} to clearly exceed {high}");
+        let params = system.params_at(pot, BlockFace::Top).unwrap();
+        assert!((params.wiper() - 0.2).abs() < 1e-4);
+        assert_eq!(ComponentParams::potentiometer(1.5, 2.0).wiper(), 1.0);
+    }
+
+    #[test]
+    fn led_conducts_only_above_its_forward_drop() {
+        let mut system = ElectricalSystem::new();
+        let chunk = ChunkPos { x: 0, z: 0 };
+        system.update_block(chunk, (2, 64, 2), BlockType::VoltageSource);
+        system.update_block(chunk, (3, 64, 2), BlockType::Led);
+        system.update_block(chunk, (4, 64, 2), BlockType::Resistor);
+        system.update_block(chunk, (5, 64, 2), BlockType::Ground);
+        let source = BlockPos3::new(2, 64, 2);
+        let led = BlockPos3::new(3, 64, 2);
+        let forward = ElectricalComponent::Led.default_params().voltage_volts.unwrap();
+        let current_at = |system: &mut ElectricalSystem, volts: f32| {
+            let params = ComponentParams::voltage_source(volts, 0.1, 10.0);
+            system.set_params(source, BlockFace::Top, params);
+            system.tick(0.0);
+            system.telemetry_at(led, BlockFace::Top).unwrap()
+        };
+
+        let dark = current_at(&mut system, forward * 0.75);
+        assert_eq!((dark.current, dark.voltage_local), (0.0, 0.0));
+        assert_eq!(led_level(dark.current), 0);
+        let dim = current_at(&mut system, 6.0);
+        let bright = current_at(&mut system, 24.0);
+        assert!(dim.current > 0.0 && bright.current > dim.current);
+        assert!(bright.voltage_local > forward);
+        assert!(led_level(bright.current) > led_level(dim.current));
+        assert!(system.take_relit_leds().contains(&led));
+        assert!(system.take_relit_leds().is_empty());
+    }
+
+    #[test]
+    fn summary_counts_circuits_power_and_tripped_components() {
+        let mut system = ElectricalSystem::new();
+        let chunk = ChunkPos { x: 0, z: 0 };
+        system.update_block(chunk, (2, 64, 2), BlockType::VoltageSource);
+        system.update_block(chunk, (3, 64, 2), BlockType::Resistor);
+        system.update_block(chunk, (4, 64, 2), BlockType::Ground);
+        // A bare wire across a source draws far more than either part is rated for.
+        system.update_block(chunk, (2, 64, 8), BlockType::VoltageSource);
+        system.update_block(chunk, (3, 64, 8), BlockType::CopperWire);
+        system.update_block(chunk, (4, 64, 8), BlockType::Ground);
+        system.tick(0.0);
+
+        let summary = system.summary();
+        assert_eq!(
+            (summary.wires, summary.resistors, summary.sources, summary.grounds),
+            (1, 1, 2, 2)
+        );
+        assert_eq!(summary.circuits, 2);
+        assert_eq!(summary.overloaded, 2);
+        let source = ElectricalComponent::VoltageSource.default_params();
+        let resistor = ElectricalComponent::Resistor.default_params();
+        let volts = source.voltage_volts.unwrap();
+        let ohms = source.resistance_ohms.unwrap() + resistor.resistance_ohms.unwrap();
+        let expected = volts * volts / ohms;
+        assert!((summary.power_watts - expected).abs() < 1e-3, "{}", summary.power_watts);
+
+        let from = BlockPos3::new(0, 64, 8);
+        assert_eq!(system.nearest_overloaded(from), Some(BlockPos3::new(2, 64, 8)));
+    }
+
+    #[test]
+    fn wetting_a_live_circuit_draws_more_source_current() {
+        let mut system = ElectricalSystem::new();
+        let chunk = ChunkPos { x: 0, z: 0 };
+        system.update_block(chunk, (2, 64, 2), BlockType::VoltageSource);
+        system.update_block(chunk, (3, 64, 2), BlockType::Resistor);
+        system.update_block(chunk, (4, 64, 2), BlockType::Ground);
+        // Rated high enough that the leak shows up as current rather than a trip.
+        let resistor = BlockPos3::new(3, 64, 2);
+        system.set_params(resistor, BlockFace::Top, ComponentParams::resistor(100.0, 8.0));
+        system.set_water_leakage(true);
+        system.tick(0.0);
+        let source = BlockPos3::new(2, 64, 2);
+        let dry = system.telemetry_at(source, BlockFace::Top).unwrap().current;
+
+        // Water sitting on top of the resistor, where its mount face opens.
+        let puddle = HashSet::from([BlockPos3::new(3, 65, 2)]);
+        assert!(system.exposed_cells().contains(&BlockPos3::new(3, 65, 2)));
+        system.set_wet_cells(&puddle);
+        system.tick(0.0);
+        assert!(system.is_wet(resistor, BlockFace::Top));
+        let wet = system.telemetry_at(source, BlockFace::Top).unwrap().current;
+        assert!(wet > dry * 5.0, "dry {dry} A, wet {wet} A");
+
+        // At its stock 2 A rating the resistor trips instead.
+        system.set_params(resistor, BlockFace::Top, ComponentParams::resistor(100.0, 2.0));
+        system.tick(0.0);
+        assert_eq!(system.summary().overloaded, 1);
+
+        // Turning the feature off dries everything out again.
+        system.set_water_leakage(false);
+        system.tick(0.0);
+        assert!(!system.is_wet(resistor, BlockFace::Top));
+        let restored = system.telemetry_at(source, BlockFace::Top).unwrap().current;
+        assert!((restored - dry).abs() < 1e-4);
+    }
+
+    #[test]
+    fn nearest_component_picks_the_closest_matching_block() {
+        let mut system = ElectricalSystem::new();
+        let chunk = ChunkPos { x: 0, z: 0 };
+        system.update_block(chunk, (2, 64, 2), BlockType::VoltageSource);
+        system.update_block(chunk, (12, 64, 12), BlockType::VoltageSource);
+        system.update_block(chunk, (8, 64, 8), BlockType::Resistor);
+
+        let source = ElectricalComponent::from_name("SOURCE").unwrap();
+        let from = BlockPos3::new(10, 64, 10);
+        assert_eq!(system.nearest_component(from, source), Some(BlockPos3::new(12, 64, 12)));
+        assert_eq!(system.nearest_component(from, ElectricalComponent::Ground), None);
+    }
+
+    #[test]
+    fn preview_connection_mask_matches_placed_component() {
+        let mut system = ElectricalSystem::new();
+        let chunk = ChunkPos { x: 0, z: 0 };
+        system.update_block(chunk, (2, 64, 2), BlockType::CopperWire);
+
+        let target = BlockPos3::new(3, 64, 2);
+        let axis = ElectricalComponent::Wire.default_axis();
+        let preview = system.preview_connection_mask(
+            target,
+            BlockFace::Top,
+            ElectricalComponent::Wire,
+            axis,
+        );
+        assert!(preview.iter().any(|&connected| connected));
+
+        let isolated = system.preview_connection_mask(
+            BlockPos3::new(9, 64, 9),
+            BlockFace::Top,
+            ElectricalComponent::Wire,
+            axis,
+        );
+        assert_eq!(isolated, [false; 6]);
+
+        system.update_block(chunk, (3, 64, 2), BlockType::CopperWire);
+        assert_eq!(system.connection_mask(target, BlockFace::Top), Some(preview));
+    }
+}
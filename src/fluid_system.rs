@@ -23,6 +23,8 @@ const GPU_COOLDOWN_MS: u64 = 80;
 const CPU_FALLBACK_COOLDOWN_MS: u64 = 16;
 const TILE_PADDING: usize = 1;
 const PADDED_TILE_EDGE: usize = TILE_EDGE_CHUNKS + TILE_PADDING * 2;
+/// Upper bound on debug markers so a flooded region can't swamp the line buffer.
+const MAX_DEBUG_CELLS: usize = 2048;
 
 enum WorkerCommand {
     Run(TileInput),
@@ -31,6 +33,78 @@ enum WorkerCommand {
 
 type WorkerResponse = Result<TileOutput>;
 
+/// A fluid cell the last water update touched, reported for the debug overlay.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ActiveFluidCell {
+    pub pos: (i32, i32, i32),
+    /// True if the amount changed; false if it only sat in a chunk still scheduled to simulate.
+    pub changed: bool,
+}
+
+/// Records which cells around a point change across one water update.
+struct FluidDebugCapture {
+    center: (i32, i32, i32),
+    radius: i32,
+    before: Vec<u8>,
+    cells: Vec<ActiveFluidCell>,
+}
+
+impl FluidDebugCapture {
+    fn new(center: (i32, i32, i32), radius: i32) -> Self {
+        Self {
+            center,
+            radius: radius.max(0),
+            before: Vec::new(),
+            cells: Vec::new(),
+        }
+    }
+
+    fn region(&self, height: usize) -> impl Iterator<Item = (i32, i32, i32)> {
+        let (cx, cy, cz) = self.center;
+        let r = self.radius;
+        let y_range = (cy - r).max(0)..=(cy + r).min(height as i32 - 1);
+        y_range.flat_map(move |y| {
+            (cz - r..=cz + r).flat_map(move |z| (cx - r..=cx + r).map(move |x| (x, y, z)))
+        })
+    }
+
+    fn begin(&mut self, world: &World) {
+        self.before.clear();
+        for (x, y, z) in self.region(world.height()) {
+            self.before.push(world.get_fluid_amount(x, y, z));
+        }
+    }
+
+    fn finish(&mut self, world: &World) {
+        let active_chunks: HashSet<ChunkPos> =
+            world.active_fluid_chunks_snapshot().into_iter().collect();
+        let mut changed = Vec::new();
+        let mut scheduled = Vec::new();
+        for (pos, &before) in self.region(world.height()).zip(self.before.iter()) {
+            let (x, y, z) = pos;
+            let after = world.get_fluid_amount(x, y, z);
+            if after != before {
+                changed.push(ActiveFluidCell { pos, changed: true });
+                continue;
+            }
+            let chunk = ChunkPos {
+                x: x.div_euclid(CHUNK_SIZE as i32),
+                z: z.div_euclid(CHUNK_SIZE as i32),
+            };
+            if after > 0 && active_chunks.contains(&chunk) {
+                scheduled.push(ActiveFluidCell {
+                    pos,
+                    changed: false,
+                });
+            }
+        }
+        // Changed cells are the interesting ones, so they claim the marker budget first.
+        changed.append(&mut scheduled);
+        changed.truncate(MAX_DEBUG_CELLS);
+        self.cells = changed;
+    }
+}
+
 pub struct FluidSystem {
     sender: Option<Sender<WorkerCommand>>,
     result_receiver: Receiver<WorkerResponse>,
@@ -40,6 +114,7 @@ pub struct FluidSystem {
     gpu_overloaded_until: Instant,
     npu_available: bool,
     fallback_ready_at: Option<Instant>,
+    debug_capture: Option<FluidDebugCapture>,
 }
 
 impl FluidSystem {
@@ -87,13 +162,53 @@ impl FluidSystem {
             gpu_overloaded_until: Instant::now(),
             npu_available: npu::is_available(),
             fallback_ready_at: None,
+            debug_capture: None,
         }
     }
 
-    pub fn pump(&mut self, world: &World) {
-        if self.sender.is_none() {
-            return;
+    /// Starts or stops recording active cells within `radius` blocks of `center`.
+    pub fn set_debug_region(&mut self, region: Option<((i32, i32, i32), i32)>) {
+        self.debug_capture = region.map(|(center, radius)| {
+            let mut capture = FluidDebugCapture::new(center, radius);
+            if let Some(previous) = self.debug_capture.take() {
+                if previous.center == center && previous.radius == capture.radius {
+                    capture.cells = previous.cells;
+                }
+            }
+            capture
+        });
+    }
+
+    /// Snapshots the debug region before a water update; a no-op while the overlay is off.
+    pub fn begin_debug_capture(&mut self, world: &World) {
+        if let Some(capture) = self.debug_capture.as_mut() {
+            capture.begin(world);
+        }
+    }
+
+    pub fn finish_debug_capture(&mut self, world: &World) {
+        if let Some(capture) = self.debug_capture.as_mut() {
+            capture.finish(world);
+        }
+    }
+
+    /// Cells changed or still scheduled during the last captured water update.
+    pub fn active_cells(&self) -> &[ActiveFluidCell] {
+        self.debug_capture
+            .as_ref()
+            .map_or(&[], |capture| capture.cells.as_slice())
+    }
+
+    /// Sends tiles of active fluid chunks to the GPU worker. Returns whether refilling
+    /// infinite sources or draining into sponges beforehand changed the world.
+    pub fn pump(&mut self, world: &mut World) -> bool {
+        // While overloaded `fallback_step` does this tick's work instead.
+        if self.sender.is_none() || requires_cpu(world) || self.is_overloaded() {
+            return false;
         }
+        // Sources refill and sponges drink before the tiles are copied out, so the kernel
+        // sees the pool as it stands this tick.
+        let refilled = world.regenerate_water_sources() | world.absorb_into_sponges();
 
         while self.pending_tiles.len() < MAX_IN_FLIGHT {
             if self.is_overloaded() {
@@ -130,6 +245,7 @@ impl FluidSystem {
                 break;
             }
         }
+        refilled
     }
 
     pub fn poll_results(&mut self, world: &mut World) -> bool {
@@ -230,7 +346,8 @@ impl FluidSystem {
     }
 
     pub fn fallback_step(&mut self, world: &mut World) -> bool {
-        if !self.is_overloaded() {
+        let cpu_only = requires_cpu(world);
+        if !self.is_overloaded() && !cpu_only {
             return false;
         }
 
@@ -241,11 +358,13 @@ impl FluidSystem {
             }
         }
 
-        let changed = if self.npu_available {
+        let refilled = world.regenerate_water_sources() | world.absorb_into_sponges();
+        let stepped = if self.npu_available && !cpu_only {
             npu::process_world(world)
         } else {
             world.step_fluids()
         };
+        let changed = refilled || stepped;
 
         self.fallback_ready_at = Some(now + Duration::from_millis(CPU_FALLBACK_COOLDOWN_MS));
 
@@ -384,6 +503,45 @@ impl Drop for FluidSystem {
     }
 }
 
+/// The GPU kernel doesn't track spread distance and its tiles are sized for default-height
+/// chunks, so spread-limited or taller worlds step on the CPU.
+fn requires_cpu(world: &World) -> bool {
+    world.fluid_spread_limit().is_some() || world.height() != CHUNK_HEIGHT
+}
+
 fn index_3d(x: usize, y: usize, z: usize, width: usize, depth: usize) -> usize {
     x + width * (z + depth * y)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{
+        WorldGenConfig, DEFAULT_SUPERFLAT_LAYERS, MAX_FLUID_LEVEL, TEST_SEED,
+    };
+
+    #[test]
+    fn debug_capture_reports_falling_water_as_changed() {
+        let mut world = World::with_seed_and_config(TEST_SEED, WorldGenConfig {
+            superflat: Some(DEFAULT_SUPERFLAT_LAYERS.to_vec()),
+            ..WorldGenConfig::default()
+        });
+        let source = (8, 120, 8);
+        world.set_fluid_amount(source.0, source.1, source.2, MAX_FLUID_LEVEL);
+
+        let mut capture = FluidDebugCapture::new(source, 2);
+        capture.begin(&world);
+        world.step_fluids();
+        capture.finish(&world);
+
+        let below = (source.0, source.1 - 1, source.2);
+        assert!(capture.cells.contains(&ActiveFluidCell {
+            pos: below,
+            changed: true
+        }));
+        assert!(capture
+            .cells
+            .iter()
+            .all(|cell| (cell.pos.0 - source.0).abs() <= 2 && (cell.pos.2 - source.2).abs() <= 2));
+    }
+}
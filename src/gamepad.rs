@@ -0,0 +1,296 @@
+//! Controller input.
+//!
+//! `Gamepad` folds controller events into stick positions and a queue of button presses
+//! and releases that `State` drains once per frame. Built with the `gamepad` feature the
+//! events come from `gilrs`; without it no controller is ever seen, so the sticks stay
+//! centred and nothing is pressed.
+
+/// Stick deflection below this reads as centred, so worn sticks don't drift the camera.
+pub const STICK_DEAD_ZONE: f32 = 0.2;
+/// Analog triggers count as pressed past this pull.
+#[cfg_attr(not(feature = "gamepad"), allow(dead_code))]
+pub const TRIGGER_THRESHOLD: f32 = 0.5;
+/// Radians per second the right stick turns the view at full tilt and sensitivity 1.
+pub const STICK_LOOK_SPEED: f32 = 3.0;
+/// Range of the right-stick sensitivity setting.
+pub const STICK_SENSITIVITY_MIN: f32 = 0.2;
+pub const STICK_SENSITIVITY_MAX: f32 = 3.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PadButton {
+    South,
+    East,
+    North,
+    Start,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+impl PadButton {
+    const ALL: [PadButton; 12] = [
+        PadButton::South,
+        PadButton::East,
+        PadButton::North,
+        PadButton::Start,
+        PadButton::LeftBumper,
+        PadButton::RightBumper,
+        PadButton::LeftTrigger,
+        PadButton::RightTrigger,
+        PadButton::DPadUp,
+        PadButton::DPadDown,
+        PadButton::DPadLeft,
+        PadButton::DPadRight,
+    ];
+
+    fn index(self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|button| *button == self)
+            .unwrap_or(0)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(not(feature = "gamepad"), allow(dead_code))]
+pub enum PadAxis {
+    LeftX,
+    LeftY,
+    RightX,
+    RightY,
+}
+
+/// Scales a stick reading so the dead zone reads as zero and the rest of the travel ramps
+/// smoothly from zero to full, instead of jumping to the dead-zone edge.
+pub fn apply_dead_zone(x: f32, y: f32) -> (f32, f32) {
+    let magnitude = (x * x + y * y).sqrt();
+    if magnitude <= STICK_DEAD_ZONE {
+        return (0.0, 0.0);
+    }
+    let scaled = ((magnitude - STICK_DEAD_ZONE) / (1.0 - STICK_DEAD_ZONE)).min(1.0);
+    let factor = scaled / magnitude;
+    (x * factor, y * factor)
+}
+
+/// Latest controller readings, independent of where they came from.
+#[derive(Debug, Default)]
+pub struct PadState {
+    /// Raw stick values, Y pointing up.
+    axes: [f32; 4],
+    held: [bool; PadButton::ALL.len()],
+    events: Vec<(PadButton, bool)>,
+}
+
+// Without the `gamepad` feature only the tests feed a `PadState`.
+#[cfg_attr(not(feature = "gamepad"), allow(dead_code))]
+impl PadState {
+    pub fn set_axis(&mut self, axis: PadAxis, value: f32) {
+        let index = match axis {
+            PadAxis::LeftX => 0,
+            PadAxis::LeftY => 1,
+            PadAxis::RightX => 2,
+            PadAxis::RightY => 3,
+        };
+        self.axes[index] = value.clamp(-1.0, 1.0);
+    }
+
+    pub fn set_button(&mut self, button: PadButton, pressed: bool) {
+        let held = &mut self.held[button.index()];
+        if *held != pressed {
+            *held = pressed;
+            self.events.push((button, pressed));
+        }
+    }
+
+    /// Analog triggers report a pull instead of a press.
+    pub fn set_trigger(&mut self, button: PadButton, value: f32) {
+        self.set_button(button, value >= TRIGGER_THRESHOLD);
+    }
+
+    pub fn is_held(&self, button: PadButton) -> bool {
+        self.held[button.index()]
+    }
+
+    /// Left stick past the dead zone: X to the right, Y forward.
+    pub fn move_stick(&self) -> (f32, f32) {
+        apply_dead_zone(self.axes[0], self.axes[1])
+    }
+
+    /// Right stick past the dead zone: X to the right, Y up.
+    pub fn look_stick(&self) -> (f32, f32) {
+        apply_dead_zone(self.axes[2], self.axes[3])
+    }
+
+    /// Presses (`true`) and releases (`false`) since the last call, oldest first.
+    pub fn take_events(&mut self) -> Vec<(PadButton, bool)> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Centres the sticks and releases everything, as when the controller is unplugged.
+    pub fn release_all(&mut self) {
+        self.axes = [0.0; 4];
+        for button in PadButton::ALL {
+            self.set_button(button, false);
+        }
+    }
+}
+
+pub struct Gamepad {
+    state: PadState,
+    #[cfg(feature = "gamepad")]
+    gilrs: Option<gilrs::Gilrs>,
+}
+
+impl Gamepad {
+    #[cfg(feature = "gamepad")]
+    pub fn new() -> Self {
+        let gilrs = match gilrs::Gilrs::new() {
+            Ok(gilrs) => Some(gilrs),
+            Err(err) => {
+                println!("[Gamepad] Controller support unavailable: {err}");
+                None
+            }
+        };
+        Self {
+            state: PadState::default(),
+            gilrs,
+        }
+    }
+
+    #[cfg(not(feature = "gamepad"))]
+    pub fn new() -> Self {
+        Self {
+            state: PadState::default(),
+        }
+    }
+
+    /// Reads every pending controller event and returns the up-to-date state.
+    #[cfg(feature = "gamepad")]
+    pub fn poll(&mut self) -> &mut PadState {
+        use gilrs::{Axis, Button, EventType};
+
+        fn pad_button(button: Button) -> Option<PadButton> {
+            Some(match button {
+                Button::South => PadButton::South,
+                Button::East => PadButton::East,
+                Button::North => PadButton::North,
+                Button::Start => PadButton::Start,
+                Button::LeftTrigger => PadButton::LeftBumper,
+                Button::RightTrigger => PadButton::RightBumper,
+                Button::LeftTrigger2 => PadButton::LeftTrigger,
+                Button::RightTrigger2 => PadButton::RightTrigger,
+                Button::DPadUp => PadButton::DPadUp,
+                Button::DPadDown => PadButton::DPadDown,
+                Button::DPadLeft => PadButton::DPadLeft,
+                Button::DPadRight => PadButton::DPadRight,
+                _ => return None,
+            })
+        }
+
+        let Some(gilrs) = self.gilrs.as_mut() else {
+            return &mut self.state;
+        };
+        while let Some(event) = gilrs.next_event() {
+            match event.event {
+                EventType::ButtonPressed(button, _) => {
+                    if let Some(button) = pad_button(button) {
+                        if !matches!(button, PadButton::LeftTrigger | PadButton::RightTrigger) {
+                            self.state.set_button(button, true);
+                        }
+                    }
+                }
+                EventType::ButtonReleased(button, _) => {
+                    if let Some(button) = pad_button(button) {
+                        if !matches!(button, PadButton::LeftTrigger | PadButton::RightTrigger) {
+                            self.state.set_button(button, false);
+                        }
+                    }
+                }
+                EventType::ButtonChanged(button, value, _) => {
+                    if let Some(trigger @ (PadButton::LeftTrigger | PadButton::RightTrigger)) =
+                        pad_button(button)
+                    {
+                        self.state.set_trigger(trigger, value);
+                    }
+                }
+                EventType::AxisChanged(axis, value, _) => match axis {
+                    Axis::LeftStickX => self.state.set_axis(PadAxis::LeftX, value),
+                    Axis::LeftStickY => self.state.set_axis(PadAxis::LeftY, value),
+                    Axis::RightStickX => self.state.set_axis(PadAxis::RightX, value),
+                    Axis::RightStickY => self.state.set_axis(PadAxis::RightY, value),
+                    // Some pads report the triggers as Z axes.
+                    Axis::LeftZ => self.state.set_trigger(PadButton::LeftTrigger, value),
+                    Axis::RightZ => self.state.set_trigger(PadButton::RightTrigger, value),
+                    _ => {}
+                },
+                EventType::Connected => {
+                    println!("[Gamepad] {} connected.", gilrs.gamepad(event.id).name());
+                }
+                EventType::Disconnected => {
+                    println!("[Gamepad] Controller disconnected.");
+                    self.state.release_all();
+                }
+                _ => {}
+            }
+        }
+        &mut self.state
+    }
+
+    #[cfg(not(feature = "gamepad"))]
+    pub fn poll(&mut self) -> &mut PadState {
+        &mut self.state
+    }
+}
+
+impl Default for Gamepad {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dead_zone_hides_drift_and_ramps_from_zero() {
+        assert_eq!(apply_dead_zone(0.1, -0.12), (0.0, 0.0));
+        let (x, y) = apply_dead_zone(STICK_DEAD_ZONE + 0.01, 0.0);
+        assert!(
+            x > 0.0 && x < 0.05,
+            "just past the edge should barely move, got {x}"
+        );
+        assert_eq!(y, 0.0);
+        let (x, y) = apply_dead_zone(0.0, -1.0);
+        assert_eq!(x, 0.0);
+        assert!((y + 1.0).abs() < 1e-6);
+        // Corners of square-gated sticks exceed 1 but still cap at full tilt.
+        let (x, y) = apply_dead_zone(1.0, 1.0);
+        assert!(((x * x + y * y).sqrt() - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn buttons_and_triggers_report_edges_once() {
+        let mut pad = PadState::default();
+        pad.set_trigger(PadButton::RightTrigger, 0.3);
+        pad.set_trigger(PadButton::RightTrigger, 0.8);
+        pad.set_trigger(PadButton::RightTrigger, 0.9);
+        pad.set_button(PadButton::LeftBumper, true);
+        assert!(pad.is_held(PadButton::RightTrigger));
+        assert_eq!(
+            pad.take_events(),
+            vec![
+                (PadButton::RightTrigger, true),
+                (PadButton::LeftBumper, true)
+            ]
+        );
+        pad.release_all();
+        assert!(!pad.is_held(PadButton::RightTrigger));
+        assert_eq!(pad.take_events().len(), 2);
+    }
+}
@@ -0,0 +1,153 @@
+//! One-shot motions for the held item: a punch while breaking and a nudge on placing.
+//!
+//! `State` starts an action and advances it by the frame time; the renderer turns the
+//! current `HandPose` into an offset and tilt of the hand mesh.
+
+use cgmath::Rad;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HandAction {
+    /// Punch forward and chop down, as when hitting a block.
+    Swing,
+    /// Short push toward the target with a slight lift.
+    Place,
+}
+
+impl HandAction {
+    fn duration(self) -> f32 {
+        match self {
+            HandAction::Swing => 0.28,
+            HandAction::Place => 0.2,
+        }
+    }
+
+    /// Fraction of the motion spent reaching full extension; the rest eases back.
+    fn peak(self) -> f32 {
+        match self {
+            HandAction::Swing => 0.3,
+            HandAction::Place => 0.25,
+        }
+    }
+}
+
+/// Displacement of the hand from rest, in camera space.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HandPose {
+    pub right: f32,
+    pub up: f32,
+    pub forward: f32,
+    /// Tilt about the camera's right axis; negative tips the item down.
+    pub pitch: Rad<f32>,
+    /// Tilt about the view direction.
+    pub roll: Rad<f32>,
+}
+
+impl HandPose {
+    pub const REST: HandPose = HandPose {
+        right: 0.0,
+        up: 0.0,
+        forward: 0.0,
+        pitch: Rad(0.0),
+        roll: Rad(0.0),
+    };
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HandAnimation {
+    action: Option<HandAction>,
+    elapsed: f32,
+}
+
+impl HandAnimation {
+    /// Starts `action` from the beginning, cutting off whatever was playing.
+    pub fn start(&mut self, action: HandAction) {
+        self.action = Some(action);
+        self.elapsed = 0.0;
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.action.is_none()
+    }
+
+    pub fn advance(&mut self, dt: f32) {
+        let Some(action) = self.action else {
+            return;
+        };
+        self.elapsed += dt.max(0.0);
+        if self.elapsed >= action.duration() {
+            self.action = None;
+            self.elapsed = 0.0;
+        }
+    }
+
+    pub fn pose(&self) -> HandPose {
+        let Some(action) = self.action else {
+            return HandPose::REST;
+        };
+        let t = (self.elapsed / action.duration()).clamp(0.0, 1.0);
+        let amount = envelope(t, action.peak());
+        match action {
+            HandAction::Swing => HandPose {
+                right: -0.08 * amount,
+                up: -0.06 * amount,
+                forward: 0.12 * amount,
+                pitch: Rad(-0.7 * amount),
+                roll: Rad(0.35 * amount),
+            },
+            HandAction::Place => HandPose {
+                right: -0.02 * amount,
+                up: 0.025 * amount,
+                forward: 0.09 * amount,
+                pitch: Rad(0.18 * amount),
+                roll: Rad(0.0),
+            },
+        }
+    }
+}
+
+/// Rises from 0 to 1 with an ease-out until `peak`, then smoothsteps back to 0 at `t = 1`.
+fn envelope(t: f32, peak: f32) -> f32 {
+    if t < peak {
+        let rise = 1.0 - t / peak;
+        1.0 - rise * rise
+    } else {
+        let fall = ((t - peak) / (1.0 - peak)).clamp(0.0, 1.0);
+        1.0 - fall * fall * (3.0 - 2.0 * fall)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swing_peaks_then_settles_back_to_rest() {
+        let mut hand = HandAnimation::default();
+        assert_eq!(hand.pose(), HandPose::REST);
+        hand.start(HandAction::Swing);
+
+        let mut deepest: f32 = 0.0;
+        let mut frames = 0;
+        while !hand.is_idle() {
+            hand.advance(1.0 / 60.0);
+            deepest = deepest.min(hand.pose().pitch.0);
+            frames += 1;
+            assert!(frames < 60, "swing never finished");
+        }
+        assert!(
+            deepest < -0.6,
+            "swing should tip the item down, got {deepest}"
+        );
+        assert_eq!(hand.pose(), HandPose::REST);
+    }
+
+    #[test]
+    fn place_pushes_forward_and_tilts_the_other_way() {
+        let mut hand = HandAnimation::default();
+        hand.start(HandAction::Place);
+        hand.advance(0.05);
+        let pose = hand.pose();
+        assert!(pose.forward > 0.08);
+        assert!(pose.pitch.0 > 0.0);
+    }
+}
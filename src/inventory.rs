@@ -1,8 +1,17 @@
+use std::path::Path;
+
+use anyhow::Context;
+
 use crate::block::BlockType;
-use crate::item::ItemType;
+use crate::item::{BucketState, ItemType};
+use crate::save::{SaveReader, SaveWriter};
 
 pub const HOTBAR_SIZE: usize = 9;
-pub const AVAILABLE_BLOCKS: [BlockType; 18] = [
+/// Saved hotbars kept at once; saving another needs one deleted first.
+pub const MAX_LOADOUTS: usize = 6;
+const LOADOUT_MAGIC: &[u8; 8] = b"RCLOADS\0";
+const LOADOUT_VERSION: u32 = 1;
+pub const AVAILABLE_BLOCKS: [BlockType; 25] = [
     BlockType::Grass,
     BlockType::Dirt,
     BlockType::Stone,
@@ -13,6 +22,8 @@ pub const AVAILABLE_BLOCKS: [BlockType; 18] = [
     BlockType::IronOre,
     BlockType::Terracotta,
     BlockType::Water,
+    BlockType::Lava,
+    BlockType::Sponge,
     BlockType::FlowerRose,
     BlockType::FlowerTulip,
     BlockType::Torch,
@@ -21,28 +32,69 @@ pub const AVAILABLE_BLOCKS: [BlockType; 18] = [
     BlockType::Resistor,
     BlockType::VoltageSource,
     BlockType::Ground,
+    BlockType::Capacitor,
+    BlockType::Switch,
+    BlockType::Led,
+    BlockType::Diode,
+    BlockType::Potentiometer,
 ];
 
+/// Some number of one item sitting in a slot; a stack never holds zero.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ItemStack {
+    pub item: ItemType,
+    pub count: u16,
+}
+
+impl ItemStack {
+    pub fn new(item: ItemType, count: u16) -> Self {
+        Self {
+            item,
+            count: count.clamp(1, item.max_stack_size()),
+        }
+    }
+
+    pub fn single(item: ItemType) -> Self {
+        Self::new(item, 1)
+    }
+
+    /// As many of `item` as fit in one slot.
+    pub fn full(item: ItemType) -> Self {
+        Self::new(item, item.max_stack_size())
+    }
+}
+
+/// A hotbar arrangement saved under a name. Items are stored by kind alone, so
+/// electrical blocks come back with the same default parameters the palette gives them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Loadout {
+    pub name: String,
+    pub hotbar: [Option<ItemStack>; HOTBAR_SIZE],
+    pub selected_slot: usize,
+}
+
 pub struct Inventory {
-    pub hotbar: [Option<ItemType>; HOTBAR_SIZE],
+    pub hotbar: [Option<ItemStack>; HOTBAR_SIZE],
     pub selected_slot: usize,
+    pub loadouts: Vec<Loadout>,
 }
 
 impl Inventory {
     pub fn new() -> Self {
         Self {
             hotbar: [
-                Some(ItemType::Block(BlockType::Grass)),
-                Some(ItemType::Block(BlockType::Dirt)),
-                Some(ItemType::Block(BlockType::Stone)),
-                Some(ItemType::Block(BlockType::CopperWire)),
-                Some(ItemType::Block(BlockType::Resistor)),
-                Some(ItemType::Block(BlockType::VoltageSource)),
-                Some(ItemType::Block(BlockType::Ground)),
-                Some(ItemType::Block(BlockType::Water)),
-                Some(ItemType::Block(BlockType::FlowerRose)),
+                Some(ItemStack::full(ItemType::Block(BlockType::Grass))),
+                Some(ItemStack::full(ItemType::Block(BlockType::Dirt))),
+                Some(ItemStack::full(ItemType::Block(BlockType::Stone))),
+                Some(ItemStack::full(ItemType::Block(BlockType::CopperWire))),
+                Some(ItemStack::full(ItemType::Block(BlockType::Resistor))),
+                Some(ItemStack::full(ItemType::Block(BlockType::VoltageSource))),
+                Some(ItemStack::full(ItemType::Block(BlockType::Ground))),
+                Some(ItemStack::full(ItemType::Block(BlockType::Water))),
+                Some(ItemStack::single(ItemType::Bucket(BucketState::Empty))),
             ],
             selected_slot: 0,
+            loadouts: Vec::new(),
         }
     }
 
@@ -53,15 +105,23 @@ impl Inventory {
     }
 
     pub fn selected_item(&self) -> Option<ItemType> {
-        self.hotbar[self.selected_slot]
+        self.item_at(self.selected_slot)
+    }
+
+    pub fn item_at(&self, slot: usize) -> Option<ItemType> {
+        self.hotbar
+            .get(slot)
+            .copied()
+            .flatten()
+            .map(|stack| stack.item)
     }
 
     /// Get selected block (for placement) - returns None if holding a tool or material
     pub fn selected_block(&self) -> Option<BlockType> {
-        match self.hotbar[self.selected_slot] {
+        match self.selected_item() {
             Some(ItemType::Block(block)) => Some(block),
             Some(ItemType::Tool(_, _)) => None,
-            Some(ItemType::Material(_)) => None,
+            Some(ItemType::Material(_)) | Some(ItemType::Bucket(_)) => None,
             None => None,
         }
     }
@@ -98,22 +158,91 @@ impl Inventory {
             return;
         }
 
-        let current_index = self.hotbar[slot]
+        let current_index = self
+            .item_at(slot)
             .and_then(|item| match item {
                 ItemType::Block(block) => AVAILABLE_BLOCKS
                     .iter()
                     .position(|candidate| *candidate == block),
                 ItemType::Tool(_, _) => None,
-                ItemType::Material(_) => None,
+                ItemType::Material(_) | ItemType::Bucket(_) => None,
             })
             .unwrap_or(0) as i32;
         let next_index = (current_index + delta).rem_euclid(total) as usize;
-        self.hotbar[slot] = Some(ItemType::Block(AVAILABLE_BLOCKS[next_index]));
+        self.hotbar[slot] = Some(ItemStack::full(ItemType::Block(
+            AVAILABLE_BLOCKS[next_index],
+        )));
     }
 
-    pub fn set_slot(&mut self, slot: usize, item: Option<ItemType>) {
+    pub fn set_slot(&mut self, slot: usize, stack: Option<ItemStack>) {
         if slot < HOTBAR_SIZE {
-            self.hotbar[slot] = item;
+            self.hotbar[slot] = stack;
+        }
+    }
+
+    /// Takes one item out of `slot`, emptying it when the last one goes.
+    pub fn remove_one(&mut self, slot: usize) -> Option<ItemType> {
+        let stack = self.hotbar.get_mut(slot)?;
+        let item = stack.as_ref()?.item;
+        match stack {
+            Some(held) if held.count > 1 => held.count -= 1,
+            _ => *stack = None,
+        }
+        Some(item)
+    }
+
+    /// Tops up existing stacks of `item` before filling empty slots, and returns how many
+    /// didn't fit.
+    pub fn add_item(&mut self, item: ItemType, mut count: u16) -> u16 {
+        let limit = item.max_stack_size();
+        for stack in self.hotbar.iter_mut().flatten() {
+            if count == 0 {
+                break;
+            }
+            if stack.item == item && stack.count < limit {
+                let moved = count.min(limit - stack.count);
+                stack.count += moved;
+                count -= moved;
+            }
+        }
+        while count > 0 {
+            let Some(slot) = self.first_empty_slot() else {
+                break;
+            };
+            let moved = count.min(limit);
+            self.hotbar[slot] = Some(ItemStack::new(item, moved));
+            count -= moved;
+        }
+        count
+    }
+
+    /// Lifts the larger half of the stack in `slot`, leaving the rest behind.
+    pub fn split_slot(&mut self, slot: usize) -> Option<ItemStack> {
+        let stack = self.hotbar.get_mut(slot)?.as_mut()?;
+        let taken = stack.count - stack.count / 2;
+        let lifted = ItemStack::new(stack.item, taken);
+        if taken == stack.count {
+            self.hotbar[slot] = None;
+        } else {
+            stack.count -= taken;
+        }
+        Some(lifted)
+    }
+
+    /// Drops `stack` onto `slot`: an empty slot takes it, a matching stack tops up and
+    /// anything else is swapped out. Returns whatever is still in hand afterwards.
+    pub fn place_stack(&mut self, slot: usize, stack: ItemStack) -> Option<ItemStack> {
+        if slot >= HOTBAR_SIZE {
+            return Some(stack);
+        }
+        match self.hotbar[slot].as_mut() {
+            Some(existing) if existing.item == stack.item => {
+                let room = stack.item.max_stack_size().saturating_sub(existing.count);
+                let moved = stack.count.min(room);
+                existing.count += moved;
+                (moved < stack.count).then(|| ItemStack::new(stack.item, stack.count - moved))
+            }
+            _ => self.hotbar[slot].replace(stack),
         }
     }
 
@@ -125,10 +254,123 @@ impl Inventory {
         self.hotbar.iter().position(|slot| slot.is_none())
     }
 
+    pub fn loadout_index(&self, name: &str) -> Option<usize> {
+        self.loadouts
+            .iter()
+            .position(|loadout| loadout.name.eq_ignore_ascii_case(name))
+    }
+
+    /// First "Loadout N" name not already taken.
+    pub fn next_loadout_name(&self) -> String {
+        (1..)
+            .map(|n| format!("Loadout {n}"))
+            .find(|name| self.loadout_index(name).is_none())
+            .unwrap_or_default()
+    }
+
+    /// Stores the hotbar under `name`, overwriting a loadout of the same name. Returns
+    /// false when every loadout is taken by another name.
+    pub fn save_loadout(&mut self, name: &str) -> bool {
+        let loadout = Loadout {
+            name: name.to_string(),
+            hotbar: self.hotbar,
+            selected_slot: self.selected_slot,
+        };
+        if let Some(index) = self.loadout_index(name) {
+            self.loadouts[index] = loadout;
+        } else if self.loadouts.len() < MAX_LOADOUTS {
+            self.loadouts.push(loadout);
+        } else {
+            return false;
+        }
+        true
+    }
+
+    /// Swaps the hotbar for a saved one, selecting the slot that was selected when it was
+    /// saved.
+    pub fn apply_loadout(&mut self, index: usize) -> bool {
+        let Some(loadout) = self.loadouts.get(index) else {
+            return false;
+        };
+        self.hotbar = loadout.hotbar;
+        self.selected_slot = loadout.selected_slot.min(HOTBAR_SIZE - 1);
+        true
+    }
+
+    pub fn delete_loadout(&mut self, index: usize) -> Option<Loadout> {
+        (index < self.loadouts.len()).then(|| self.loadouts.remove(index))
+    }
+
+    pub fn save_loadouts_to_path(&self, path: &Path) -> anyhow::Result<()> {
+        let mut out = SaveWriter::new();
+        out.put_bytes(LOADOUT_MAGIC);
+        out.put_u32(LOADOUT_VERSION);
+        out.put_u32(self.loadouts.len() as u32);
+        for loadout in &self.loadouts {
+            out.put_u32(loadout.name.len() as u32);
+            out.put_bytes(loadout.name.as_bytes());
+            out.put_u8(loadout.selected_slot as u8);
+            for slot in &loadout.hotbar {
+                out.put_bool(slot.is_some());
+                if let Some(stack) = slot {
+                    stack.item.write_save(&mut out);
+                    out.put_u32(stack.count as u32);
+                }
+            }
+        }
+        std::fs::write(path, out.into_bytes())
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// Replaces the saved loadouts with the ones in a file written by
+    /// `save_loadouts_to_path`.
+    pub fn load_loadouts_from_path(&mut self, path: &Path) -> anyhow::Result<()> {
+        let bytes =
+            std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+        let mut input = SaveReader::new(&bytes);
+        anyhow::ensure!(
+            input.take(LOADOUT_MAGIC.len())? == LOADOUT_MAGIC,
+            "{} is not a loadout file",
+            path.display()
+        );
+        let version = input.u32()?;
+        anyhow::ensure!(
+            version == LOADOUT_VERSION,
+            "unsupported loadout version {version} (expected {LOADOUT_VERSION})"
+        );
+        let mut loadouts = Vec::new();
+        for _ in 0..input.u32()? {
+            let name_len = input.u32()? as usize;
+            let name = String::from_utf8(input.take(name_len)?.to_vec())
+                .context("loadout name is not UTF-8")?;
+            let selected_slot = input.u8()? as usize;
+            let mut hotbar = [None; HOTBAR_SIZE];
+            for slot in &mut hotbar {
+                if input.bool()? {
+                    let item = ItemType::read_save(&mut input)
+                        .with_context(|| format!("loadout '{name}'"))?;
+                    *slot = Some(ItemStack::new(
+                        item,
+                        input.u32()?.min(u16::MAX as u32) as u16,
+                    ));
+                }
+            }
+            loadouts.push(Loadout {
+                name,
+                hotbar,
+                selected_slot,
+            });
+        }
+        anyhow::ensure!(input.is_at_end(), "unexpected data after the last loadout");
+        loadouts.truncate(MAX_LOADOUTS);
+        self.loadouts = loadouts;
+        Ok(())
+    }
+
     /// Damage the currently selected tool, returns true if tool broke
     pub fn damage_selected_tool(&mut self) -> bool {
-        if let Some(item) = &mut self.hotbar[self.selected_slot] {
-            if item.damage() {
+        if let Some(stack) = &mut self.hotbar[self.selected_slot] {
+            if stack.item.damage() {
                 // Tool broke, remove it
                 self.hotbar[self.selected_slot] = None;
                 return true;
@@ -138,3 +380,98 @@ impl Inventory {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::{MaterialType, ToolType, MAX_STACK_SIZE};
+
+    #[test]
+    fn stacks_fill_up_before_spilling_into_empty_slots() {
+        let mut inventory = Inventory::new();
+        let stone = ItemType::Block(BlockType::Stone);
+        inventory.hotbar = [None; HOTBAR_SIZE];
+        inventory.set_slot(3, Some(ItemStack::new(stone, MAX_STACK_SIZE - 2)));
+
+        assert_eq!(inventory.add_item(stone, 5), 0);
+        assert_eq!(inventory.hotbar[3].unwrap().count, MAX_STACK_SIZE);
+        assert_eq!(inventory.hotbar[0], Some(ItemStack::new(stone, 3)));
+
+        inventory.select_slot(0);
+        for _ in 0..3 {
+            assert_eq!(inventory.remove_one(0), Some(stone));
+        }
+        assert_eq!(inventory.hotbar[0], None, "the last item empties the slot");
+        assert_eq!(inventory.selected_block(), None);
+
+        let bucket = ItemType::Bucket(BucketState::Empty);
+        assert_eq!(ItemStack::full(bucket).count, 1);
+        assert_eq!(inventory.add_item(bucket, 2), 0);
+        assert_eq!(inventory.item_at(0), Some(bucket));
+        assert_eq!(inventory.item_at(1), Some(bucket));
+    }
+
+    #[test]
+    fn splitting_and_dropping_stacks_merge_or_swap() {
+        let mut inventory = Inventory::new();
+        let dirt = ItemType::Block(BlockType::Dirt);
+        let sand = ItemType::Block(BlockType::Sand);
+        inventory.set_slot(0, Some(ItemStack::new(dirt, 7)));
+        inventory.set_slot(1, Some(ItemStack::new(dirt, MAX_STACK_SIZE - 2)));
+        inventory.set_slot(2, Some(ItemStack::new(sand, 1)));
+
+        let half = inventory.split_slot(0).unwrap();
+        assert_eq!(half.count, 4);
+        assert_eq!(inventory.hotbar[0].unwrap().count, 3);
+
+        let rest = inventory.place_stack(1, half);
+        assert_eq!(inventory.hotbar[1].unwrap().count, MAX_STACK_SIZE);
+        assert_eq!(
+            rest,
+            Some(ItemStack::new(dirt, 2)),
+            "what doesn't fit stays in hand"
+        );
+
+        let swapped = inventory.place_stack(2, ItemStack::new(dirt, 3));
+        assert_eq!(swapped, Some(ItemStack::new(sand, 1)));
+        assert_eq!(inventory.split_slot(HOTBAR_SIZE), None);
+    }
+
+    #[test]
+    fn loadouts_round_trip_through_a_file() {
+        let mut inventory = Inventory::new();
+        inventory.select_slot(4);
+        assert!(inventory.save_loadout("Circuits"));
+        let saved = inventory.hotbar;
+
+        inventory.hotbar = [None; HOTBAR_SIZE];
+        inventory.set_slot(
+            0,
+            Some(ItemStack::single(ItemType::Tool(ToolType::IronAxe, 17))),
+        );
+        inventory.set_slot(
+            1,
+            Some(ItemStack::new(ItemType::Material(MaterialType::Stick), 9)),
+        );
+        inventory.select_slot(1);
+        assert!(inventory.save_loadout("tools"));
+        assert!(inventory.save_loadout("TOOLS"), "same name overwrites");
+        assert_eq!(inventory.loadouts.len(), 2);
+        assert_eq!(inventory.next_loadout_name(), "Loadout 1");
+
+        let path = std::env::temp_dir().join(format!("loadouts-{}.rcl", std::process::id()));
+        inventory.save_loadouts_to_path(&path).unwrap();
+        let mut restored = Inventory::new();
+        restored.load_loadouts_from_path(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(restored.loadouts, inventory.loadouts);
+
+        assert!(restored.apply_loadout(0));
+        assert_eq!(restored.hotbar, saved);
+        assert_eq!(restored.selected_block(), Some(BlockType::Resistor));
+        assert!(restored.apply_loadout(1));
+        assert_eq!(restored.selected_slot_index(), 1);
+        assert!(!restored.apply_loadout(2));
+        assert!(restored.delete_loadout(0).is_some());
+        assert_eq!(restored.loadout_index("tools"), Some(0));
+    }
+}
@@ -1,4 +1,10 @@
-use crate::block::BlockType;
+use anyhow::Context;
+
+use crate::block::{BlockType, FluidKind};
+use crate::save::{SaveReader, SaveWriter};
+
+/// Largest stack of blocks or materials a single inventory slot holds.
+pub const MAX_STACK_SIZE: u16 = 64;
 
 /// Represents an item that can be held in inventory
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -6,6 +12,43 @@ pub enum ItemType {
     Block(BlockType),
     Tool(ToolType, u32), // ToolType and current durability
     Material(MaterialType),
+    Bucket(BucketState),
+}
+
+/// What a bucket is carrying; a full bucket holds exactly one source cell of fluid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BucketState {
+    Empty,
+    Water,
+    Lava,
+}
+
+impl BucketState {
+    pub const ALL: [BucketState; 3] = [BucketState::Empty, BucketState::Water, BucketState::Lava];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BucketState::Empty => "Bucket",
+            BucketState::Water => "Water Bucket",
+            BucketState::Lava => "Lava Bucket",
+        }
+    }
+
+    /// A bucket filled with one source cell of `kind`.
+    pub fn holding(kind: FluidKind) -> Self {
+        match kind {
+            FluidKind::Water => BucketState::Water,
+            FluidKind::Lava => BucketState::Lava,
+        }
+    }
+
+    pub fn fluid(self) -> Option<FluidKind> {
+        match self {
+            BucketState::Empty => None,
+            BucketState::Water => Some(FluidKind::Water),
+            BucketState::Lava => Some(FluidKind::Lava),
+        }
+    }
 }
 
 /// Material items (crafting ingredients that aren't blocks)
@@ -16,6 +59,8 @@ pub enum MaterialType {
 }
 
 impl MaterialType {
+    pub const ALL: [MaterialType; 2] = [MaterialType::Plank, MaterialType::Stick];
+
     pub fn name(&self) -> &'static str {
         match self {
             MaterialType::Plank => "Plank",
@@ -30,6 +75,7 @@ impl ItemType {
             ItemType::Block(block) => block.name(),
             ItemType::Tool(tool, _) => tool.name(),
             ItemType::Material(material) => material.name(),
+            ItemType::Bucket(state) => state.name(),
         }
     }
 
@@ -38,7 +84,7 @@ impl ItemType {
         match self {
             ItemType::Block(_) => 0,
             ItemType::Tool(tool, _) => tool.max_durability(),
-            ItemType::Material(_) => 0,
+            ItemType::Material(_) | ItemType::Bucket(_) => 0,
         }
     }
 
@@ -47,7 +93,7 @@ impl ItemType {
         match self {
             ItemType::Block(_) => 0,
             ItemType::Tool(_, dur) => *dur,
-            ItemType::Material(_) => 0,
+            ItemType::Material(_) | ItemType::Bucket(_) => 0,
         }
     }
 
@@ -56,7 +102,7 @@ impl ItemType {
         match self {
             ItemType::Block(_) => false, // Blocks don't mine faster
             ItemType::Tool(tool, _) => tool.is_effective_for(block),
-            ItemType::Material(_) => false,
+            ItemType::Material(_) | ItemType::Bucket(_) => false,
         }
     }
 
@@ -65,7 +111,7 @@ impl ItemType {
         match self {
             ItemType::Block(_) => 1.0, // Hand speed
             ItemType::Tool(tool, _) => tool.mining_speed_multiplier(),
-            ItemType::Material(_) => 1.0,
+            ItemType::Material(_) | ItemType::Bucket(_) => 1.0,
         }
     }
 
@@ -81,7 +127,60 @@ impl ItemType {
                     true // Already broken
                 }
             }
-            ItemType::Material(_) => false,
+            ItemType::Material(_) | ItemType::Bucket(_) => false,
+        }
+    }
+
+    /// Writes a kind tag followed by the variant: a block id, a tool and its durability,
+    /// a material or a bucket's contents.
+    pub fn write_save(&self, out: &mut SaveWriter) {
+        fn index_of<T: PartialEq>(all: &[T], value: &T) -> u8 {
+            all.iter().position(|candidate| candidate == value).unwrap_or(0) as u8
+        }
+        match self {
+            ItemType::Block(block) => {
+                out.put_u8(0);
+                out.put_u8(*block as u8);
+            }
+            ItemType::Tool(tool, durability) => {
+                out.put_u8(1);
+                out.put_u8(index_of(&ToolType::ALL, tool));
+                out.put_u32(*durability);
+            }
+            ItemType::Material(material) => {
+                out.put_u8(2);
+                out.put_u8(index_of(&MaterialType::ALL, material));
+            }
+            ItemType::Bucket(state) => {
+                out.put_u8(3);
+                out.put_u8(index_of(&BucketState::ALL, state));
+            }
+        }
+    }
+
+    pub fn read_save(input: &mut SaveReader) -> anyhow::Result<Self> {
+        let tag = input.u8()?;
+        let id = input.u8()?;
+        let item = match tag {
+            0 => BlockType::from_id(id).map(ItemType::Block),
+            1 => {
+                let durability = input.u32()?;
+                ToolType::ALL
+                    .get(id as usize)
+                    .map(|tool| ItemType::Tool(*tool, durability))
+            }
+            2 => MaterialType::ALL.get(id as usize).copied().map(ItemType::Material),
+            3 => BucketState::ALL.get(id as usize).copied().map(ItemType::Bucket),
+            _ => anyhow::bail!("unknown item kind {tag}"),
+        };
+        item.with_context(|| format!("unknown item id {id} of kind {tag}"))
+    }
+
+    /// How many of this item share one inventory slot; tools and buckets never stack.
+    pub fn max_stack_size(&self) -> u16 {
+        match self {
+            ItemType::Block(_) | ItemType::Material(_) => MAX_STACK_SIZE,
+            ItemType::Tool(_, _) | ItemType::Bucket(_) => 1,
         }
     }
 }
@@ -115,6 +214,25 @@ pub enum ToolType {
 }
 
 impl ToolType {
+    pub const ALL: [ToolType; 16] = [
+        ToolType::WoodenPickaxe,
+        ToolType::StonePickaxe,
+        ToolType::IronPickaxe,
+        ToolType::DiamondPickaxe,
+        ToolType::WoodenAxe,
+        ToolType::StoneAxe,
+        ToolType::IronAxe,
+        ToolType::DiamondAxe,
+        ToolType::WoodenShovel,
+        ToolType::StoneShovel,
+        ToolType::IronShovel,
+        ToolType::DiamondShovel,
+        ToolType::WoodenSword,
+        ToolType::StoneSword,
+        ToolType::IronSword,
+        ToolType::DiamondSword,
+    ];
+
     pub fn name(&self) -> &'static str {
         match self {
             ToolType::WoodenPickaxe => "Wooden Pickaxe",
@@ -1,7 +1,7 @@
 use std::collections::VecDeque;
 
 use crate::block::BlockType;
-use crate::chunk::{CHUNK_HEIGHT, CHUNK_SIZE};
+use crate::chunk::CHUNK_SIZE;
 use crate::world::{ChunkPos, World};
 
 /// Light propagation system for skylight and blocklight
@@ -10,11 +10,12 @@ pub struct LightingSystem;
 impl LightingSystem {
     /// Calculate initial skylight for a chunk (top-down flood fill)
     pub fn calculate_skylight(world: &mut World, chunk_pos: ChunkPos) {
+        let height = world.height();
         // Step 1: Set top layer to max skylight (15)
         for x in 0..CHUNK_SIZE {
             for z in 0..CHUNK_SIZE {
                 if let Some(chunk) = world.chunks_mut().get_mut(&chunk_pos) {
-                    chunk.set_skylight(x, CHUNK_HEIGHT - 1, z, 15);
+                    chunk.set_skylight(x, height - 1, z, 15);
                 }
             }
         }
@@ -24,7 +25,7 @@ impl LightingSystem {
             for z in 0..CHUNK_SIZE {
                 let mut light_level = 15u8;
 
-                for y in (0..CHUNK_HEIGHT).rev() {
+                for y in (0..height).rev() {
                     if let Some(chunk) = world.chunks_mut().get_mut(&chunk_pos) {
                         let block = chunk.get_block(x, y, z);
 
@@ -46,11 +47,12 @@ impl LightingSystem {
 
     /// Propagate skylight laterally using BFS
     fn propagate_skylight(world: &mut World, chunk_pos: ChunkPos) {
+        let height = world.height();
         let mut queue = VecDeque::new();
 
         // Collect all lit blocks as starting points
         for x in 0..CHUNK_SIZE {
-            for y in 0..CHUNK_HEIGHT {
+            for y in 0..height {
                 for z in 0..CHUNK_SIZE {
                     if let Some(chunk) = world.chunks().get(&chunk_pos) {
                         let light = chunk.get_skylight(x, y, z);
@@ -82,7 +84,7 @@ impl LightingSystem {
                 let ny = wy + dy;
                 let nz = wz + dz;
 
-                if ny < 0 || ny >= CHUNK_HEIGHT as i32 {
+                if ny < 0 || ny >= height as i32 {
                     continue;
                 }
 
@@ -111,11 +113,12 @@ impl LightingSystem {
 
     /// Calculate blocklight from light-emitting blocks
     pub fn calculate_blocklight(world: &mut World, chunk_pos: ChunkPos) {
+        let height = world.height();
         let mut queue = VecDeque::new();
 
         // Find all light-emitting blocks
         for x in 0..CHUNK_SIZE {
-            for y in 0..CHUNK_HEIGHT {
+            for y in 0..height {
                 for z in 0..CHUNK_SIZE {
                     if let Some(chunk) = world.chunks().get(&chunk_pos) {
                         let block = chunk.get_block(x, y, z);
@@ -156,7 +159,7 @@ impl LightingSystem {
                 let ny = wy + dy;
                 let nz = wz + dz;
 
-                if ny < 0 || ny >= CHUNK_HEIGHT as i32 {
+                if ny < 0 || ny >= height as i32 {
                     continue;
                 }
 
@@ -1,3 +1,4 @@
+mod audio;
 mod block;
 mod camera;
 mod chunk;
@@ -6,53 +7,77 @@ mod electric;
 mod entity;
 mod fluid_gpu;
 mod fluid_system;
+mod gamepad;
+mod hand;
 mod inventory;
 mod item;
 mod lighting;
 mod mesh;
 mod npu;
+mod particles;
 mod profiler;
 mod raycast;
 mod renderer;
+mod save;
 mod texture;
 mod world;
 
 use std::cell::Cell;
 use std::collections::HashSet;
-use std::time::Instant;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use anyhow::Context;
+use audio::{AudioSystem, SoundEvent, SoundMaterial};
 use camera::{
-    Camera, CameraController, Projection, PLAYER_EYE_HEIGHT, PLAYER_HEIGHT, PLAYER_RADIUS,
+    Camera, CameraController, MovementFeel, Projection, FLY_SPEED_MAX, FLY_SPEED_MIN,
+    PLAYER_EYE_HEIGHT, PLAYER_HEIGHT, PLAYER_RADIUS, SPRINT_MULTIPLIER_MAX,
+    SPRINT_MULTIPLIER_MIN, WALK_SPEED_MAX, WALK_SPEED_MIN,
 };
-use cgmath::{point3, Point3, Rad, Vector3};
+use cgmath::{point3, InnerSpace, Point3, Rad, Vector3};
 use crafting::CraftingSystem;
 use entity::ItemEntity;
 use fluid_system::FluidSystem;
-use inventory::{Inventory, AVAILABLE_BLOCKS, HOTBAR_SIZE};
-use item::ItemType;
-use renderer::{Renderer, UiVertex};
+use gamepad::{Gamepad, PadButton};
+use hand::{HandAction, HandAnimation, HandPose};
+use inventory::{Inventory, ItemStack, AVAILABLE_BLOCKS, HOTBAR_SIZE, MAX_LOADOUTS};
+use item::{BucketState, ItemType};
+use mesh::FoliageDetail;
+use renderer::{PointLight, PowerOverlay, Renderer, ShadowQuality, UiVertex, MAX_POINT_LIGHTS};
 use winit::{
     event::*,
     event_loop::EventLoop,
     keyboard::{KeyCode, PhysicalKey},
     window::{CursorGrabMode, Window, WindowBuilder},
 };
-use world::{ChunkPos, World, MAX_FLUID_LEVEL};
+use world::{
+    AtmospherePreset, ChunkPos, Schematic, World, WorldEnvironment, WorldGenConfig,
+    DEFAULT_FLUID_SPREAD_LIMIT, MAX_FLUID_LEVEL,
+};
 
-use crate::block::{Axis, BlockFace, BlockType};
-use crate::chunk::{CHUNK_HEIGHT, CHUNK_SIZE};
-use crate::electric::{BlockPos3, ComponentParams, ComponentTelemetry, ElectricalComponent};
-use crate::raycast::{raycast, RaycastHit};
-use crate::texture::atlas_uv_bounds;
+use crate::block::{Axis, BlockFace, BlockType, FluidKind};
+use crate::chunk::CHUNK_SIZE;
+use crate::electric::{
+    BlockPos3, ComponentParams, ComponentTelemetry, ElectricalComponent, LED_FULL_BRIGHTNESS_AMPS,
+};
+use crate::raycast::{raycast, raycast_with_mode, RaycastHit, RaycastMode};
+use crate::texture::{atlas_uv_bounds, average_tile_colors, TexturePack, TEXTURE_PACK_DIR};
 
 const INVENTORY_COLS: usize = 3;
 const INVENTORY_ROWS: usize = 3;
 const INVENTORY_SLOT_COUNT: usize = INVENTORY_COLS * INVENTORY_ROWS;
+// The grid shows hotbar slots, so a cell past the hotbar would be drawn but hold nothing.
+const _: () = assert!(
+    INVENTORY_SLOT_COUNT <= HOTBAR_SIZE,
+    "the inventory grid can't have more cells than the hotbar has slots"
+);
 const INVENTORY_SLOT_SIZE: f32 = 0.072;
 const INVENTORY_SLOT_GAP: f32 = 0.018;
 const INVENTORY_START_X: f32 = 0.22;
 const INVENTORY_START_Y: f32 = 0.34;
+/// Room the slot grid may take along either axis: a 3x3 grid of full-size slots. Larger
+/// grids shrink their slots to fit rather than spilling into the palette.
+const INVENTORY_GRID_MAX_EXTENT: f32 = 3.0 * INVENTORY_SLOT_SIZE + 2.0 * INVENTORY_SLOT_GAP;
 const INVENTORY_ICON_PAD: f32 = 0.006;
 const PALETTE_COLS: usize = 6;
 const PALETTE_SLOT_SIZE: f32 = 0.048;
@@ -65,6 +90,9 @@ const FILTER_CHIP_HEIGHT: f32 = 0.034;
 const FILTER_CHIP_GAP: f32 = 0.012;
 const FILTER_AREA_PADDING_X: f32 = 0.02;
 const FILTER_AREA_PADDING_Y: f32 = 0.02;
+const LOADOUT_TEXT_HEIGHT: f32 = 0.012;
+/// Longest loadout name shown on a chip; the console still takes the full name.
+const LOADOUT_LABEL_CHARS: usize = 14;
 const SEARCH_FIELD_HEIGHT: f32 = 0.038;
 const SEARCH_FIELD_PADDING: f32 = 0.012;
 
@@ -73,6 +101,63 @@ struct PaletteCategory {
     blocks: &'static [BlockType],
 }
 
+/// Order of the palette grid; kept when switching categories.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum PaletteSort {
+    /// The fixed `AVAILABLE_BLOCKS` order.
+    #[default]
+    List,
+    Name,
+    /// By the hue of the block's top tile, with greys last from dark to light.
+    Color,
+}
+
+impl PaletteSort {
+    fn next(self) -> Self {
+        match self {
+            Self::List => Self::Name,
+            Self::Name => Self::Color,
+            Self::Color => Self::List,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::List => "Sort: List",
+            Self::Name => "Sort: A-Z",
+            Self::Color => "Sort: Hue",
+        }
+    }
+}
+
+/// Sort key for `PaletteSort::Color`, indexed by block id: hue in `0..1` for coloured
+/// tiles, and `1 + lightness` for near-grey ones so they trail the rainbow.
+fn block_color_keys(pack: &TexturePack) -> Vec<f32> {
+    let tiles: Vec<(u32, u32)> = BlockType::ALL
+        .iter()
+        .map(|block| block.atlas_coords(BlockFace::Top))
+        .collect();
+    average_tile_colors(pack, &tiles)
+        .into_iter()
+        .map(|[r, g, b]| {
+            let max = r.max(g).max(b);
+            let min = r.min(g).min(b);
+            let chroma = max - min;
+            if chroma < 0.08 {
+                return 1.0 + (max + min) * 0.5;
+            }
+            let sector = if max == r {
+                ((g - b) / chroma).rem_euclid(6.0)
+            } else if max == g {
+                (b - r) / chroma + 2.0
+            } else {
+                (r - g) / chroma + 4.0
+            };
+            sector / 6.0
+        })
+        .collect()
+}
+
 const CATEGORY_TERRAIN: &[BlockType] = &[
     BlockType::Grass,
     BlockType::Dirt,
@@ -92,7 +177,7 @@ const CATEGORY_FOLIAGE: &[BlockType] = &[
 
 const CATEGORY_ORES: &[BlockType] = &[BlockType::CoalOre, BlockType::IronOre];
 
-const CATEGORY_FLUIDS: &[BlockType] = &[BlockType::Water];
+const CATEGORY_FLUIDS: &[BlockType] = &[BlockType::Water, BlockType::Lava, BlockType::Sponge];
 
 const CATEGORY_LIGHTS: &[BlockType] = &[BlockType::Torch, BlockType::GlowShroom];
 
@@ -101,6 +186,11 @@ const CATEGORY_ELECTRICAL: &[BlockType] = &[
     BlockType::Resistor,
     BlockType::VoltageSource,
     BlockType::Ground,
+    BlockType::Capacitor,
+    BlockType::Switch,
+    BlockType::Led,
+    BlockType::Diode,
+    BlockType::Potentiometer,
 ];
 
 const PALETTE_CATEGORIES: &[PaletteCategory] = &[
@@ -136,6 +226,144 @@ const PALETTE_CATEGORIES: &[PaletteCategory] = &[
 
 type Rect = ((f32, f32), (f32, f32));
 
+/// Rect of slot `index` in a `cols` x `rows` inventory grid anchored at the inventory origin.
+fn inventory_grid_slot_rect(cols: usize, rows: usize, index: usize) -> Option<Rect> {
+    if cols == 0 || index >= cols * rows {
+        return None;
+    }
+    let cells = cols.max(rows) as f32;
+    let gap_ratio = INVENTORY_SLOT_GAP / INVENTORY_SLOT_SIZE;
+    let fitted = INVENTORY_GRID_MAX_EXTENT / (cells + (cells - 1.0) * gap_ratio);
+    let slot_size = INVENTORY_SLOT_SIZE.min(fitted);
+    let gap = slot_size * gap_ratio;
+
+    let col = index % cols;
+    let row = index / cols;
+    let min_x = INVENTORY_START_X + col as f32 * ui_width(slot_size + gap);
+    let min_y = INVENTORY_START_Y + row as f32 * (slot_size + gap);
+    Some(((min_x, min_y), (min_x + ui_width(slot_size), min_y + slot_size)))
+}
+
+fn console_char(key: KeyCode) -> Option<char> {
+    let letters = [
+        KeyCode::KeyA, KeyCode::KeyB, KeyCode::KeyC, KeyCode::KeyD, KeyCode::KeyE,
+        KeyCode::KeyF, KeyCode::KeyG, KeyCode::KeyH, KeyCode::KeyI, KeyCode::KeyJ,
+        KeyCode::KeyK, KeyCode::KeyL, KeyCode::KeyM, KeyCode::KeyN, KeyCode::KeyO,
+        KeyCode::KeyP, KeyCode::KeyQ, KeyCode::KeyR, KeyCode::KeyS, KeyCode::KeyT,
+        KeyCode::KeyU, KeyCode::KeyV, KeyCode::KeyW, KeyCode::KeyX, KeyCode::KeyY,
+        KeyCode::KeyZ,
+    ];
+    if let Some(offset) = letters.iter().position(|&letter| letter == key) {
+        return Some((b'a' + offset as u8) as char);
+    }
+    match key {
+        KeyCode::Space => Some(' '),
+        other => config_entry_char(other),
+    }
+}
+
+fn config_entry_char(key: KeyCode) -> Option<char> {
+    match key {
+        KeyCode::Digit0 | KeyCode::Numpad0 => Some('0'),
+        KeyCode::Digit1 | KeyCode::Numpad1 => Some('1'),
+        KeyCode::Digit2 | KeyCode::Numpad2 => Some('2'),
+        KeyCode::Digit3 | KeyCode::Numpad3 => Some('3'),
+        KeyCode::Digit4 | KeyCode::Numpad4 => Some('4'),
+        KeyCode::Digit5 | KeyCode::Numpad5 => Some('5'),
+        KeyCode::Digit6 | KeyCode::Numpad6 => Some('6'),
+        KeyCode::Digit7 | KeyCode::Numpad7 => Some('7'),
+        KeyCode::Digit8 | KeyCode::Numpad8 => Some('8'),
+        KeyCode::Digit9 | KeyCode::Numpad9 => Some('9'),
+        KeyCode::Period | KeyCode::NumpadDecimal => Some('.'),
+        _ => None,
+    }
+}
+
+/// Names of the palette categories (other than "All") that list this block.
+fn palette_categories_for(block: BlockType) -> Vec<&'static str> {
+    PALETTE_CATEGORIES
+        .iter()
+        .skip(1)
+        .filter(|category| category.blocks.contains(&block))
+        .map(|category| category.name)
+        .collect()
+}
+
+/// Selection outline color for a targeted block, by its palette category.
+fn outline_color_for(block: BlockType, fluid_hit: bool) -> [f32; 3] {
+    if block.is_electrical() {
+        OUTLINE_ELECTRICAL_COLOR
+    } else if fluid_hit || CATEGORY_FLUIDS.contains(&block) {
+        OUTLINE_FLUID_COLOR
+    } else {
+        OUTLINE_NEUTRAL_COLOR
+    }
+}
+
+/// Extra lines shown under the block name in the palette tooltip.
+fn palette_tooltip_lines(block: BlockType) -> Vec<String> {
+    let mut lines = Vec::new();
+    let categories = palette_categories_for(block);
+    if !categories.is_empty() {
+        lines.push(format!("Category: {}", categories.join(", ")));
+    }
+    if let Some(component) = ElectricalComponent::from_block(block) {
+        let params = component.default_params();
+        match component {
+            ElectricalComponent::Wire | ElectricalComponent::Resistor => {
+                if let Some(r) = params.resistance_ohms {
+                    lines.push(format!("Default: {:.2} OHM", r));
+                }
+            }
+            ElectricalComponent::VoltageSource => {
+                if let Some(v) = params.voltage_volts {
+                    lines.push(format!("Default: {:.2} V", v));
+                }
+            }
+            ElectricalComponent::Ground => lines.push("Reference node at 0 V".to_string()),
+            ElectricalComponent::Capacitor => {
+                if let Some(c) = params.capacitance_farads {
+                    lines.push(format!("Default: {:.3} F", c));
+                }
+            }
+            ElectricalComponent::Switch => {
+                lines.push("Placed open; right click to flip".to_string())
+            }
+            ElectricalComponent::Led => {
+                if let Some(v) = params.voltage_volts {
+                    lines.push(format!("Forward drop: {:.2} V", v));
+                }
+                lines.push("Glows with the current through it".to_string());
+            }
+            ElectricalComponent::Diode => {
+                lines.push("Conducts from its positive face to its negative face".to_string())
+            }
+            ElectricalComponent::Potentiometer => {
+                if let Some(r) = params.resistance_ohms {
+                    lines.push(format!("Default: {:.2} OHM", r));
+                }
+                lines.push("Scroll while looking at it to turn".to_string());
+            }
+        }
+        if let Some(i) = params.max_current_amps {
+            lines.push(format!("Rated current: {:.2} A", i));
+        }
+        lines.push("Attaches to the face you click".to_string());
+    }
+    match block {
+        BlockType::FlowerRose | BlockType::FlowerTulip => {
+            lines.push("Needs a block below".to_string())
+        }
+        BlockType::LilyPad => lines.push("Must be placed on water".to_string()),
+        BlockType::Water => lines.push("Spreads and flows as fluid".to_string()),
+        BlockType::Lava => lines.push("Creeps slowly; cools to stone in water".to_string()),
+        BlockType::Sponge => lines.push("Drains water nearby, then turns wet".to_string()),
+        BlockType::WetSponge => lines.push("Break it to get a dry sponge back".to_string()),
+        _ => {}
+    }
+    lines
+}
+
 struct InventoryLayout {
     panel: Rect,
     header: Rect,
@@ -145,14 +373,95 @@ struct InventoryLayout {
     search_rect: Rect,
     search_clear_rect: Rect,
     chip_rects: Vec<Rect>,
+    sort_rect: Rect,
     palette_content_origin: (f32, f32),
     palette_view_height: f32,
+    loadout_strip: Rect,
+    loadout_save_rect: Rect,
+    /// One chip per saved loadout, in order, for as many as fit across the strip.
+    loadout_rects: Vec<Rect>,
 }
 
 const FIXED_TICK_RATE: f32 = 60.0;
 const FIXED_TICK_STEP: f32 = 1.0 / FIXED_TICK_RATE;
 const MAX_TICKS_PER_FRAME: usize = 6;
+/// Chunk radius loaded around the player at startup and in headless runs.
+const RENDER_DISTANCE: i32 = 3;
+/// Range of the Display > Render Distance setting, in chunks.
+const MIN_RENDER_DISTANCE: i32 = 2;
+const MAX_RENDER_DISTANCE: i32 = 12;
+/// Chunks generated per frame while exploring; the rest follow on later frames, nearest first.
+const CHUNK_LOADS_PER_FRAME: usize = 6;
+const BUILD_GRID_RADIUS: i32 = 12;
+const BUILD_GRID_SPACINGS: [i32; 3] = [1, 4, 8];
+const ANIMATION_SCALE_MIN: f32 = 0.125;
+const ANIMATION_SCALE_MAX: f32 = 4.0;
+/// Cool tint that marks the placement ghost as not-yet-placed.
+const PLACEMENT_PREVIEW_TINT: [f32; 3] = [0.55, 0.8, 1.0];
+const DEFAULT_FREE_LOOK_KEY: KeyCode = KeyCode::F7;
+const PHOTO_MODE_KEY: KeyCode = KeyCode::F2;
+/// F4 already freezes animations, so the profiler breakdown sits one key over.
+const PROFILER_OVERLAY_KEY: KeyCode = KeyCode::F5;
+/// Switches the world to triangle edges; only while the F3 debug overlay is up.
+const WIREFRAME_KEY: KeyCode = KeyCode::F11;
+/// Toggles the depth-of-field blur while photo mode is on.
+const PHOTO_BLUR_KEY: KeyCode = KeyCode::KeyB;
+/// Photo mode autofocuses on whatever the crosshair rests on within this distance.
+const PHOTO_FOCUS_REACH: f32 = 160.0;
+/// How quickly the focus distance eases toward a new target, per second.
+const PHOTO_FOCUS_RATE: f32 = 6.0;
+/// Horizontal distance walked between footstep sounds.
+const FOOTSTEP_STRIDE: f32 = 1.6;
+/// Sprinting lengthens the stride by less than it raises speed, so steps come quicker.
+const SPRINT_FOOTSTEP_STRIDE: f32 = 2.0;
+/// Blocks around the player whose fluid activity the F9 overlay reports.
+const FLUID_DEBUG_RADIUS: i32 = 12;
+/// How far the crosshair can reach to break, place, outline or inspect a block.
+const BLOCK_REACH: f32 = 5.0;
+/// World file written by a bare `save` console command, next to the telemetry CSV.
+const DEFAULT_SAVE_NAME: &str = "world";
+const SAVE_FILE_EXTENSION: &str = ".rcw";
+/// Named hotbar loadouts, kept next to the world saves and shared between worlds.
+const LOADOUTS_FILE: &str = "loadouts.rcl";
+/// Measuring tape endpoints can be picked further away than blocks can be edited.
+const MEASURE_REACH: f32 = 64.0;
+const SCHEMATIC_KEY: KeyCode = KeyCode::KeyV;
+/// Largest box the schematic clipboard copies, so a stray corner can't stall a frame.
+const SCHEMATIC_MAX_VOLUME: i64 = 32 * 32 * 32;
+const ZOOM_KEY: KeyCode = KeyCode::KeyZ;
+const ZOOM_FOV_DEG: f32 = 20.0;
+/// Seconds for the underwater tint to fully fade in or out.
+const UNDERWATER_FADE_SECONDS: f32 = 0.3;
+/// Distance below the eye that has to be in a liquid before the player swims.
+const SWIM_CHEST_DEPTH: f32 = 0.7;
+/// Seconds of air a full breath lasts with the head underwater.
+const MAX_BREATH: f32 = 10.0;
+/// Seconds of air regained per second with the head above the surface.
+const BREATH_RECOVERY_RATE: f32 = 5.0;
+const MAX_HEALTH: f32 = 20.0;
+const HEALTH_REGEN_PER_SECOND: f32 = 0.25;
+const DROWNING_DAMAGE: f32 = 2.0;
+const DROWNING_DAMAGE_INTERVAL: f32 = 1.0;
+/// Weight of the newest frame in the debug overlay's averaged frame time.
+const FRAME_TIME_SMOOTHING: f32 = 0.05;
+/// Longest sections listed by the profiler breakdown.
+const PROFILER_OVERLAY_ROWS: usize = 12;
+/// How long the inspect overlay stays up after the crosshair leaves a component.
+const INSPECT_STICKY_SECONDS: f32 = 0.5;
+/// How long the crosshair flashes after a successful break or place.
+const HIT_MARKER_SECONDS: f32 = 0.1;
+const HIT_MARKER_BREAK_COLOR: [f32; 3] = [1.0, 0.62, 0.36];
+const HIT_MARKER_PLACE_COLOR: [f32; 3] = [0.56, 0.94, 0.68];
+const UNDERWATER_OVERLAY_COLOR: [f32; 3] = [0.05, 0.2, 0.45];
+const LAVA_OVERLAY_COLOR: [f32; 3] = [0.95, 0.32, 0.04];
+/// Selection outline colors; only the neutral one is used when color-coding is off.
+const OUTLINE_NEUTRAL_COLOR: [f32; 3] = [1.0, 0.95, 0.45];
+const OUTLINE_ELECTRICAL_COLOR: [f32; 3] = [0.4, 0.85, 1.0];
+const OUTLINE_FLUID_COLOR: [f32; 3] = [0.45, 0.6, 1.0];
+const TELEMETRY_EXPORT_PATH: &str = "electrical_telemetry.csv";
 const WATER_UPDATE_INTERVAL: u32 = 10; // Water updates every 10 ticks (6 times per second)
+/// Lava steps three times slower than water, so it creeps where water rushes.
+const LAVA_UPDATE_INTERVAL: u32 = WATER_UPDATE_INTERVAL * 3;
 
 fn ui_width(value: f32) -> f32 {
     value / UI_REFERENCE_ASPECT
@@ -190,6 +499,23 @@ fn axis_name(axis: Axis) -> &'static str {
     }
 }
 
+/// Whether a source holds its rated current on a short instead of tripping.
+fn current_limit_line(params: &ComponentParams) -> String {
+    format!(
+        "Current Limit: {}",
+        if params.current_limit { "On" } else { "Off" }
+    )
+}
+
+/// Charge held by a capacitor, Q = C * V, alongside the plate voltage it comes from.
+fn stored_charge_line(capacitance_farads: f32, charge_volts: f32) -> String {
+    format!(
+        "Stored Charge: {:.3} C at {:.2} V",
+        capacitance_farads * charge_volts,
+        charge_volts
+    )
+}
+
 #[derive(Clone, PartialEq)]
 struct InspectInfo {
     handle: AttachmentTarget,
@@ -200,6 +526,83 @@ struct InspectInfo {
     negative_face: BlockFace,
     params: ComponentParams,
     telemetry: ComponentTelemetry,
+    /// Capacitor plate voltage; 0 for other components.
+    charge_volts: f32,
+    /// Whether a switch conducts; false for other components.
+    closed: bool,
+    /// Fluid is touching it and leaking its network to ground.
+    wet: bool,
+    /// Current it would have carried when it tripped its network, if over its rating.
+    overcurrent: Option<f32>,
+}
+
+/// What the crosshair rests on, resolved once per frame so breaking, placing, the outline
+/// and the inspect overlay all agree on the same cell.
+#[derive(Clone, Copy, Debug)]
+pub struct LookTarget {
+    pub hit: RaycastHit,
+    /// Face of the hit cell the ray entered through.
+    pub face: BlockFace,
+    /// The attached component's block type when one was hit, otherwise the cell's block.
+    pub block: BlockType,
+    pub component: Option<(ElectricalComponent, ComponentTelemetry)>,
+}
+
+impl LookTarget {
+    pub fn block_pos(&self) -> BlockPos3 {
+        let (x, y, z) = self.hit.block_pos;
+        BlockPos3::new(x, y, z)
+    }
+}
+
+/// A block picked with the measuring tape and the face point its line attaches to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct MeasurePoint {
+    block: (i32, i32, i32),
+    anchor: [f32; 3],
+}
+
+impl MeasurePoint {
+    fn from_hit(hit: &RaycastHit) -> Self {
+        let (x, y, z) = hit.block_pos;
+        // Sit the line just off the clicked face so depth testing doesn't bury it in the block.
+        let lift = 0.52;
+        Self {
+            block: hit.block_pos,
+            anchor: [
+                x as f32 + hit.normal.x * lift,
+                y as f32 + hit.normal.y * lift,
+                z as f32 + hit.normal.z * lift,
+            ],
+        }
+    }
+}
+
+/// Distance between two block positions picked with the measuring tape.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Measurement {
+    start: (i32, i32, i32),
+    end: (i32, i32, i32),
+}
+
+impl Measurement {
+    fn delta(&self) -> (i32, i32, i32) {
+        (
+            self.end.0 - self.start.0,
+            self.end.1 - self.start.1,
+            self.end.2 - self.start.2,
+        )
+    }
+
+    fn euclidean(&self) -> f32 {
+        let (dx, dy, dz) = self.delta();
+        ((dx * dx + dy * dy + dz * dz) as f32).sqrt()
+    }
+
+    fn manhattan(&self) -> i32 {
+        let (dx, dy, dz) = self.delta();
+        dx.abs() + dy.abs() + dz.abs()
+    }
 }
 
 #[derive(Clone)]
@@ -208,6 +611,305 @@ struct ConfigEditor {
     label: String,
     component: ElectricalComponent,
     params: ComponentParams,
+    /// Typed value for the primary parameter while numeric entry is focused.
+    entry: Option<String>,
+}
+
+const CONFIG_ENTRY_MAX_LEN: usize = 10;
+/// Share of a potentiometer's travel one mouse wheel notch turns it.
+const POTENTIOMETER_WHEEL_STEP: f32 = 0.05;
+/// Capacitance the config editor's arrow keys move by, and the range it accepts.
+const CAPACITANCE_STEP_FARADS: f32 = 0.005;
+const CAPACITANCE_MIN_FARADS: f32 = 0.001;
+const CAPACITANCE_MAX_FARADS: f32 = 100.0;
+/// Forward drop the config editor's arrow keys move an LED's by, and the range it accepts.
+const FORWARD_DROP_STEP_VOLTS: f32 = 0.1;
+const FORWARD_DROP_MIN_VOLTS: f32 = 0.5;
+const FORWARD_DROP_MAX_VOLTS: f32 = 5.0;
+/// How far a fully lit LED reaches, and how close to the LED the light sits.
+const LED_LIGHT_RADIUS: f32 = 8.0;
+const LED_LIGHT_LIFT: f32 = 0.7;
+/// Reach of the glow around a voltage source that is driving current.
+const SOURCE_LIGHT_RADIUS: f32 = 4.0;
+const GLOW_SHROOM_LIGHT_RADIUS: f32 = 6.0;
+const GLOW_SHROOM_LIGHT_COLOR: [f32; 3] = [0.32, 0.95, 0.78];
+/// Glowing blocks further than this from the camera aren't considered for a light slot.
+const GLOW_LIGHT_SEARCH_RANGE: f32 = 48.0;
+
+/// One group of controls in the F1 help overlay and the startup printout.
+struct HelpSection {
+    title: &'static str,
+    /// `(keys, action)` pairs.
+    entries: &'static [(&'static str, &'static str)],
+}
+
+const HELP_SECTIONS: &[HelpSection] = &[
+    HelpSection {
+        title: "Movement",
+        entries: &[
+            ("W A S D", "Move (fly when noclip is on)"),
+            ("Space", "Jump / ascend in noclip"),
+            ("Left Ctrl", "Sprint"),
+            ("Shift (hold)", "Sneak: walk slowly without falling off edges"),
+            ("F", "Toggle noclip"),
+            ("Z (hold)", "Zoom in with the spyglass"),
+            ("Mouse", "Look around"),
+            ("F7", "Detach a free-look camera (player stays put)"),
+            ("F2", "Photo mode: hide the HUD, fly freely, B toggles blur"),
+        ],
+    },
+    HelpSection {
+        title: "Building",
+        entries: &[
+            ("Left click", "Break block"),
+            ("Right click", "Place block / use the bucket"),
+            ("1-9", "Select hotbar slot"),
+            ("Wheel", "Cycle hotbar"),
+            ("E", "Inventory"),
+            ("C", "Crafting"),
+            ("G", "Cycle build grid (off, 1, 4, 8)"),
+            ("M", "Measuring tape (click start, click end)"),
+            ("V", "Copy a box (click two corners), right click pastes"),
+        ],
+    },
+    HelpSection {
+        title: "Electrical",
+        entries: &[
+            ("T", "Configure the highlighted component"),
+            ("Right click", "Open or close the highlighted switch"),
+            ("Mouse wheel", "Turn the highlighted potentiometer"),
+            ("F6", "Circuit summary: parts, power, overloads"),
+            ("F10", "Show power overlays through walls"),
+            ("F8", "Append electrical telemetry to a CSV file"),
+            ("/", "Console: find wire, resistor, source, ground"),
+            ("/ find overloaded", "Turn toward a component that tripped"),
+            ("/ gamemode", "Switch between creative and survival"),
+            ("/ keepinventory", "Keep the hotbar when dying in survival"),
+            ("/ reset area", "Regenerate nearby chunks from the seed"),
+            ("/ save | load", "Write or read the world file, e.g. save castle"),
+            ("/ time", "Jump to noon, midnight, sunrise, sunset or HH:MM"),
+            ("/ daylength", "Cycle day length presets, or set seconds"),
+            ("/ loadout", "Save, load or delete a named hotbar"),
+        ],
+    },
+    HelpSection {
+        title: "Debug",
+        entries: &[
+            ("F3", "Debug overlay: FPS, frame time, chunk stats"),
+            ("F4", "Freeze / resume animations"),
+            ("F5", "Profiler: last frame's sections by time"),
+            ("[ / ]", "Slow down / speed up animations"),
+            ("F9", "Show active fluid cells near the player"),
+            ("F11", "Wireframe view of chunk meshes (with F3 on)"),
+        ],
+    },
+    HelpSection {
+        title: "Interface",
+        entries: &[
+            ("Click", "Grab the mouse"),
+            ("Esc", "Pause and release the mouse"),
+            ("S", "Settings (while paused)"),
+            ("F1", "Show or hide this help"),
+        ],
+    },
+];
+
+const HELP_KEY: KeyCode = KeyCode::F1;
+/// Rows of the help overlay shown at once; the rest scroll.
+const HELP_VISIBLE_ROWS: usize = 20;
+
+/// Help overlay rows: each section title followed by its entries.
+fn help_rows() -> Vec<(Option<&'static str>, &'static str)> {
+    let mut rows = Vec::new();
+    for section in HELP_SECTIONS {
+        rows.push((None, section.title));
+        rows.extend(section.entries.iter().map(|&(keys, action)| (Some(keys), action)));
+    }
+    rows
+}
+
+const CONSOLE_KEY: KeyCode = KeyCode::Slash;
+const CONSOLE_MAX_LEN: usize = 32;
+/// Seconds a console reply stays on screen.
+const CONSOLE_MESSAGE_SECONDS: f32 = 4.0;
+/// Largest `reset area` radius in chunks; 4 already regenerates 81 chunks.
+const MAX_RESET_RADIUS: i32 = 4;
+
+/// Session rules for the hotbar and the player. Creative (the default) keeps the infinite
+/// hotbar, leaves no drops and can't be hurt.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum GameMode {
+    #[default]
+    Creative,
+    /// Placing uses one of the held stack; breaking and picking up the drop gets it back.
+    /// Drowning hurts, and dying empties the hotbar unless keep-inventory is on.
+    Survival,
+}
+
+impl GameMode {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "creative" | "c" => Some(Self::Creative),
+            "survival" | "s" => Some(Self::Survival),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Creative => "Creative",
+            Self::Survival => "Survival",
+        }
+    }
+
+    fn consumes_blocks(self) -> bool {
+        self == Self::Survival
+    }
+
+    fn drops_items(self) -> bool {
+        self == Self::Survival
+    }
+
+    fn takes_damage(self) -> bool {
+        self == Self::Survival
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum ConsoleCommand {
+    /// Point the camera at the nearest block holding this component.
+    Find(ElectricalComponent),
+    /// Point the camera at the nearest component that tripped its circuit.
+    FindOverloaded,
+    /// Switch between creative and survival without restarting.
+    GameMode(GameMode),
+    /// Set whether dying in survival keeps the hotbar, or flip it when absent.
+    KeepInventory(Option<bool>),
+    /// Regenerate the chunks within this many chunks of the player; 0 is the current one.
+    ResetArea(i32),
+    /// Write the loaded world to this file.
+    Save(String),
+    /// Replace the world with the one saved in this file.
+    Load(String),
+    /// Jump to this `(hour, minute)` on the world clock.
+    Time(u32, u32),
+    /// Set the day length in seconds, or step to the next preset when absent.
+    DayLength(Option<u32>),
+    /// Save, load or delete the hotbar loadout with this name.
+    Loadout(LoadoutAction, String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum LoadoutAction {
+    Save,
+    Load,
+    Delete,
+}
+
+/// Clock time for `time`: a named moment or `HH:MM`.
+fn parse_clock_time(value: &str) -> Option<(u32, u32)> {
+    match value.to_ascii_lowercase().as_str() {
+        "sunrise" | "dawn" => Some((6, 0)),
+        "noon" => Some((12, 0)),
+        "sunset" | "dusk" => Some((18, 0)),
+        "midnight" => Some((0, 0)),
+        clock => {
+            let (hour, minute) = clock.split_once(':')?;
+            let (hour, minute) = (hour.parse().ok()?, minute.parse().ok()?);
+            (hour < 24 && minute < 60).then_some((hour, minute))
+        }
+    }
+}
+
+/// Save file for a console name: `save` alone uses `world.rcw`, `save castle` uses
+/// `castle.rcw`.
+fn save_file_name(name: Option<&str>) -> String {
+    let name = name.unwrap_or(DEFAULT_SAVE_NAME);
+    if name.ends_with(SAVE_FILE_EXTENSION) {
+        name.to_string()
+    } else {
+        format!("{name}{SAVE_FILE_EXTENSION}")
+    }
+}
+
+/// Parses a line typed into the `/` console, or explains why it was rejected.
+fn parse_console_command(line: &str) -> Result<ConsoleCommand, String> {
+    let mut words = line.split_whitespace();
+    match words.next().map(|word| word.to_ascii_lowercase()).as_deref() {
+        Some("find") => {
+            let Some(name) = words.next() else {
+                return Err(
+                    "Usage: find <wire|resistor|source|ground|capacitor|overloaded>".to_string(),
+                );
+            };
+            if name.eq_ignore_ascii_case("overloaded") {
+                return Ok(ConsoleCommand::FindOverloaded);
+            }
+            ElectricalComponent::from_name(name)
+                .map(ConsoleCommand::Find)
+                .ok_or_else(|| format!("Unknown component '{name}'"))
+        }
+        Some("gamemode") => {
+            let Some(name) = words.next() else {
+                return Err("Usage: gamemode <creative|survival>".to_string());
+            };
+            GameMode::from_name(name)
+                .map(ConsoleCommand::GameMode)
+                .ok_or_else(|| format!("Unknown game mode '{name}'"))
+        }
+        Some("keepinventory") => match words.next().map(|word| word.to_ascii_lowercase()) {
+            None => Ok(ConsoleCommand::KeepInventory(None)),
+            Some(value) => match value.as_str() {
+                "on" | "true" => Ok(ConsoleCommand::KeepInventory(Some(true))),
+                "off" | "false" => Ok(ConsoleCommand::KeepInventory(Some(false))),
+                _ => Err("Usage: keepinventory [on|off]".to_string()),
+            },
+        },
+        Some("reset") => {
+            let usage = || format!("Usage: reset chunk | reset area <0-{MAX_RESET_RADIUS}>");
+            match words.next().map(|word| word.to_ascii_lowercase()).as_deref() {
+                Some("chunk") => Ok(ConsoleCommand::ResetArea(0)),
+                Some("area") => words
+                    .next()
+                    .and_then(|radius| radius.parse().ok())
+                    .filter(|radius| (0..=MAX_RESET_RADIUS).contains(radius))
+                    .map(ConsoleCommand::ResetArea)
+                    .ok_or_else(usage),
+                _ => Err(usage()),
+            }
+        }
+        Some("time") => words
+            .next()
+            .and_then(parse_clock_time)
+            .map(|(hour, minute)| ConsoleCommand::Time(hour, minute))
+            .ok_or_else(|| "Usage: time <noon|midnight|sunrise|sunset|HH:MM>".to_string()),
+        Some("daylength") => match words.next() {
+            None => Ok(ConsoleCommand::DayLength(None)),
+            Some(value) => value
+                .parse()
+                .ok()
+                .filter(|&seconds| seconds > 1)
+                .map(|seconds| ConsoleCommand::DayLength(Some(seconds)))
+                .ok_or_else(|| "Usage: daylength [seconds above 1]".to_string()),
+        },
+        Some("loadout") => {
+            let action = match words.next().map(|word| word.to_ascii_lowercase()).as_deref() {
+                Some("save") => Some(LoadoutAction::Save),
+                Some("load") => Some(LoadoutAction::Load),
+                Some("delete") => Some(LoadoutAction::Delete),
+                _ => None,
+            };
+            let name = words.collect::<Vec<_>>().join(" ");
+            match action {
+                Some(action) if !name.is_empty() => Ok(ConsoleCommand::Loadout(action, name)),
+                _ => Err("Usage: loadout <save|load|delete> <name>".to_string()),
+            }
+        }
+        Some("save") => Ok(ConsoleCommand::Save(save_file_name(words.next()))),
+        Some("load") => Ok(ConsoleCommand::Load(save_file_name(words.next()))),
+        Some(other) => Err(format!("Unknown command '{other}'")),
+        None => Err("Type a command, e.g. find source".to_string()),
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -215,22 +917,59 @@ enum SettingsTab {
     Display,
     Audio,
     Controls,
+    Lighting,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum SettingsSlider {
     Fov,
     Sensitivity,
+    WalkSpeed,
+    SprintMultiplier,
+    FlySpeed,
+}
+
+impl SettingsSlider {
+    const ALL: [Self; 5] = [
+        Self::Fov,
+        Self::Sensitivity,
+        Self::WalkSpeed,
+        Self::SprintMultiplier,
+        Self::FlySpeed,
+    ];
+
+    /// Tab and focus row the slider sits on.
+    fn focus(self) -> (SettingsTab, usize) {
+        match self {
+            Self::Fov => (SettingsTab::Display, 0),
+            Self::Sensitivity => (SettingsTab::Display, 1),
+            Self::WalkSpeed => (SettingsTab::Controls, 3),
+            Self::SprintMultiplier => (SettingsTab::Controls, 4),
+            Self::FlySpeed => (SettingsTab::Controls, 5),
+        }
+    }
+
+    /// Values at the left and right ends of the track.
+    fn range(self) -> (f32, f32) {
+        match self {
+            Self::Fov => (60.0, 100.0),
+            Self::Sensitivity => (0.0005, 0.02),
+            Self::WalkSpeed => (WALK_SPEED_MIN, WALK_SPEED_MAX),
+            Self::SprintMultiplier => (SPRINT_MULTIPLIER_MIN, SPRINT_MULTIPLIER_MAX),
+            Self::FlySpeed => (FLY_SPEED_MIN, FLY_SPEED_MAX),
+        }
+    }
 }
 
 impl SettingsTab {
-    const ALL: [Self; 3] = [Self::Display, Self::Audio, Self::Controls];
+    const ALL: [Self; 4] = [Self::Display, Self::Audio, Self::Controls, Self::Lighting];
 
     fn label(self) -> &'static str {
         match self {
             Self::Display => "DISPLAY",
             Self::Audio => "AUDIO",
             Self::Controls => "CONTROLS",
+            Self::Lighting => "LIGHTING",
         }
     }
 
@@ -239,6 +978,7 @@ impl SettingsTab {
             Self::Display => 0,
             Self::Audio => 1,
             Self::Controls => 2,
+            Self::Lighting => 3,
         }
     }
 }
@@ -248,6 +988,7 @@ enum HotbarState {
     Normal,
     Noclip,
     Underwater,
+    Sneaking,
 }
 
 struct HotbarStatusData {
@@ -281,22 +1022,50 @@ struct State<'window> {
     inventory_palette_hover: Option<usize>,
     inventory_cursor_pos: Option<(f32, f32)>,
     inventory_drag_origin: Option<usize>,
-    inventory_drag_block: Option<ItemType>,
+    inventory_drag_block: Option<ItemStack>,
+    inventory_trashed: Option<(usize, ItemStack)>,
     inventory_swap_slot: Option<usize>,
     inventory_last_hover_slot: Option<usize>,
     inventory_last_hover_palette: Option<usize>,
     inventory_filter_chip_hover: Option<usize>,
+    /// Category chip holding keyboard focus after tabbing out of the search field.
+    inventory_chip_focus: Option<usize>,
     inventory_active_category: usize,
     inventory_search_query: String,
     inventory_search_active: bool,
     inventory_palette_scroll: f32,
     inventory_palette_filtered: Vec<BlockType>,
+    inventory_palette_sort: PaletteSort,
+    /// `block_color_keys` for the active texture pack.
+    palette_color_keys: Vec<f32>,
+    look_target: Option<LookTarget>,
     highlight_target: Option<AttachmentTarget>,
+    /// Potentiometer under the crosshair this frame, which the mouse wheel turns instead of
+    /// cycling the hotbar. Unlike `highlight_target` it never lingers.
+    dial_target: Option<AttachmentTarget>,
     inspect_info: Option<InspectInfo>,
+    /// Last time the crosshair was on a component; the overlay lingers briefly after this.
+    inspect_seen_at: Instant,
     config_editor: Option<ConfigEditor>,
     last_frame: Instant,
     tick_accumulator: f32,
     animation_time: f32,
+    animations_frozen: bool,
+    animation_time_scale: f32,
+    /// Detached camera flown independently while the player body stays put.
+    free_camera: Option<Camera>,
+    free_look_key: KeyCode,
+    /// Photo mode hides the HUD and flies the free camera.
+    photo_mode: bool,
+    /// Whether entering photo mode switched free-look on, so leaving switches it back off.
+    photo_owns_free_camera: bool,
+    photo_blur: bool,
+    /// Smoothed distance to the crosshair target, fed to the depth-of-field pass.
+    photo_focus: f32,
+    audio: AudioSystem,
+    gamepad: Gamepad,
+    /// Distance walked since the last footstep sound.
+    footstep_distance: f32,
     debug_tick_counter: u32,
     water_tick_counter: u32,
     mouse_grabbed: bool,
@@ -304,6 +1073,47 @@ struct State<'window> {
     dirty_chunks: HashSet<ChunkPos>,
     force_full_remesh: bool,
     debug_mode: bool,
+    /// Exponential moving average of the frame time, in seconds, for the debug overlay.
+    smoothed_frame_dt: f32,
+    /// Bars of the last frame's profiler sections under the debug overlay.
+    profiler_overlay: bool,
+    fluid_debug_view: bool,
+    /// Chunk meshes drawn as edges to inspect meshing (F11 in debug mode).
+    wireframe: bool,
+    help_open: bool,
+    /// First help row shown; the overlay scrolls when the list outgrows the panel.
+    help_scroll: usize,
+    /// Text typed into the `/` command console while it is open.
+    console_input: Option<String>,
+    /// Latest console reply and when it stops being shown.
+    console_message: Option<(String, Instant)>,
+    /// Draws power overlays and terminal arrows through terrain (F10).
+    electrical_xray: bool,
+    /// Always-available circuit totals panel, separate from the F3 debug overlay.
+    electrical_summary_open: bool,
+    /// 0 above water, 1 fully submerged; eased so dipping in and out isn't abrupt.
+    underwater_blend: f32,
+    /// Liquid the view last dipped into, which tints the fade both ways.
+    submerged_fluid: FluidKind,
+    /// Seconds of air left; drains with the head underwater.
+    breath: f32,
+    health: f32,
+    /// Time spent out of air since the last drowning hit.
+    drowning_timer: f32,
+    /// Spyglass key held: the FOV eases toward `ZOOM_FOV_DEG`.
+    zoom_held: bool,
+    /// Left clicks pick measuring tape endpoints instead of breaking blocks.
+    measure_mode: bool,
+    measure_start: Option<MeasurePoint>,
+    /// Live crosshair endpoint until a second click freezes it.
+    measure_end: Option<MeasurePoint>,
+    measure_frozen: bool,
+    /// Left clicks pick the corners of a box to copy; right clicks paste the clipboard.
+    schematic_mode: bool,
+    schematic_corner: Option<(i32, i32, i32)>,
+    clipboard: Option<Schematic>,
+    build_grid_spacing: Option<i32>,
+    build_grid_key: Option<(i32, i32, i32, i32)>,
     paused: bool,
     inventory_open: bool,
     menu_restore_mouse: bool,
@@ -315,18 +1125,44 @@ struct State<'window> {
     settings_fov_deg: f32,
     settings_sensitivity: f32,
     settings_volume: f32,
+    settings_clouds: bool,
+    /// Turns off the hit marker and the sprint FOV kick.
+    settings_reduce_motion: bool,
+    /// Color the selection outline by block category instead of one neutral color.
+    settings_outline_colors: bool,
+    /// 24-hour world clock with a sun/moon icon in the top-right corner.
+    settings_show_clock: bool,
+    /// Flower geometry in chunk meshes; lower levels help dense fields on slow GPUs.
+    settings_foliage_detail: FoliageDetail,
+    /// Sun shadow map resolution, or no shadow pass at all.
+    settings_shadow_quality: ShadowQuality,
+    settings_movement_feel: MovementFeel,
+    /// Shade faces from a chosen sun angle instead of the day cycle's sun.
+    settings_fixed_light: bool,
+    settings_sun_azimuth: f32,
+    settings_sun_elevation: f32,
+    /// Built-in packs plus those found in `TEXTURE_PACK_DIR` at startup.
+    texture_packs: Vec<TexturePack>,
+    settings_texture_pack: usize,
+    /// Chunk radius kept loaded and meshed around the player.
+    settings_render_distance: i32,
+    /// Colour and remaining seconds of the crosshair flash from the last edit.
+    hit_marker: Option<([f32; 3], f32)>,
     settings_cursor_pos: Option<(f32, f32)>,
     settings_active_slider: Option<SettingsSlider>,
-    settings_fov_slider: Cell<Option<Rect>>,
-    settings_sensitivity_slider: Cell<Option<Rect>>,
+    /// Track of each slider as last drawn; `None` while its tab is hidden.
+    settings_slider_rects: Cell<[Option<Rect>; SettingsSlider::ALL.len()]>,
     // Block breaking state
     breaking_block: Option<(i32, i32, i32)>,
     breaking_progress: f32,
     left_mouse_held: bool,
     // Hand animation state
-    placement_progress: f32,
+    hand_animation: HandAnimation,
     // Item entities
     entities: Vec<ItemEntity>,
+    game_mode: GameMode,
+    /// Dying in survival leaves the hotbar alone instead of emptying it.
+    keep_inventory: bool,
     // Crafting system
     crafting_open: bool,
     crafting_grid: [Option<ItemType>; 9],
@@ -339,7 +1175,13 @@ struct State<'window> {
 
 impl<'window> State<'window> {
     fn is_in_menu(&self) -> bool {
-        self.paused || self.inventory_open || self.config_editor.is_some() || self.settings_open || self.crafting_open
+        self.paused
+            || self.inventory_open
+            || self.config_editor.is_some()
+            || self.settings_open
+            || self.crafting_open
+            || self.console_input.is_some()
+            || self.help_open
     }
 
     fn mark_ui_dirty(&mut self) {
@@ -393,8 +1235,7 @@ impl<'window> State<'window> {
         self.settings_open = false;
         self.settings_active_slider = None;
         self.settings_cursor_pos = None;
-        self.settings_fov_slider.set(None);
-        self.settings_sensitivity_slider.set(None);
+        self.settings_slider_rects.set([None; SettingsSlider::ALL.len()]);
         self.exit_menu_mode_if_needed();
         self.mark_ui_dirty();
         println!("Resumed.");
@@ -419,6 +1260,7 @@ impl<'window> State<'window> {
         self.inventory_last_hover_slot = None;
         self.inventory_last_hover_palette = None;
         self.inventory_filter_chip_hover = None;
+        self.inventory_chip_focus = None;
         self.inventory_search_active = false;
         self.inventory_search_query.clear();
         self.inventory_active_category = 0;
@@ -444,6 +1286,7 @@ impl<'window> State<'window> {
         self.inventory_last_hover_slot = None;
         self.inventory_last_hover_palette = None;
         self.inventory_search_active = false;
+        self.inventory_chip_focus = None;
         self.exit_menu_mode_if_needed();
         self.mark_ui_dirty();
         println!("Inventory closed.");
@@ -473,10 +1316,8 @@ impl<'window> State<'window> {
         // Return items from crafting grid to inventory
         for item in self.crafting_grid.iter_mut() {
             if let Some(i) = item.take() {
-                if let Some(slot) = self.inventory.first_empty_slot() {
-                    self.inventory.set_slot(slot, Some(i));
-                }
-                // If no empty slot, item is lost (could drop as entity instead)
+                // If there's no room, the item is lost (could drop as entity instead)
+                self.inventory.add_item(i, 1);
             }
         }
         self.crafting_open = false;
@@ -500,8 +1341,7 @@ impl<'window> State<'window> {
         self.settings_sensitivity = self.controller.sensitivity();
         self.settings_active_slider = None;
         self.settings_cursor_pos = None;
-        self.settings_fov_slider.set(None);
-        self.settings_sensitivity_slider.set(None);
+        self.settings_slider_rects.set([None; SettingsSlider::ALL.len()]);
         self.mark_ui_dirty();
     }
 
@@ -512,8 +1352,7 @@ impl<'window> State<'window> {
         self.settings_open = false;
         self.settings_active_slider = None;
         self.settings_cursor_pos = None;
-        self.settings_fov_slider.set(None);
-        self.settings_sensitivity_slider.set(None);
+        self.settings_slider_rects.set([None; SettingsSlider::ALL.len()]);
         self.mark_ui_dirty();
     }
 
@@ -564,11 +1403,10 @@ impl<'window> State<'window> {
             WindowEvent::MouseInput { state, button, .. } if *button == MouseButton::Left => {
                 if *state == ElementState::Pressed {
                     if let Some(point) = self.settings_cursor_pos {
-                        if self.try_begin_slider_drag(SettingsSlider::Fov, point) {
-                            return true;
-                        }
-                        if self.try_begin_slider_drag(SettingsSlider::Sensitivity, point) {
-                            return true;
+                        for slider in SettingsSlider::ALL {
+                            if self.try_begin_slider_drag(slider, point) {
+                                return true;
+                            }
                         }
                     }
                     false
@@ -598,10 +1436,7 @@ impl<'window> State<'window> {
         if let Some(rect) = self.slider_rect(slider) {
             if point_in_rect(point, rect) {
                 self.settings_active_slider = Some(slider);
-                match slider {
-                    SettingsSlider::Fov => self.settings_focus_index = 0,
-                    SettingsSlider::Sensitivity => self.settings_focus_index = 1,
-                }
+                self.settings_focus_index = slider.focus().1;
                 self.update_slider_from_point(slider, point.0);
                 return true;
             }
@@ -610,33 +1445,55 @@ impl<'window> State<'window> {
     }
 
     fn slider_rect(&self, slider: SettingsSlider) -> Option<Rect> {
+        self.settings_slider_rects.get()[slider as usize]
+    }
+
+    fn set_slider_rect(&self, slider: SettingsSlider, rect: Rect) {
+        let mut rects = self.settings_slider_rects.get();
+        rects[slider as usize] = Some(rect);
+        self.settings_slider_rects.set(rects);
+    }
+
+    fn slider_value(&self, slider: SettingsSlider) -> f32 {
         match slider {
-            SettingsSlider::Fov => self.settings_fov_slider.get(),
-            SettingsSlider::Sensitivity => self.settings_sensitivity_slider.get(),
+            SettingsSlider::Fov => self.settings_fov_deg,
+            SettingsSlider::Sensitivity => self.settings_sensitivity,
+            SettingsSlider::WalkSpeed => self.controller.walk_speed(),
+            SettingsSlider::SprintMultiplier => self.controller.sprint_multiplier(),
+            SettingsSlider::FlySpeed => self.controller.fly_speed(),
         }
     }
 
     fn update_slider_from_point(&mut self, slider: SettingsSlider, cursor_x: f32) {
-        let rect = match slider {
-            SettingsSlider::Fov => self.settings_fov_slider.get(),
-            SettingsSlider::Sensitivity => self.settings_sensitivity_slider.get(),
-        };
-        let Some(rect) = rect else {
+        let Some(rect) = self.slider_rect(slider) else {
             return;
         };
         let width = (rect.1 .0 - rect.0 .0).max(f32::EPSILON);
         let ratio = ((cursor_x - rect.0 .0) / width).clamp(0.0, 1.0);
+        let (min, max) = slider.range();
+        let value = min + ratio * (max - min);
         match slider {
             SettingsSlider::Fov => {
-                self.settings_fov_deg = 60.0 + ratio * 40.0;
+                self.settings_fov_deg = value;
+                self.apply_display_settings();
             }
             SettingsSlider::Sensitivity => {
-                let min = 0.0005;
-                let max = 0.02;
-                self.settings_sensitivity = min + ratio * (max - min);
+                self.settings_sensitivity = value;
+                self.apply_display_settings();
+            }
+            SettingsSlider::WalkSpeed => {
+                self.controller.set_walk_speed(value);
+                self.mark_ui_dirty();
+            }
+            SettingsSlider::SprintMultiplier => {
+                self.controller.set_sprint_multiplier(value);
+                self.mark_ui_dirty();
+            }
+            SettingsSlider::FlySpeed => {
+                self.controller.set_fly_speed(value);
+                self.mark_ui_dirty();
             }
         }
-        self.apply_display_settings();
     }
 
     fn cycle_settings_tab(&mut self, delta: i32) {
@@ -644,8 +1501,7 @@ impl<'window> State<'window> {
         let next = (current + delta).rem_euclid(SettingsTab::ALL.len() as i32) as usize;
         self.settings_selected_tab = SettingsTab::ALL[next];
         self.settings_active_slider = None;
-        self.settings_fov_slider.set(None);
-        self.settings_sensitivity_slider.set(None);
+        self.settings_slider_rects.set([None; SettingsSlider::ALL.len()]);
         let count = self.settings_focus_count();
         if count == 0 {
             self.settings_focus_index = 0;
@@ -657,9 +1513,10 @@ impl<'window> State<'window> {
 
     fn settings_focus_count(&self) -> usize {
         match self.settings_selected_tab {
-            SettingsTab::Display => 2,
+            SettingsTab::Display => 11,
             SettingsTab::Audio => 1,
-            SettingsTab::Controls => 0,
+            SettingsTab::Controls => 6,
+            SettingsTab::Lighting => 3,
         }
     }
 
@@ -689,47 +1546,450 @@ impl<'window> State<'window> {
                         (self.settings_sensitivity + delta * step).clamp(0.0005, 0.02);
                     self.apply_display_settings();
                 }
+                2 => {
+                    self.settings_clouds = !self.settings_clouds;
+                    self.renderer.set_clouds_enabled(self.settings_clouds);
+                    self.mark_ui_dirty();
+                }
+                3 => {
+                    let step = if delta < 0.0 { -1 } else { 1 };
+                    let preset = self.world.atmosphere_preset().cycle(step);
+                    self.world.set_atmosphere_preset(preset);
+                    self.mark_ui_dirty();
+                }
+                4 => {
+                    self.settings_reduce_motion = !self.settings_reduce_motion;
+                    self.hit_marker = None;
+                    self.mark_ui_dirty();
+                }
+                5 => {
+                    self.settings_outline_colors = !self.settings_outline_colors;
+                    self.mark_ui_dirty();
+                }
+                6 => {
+                    self.settings_show_clock = !self.settings_show_clock;
+                    self.mark_ui_dirty();
+                }
+                7 => {
+                    let step = if delta < 0.0 { -1 } else { 1 };
+                    self.settings_foliage_detail = self.settings_foliage_detail.cycle(step);
+                    self.renderer.set_foliage_detail(self.settings_foliage_detail);
+                    self.world_dirty = true;
+                    self.force_full_remesh = true;
+                    self.mark_ui_dirty();
+                }
+                8 => {
+                    let step = if delta < 0.0 { -1 } else { 1 };
+                    let count = self.texture_packs.len() as i32;
+                    self.settings_texture_pack =
+                        (self.settings_texture_pack as i32 + step).rem_euclid(count) as usize;
+                    self.reload_texture_pack();
+                    self.mark_ui_dirty();
+                }
+                9 => {
+                    let step = if delta < 0.0 { -1 } else { 1 };
+                    self.set_render_distance(self.settings_render_distance + step);
+                }
+                10 => {
+                    let step = if delta < 0.0 { -1 } else { 1 };
+                    self.settings_shadow_quality = self.settings_shadow_quality.cycle(step);
+                    self.renderer.set_shadow_quality(self.settings_shadow_quality);
+                    self.mark_ui_dirty();
+                }
                 _ => {}
             },
             SettingsTab::Audio => {
                 self.settings_volume = (self.settings_volume + delta * 0.05).clamp(0.0, 1.0);
+                self.audio.set_volume(self.settings_volume);
+                self.mark_ui_dirty();
+            }
+            SettingsTab::Controls => match self.settings_focus_index {
+                0 => {
+                    let enabled = !self.controller.auto_jump();
+                    self.controller.set_auto_jump(enabled);
+                    self.mark_ui_dirty();
+                }
+                1 => {
+                    let step = if delta < 0.0 { -1 } else { 1 };
+                    self.settings_movement_feel = self.settings_movement_feel.cycle(step);
+                    self.controller
+                        .set_movement_model(self.settings_movement_feel.model());
+                    self.mark_ui_dirty();
+                }
+                2 => {
+                    let sensitivity = self.controller.stick_sensitivity() + delta * 0.1;
+                    self.controller.set_stick_sensitivity(sensitivity);
+                    self.mark_ui_dirty();
+                }
+                3 => {
+                    let speed = self.controller.walk_speed() + delta;
+                    self.controller.set_walk_speed(speed);
+                    self.mark_ui_dirty();
+                }
+                4 => {
+                    let multiplier = self.controller.sprint_multiplier() + delta * 0.1;
+                    self.controller.set_sprint_multiplier(multiplier);
+                    self.mark_ui_dirty();
+                }
+                5 => {
+                    let speed = self.controller.fly_speed() + delta * 2.0;
+                    self.controller.set_fly_speed(speed);
+                    self.mark_ui_dirty();
+                }
+                _ => {}
+            },
+            SettingsTab::Lighting => {
+                match self.settings_focus_index {
+                    0 => self.settings_fixed_light = !self.settings_fixed_light,
+                    1 => {
+                        self.settings_sun_azimuth =
+                            (self.settings_sun_azimuth + delta * 5.0).rem_euclid(360.0);
+                    }
+                    2 => {
+                        self.settings_sun_elevation =
+                            (self.settings_sun_elevation + delta * 5.0).clamp(5.0, 90.0);
+                    }
+                    _ => {}
+                }
+                self.apply_lighting_settings();
                 self.mark_ui_dirty();
             }
-            SettingsTab::Controls => {}
         }
     }
 
+    fn apply_lighting_settings(&mut self) {
+        let direction = self.settings_fixed_light.then(|| {
+            WorldEnvironment::direction_from_angles(
+                self.settings_sun_azimuth,
+                self.settings_sun_elevation,
+            )
+        });
+        self.renderer.set_fixed_light_direction(direction);
+    }
+
     fn apply_display_settings(&mut self) {
         self.projection
-            .set_target_fov(Rad(self.settings_fov_deg.to_radians()));
+            .set_base_fov(Rad(self.settings_fov_deg.to_radians()));
         self.controller.set_sensitivity(self.settings_sensitivity);
-        self.renderer.update_camera(&self.camera, &self.projection);
+        let view_camera = self.free_camera.as_ref().unwrap_or(&self.camera);
+        self.renderer.update_camera(view_camera, &self.projection);
         self.mark_ui_dirty();
     }
 
-    fn hotbar_state(&self) -> HotbarState {
-        if self.controller.noclip {
-            HotbarState::Noclip
-        } else if self.player_is_submerged() {
-            HotbarState::Underwater
+    /// Switches to `pack`, adding it to the Texture Pack cycle if discovery missed it.
+    fn use_texture_pack(&mut self, pack: TexturePack) {
+        self.settings_texture_pack = match self.texture_packs.iter().position(|p| *p == pack) {
+            Some(index) => index,
+            None => {
+                self.texture_packs.push(pack);
+                self.texture_packs.len() - 1
+            }
+        };
+        self.reload_texture_pack();
+    }
+
+    /// Uploads the selected pack and re-derives the colours the palette sorts by.
+    fn reload_texture_pack(&mut self) {
+        let pack = &self.texture_packs[self.settings_texture_pack];
+        self.renderer.reload_atlas(pack);
+        self.palette_color_keys = block_color_keys(pack);
+        if self.inventory_palette_sort == PaletteSort::Color {
+            self.refresh_palette_filter();
+        }
+    }
+
+    /// Grows or shrinks the loaded area and remeshes so the old edge doesn't linger.
+    fn set_render_distance(&mut self, distance: i32) {
+        let distance = distance.clamp(MIN_RENDER_DISTANCE, MAX_RENDER_DISTANCE);
+        if distance == self.settings_render_distance {
+            return;
+        }
+        self.settings_render_distance = distance;
+        self.projection
+            .set_zfar(far_plane_for(distance, self.world.height()));
+        self.renderer.set_view_distance(distance);
+        let view_camera = self.free_camera.as_ref().unwrap_or(&self.camera);
+        self.renderer.update_camera(view_camera, &self.projection);
+        // New chunks stream in over the next frames; a shrink drops the far ring right away.
+        let changes =
+            self.world
+                .update_loaded_chunks_within_budget(self.camera.position, distance, 0);
+        self.renderer.unload_chunks(&changes.unloaded);
+        self.dirty_chunks.clear();
+        self.world_dirty = true;
+        self.force_full_remesh = true;
+        self.mark_ui_dirty();
+    }
+
+    fn toggle_free_look(&mut self) {
+        self.controller.reset_motion();
+        if self.free_camera.take().is_some() {
+            println!("Free-look OFF - camera returned to the player");
         } else {
-            HotbarState::Normal
+            self.free_camera = Some(Camera::new(
+                self.camera.position,
+                self.camera.yaw,
+                self.camera.pitch,
+            ));
+            println!("Free-look ON - player frozen, camera flies freely");
+        }
+        let view_camera = self.free_camera.as_ref().unwrap_or(&self.camera);
+        self.renderer.update_camera(view_camera, &self.projection);
+        self.mark_ui_dirty();
+    }
+
+    /// Swaps in a loaded world, carrying over the runtime options that came from flags or
+    /// settings, and remeshes everything on the next frame.
+    fn replace_world(&mut self, mut world: World) {
+        world.set_fluid_spread_limit(self.world.fluid_spread_limit());
+        world.set_atmosphere_preset(self.world.atmosphere_preset());
+        world
+            .electrical_mut()
+            .set_water_leakage(self.world.electrical().water_leakage());
+        *world.environment_mut() = self.world.environment().clone();
+        self.world = world;
+        self.dirty_chunks.clear();
+        self.world_dirty = true;
+        self.force_full_remesh = true;
+        self.refresh_look_target();
+        self.refresh_inspect_info();
+    }
+
+    fn toggle_photo_mode(&mut self) {
+        self.photo_mode = !self.photo_mode;
+        if self.photo_mode {
+            self.photo_owns_free_camera = self.free_camera.is_none();
+            if self.photo_owns_free_camera {
+                self.toggle_free_look();
+            }
+            self.photo_focus = self.photo_focus_target();
+            println!("Photo mode ON - HUD hidden, B toggles depth of field");
+        } else {
+            if self.photo_owns_free_camera && self.free_camera.is_some() {
+                self.toggle_free_look();
+            }
+            self.photo_owns_free_camera = false;
+            println!("Photo mode OFF");
         }
+        self.sync_depth_of_field();
+        self.refresh_look_target();
+        self.mark_ui_dirty();
     }
 
-    fn hotbar_theme(&self) -> HotbarTheme {
-        match self.hotbar_state() {
-            HotbarState::Normal => HotbarTheme {
-                panel_border: [0.06, 0.07, 0.12, 0.96],
-                panel_fill: [0.04, 0.05, 0.08, 0.88],
-                panel_highlight: [0.34, 0.52, 0.86, 0.28],
-                slot_default: [0.16, 0.19, 0.27, 0.88],
-                slot_selected: [0.28, 0.36, 0.55, 0.95],
-                status: None,
-            },
-            HotbarState::Noclip => HotbarTheme {
-                panel_border: [0.14, 0.08, 0.24, 0.96],
-                panel_fill: [0.1, 0.05, 0.18, 0.9],
+    /// Distance from the viewing camera to the block under the crosshair.
+    fn photo_focus_target(&self) -> f32 {
+        let camera = self.free_camera.as_ref().unwrap_or(&self.camera);
+        let direction = self
+            .projection
+            .ray_direction(camera, self.crosshair_screen_uv());
+        raycast_with_mode(
+            &self.world,
+            camera.position,
+            direction,
+            PHOTO_FOCUS_REACH,
+            RaycastMode::BlocksAndFluids,
+        )
+        .map(|hit| (hit.point - camera.position).magnitude())
+        .unwrap_or(PHOTO_FOCUS_REACH)
+    }
+
+    fn sync_depth_of_field(&mut self) {
+        let focus = (self.photo_mode && self.photo_blur).then_some(self.photo_focus);
+        self.renderer.set_depth_of_field(focus);
+    }
+
+    fn toggle_measure_mode(&mut self) {
+        if self.schematic_mode {
+            self.toggle_schematic_mode();
+        }
+        self.measure_mode = !self.measure_mode;
+        self.left_mouse_held = false;
+        self.breaking_block = None;
+        self.breaking_progress = 0.0;
+        if !self.measure_mode {
+            self.clear_measurement();
+        }
+        println!(
+            "Measuring tape: {}",
+            if self.measure_mode { "ON (click two blocks)" } else { "OFF" }
+        );
+        self.mark_ui_dirty();
+    }
+
+    /// First click anchors the tape, second freezes it, third clears it.
+    fn measure_click(&mut self) {
+        if self.measure_frozen {
+            self.clear_measurement();
+            return;
+        }
+        let direction = self.crosshair_direction();
+        let Some(hit) = raycast(&self.world, self.camera.position, direction, MEASURE_REACH) else {
+            return;
+        };
+        let point = MeasurePoint::from_hit(&hit);
+        if self.measure_start.is_none() {
+            self.measure_start = Some(point);
+            self.measure_end = Some(point);
+        } else {
+            self.measure_end = Some(point);
+            self.measure_frozen = true;
+            if let Some(measurement) = self.measurement() {
+                let (dx, dy, dz) = measurement.delta();
+                println!(
+                    "Measured dx {dx} dy {dy} dz {dz} | euclidean {:.2} | manhattan {}",
+                    measurement.euclidean(),
+                    measurement.manhattan()
+                );
+            }
+        }
+        self.refresh_measure_line();
+    }
+
+    fn clear_measurement(&mut self) {
+        self.measure_start = None;
+        self.measure_end = None;
+        self.measure_frozen = false;
+        self.refresh_measure_line();
+    }
+
+    fn measurement(&self) -> Option<Measurement> {
+        Some(Measurement {
+            start: self.measure_start?.block,
+            end: self.measure_end?.block,
+        })
+    }
+
+    /// Follows the crosshair with the tape's loose end until it is frozen.
+    fn track_measure_target(&mut self, in_menu: bool) {
+        if in_menu || self.measure_start.is_none() || self.measure_frozen {
+            return;
+        }
+        let direction = self.crosshair_direction();
+        let target = raycast(&self.world, self.camera.position, direction, MEASURE_REACH)
+            .map(|hit| MeasurePoint::from_hit(&hit));
+        if target.is_some() && target != self.measure_end {
+            self.measure_end = target;
+            self.refresh_measure_line();
+        }
+    }
+
+    fn refresh_measure_line(&mut self) {
+        let segment = self
+            .measure_start
+            .zip(self.measure_end)
+            .map(|(start, end)| (start.anchor, end.anchor));
+        self.renderer.update_measure_line(segment);
+        self.mark_ui_dirty();
+    }
+
+    fn toggle_schematic_mode(&mut self) {
+        if self.measure_mode {
+            self.toggle_measure_mode();
+        }
+        self.schematic_mode = !self.schematic_mode;
+        self.left_mouse_held = false;
+        self.breaking_block = None;
+        self.breaking_progress = 0.0;
+        self.schematic_corner = None;
+        println!(
+            "Schematic clipboard: {}",
+            if self.schematic_mode {
+                "ON (click two corners to copy, right click to paste)"
+            } else {
+                "OFF"
+            }
+        );
+        self.mark_ui_dirty();
+    }
+
+    /// First click marks one corner of the box, second copies it into the clipboard.
+    fn schematic_click(&mut self) {
+        let direction = self.crosshair_direction();
+        let Some(hit) = raycast(&self.world, self.camera.position, direction, MEASURE_REACH) else {
+            return;
+        };
+        let Some(corner) = self.schematic_corner.take() else {
+            self.schematic_corner = Some(hit.block_pos);
+            self.mark_ui_dirty();
+            return;
+        };
+        let size = [
+            corner.0.abs_diff(hit.block_pos.0),
+            corner.1.abs_diff(hit.block_pos.1),
+            corner.2.abs_diff(hit.block_pos.2),
+        ];
+        let volume: i64 = size.iter().map(|&extent| i64::from(extent) + 1).product();
+        if volume > SCHEMATIC_MAX_VOLUME {
+            println!(
+                "Selection of {volume} blocks is too big to copy (max {SCHEMATIC_MAX_VOLUME})"
+            );
+        } else {
+            let schematic = self.world.copy_region(corner, hit.block_pos);
+            let (x, y, z) = schematic.size();
+            println!(
+                "Copied {x}x{y}x{z} blocks and {} electrical parts",
+                schematic.components().len()
+            );
+            self.clipboard = Some(schematic);
+        }
+        self.mark_ui_dirty();
+    }
+
+    /// Pastes the clipboard with its lowest corner against the face under the crosshair.
+    fn schematic_paste(&mut self) {
+        if self.game_mode.consumes_blocks() {
+            println!("Pasting schematics is only available in creative mode");
+            return;
+        }
+        let Some(schematic) = &self.clipboard else {
+            return;
+        };
+        let direction = self.crosshair_direction();
+        let Some(hit) = raycast(&self.world, self.camera.position, direction, MEASURE_REACH) else {
+            return;
+        };
+        let origin = (
+            hit.block_pos.0 + hit.normal.x as i32,
+            hit.block_pos.1 + hit.normal.y as i32,
+            hit.block_pos.2 + hit.normal.z as i32,
+        );
+        let touched = self.world.paste_region(origin, schematic);
+        self.world_dirty = true;
+        if !self.force_full_remesh {
+            let loaded = touched
+                .into_iter()
+                .filter(|pos| self.world.chunks().contains_key(pos));
+            self.dirty_chunks.extend(loaded);
+        }
+        self.refresh_look_target();
+        self.refresh_inspect_info();
+    }
+
+    fn hotbar_state(&self) -> HotbarState {
+        if self.controller.noclip {
+            HotbarState::Noclip
+        } else if self.player_is_submerged() {
+            HotbarState::Underwater
+        } else if self.controller.is_sneaking() {
+            HotbarState::Sneaking
+        } else {
+            HotbarState::Normal
+        }
+    }
+
+    fn hotbar_theme(&self) -> HotbarTheme {
+        match self.hotbar_state() {
+            HotbarState::Normal => HotbarTheme {
+                panel_border: [0.06, 0.07, 0.12, 0.96],
+                panel_fill: [0.04, 0.05, 0.08, 0.88],
+                panel_highlight: [0.34, 0.52, 0.86, 0.28],
+                slot_default: [0.16, 0.19, 0.27, 0.88],
+                slot_selected: [0.28, 0.36, 0.55, 0.95],
+                status: None,
+            },
+            HotbarState::Noclip => HotbarTheme {
+                panel_border: [0.14, 0.08, 0.24, 0.96],
+                panel_fill: [0.1, 0.05, 0.18, 0.9],
                 panel_highlight: [0.54, 0.38, 0.86, 0.32],
                 slot_default: [0.2, 0.13, 0.28, 0.88],
                 slot_selected: [0.48, 0.34, 0.7, 0.95],
@@ -753,28 +2013,59 @@ impl<'window> State<'window> {
                     chip_text: [0.9, 0.97, 1.0, 1.0],
                 }),
             },
+            HotbarState::Sneaking => HotbarTheme {
+                panel_border: [0.14, 0.12, 0.06, 0.96],
+                panel_fill: [0.1, 0.09, 0.05, 0.9],
+                panel_highlight: [0.7, 0.58, 0.3, 0.3],
+                slot_default: [0.22, 0.2, 0.14, 0.88],
+                slot_selected: [0.52, 0.44, 0.24, 0.95],
+                status: Some(HotbarStatusData {
+                    label: "SNEAKING",
+                    detail: Some("Release Shift to stand"),
+                    chip_fill: [0.6, 0.48, 0.2, 0.95],
+                    chip_text: [1.0, 0.97, 0.9, 1.0],
+                }),
+            },
         }
     }
 
     fn player_is_submerged(&self) -> bool {
-        let pos = self.camera.position;
-        let x = pos.x.floor() as i32;
-        let y = pos.y.floor() as i32;
-        let z = pos.z.floor() as i32;
-        matches!(self.world.get_block(x, y, z), BlockType::Water)
+        self.is_submerged_at(self.camera.position)
     }
 
-    fn new(window: &'window Window) -> anyhow::Result<Self> {
+    /// True when `pos` sits below the surface of the fluid in the cell containing it.
+    fn is_submerged_at(&self, pos: Point3<f32>) -> bool {
+        self.submerged_fluid_at(pos).is_some()
+    }
+
+    /// The liquid whose surface `pos` sits below, if any.
+    fn submerged_fluid_at(&self, pos: Point3<f32>) -> Option<FluidKind> {
+        // Cells are centred on integer coordinates.
+        let x = pos.x.round() as i32;
+        let y = pos.y.round() as i32;
+        let z = pos.z.round() as i32;
+        let amount = self.world.get_fluid_amount(x, y, z);
+        let surface = y as f32 - 0.5 + amount as f32 / MAX_FLUID_LEVEL as f32;
+        (amount > 0 && pos.y < surface).then(|| self.world.get_fluid_kind(x, y, z))
+    }
+
+    fn new(
+        window: &'window Window,
+        gen_config: WorldGenConfig,
+        seed: Option<u64>,
+    ) -> anyhow::Result<Self> {
         let size = window.inner_size();
 
-        let projection =
+        let mut projection =
             Projection::new(size.width, size.height, 45.0_f32.to_radians(), 0.1, 1000.0);
+        projection.set_zfar(far_plane_for(RENDER_DISTANCE, gen_config.height));
         let ui_scaler = UiScaler::new(projection.aspect());
         let settings_fov_deg = projection.base_fov().0.to_degrees();
 
-        let renderer = Renderer::new(&window).context("failed to create renderer")?;
+        let mut renderer = Renderer::new(&window).context("failed to create renderer")?;
+        renderer.set_view_distance(RENDER_DISTANCE);
         let fluid_system = FluidSystem::new(renderer.device_handle(), renderer.queue_handle());
-        let mut world = World::new();
+        let mut world = create_world(gen_config, seed);
 
         let spawn_x = 0.5;
         let spawn_z = 0.5;
@@ -782,22 +2073,19 @@ impl<'window> State<'window> {
         let controller = CameraController::new(15.0, 0.0025);
         let settings_sensitivity = controller.sensitivity();
         let settings_volume = 0.8;
-        let inventory = Inventory::new();
-
-        let _ = world.update_loaded_chunks(camera.position, 3);
-
-        let column_x = camera.position.x.floor() as i32;
-        let column_z = camera.position.z.floor() as i32;
-        if let Some(surface_y) = find_surface_level(&world, column_x, column_z) {
-            camera.position.y = surface_y + PLAYER_EYE_HEIGHT + 0.05;
-        }
-        for _ in 0..50 {
-            if !player_aabb_collides(&world, camera.position) {
-                break;
+        let mut inventory = Inventory::new();
+        let loadouts_path = Path::new(LOADOUTS_FILE);
+        if loadouts_path.exists() {
+            if let Err(err) = inventory.load_loadouts_from_path(loadouts_path) {
+                eprintln!("Ignoring {LOADOUTS_FILE}: {err:#}");
             }
-            camera.position.y += 0.1;
         }
 
+        let _ = world.update_loaded_chunks(camera.position, RENDER_DISTANCE);
+
+        let spawn = world.find_spawn(camera.position);
+        camera.position = point3(spawn.x, spawn.y + PLAYER_EYE_HEIGHT + 0.05, spawn.z);
+
         let mut state = Self {
             window,
             renderer,
@@ -814,21 +2102,39 @@ impl<'window> State<'window> {
             inventory_cursor_pos: None,
             inventory_drag_origin: None,
             inventory_drag_block: None,
+            inventory_trashed: None,
             inventory_swap_slot: None,
             inventory_last_hover_slot: None,
             inventory_last_hover_palette: None,
             inventory_filter_chip_hover: None,
+            inventory_chip_focus: None,
             inventory_active_category: 0,
             inventory_search_query: String::new(),
             inventory_search_active: false,
             inventory_palette_scroll: 0.0,
             inventory_palette_filtered: Vec::new(),
+            inventory_palette_sort: PaletteSort::default(),
+            palette_color_keys: block_color_keys(&TexturePack::Default),
             last_frame: Instant::now(),
+            look_target: None,
             highlight_target: None,
+            dial_target: None,
             inspect_info: None,
+            inspect_seen_at: Instant::now(),
             config_editor: None,
             tick_accumulator: 0.0,
             animation_time: 0.0,
+            animations_frozen: false,
+            animation_time_scale: 1.0,
+            free_camera: None,
+            free_look_key: DEFAULT_FREE_LOOK_KEY,
+            photo_mode: false,
+            photo_owns_free_camera: false,
+            photo_blur: true,
+            photo_focus: PHOTO_FOCUS_REACH,
+            audio: AudioSystem::new(settings_volume),
+            gamepad: Gamepad::new(),
+            footstep_distance: 0.0,
             debug_tick_counter: 0,
             water_tick_counter: 0,
             mouse_grabbed: false,
@@ -836,6 +2142,31 @@ impl<'window> State<'window> {
             dirty_chunks: HashSet::new(),
             force_full_remesh: true,
             debug_mode: false,
+            smoothed_frame_dt: FIXED_TICK_STEP,
+            profiler_overlay: false,
+            fluid_debug_view: false,
+            wireframe: false,
+            help_open: false,
+            help_scroll: 0,
+            console_input: None,
+            console_message: None,
+            electrical_xray: false,
+            electrical_summary_open: false,
+            underwater_blend: 0.0,
+            submerged_fluid: FluidKind::Water,
+            breath: MAX_BREATH,
+            health: MAX_HEALTH,
+            drowning_timer: 0.0,
+            zoom_held: false,
+            measure_mode: false,
+            measure_start: None,
+            measure_end: None,
+            measure_frozen: false,
+            schematic_mode: false,
+            schematic_corner: None,
+            clipboard: None,
+            build_grid_spacing: None,
+            build_grid_key: None,
             paused: false,
             inventory_open: false,
             menu_restore_mouse: false,
@@ -847,15 +2178,31 @@ impl<'window> State<'window> {
             settings_fov_deg,
             settings_sensitivity,
             settings_volume,
+            settings_clouds: true,
+            settings_reduce_motion: false,
+            settings_outline_colors: true,
+            settings_show_clock: false,
+            settings_foliage_detail: FoliageDetail::default(),
+            settings_shadow_quality: ShadowQuality::default(),
+            settings_movement_feel: MovementFeel::default(),
+            settings_fixed_light: false,
+            // Roughly the direction faces were shaded from before the sun moved.
+            settings_sun_azimuth: 30.0,
+            settings_sun_elevation: 60.0,
+            texture_packs: TexturePack::discover(Path::new(TEXTURE_PACK_DIR)),
+            settings_render_distance: RENDER_DISTANCE,
+            settings_texture_pack: 0,
+            hit_marker: None,
             settings_cursor_pos: None,
             settings_active_slider: None,
-            settings_fov_slider: Cell::new(None),
-            settings_sensitivity_slider: Cell::new(None),
+            settings_slider_rects: Cell::new([None; SettingsSlider::ALL.len()]),
             breaking_block: None,
             breaking_progress: 0.0,
             left_mouse_held: false,
-            placement_progress: 0.0,
+            hand_animation: HandAnimation::default(),
             entities: Vec::new(),
+            game_mode: GameMode::default(),
+            keep_inventory: false,
             crafting_open: false,
             crafting_grid: [None; 9],
             crafting_system: CraftingSystem::new(),
@@ -899,16 +2246,26 @@ impl<'window> State<'window> {
     }
 
     fn input(&mut self, event: &WindowEvent) -> bool {
+        if self.help_open && self.handle_help_input(event) {
+            return true;
+        }
         if let WindowEvent::KeyboardInput { event, .. } = event {
             if let PhysicalKey::Code(key) = event.physical_key {
                 if event.state == ElementState::Pressed {
                     if self.settings_open && self.handle_settings_key(key) {
                         return true;
                     }
+                    if self.console_input.is_some() {
+                        return self.handle_console_key(key);
+                    }
                     if self.handle_config_key(key) {
                         return true;
                     }
                     match key {
+                        HELP_KEY => {
+                            self.open_help();
+                            return true;
+                        }
                         KeyCode::Escape => {
                             if self.settings_open {
                                 self.close_settings();
@@ -917,6 +2274,11 @@ impl<'window> State<'window> {
                             } else if self.inventory_open {
                                 self.close_inventory();
                                 self.close_pause();
+                            } else if self.measure_start.is_some() {
+                                self.clear_measurement();
+                            } else if self.schematic_corner.is_some() {
+                                self.schematic_corner = None;
+                                self.mark_ui_dirty();
                             } else {
                                 self.open_pause();
                             }
@@ -991,6 +2353,24 @@ impl<'window> State<'window> {
                     }
                 } else {
                     match button {
+                        MouseButton::Left if self.measure_mode => {
+                            if *state == ElementState::Pressed {
+                                self.measure_click();
+                            }
+                            return true;
+                        }
+                        MouseButton::Left if self.schematic_mode => {
+                            if *state == ElementState::Pressed {
+                                self.schematic_click();
+                            }
+                            return true;
+                        }
+                        MouseButton::Right if self.schematic_mode => {
+                            if *state == ElementState::Pressed {
+                                self.schematic_paste();
+                            }
+                            return true;
+                        }
                         MouseButton::Left => {
                             if *state == ElementState::Pressed {
                                 self.left_mouse_held = true;
@@ -1022,7 +2402,6 @@ impl<'window> State<'window> {
                     if ch.is_control() {
                         continue;
                     }
-                    let ch = ch.to_ascii_uppercase();
                     if !(ch.is_ascii_alphanumeric() || ch == ' ') {
                         continue;
                     }
@@ -1041,11 +2420,32 @@ impl<'window> State<'window> {
             }
 
             WindowEvent::KeyboardInput { event, .. } => {
+                if event.physical_key == PhysicalKey::Code(ZOOM_KEY) {
+                    self.zoom_held = event.state == ElementState::Pressed;
+                    return true;
+                }
                 if event.state == ElementState::Pressed {
                     if let PhysicalKey::Code(key) = event.physical_key {
                         if self.handle_config_key(key) {
                             return true;
                         }
+                        if key == self.free_look_key {
+                            self.toggle_free_look();
+                            return true;
+                        }
+                        if key == PHOTO_MODE_KEY {
+                            self.toggle_photo_mode();
+                            return true;
+                        }
+                        if key == PHOTO_BLUR_KEY && self.photo_mode {
+                            self.photo_blur = !self.photo_blur;
+                            println!(
+                                "Depth of field: {}",
+                                if self.photo_blur { "ON" } else { "OFF" }
+                            );
+                            self.sync_depth_of_field();
+                            return true;
+                        }
                         match key {
                             KeyCode::Digit1 => {
                                 self.inventory.select_slot(0);
@@ -1113,12 +2513,81 @@ impl<'window> State<'window> {
                                 println!("========================================\n");
                                 return true;
                             }
+                            KeyCode::KeyG => {
+                                self.cycle_build_grid();
+                                return true;
+                            }
+                            KeyCode::KeyM => {
+                                self.toggle_measure_mode();
+                                return true;
+                            }
+                            SCHEMATIC_KEY => {
+                                self.toggle_schematic_mode();
+                                return true;
+                            }
+                            CONSOLE_KEY => {
+                                self.open_console();
+                                return true;
+                            }
                             KeyCode::F3 => {
                                 self.debug_mode = !self.debug_mode;
                                 println!(
                                     "Debug Mode: {}",
                                     if self.debug_mode { "ON" } else { "OFF" }
                                 );
+                                // The wireframe toggle lives under F3, so it leaves with it.
+                                if !self.debug_mode && self.wireframe {
+                                    self.toggle_wireframe();
+                                }
+                                self.mark_ui_dirty();
+                                return true;
+                            }
+                            PROFILER_OVERLAY_KEY => {
+                                self.profiler_overlay = !self.profiler_overlay;
+                                self.mark_ui_dirty();
+                                return true;
+                            }
+                            KeyCode::F4 => {
+                                self.animations_frozen = !self.animations_frozen;
+                                println!(
+                                    "Animations: {}",
+                                    if self.animations_frozen { "FROZEN" } else { "RUNNING" }
+                                );
+                                self.mark_ui_dirty();
+                                return true;
+                            }
+                            KeyCode::F8 => {
+                                self.export_telemetry_snapshot();
+                                return true;
+                            }
+                            KeyCode::F9 => {
+                                self.toggle_fluid_debug_view();
+                                return true;
+                            }
+                            WIREFRAME_KEY if self.debug_mode => {
+                                self.toggle_wireframe();
+                                return true;
+                            }
+                            KeyCode::F6 => {
+                                self.electrical_summary_open = !self.electrical_summary_open;
+                                self.mark_ui_dirty();
+                                return true;
+                            }
+                            KeyCode::F10 => {
+                                self.electrical_xray = !self.electrical_xray;
+                                self.renderer.set_electrical_xray(self.electrical_xray);
+                                println!(
+                                    "Electrical x-ray: {}",
+                                    if self.electrical_xray { "ON" } else { "OFF" }
+                                );
+                                return true;
+                            }
+                            KeyCode::BracketLeft => {
+                                self.scale_animation_time(0.5);
+                                return true;
+                            }
+                            KeyCode::BracketRight => {
+                                self.scale_animation_time(2.0);
                                 return true;
                             }
                             _ => {}
@@ -1132,6 +2601,9 @@ impl<'window> State<'window> {
                         MouseScrollDelta::LineDelta(_, y) => -(*y as i32),
                         MouseScrollDelta::PixelDelta(pos) => -(pos.y.signum() as i32),
                     };
+                    if self.turn_highlighted_potentiometer(-scroll) {
+                        return true;
+                    }
                     self.inventory.cycle_selection(scroll);
                     self.print_selected();
                     self.mark_ui_dirty();
@@ -1196,17 +2668,16 @@ impl<'window> State<'window> {
                                     if self.crafting_grid[idx].is_some() {
                                         // Remove item from grid, put back in inventory
                                         if let Some(item) = self.crafting_grid[idx].take() {
-                                            if let Some(slot) = self.inventory.first_empty_slot() {
-                                                self.inventory.set_slot(slot, Some(item));
+                                            if self.inventory.add_item(item, 1) == 0 {
                                                 println!("Removed {} from crafting grid", item.name());
                                             }
                                         }
                                     } else {
                                         // Place selected hotbar item in grid
-                                        if let Some(item) = self.inventory.selected_item() {
+                                        // Take one from the selected hotbar stack
+                                        let slot = self.inventory.selected_slot_index();
+                                        if let Some(item) = self.inventory.remove_one(slot) {
                                             self.crafting_grid[idx] = Some(item);
-                                            // Remove from hotbar
-                                            self.inventory.clear_slot(self.inventory.selected_slot_index());
                                             println!("Placed {} in crafting grid", item.name());
                                         }
                                     }
@@ -1228,9 +2699,8 @@ impl<'window> State<'window> {
                                 // Clear crafting grid
                                 self.crafting_grid = [None; 9];
                                 // Add output to inventory
-                                if let Some(slot) = self.inventory.first_empty_slot() {
-                                    // For now, just add one item (TODO: handle output_count > 1)
-                                    self.inventory.set_slot(slot, Some(output_item));
+                                let count = output_count.min(u16::MAX as usize) as u16;
+                                if self.inventory.add_item(output_item, count) < count {
                                     println!("Crafted {} (x{})", output_item.name(), output_count);
                                 } else {
                                     println!("Inventory full! Can't craft.");
@@ -1248,17 +2718,18 @@ impl<'window> State<'window> {
     }
 
     fn break_block(&mut self) {
-        let direction = self.crosshair_direction();
-        if let Some(hit) = raycast(&self.world, self.camera.position, direction, 5.0) {
-            let face = BlockFace::from_normal_f32(hit.normal)
-                .or_else(|| BlockFace::from_normal_f32(-hit.normal))
-                .unwrap_or(BlockFace::Top);
-            if self.world.remove_electrical_face(
-                hit.block_pos.0,
-                hit.block_pos.1,
-                hit.block_pos.2,
-                face,
-            ) {
+        // Fluid surfaces are only targeted for lily pads; there is nothing to break there.
+        if let Some(target) = self.looked_at().filter(|target| !target.hit.fluid) {
+            let hit = target.hit;
+            let face = target.face;
+            let (x, y, z) = hit.block_pos;
+            let component = target.component.map(|(component, _)| component);
+            if self.world.remove_electrical_face(x, y, z, face) {
+                if let Some(component) = component {
+                    self.drop_item(hit.block_pos, ItemType::Block(component.block_type()));
+                }
+                self.audio.play(SoundMaterial::Metal, SoundEvent::Break);
+                self.trigger_hit_marker(HIT_MARKER_BREAK_COLOR);
                 self.mark_block_dirty(hit.block_pos.0, hit.block_pos.1, hit.block_pos.2);
                 self.refresh_inspect_info();
             } else {
@@ -1270,13 +2741,21 @@ impl<'window> State<'window> {
                 );
 
                 // Spawn item entity if block is droppable
-                if block != BlockType::Air && block != BlockType::Water {
-                    let item_pos = Point3::new(
-                        hit.block_pos.0 as f32 + 0.5,
-                        hit.block_pos.1 as f32 + 0.5,
-                        hit.block_pos.2 as f32 + 0.5,
+                if block != BlockType::Air && FluidKind::from_block(block).is_none() {
+                    if !block.is_electrical() {
+                        self.drop_item(hit.block_pos, ItemType::Block(block.dropped_as()));
+                    }
+                    self.play_block_sound(block, SoundEvent::Break);
+                    let light = self.world.get_light(x, y, z) as f32;
+                    self.renderer.emit_break_particles(
+                        block,
+                        Point3::new(x as f32, y as f32, z as f32),
+                        light,
                     );
-                    self.entities.push(ItemEntity::new(item_pos, ItemType::Block(block)));
+                }
+                // Breaking the host takes its attachments along; hand them back too.
+                for component in self.world.electrical_components_at(x, y, z) {
+                    self.drop_item(hit.block_pos, ItemType::Block(component.block_type()));
                 }
 
                 self.world.set_block(
@@ -1285,17 +2764,69 @@ impl<'window> State<'window> {
                     hit.block_pos.2,
                     BlockType::Air,
                 );
+                self.trigger_hit_marker(HIT_MARKER_BREAK_COLOR);
                 self.mark_block_dirty(hit.block_pos.0, hit.block_pos.1, hit.block_pos.2);
             }
+            self.refresh_look_target();
+        }
+    }
+
+    /// Spawns a pickup for a broken block; creative breaks leave nothing behind.
+    fn drop_item(&mut self, block_pos: (i32, i32, i32), item: ItemType) {
+        if !self.game_mode.drops_items() {
+            return;
+        }
+        let item_pos = Point3::new(
+            block_pos.0 as f32 + 0.5,
+            block_pos.1 as f32 + 0.5,
+            block_pos.2 as f32 + 0.5,
+        );
+        self.entities.push(ItemEntity::new(item_pos, item));
+    }
+
+    /// Starts the crosshair flash that confirms an edit landed, unless motion is reduced.
+    fn trigger_hit_marker(&mut self, color: [f32; 3]) {
+        if self.settings_reduce_motion {
+            return;
+        }
+        self.hit_marker = Some((color, HIT_MARKER_SECONDS));
+        self.mark_ui_dirty();
+    }
+
+    /// Survival uses up one of the held blocks; the slot empties with the last one.
+    fn consume_placed_block(&mut self) {
+        if self.game_mode.consumes_blocks() {
+            self.inventory.remove_one(self.inventory.selected_slot_index());
+            self.mark_ui_dirty();
         }
     }
 
     fn place_block(&mut self) {
+        if self.free_camera.is_some() {
+            return;
+        }
+        if self.toggle_highlighted_switch() {
+            return;
+        }
+        if let Some(ItemType::Bucket(state)) = self.inventory.selected_item() {
+            self.use_bucket(state);
+            return;
+        }
         if let Some(block_type) = self.inventory.selected_block() {
-            let direction = self.crosshair_direction();
-            if let Some(hit) = raycast(&self.world, self.camera.position, direction, 5.0) {
+            if let Some(target) = self.looked_at() {
+                let hit = target.hit;
+                // The target was resolved for the previous selection if it just changed;
+                // only lily pads go on fluid surfaces, and they go nowhere else.
+                if hit.fluid != (block_type == BlockType::LilyPad) {
+                    return;
+                }
+                // Lily pads float on top of water; lava would only burn them.
+                if hit.fluid && (target.block == BlockType::Lava || hit.normal.y < 0.5) {
+                    return;
+                }
                 if block_type.is_electrical() {
                     self.place_electrical_component(block_type, &hit);
+                    self.refresh_look_target();
                     return;
                 }
 
@@ -1306,9 +2837,10 @@ impl<'window> State<'window> {
                 );
 
                 // Don't place block if it would intersect with the player
-                // Player bounding box: feet at (camera.y - PLAYER_EYE_HEIGHT), head at (camera.y - PLAYER_EYE_HEIGHT + PLAYER_HEIGHT)
-                let player_feet_y = self.camera.position.y - PLAYER_EYE_HEIGHT;
-                let player_head_y = player_feet_y + PLAYER_HEIGHT;
+                // Player bounding box: feet at (camera.y - eye height), head a fixed
+                // distance above the eye
+                let player_feet_y = self.camera.position.y - self.controller.eye_height();
+                let player_head_y = self.camera.position.y + PLAYER_HEIGHT - PLAYER_EYE_HEIGHT;
 
                 // Define player bounding box with proper radius
                 let player_min = (
@@ -1340,13 +2872,30 @@ impl<'window> State<'window> {
                 if existing.is_solid() {
                     return;
                 }
+                // Cells carrying components refuse ordinary blocks; break the parts first.
+                if !self
+                    .world
+                    .electrical_components_at(place_pos.0, place_pos.1, place_pos.2)
+                    .is_empty()
+                {
+                    return;
+                }
+                if matches!(block_type, BlockType::FlowerRose | BlockType::FlowerTulip)
+                    && !self
+                        .world
+                        .get_block(place_pos.0, place_pos.1 - 1, place_pos.2)
+                        .is_solid()
+                {
+                    return;
+                }
 
                 // Place the block
-                if block_type == BlockType::Water {
+                if let Some(fluid) = FluidKind::from_block(block_type) {
                     self.world.add_fluid(
                         place_pos.0,
                         place_pos.1,
                         place_pos.2,
+                        fluid,
                         MAX_FLUID_LEVEL,
                     );
                 } else {
@@ -1358,60 +2907,579 @@ impl<'window> State<'window> {
                         None,
                         None,
                     );
+                    let light = self.world.get_light(place_pos.0, place_pos.1, place_pos.2);
+                    self.renderer.emit_place_particles(
+                        block_type,
+                        Point3::new(place_pos.0 as f32, place_pos.1 as f32, place_pos.2 as f32),
+                        light as f32,
+                    );
                 }
+                self.play_block_sound(block_type, SoundEvent::Place);
+                self.trigger_hit_marker(HIT_MARKER_PLACE_COLOR);
+                self.consume_placed_block();
                 self.mark_block_dirty(place_pos.0, place_pos.1, place_pos.2);
-                // Trigger placement animation
-                self.placement_progress = 1.0;
+                self.hand_animation.start(HandAction::Place);
+                self.refresh_look_target();
             }
         }
     }
 
-    fn place_electrical_component(&mut self, block_type: BlockType, hit: &RaycastHit) {
-        let Some(face) = BlockFace::from_normal_f32(hit.normal) else {
-            return;
-        };
-
-        let axis = self.determine_electrical_axis(block_type, face);
-        self.world.set_block_with_axis(
-            hit.block_pos.0,
-            hit.block_pos.1,
-            hit.block_pos.2,
-            block_type,
-            Some(axis),
-            Some(face),
+    fn toggle_fluid_debug_view(&mut self) {
+        self.fluid_debug_view = !self.fluid_debug_view;
+        if !self.fluid_debug_view {
+            self.fluid_system.set_debug_region(None);
+            self.renderer.update_fluid_debug(&[]);
+        }
+        println!(
+            "Fluid debug view: {}",
+            if self.fluid_debug_view { "ON" } else { "OFF" }
         );
-        self.mark_block_dirty(hit.block_pos.0, hit.block_pos.1, hit.block_pos.2);
-        self.refresh_inspect_info();
-        // Trigger placement animation
-        self.placement_progress = 1.0;
     }
 
-    fn mark_block_dirty(&mut self, world_x: i32, _world_y: i32, world_z: i32) {
-        self.world_dirty = true;
-        if self.force_full_remesh {
+    fn toggle_wireframe(&mut self) {
+        if !self.renderer.supports_wireframe() {
+            println!("Wireframe view: not supported by this GPU (needs line polygon mode)");
             return;
         }
+        self.wireframe = !self.wireframe;
+        self.renderer.set_wireframe(self.wireframe);
+        println!("Wireframe view: {}", if self.wireframe { "ON" } else { "OFF" });
+        self.mark_ui_dirty();
+    }
 
-        let chunk_size = CHUNK_SIZE as i32;
-        let chunk_x = world_x.div_euclid(chunk_size);
-        let chunk_z = world_z.div_euclid(chunk_size);
-        let local_x = world_x.rem_euclid(chunk_size);
-        let local_z = world_z.rem_euclid(chunk_size);
+    fn open_help(&mut self) {
+        if self.help_open {
+            return;
+        }
+        self.enter_menu_mode();
+        self.help_open = true;
+        self.help_scroll = 0;
+        self.mark_ui_dirty();
+    }
 
-        self.dirty_chunks.insert(ChunkPos {
-            x: chunk_x,
-            z: chunk_z,
-        });
+    fn close_help(&mut self) {
+        if self.help_open {
+            self.help_open = false;
+            self.exit_menu_mode_if_needed();
+            self.mark_ui_dirty();
+        }
+    }
 
-        if local_x == 0 {
-            self.dirty_chunks.insert(ChunkPos {
-                x: chunk_x - 1,
-                z: chunk_z,
-            });
+    fn scroll_help(&mut self, delta: i32) {
+        let max_scroll = help_rows().len().saturating_sub(HELP_VISIBLE_ROWS);
+        let scroll = (self.help_scroll as i32 + delta).clamp(0, max_scroll as i32) as usize;
+        if scroll != self.help_scroll {
+            self.help_scroll = scroll;
+            self.mark_ui_dirty();
         }
-        if local_x == chunk_size - 1 {
-            self.dirty_chunks.insert(ChunkPos {
-                x: chunk_x + 1,
+    }
+
+    /// Input while the help overlay is open: F1/Esc close it, arrows and the wheel scroll.
+    /// Everything else is swallowed so keys don't leak into the game underneath.
+    fn handle_help_input(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::KeyboardInput { event, .. } if event.state == ElementState::Pressed => {
+                if let PhysicalKey::Code(key) = event.physical_key {
+                    match key {
+                        HELP_KEY | KeyCode::Escape => self.close_help(),
+                        KeyCode::ArrowUp => self.scroll_help(-1),
+                        KeyCode::ArrowDown => self.scroll_help(1),
+                        KeyCode::PageUp => self.scroll_help(-(HELP_VISIBLE_ROWS as i32)),
+                        KeyCode::PageDown => self.scroll_help(HELP_VISIBLE_ROWS as i32),
+                        _ => {}
+                    }
+                }
+                true
+            }
+            WindowEvent::KeyboardInput { .. } => true,
+            WindowEvent::MouseWheel { delta, .. } => {
+                let steps = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => -(y.round() as i32),
+                    MouseScrollDelta::PixelDelta(pos) => -(pos.y.signum() as i32),
+                };
+                self.scroll_help(steps);
+                true
+            }
+            WindowEvent::MouseInput { .. } => true,
+            _ => false,
+        }
+    }
+
+    fn open_console(&mut self) {
+        self.enter_menu_mode();
+        self.console_input = Some(String::new());
+        self.mark_ui_dirty();
+    }
+
+    fn close_console(&mut self) {
+        if self.console_input.take().is_some() {
+            self.exit_menu_mode_if_needed();
+            self.mark_ui_dirty();
+        }
+    }
+
+    fn handle_console_key(&mut self, key: KeyCode) -> bool {
+        match key {
+            KeyCode::Escape => self.close_console(),
+            KeyCode::Enter | KeyCode::NumpadEnter => {
+                let line = self.console_input.clone().unwrap_or_default();
+                self.close_console();
+                self.run_console_command(&line);
+            }
+            KeyCode::Backspace => {
+                if let Some(input) = self.console_input.as_mut() {
+                    input.pop();
+                }
+                self.mark_ui_dirty();
+            }
+            _ => {
+                if let (Some(ch), Some(input)) = (console_char(key), self.console_input.as_mut()) {
+                    if input.len() < CONSOLE_MAX_LEN {
+                        input.push(ch);
+                    }
+                }
+                self.mark_ui_dirty();
+            }
+        }
+        true
+    }
+
+    fn eye_block(&self) -> BlockPos3 {
+        let eye = self.camera.position;
+        BlockPos3::new(eye.x.round() as i32, eye.y.round() as i32, eye.z.round() as i32)
+    }
+
+    /// Looks at `pos` and describes where it is relative to `from`.
+    fn turn_toward(&mut self, from: BlockPos3, pos: BlockPos3, label: &str) -> String {
+        self.camera
+            .look_at(Point3::new(pos.x as f32, pos.y as f32, pos.z as f32));
+        let (dx, dy, dz) = (pos.x - from.x, pos.y - from.y, pos.z - from.z);
+        let distance = ((dx * dx + dy * dy + dz * dz) as f32).sqrt();
+        format!("{label} at {} {} {} ({distance:.1} blocks)", pos.x, pos.y, pos.z)
+    }
+
+    fn run_console_command(&mut self, line: &str) {
+        let reply = match parse_console_command(line) {
+            Ok(ConsoleCommand::Find(component)) => {
+                let from = self.eye_block();
+                let name = component.block_type().name();
+                match self.world.electrical().nearest_component(from, component) {
+                    Some(pos) => self.turn_toward(from, pos, name),
+                    None => format!("No {name} in the world"),
+                }
+            }
+            Ok(ConsoleCommand::FindOverloaded) => {
+                let from = self.eye_block();
+                match self.world.electrical().nearest_overloaded(from) {
+                    Some(pos) => self.turn_toward(from, pos, "Overloaded component"),
+                    None => "No overloaded components".to_string(),
+                }
+            }
+            Ok(ConsoleCommand::GameMode(mode)) => {
+                self.game_mode = mode;
+                format!("Game mode: {}", mode.label())
+            }
+            Ok(ConsoleCommand::KeepInventory(keep)) => {
+                self.keep_inventory = keep.unwrap_or(!self.keep_inventory);
+                format!(
+                    "Keep inventory: {}",
+                    if self.keep_inventory { "ON" } else { "OFF" }
+                )
+            }
+            Ok(ConsoleCommand::ResetArea(radius)) => {
+                let eye = self.eye_block();
+                let (center_x, center_z) = (
+                    eye.x.div_euclid(CHUNK_SIZE as i32),
+                    eye.z.div_euclid(CHUNK_SIZE as i32),
+                );
+                let mut reset = 0;
+                for cz in center_z - radius..=center_z + radius {
+                    for cx in center_x - radius..=center_x + radius {
+                        let stale = self.world.regenerate_chunk(ChunkPos { x: cx, z: cz });
+                        if !stale.is_empty() {
+                            reset += 1;
+                        }
+                        self.dirty_chunks.extend(stale);
+                    }
+                }
+                self.world_dirty = true;
+                self.refresh_look_target();
+                self.refresh_inspect_info();
+                format!("Reset {reset} chunks to generated terrain")
+            }
+            Ok(ConsoleCommand::Time(hour, minute)) => {
+                self.world.environment_mut().set_clock_time(hour, minute);
+                format!("Time set to {hour:02}:{minute:02}")
+            }
+            Ok(ConsoleCommand::DayLength(seconds)) => {
+                let environment = self.world.environment_mut();
+                let seconds = match seconds {
+                    Some(seconds) => {
+                        environment.set_day_length(seconds as f32);
+                        environment.day_length()
+                    }
+                    None => environment.cycle_day_length(),
+                };
+                format!("Day length: {seconds:.0} s")
+            }
+            Ok(ConsoleCommand::Loadout(action, name)) => {
+                let index = self.inventory.loadout_index(&name);
+                match (action, index) {
+                    (LoadoutAction::Save, _) => self.save_loadout(&name),
+                    (LoadoutAction::Load, Some(index)) => self.apply_loadout(index),
+                    (LoadoutAction::Delete, Some(index)) => self.delete_loadout(index),
+                    (_, None) => format!("No loadout named '{name}'"),
+                }
+            }
+            Ok(ConsoleCommand::Save(file)) => match self.world.save_to_path(Path::new(&file)) {
+                Ok(()) => format!("Saved {} chunks to {file}", self.world.chunks().len()),
+                Err(err) => format!("Save failed: {err:#}"),
+            },
+            Ok(ConsoleCommand::Load(file)) => match World::load_from_path(Path::new(&file)) {
+                Ok(world) => {
+                    self.replace_world(world);
+                    format!("Loaded {file}")
+                }
+                Err(err) => format!("Load failed: {err:#}"),
+            },
+            Err(message) => message,
+        };
+        self.show_console_message(reply);
+    }
+
+    /// Stores the hotbar under `name` and rewrites the loadouts file.
+    fn save_loadout(&mut self, name: &str) -> String {
+        if !self.inventory.save_loadout(name) {
+            return format!("All {MAX_LOADOUTS} loadouts are in use; delete one first");
+        }
+        self.persist_loadouts();
+        format!("Saved loadout '{name}'")
+    }
+
+    fn apply_loadout(&mut self, index: usize) -> String {
+        if !self.inventory.apply_loadout(index) {
+            return "No such loadout".to_string();
+        }
+        self.cancel_inventory_drag();
+        self.inventory_cursor = self.inventory.selected_slot_index();
+        self.inventory_swap_slot = None;
+        self.mark_ui_dirty();
+        self.refresh_look_target();
+        format!("Loaded loadout '{}'", self.inventory.loadouts[index].name)
+    }
+
+    fn delete_loadout(&mut self, index: usize) -> String {
+        let Some(loadout) = self.inventory.delete_loadout(index) else {
+            return "No such loadout".to_string();
+        };
+        self.persist_loadouts();
+        format!("Deleted loadout '{}'", loadout.name)
+    }
+
+    fn persist_loadouts(&mut self) {
+        if let Err(err) = self.inventory.save_loadouts_to_path(Path::new(LOADOUTS_FILE)) {
+            eprintln!("Failed to save loadouts: {err:#}");
+        }
+        self.mark_ui_dirty();
+    }
+
+    /// Prints `text` and shows it under the console line for a few seconds.
+    fn show_console_message(&mut self, text: String) {
+        println!("{text}");
+        let until = Instant::now() + Duration::from_secs_f32(CONSOLE_MESSAGE_SECONDS);
+        self.console_message = Some((text, until));
+        self.mark_ui_dirty();
+    }
+
+    /// Drains air while the head is underwater and hurts the player once it runs out;
+    /// air and health come back above the surface.
+    fn update_breath(&mut self, dt: f32) {
+        let before = (self.breath, self.health);
+        if self.player_is_submerged() && !self.controller.noclip {
+            self.breath = (self.breath - dt).max(0.0);
+            if self.breath <= 0.0 {
+                self.drowning_timer += dt;
+                if self.drowning_timer >= DROWNING_DAMAGE_INTERVAL {
+                    self.drowning_timer -= DROWNING_DAMAGE_INTERVAL;
+                    self.damage_player(DROWNING_DAMAGE, "You drowned");
+                }
+            }
+        } else {
+            self.breath = (self.breath + dt * BREATH_RECOVERY_RATE).min(MAX_BREATH);
+            self.drowning_timer = 0.0;
+        }
+        if self.breath > 0.0 {
+            self.health = (self.health + dt * HEALTH_REGEN_PER_SECOND).min(MAX_HEALTH);
+        }
+        if (self.breath, self.health) != before {
+            self.mark_ui_dirty();
+        }
+    }
+
+    /// Takes `amount` of health in survival; at zero the player respawns on dry ground
+    /// nearby, with an empty hotbar unless keep-inventory is on.
+    fn damage_player(&mut self, amount: f32, cause: &str) {
+        if !self.game_mode.takes_damage() {
+            return;
+        }
+        self.health = (self.health - amount).max(0.0);
+        if self.health > 0.0 {
+            return;
+        }
+        let spawn = self.world.find_spawn(self.camera.position);
+        self.camera.position = point3(
+            spawn.x,
+            spawn.y + self.controller.eye_height() + 0.05,
+            spawn.z,
+        );
+        self.controller.reset_motion();
+        self.health = MAX_HEALTH;
+        self.breath = MAX_BREATH;
+        self.drowning_timer = 0.0;
+        if !self.keep_inventory {
+            for slot in 0..HOTBAR_SIZE {
+                self.inventory.clear_slot(slot);
+            }
+            self.mark_ui_dirty();
+        }
+        self.show_console_message(format!("{cause} - respawned nearby"));
+    }
+
+    /// Plays a step sound for the block underfoot every stride walked on the ground.
+    fn advance_footsteps(&mut self, previous_position: Point3<f32>) {
+        if self.controller.noclip
+            || self.controller.is_swimming()
+            || !self.controller.is_on_ground()
+        {
+            self.footstep_distance = 0.0;
+            return;
+        }
+        let dx = self.camera.position.x - previous_position.x;
+        let dz = self.camera.position.z - previous_position.z;
+        self.footstep_distance += (dx * dx + dz * dz).sqrt();
+        let stride = if self.controller.is_sprinting() {
+            SPRINT_FOOTSTEP_STRIDE
+        } else {
+            FOOTSTEP_STRIDE
+        };
+        if self.footstep_distance < stride {
+            return;
+        }
+        self.footstep_distance -= stride;
+
+        // Cells are centred on integer coordinates, and feet rest half a block above the
+        // centre of the cell underfoot.
+        let eye = self.camera.position;
+        let x = eye.x.round() as i32;
+        let y = (eye.y - self.controller.eye_height() - 0.05).floor() as i32;
+        let z = eye.z.round() as i32;
+        if self.world.get_fluid_amount(x, y + 1, z) > 0 {
+            self.audio.play(SoundMaterial::Water, SoundEvent::Footstep);
+        } else {
+            self.play_block_sound(self.world.get_block(x, y, z), SoundEvent::Footstep);
+        }
+    }
+
+    fn play_block_sound(&mut self, block: BlockType, event: SoundEvent) {
+        if let Some(material) = block.sound_material() {
+            self.audio.play(material, event);
+        }
+    }
+
+    /// Empty buckets scoop a full source cell of either liquid; full buckets pour it back out.
+    fn use_bucket(&mut self, state: BucketState) {
+        let Some(LookTarget { hit, .. }) = self.looked_at() else {
+            return;
+        };
+        let slot = self.inventory.selected_slot_index();
+
+        match state.fluid() {
+            None => {
+                if !hit.fluid {
+                    return;
+                }
+                let (x, y, z) = hit.block_pos;
+                if self.world.get_fluid_amount(x, y, z) < MAX_FLUID_LEVEL {
+                    println!("Only a full source can be scooped up.");
+                    return;
+                }
+                let kind = self.world.get_fluid_kind(x, y, z);
+                self.world.remove_fluid(x, y, z, MAX_FLUID_LEVEL);
+                let filled = BucketState::holding(kind);
+                self.inventory
+                    .set_slot(slot, Some(ItemStack::single(ItemType::Bucket(filled))));
+                println!("Filled {}.", filled.name());
+                self.play_block_sound(kind.block_type(), SoundEvent::Break);
+                self.mark_block_dirty(x, y, z);
+            }
+            Some(kind) => {
+                let (x, y, z) = (
+                    hit.block_pos.0 + hit.normal.x as i32,
+                    hit.block_pos.1 + hit.normal.y as i32,
+                    hit.block_pos.2 + hit.normal.z as i32,
+                );
+                // Pour only into empty or liquid cells; flowers, torches and parts stay put.
+                let block = self.world.get_block(x, y, z);
+                if (block != BlockType::Air && FluidKind::from_block(block).is_none())
+                    || !self.world.electrical_components_at(x, y, z).is_empty()
+                {
+                    return;
+                }
+                self.world.set_fluid(x, y, z, kind, MAX_FLUID_LEVEL);
+                self.inventory
+                    .set_slot(slot, Some(ItemStack::single(ItemType::Bucket(BucketState::Empty))));
+                println!("Emptied bucket.");
+                self.play_block_sound(kind.block_type(), SoundEvent::Place);
+                self.mark_block_dirty(x, y, z);
+                self.hand_animation.start(HandAction::Place);
+            }
+        }
+        self.refresh_look_target();
+        self.mark_ui_dirty();
+    }
+
+    /// Shows a ghost of the held electrical component where a right-click would put it,
+    /// with plates lit for the neighbours it would connect to.
+    fn refresh_placement_preview(&mut self, in_menu: bool) {
+        let preview = self
+            .inventory
+            .selected_block()
+            .filter(|_| !in_menu && self.free_camera.is_none())
+            .and_then(ElectricalComponent::from_block)
+            .and_then(|component| {
+                let target = self.looked_at().filter(|target| !target.hit.fluid)?;
+                let face = BlockFace::from_normal_f32(target.hit.normal)?;
+                let axis = self.determine_electrical_axis(component.block_type(), face);
+                let pos = target.block_pos();
+                // An occupied face already shows its component; a ghost would z-fight it.
+                if target.component.is_some() {
+                    return None;
+                }
+                let connections = self
+                    .world
+                    .electrical()
+                    .preview_connection_mask(pos, face, component, axis);
+                Some(mesh::generate_component_preview(
+                    component,
+                    pos,
+                    face,
+                    axis,
+                    connections,
+                    PLACEMENT_PREVIEW_TINT,
+                ))
+            });
+        self.renderer.update_placement_preview(preview.as_ref());
+    }
+
+    /// Flips the switch under the crosshair, if that is what it rests on. Returns whether
+    /// the click was used up.
+    fn toggle_highlighted_switch(&mut self) -> bool {
+        let Some(handle) = self.highlight_target else {
+            return false;
+        };
+        let Some(closed) = self
+            .world
+            .electrical_mut()
+            .toggle_switch(handle.pos, handle.face)
+        else {
+            return false;
+        };
+        println!("Switch {}.", if closed { "closed" } else { "opened" });
+        self.mark_block_dirty(handle.pos.x, handle.pos.y, handle.pos.z);
+        self.refresh_inspect_info();
+        self.mark_ui_dirty();
+        true
+    }
+
+    /// Moves the wiper of the potentiometer under the crosshair `notches` wheel steps, up for
+    /// positive. Returns false when there is none, so the wheel cycles the hotbar instead.
+    fn turn_highlighted_potentiometer(&mut self, notches: i32) -> bool {
+        if notches == 0 || self.is_in_menu() {
+            return false;
+        }
+        let Some(handle) = self.dial_target else {
+            return false;
+        };
+        let Some(params) = self.world.electrical().params_at(handle.pos, handle.face) else {
+            return false;
+        };
+        let wiper = params.wiper() + notches as f32 * POTENTIOMETER_WHEEL_STEP;
+        let max_current = params.max_current_amps.unwrap_or(0.0);
+        let turned = ComponentParams::potentiometer(wiper, max_current);
+        self.world
+            .electrical_mut()
+            .set_params(handle.pos, handle.face, turned);
+        self.mark_block_dirty(handle.pos.x, handle.pos.y, handle.pos.z);
+        self.refresh_inspect_info();
+        self.mark_ui_dirty();
+        true
+    }
+
+    fn place_electrical_component(&mut self, block_type: BlockType, hit: &RaycastHit) {
+        let Some(face) = BlockFace::from_normal_f32(hit.normal) else {
+            return;
+        };
+
+        let axis = self.determine_electrical_axis(block_type, face);
+        self.world.set_block_with_axis(
+            hit.block_pos.0,
+            hit.block_pos.1,
+            hit.block_pos.2,
+            block_type,
+            Some(axis),
+            Some(face),
+        );
+        self.play_block_sound(block_type, SoundEvent::Place);
+        self.trigger_hit_marker(HIT_MARKER_PLACE_COLOR);
+        self.consume_placed_block();
+        self.mark_block_dirty(hit.block_pos.0, hit.block_pos.1, hit.block_pos.2);
+        self.refresh_inspect_info();
+        self.hand_animation.start(HandAction::Place);
+    }
+
+    /// Remeshes the chunks the fluid simulation touched, plus their neighbours whose border
+    /// faces it can change, instead of the whole world.
+    fn queue_fluid_remesh(&mut self) {
+        self.world_dirty = true;
+        for pos in self.world.take_fluid_remesh() {
+            for (dx, dz) in [(0, 0), (1, 0), (-1, 0), (0, 1), (0, -1)] {
+                let neighbor = ChunkPos {
+                    x: pos.x + dx,
+                    z: pos.z + dz,
+                };
+                if self.world.chunks().contains_key(&neighbor) {
+                    self.dirty_chunks.insert(neighbor);
+                }
+            }
+        }
+    }
+
+    fn mark_block_dirty(&mut self, world_x: i32, _world_y: i32, world_z: i32) {
+        self.world_dirty = true;
+        if self.force_full_remesh {
+            return;
+        }
+
+        let chunk_size = CHUNK_SIZE as i32;
+        let chunk_x = world_x.div_euclid(chunk_size);
+        let chunk_z = world_z.div_euclid(chunk_size);
+        let local_x = world_x.rem_euclid(chunk_size);
+        let local_z = world_z.rem_euclid(chunk_size);
+
+        self.dirty_chunks.insert(ChunkPos {
+            x: chunk_x,
+            z: chunk_z,
+        });
+
+        if local_x == 0 {
+            self.dirty_chunks.insert(ChunkPos {
+                x: chunk_x - 1,
+                z: chunk_z,
+            });
+        }
+        if local_x == chunk_size - 1 {
+            self.dirty_chunks.insert(ChunkPos {
+                x: chunk_x + 1,
                 z: chunk_z,
             });
         }
@@ -1483,6 +3551,56 @@ impl<'window> State<'window> {
         self.projection.ray_direction(&self.camera, self.crosshair_screen_uv())
     }
 
+    /// The block under the crosshair as of the last refresh; `None` while a menu is open
+    /// or photo mode is on.
+    pub fn looked_at(&self) -> Option<LookTarget> {
+        self.look_target
+    }
+
+    /// Re-resolves the crosshair target. Runs once per frame after the camera and world
+    /// have moved, and again after each edit so the next tick never acts on a stale cell.
+    fn refresh_look_target(&mut self) {
+        self.look_target = if self.is_in_menu() || self.photo_mode {
+            None
+        } else {
+            self.resolve_look_target()
+        };
+    }
+
+    fn resolve_look_target(&self) -> Option<LookTarget> {
+        let direction = self.crosshair_direction();
+        // Lily pads and buckets target the liquid surface instead of the block underneath it.
+        let targets_fluid = self.inventory.selected_block() == Some(BlockType::LilyPad)
+            || matches!(self.inventory.selected_item(), Some(ItemType::Bucket(_)));
+        let mode = if targets_fluid {
+            RaycastMode::BlocksAndFluids
+        } else {
+            RaycastMode::Blocks
+        };
+        let hit =
+            raycast_with_mode(&self.world, self.camera.position, direction, BLOCK_REACH, mode)?;
+        let face = BlockFace::from_normal_f32(hit.normal)
+            .or_else(|| BlockFace::from_normal_f32(-hit.normal))
+            .unwrap_or(BlockFace::Top);
+        let (x, y, z) = hit.block_pos;
+        let pos = BlockPos3::new(x, y, z);
+        let electrical = self.world.electrical();
+        let component = electrical.component_at(pos, face).map(|component| {
+            let telemetry = electrical.telemetry_at(pos, face).unwrap_or_default();
+            (component, telemetry)
+        });
+        let block = match component {
+            Some((component, _)) => component.block_type(),
+            None => self.world.get_block(x, y, z),
+        };
+        Some(LookTarget {
+            hit,
+            face,
+            block,
+            component,
+        })
+    }
+
     fn set_mouse_grab(&mut self, grab: bool) {
         if self.mouse_grabbed == grab {
             return;
@@ -1502,22 +3620,93 @@ impl<'window> State<'window> {
 
     fn mouse_motion(&mut self, delta: (f64, f64)) {
         if self.mouse_grabbed {
-            self.controller.process_mouse(delta, &mut self.camera);
+            let camera = self.free_camera.as_mut().unwrap_or(&mut self.camera);
+            self.controller.process_mouse(delta, camera);
         }
     }
 
-    fn inventory_slot_rect(&self, index: usize) -> Option<((f32, f32), (f32, f32))> {
-        if index >= INVENTORY_SLOT_COUNT {
-            return None;
+    /// Applies this frame's controller input. In game the sticks move and look, the
+    /// triggers break and place and the bumpers cycle the hotbar.
+    fn poll_gamepad(&mut self, frame_dt: f32) {
+        let pad = self.gamepad.poll();
+        let events = pad.take_events();
+        let move_stick = pad.move_stick();
+        let look_stick = pad.look_stick();
+        let jump = pad.is_held(PadButton::South);
+
+        for (button, pressed) in events {
+            if pressed {
+                self.gamepad_press(button);
+            } else if button == PadButton::RightTrigger && self.left_mouse_held {
+                self.left_mouse_held = false;
+                self.breaking_block = None;
+                self.breaking_progress = 0.0;
+            }
         }
-        let col = index % INVENTORY_COLS;
-        let row = index / INVENTORY_COLS;
-        let step_x = ui_width(INVENTORY_SLOT_SIZE + INVENTORY_SLOT_GAP);
-        let min_x = INVENTORY_START_X + col as f32 * step_x;
-        let min_y = INVENTORY_START_Y + row as f32 * (INVENTORY_SLOT_SIZE + INVENTORY_SLOT_GAP);
-        let max_x = min_x + ui_width(INVENTORY_SLOT_SIZE);
-        let max_y = min_y + INVENTORY_SLOT_SIZE;
-        Some(((min_x, min_y), (max_x, max_y)))
+
+        // Menus reset the controller's motion every tick, so there is nothing to clear.
+        if self.is_in_menu() {
+            return;
+        }
+        self.controller.set_gamepad_motion(move_stick, jump);
+        let camera = self.free_camera.as_mut().unwrap_or(&mut self.camera);
+        self.controller.process_stick_look(look_stick, frame_dt, camera);
+    }
+
+    /// Menus take the d-pad as the arrow keys, South as Enter and East as Escape.
+    fn gamepad_press(&mut self, button: PadButton) {
+        let arrow = match button {
+            PadButton::DPadUp => Some(KeyCode::ArrowUp),
+            PadButton::DPadDown => Some(KeyCode::ArrowDown),
+            PadButton::DPadLeft => Some(KeyCode::ArrowLeft),
+            PadButton::DPadRight => Some(KeyCode::ArrowRight),
+            _ => None,
+        };
+        if self.settings_open {
+            match (button, arrow) {
+                (_, Some(key)) => {
+                    self.handle_settings_key(key);
+                }
+                (PadButton::East | PadButton::Start, _) => self.close_settings(),
+                _ => {}
+            }
+        } else if self.inventory_open {
+            match (button, arrow) {
+                (_, Some(key)) => {
+                    self.handle_inventory_key(key);
+                }
+                (PadButton::South, _) => {
+                    self.handle_inventory_key(KeyCode::Enter);
+                }
+                (PadButton::East | PadButton::North, _) => self.close_inventory(),
+                _ => {}
+            }
+        } else if self.paused {
+            match button {
+                PadButton::South => self.open_settings(),
+                PadButton::East | PadButton::Start => self.close_pause(),
+                _ => {}
+            }
+        } else if !self.is_in_menu() {
+            match button {
+                PadButton::Start => self.open_pause(),
+                PadButton::North => self.open_inventory(),
+                PadButton::LeftBumper | PadButton::RightBumper => {
+                    let step = if button == PadButton::LeftBumper { -1 } else { 1 };
+                    self.inventory.cycle_selection(step);
+                    self.print_selected();
+                    self.mark_ui_dirty();
+                }
+                PadButton::RightTrigger if self.measure_mode => self.measure_click(),
+                PadButton::RightTrigger => self.left_mouse_held = true,
+                PadButton::LeftTrigger => self.place_block(),
+                _ => {}
+            }
+        }
+    }
+
+    fn inventory_slot_rect(&self, index: usize) -> Option<((f32, f32), (f32, f32))> {
+        inventory_grid_slot_rect(INVENTORY_COLS, INVENTORY_ROWS, index)
     }
 
     fn inventory_slot_from_point(&self, point: (f32, f32)) -> Option<usize> {
@@ -1542,7 +3731,7 @@ impl<'window> State<'window> {
 
         if let (Some((slot_min, _)), Some((_, slot_max))) = (
             self.inventory_slot_rect(0),
-            self.inventory_slot_rect(HOTBAR_SIZE - 1),
+            self.inventory_slot_rect(INVENTORY_SLOT_COUNT - 1),
         ) {
             let margin_x = ui_width(0.035);
             let margin_top = 0.045;
@@ -1606,10 +3795,18 @@ impl<'window> State<'window> {
             chip_rects.push(rect);
             chip_cursor_x = chip_cursor_x + chip_width + ui_width(FILTER_CHIP_GAP);
         }
-        let chips_bottom = chip_rects
-            .last()
-            .map(|(_, max)| max.1)
-            .unwrap_or(search_max.1);
+        // The sort toggle trails the category chips on the same rows.
+        let sort_label_len = self.inventory_palette_sort.label().len() as f32;
+        let sort_width = ui_width(0.055) + sort_label_len * ui_width(0.008);
+        if chip_cursor_x + sort_width > palette_panel_max.0 - ui_width(FILTER_AREA_PADDING_X) {
+            chip_cursor_x = chip_start_x;
+            chip_cursor_y += chip_height + FILTER_CHIP_GAP;
+        }
+        let sort_rect = (
+            (chip_cursor_x, chip_cursor_y),
+            (chip_cursor_x + sort_width, chip_cursor_y + chip_height),
+        );
+        let chips_bottom = sort_rect.1 .1;
 
         let palette_content_origin = (
             palette_panel_min.0 + ui_width(FILTER_AREA_PADDING_X),
@@ -1618,6 +3815,40 @@ impl<'window> State<'window> {
         let palette_view_height =
             (palette_panel_max.1 - FILTER_AREA_PADDING_Y) - palette_content_origin.1;
 
+        // The loadout strip fills the gap between the grid/palette and the footer.
+        let loadout_strip = (
+            (
+                instructions_panel_min.0,
+                grid_panel_max.1.max(palette_panel_max.1) + 0.012,
+            ),
+            (instructions_panel_max.0, instructions_panel_min.1 - 0.012),
+        );
+        let chip_top = (loadout_strip.0).1 + 0.011;
+        let chip_bottom = ((loadout_strip.1).1 - 0.011).max(chip_top + 0.02);
+        let chip_pad = ui_width(0.014);
+        let mut loadout_cursor_x = (loadout_strip.0).0
+            + ui_width(0.02)
+            + text_width("LOADOUTS", 0.014)
+            + ui_width(0.024);
+        let mut chip_rect = |label: &str| {
+            let width = text_width(label, LOADOUT_TEXT_HEIGHT) + chip_pad * 2.0;
+            let rect = (
+                (loadout_cursor_x, chip_top),
+                (loadout_cursor_x + width, chip_bottom),
+            );
+            loadout_cursor_x += width + ui_width(FILTER_CHIP_GAP);
+            rect
+        };
+        let loadout_save_rect = chip_rect("SAVE HOTBAR");
+        let strip_right = (loadout_strip.1).0 - ui_width(0.016);
+        let loadout_rects = self
+            .inventory
+            .loadouts
+            .iter()
+            .map(|loadout| chip_rect(&loadout_label(&loadout.name)))
+            .take_while(|rect| (rect.1).0 <= strip_right)
+            .collect();
+
         InventoryLayout {
             panel: (panel_min, panel_max),
             header: (header_min, header_max),
@@ -1627,8 +3858,12 @@ impl<'window> State<'window> {
             search_rect: (search_min, search_max),
             search_clear_rect,
             chip_rects,
+            sort_rect,
             palette_content_origin,
             palette_view_height: palette_view_height.max(0.0),
+            loadout_strip,
+            loadout_save_rect,
+            loadout_rects,
         }
     }
 
@@ -1678,6 +3913,14 @@ impl<'window> State<'window> {
                 .unwrap_or(usize::MAX)
         });
         blocks.dedup();
+        match self.inventory_palette_sort {
+            PaletteSort::List => {}
+            PaletteSort::Name => blocks.sort_by_key(|block| block.name().to_ascii_lowercase()),
+            PaletteSort::Color => {
+                let key = |block: &BlockType| self.palette_color_keys[*block as usize];
+                blocks.sort_by(|a, b| key(a).total_cmp(&key(b)));
+            }
+        }
 
         if !self.inventory_search_query.is_empty() {
             let needle = self.inventory_search_query.to_ascii_lowercase();
@@ -1715,9 +3958,9 @@ impl<'window> State<'window> {
     }
 
     fn cancel_inventory_drag(&mut self) {
-        if let Some(block) = self.inventory_drag_block.take() {
+        if let Some(stack) = self.inventory_drag_block.take() {
             if let Some(origin) = self.inventory_drag_origin.take() {
-                self.inventory.set_slot(origin, Some(block));
+                self.inventory.place_stack(origin, stack);
                 self.inventory_cursor = origin;
                 self.inventory.select_slot(origin);
                 self.print_selected();
@@ -1728,6 +3971,83 @@ impl<'window> State<'window> {
         }
     }
 
+    /// Drops the dragged stack on whatever is under the cursor; returns false when nothing
+    /// was being dragged.
+    fn finish_inventory_drag(&mut self, dropped_outside: bool) -> bool {
+        let Some(stack) = self.inventory_drag_block.take() else {
+            return false;
+        };
+        let item = stack.item;
+        let origin = self.inventory_drag_origin.take();
+        if let Some(slot) = self.inventory_hover_slot {
+            // Half a split stack stays behind at the origin, so there's nowhere to swap
+            // a different item out to; send the lifted half home instead.
+            let origin_kept = origin.is_some_and(|origin_slot| {
+                origin_slot != slot && self.inventory.hotbar[origin_slot].is_some()
+            });
+            let blocked =
+                origin_kept && self.inventory.item_at(slot).is_some_and(|held| held != item);
+            let leftover = if blocked {
+                Some(stack)
+            } else {
+                self.inventory.place_stack(slot, stack)
+            };
+            if let Some(rest) = leftover {
+                match origin {
+                    Some(origin_slot) => {
+                        self.inventory.place_stack(origin_slot, rest);
+                    }
+                    None => {
+                        self.inventory.add_item(rest.item, rest.count);
+                    }
+                }
+            }
+            self.inventory_cursor = slot;
+            self.inventory.select_slot(slot);
+            if !blocked {
+                println!("Placed {} in slot {}.", item.name(), slot + 1);
+            }
+            self.print_selected();
+        } else if let Some(index) = self.inventory_palette_hover {
+            if let Some(new_block) = self.inventory_palette_filtered.get(index).copied() {
+                let target_slot = origin.unwrap_or(self.inventory_cursor).min(HOTBAR_SIZE - 1);
+                self.inventory
+                    .set_slot(target_slot, Some(ItemStack::full(ItemType::Block(new_block))));
+                self.inventory_cursor = target_slot;
+                self.inventory.select_slot(target_slot);
+                println!(
+                    "Replaced slot {} with {} (was {}).",
+                    target_slot + 1,
+                    new_block.name(),
+                    item.name()
+                );
+                self.print_selected();
+            }
+        } else if let Some(origin_slot) = origin.filter(|_| dropped_outside) {
+            // Dropped outside the panel: discard, keeping it for Ctrl+Z.
+            self.inventory_trashed = Some((origin_slot, stack));
+            println!(
+                "Discarded {} from slot {} (Ctrl+Z to undo).",
+                item.name(),
+                origin_slot + 1
+            );
+            self.print_selected();
+        } else if let Some(origin_slot) = origin {
+            self.inventory.place_stack(origin_slot, stack);
+            self.inventory_cursor = origin_slot;
+            self.inventory.select_slot(origin_slot);
+            self.print_selected();
+        } else {
+            let slot = self.inventory_cursor.min(HOTBAR_SIZE - 1);
+            self.inventory.set_slot(slot, Some(stack));
+            println!("Slot {} set to {}.", slot + 1, item.name());
+            self.inventory.select_slot(slot);
+            self.print_selected();
+        }
+        self.mark_ui_dirty();
+        true
+    }
+
     fn move_inventory_cursor(&mut self, dx: i32, dy: i32) {
         let cols = INVENTORY_COLS as i32;
         let rows = INVENTORY_ROWS as i32;
@@ -1736,12 +4056,63 @@ impl<'window> State<'window> {
         col = (col + dx).rem_euclid(cols);
         row = (row + dy).rem_euclid(rows);
         let new_index = (row * cols + col) as usize;
-        self.inventory_cursor = new_index.min(HOTBAR_SIZE - 1);
+        self.inventory_cursor = new_index.min(INVENTORY_SLOT_COUNT - 1);
         self.inventory.select_slot(self.inventory_cursor);
         self.print_selected();
         self.mark_ui_dirty();
     }
 
+    fn toggle_palette_category(&mut self, chip_index: usize) {
+        // Toggle category if clicking the active one, otherwise switch to new category
+        let new_category = if chip_index == self.inventory_active_category && chip_index != 0 {
+            0
+        } else {
+            chip_index
+        };
+
+        // Only reset scroll if changing category
+        if new_category != self.inventory_active_category {
+            self.inventory_palette_scroll = 0.0;
+        }
+
+        self.inventory_active_category = new_category;
+        self.refresh_palette_filter();
+    }
+
+    fn focus_inventory_search(&mut self) {
+        self.inventory_search_active = true;
+        self.inventory_chip_focus = None;
+        self.mark_ui_dirty();
+    }
+
+    /// Keys while a category chip has focus: arrows walk the chips, Enter or
+    /// Space toggles the focused one and Tab hands focus back to the search field.
+    fn handle_chip_focus_key(&mut self, key: KeyCode, focus: usize) -> bool {
+        let count = PALETTE_CATEGORIES.len();
+        match key {
+            KeyCode::ArrowLeft | KeyCode::ArrowUp => {
+                self.inventory_chip_focus = Some((focus + count - 1) % count);
+            }
+            KeyCode::ArrowRight | KeyCode::ArrowDown => {
+                self.inventory_chip_focus = Some((focus + 1) % count);
+            }
+            KeyCode::Enter | KeyCode::Space => {
+                self.toggle_palette_category(focus);
+                return true;
+            }
+            KeyCode::Tab => {
+                self.focus_inventory_search();
+                return true;
+            }
+            KeyCode::Escape => {
+                self.inventory_chip_focus = None;
+            }
+            _ => return false,
+        }
+        self.mark_ui_dirty();
+        true
+    }
+
     fn handle_inventory_input(&mut self, event: &WindowEvent) -> bool {
         match event {
             WindowEvent::CursorMoved { position, .. } => {
@@ -1753,6 +4124,10 @@ impl<'window> State<'window> {
                 let norm_y = (position.y as f32 / size.height as f32).clamp(0.0, 1.0);
                 let ui_point = self.ui_scaler.unproject((norm_x, norm_y));
                 self.inventory_cursor_pos = Some(ui_point);
+                if self.inventory_drag_block.is_some() {
+                    // Keep the drag icon and trash indicator under the cursor.
+                    self.mark_ui_dirty();
+                }
 
                 let layout = self.inventory_layout();
 
@@ -1760,7 +4135,9 @@ impl<'window> State<'window> {
                 if slot_hover != self.inventory_hover_slot {
                     self.inventory_hover_slot = slot_hover;
                     if let Some(slot) = slot_hover {
-                        let description = self.inventory.hotbar[slot]
+                        let description = self
+                            .inventory
+                            .item_at(slot)
                             .map(|item| item.name())
                             .unwrap_or("Empty");
                         if self.inventory_last_hover_slot != Some(slot) {
@@ -1840,7 +4217,9 @@ impl<'window> State<'window> {
                 self.inventory_cursor = slot;
                 self.inventory.select_slot(slot);
                 self.inventory.cycle_slot_block(slot, direction);
-                let description = self.inventory.hotbar[slot]
+                let description = self
+                    .inventory
+                    .item_at(slot)
                     .map(|item| item.name())
                     .unwrap_or("Empty");
                 println!("Slot {} set to {}.", slot + 1, description);
@@ -1872,6 +4251,7 @@ impl<'window> State<'window> {
                                 return true;
                             }
 
+                            self.inventory_chip_focus = None;
                             if point_in_rect(point, layout.search_rect) {
                                 self.inventory_search_active = true;
                                 self.mark_ui_dirty();
@@ -1885,24 +4265,30 @@ impl<'window> State<'window> {
                                 .iter()
                                 .position(|rect| point_in_rect(point, *rect))
                             {
-                                // Toggle category if clicking the active one, otherwise switch to new category
-                                let new_category = if chip_index == self.inventory_active_category
-                                    && chip_index != 0
-                                {
-                                    0
-                                } else {
-                                    chip_index
-                                };
-
-                                // Only reset scroll if changing category
-                                if new_category != self.inventory_active_category {
-                                    self.inventory_palette_scroll = 0.0;
-                                }
+                                self.toggle_palette_category(chip_index);
+                                return true;
+                            }
 
-                                self.inventory_active_category = new_category;
+                            if point_in_rect(point, layout.sort_rect) {
+                                self.inventory_palette_sort = self.inventory_palette_sort.next();
                                 self.refresh_palette_filter();
                                 return true;
                             }
+
+                            if point_in_rect(point, layout.loadout_save_rect) {
+                                let name = self.inventory.next_loadout_name();
+                                println!("{}", self.save_loadout(&name));
+                                return true;
+                            }
+                            if let Some(index) = layout
+                                .loadout_rects
+                                .iter()
+                                .position(|rect| point_in_rect(point, *rect))
+                            {
+                                println!("{}", self.apply_loadout(index));
+                                self.print_selected();
+                                return true;
+                            }
                         }
 
                         if ctrl {
@@ -1915,7 +4301,10 @@ impl<'window> State<'window> {
                                         .first_empty_slot()
                                         .unwrap_or(self.inventory_cursor)
                                         .min(HOTBAR_SIZE - 1);
-                                    self.inventory.set_slot(target_slot, Some(ItemType::Block(block)));
+                                    self.inventory.set_slot(
+                                        target_slot,
+                                        Some(ItemStack::full(ItemType::Block(block))),
+                                    );
                                     self.inventory_cursor = target_slot;
                                     self.inventory.select_slot(target_slot);
                                     self.print_selected();
@@ -1978,7 +4367,8 @@ impl<'window> State<'window> {
                                     .inventory_hover_slot
                                     .unwrap_or(self.inventory_cursor)
                                     .min(HOTBAR_SIZE - 1);
-                                self.inventory.set_slot(slot, Some(ItemType::Block(block)));
+                                self.inventory
+                                    .set_slot(slot, Some(ItemStack::full(ItemType::Block(block))));
                                 println!("Slot {} set to {}.", slot + 1, block.name());
                                 self.inventory_cursor = slot;
                                 self.inventory.select_slot(slot);
@@ -1992,11 +4382,11 @@ impl<'window> State<'window> {
                             self.inventory_cursor = slot;
                             self.inventory.select_slot(slot);
                             self.print_selected();
-                            if let Some(item) = self.inventory.hotbar[slot] {
+                            if let Some(stack) = self.inventory.hotbar[slot] {
                                 self.inventory_drag_origin = Some(slot);
-                                self.inventory_drag_block = Some(item);
+                                self.inventory_drag_block = Some(stack);
                                 self.inventory.set_slot(slot, None);
-                                println!("Picked up {} from slot {}.", item.name(), slot + 1);
+                                println!("Picked up {} from slot {}.", stack.item.name(), slot + 1);
                             }
                             self.inventory_swap_slot = None;
                             self.mark_ui_dirty();
@@ -2005,55 +4395,10 @@ impl<'window> State<'window> {
 
                         false
                     }
-                    (ElementState::Released, MouseButton::Left) => {
-                        if let Some(item) = self.inventory_drag_block.take() {
-                            let origin = self.inventory_drag_origin.take();
-                            if let Some(slot) = self.inventory_hover_slot {
-                                let previous = self.inventory.hotbar[slot];
-                                self.inventory.set_slot(slot, Some(item));
-                                if let Some(origin_slot) = origin {
-                                    if origin_slot != slot {
-                                        self.inventory.set_slot(origin_slot, previous);
-                                    }
-                                }
-                                self.inventory_cursor = slot;
-                                self.inventory.select_slot(slot);
-                                println!("Placed {} in slot {}.", item.name(), slot + 1);
-                                self.print_selected();
-                            } else if let Some(index) = self.inventory_palette_hover {
-                                if let Some(new_block) =
-                                    self.inventory_palette_filtered.get(index).copied()
-                                {
-                                    let target_slot = origin
-                                        .unwrap_or(self.inventory_cursor)
-                                        .min(HOTBAR_SIZE - 1);
-                                    self.inventory.set_slot(target_slot, Some(ItemType::Block(new_block)));
-                                    self.inventory_cursor = target_slot;
-                                    self.inventory.select_slot(target_slot);
-                                    println!(
-                                        "Replaced slot {} with {} (was {}).",
-                                        target_slot + 1,
-                                        new_block.name(),
-                                        item.name()
-                                    );
-                                    self.print_selected();
-                                }
-                            } else if let Some(origin_slot) = origin {
-                                self.inventory.set_slot(origin_slot, Some(item));
-                                self.inventory_cursor = origin_slot;
-                                self.inventory.select_slot(origin_slot);
-                                self.print_selected();
-                            } else {
-                                let slot = self.inventory_cursor.min(HOTBAR_SIZE - 1);
-                                self.inventory.set_slot(slot, Some(item));
-                                println!("Slot {} set to {}.", slot + 1, item.name());
-                                self.inventory.select_slot(slot);
-                                self.print_selected();
-                            }
-                            self.mark_ui_dirty();
-                            return true;
-                        }
-                        false
+                    (ElementState::Released, MouseButton::Left | MouseButton::Right) => {
+                        let dropped_outside =
+                            cursor.is_some_and(|point| !point_in_rect(point, layout.panel));
+                        self.finish_inventory_drag(dropped_outside)
                     }
                     (ElementState::Pressed, MouseButton::Right) => {
                         if self.inventory_drag_block.is_some() {
@@ -2062,12 +4407,29 @@ impl<'window> State<'window> {
                             return true;
                         }
 
+                        if let Some(index) = cursor.and_then(|point| {
+                            layout
+                                .loadout_rects
+                                .iter()
+                                .position(|rect| point_in_rect(point, *rect))
+                        }) {
+                            println!("{}", self.delete_loadout(index));
+                            return true;
+                        }
+
                         if let Some(slot) = self.inventory_hover_slot {
-                            self.inventory.clear_slot(slot);
-                            println!("Cleared hotbar slot {}.", slot + 1);
-                            if self.inventory_cursor == slot {
-                                self.print_selected();
+                            // Lift half the stack; releasing drops it like a dragged stack.
+                            if let Some(half) = self.inventory.split_slot(slot) {
+                                self.inventory_drag_origin = Some(slot);
+                                self.inventory_drag_block = Some(half);
+                                println!(
+                                    "Picked up {} {} from slot {}.",
+                                    half.count,
+                                    half.item.name(),
+                                    slot + 1
+                                );
                             }
+                            self.inventory_swap_slot = None;
                             self.mark_ui_dirty();
                             return true;
                         }
@@ -2077,7 +4439,8 @@ impl<'window> State<'window> {
                             {
                                 let slot =
                                     self.inventory_hover_slot.unwrap_or(self.inventory_cursor);
-                                self.inventory.set_slot(slot, Some(ItemType::Block(block)));
+                                self.inventory
+                                    .set_slot(slot, Some(ItemStack::full(ItemType::Block(block))));
                                 println!("Slot {} set to {}.", slot + 1, block.name());
                                 self.inventory_cursor = slot;
                                 self.inventory.select_slot(slot);
@@ -2096,147 +4459,225 @@ impl<'window> State<'window> {
                 if event.state != ElementState::Pressed {
                     return false;
                 }
-                if let PhysicalKey::Code(key) = event.physical_key {
-                    if self.inventory_search_active {
-                        match key {
-                            KeyCode::Backspace => {
-                                if !self.inventory_search_query.is_empty() {
-                                    self.inventory_search_query.pop();
-                                    self.refresh_palette_filter();
-                                }
-                                return true;
-                            }
-                            KeyCode::Escape => {
-                                self.inventory_search_active = false;
-                                self.inventory_search_query.clear();
-                                self.inventory_palette_scroll = 0.0;
-                                self.refresh_palette_filter();
-                                return true;
-                            }
-                            KeyCode::Enter => {
-                                self.inventory_search_active = false;
-                                self.mark_ui_dirty();
-                                return true;
-                            }
-                            KeyCode::ArrowLeft
-                            | KeyCode::ArrowRight
-                            | KeyCode::ArrowUp
-                            | KeyCode::ArrowDown => {}
-                            _ => {
-                                return false;
-                            }
-                        }
+                match event.physical_key {
+                    PhysicalKey::Code(key) => self.handle_inventory_key(key),
+                    _ => false,
+                }
+            }
+            _ => false,
+        }
+    }
+
+    fn handle_inventory_key(&mut self, key: KeyCode) -> bool {
+        if let Some(focus) = self.inventory_chip_focus {
+            if self.handle_chip_focus_key(key, focus) {
+                return true;
+            }
+        }
+        if self.inventory_search_active {
+            match key {
+                KeyCode::Tab => {
+                    self.inventory_search_active = false;
+                    self.inventory_chip_focus = Some(self.inventory_active_category);
+                    self.mark_ui_dirty();
+                    return true;
+                }
+                KeyCode::Backspace => {
+                    if !self.inventory_search_query.is_empty() {
+                        self.inventory_search_query.pop();
+                        self.refresh_palette_filter();
                     }
+                    return true;
+                }
+                KeyCode::Escape => {
+                    self.inventory_search_active = false;
+                    self.inventory_search_query.clear();
+                    self.inventory_palette_scroll = 0.0;
+                    self.refresh_palette_filter();
+                    return true;
+                }
+                KeyCode::Enter => {
+                    self.inventory_search_active = false;
+                    self.mark_ui_dirty();
+                    return true;
+                }
+                KeyCode::ArrowLeft
+                | KeyCode::ArrowRight
+                | KeyCode::ArrowUp
+                | KeyCode::ArrowDown => {}
+                _ => {
+                    return false;
+                }
+            }
+        }
 
-                    match key {
-                        KeyCode::ArrowLeft => {
-                            self.move_inventory_cursor(-1, 0);
-                            return true;
-                        }
-                        KeyCode::ArrowRight => {
-                            self.move_inventory_cursor(1, 0);
-                            return true;
-                        }
-                        KeyCode::ArrowUp => {
-                            self.move_inventory_cursor(0, -1);
-                            return true;
-                        }
-                        KeyCode::ArrowDown => {
-                            self.move_inventory_cursor(0, 1);
-                            return true;
-                        }
-                        KeyCode::Enter | KeyCode::Space => {
-                            if let Some(origin) = self.inventory_swap_slot {
-                                if origin == self.inventory_cursor {
-                                    println!("Swap cancelled.");
-                                    self.inventory_swap_slot = None;
-                                } else {
-                                    let target = self.inventory_cursor;
-                                    self.inventory.swap_slots(origin, target);
-                                    println!(
-                                        "Swapped hotbar slots {} and {}.",
-                                        origin + 1,
-                                        target + 1
-                                    );
-                                    self.inventory_swap_slot = None;
-                                    self.print_selected();
-                                }
-                            } else {
-                                self.inventory_swap_slot = Some(self.inventory_cursor);
-                                println!(
-                                    "Slot {} ready to swap. Select another slot.",
-                                    self.inventory_cursor + 1
-                                );
-                            }
-                            self.mark_ui_dirty();
-                            return true;
-                        }
-                        KeyCode::KeyZ => {
-                            self.inventory.cycle_slot_block(self.inventory_cursor, -1);
-                            let description = self.inventory.hotbar[self.inventory_cursor]
-                                .map(|block| block.name())
-                                .unwrap_or("Empty");
-                            println!("Slot {} set to {}.", self.inventory_cursor + 1, description);
-                            self.inventory.select_slot(self.inventory_cursor);
-                            self.print_selected();
-                            self.mark_ui_dirty();
-                            return true;
-                        }
-                        KeyCode::KeyX => {
-                            self.inventory.cycle_slot_block(self.inventory_cursor, 1);
-                            let description = self.inventory.hotbar[self.inventory_cursor]
-                                .map(|block| block.name())
-                                .unwrap_or("Empty");
-                            println!("Slot {} set to {}.", self.inventory_cursor + 1, description);
-                            self.inventory.select_slot(self.inventory_cursor);
-                            self.print_selected();
-                            self.mark_ui_dirty();
-                            return true;
-                        }
-                        KeyCode::Backspace | KeyCode::Delete => {
-                            self.inventory.clear_slot(self.inventory_cursor);
-                            println!("Cleared hotbar slot {}.", self.inventory_cursor + 1);
-                            self.print_selected();
-                            self.mark_ui_dirty();
-                            return true;
-                        }
-                        KeyCode::Digit1
-                        | KeyCode::Digit2
-                        | KeyCode::Digit3
-                        | KeyCode::Digit4
-                        | KeyCode::Digit5
-                        | KeyCode::Digit6
-                        | KeyCode::Digit7
-                        | KeyCode::Digit8
-                        | KeyCode::Digit9 => {
-                            let slot_index = match key {
-                                KeyCode::Digit1 => 0,
-                                KeyCode::Digit2 => 1,
-                                KeyCode::Digit3 => 2,
-                                KeyCode::Digit4 => 3,
-                                KeyCode::Digit5 => 4,
-                                KeyCode::Digit6 => 5,
-                                KeyCode::Digit7 => 6,
-                                KeyCode::Digit8 => 7,
-                                KeyCode::Digit9 => 8,
-                                _ => 0,
-                            };
-                            if slot_index < HOTBAR_SIZE {
-                                self.inventory_cursor = slot_index;
-                                self.inventory.select_slot(slot_index);
-                                self.print_selected();
-                                self.mark_ui_dirty();
-                                return true;
-                            }
-                        }
-                        _ => {}
+        match key {
+            KeyCode::Slash => {
+                self.focus_inventory_search();
+                return true;
+            }
+            KeyCode::KeyF if self.modifiers.state().control_key() => {
+                self.focus_inventory_search();
+                return true;
+            }
+            KeyCode::Tab => {
+                self.focus_inventory_search();
+                return true;
+            }
+            KeyCode::ArrowLeft => {
+                self.move_inventory_cursor(-1, 0);
+                return true;
+            }
+            KeyCode::ArrowRight => {
+                self.move_inventory_cursor(1, 0);
+                return true;
+            }
+            KeyCode::ArrowUp => {
+                self.move_inventory_cursor(0, -1);
+                return true;
+            }
+            KeyCode::ArrowDown => {
+                self.move_inventory_cursor(0, 1);
+                return true;
+            }
+            KeyCode::Enter | KeyCode::Space => {
+                if let Some(origin) = self.inventory_swap_slot {
+                    if origin == self.inventory_cursor {
+                        println!("Swap cancelled.");
+                        self.inventory_swap_slot = None;
+                    } else {
+                        let target = self.inventory_cursor;
+                        self.inventory.swap_slots(origin, target);
+                        println!(
+                            "Swapped hotbar slots {} and {}.",
+                            origin + 1,
+                            target + 1
+                        );
+                        self.inventory_swap_slot = None;
+                        self.print_selected();
                     }
+                } else {
+                    self.inventory_swap_slot = Some(self.inventory_cursor);
+                    println!(
+                        "Slot {} ready to swap. Select another slot.",
+                        self.inventory_cursor + 1
+                    );
+                }
+                self.mark_ui_dirty();
+                return true;
+            }
+            KeyCode::KeyZ if self.modifiers.state().control_key() => {
+                if let Some((slot, stack)) = self.inventory_trashed.take() {
+                    let name = stack.item.name();
+                    let target = if self.inventory.hotbar[slot].is_none() {
+                        slot
+                    } else if let Some(empty) = self.inventory.first_empty_slot() {
+                        empty
+                    } else {
+                        println!("No free slot to restore {}.", name);
+                        self.inventory_trashed = Some((slot, stack));
+                        return true;
+                    };
+                    self.inventory.set_slot(target, Some(stack));
+                    println!("Restored {} to slot {}.", name, target + 1);
+                    self.print_selected();
+                    self.mark_ui_dirty();
+                }
+                return true;
+            }
+            KeyCode::KeyZ => {
+                self.inventory.cycle_slot_block(self.inventory_cursor, -1);
+                let description = self
+                    .inventory
+                    .item_at(self.inventory_cursor)
+                    .map(|block| block.name())
+                    .unwrap_or("Empty");
+                println!("Slot {} set to {}.", self.inventory_cursor + 1, description);
+                self.inventory.select_slot(self.inventory_cursor);
+                self.print_selected();
+                self.mark_ui_dirty();
+                return true;
+            }
+            KeyCode::KeyX => {
+                self.inventory.cycle_slot_block(self.inventory_cursor, 1);
+                let description = self
+                    .inventory
+                    .item_at(self.inventory_cursor)
+                    .map(|block| block.name())
+                    .unwrap_or("Empty");
+                println!("Slot {} set to {}.", self.inventory_cursor + 1, description);
+                self.inventory.select_slot(self.inventory_cursor);
+                self.print_selected();
+                self.mark_ui_dirty();
+                return true;
+            }
+            KeyCode::Backspace | KeyCode::Delete => {
+                self.inventory.clear_slot(self.inventory_cursor);
+                println!("Cleared hotbar slot {}.", self.inventory_cursor + 1);
+                self.print_selected();
+                self.mark_ui_dirty();
+                return true;
+            }
+            KeyCode::Digit1
+            | KeyCode::Digit2
+            | KeyCode::Digit3
+            | KeyCode::Digit4
+            | KeyCode::Digit5
+            | KeyCode::Digit6
+            | KeyCode::Digit7
+            | KeyCode::Digit8
+            | KeyCode::Digit9 => {
+                let slot_index = match key {
+                    KeyCode::Digit1 => 0,
+                    KeyCode::Digit2 => 1,
+                    KeyCode::Digit3 => 2,
+                    KeyCode::Digit4 => 3,
+                    KeyCode::Digit5 => 4,
+                    KeyCode::Digit6 => 5,
+                    KeyCode::Digit7 => 6,
+                    KeyCode::Digit8 => 7,
+                    KeyCode::Digit9 => 8,
+                    _ => 0,
+                };
+                if slot_index < HOTBAR_SIZE {
+                    self.inventory_cursor = slot_index;
+                    self.inventory.select_slot(slot_index);
+                    self.print_selected();
+                    self.mark_ui_dirty();
+                    return true;
                 }
-                false
             }
-            _ => false,
+            _ => {}
+        }
+        false
+    }
+
+    /// Health, and air while it isn't full, as bars stacked up from `bottom` and
+    /// right-aligned to `right`.
+    fn draw_vitals(&self, ui: &mut UiGeometry, right: f32, bottom: f32) {
+        let width = ui_width(0.2);
+        let height = 0.016;
+        let gap = 0.006;
+        let mut bars = vec![("HEALTH", self.health / MAX_HEALTH, [0.84, 0.24, 0.22, 0.95])];
+        if self.breath < MAX_BREATH {
+            bars.push(("AIR", self.breath / MAX_BREATH, [0.36, 0.7, 0.95, 0.95]));
+        }
+        let mut y = bottom;
+        for (label, fraction, color) in bars {
+            let min = (right - width, y - height);
+            let max = (right, y);
+            ui.add_panel(min, max, [0.06, 0.07, 0.12, 0.96], [0.04, 0.05, 0.08, 0.8], None);
+            ui.add_rect(min, (min.0 + width * fraction.clamp(0.0, 1.0), max.1), color);
+            ui.add_text(
+                (min.0 + ui_width(0.006), min.1 + 0.004),
+                0.009,
+                [1.0, 1.0, 1.0, 0.95],
+                label,
+            );
+            y -= height + gap;
         }
     }
+
     fn draw_hotbar(&self, ui: &mut UiGeometry) {
         let slot_count = self.inventory.hotbar.len();
         if slot_count == 0 {
@@ -2276,6 +4717,7 @@ impl<'window> State<'window> {
 
         let title_pos = (bar_left, (bar_top - 0.03).max(0.06));
         ui.add_text(title_pos, 0.016, [0.86, 0.9, 1.0, 0.95], "QUICK BAR");
+        self.draw_vitals(ui, bar_right, bar_top - 0.012);
 
         let slot_start_x = 0.5 - total_width * 0.5;
         let slot_top = bar_top + panel_pad_y;
@@ -2324,7 +4766,7 @@ impl<'window> State<'window> {
             let icon_min = (slot_min.0 + icon_pad_x, slot_min.1 + icon_pad_y);
             let icon_max = (slot_max.0 - icon_pad_x, slot_max.1 - icon_pad_y);
 
-            match slot {
+            match slot.map(|stack| stack.item) {
                 Some(ItemType::Block(block)) => {
                     let tint = if index == selected_slot {
                         [1.0, 0.96, 0.86, 1.0]
@@ -2357,6 +4799,15 @@ impl<'window> State<'window> {
                     };
                     ui.add_rect(icon_min, icon_max, tint);
                 }
+                Some(ItemType::Bucket(state)) => {
+                    let mut tint = bucket_icon_color(state);
+                    if index == selected_slot || self.inventory_cursor == index {
+                        for channel in &mut tint[..3] {
+                            *channel = (*channel + 0.12).min(1.0);
+                        }
+                    }
+                    ui.add_rect(icon_min, icon_max, tint);
+                }
                 None => {
                     ui.add_rect(icon_min, icon_max, [0.08, 0.09, 0.12, 0.55]);
                 }
@@ -2369,6 +4820,7 @@ impl<'window> State<'window> {
                 [0.7, 0.76, 0.92, 1.0],
                 &(index + 1).to_string(),
             );
+            draw_stack_count(ui, *slot, slot_max, 0.013);
         }
 
         if let Some(status) = &theme.status {
@@ -2388,19 +4840,20 @@ impl<'window> State<'window> {
             );
             let text_margin = ui_width(0.014);
             let text_width = (chip_width - text_margin * 2.0).max(0.02);
-            let mut status_y = ui.add_wrapped_text(
-                (chip_min.0 + text_margin, chip_min.1 + 0.016),
-                0.014,
+            let mut status_y = chip_min.1 + 0.016;
+            status_y += ui.add_text_wrapped(
+                (chip_min.0 + text_margin, status_y),
                 text_width,
+                0.014,
                 status.chip_text,
                 status.label,
             );
             if let Some(detail) = status.detail {
                 status_y += 0.002;
-                ui.add_wrapped_text(
+                ui.add_text_wrapped(
                     (chip_min.0 + text_margin, status_y),
-                    0.011,
                     text_width,
+                    0.011,
                     [0.78, 0.82, 0.96, 1.0],
                     detail,
                 );
@@ -2422,8 +4875,8 @@ impl<'window> State<'window> {
 
         ui.add_rect_fullscreen((0.0, 0.0), (1.0, 1.0), [0.01, 0.02, 0.05, 0.68]);
 
-        let panel_min = (ui_width(0.22), 0.24);
-        let panel_max = (1.0 - ui_width(0.22), 0.78);
+        let panel_min = (ui_width(0.22), 0.18);
+        let panel_max = (1.0 - ui_width(0.22), 0.86);
         let shadow_offset = ui_width(0.016);
 
         ui.add_rect(
@@ -2459,6 +4912,7 @@ impl<'window> State<'window> {
         let menu_items = [
             ("RESUME", "Press ESC to return to the game"),
             ("SETTINGS", "Press S to adjust display, audio, and controls"),
+            ("CONTROLS", "Press F1 to list every key binding"),
             ("QUIT TO DESKTOP", "Press Alt+F4 to close the game"),
         ];
 
@@ -2495,9 +4949,64 @@ impl<'window> State<'window> {
             "ESC: resume | S: open settings | Click: return to cursor",
         );
     }
+    /// Draws a labelled, draggable slider row spanning `columns` and records its track for
+    /// pointer hits. Returns the cursor below the row.
+    fn draw_settings_slider(
+        &self,
+        ui: &mut UiGeometry,
+        slider: SettingsSlider,
+        label: &str,
+        value: &str,
+        columns: (f32, f32),
+        mut cursor_y: f32,
+    ) -> f32 {
+        let slider_width = ui_width(0.32);
+        let slider_height = 0.012;
+        let (tab, focus_index) = slider.focus();
+        let focused = self.settings_focus_index == focus_index && self.settings_selected_tab == tab;
+        let label_color = if focused {
+            [0.95, 0.98, 1.0, 1.0]
+        } else {
+            [0.78, 0.82, 0.94, 1.0]
+        };
+        ui.add_text((columns.0, cursor_y), 0.014, label_color, label);
+        ui.add_text(
+            (columns.1 - ui_width(0.09), cursor_y),
+            0.014,
+            [0.86, 0.9, 1.0, 1.0],
+            value,
+        );
+        cursor_y += 0.024;
+
+        let (min, max) = slider.range();
+        let ratio = ((self.slider_value(slider) - min) / (max - min)).clamp(0.0, 1.0);
+        let track_min = (columns.0, cursor_y);
+        let track_max = (columns.0 + slider_width, cursor_y + slider_height);
+        ui.add_rect(track_min, track_max, [0.16, 0.18, 0.26, 0.9]);
+        let fill_max_x = track_min.0 + slider_width * ratio;
+        ui.add_rect(
+            track_min,
+            (fill_max_x, track_max.1),
+            [0.36, 0.54, 0.88, 0.95],
+        );
+        let handle_width = ui_width(0.01);
+        let handle_min_x =
+            (fill_max_x - handle_width * 0.5).clamp(track_min.0, track_max.0 - handle_width);
+        ui.add_rect(
+            (handle_min_x, track_min.1 - 0.005),
+            (handle_min_x + handle_width, track_max.1 + 0.005),
+            if focused {
+                [0.95, 0.98, 1.0, 1.0]
+            } else {
+                [0.72, 0.78, 0.94, 1.0]
+            },
+        );
+        self.set_slider_rect(slider, (track_min, track_max));
+        cursor_y + slider_height + 0.04
+    }
+
     fn draw_settings_overlay(&self, ui: &mut UiGeometry) {
-        self.settings_fov_slider.set(None);
-        self.settings_sensitivity_slider.set(None);
+        self.settings_slider_rects.set([None; SettingsSlider::ALL.len()]);
         ui.add_rect_fullscreen((0.0, 0.0), (1.0, 1.0), [0.01, 0.02, 0.05, 0.72]);
 
         let panel_min = (ui_width(0.18), 0.16);
@@ -2573,48 +5082,354 @@ impl<'window> State<'window> {
         let mut cursor_y = content_min.1;
         match self.settings_selected_tab {
             SettingsTab::Display => {
-                let mut entries = Vec::new();
-                let fov_ratio = ((self.settings_fov_deg - 60.0) / 40.0).clamp(0.0, 1.0);
-                entries.push((
-                    "FIELD OF VIEW".to_string(),
-                    format!("{:.0} DEG", self.settings_fov_deg),
-                    fov_ratio,
-                    0usize,
-                ));
-                let sens_ratio =
-                    ((self.settings_sensitivity - 0.0005) / (0.02 - 0.0005)).clamp(0.0, 1.0);
-                entries.push((
-                    "LOOK SENSITIVITY".to_string(),
-                    format!("{:.3}", self.settings_sensitivity * 1000.0),
-                    sens_ratio,
-                    1usize,
-                ));
+                let entries = [
+                    (
+                        SettingsSlider::Fov,
+                        "FIELD OF VIEW",
+                        format!("{:.0} DEG", self.settings_fov_deg),
+                    ),
+                    (
+                        SettingsSlider::Sensitivity,
+                        "LOOK SENSITIVITY",
+                        format!("{:.3}", self.settings_sensitivity * 1000.0),
+                    ),
+                ];
+                for (slider, label, value) in entries {
+                    cursor_y = self.draw_settings_slider(
+                        ui,
+                        slider,
+                        label,
+                        &value,
+                        (content_min.0, content_max.0),
+                        cursor_y,
+                    );
+                }
+
+                let focused = self.settings_focus_index == 2;
+                ui.add_text(
+                    (content_min.0, cursor_y),
+                    0.014,
+                    if focused {
+                        [0.95, 0.98, 1.0, 1.0]
+                    } else {
+                        [0.78, 0.82, 0.94, 1.0]
+                    },
+                    "CLOUDS",
+                );
+                ui.add_text(
+                    (content_max.0 - ui_width(0.09), cursor_y),
+                    0.014,
+                    if self.settings_clouds {
+                        [0.56, 0.9, 0.64, 1.0]
+                    } else {
+                        [0.86, 0.6, 0.56, 1.0]
+                    },
+                    if self.settings_clouds { "ON" } else { "OFF" },
+                );
+                cursor_y += 0.03;
 
-                for (label, value, ratio, focus_index) in entries {
-                    let focused = self.settings_focus_index == focus_index
-                        && self.settings_selected_tab == SettingsTab::Display;
-                    let label_color = if focused {
+                let focused = self.settings_focus_index == 3;
+                ui.add_text(
+                    (content_min.0, cursor_y),
+                    0.014,
+                    if focused {
                         [0.95, 0.98, 1.0, 1.0]
                     } else {
                         [0.78, 0.82, 0.94, 1.0]
-                    };
-                    ui.add_text((content_min.0, cursor_y), 0.014, label_color, &label);
+                    },
+                    "ATMOSPHERE",
+                );
+                ui.add_text(
+                    (content_max.0 - ui_width(0.09), cursor_y),
+                    0.014,
+                    [0.86, 0.9, 1.0, 1.0],
+                    self.world.atmosphere_preset().label(),
+                );
+                cursor_y += 0.03;
+
+                for (index, label, enabled) in [
+                    (4, "REDUCE MOTION", self.settings_reduce_motion),
+                    (5, "OUTLINE COLORS", self.settings_outline_colors),
+                    (6, "SHOW CLOCK", self.settings_show_clock),
+                ] {
+                    let focused = self.settings_focus_index == index;
+                    ui.add_text(
+                        (content_min.0, cursor_y),
+                        0.014,
+                        if focused {
+                            [0.95, 0.98, 1.0, 1.0]
+                        } else {
+                            [0.78, 0.82, 0.94, 1.0]
+                        },
+                        label,
+                    );
+                    ui.add_text(
+                        (content_max.0 - ui_width(0.09), cursor_y),
+                        0.014,
+                        if enabled {
+                            [0.56, 0.9, 0.64, 1.0]
+                        } else {
+                            [0.86, 0.6, 0.56, 1.0]
+                        },
+                        if enabled { "ON" } else { "OFF" },
+                    );
+                    cursor_y += 0.03;
+                }
+
+                let texture_pack = self.texture_packs[self.settings_texture_pack].label();
+                let render_distance = format!("{} CHUNKS", self.settings_render_distance);
+                for (index, label, value) in [
+                    (7, "FOLIAGE DETAIL", self.settings_foliage_detail.label()),
+                    (8, "TEXTURE PACK", texture_pack.as_str()),
+                    (9, "RENDER DISTANCE", render_distance.as_str()),
+                    (10, "SHADOWS", self.settings_shadow_quality.label()),
+                ] {
+                    let focused = self.settings_focus_index == index;
+                    ui.add_text(
+                        (content_min.0, cursor_y),
+                        0.014,
+                        if focused {
+                            [0.95, 0.98, 1.0, 1.0]
+                        } else {
+                            [0.78, 0.82, 0.94, 1.0]
+                        },
+                        label,
+                    );
                     ui.add_text(
                         (content_max.0 - ui_width(0.09), cursor_y),
                         0.014,
                         [0.86, 0.9, 1.0, 1.0],
+                        value,
+                    );
+                    cursor_y += 0.03;
+                }
+            }
+            SettingsTab::Audio => {
+                let focused = self.settings_focus_index == 0;
+                ui.add_text(
+                    (content_min.0, cursor_y),
+                    0.014,
+                    if focused {
+                        [0.95, 0.98, 1.0, 1.0]
+                    } else {
+                        [0.78, 0.82, 0.94, 1.0]
+                    },
+                    "MASTER VOLUME",
+                );
+                ui.add_text(
+                    (content_max.0 - ui_width(0.09), cursor_y),
+                    0.014,
+                    [0.86, 0.9, 1.0, 1.0],
+                    &format!("{:.0}%", self.settings_volume * 100.0),
+                );
+                cursor_y += 0.024;
+                let track_min = (content_min.0, cursor_y);
+                let track_max = (content_min.0 + slider_width, cursor_y + slider_height);
+                let ratio = self.settings_volume.clamp(0.0, 1.0);
+                ui.add_rect(track_min, track_max, [0.16, 0.18, 0.26, 0.9]);
+                let fill_max_x = track_min.0 + slider_width * ratio;
+                ui.add_rect(
+                    track_min,
+                    (fill_max_x, track_max.1),
+                    [0.28, 0.62, 0.82, 0.95],
+                );
+                let handle_width = ui_width(0.01);
+                let handle_min_x = (fill_max_x - handle_width * 0.5)
+                    .clamp(track_min.0, track_max.0 - handle_width);
+                ui.add_rect(
+                    (handle_min_x, track_min.1 - 0.005),
+                    (handle_min_x + handle_width, track_max.1 + 0.005),
+                    if focused {
+                        [0.95, 0.98, 1.0, 1.0]
+                    } else {
+                        [0.72, 0.78, 0.94, 1.0]
+                    },
+                );
+                cursor_y += slider_height + 0.04;
+                ui.add_text_wrapped(
+                    (content_min.0, cursor_y),
+                    (content_max.0 - content_min.0).max(0.05),
+                    0.012,
+                    [0.74, 0.79, 0.94, 1.0],
+                    if self.audio.is_live() {
+                        "Scales every sound, including ones already playing."
+                    } else {
+                        "No audio output: build with the audio feature and a sound device."
+                    },
+                );
+            }
+            SettingsTab::Controls => {
+                let focused = self.settings_focus_index == 0;
+                let auto_jump = self.controller.auto_jump();
+                ui.add_text(
+                    (content_min.0, cursor_y),
+                    0.014,
+                    if focused {
+                        [0.95, 0.98, 1.0, 1.0]
+                    } else {
+                        [0.78, 0.82, 0.94, 1.0]
+                    },
+                    "AUTO-JUMP",
+                );
+                ui.add_text(
+                    (content_max.0 - ui_width(0.09), cursor_y),
+                    0.014,
+                    if auto_jump {
+                        [0.56, 0.9, 0.64, 1.0]
+                    } else {
+                        [0.86, 0.6, 0.56, 1.0]
+                    },
+                    if auto_jump { "ON" } else { "OFF" },
+                );
+                cursor_y += 0.034;
+
+                let focused = self.settings_focus_index == 1;
+                ui.add_text(
+                    (content_min.0, cursor_y),
+                    0.014,
+                    if focused {
+                        [0.95, 0.98, 1.0, 1.0]
+                    } else {
+                        [0.78, 0.82, 0.94, 1.0]
+                    },
+                    "MOVEMENT FEEL",
+                );
+                ui.add_text(
+                    (content_max.0 - ui_width(0.09), cursor_y),
+                    0.014,
+                    [0.86, 0.9, 1.0, 1.0],
+                    self.settings_movement_feel.label(),
+                );
+                cursor_y += 0.034;
+
+                let focused = self.settings_focus_index == 2;
+                ui.add_text(
+                    (content_min.0, cursor_y),
+                    0.014,
+                    if focused {
+                        [0.95, 0.98, 1.0, 1.0]
+                    } else {
+                        [0.78, 0.82, 0.94, 1.0]
+                    },
+                    "STICK SENSITIVITY",
+                );
+                ui.add_text(
+                    (content_max.0 - ui_width(0.09), cursor_y),
+                    0.014,
+                    [0.86, 0.9, 1.0, 1.0],
+                    &format!("{:.1}X", self.controller.stick_sensitivity()),
+                );
+                cursor_y += 0.034;
+
+                let entries = [
+                    (
+                        SettingsSlider::WalkSpeed,
+                        "WALK SPEED",
+                        format!("{:.0}", self.controller.walk_speed()),
+                    ),
+                    (
+                        SettingsSlider::SprintMultiplier,
+                        "SPRINT",
+                        format!("{:.1}X", self.controller.sprint_multiplier()),
+                    ),
+                    (
+                        SettingsSlider::FlySpeed,
+                        "FLY SPEED",
+                        format!("{:.0}", self.controller.fly_speed()),
+                    ),
+                ];
+                for (slider, label, value) in entries {
+                    cursor_y = self.draw_settings_slider(
+                        ui,
+                        slider,
+                        label,
                         &value,
+                        (content_min.0, content_max.0),
+                        cursor_y,
                     );
-                    cursor_y += 0.024;
+                }
 
+                ui.add_text(
+                    (content_min.0, cursor_y),
+                    0.014,
+                    [0.9, 0.93, 1.0, 1.0],
+                    "Control remapping is coming soon.",
+                );
+                cursor_y += 0.028;
+                ui.add_text_wrapped(
+                    (content_min.0, cursor_y),
+                    (content_max.0 - content_min.0).max(0.05),
+                    0.012,
+                    [0.74, 0.79, 0.94, 1.0],
+                    "Use T on highlighted components to tweak electrical settings.",
+                );
+            }
+            SettingsTab::Lighting => {
+                let fixed = self.settings_fixed_light;
+                ui.add_text(
+                    (content_min.0, cursor_y),
+                    0.014,
+                    if self.settings_focus_index == 0 {
+                        [0.95, 0.98, 1.0, 1.0]
+                    } else {
+                        [0.78, 0.82, 0.94, 1.0]
+                    },
+                    "FIXED SUN",
+                );
+                ui.add_text(
+                    (content_max.0 - ui_width(0.09), cursor_y),
+                    0.014,
+                    if fixed {
+                        [0.56, 0.9, 0.64, 1.0]
+                    } else {
+                        [0.86, 0.6, 0.56, 1.0]
+                    },
+                    if fixed { "ON" } else { "OFF" },
+                );
+                cursor_y += 0.034;
+
+                for (index, label, degrees, ratio) in [
+                    (
+                        1,
+                        "SUN AZIMUTH",
+                        self.settings_sun_azimuth,
+                        self.settings_sun_azimuth / 360.0,
+                    ),
+                    (
+                        2,
+                        "SUN ELEVATION",
+                        self.settings_sun_elevation,
+                        (self.settings_sun_elevation - 5.0) / 85.0,
+                    ),
+                ] {
+                    let focused = self.settings_focus_index == index;
+                    // The angles only matter while the sun is pinned.
+                    let alpha = if fixed { 1.0 } else { 0.5 };
+                    ui.add_text(
+                        (content_min.0, cursor_y),
+                        0.014,
+                        if focused {
+                            [0.95, 0.98, 1.0, alpha]
+                        } else {
+                            [0.78, 0.82, 0.94, alpha]
+                        },
+                        label,
+                    );
+                    ui.add_text(
+                        (content_max.0 - ui_width(0.09), cursor_y),
+                        0.014,
+                        [0.86, 0.9, 1.0, alpha],
+                        &format!("{degrees:.0} DEG"),
+                    );
+                    cursor_y += 0.024;
                     let track_min = (content_min.0, cursor_y);
                     let track_max = (content_min.0 + slider_width, cursor_y + slider_height);
                     ui.add_rect(track_min, track_max, [0.16, 0.18, 0.26, 0.9]);
-                    let fill_max_x = track_min.0 + slider_width * ratio;
+                    let fill_max_x = track_min.0 + slider_width * ratio.clamp(0.0, 1.0);
                     ui.add_rect(
                         track_min,
                         (fill_max_x, track_max.1),
-                        [0.36, 0.54, 0.88, 0.95],
+                        [0.92, 0.74, 0.36, 0.95 * alpha],
                     );
                     let handle_width = ui_width(0.01);
                     let handle_min_x = (fill_max_x - handle_width * 0.5)
@@ -2628,90 +5443,24 @@ impl<'window> State<'window> {
                             [0.72, 0.78, 0.94, 1.0]
                         },
                     );
-                    match focus_index {
-                        0 => self.settings_fov_slider.set(Some((track_min, track_max))),
-                        1 => self
-                            .settings_sensitivity_slider
-                            .set(Some((track_min, track_max))),
-                        _ => {}
-                    }
                     cursor_y += slider_height + 0.04;
                 }
-            }
-            SettingsTab::Audio => {
-                let focused = self.settings_focus_index == 0;
-                ui.add_text(
-                    (content_min.0, cursor_y),
-                    0.014,
-                    if focused {
-                        [0.95, 0.98, 1.0, 1.0]
-                    } else {
-                        [0.78, 0.82, 0.94, 1.0]
-                    },
-                    "MASTER VOLUME",
-                );
-                ui.add_text(
-                    (content_max.0 - ui_width(0.09), cursor_y),
-                    0.014,
-                    [0.86, 0.9, 1.0, 1.0],
-                    &format!("{:.0}%", self.settings_volume * 100.0),
-                );
-                cursor_y += 0.024;
-                let track_min = (content_min.0, cursor_y);
-                let track_max = (content_min.0 + slider_width, cursor_y + slider_height);
-                let ratio = self.settings_volume.clamp(0.0, 1.0);
-                ui.add_rect(track_min, track_max, [0.16, 0.18, 0.26, 0.9]);
-                let fill_max_x = track_min.0 + slider_width * ratio;
-                ui.add_rect(
-                    track_min,
-                    (fill_max_x, track_max.1),
-                    [0.28, 0.62, 0.82, 0.95],
-                );
-                let handle_width = ui_width(0.01);
-                let handle_min_x = (fill_max_x - handle_width * 0.5)
-                    .clamp(track_min.0, track_max.0 - handle_width);
-                ui.add_rect(
-                    (handle_min_x, track_min.1 - 0.005),
-                    (handle_min_x + handle_width, track_max.1 + 0.005),
-                    if focused {
-                        [0.95, 0.98, 1.0, 1.0]
-                    } else {
-                        [0.72, 0.78, 0.94, 1.0]
-                    },
-                );
-                cursor_y += slider_height + 0.04;
-                ui.add_wrapped_text(
+                ui.add_text_wrapped(
                     (content_min.0, cursor_y),
-                    0.012,
                     (content_max.0 - content_min.0).max(0.05),
-                    [0.74, 0.79, 0.94, 1.0],
-                    "Volume slider is placeholder until the full audio mix is implemented.",
-                );
-            }
-            SettingsTab::Controls => {
-                ui.add_text(
-                    (content_min.0, cursor_y),
-                    0.014,
-                    [0.9, 0.93, 1.0, 1.0],
-                    "Control remapping is coming soon.",
-                );
-                cursor_y += 0.028;
-                ui.add_wrapped_text(
-                    (content_min.0, cursor_y),
                     0.012,
-                    (content_max.0 - content_min.0).max(0.05),
                     [0.74, 0.79, 0.94, 1.0],
-                    "Use T on highlighted components to tweak electrical settings.",
+                    "A fixed sun keeps face shading still while you build.",
                 );
             }
         }
 
         let instructions_width =
             (panel_max.0 - panel_min.0 - ui_width(0.08)).max(0.05);
-        ui.add_wrapped_text(
+        ui.add_text_wrapped(
             (panel_min.0 + ui_width(0.04), panel_max.1 - 0.075),
-            0.012,
             instructions_width,
+            0.012,
             [0.72, 0.78, 0.92, 1.0],
             "TAB: cycle categories   Arrow keys: adjust   ESC: close",
         );
@@ -2789,7 +5538,7 @@ impl<'window> State<'window> {
         );
 
         let selected_slot = self.inventory.selected_slot_index();
-        for idx in 0..HOTBAR_SIZE {
+        for idx in 0..INVENTORY_SLOT_COUNT {
             if let Some((min, max)) = self.inventory_slot_rect(idx) {
                 let mut slot_fill = [0.18, 0.2, 0.28, 0.82];
                 if Some(idx) == self.inventory_hover_slot {
@@ -2826,7 +5575,8 @@ impl<'window> State<'window> {
                 let icon_min = (min.0 + icon_pad_x, min.1 + icon_pad_y);
                 let icon_max = (max.0 - icon_pad_x, max.1 - icon_pad_y);
 
-                match self.inventory.hotbar[idx] {
+                let stack = self.inventory.hotbar.get(idx).copied().flatten();
+                match stack.map(|stack| stack.item) {
                     Some(ItemType::Block(block)) => {
                         ui.add_rect_textured(
                             icon_min,
@@ -2843,6 +5593,9 @@ impl<'window> State<'window> {
                         // Material placeholder
                         ui.add_rect(icon_min, icon_max, [0.6, 0.4, 0.2, 1.0]);
                     }
+                    Some(ItemType::Bucket(state)) => {
+                        ui.add_rect(icon_min, icon_max, bucket_icon_color(state));
+                    }
                     None => {
                         ui.add_rect(icon_min, icon_max, [0.08, 0.09, 0.12, 0.5]);
                     }
@@ -2854,6 +5607,7 @@ impl<'window> State<'window> {
                     [0.72, 0.76, 0.95, 1.0],
                     &format!("{}", idx + 1),
                 );
+                draw_stack_count(ui, stack, max, 0.014);
             }
         }
 
@@ -2909,9 +5663,9 @@ impl<'window> State<'window> {
         );
 
         let query = if self.inventory_search_query.is_empty() {
-            "Search blocks...".to_string()
+            "Search blocks... (/)".to_string()
         } else {
-            self.inventory_search_query.to_ascii_uppercase()
+            self.inventory_search_query.clone()
         };
         let search_text_color = if self.inventory_search_query.is_empty() {
             [0.65, 0.7, 0.82, 1.0]
@@ -2960,7 +5714,12 @@ impl<'window> State<'window> {
             } else if Some(idx) == self.inventory_filter_chip_hover {
                 fill = [0.28, 0.32, 0.46, 0.88];
             }
-            ui.add_panel(min, max, [0.12, 0.13, 0.19, 0.0], fill, None);
+            let border = if Some(idx) == self.inventory_chip_focus {
+                [0.95, 0.86, 0.52, 1.0]
+            } else {
+                [0.12, 0.13, 0.19, 0.0]
+            };
+            ui.add_panel(min, max, border, fill, None);
             ui.add_text(
                 (min.0 + ui_width(0.012), min.1 + 0.008),
                 0.013,
@@ -2969,6 +5728,23 @@ impl<'window> State<'window> {
             );
         }
 
+        let (sort_min, sort_max) = layout.sort_rect;
+        let sort_hover = self
+            .inventory_cursor_pos
+            .is_some_and(|pt| point_in_rect(pt, layout.sort_rect));
+        let sort_fill = match (self.inventory_palette_sort, sort_hover) {
+            (_, true) => [0.34, 0.38, 0.3, 0.9],
+            (PaletteSort::List, false) => [0.18, 0.2, 0.28, 0.8],
+            (_, false) => [0.3, 0.36, 0.26, 0.9],
+        };
+        ui.add_panel(sort_min, sort_max, [0.12, 0.13, 0.19, 0.0], sort_fill, None);
+        ui.add_text(
+            (sort_min.0 + ui_width(0.012), sort_min.1 + 0.008),
+            0.013,
+            [0.94, 0.98, 0.9, 1.0],
+            self.inventory_palette_sort.label(),
+        );
+
         let palette_blocks = &self.inventory_palette_filtered;
         let palette_view_top = layout.palette_content_origin.1;
         let palette_view_bottom = palette_panel_max.1 - FILTER_AREA_PADDING_Y;
@@ -3000,7 +5776,7 @@ impl<'window> State<'window> {
                 {
                     color = [0.58, 0.4, 0.34, 0.92];
                 }
-                if self.inventory.hotbar[self.inventory_cursor] == Some(ItemType::Block(*block)) {
+                if self.inventory.item_at(self.inventory_cursor) == Some(ItemType::Block(*block)) {
                     color = [0.36, 0.44, 0.62, 0.9];
                 }
                 ui.add_panel(
@@ -3023,6 +5799,78 @@ impl<'window> State<'window> {
             }
         }
 
+        // Loadout strip
+        let (strip_min, strip_max) = layout.loadout_strip;
+        if strip_max.1 > strip_min.1 {
+            ui.add_panel(
+                strip_min,
+                strip_max,
+                [0.14, 0.16, 0.22, 0.92],
+                [0.11, 0.12, 0.18, 0.92],
+                None,
+            );
+            let strip_mid = (strip_min.1 + strip_max.1) * 0.5;
+            ui.add_text(
+                (strip_min.0 + ui_width(0.02), strip_mid - 0.007),
+                0.014,
+                [0.9, 0.93, 1.0, 1.0],
+                "LOADOUTS",
+            );
+            let hovered = |rect: Rect| {
+                self.inventory_cursor_pos
+                    .is_some_and(|cursor| point_in_rect(cursor, rect))
+            };
+            let chip_text = |ui: &mut UiGeometry, rect: Rect, color: [f32; 4], label: &str| {
+                let (min, max) = rect;
+                let text_y = (min.1 + max.1) * 0.5 - LOADOUT_TEXT_HEIGHT * 0.5;
+                ui.add_text(
+                    (min.0 + ui_width(0.014), text_y),
+                    LOADOUT_TEXT_HEIGHT,
+                    color,
+                    label,
+                );
+            };
+
+            let full = self.inventory.loadouts.len() >= MAX_LOADOUTS;
+            let save_fill = if full {
+                [0.16, 0.17, 0.22, 0.8]
+            } else if hovered(layout.loadout_save_rect) {
+                [0.3, 0.5, 0.4, 0.95]
+            } else {
+                [0.2, 0.36, 0.3, 0.92]
+            };
+            let (save_min, save_max) = layout.loadout_save_rect;
+            ui.add_panel(save_min, save_max, [0.1, 0.12, 0.16, 0.9], save_fill, None);
+            let save_text = if full {
+                [0.55, 0.58, 0.66, 1.0]
+            } else {
+                [0.9, 1.0, 0.94, 1.0]
+            };
+            chip_text(ui, layout.loadout_save_rect, save_text, "SAVE HOTBAR");
+
+            for (loadout, rect) in self.inventory.loadouts.iter().zip(&layout.loadout_rects) {
+                // The chip whose hotbar matches the current one reads as active.
+                let active = loadout.hotbar == self.inventory.hotbar;
+                let fill = if hovered(*rect) {
+                    [0.32, 0.4, 0.58, 0.95]
+                } else if active {
+                    [0.28, 0.36, 0.56, 0.94]
+                } else {
+                    [0.18, 0.2, 0.28, 0.88]
+                };
+                ui.add_panel(rect.0, rect.1, [0.1, 0.11, 0.16, 0.9], fill, None);
+                chip_text(ui, *rect, [0.88, 0.92, 1.0, 1.0], &loadout_label(&loadout.name));
+            }
+            if self.inventory.loadouts.is_empty() {
+                ui.add_text(
+                    (save_max.0 + ui_width(0.02), strip_mid - LOADOUT_TEXT_HEIGHT * 0.5),
+                    LOADOUT_TEXT_HEIGHT,
+                    [0.6, 0.64, 0.76, 1.0],
+                    "None saved yet",
+                );
+            }
+        }
+
         // Instructions footer
         ui.add_panel(
             instructions_panel_min,
@@ -3035,23 +5883,57 @@ impl<'window> State<'window> {
         let instructions_width =
             (instructions_panel_max.0 - instructions_panel_min.0 - instructions_pad * 2.0).max(0.05);
         let mut instructions_y = instructions_panel_min.1 + 0.018;
-        instructions_y = ui.add_wrapped_text(
+        instructions_y += ui.add_text_wrapped(
             (instructions_panel_min.0 + instructions_pad, instructions_y),
-            0.012,
             instructions_width,
+            0.012,
             [0.9, 0.93, 1.0, 1.0],
-            "Left click: drag/place   Right click: clear slot   Ctrl+Click: quick assign",
+            "Left click: drag/place   Right click: split stack   Ctrl+Click: quick assign   Drop outside: discard (Ctrl+Z undo)",
         );
         instructions_y += 0.004;
-        ui.add_wrapped_text(
+        ui.add_text_wrapped(
             (instructions_panel_min.0 + instructions_pad, instructions_y),
-            0.012,
             instructions_width,
+            0.012,
             [0.75, 0.8, 0.94, 1.0],
-            "Scroll over the palette to browse, type to search, and press Enter/Esc to exit search.",
+            "Scroll over the palette to browse, type to search, and press Enter/Esc to exit search. Click a loadout to load it, right-click to delete it.",
         );
 
-        if let (Some(item), Some(cursor)) = (self.inventory_drag_block, self.inventory_cursor_pos)
+        if let (None, Some(index)) = (self.inventory_drag_block, self.inventory_palette_hover) {
+            if let (Some(block), Some((slot_min, slot_max))) = (
+                self.inventory_palette_filtered.get(index).copied(),
+                self.palette_slot_rect(&layout, index),
+            ) {
+                self.draw_palette_tooltip(ui, block, slot_min, slot_max);
+            }
+        }
+
+        if self.inventory_drag_block.is_some() {
+            let over_trash = self
+                .inventory_cursor_pos
+                .is_some_and(|cursor| !point_in_rect(cursor, layout.panel));
+            let (border, fill, text_color) = if over_trash {
+                ([0.86, 0.32, 0.3, 0.95], [0.42, 0.1, 0.1, 0.92], [1.0, 0.9, 0.88, 1.0])
+            } else {
+                ([0.3, 0.32, 0.4, 0.85], [0.1, 0.1, 0.14, 0.85], [0.78, 0.8, 0.88, 1.0])
+            };
+            let trash_min = (0.5 - ui_width(0.16), panel_max.1 + 0.016);
+            let trash_max = (0.5 + ui_width(0.16), panel_max.1 + 0.07);
+            ui.add_panel(trash_min, trash_max, border, fill, None);
+            let label = if over_trash {
+                "RELEASE TO DISCARD"
+            } else {
+                "DROP OUTSIDE TO DISCARD"
+            };
+            ui.add_text(
+                (trash_min.0 + ui_width(0.02), trash_min.1 + 0.018),
+                0.016,
+                text_color,
+                label,
+            );
+        }
+
+        if let (Some(stack), Some(cursor)) = (self.inventory_drag_block, self.inventory_cursor_pos)
         {
             let half_y = DRAG_ICON_SIZE * 0.5;
             let half_x = ui_width(half_y);
@@ -3060,7 +5942,7 @@ impl<'window> State<'window> {
             let min_y = (cursor.1 - half_y).clamp(0.0, 1.0 - DRAG_ICON_SIZE);
             let max_x = (min_x + icon_width).min(0.995);
             let max_y = (min_y + DRAG_ICON_SIZE).min(0.995);
-            match item {
+            match stack.item {
                 ItemType::Block(block) => {
                     ui.add_rect_textured(
                         (min_x, min_y),
@@ -3075,8 +5957,80 @@ impl<'window> State<'window> {
                 ItemType::Material(_) => {
                     ui.add_rect((min_x, min_y), (max_x, max_y), [0.6, 0.4, 0.2, 0.92]);
                 }
+                ItemType::Bucket(state) => {
+                    let mut tint = bucket_icon_color(state);
+                    tint[3] = 0.92;
+                    ui.add_rect((min_x, min_y), (max_x, max_y), tint);
+                }
             }
             ui.add_rect((min_x, min_y), (max_x, max_y), [0.95, 0.98, 1.0, 0.32]);
+            draw_stack_count(ui, Some(stack), (max_x, max_y), 0.014);
+        }
+    }
+
+    fn draw_palette_tooltip(
+        &self,
+        ui: &mut UiGeometry,
+        block: BlockType,
+        slot_min: (f32, f32),
+        slot_max: (f32, f32),
+    ) {
+        let mut lines = vec![block.description().to_string()];
+        lines.extend(palette_tooltip_lines(block));
+        let title = block.name();
+        let line_height = 0.013;
+        let line_gap = 0.006;
+        let pad = ui_width(0.016);
+        // Long descriptions wrap rather than stretching the tooltip across the screen.
+        let wrap_width = ui_width(0.3).max(text_width(title, 0.016));
+        let content_width = lines
+            .iter()
+            .flat_map(|line| wrap_text(line, line_height, wrap_width))
+            .map(|line| text_width(&line, line_height))
+            .fold(text_width(title, 0.016), f32::max);
+        let width = (content_width + pad * 2.0).max(ui_width(0.2));
+        let lines_height: f32 = lines
+            .iter()
+            .map(|line| {
+                let count = wrap_text(line, line_height, content_width).len();
+                count as f32 * text_line_height(line_height) + line_gap
+            })
+            .sum();
+        let height = 0.05 + lines_height;
+
+        // Follow the cursor (or sit beside the slot without one), flipping to the other
+        // side near the right edge and clamping like the drag icon so it stays on-screen.
+        let gap = ui_width(0.02);
+        let anchor = self
+            .inventory_cursor_pos
+            .unwrap_or((slot_max.0, slot_min.1));
+        let mut min_x = anchor.0 + gap;
+        if min_x + width > 0.99 {
+            min_x = anchor.0 - gap - width;
+        }
+        let min_x = min_x.clamp(0.01, (0.99 - width).max(0.01));
+        let min_y = (anchor.1 + 0.02).clamp(0.01, (0.99 - height).max(0.01));
+        let min = (min_x, min_y);
+        let max = (min_x + width, min_y + height);
+
+        ui.add_panel(
+            min,
+            max,
+            [0.3, 0.4, 0.62, 0.96],
+            [0.06, 0.07, 0.11, 0.96],
+            Some([0.34, 0.52, 0.86, 0.28]),
+        );
+        ui.add_text((min.0 + pad, min.1 + 0.014), 0.016, [0.95, 0.97, 1.0, 1.0], title);
+        let mut y = min.1 + 0.042;
+        for (index, line) in lines.iter().enumerate() {
+            // The description leads in a brighter colour than the detail lines under it.
+            let color = if index == 0 {
+                [0.92, 0.94, 0.86, 1.0]
+            } else {
+                [0.8, 0.85, 0.96, 1.0]
+            };
+            y += ui.add_text_wrapped((min.0 + pad, y), content_width, line_height, color, line);
+            y += line_gap;
         }
     }
 
@@ -3157,6 +6111,9 @@ impl<'window> State<'window> {
                         ItemType::Material(_) => {
                             ui.add_rect(icon_min, icon_max, [0.6, 0.4, 0.2, 1.0]);
                         }
+                        ItemType::Bucket(state) => {
+                            ui.add_rect(icon_min, icon_max, bucket_icon_color(state));
+                        }
                     }
                 }
             }
@@ -3208,6 +6165,9 @@ impl<'window> State<'window> {
                 ItemType::Material(_) => {
                     ui.add_rect(icon_min, icon_max, [0.6, 0.4, 0.2, 1.0]);
                 }
+                ItemType::Bucket(state) => {
+                    ui.add_rect(icon_min, icon_max, bucket_icon_color(state));
+                }
             }
 
             // Show count if > 1
@@ -3233,16 +6193,41 @@ impl<'window> State<'window> {
     fn build_ui_geometry(&self) -> UiGeometry {
         let mut ui = UiGeometry::new(self.ui_scaler);
 
+        if self.underwater_blend > 0.0 {
+            let ([r, g, b], alpha) = match self.submerged_fluid {
+                FluidKind::Water => (UNDERWATER_OVERLAY_COLOR, 0.28),
+                FluidKind::Lava => (LAVA_OVERLAY_COLOR, 0.6),
+            };
+            let color = [r, g, b, alpha * self.underwater_blend];
+            ui.add_rect_fullscreen((0.0, 0.0), (1.0, 1.0), color);
+        }
+
+        // Photo mode keeps the frame clean until a menu is opened on top of it.
+        if self.photo_mode && !self.is_in_menu() {
+            return ui;
+        }
+
         if self.mouse_grabbed && !self.is_in_menu() {
             let center = self.crosshair_ui_center();
             let thickness = 0.0045;
             let half_thickness = thickness * 0.5;
             let half_thickness_x = ui_width(half_thickness);
-            let gap = 0.014;
+            // A fresh edit pushes the arms out a little and tints them, easing back over
+            // `HIT_MARKER_SECONDS`.
+            let (pulse, pulse_color) = self
+                .hit_marker
+                .map(|(color, remaining)| ((remaining / HIT_MARKER_SECONDS).clamp(0.0, 1.0), color))
+                .unwrap_or((0.0, [1.0, 1.0, 1.0]));
+            let gap = 0.014 + 0.005 * pulse;
             let gap_x = ui_width(gap);
             let arm = 0.03;
             let arm_x = ui_width(arm);
-            let crosshair_color = [1.0, 1.0, 1.0, 0.78];
+            let crosshair_color = [
+                1.0 + (pulse_color[0] - 1.0) * pulse,
+                1.0 + (pulse_color[1] - 1.0) * pulse,
+                1.0 + (pulse_color[2] - 1.0) * pulse,
+                0.78 + 0.2 * pulse,
+            ];
 
             ui.add_rect(
                 (center.0 - half_thickness_x, center.1 - gap - arm),
@@ -3275,31 +6260,421 @@ impl<'window> State<'window> {
             );
         }
 
-        if let Some(editor) = &self.config_editor {
-            self.draw_config_overlay(&mut ui, editor);
-        } else if let Some(info) = &self.inspect_info {
-            self.draw_inspect_overlay(&mut ui, info);
-        }
+        if !self.is_in_menu() {
+            let mut corner_y = 0.01;
+            if self.debug_mode {
+                corner_y = self.draw_debug_overlay(&mut ui) + 0.01;
+            }
+            if self.profiler_overlay {
+                self.draw_profiler_overlay(&mut ui, corner_y);
+            }
+        }
+
+        if !self.is_in_menu() {
+            let mut corner_y = 0.01;
+            if self.electrical_summary_open {
+                corner_y = self.draw_electrical_summary(&mut ui) + 0.01;
+            }
+            if self.settings_show_clock {
+                self.draw_clock(&mut ui, corner_y);
+            }
+        }
+
+        if let Some(measurement) = self.measurement() {
+            self.draw_measure_overlay(&mut ui, measurement);
+        }
+
+        if self.schematic_mode {
+            self.draw_schematic_overlay(&mut ui);
+        }
+
+        if self.console_input.is_some() || self.console_message.is_some() {
+            self.draw_console_overlay(&mut ui);
+        }
+
+        if let Some(editor) = &self.config_editor {
+            self.draw_config_overlay(&mut ui, editor);
+        } else if let Some(info) = &self.inspect_info {
+            self.draw_inspect_overlay(&mut ui, info);
+        }
+
+        if !self.paused {
+            self.draw_hotbar(&mut ui);
+        }
+
+        if self.inventory_open {
+            self.draw_inventory_overlay(&mut ui);
+        }
+
+        if self.crafting_open {
+            self.draw_crafting_overlay(&mut ui);
+        }
+
+        if self.settings_open {
+            self.draw_settings_overlay(&mut ui);
+        } else if self.paused {
+            self.draw_pause_overlay(&mut ui);
+        }
+
+        if self.help_open {
+            self.draw_help_overlay(&mut ui);
+        }
+
+        ui
+    }
+
+    fn draw_help_overlay(&self, ui: &mut UiGeometry) {
+        ui.add_rect_fullscreen((0.0, 0.0), (1.0, 1.0), [0.01, 0.02, 0.05, 0.72]);
+
+        let panel_min = (ui_width(0.18), 0.1);
+        let panel_max = (1.0 - ui_width(0.18), 0.9);
+        ui.add_panel(
+            panel_min,
+            panel_max,
+            [0.12, 0.14, 0.2, 0.98],
+            [0.08, 0.09, 0.14, 0.95],
+            Some([0.36, 0.54, 0.88, 0.34]),
+        );
+
+        let header_min = (panel_min.0 + ui_width(0.03), panel_min.1 + 0.03);
+        let header_max = (panel_max.0 - ui_width(0.03), header_min.1 + 0.07);
+        ui.add_rect(header_min, header_max, [0.18, 0.2, 0.28, 0.96]);
+        ui.add_text(
+            (header_min.0 + ui_width(0.012), header_min.1 + 0.02),
+            0.028,
+            [0.95, 0.98, 1.0, 1.0],
+            "CONTROLS",
+        );
+
+        let rows = help_rows();
+        let key_x = panel_min.0 + ui_width(0.05);
+        let action_x = panel_min.0 + ui_width(0.2);
+        let row_step = 0.03;
+        let mut y = header_max.1 + 0.03;
+        for (keys, text) in rows.iter().skip(self.help_scroll).take(HELP_VISIBLE_ROWS) {
+            match keys {
+                None => ui.add_text(
+                    (panel_min.0 + ui_width(0.04), y),
+                    0.016,
+                    [0.55, 0.72, 1.0, 1.0],
+                    &text.to_ascii_uppercase(),
+                ),
+                Some(keys) => {
+                    ui.add_text(
+                        (key_x, y),
+                        0.013,
+                        [0.95, 0.98, 1.0, 1.0],
+                        &keys.to_ascii_uppercase(),
+                    );
+                    ui.add_text(
+                        (action_x, y),
+                        0.013,
+                        [0.76, 0.81, 0.94, 1.0],
+                        &text.to_ascii_uppercase(),
+                    );
+                }
+            }
+            y += row_step;
+        }
+
+        let footer = if rows.len() > HELP_VISIBLE_ROWS {
+            "F1/ESC: close | UP/DOWN or wheel: scroll"
+        } else {
+            "F1/ESC: close"
+        };
+        ui.add_text(
+            (panel_min.0 + ui_width(0.04), panel_max.1 - 0.045),
+            0.012,
+            [0.72, 0.78, 0.92, 1.0],
+            &footer.to_ascii_uppercase(),
+        );
+    }
+
+    /// Draws the stats panel in the top-left corner and returns its bottom edge.
+    fn draw_debug_overlay(&self, ui: &mut UiGeometry) -> f32 {
+        let world = self.world.stats();
+        let gpu = self.renderer.stats();
+        let frame_ms = self.smoothed_frame_dt * 1000.0;
+        let lines = [
+            format!(
+                "FPS: {:.0}  Frame: {:.2} ms",
+                1.0 / self.smoothed_frame_dt.max(1e-6),
+                frame_ms
+            ),
+            format!("Chunks loaded: {}", world.loaded_chunks),
+            {
+                let environment = self.world.environment();
+                let (hour, minute) = environment.clock_time();
+                format!(
+                    "Time: {hour:02}:{minute:02}  Day length: {:.0} s",
+                    environment.day_length()
+                )
+            },
+            format!("Blocks: {}  Fluid cells: {}", world.non_air_blocks, world.fluid_cells),
+            format!(
+                "Electrical nodes: {}  Rebuilds: {}",
+                world.electrical_nodes, world.electrical_rebuilds
+            ),
+            format!(
+                "Sound: {}{}",
+                self.audio.last_cue().unwrap_or("none"),
+                if self.audio.is_submerged() {
+                    " (muffled)"
+                } else {
+                    ""
+                }
+            ),
+            format!(
+                "Animation: x{:.3}{}",
+                self.animation_time_scale,
+                if self.animations_frozen { "  FROZEN" } else { "" }
+            ),
+            format!(
+                "Meshes: {}  Verts: {}  Indices: {}",
+                gpu.chunk_meshes, gpu.vertices, gpu.indices
+            ),
+            format!(
+                "Chunk buffers: {:.1} MB  Allocs: {}  Draw calls: {}",
+                gpu.chunk_buffer_bytes as f64 / (1024.0 * 1024.0),
+                gpu.chunk_buffer_allocations,
+                gpu.draw_calls
+            ),
+            format!("Remeshed last update: {} chunks", gpu.chunks_remeshed),
+            format!(
+                "Wireframe (F11): {}",
+                match (self.renderer.supports_wireframe(), self.wireframe) {
+                    (false, _) => "unsupported",
+                    (true, true) => "on",
+                    (true, false) => "off",
+                }
+            ),
+        ];
+
+        let line_height = 0.013;
+        let line_step = line_height + 0.008;
+        let min = (0.01, 0.01);
+        let max = (
+            min.0 + ui_width(0.52),
+            min.1 + 0.02 + lines.len() as f32 * line_step,
+        );
+        ui.add_panel(min, max, [0.1, 0.12, 0.16, 0.7], [0.03, 0.04, 0.06, 0.7], None);
+        let mut y = min.1 + 0.012;
+        for line in &lines {
+            ui.add_text(
+                (min.0 + ui_width(0.014), y),
+                line_height,
+                [0.86, 0.92, 0.86, 1.0],
+                line,
+            );
+            y += line_step;
+        }
+        max.1
+    }
+
+    /// Bars for the last frame's profiler sections, longest first, with their times.
+    fn draw_profiler_overlay(&self, ui: &mut UiGeometry, top: f32) {
+        let sections = profiler::last_frame_sections();
+        let line_height = 0.013;
+        let line_step = line_height + 0.012;
+        let rows = sections
+            .as_ref()
+            .map_or(1, |sections| sections.len().clamp(1, PROFILER_OVERLAY_ROWS));
+        let min = (0.01, top);
+        let max = (
+            min.0 + ui_width(0.52),
+            min.1 + 0.02 + (rows + 1) as f32 * line_step,
+        );
+        ui.add_panel(min, max, [0.1, 0.12, 0.16, 0.7], [0.03, 0.04, 0.06, 0.7], None);
+        let text_x = min.0 + ui_width(0.014);
+        let mut y = min.1 + 0.012;
+        ui.add_text((text_x, y), line_height, [0.55, 0.72, 1.0, 1.0], "PROFILER: LAST FRAME");
+        y += line_step;
+
+        let sections = match sections {
+            Some(sections) if !sections.is_empty() => sections,
+            Some(_) => {
+                let color = [0.78, 0.82, 0.94, 1.0];
+                ui.add_text((text_x, y), line_height, color, "No sections recorded yet");
+                return;
+            }
+            None => {
+                let color = [0.86, 0.6, 0.56, 1.0];
+                ui.add_text((text_x, y), line_height, color, "Profiling disabled");
+                return;
+            }
+        };
+        let longest = sections[0].1.as_secs_f32().max(f32::EPSILON);
+        let bar_width = max.0 - text_x - ui_width(0.014);
+        for (label, duration) in sections.iter().take(PROFILER_OVERLAY_ROWS) {
+            let ratio = duration.as_secs_f32() / longest;
+            ui.add_rect(
+                (text_x, y - 0.004),
+                (text_x + bar_width * ratio, y + line_height + 0.004),
+                [0.36, 0.54, 0.88, 0.55],
+            );
+            // The UI font has no underscore.
+            let text = format!(
+                "{}  {:.2} ms",
+                label.replace('_', " "),
+                duration.as_secs_f64() * 1000.0
+            );
+            ui.add_text((text_x + ui_width(0.006), y), line_height, [0.92, 0.95, 1.0, 1.0], &text);
+            y += line_step;
+        }
+    }
+
+    /// Draws the circuit summary in the top-right corner and returns its bottom edge.
+    fn draw_electrical_summary(&self, ui: &mut UiGeometry) -> f32 {
+        let summary = self.world.electrical().summary();
+        let lines = [
+            format!(
+                "{} circuit{}  {:.2} W",
+                summary.circuits,
+                if summary.circuits == 1 { "" } else { "s" },
+                summary.power_watts
+            ),
+            format!(
+                "Wires {}  Resistors {}  Pots {}",
+                summary.wires, summary.resistors, summary.potentiometers
+            ),
+            format!("Sources {}  Grounds {}", summary.sources, summary.grounds),
+            format!("Capacitors {}  Switches {}", summary.capacitors, summary.switches),
+            format!("LEDs {}  Diodes {}", summary.leds, summary.diodes),
+            format!("Overloaded {}", summary.overloaded),
+        ];
 
-        if !self.paused {
-            self.draw_hotbar(&mut ui);
+        let line_height = 0.013;
+        let line_step = line_height + 0.008;
+        let width = ui_width(0.34);
+        let min = (1.0 - ui_width(0.01) - width, 0.01);
+        let max = (min.0 + width, min.1 + 0.02 + lines.len() as f32 * line_step);
+        ui.add_panel(min, max, [0.08, 0.12, 0.18, 0.7], [0.03, 0.05, 0.08, 0.7], None);
+        let mut y = min.1 + 0.012;
+        for (index, line) in lines.iter().enumerate() {
+            let color = if index == lines.len() - 1 && summary.overloaded > 0 {
+                [1.0, 0.5, 0.4, 1.0]
+            } else {
+                [0.8, 0.9, 1.0, 1.0]
+            };
+            ui.add_text((min.0 + ui_width(0.014), y), line_height, color, line);
+            y += line_step;
         }
+        max.1
+    }
 
-        if self.inventory_open {
-            self.draw_inventory_overlay(&mut ui);
-        }
+    fn draw_clock(&self, ui: &mut UiGeometry, top: f32) {
+        let environment = self.world.environment();
+        let (hour, minute) = environment.clock_time();
+        let text = format!("{hour:02}:{minute:02}");
 
-        if self.crafting_open {
-            self.draw_crafting_overlay(&mut ui);
+        let line_height = 0.016;
+        let icon = 0.018;
+        let width = ui_width(0.15);
+        let min = (1.0 - ui_width(0.01) - width, top);
+        let max = (min.0 + width, min.1 + 0.036);
+        ui.add_panel(min, max, [0.08, 0.12, 0.18, 0.7], [0.03, 0.05, 0.08, 0.7], None);
+
+        // Sun: a warm square. Moon: a pale square with a panel-colored bite for the crescent.
+        let icon_min = (min.0 + ui_width(0.014), min.1 + 0.009);
+        let icon_max = (icon_min.0 + ui_width(icon), icon_min.1 + icon);
+        if environment.is_daytime() {
+            ui.add_rect(icon_min, icon_max, [1.0, 0.84, 0.3, 1.0]);
+        } else {
+            ui.add_rect(icon_min, icon_max, [0.86, 0.9, 1.0, 1.0]);
+            let bite = ui_width(icon * 0.45);
+            ui.add_rect(
+                (icon_max.0 - bite, icon_min.1),
+                icon_max,
+                [0.03, 0.05, 0.08, 1.0],
+            );
         }
+        ui.add_text(
+            (icon_max.0 + ui_width(0.016), min.1 + 0.01),
+            line_height,
+            [0.9, 0.94, 1.0, 1.0],
+            &text,
+        );
+    }
 
-        if self.settings_open {
-            self.draw_settings_overlay(&mut ui);
-        } else if self.paused {
-            self.draw_pause_overlay(&mut ui);
+    fn draw_console_overlay(&self, ui: &mut UiGeometry) {
+        let line_height = 0.016;
+        let width = ui_width(0.56);
+        let min = (0.5 - width * 0.5, 0.86);
+        let max = (min.0 + width, min.1 + 0.05);
+        ui.add_panel(min, max, [0.1, 0.1, 0.12, 0.85], [0.04, 0.04, 0.05, 0.85], None);
+        let (text, color) = match (&self.console_input, &self.console_message) {
+            (Some(input), _) => (format!("/{input}|"), [1.0, 1.0, 1.0, 1.0]),
+            (None, Some((message, _))) => (message.clone(), [0.75, 0.9, 1.0, 1.0]),
+            (None, None) => return,
+        };
+        ui.add_text(
+            (min.0 + ui_width(0.018), min.1 + 0.017),
+            line_height,
+            color,
+            &text.to_ascii_uppercase(),
+        );
+    }
+
+    fn draw_measure_overlay(&self, ui: &mut UiGeometry, measurement: Measurement) {
+        let (dx, dy, dz) = measurement.delta();
+        let lines = [
+            format!(
+                "{}  dX {dx}  dY {dy}  dZ {dz}",
+                if self.measure_frozen { "Measured" } else { "Measuring" }
+            ),
+            format!(
+                "Euclidean {:.2}  Manhattan {}",
+                measurement.euclidean(),
+                measurement.manhattan()
+            ),
+        ];
+        let line_height = 0.014;
+        let line_step = line_height + 0.01;
+        let width = ui_width(0.4);
+        let min = (0.5 - width * 0.5, 0.6);
+        let max = (min.0 + width, min.1 + 0.024 + lines.len() as f32 * line_step);
+        ui.add_panel(min, max, [0.16, 0.15, 0.08, 0.8], [0.06, 0.06, 0.04, 0.8], None);
+        let mut y = min.1 + 0.014;
+        for line in &lines {
+            ui.add_text(
+                (min.0 + ui_width(0.018), y),
+                line_height,
+                [1.0, 0.92, 0.5, 1.0],
+                line,
+            );
+            y += line_step;
         }
+    }
 
-        ui
+    fn draw_schematic_overlay(&self, ui: &mut UiGeometry) {
+        let selection = match self.schematic_corner {
+            Some((x, y, z)) => format!("Corner {x} {y} {z}, click the opposite one"),
+            None => "Click two corners to copy".to_string(),
+        };
+        let clipboard = match &self.clipboard {
+            Some(schematic) => {
+                let (x, y, z) = schematic.size();
+                format!("Clipboard {x}x{y}x{z}, right click to paste")
+            }
+            None => "Clipboard empty".to_string(),
+        };
+        let lines = [selection, clipboard];
+        let line_height = 0.014;
+        let line_step = line_height + 0.01;
+        let width = ui_width(0.4);
+        let min = (0.5 - width * 0.5, 0.68);
+        let max = (min.0 + width, min.1 + 0.024 + lines.len() as f32 * line_step);
+        ui.add_panel(min, max, [0.08, 0.13, 0.16, 0.8], [0.04, 0.06, 0.06, 0.8], None);
+        let mut y = min.1 + 0.014;
+        for line in &lines {
+            ui.add_text(
+                (min.0 + ui_width(0.018), y),
+                line_height,
+                [0.6, 0.9, 1.0, 1.0],
+                line,
+            );
+            y += line_step;
+        }
     }
 
     fn draw_inspect_overlay(&self, ui: &mut UiGeometry, info: &InspectInfo) {
@@ -3318,7 +6693,7 @@ impl<'window> State<'window> {
             (min.0 + ui_width(0.02), min.1 + 0.02),
             0.018,
             [0.92, 0.95, 1.0, 1.0],
-            &info.label.to_ascii_uppercase(),
+            &info.label,
         );
 
         let mut lines: Vec<String> = vec![
@@ -3353,6 +6728,7 @@ impl<'window> State<'window> {
                 if let Some(i) = info.params.max_current_amps {
                     lines.push(format!("Max Current: {:.2} A", i));
                 }
+                lines.push(current_limit_line(&info.params));
             }
             ElectricalComponent::Resistor | ElectricalComponent::Wire => {
                 if let Some(r) = info.params.resistance_ohms {
@@ -3365,19 +6741,76 @@ impl<'window> State<'window> {
             ElectricalComponent::Ground => {
                 lines.push("Reference node".to_string());
             }
+            ElectricalComponent::Capacitor => {
+                if let Some(c) = info.params.capacitance_farads {
+                    lines.push(format!("Capacitance: {:.3} F", c));
+                    lines.push(stored_charge_line(c, info.charge_volts));
+                }
+                if let Some(i) = info.params.max_current_amps {
+                    lines.push(format!("Rated Current: {:.2} A", i));
+                }
+            }
+            ElectricalComponent::Switch => {
+                lines.push(format!(
+                    "State: {} (right click to flip)",
+                    if info.closed { "Closed" } else { "Open" }
+                ));
+            }
+            ElectricalComponent::Led => {
+                lines.push(format!("Forward Current: {:.3} A", info.telemetry.current));
+                if let Some(v) = info.params.voltage_volts {
+                    lines.push(format!("Forward Drop: {:.2} V", v));
+                }
+                if let Some(i) = info.params.max_current_amps {
+                    lines.push(format!("Rated Current: {:.2} A", i));
+                }
+            }
+            ElectricalComponent::Potentiometer => {
+                lines.push(format!(
+                    "Wiper: {:.0}% (scroll to turn)",
+                    info.params.wiper() * 100.0
+                ));
+                if let Some(r) = info.params.resistance_ohms {
+                    lines.push(format!("Resistance: {:.2} OHM", r));
+                }
+                if let Some(i) = info.params.max_current_amps {
+                    lines.push(format!("Rated Current: {:.2} A", i));
+                }
+            }
+            ElectricalComponent::Diode => {
+                lines.push(format!("Forward Current: {:.3} A", info.telemetry.current));
+                lines.push(if info.telemetry.current > 0.0 {
+                    "Conducting".to_string()
+                } else {
+                    "Blocking".to_string()
+                });
+                if let Some(v) = info.params.voltage_volts {
+                    lines.push(format!("Forward Drop: {:.2} V", v));
+                }
+            }
         }
         if lines.len() == 1 {
             lines.push("No component parameters".to_string());
         }
+        if info.wet {
+            lines.push("WET - leaking to ground".to_string());
+        }
+        if let Some(demanded) = info.overcurrent {
+            let rated = info.params.max_current_amps.unwrap_or(0.0);
+            lines.push(format!(
+                "OVERCURRENT: {:.2} A measured, {:.2} A rated",
+                demanded, rated
+            ));
+        }
 
         let mut y = min.1 + 0.048;
         let line_height = 0.016;
         let text_width = (width - ui_width(0.04)).max(0.05);
         for line in &lines {
-            y = ui.add_wrapped_text(
+            y += ui.add_text_wrapped(
                 (min.0 + ui_width(0.02), y),
-                line_height,
                 text_width,
+                line_height,
                 [0.88, 0.92, 1.0, 1.0],
                 line,
             );
@@ -3386,7 +6819,7 @@ impl<'window> State<'window> {
     }
     fn draw_config_overlay(&self, ui: &mut UiGeometry, editor: &ConfigEditor) {
         let width = 0.46;
-        let height = 0.2;
+        let height = if editor.entry.is_some() { 0.25 } else { 0.2 };
         let min = (0.5 - width * 0.5, 0.22);
         let max = (0.5 + width * 0.5, 0.22 + height);
         ui.add_panel(
@@ -3447,6 +6880,7 @@ impl<'window> State<'window> {
                 if let Some(r) = editor.params.resistance_ohms {
                     lines.push(format!("Internal R: {:.2} OHM", r));
                 }
+                lines.push(current_limit_line(&editor.params));
             }
             ElectricalComponent::Resistor => {
                 if let Some(r) = editor.params.resistance_ohms {
@@ -3456,6 +6890,29 @@ impl<'window> State<'window> {
                     lines.push(format!("Rated Current: {:.2} A", i));
                 }
             }
+            ElectricalComponent::Capacitor => {
+                if let Some(c) = editor.params.capacitance_farads {
+                    let charge_volts = self
+                        .world
+                        .electrical()
+                        .charge_at(editor.handle.pos, editor.handle.face)
+                        .unwrap_or(0.0);
+                    lines.push(format!("Capacitance: {:.3} F", c));
+                    lines.push(stored_charge_line(c, charge_volts));
+                }
+                if let Some(i) = editor.params.max_current_amps {
+                    lines.push(format!("Rated Current: {:.2} A", i));
+                }
+            }
+            ElectricalComponent::Led => {
+                lines.push(format!("Forward Current: {:.3} A", telemetry.current));
+                if let Some(v) = editor.params.voltage_volts {
+                    lines.push(format!("Forward Drop: {:.2} V", v));
+                }
+                if let Some(i) = editor.params.max_current_amps {
+                    lines.push(format!("Rated Current: {:.2} A", i));
+                }
+            }
             _ => {}
         }
 
@@ -3463,35 +6920,71 @@ impl<'window> State<'window> {
         let line_height = 0.016;
         let text_width = (width - 0.04).max(0.05);
         for line in &lines {
-            y = ui.add_wrapped_text(
+            y += ui.add_text_wrapped(
                 (min.0 + 0.02, y),
-                line_height,
                 text_width,
+                line_height,
                 [0.88, 0.92, 1.0, 1.0],
                 line,
             );
             y += 0.008;
         }
 
-        let instructions: &[&str] = match editor.component {
-            ElectricalComponent::VoltageSource => &[
-                "UP/DOWN: adjust voltage",
-                "LEFT/RIGHT: adjust max current",
+        if let Some(entry) = &editor.entry {
+            let (label, unit) = match editor.component {
+                ElectricalComponent::VoltageSource => ("Voltage", "V"),
+                ElectricalComponent::Capacitor => ("Capacitance", "F"),
+                ElectricalComponent::Led => ("Forward Drop", "V"),
+                _ => ("Resistance", "OHM"),
+            };
+            let field_min = (min.0 + 0.02, y);
+            let field_max = (max.0 - 0.02, y + 0.03);
+            ui.add_panel(
+                field_min,
+                field_max,
+                [0.42, 0.56, 0.9, 0.95],
+                [0.04, 0.05, 0.08, 0.95],
+                None,
+            );
+            ui.add_text(
+                (field_min.0 + 0.01, field_min.1 + 0.008),
+                0.014,
+                [0.95, 0.97, 1.0, 1.0],
+                &format!("{}: {}| {}", label, entry, unit),
+            );
+            y = field_max.1 + 0.01;
+        }
+
+        let instructions: &[&str] = match (editor.component, editor.entry.is_some()) {
+            (_, true) => &["TYPE VALUE   ENTER: set   ESC: cancel entry"],
+            (ElectricalComponent::VoltageSource, false) => &[
+                "UP/DOWN: adjust voltage   TAB or digits: type value",
+                "LEFT/RIGHT: adjust max current   A: apply to connected",
+                "L: toggle current limit   ENTER: apply   ESC: close",
+            ],
+            (ElectricalComponent::Resistor, false) => &[
+                "UP/DOWN: adjust resistance   TAB or digits: type value",
+                "LEFT/RIGHT: adjust max current   A: apply to connected",
                 "ENTER: apply   ESC: close",
             ],
-            ElectricalComponent::Resistor => &[
-                "UP/DOWN: adjust resistance",
-                "LEFT/RIGHT: adjust max current",
+            (ElectricalComponent::Capacitor, false) => &[
+                "UP/DOWN: adjust capacitance   TAB or digits: type value",
+                "LEFT/RIGHT: adjust max current   A: apply to connected",
+                "ENTER: apply   ESC: close",
+            ],
+            (ElectricalComponent::Led, false) => &[
+                "UP/DOWN: adjust forward drop   TAB or digits: type value",
+                "LEFT/RIGHT: adjust max current   A: apply to connected",
                 "ENTER: apply   ESC: close",
             ],
             _ => &["ENTER: apply   ESC: close"],
         };
 
         for line in instructions {
-            y = ui.add_wrapped_text(
+            y += ui.add_text_wrapped(
                 (min.0 + 0.02, y),
-                0.014,
                 text_width,
+                0.014,
                 [0.76, 0.82, 0.94, 1.0],
                 line,
             );
@@ -3499,36 +6992,189 @@ impl<'window> State<'window> {
         }
     }
 
+    fn cycle_build_grid(&mut self) {
+        self.build_grid_spacing = match self.build_grid_spacing {
+            None => Some(BUILD_GRID_SPACINGS[0]),
+            Some(current) => BUILD_GRID_SPACINGS
+                .iter()
+                .position(|&spacing| spacing == current)
+                .and_then(|index| BUILD_GRID_SPACINGS.get(index + 1))
+                .copied(),
+        };
+        self.build_grid_key = None;
+        match self.build_grid_spacing {
+            Some(spacing) => println!("Build grid: every {} block(s)", spacing),
+            None => {
+                println!("Build grid: OFF");
+                self.renderer.update_build_grid(&[]);
+            }
+        }
+    }
+
+    fn refresh_build_grid(&mut self, world_changed: bool) {
+        let Some(spacing) = self.build_grid_spacing else {
+            return;
+        };
+        let center = (
+            self.camera.position.x.round() as i32,
+            self.camera.position.y.round() as i32,
+            self.camera.position.z.round() as i32,
+        );
+        let key = (center.0, center.1, center.2, spacing);
+        if !world_changed && self.build_grid_key == Some(key) {
+            return;
+        }
+        self.build_grid_key = Some(key);
+
+        let mut segments = Vec::new();
+        for x in (center.0 - BUILD_GRID_RADIUS)..=(center.0 + BUILD_GRID_RADIUS) {
+            for z in (center.2 - BUILD_GRID_RADIUS)..=(center.2 + BUILD_GRID_RADIUS) {
+                let on_x_line = x.rem_euclid(spacing) == 0;
+                let on_z_line = z.rem_euclid(spacing) == 0;
+                if !on_x_line && !on_z_line {
+                    continue;
+                }
+                let Some(top) = build_grid_surface(&self.world, x, z, center.1) else {
+                    continue;
+                };
+                // Lift slightly off the face to avoid z-fighting with the block top.
+                let y = top as f32 + 0.5 + 0.01;
+                let (x0, x1) = (x as f32 - 0.5, x as f32 + 0.5);
+                let (z0, z1) = (z as f32 - 0.5, z as f32 + 0.5);
+                if on_x_line {
+                    segments.push(([x0, y, z0], [x0, y, z1]));
+                }
+                if on_z_line {
+                    segments.push(([x0, y, z0], [x1, y, z0]));
+                }
+            }
+        }
+        self.renderer.update_build_grid(&segments);
+    }
+
     fn update_inspect_state(
         &mut self,
         target: Option<AttachmentTarget>,
         info: Option<InspectInfo>,
     ) {
+        let (target, info) = match (target, self.highlight_target) {
+            (Some(_), _) => {
+                self.inspect_seen_at = Instant::now();
+                (target, info)
+            }
+            // Crosshair just slipped off: keep the last component (with live values) for a
+            // moment so jitter doesn't flicker the overlay. A removed component clears at once.
+            (None, Some(previous))
+                if self.inspect_seen_at.elapsed().as_secs_f32() < INSPECT_STICKY_SECONDS =>
+            {
+                match self.inspect_info_for(previous) {
+                    Some(info) => (Some(previous), Some(info)),
+                    None => (None, None),
+                }
+            }
+            (None, _) => (None, info),
+        };
         if self.highlight_target != target {
             self.highlight_target = target;
         }
         if self.inspect_info != info {
+            // Arrows float just clear of the tallest component body so depth testing keeps them.
+            let terminals = info.as_ref().map(|info| {
+                let pos = info.handle.pos;
+                let normal = info.handle.face.normal();
+                let origin = Vector3::new(pos.x as f32, pos.y as f32, pos.z as f32)
+                    + Vector3::new(normal.x as f32, normal.y as f32, normal.z as f32) * 0.94;
+                (origin, info.positive_face, info.negative_face)
+            });
+            self.renderer.update_terminal_arrows(terminals);
             self.inspect_info = info;
             self.mark_ui_dirty();
         }
     }
 
+    /// Overlays for every component carrying at least `min_current` or tripped by an
+    /// overcurrent, and a point light for each lit LED, working voltage source and
+    /// meshed glow shroom, nearest to the camera first and capped at `MAX_POINT_LIGHTS`.
     fn collect_power_highlights(
         &self,
         min_current: f32,
-    ) -> Vec<(Vector3<f32>, ElectricalComponent, ComponentTelemetry)> {
-        self.world
-            .electrical()
+    ) -> (Vec<PowerOverlay>, Vec<PointLight>) {
+        let electrical = self.world.electrical();
+        let center = |pos: BlockPos3| {
+            Vector3::new(pos.x as f32 + 0.5, pos.y as f32 + 0.5, pos.z as f32 + 0.5)
+        };
+        let mut overlays: Vec<PowerOverlay> = electrical
             .powered_nodes(min_current)
             .into_iter()
-            .map(|(pos, component, telemetry)| {
-                (
-                    Vector3::new(pos.x as f32 + 0.5, pos.y as f32 + 0.5, pos.z as f32 + 0.5),
-                    component,
-                    telemetry,
-                )
+            .map(|(pos, component, telemetry)| PowerOverlay {
+                position: center(pos),
+                component,
+                telemetry,
+                overloaded: false,
+            })
+            .collect();
+        overlays.extend(electrical.overcurrent_nodes().into_iter().filter_map(|(pos, face)| {
+            Some(PowerOverlay {
+                position: center(pos),
+                component: electrical.component_at(pos, face)?,
+                telemetry: electrical.telemetry_at(pos, face).unwrap_or_default(),
+                overloaded: true,
+            })
+        }));
+
+        let eye = Vector3::new(
+            self.camera.position.x,
+            self.camera.position.y,
+            self.camera.position.z,
+        );
+        let mut lights: Vec<PointLight> = self
+            .world
+            .electrical()
+            .lit_leds()
+            .into_iter()
+            .map(|(pos, face, current)| {
+                let center = Vector3::new(pos.x as f32, pos.y as f32, pos.z as f32);
+                let strength = (current / LED_FULL_BRIGHTNESS_AMPS).clamp(0.0, 1.0);
+                PointLight {
+                    position: center + face.normal_f32() * LED_LIGHT_LIFT,
+                    color: [1.0, 0.28, 0.2],
+                    intensity: strength * 1.5,
+                    radius: LED_LIGHT_RADIUS * (0.4 + 0.6 * strength),
+                }
             })
-            .collect()
+            .collect();
+        // Overlay positions carry a half-block offset; the light sits at the block centre.
+        lights.extend(
+            overlays
+                .iter()
+                .filter(|overlay| {
+                    overlay.component == ElectricalComponent::VoltageSource && !overlay.overloaded
+                })
+                .map(|overlay| PointLight {
+                    position: overlay.position - Vector3::new(0.5, 0.5, 0.5),
+                    color: [1.0, 0.78, 0.36],
+                    intensity: 0.8,
+                    radius: SOURCE_LIGHT_RADIUS,
+                }),
+        );
+        lights.extend(
+            self.renderer
+                .glow_sources_near(eye, GLOW_LIGHT_SEARCH_RANGE)
+                .into_iter()
+                .map(|position| PointLight {
+                    position,
+                    color: GLOW_SHROOM_LIGHT_COLOR,
+                    intensity: 0.9,
+                    radius: GLOW_SHROOM_LIGHT_RADIUS,
+                }),
+        );
+        lights.sort_by(|a, b| {
+            (a.position - eye)
+                .magnitude2()
+                .total_cmp(&(b.position - eye).magnitude2())
+        });
+        lights.truncate(MAX_POINT_LIGHTS);
+        (overlays, lights)
     }
 
     fn inspect_info_for(&self, handle: AttachmentTarget) -> Option<InspectInfo> {
@@ -3553,6 +7199,7 @@ impl<'window> State<'window> {
             .axis_at(handle.pos, handle.face)
             .unwrap_or_else(|| component.default_axis());
         let (positive_face, negative_face) = component.terminal_faces(axis, handle.face);
+        let wet = self.world.electrical().is_wet(handle.pos, handle.face);
         Some(InspectInfo {
             handle,
             label,
@@ -3562,6 +7209,21 @@ impl<'window> State<'window> {
             negative_face,
             params,
             telemetry,
+            charge_volts: self
+                .world
+                .electrical()
+                .charge_at(handle.pos, handle.face)
+                .unwrap_or(0.0),
+            closed: self
+                .world
+                .electrical()
+                .switch_closed(handle.pos, handle.face)
+                .unwrap_or(false),
+            wet,
+            overcurrent: self
+                .world
+                .electrical()
+                .overcurrent_at(handle.pos, handle.face),
         })
     }
 
@@ -3584,6 +7246,7 @@ impl<'window> State<'window> {
             label: component.block_type().name().to_string(),
             component,
             params,
+            entry: None,
         });
         self.mark_ui_dirty();
     }
@@ -3616,7 +7279,10 @@ impl<'window> State<'window> {
         };
         if !matches!(
             component,
-            ElectricalComponent::Resistor | ElectricalComponent::VoltageSource
+            ElectricalComponent::Resistor
+                | ElectricalComponent::VoltageSource
+                | ElectricalComponent::Capacitor
+                | ElectricalComponent::Led
         ) {
             return false;
         }
@@ -3630,10 +7296,30 @@ impl<'window> State<'window> {
     }
 
     fn handle_config_key(&mut self, key: KeyCode) -> bool {
-        if self.config_editor.is_none() {
+        let Some(editor) = self.config_editor.as_ref() else {
             return false;
+        };
+        if editor.entry.is_some() {
+            return self.handle_config_entry_key(key);
+        }
+        if let Some(ch) = config_entry_char(key) {
+            self.begin_config_entry();
+            self.push_config_entry_char(ch);
+            return true;
         }
         match key {
+            KeyCode::Tab => {
+                self.begin_config_entry();
+                true
+            }
+            KeyCode::KeyA => {
+                self.apply_config_to_network();
+                true
+            }
+            KeyCode::KeyL => {
+                self.toggle_config_current_limit();
+                true
+            }
             KeyCode::Escape => {
                 self.close_config_editor();
                 true
@@ -3662,6 +7348,90 @@ impl<'window> State<'window> {
         }
     }
 
+    fn begin_config_entry(&mut self) {
+        if let Some(editor) = self.config_editor.as_mut() {
+            if matches!(
+                editor.component,
+                ElectricalComponent::VoltageSource
+                    | ElectricalComponent::Resistor
+                    | ElectricalComponent::Capacitor
+                    | ElectricalComponent::Led
+            ) {
+                editor.entry = Some(String::new());
+                self.mark_ui_dirty();
+            }
+        }
+    }
+
+    fn push_config_entry_char(&mut self, ch: char) {
+        if let Some(entry) = self
+            .config_editor
+            .as_mut()
+            .and_then(|editor| editor.entry.as_mut())
+        {
+            if entry.len() < CONFIG_ENTRY_MAX_LEN && !(ch == '.' && entry.contains('.')) {
+                entry.push(ch);
+            }
+            self.mark_ui_dirty();
+        }
+    }
+
+    fn handle_config_entry_key(&mut self, key: KeyCode) -> bool {
+        if let Some(ch) = config_entry_char(key) {
+            self.push_config_entry_char(ch);
+            return true;
+        }
+        let Some(editor) = self.config_editor.as_mut() else {
+            return false;
+        };
+        match key {
+            KeyCode::Backspace => {
+                if let Some(entry) = editor.entry.as_mut() {
+                    entry.pop();
+                }
+            }
+            KeyCode::Escape => {
+                editor.entry = None;
+            }
+            KeyCode::Enter | KeyCode::NumpadEnter => {
+                let text = editor.entry.take().unwrap_or_default();
+                match text.parse::<f32>() {
+                    Ok(value) if value.is_finite() => {
+                        match editor.component {
+                            ElectricalComponent::VoltageSource => {
+                                editor.params.voltage_volts = Some(value.clamp(0.0, 10_000.0));
+                            }
+                            ElectricalComponent::Resistor => {
+                                editor.params.resistance_ohms =
+                                    Some(value.clamp(0.1, 1_000_000.0));
+                            }
+                            ElectricalComponent::Capacitor => {
+                                editor.params.capacitance_farads = Some(
+                                    value.clamp(CAPACITANCE_MIN_FARADS, CAPACITANCE_MAX_FARADS),
+                                );
+                            }
+                            ElectricalComponent::Led => {
+                                editor.params.voltage_volts = Some(
+                                    value.clamp(FORWARD_DROP_MIN_VOLTS, FORWARD_DROP_MAX_VOLTS),
+                                );
+                            }
+                            _ => {}
+                        }
+                        self.commit_config_params();
+                    }
+                    _ => {
+                        println!("'{}' is not a valid number.", text);
+                        editor.entry = Some(text);
+                    }
+                }
+            }
+            _ => {}
+        }
+        // Swallow everything else so typed keys never reach gameplay bindings.
+        self.mark_ui_dirty();
+        true
+    }
+
     fn adjust_config_primary(&mut self, direction: f32) {
         if let Some(editor) = self.config_editor.as_mut() {
             match editor.component {
@@ -3677,6 +7447,20 @@ impl<'window> State<'window> {
                         editor.params.resistance_ohms = Some(value);
                     }
                 }
+                ElectricalComponent::Capacitor => {
+                    if let Some(mut value) = editor.params.capacitance_farads {
+                        value = (value + direction * CAPACITANCE_STEP_FARADS)
+                            .clamp(CAPACITANCE_MIN_FARADS, CAPACITANCE_MAX_FARADS);
+                        editor.params.capacitance_farads = Some(value);
+                    }
+                }
+                ElectricalComponent::Led => {
+                    if let Some(mut value) = editor.params.voltage_volts {
+                        value = (value + direction * FORWARD_DROP_STEP_VOLTS)
+                            .clamp(FORWARD_DROP_MIN_VOLTS, FORWARD_DROP_MAX_VOLTS);
+                        editor.params.voltage_volts = Some(value);
+                    }
+                }
                 _ => {}
             }
             self.commit_config_params();
@@ -3686,7 +7470,10 @@ impl<'window> State<'window> {
     fn adjust_config_secondary(&mut self, direction: f32) {
         if let Some(editor) = self.config_editor.as_mut() {
             match editor.component {
-                ElectricalComponent::VoltageSource | ElectricalComponent::Resistor => {
+                ElectricalComponent::VoltageSource
+                | ElectricalComponent::Resistor
+                | ElectricalComponent::Capacitor
+                | ElectricalComponent::Led => {
                     let current = editor.params.max_current_amps.unwrap_or(0.0);
                     let new_current = (current + direction * 0.5).max(0.0);
                     editor.params.max_current_amps = Some(new_current);
@@ -3697,6 +7484,91 @@ impl<'window> State<'window> {
         }
     }
 
+    fn apply_config_to_network(&mut self) {
+        let Some(editor) = self.config_editor.clone() else {
+            return;
+        };
+        let updated = self.world.electrical_mut().set_params_in_network(
+            editor.handle.pos,
+            editor.handle.face,
+            editor.params,
+        );
+        println!(
+            "Applied settings to {} connected {} component(s).",
+            updated, editor.label
+        );
+        self.refresh_inspect_info();
+        self.mark_ui_dirty();
+    }
+
+    fn scale_animation_time(&mut self, factor: f32) {
+        self.animation_time_scale = (self.animation_time_scale * factor)
+            .clamp(ANIMATION_SCALE_MIN, ANIMATION_SCALE_MAX);
+        println!("Animation speed: x{:.3}", self.animation_time_scale);
+        self.mark_ui_dirty();
+    }
+
+    fn export_telemetry_snapshot(&self) {
+        match self.append_telemetry_csv() {
+            Ok(rows) => println!(
+                "Exported {} electrical component(s) to {}",
+                rows, TELEMETRY_EXPORT_PATH
+            ),
+            Err(err) => eprintln!("Failed to export telemetry: {err:#}"),
+        }
+    }
+
+    fn append_telemetry_csv(&self) -> anyhow::Result<usize> {
+        use std::io::Write;
+
+        let snapshot = self.world.electrical().telemetry_snapshot();
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs_f64())
+            .unwrap_or_default();
+        let write_header = std::fs::metadata(TELEMETRY_EXPORT_PATH)
+            .map(|meta| meta.len() == 0)
+            .unwrap_or(true);
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(TELEMETRY_EXPORT_PATH)
+            .with_context(|| format!("opening {TELEMETRY_EXPORT_PATH}"))?;
+        let mut out = std::io::BufWriter::new(file);
+        if write_header {
+            writeln!(
+                out,
+                "timestamp,x,y,z,face,component,voltage_local,voltage_ground,current"
+            )?;
+        }
+        for (pos, face, component, telemetry) in &snapshot {
+            writeln!(
+                out,
+                "{:.3},{},{},{},{:?},{:?},{:.6},{:.6},{:.6}",
+                timestamp,
+                pos.x,
+                pos.y,
+                pos.z,
+                face,
+                component,
+                telemetry.voltage_local,
+                telemetry.voltage_ground,
+                telemetry.current
+            )?;
+        }
+        out.flush()?;
+        Ok(snapshot.len())
+    }
+
+    fn toggle_config_current_limit(&mut self) {
+        if let Some(editor) = self.config_editor.as_mut() {
+            if editor.component == ElectricalComponent::VoltageSource {
+                editor.params.current_limit = !editor.params.current_limit;
+                self.commit_config_params();
+            }
+        }
+    }
+
     fn commit_config_params(&mut self) {
         if let Some(editor) = &self.config_editor {
             self.world.electrical_mut().set_params(
@@ -3714,13 +7586,17 @@ impl<'window> State<'window> {
         let frame_dt = now.duration_since(self.last_frame).as_secs_f32();
         self.last_frame = now;
         self.tick_accumulator += frame_dt;
-        self.animation_time += frame_dt;
+        self.smoothed_frame_dt += (frame_dt - self.smoothed_frame_dt) * FRAME_TIME_SMOOTHING;
+        if !self.animations_frozen {
+            self.animation_time += frame_dt * self.animation_time_scale;
+        }
 
         let frame_profiler = profiler::begin_frame();
         let _update_scope = frame_profiler
             .as_ref()
             .map(|ctx| ctx.section("frame_update"));
 
+        self.poll_gamepad(frame_dt);
         let in_menu = self.is_in_menu();
         let mut ticks_executed = 0;
         while self.tick_accumulator >= FIXED_TICK_STEP && ticks_executed < MAX_TICKS_PER_FRAME {
@@ -3733,7 +7609,12 @@ impl<'window> State<'window> {
             self.tick_accumulator = self.tick_accumulator.min(FIXED_TICK_STEP);
         }
 
+        self.refresh_look_target();
         self.frame_update(frame_dt, in_menu, ticks_executed, &frame_profiler);
+        if (self.debug_mode || self.profiler_overlay) && !in_menu {
+            // The frame-time readout and profiler bars change every frame.
+            self.mark_ui_dirty();
+        }
 
         if self.ui_dirty {
             profiler::scope(&frame_profiler, "ui_rebuild", || {
@@ -3748,34 +7629,55 @@ impl<'window> State<'window> {
         in_menu: bool,
         frame_profiler: &Option<profiler::FrameCtx>,
     ) {
+        let mut sprint_bonus = 0.0;
         if in_menu {
             self.controller.reset_motion();
-            let base_fov = self.projection.base_fov();
-            self.projection.set_target_fov(base_fov);
+            // The key release is swallowed by the menu, so drop the zoom here.
+            self.zoom_held = false;
+        } else if let Some(free_camera) = self.free_camera.as_mut() {
+            // Player body stays frozen; only the detached camera moves.
+            self.controller.fly_camera(free_camera, tick_dt);
         } else {
+            let previous_position = self.camera.position;
+            let was_sneaking = self.controller.is_sneaking();
+            let chest = self.camera.position - Vector3::new(0.0, SWIM_CHEST_DEPTH, 0.0);
+            self.controller.set_swimming(self.is_submerged_at(chest));
             {
                 let world_ref = &self.world;
-                let check_collision =
-                    |pos: cgmath::Point3<f32>| player_aabb_collides(world_ref, pos);
+                let check_collision = |pos: cgmath::Point3<f32>, eye_height: f32| {
+                    player_aabb_collides(world_ref, pos, eye_height)
+                };
                 self.controller
                     .update_camera(&mut self.camera, tick_dt, check_collision);
             }
-            let sprint_bonus = if self.controller.is_sprinting() {
-                7.0_f32.to_radians()
-            } else {
-                0.0
-            };
-            let base_fov = self.projection.base_fov();
-            self.projection
-                .set_target_fov(Rad(base_fov.0 + sprint_bonus));
+            if self.controller.is_sneaking() != was_sneaking {
+                // The hotbar chip shows the sneak state.
+                self.mark_ui_dirty();
+            }
+            self.advance_footsteps(previous_position);
+            if self.controller.is_sprinting() && !self.settings_reduce_motion {
+                sprint_bonus = 7.0_f32.to_radians();
+            }
+        }
+        self.audio.set_submerged(self.player_is_submerged());
+        if !in_menu {
+            self.update_breath(tick_dt);
         }
+        // Zoom wins over the sprint bump; both are offsets from the settings FOV.
+        let base_fov = self.projection.base_fov();
+        let target_fov = if self.zoom_held {
+            Rad(ZOOM_FOV_DEG.to_radians())
+        } else {
+            Rad(base_fov.0 + sprint_bonus)
+        };
+        self.projection.set_target_fov(target_fov);
         self.projection.animate(tick_dt);
+        self.controller.set_look_scale(self.projection.fov().0 / base_fov.0);
 
         // Handle block breaking
-        if !in_menu && self.left_mouse_held {
-            let direction = self.crosshair_direction();
-            if let Some(hit) = raycast(&self.world, self.camera.position, direction, 5.0) {
-                let target_pos = hit.block_pos;
+        if !in_menu && self.left_mouse_held && self.free_camera.is_none() {
+            if let Some(target) = self.looked_at().filter(|target| !target.hit.fluid) {
+                let target_pos = target.hit.block_pos;
 
                 // Check if we're still targeting the same block
                 if self.breaking_block != Some(target_pos) {
@@ -3828,14 +7730,6 @@ impl<'window> State<'window> {
             self.breaking_progress = 0.0;
         }
 
-        // Decay placement animation (animation lasts ~0.3 seconds)
-        if self.placement_progress > 0.0 {
-            self.placement_progress -= tick_dt * 3.3; // Decay rate
-            if self.placement_progress < 0.0 {
-                self.placement_progress = 0.0;
-            }
-        }
-
         // Update item entities (physics and lifetime)
         self.entities.retain_mut(|entity| entity.update(tick_dt, &self.world));
 
@@ -3845,8 +7739,7 @@ impl<'window> State<'window> {
             self.entities.retain(|entity| {
                 if entity.can_pickup() && entity.in_pickup_range(player_pos) {
                     // Try to add to inventory
-                    if let Some(empty_slot) = self.inventory.first_empty_slot() {
-                        self.inventory.set_slot(empty_slot, Some(entity.item));
+                    if self.inventory.add_item(entity.item, 1) == 0 {
                         println!("Picked up {}!", entity.item.name());
                         false // Remove entity
                     } else {
@@ -3879,47 +7772,84 @@ impl<'window> State<'window> {
             }
         }
 
-        let updated_chunks = if !in_menu {
-            profiler::scope(&frame_profiler, "world_update_chunks", || {
-                self.world.update_loaded_chunks(self.camera.position, 3)
-            })
-        } else {
-            false
-        };
-        if updated_chunks {
-            self.world_dirty = true;
-            self.force_full_remesh = true;
-            self.dirty_chunks.clear();
+        if !in_menu {
+            let changes = profiler::scope(&frame_profiler, "world_update_chunks", || {
+                self.world.update_loaded_chunks_within_budget(
+                    self.camera.position,
+                    self.settings_render_distance,
+                    CHUNK_LOADS_PER_FRAME,
+                )
+            });
+            if !changes.is_empty() {
+                self.renderer.unload_chunks(&changes.unloaded);
+                // New chunks and their neighbours (whose border faces change) need meshes.
+                for pos in changes.loaded.iter().chain(changes.unloaded.iter()) {
+                    for (dx, dz) in [(0, 0), (1, 0), (-1, 0), (0, 1), (0, -1)] {
+                        let neighbor = ChunkPos {
+                            x: pos.x + dx,
+                            z: pos.z + dz,
+                        };
+                        if self.world.chunks().contains_key(&neighbor) {
+                            self.dirty_chunks.insert(neighbor);
+                        }
+                    }
+                }
+                self.world_dirty = true;
+            }
         }
 
         // Water simulation runs every 10 ticks (6 times per second) to reduce lag
         if self.water_tick_counter % WATER_UPDATE_INTERVAL == 0 {
+            // The circuit summary and clock refresh on the same six-per-second cadence.
+            if self.electrical_summary_open || self.settings_show_clock {
+                self.mark_ui_dirty();
+            }
+            if self.fluid_debug_view {
+                let pos = self.camera.position;
+                let center = (pos.x.floor() as i32, pos.y.floor() as i32, pos.z.floor() as i32);
+                self.fluid_system.set_debug_region(Some((center, FLUID_DEBUG_RADIUS)));
+                self.fluid_system.begin_debug_capture(&self.world);
+            }
+
             if profiler::scope(&frame_profiler, "fluid_poll", || {
                 self.fluid_system.poll_results(&mut self.world)
             }) {
-                self.world_dirty = true;
-                self.force_full_remesh = true;
-                self.dirty_chunks.clear();
+                self.queue_fluid_remesh();
             }
 
-            if !in_menu {
-                profiler::scope(&frame_profiler, "fluid_pump", || {
-                    self.fluid_system.pump(&self.world);
-                });
+            if !in_menu
+                && profiler::scope(&frame_profiler, "fluid_pump", || {
+                    self.fluid_system.pump(&mut self.world)
+                })
+            {
+                self.queue_fluid_remesh();
             }
 
             if profiler::scope(&frame_profiler, "fluid_fallback", || {
                 self.fluid_system.fallback_step(&mut self.world)
             }) {
-                self.world_dirty = true;
-                self.force_full_remesh = true;
-                self.dirty_chunks.clear();
+                self.queue_fluid_remesh();
             }
+
+            if self.fluid_debug_view {
+                self.fluid_system.finish_debug_capture(&self.world);
+                self.renderer.update_fluid_debug(self.fluid_system.active_cells());
+            }
+        }
+
+        // Lava never leaves the CPU; it is cheap because it settles and drops out quickly.
+        if self.water_tick_counter % LAVA_UPDATE_INTERVAL == 0
+            && profiler::scope(frame_profiler, "lava_step", || self.world.step_lava())
+        {
+            self.queue_fluid_remesh();
         }
 
         profiler::scope(&frame_profiler, "electric_tick", || {
-            self.world.tick_electrical();
+            self.world.tick_electrical(FIXED_TICK_STEP);
         });
+        for pos in self.world.electrical_mut().take_relit_leds() {
+            self.mark_block_dirty(pos.x, pos.y, pos.z);
+        }
         self.refresh_inspect_info();
     }
 
@@ -3938,12 +7868,57 @@ impl<'window> State<'window> {
             self.projection.animate(frame_dt.min(FIXED_TICK_STEP));
         }
 
-        self.renderer.update_camera(&self.camera, &self.projection);
+        if let Some((color, remaining)) = self.hit_marker {
+            let remaining = remaining - frame_dt;
+            self.hit_marker = (remaining > 0.0).then_some((color, remaining));
+            self.mark_ui_dirty();
+        }
+
+        if matches!(&self.console_message, Some((_, until)) if Instant::now() >= *until) {
+            self.console_message = None;
+            self.mark_ui_dirty();
+        }
 
-        let atmosphere = self.world.atmosphere_at(
-            self.camera.position.x.floor() as i32,
-            self.camera.position.z.floor() as i32,
-        );
+        if self.photo_mode && self.photo_blur {
+            let target = self.photo_focus_target();
+            let ease = 1.0 - (-PHOTO_FOCUS_RATE * frame_dt).exp();
+            self.photo_focus += (target - self.photo_focus) * ease;
+            self.sync_depth_of_field();
+        }
+
+        // Draw from the detached camera when free-look is on; physics keeps the player.
+        let view_camera = self.free_camera.as_ref().unwrap_or(&self.camera);
+        self.renderer.update_camera(view_camera, &self.projection);
+
+        let view_position = view_camera.position;
+        let underwater_target = if let Some(fluid) = self.submerged_fluid_at(view_position) {
+            if fluid != self.submerged_fluid {
+                self.submerged_fluid = fluid;
+                self.mark_ui_dirty();
+            }
+            1.0
+        } else {
+            0.0
+        };
+        let fade_step = frame_dt / UNDERWATER_FADE_SECONDS;
+        let blend = if self.underwater_blend < underwater_target {
+            (self.underwater_blend + fade_step).min(underwater_target)
+        } else {
+            (self.underwater_blend - fade_step).max(underwater_target)
+        };
+        if blend != self.underwater_blend {
+            self.underwater_blend = blend;
+            // The blue overlay lives in the UI layer.
+            self.mark_ui_dirty();
+        }
+
+        let atmosphere = self
+            .world
+            .atmosphere_at(
+                self.camera.position.x.floor() as i32,
+                self.camera.position.z.floor() as i32,
+            )
+            .submerged(self.submerged_fluid, self.underwater_blend);
         self.renderer.update_environment(
             &atmosphere,
             [
@@ -3951,6 +7926,7 @@ impl<'window> State<'window> {
                 self.camera.position.y,
                 self.camera.position.z,
             ],
+            self.animation_time,
         );
         let blended_clear = [
             (atmosphere.sky_zenith[0] + atmosphere.sky_horizon[0]) * 0.5,
@@ -3958,42 +7934,50 @@ impl<'window> State<'window> {
             (atmosphere.sky_zenith[2] + atmosphere.sky_horizon[2]) * 0.5,
         ];
         self.renderer.set_clear_color(blended_clear);
+        self.renderer.update_clouds(self.animation_time);
 
         let mut highlight_bounds = None;
+        let mut highlight_color = OUTLINE_NEUTRAL_COLOR;
         let mut new_highlight = None;
         let mut new_info = None;
+        let mut new_dial = None;
 
         if !in_menu {
-            let direction = self.crosshair_direction();
-            if let Some(hit) = raycast(&self.world, self.camera.position, direction, 6.0) {
+            if let Some(target) = self.looked_at() {
+                let hit = target.hit;
                 let pad = 0.002;
                 let min = [
                     hit.block_pos.0 as f32 - 0.5 - pad,
                     hit.block_pos.1 as f32 - 0.5 - pad,
                     hit.block_pos.2 as f32 - 0.5 - pad,
                 ];
+                // Fluid hits outline only the filled part of the cell.
+                let top = if hit.fluid {
+                    let (x, y, z) = hit.block_pos;
+                    let amount = self.world.get_fluid_amount(x, y, z);
+                    let fill = (amount as f32 / MAX_FLUID_LEVEL as f32).min(1.0);
+                    y as f32 - 0.5 + fill + pad
+                } else {
+                    hit.block_pos.1 as f32 + 0.5 + pad
+                };
                 let max = [
                     hit.block_pos.0 as f32 + 0.5 + pad,
-                    hit.block_pos.1 as f32 + 0.5 + pad,
+                    top,
                     hit.block_pos.2 as f32 + 0.5 + pad,
                 ];
                 highlight_bounds = Some((min, max));
 
-                let face = BlockFace::from_normal_f32(hit.normal)
-                    .or_else(|| BlockFace::from_normal_f32(-hit.normal))
-                    .unwrap_or(BlockFace::Top);
-                let pos = BlockPos3::new(hit.block_pos.0, hit.block_pos.1, hit.block_pos.2);
-                if let Some(component) = self.world.electrical().component_at(pos, face) {
+                let face = target.face;
+                let pos = target.block_pos();
+                if self.settings_outline_colors {
+                    highlight_color = outline_color_for(target.block, hit.fluid);
+                }
+                if let Some((component, telemetry)) = target.component {
                     let params = self
                         .world
                         .electrical()
                         .params_at(pos, face)
                         .unwrap_or_else(|| component.default_params());
-                    let telemetry = self
-                        .world
-                        .electrical()
-                        .telemetry_at(pos, face)
-                        .unwrap_or_default();
                     let label = component.block_type().name().to_string();
                     let axis = self
                         .world
@@ -4003,6 +7987,9 @@ impl<'window> State<'window> {
                     let (positive_face, negative_face) = component.terminal_faces(axis, face);
                     let handle = AttachmentTarget { pos, face };
                     new_highlight = Some(handle);
+                    if component == ElectricalComponent::Potentiometer {
+                        new_dial = Some(handle);
+                    }
                     new_info = Some(InspectInfo {
                         handle,
                         label,
@@ -4012,31 +7999,60 @@ impl<'window> State<'window> {
                         negative_face,
                         params,
                         telemetry,
+                        charge_volts: self.world.electrical().charge_at(pos, face).unwrap_or(0.0),
+                        closed: self
+                            .world
+                            .electrical()
+                            .switch_closed(pos, face)
+                            .unwrap_or(false),
+                        wet: self.world.electrical().is_wet(pos, face),
+                        overcurrent: self.world.electrical().overcurrent_at(pos, face),
                     });
                 }
             }
         }
 
-        let power_instances = if in_menu {
-            Vec::new()
+        let (power_instances, point_lights) = if in_menu {
+            (Vec::new(), Vec::new())
         } else {
             self.collect_power_highlights(0.01)
         };
         self.renderer
             .update_power_overlays(&power_instances, self.animation_time);
-        self.renderer.update_highlight(highlight_bounds, self.breaking_progress);
+        self.renderer.update_point_lights(&point_lights);
+        self.renderer.update_highlight(
+            highlight_bounds,
+            highlight_color,
+            self.breaking_progress,
+            new_highlight.is_some(),
+        );
+        self.dial_target = new_dial;
         self.update_inspect_state(new_highlight, new_info);
+        self.track_measure_target(in_menu);
 
         // Update item entities
         self.renderer.update_entities(&self.entities);
+        let world = &self.world;
+        self.renderer.update_particles(frame_dt, &self.camera, |x, y, z| {
+            world.get_block(x, y, z).is_solid()
+        });
 
-        if in_menu {
+        self.refresh_placement_preview(in_menu);
+
+        // Holding break keeps punching; each swing plays out before the next starts.
+        if !in_menu && self.left_mouse_held && self.hand_animation.is_idle() {
+            self.hand_animation.start(HandAction::Swing);
+        }
+        self.hand_animation.advance(frame_dt);
+
+        // The held item belongs to the player's view, so hide it while detached.
+        if in_menu || self.free_camera.is_some() {
             self.renderer.update_hand(
                 None,
                 &self.camera,
                 self.animation_time,
                 0.0,
-                0.0,
+                HandPose::REST,
             );
         } else {
             self.renderer.update_hand(
@@ -4044,20 +8060,23 @@ impl<'window> State<'window> {
                 &self.camera,
                 self.animation_time,
                 self.breaking_progress,
-                self.placement_progress,
+                self.hand_animation.pose(),
             );
         }
 
+        self.refresh_build_grid(self.world_dirty);
+
         if !in_menu && self.world_dirty {
-            profiler::scope(&frame_profiler, "mesh_update", || {
+            let remeshed = profiler::scope(&frame_profiler, "mesh_update", || {
                 if self.force_full_remesh {
-                    self.renderer.rebuild_world_mesh(&self.world);
                     self.dirty_chunks.clear();
+                    self.renderer.rebuild_world_mesh(&self.world)
                 } else {
                     let dirty_chunks: HashSet<ChunkPos> = self.dirty_chunks.drain().collect();
-                    self.renderer.update_chunks(&self.world, &dirty_chunks);
+                    self.renderer.update_chunks(&self.world, &dirty_chunks)
                 }
             });
+            profiler::count(frame_profiler, "chunks_remeshed", remeshed);
             self.world_dirty = false;
             self.force_full_remesh = false;
         }
@@ -4071,11 +8090,13 @@ impl<'window> State<'window> {
     }
 }
 
-fn player_aabb_collides(world: &World, pos: cgmath::Point3<f32>) -> bool {
+/// Tests the player's box for an eye at `pos`, `eye_height` above the feet; the head always
+/// reaches the same distance above the eye.
+fn player_aabb_collides(world: &World, pos: cgmath::Point3<f32>, eye_height: f32) -> bool {
     const EPSILON: f32 = 0.001;
 
-    let bottom = pos.y - PLAYER_EYE_HEIGHT;
-    let top = bottom + PLAYER_HEIGHT;
+    let bottom = pos.y - eye_height;
+    let top = pos.y + PLAYER_HEIGHT - PLAYER_EYE_HEIGHT;
 
     let min_x_bound = pos.x - PLAYER_RADIUS;
     let max_x_bound = pos.x + PLAYER_RADIUS;
@@ -4108,13 +8129,59 @@ fn player_aabb_collides(world: &World, pos: cgmath::Point3<f32>) -> bool {
     false
 }
 
-fn find_surface_level(world: &World, x: i32, z: i32) -> Option<f32> {
-    for y in (0..CHUNK_HEIGHT as i32).rev() {
-        if world.get_block(x, y, z).is_solid() {
-            return Some(y as f32 + 0.5);
-        }
+/// Top solid block of the column near `from_y`, skipping overhangs far above the player.
+fn build_grid_surface(world: &World, x: i32, z: i32, from_y: i32) -> Option<i32> {
+    let start = (from_y + 4).min(world.height() as i32 - 1);
+    let end = (from_y - 24).max(0);
+    (end..=start)
+        .rev()
+        .find(|&y| world.get_block(x, y, z).is_solid() && !world.get_block(x, y + 1, z).is_solid())
+}
+
+/// Far clip distance that still covers the unload margin around the loaded
+/// square plus the full column height.
+fn far_plane_for(render_distance: i32, column_height: usize) -> f32 {
+    let horizontal = ((render_distance + 2) * CHUNK_SIZE as i32) as f32 * std::f32::consts::SQRT_2;
+    horizontal + column_height as f32
+}
+
+/// Chip text for a saved loadout, shortened so a long name can't crowd out the rest.
+fn loadout_label(name: &str) -> String {
+    let mut label: String = name.chars().take(LOADOUT_LABEL_CHARS).collect();
+    if name.chars().count() > LOADOUT_LABEL_CHARS {
+        label.push_str("..");
+    }
+    label.to_ascii_uppercase()
+}
+
+/// Writes a slot's item count into the bottom-right corner of the slot ending at
+/// `slot_max`; single items and empty slots stay unlabelled.
+fn draw_stack_count(
+    ui: &mut UiGeometry,
+    stack: Option<ItemStack>,
+    slot_max: (f32, f32),
+    height: f32,
+) {
+    let Some(stack) = stack.filter(|stack| stack.count > 1) else {
+        return;
+    };
+    let label = stack.count.to_string();
+    let origin = (
+        slot_max.0 - ui_width(0.004) - text_width(&label, height),
+        slot_max.1 - height - 0.004,
+    );
+    let shadow = (origin.0 + ui_width(0.0015), origin.1 + 0.0015);
+    ui.add_text(shadow, height, [0.0, 0.0, 0.0, 0.8], &label);
+    ui.add_text(origin, height, [1.0, 1.0, 1.0, 1.0], &label);
+}
+
+/// Placeholder icon colour for buckets until items get their own sprites.
+fn bucket_icon_color(state: BucketState) -> [f32; 4] {
+    match state {
+        BucketState::Empty => [0.62, 0.64, 0.68, 1.0],
+        BucketState::Water => [0.24, 0.46, 0.88, 1.0],
+        BucketState::Lava => [0.95, 0.42, 0.1, 1.0],
     }
-    None
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -4196,6 +8263,54 @@ impl UiScaler {
 const FONT_WIDTH: usize = 5;
 const FONT_HEIGHT: usize = 7;
 
+/// Width `add_text` covers drawing a single line of `text` at `height`.
+fn text_width(text: &str, height: f32) -> f32 {
+    let scale = height / FONT_HEIGHT as f32;
+    let chars = text.chars().count() as f32;
+    (chars * (FONT_WIDTH as f32 + 0.4) - 0.4).max(0.0) * scale
+}
+
+/// Distance between the tops of consecutive lines of text drawn at `height`.
+fn text_line_height(height: f32) -> f32 {
+    height + height / FONT_HEIGHT as f32 * 1.6
+}
+
+/// Splits `text` into lines no wider than `max_width` at `height`, breaking at spaces and
+/// hard-breaking words that don't fit on a line of their own.
+fn wrap_text(text: &str, height: f32, max_width: f32) -> Vec<String> {
+    if height <= 0.0 || max_width <= 0.0 {
+        return Vec::new();
+    }
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        let candidate = if current.is_empty() {
+            word.to_string()
+        } else {
+            format!("{current} {word}")
+        };
+        if text_width(&candidate, height) <= max_width {
+            current = candidate;
+            continue;
+        }
+        if !current.is_empty() {
+            lines.push(std::mem::take(&mut current));
+        }
+        for ch in word.chars() {
+            current.push(ch);
+            // A single glyph wider than the line still gets a line to itself.
+            if current.chars().count() > 1 && text_width(&current, height) > max_width {
+                current.pop();
+                lines.push(std::mem::replace(&mut current, ch.to_string()));
+            }
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
 fn glyph_for_char(ch: char) -> Option<[u8; FONT_HEIGHT]> {
     match ch {
         'A' => Some([
@@ -4276,6 +8391,84 @@ fn glyph_for_char(ch: char) -> Option<[u8; FONT_HEIGHT]> {
         'Z' => Some([
             0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111,
         ]),
+        'a' => Some([
+            0b00000, 0b00000, 0b01110, 0b00001, 0b01111, 0b10001, 0b01111,
+        ]),
+        'b' => Some([
+            0b10000, 0b10000, 0b10110, 0b11001, 0b10001, 0b10001, 0b11110,
+        ]),
+        'c' => Some([
+            0b00000, 0b00000, 0b01110, 0b10000, 0b10000, 0b10001, 0b01110,
+        ]),
+        'd' => Some([
+            0b00001, 0b00001, 0b01101, 0b10011, 0b10001, 0b10001, 0b01111,
+        ]),
+        'e' => Some([
+            0b00000, 0b00000, 0b01110, 0b10001, 0b11111, 0b10000, 0b01110,
+        ]),
+        'f' => Some([
+            0b00110, 0b01001, 0b01000, 0b11100, 0b01000, 0b01000, 0b01000,
+        ]),
+        'g' => Some([
+            0b00000, 0b01111, 0b10001, 0b10001, 0b01111, 0b00001, 0b01110,
+        ]),
+        'h' => Some([
+            0b10000, 0b10000, 0b10110, 0b11001, 0b10001, 0b10001, 0b10001,
+        ]),
+        'i' => Some([
+            0b00100, 0b00000, 0b01100, 0b00100, 0b00100, 0b00100, 0b01110,
+        ]),
+        'j' => Some([
+            0b00010, 0b00000, 0b00110, 0b00010, 0b00010, 0b10010, 0b01100,
+        ]),
+        'k' => Some([
+            0b10000, 0b10000, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010,
+        ]),
+        'l' => Some([
+            0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110,
+        ]),
+        'm' => Some([
+            0b00000, 0b00000, 0b11010, 0b10101, 0b10101, 0b10001, 0b10001,
+        ]),
+        'n' => Some([
+            0b00000, 0b00000, 0b10110, 0b11001, 0b10001, 0b10001, 0b10001,
+        ]),
+        'o' => Some([
+            0b00000, 0b00000, 0b01110, 0b10001, 0b10001, 0b10001, 0b01110,
+        ]),
+        'p' => Some([
+            0b00000, 0b00000, 0b11110, 0b10001, 0b11110, 0b10000, 0b10000,
+        ]),
+        'q' => Some([
+            0b00000, 0b00000, 0b01101, 0b10011, 0b01111, 0b00001, 0b00001,
+        ]),
+        'r' => Some([
+            0b00000, 0b00000, 0b10110, 0b11001, 0b10000, 0b10000, 0b10000,
+        ]),
+        's' => Some([
+            0b00000, 0b00000, 0b01110, 0b10000, 0b01110, 0b00001, 0b11110,
+        ]),
+        't' => Some([
+            0b01000, 0b01000, 0b11100, 0b01000, 0b01000, 0b01001, 0b00110,
+        ]),
+        'u' => Some([
+            0b00000, 0b00000, 0b10001, 0b10001, 0b10001, 0b10011, 0b01101,
+        ]),
+        'v' => Some([
+            0b00000, 0b00000, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100,
+        ]),
+        'w' => Some([
+            0b00000, 0b00000, 0b10001, 0b10001, 0b10101, 0b10101, 0b01010,
+        ]),
+        'x' => Some([
+            0b00000, 0b00000, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001,
+        ]),
+        'y' => Some([
+            0b00000, 0b00000, 0b10001, 0b10001, 0b01111, 0b00001, 0b01110,
+        ]),
+        'z' => Some([
+            0b00000, 0b00000, 0b11111, 0b00010, 0b00100, 0b01000, 0b11111,
+        ]),
         '0' => Some([
             0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110,
         ]),
@@ -4324,6 +8517,12 @@ fn glyph_for_char(ch: char) -> Option<[u8; FONT_HEIGHT]> {
         ')' => Some([
             0b01000, 0b00100, 0b00010, 0b00010, 0b00010, 0b00100, 0b01000,
         ]),
+        '[' => Some([
+            0b01110, 0b01000, 0b01000, 0b01000, 0b01000, 0b01000, 0b01110,
+        ]),
+        ']' => Some([
+            0b01110, 0b00010, 0b00010, 0b00010, 0b00010, 0b00010, 0b01110,
+        ]),
         '%' => Some([
             0b11001, 0b11010, 0b00100, 0b01000, 0b10110, 0b00110, 0b00000,
         ]),
@@ -4345,6 +8544,21 @@ fn glyph_for_char(ch: char) -> Option<[u8; FONT_HEIGHT]> {
         '|' => Some([
             0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100,
         ]),
+        '=' => Some([
+            0b00000, 0b00000, 0b11111, 0b00000, 0b11111, 0b00000, 0b00000,
+        ]),
+        '+' => Some([
+            0b00000, 0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0b00000,
+        ]),
+        '<' => Some([
+            0b00010, 0b00100, 0b01000, 0b10000, 0b01000, 0b00100, 0b00010,
+        ]),
+        '>' => Some([
+            0b01000, 0b00100, 0b00010, 0b00001, 0b00010, 0b00100, 0b01000,
+        ]),
+        '#' => Some([
+            0b01010, 0b01010, 0b11111, 0b01010, 0b11111, 0b01010, 0b01010,
+        ]),
         _ => None,
     }
 }
@@ -4431,8 +8645,7 @@ impl UiGeometry {
                 cursor_x += char_width + spacing;
                 continue;
             }
-            let upper = ch.to_ascii_uppercase();
-            if let Some(pattern) = glyph_for_char(upper) {
+            if let Some(pattern) = glyph_for_char(ch) {
                 for (row, bits) in pattern.iter().enumerate() {
                     for col in 0..FONT_WIDTH {
                         if (bits >> (FONT_WIDTH - 1 - col)) & 1 == 1 {
@@ -4454,76 +8667,23 @@ impl UiGeometry {
         }
     }
 
-    fn add_wrapped_text(
+    /// Draws `text` broken at spaces to fit `max_width`, hard-breaking any word that is
+    /// wider on its own. Returns the height the lines took up.
+    fn add_text_wrapped(
         &mut self,
         origin: (f32, f32),
-        height: f32,
         max_width: f32,
+        height: f32,
         color: [f32; 4],
         text: &str,
     ) -> f32 {
-        if height <= 0.0 || max_width <= 0.0 {
-            return origin.1;
-        }
-        let content = text.trim();
-        if content.is_empty() {
-            return origin.1;
-        }
-
-        let scale = height / FONT_HEIGHT as f32;
-        let char_width = FONT_WIDTH as f32 * scale;
-        let spacing = scale * 0.4;
-        let char_step = char_width + spacing;
-        let line_height = height + scale * 1.6;
-
-        let mut lines: Vec<String> = Vec::new();
-        let mut current_line = String::new();
-        let mut current_width = 0.0;
-
-        let flush_line = |lines: &mut Vec<String>, line: &mut String, width: &mut f32| {
-            if !line.is_empty() {
-                lines.push(std::mem::take(line));
-                *width = 0.0;
-            }
-        };
-
-        for word in content.split_whitespace() {
-            let word_width = word.chars().count() as f32 * char_step;
-            if !current_line.is_empty() && current_width + char_step + word_width > max_width {
-                flush_line(&mut lines, &mut current_line, &mut current_width);
-            }
-
-            if !current_line.is_empty() {
-                current_line.push(' ');
-                current_width += char_step;
-            }
-
-            if word_width > max_width {
-                for ch in word.chars() {
-                    if !current_line.is_empty() && current_width + char_step > max_width {
-                        flush_line(&mut lines, &mut current_line, &mut current_width);
-                    }
-                    current_line.push(ch);
-                    current_width += char_step;
-                }
-            } else {
-                current_line.push_str(word);
-                current_width += word_width;
-            }
-        }
-
-        flush_line(&mut lines, &mut current_line, &mut current_width);
-
-        if lines.is_empty() {
-            return origin.1;
-        }
-
-        let mut y = origin.1;
-        for line in lines {
-            self.add_text((origin.0, y), height, color, &line);
-            y += line_height;
+        let lines = wrap_text(text, height, max_width);
+        let line_height = text_line_height(height);
+        for (index, line) in lines.iter().enumerate() {
+            let y = origin.1 + index as f32 * line_height;
+            self.add_text((origin.0, y), height, color, line);
         }
-        y
+        lines.len() as f32 * line_height
     }
 
     fn add_rect_internal(
@@ -4604,34 +8764,100 @@ fn main() -> anyhow::Result<()> {
     println!("╚════════════════════════════════════════╝");
     println!();
     println!("CONTROLS:");
-    println!("  Click           - Grab mouse");
-    println!("  ESC             - Release mouse");
-    println!("  W/A/S/D         - Move (fly when noclip ON)");
-    println!("  Space           - Jump / Up");
-    println!("  F               - Toggle Noclip (collision ON/OFF)");
-    println!("  F3              - Toggle Debug Info");
-    println!("  Mouse           - Look around");
-    println!("  Left Click      - Break block");
-    println!("  Right Click     - Place block");
-    println!("  1-9 Keys        - Select block type");
-    println!("  Mouse Wheel     - Cycle inventory");
+    for section in HELP_SECTIONS {
+        println!("  {}:", section.title);
+        for (keys, action) in section.entries {
+            println!("    {keys:<16}- {action}");
+        }
+    }
+    println!("  --headless      - Run the simulation without a window (--ticks=N to stop)");
+    println!("  --texturepack D - Load block tiles from the PNGs in folder D");
     println!();
     println!("BLOCKS AVAILABLE:");
-    println!("  1-Grass  2-Dirt  3-Stone  4-Copper Wire  5-Voltage Source  6-Ground");
-    println!("  7-Water  8-Rose  9-Tulip");
+    println!("  1-Grass  2-Dirt  3-Stone  4-Copper Wire  5-Resistor  6-Voltage Source");
+    println!("  7-Ground  8-Water  9-Bucket (right-click water or lava to scoop, again to pour)");
     println!();
 
     if let Err(err) = profiler::init_session() {
         eprintln!("Failed to initialise profiler: {err:?}");
     }
 
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let gen_config = WorldGenConfig::from_args(args.iter().cloned());
+    let fluid_spread_limit = fluid_spread_limit_from_args(&args);
+    let seed = seed_from_args(&args);
+    let texture_pack = texture_pack_from_args(&args);
+    let atmosphere_preset = args
+        .iter()
+        .find_map(|arg| arg.strip_prefix("--atmosphere="))
+        .and_then(|name| {
+            let preset = AtmospherePreset::from_name(name);
+            if preset.is_none() {
+                eprintln!("Ignoring unknown atmosphere preset '{name}'");
+            }
+            preset
+        });
+    if let Some(limit) = fluid_spread_limit {
+        println!("Fluid spread limited to {limit} block(s) from a source.");
+        println!();
+    }
+    if args.iter().any(|arg| arg == "--headless") {
+        let max_ticks = args
+            .iter()
+            .find_map(|arg| arg.strip_prefix("--ticks="))
+            .and_then(|value| value.parse::<u64>().ok());
+        run_headless(gen_config, seed, fluid_spread_limit, max_ticks);
+        return Ok(());
+    }
+
     let event_loop = EventLoop::new()?;
     let window = WindowBuilder::new()
         .with_title("Minecraft Clone - Voxel Builder")
         .with_inner_size(winit::dpi::LogicalSize::new(1280.0, 720.0))
         .build(&event_loop)?;
 
-    let mut state = State::new(&window)?;
+    if gen_config.superflat.is_some() {
+        println!("Generating a superflat world (--flat).");
+        println!();
+    } else if gen_config.amplified {
+        println!("Generating amplified terrain (--amplified).");
+        println!();
+    }
+    if gen_config.superflat.is_none() && (gen_config.cave_density - 1.0).abs() > f64::EPSILON {
+        println!("Cave density: {:.2}", gen_config.cave_density);
+        println!();
+    }
+    if let Some(biome) = gen_config.locked_biome {
+        println!("Biome locked to {biome:?}.");
+        println!();
+    }
+
+    let mut state = match State::new(&window, gen_config, seed) {
+        Ok(state) => state,
+        Err(err) if err.downcast_ref::<renderer::NoGpuAdapter>().is_some() => {
+            return Err(err.context(
+                "no usable GPU: update your graphics drivers, or pass --headless \
+                 to run the simulation without rendering",
+            ));
+        }
+        Err(err) => return Err(err),
+    };
+    state.world.set_fluid_spread_limit(fluid_spread_limit);
+    if args.iter().any(|arg| arg == "--survival") {
+        state.game_mode = GameMode::Survival;
+    }
+    if args.iter().any(|arg| arg == "--keep-inventory") {
+        state.keep_inventory = true;
+    }
+    if args.iter().any(|arg| arg == "--water-shorts") {
+        state.world.electrical_mut().set_water_leakage(true);
+    }
+    if let Some(preset) = atmosphere_preset {
+        state.world.set_atmosphere_preset(preset);
+    }
+    if let Some(dir) = texture_pack {
+        state.use_texture_pack(TexturePack::Directory(dir));
+    }
 
     event_loop.run(move |event, target| match event {
         Event::WindowEvent {
@@ -4682,3 +8908,347 @@ fn main() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// `--fluid-spread` caps horizontal fluid spread at the default distance;
+/// `--fluid-spread=N` picks the distance. Absent means unlimited spread.
+fn fluid_spread_limit_from_args(args: &[String]) -> Option<u8> {
+    args.iter().find_map(|arg| {
+        if arg == "--fluid-spread" {
+            return Some(DEFAULT_FLUID_SPREAD_LIMIT);
+        }
+        let value = arg.strip_prefix("--fluid-spread=")?;
+        match value.parse::<u8>() {
+            Ok(limit) => Some(limit),
+            Err(_) => {
+                eprintln!("Ignoring invalid fluid spread '{value}'");
+                None
+            }
+        }
+    })
+}
+
+/// `--seed=N` or `--seed N` pins the terrain; absent or invalid picks a fresh seed.
+fn seed_from_args(args: &[String]) -> Option<u64> {
+    let value = args.iter().enumerate().find_map(|(index, arg)| {
+        if arg == "--seed" {
+            args.get(index + 1)
+        } else {
+            arg.strip_prefix("--seed=").map(|_| arg)
+        }
+    })?;
+    let value = value.strip_prefix("--seed=").unwrap_or(value);
+    match value.parse::<u64>() {
+        Ok(seed) => Some(seed),
+        Err(_) => {
+            eprintln!("Ignoring invalid seed '{value}'");
+            None
+        }
+    }
+}
+
+/// `--texturepack <dir>` or `--texturepack=<dir>` names a folder of per-tile PNGs.
+fn texture_pack_from_args(args: &[String]) -> Option<PathBuf> {
+    args.iter().enumerate().find_map(|(index, arg)| {
+        if arg == "--texturepack" {
+            args.get(index + 1).map(PathBuf::from)
+        } else {
+            arg.strip_prefix("--texturepack=").map(PathBuf::from)
+        }
+    })
+}
+
+/// Builds the world from `seed`, or a fresh one, and prints the seed so the terrain can
+/// be shared with `--seed`.
+fn create_world(gen_config: WorldGenConfig, seed: Option<u64>) -> World {
+    let world = match seed {
+        Some(seed) => World::with_seed_and_config(seed, gen_config),
+        None => World::with_config(gen_config),
+    };
+    println!("World seed: {}", world.seed());
+    world
+}
+
+/// Runs the world simulation without a window or GPU: chunk streaming around spawn,
+/// CPU fluid steps, electrical ticks and the day cycle, at the normal fixed tick rate.
+/// Stops after `max_ticks` when given, otherwise runs until the process is killed.
+fn run_headless(
+    gen_config: WorldGenConfig,
+    seed: Option<u64>,
+    fluid_spread_limit: Option<u8>,
+    max_ticks: Option<u64>,
+) {
+    println!("Running headless (no renderer).");
+    let mut world = create_world(gen_config, seed);
+    world.set_fluid_spread_limit(fluid_spread_limit);
+    let origin = point3(0.5, 30.0, 0.5);
+    let _ = world.update_loaded_chunks(origin, RENDER_DISTANCE);
+    let spawn = world.find_spawn(origin);
+    let _ = world.update_loaded_chunks(spawn, RENDER_DISTANCE);
+    println!("Spawn: ({:.1}, {:.1}, {:.1})", spawn.x, spawn.y, spawn.z);
+
+    let tick_duration = std::time::Duration::from_secs_f32(FIXED_TICK_STEP);
+    let status_interval = std::time::Duration::from_secs(10);
+    let mut next_tick = Instant::now();
+    let mut last_status = next_tick;
+    let mut fluid_countdown = 0;
+    let mut tick: u64 = 0;
+    while max_ticks != Some(tick) {
+        world.advance_time(FIXED_TICK_STEP);
+        if fluid_countdown == 0 {
+            world.step_fluids();
+            fluid_countdown = WATER_UPDATE_INTERVAL;
+        }
+        fluid_countdown -= 1;
+        if tick % LAVA_UPDATE_INTERVAL as u64 == 0 {
+            world.step_lava();
+        }
+        world.tick_electrical(FIXED_TICK_STEP);
+        tick += 1;
+
+        if last_status.elapsed() >= status_interval {
+            last_status = Instant::now();
+            println!(
+                "[headless] tick {} | chunks {} | components {} | time {:.2}",
+                tick,
+                world.chunks().len(),
+                world.electrical().node_count(),
+                world.environment().time_of_day()
+            );
+        }
+
+        next_tick += tick_duration;
+        let now = Instant::now();
+        if next_tick > now {
+            std::thread::sleep(next_tick - now);
+        } else {
+            next_tick = now;
+        }
+    }
+    println!(
+        "Headless run finished after {tick} tick(s). World state hash: {:016x}",
+        world.state_hash()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn console_parses_find_with_component_aliases() {
+        assert_eq!(
+            parse_console_command("find source"),
+            Ok(ConsoleCommand::Find(ElectricalComponent::VoltageSource))
+        );
+        assert_eq!(
+            parse_console_command("  FIND   wire "),
+            Ok(ConsoleCommand::Find(ElectricalComponent::Wire))
+        );
+        assert_eq!(
+            parse_console_command("find Overloaded"),
+            Ok(ConsoleCommand::FindOverloaded)
+        );
+        assert_eq!(
+            parse_console_command("find cap"),
+            Ok(ConsoleCommand::Find(ElectricalComponent::Capacitor))
+        );
+        assert!(parse_console_command("find banana").is_err());
+        assert!(parse_console_command("find").is_err());
+        assert!(parse_console_command("teleport").is_err());
+    }
+
+    #[test]
+    fn console_switches_game_mode() {
+        assert_eq!(
+            parse_console_command("gamemode survival"),
+            Ok(ConsoleCommand::GameMode(GameMode::Survival))
+        );
+        assert_eq!(
+            parse_console_command("GAMEMODE c"),
+            Ok(ConsoleCommand::GameMode(GameMode::Creative))
+        );
+        assert!(parse_console_command("gamemode").is_err());
+        assert!(parse_console_command("gamemode hardcore").is_err());
+        assert!(!GameMode::default().consumes_blocks());
+        assert!(GameMode::Survival.consumes_blocks());
+        assert!(!GameMode::Creative.drops_items() && !GameMode::Creative.takes_damage());
+        assert!(GameMode::Survival.drops_items() && GameMode::Survival.takes_damage());
+        assert_eq!(
+            parse_console_command("keepinventory ON"),
+            Ok(ConsoleCommand::KeepInventory(Some(true)))
+        );
+        assert_eq!(
+            parse_console_command("keepinventory"),
+            Ok(ConsoleCommand::KeepInventory(None))
+        );
+        assert!(parse_console_command("keepinventory maybe").is_err());
+    }
+
+    #[test]
+    fn console_parses_time_and_day_length() {
+        assert_eq!(parse_console_command("time noon"), Ok(ConsoleCommand::Time(12, 0)));
+        assert_eq!(parse_console_command("time 21:05"), Ok(ConsoleCommand::Time(21, 5)));
+        assert!(parse_console_command("time 25:00").is_err());
+        assert!(parse_console_command("time").is_err());
+        assert_eq!(parse_console_command("daylength"), Ok(ConsoleCommand::DayLength(None)));
+        assert_eq!(
+            parse_console_command("daylength 90"),
+            Ok(ConsoleCommand::DayLength(Some(90)))
+        );
+        assert!(parse_console_command("daylength fast").is_err());
+    }
+
+    #[test]
+    fn palette_color_keys_put_greys_after_colours() {
+        let keys = block_color_keys(&TexturePack::Default);
+        let key = |block: BlockType| keys[block as usize];
+        assert_eq!(keys.len(), BlockType::ALL.len());
+        assert!(key(BlockType::Lava) < key(BlockType::Grass), "red and orange come first");
+        assert!(key(BlockType::Grass) < key(BlockType::Water), "green before blue");
+        assert!(key(BlockType::Stone) >= 1.0, "stone counts as grey");
+        assert_eq!(PaletteSort::Color.next(), PaletteSort::List);
+    }
+
+    #[test]
+    fn console_parses_loadout_commands() {
+        assert_eq!(
+            parse_console_command("loadout save red stone"),
+            Ok(ConsoleCommand::Loadout(LoadoutAction::Save, "red stone".to_string()))
+        );
+        assert_eq!(
+            parse_console_command("loadout DELETE wiring"),
+            Ok(ConsoleCommand::Loadout(LoadoutAction::Delete, "wiring".to_string()))
+        );
+        assert!(parse_console_command("loadout load").is_err());
+        assert!(parse_console_command("loadout rename wiring").is_err());
+    }
+
+    #[test]
+    fn console_parses_reset_commands() {
+        assert_eq!(parse_console_command("reset chunk"), Ok(ConsoleCommand::ResetArea(0)));
+        assert_eq!(parse_console_command("reset area 2"), Ok(ConsoleCommand::ResetArea(2)));
+        assert!(parse_console_command("reset area 99").is_err());
+        assert!(parse_console_command("reset area -1").is_err());
+        assert!(parse_console_command("reset").is_err());
+    }
+
+    #[test]
+    fn seed_flag_accepts_both_spellings() {
+        let args = |list: &[&str]| list.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        assert_eq!(seed_from_args(&args(&["--seed", "42"])), Some(42));
+        assert_eq!(seed_from_args(&args(&["--flat", "--seed=7"])), Some(7));
+        assert_eq!(seed_from_args(&args(&["--seed=castle"])), None);
+        assert_eq!(seed_from_args(&args(&["--seed"])), None);
+    }
+
+    #[test]
+    fn console_parses_save_and_load_names() {
+        assert_eq!(
+            parse_console_command("save"),
+            Ok(ConsoleCommand::Save("world.rcw".to_string()))
+        );
+        assert_eq!(
+            parse_console_command("load castle"),
+            Ok(ConsoleCommand::Load("castle.rcw".to_string()))
+        );
+        assert_eq!(
+            parse_console_command("save castle.rcw"),
+            Ok(ConsoleCommand::Save("castle.rcw".to_string()))
+        );
+    }
+
+    #[test]
+    fn help_rows_only_use_characters_the_ui_font_can_draw() {
+        let rows = help_rows();
+        assert_eq!(
+            rows.iter().filter(|(keys, _)| keys.is_none()).count(),
+            HELP_SECTIONS.len()
+        );
+        for (keys, text) in rows {
+            for ch in keys.unwrap_or("").chars().chain(text.chars()) {
+                assert!(
+                    ch == ' ' || glyph_for_char(ch).is_some(),
+                    "no glyph for {ch:?} in {text:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn wrapped_text_breaks_at_spaces_and_splits_long_words() {
+        let height = 0.014;
+        let width = text_width("Glow Shroom", height);
+        assert_eq!(
+            wrap_text("Glow Shroom lights caves", height, width),
+            vec!["Glow Shroom", "lights", "caves"]
+        );
+        let lines = wrap_text("a Potentiometer", height, text_width("Potent", height));
+        assert_eq!(lines, vec!["a", "Potent", "iomete", "r"]);
+        for line in &lines {
+            assert!(text_width(line, height) <= text_width("Potent", height));
+        }
+        assert!(wrap_text("   ", height, width).is_empty());
+    }
+
+    #[test]
+    fn font_keeps_case_and_covers_block_names() {
+        for upper in 'A'..='Z' {
+            let lower = upper.to_ascii_lowercase();
+            let (Some(upper_glyph), Some(lower_glyph)) =
+                (glyph_for_char(upper), glyph_for_char(lower))
+            else {
+                panic!("missing glyph for {upper} or {lower}");
+            };
+            assert_ne!(upper_glyph, lower_glyph, "{lower} should not reuse {upper}");
+        }
+        for ch in "=+<>[]#".chars() {
+            assert!(glyph_for_char(ch).is_some(), "no glyph for {ch:?}");
+        }
+        for block in BlockType::ALL {
+            for ch in block.name().chars().chain(block.description().chars()) {
+                assert!(ch == ' ' || glyph_for_char(ch).is_some(), "no glyph for {ch:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn outline_color_follows_the_block_category() {
+        assert_eq!(outline_color_for(BlockType::Resistor, false), OUTLINE_ELECTRICAL_COLOR);
+        assert_eq!(outline_color_for(BlockType::Water, false), OUTLINE_FLUID_COLOR);
+        assert_eq!(outline_color_for(BlockType::Stone, true), OUTLINE_FLUID_COLOR);
+        assert_eq!(outline_color_for(BlockType::Stone, false), OUTLINE_NEUTRAL_COLOR);
+    }
+
+    #[test]
+    fn measurement_reports_euclidean_and_manhattan_distances() {
+        let measurement = Measurement {
+            start: (2, 64, -1),
+            end: (5, 64, -5),
+        };
+        assert_eq!(measurement.delta(), (3, 0, -4));
+        assert_eq!(measurement.euclidean(), 5.0);
+        assert_eq!(measurement.manhattan(), 7);
+    }
+
+    #[test]
+    fn four_by_four_inventory_slots_stay_in_bounds_without_overlap() {
+        let rects: Vec<Rect> = (0..16)
+            .map(|index| inventory_grid_slot_rect(4, 4, index).unwrap())
+            .collect();
+        assert!(inventory_grid_slot_rect(4, 4, 16).is_none());
+
+        let max_x = INVENTORY_START_X + ui_width(INVENTORY_GRID_MAX_EXTENT) + 1e-4;
+        let max_y = INVENTORY_START_Y + INVENTORY_GRID_MAX_EXTENT + 1e-4;
+        for (index, &(min, max)) in rects.iter().enumerate() {
+            assert!(min.0 >= INVENTORY_START_X && min.1 >= INVENTORY_START_Y);
+            assert!(max.0 <= max_x && max.1 <= max_y, "slot {index} spills out of the grid");
+            for &(other_min, other_max) in &rects[index + 1..] {
+                let overlaps = min.0 < other_max.0
+                    && other_min.0 < max.0
+                    && min.1 < other_max.1
+                    && other_min.1 < max.1;
+                assert!(!overlaps, "slot {index} overlaps a later slot");
+            }
+        }
+    }
+}
@@ -1,8 +1,12 @@
+use std::ops::Range;
+
 use cgmath::{InnerSpace, Vector3};
 
-use crate::block::{Axis, BlockFace, BlockType, RenderKind};
+use crate::block::{Axis, BlockFace, BlockType, FluidKind, RenderKind};
 use crate::chunk::{Chunk, CHUNK_SIZE};
-use crate::electric::{BlockPos3, ElectricalComponent, ElectricalNode};
+use crate::electric::{
+    led_level, BlockPos3, ElectricalComponent, ElectricalNode, LED_BRIGHTNESS_LEVELS,
+};
 use crate::texture::{
     atlas_uv_bounds, TILE_FLOWER_LEAF, TILE_FLOWER_ROSE_PETAL, TILE_FLOWER_STEM,
     TILE_FLOWER_TULIP_PETAL, TILE_GROUND_SIDE_CONNECTED, TILE_GROUND_SIDE_UNCONNECTED,
@@ -15,6 +19,8 @@ use crate::texture::{
 use crate::world::{ChunkPos, World, MAX_FLUID_LEVEL};
 
 const HALF_BLOCK: f32 = 0.5;
+/// Tint of a solid-block vertex with 0 (tucked into a corner) to 3 (open) clear neighbours.
+const AO_LEVELS: [f32; 4] = [0.55, 0.7, 0.85, 1.0];
 
 #[repr(C)]
 #[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
@@ -27,23 +33,119 @@ pub struct Vertex {
     pub light: f32, // Combined light level (0-15)
 }
 
+/// How much geometry flowers get in chunk meshes; a knob for dense fields on slow GPUs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FoliageDetail {
+    /// Full 3D models.
+    #[default]
+    High,
+    /// 3D models without the inner petal ring and the smaller leaves.
+    Medium,
+    /// A flat cross billboard, like grass.
+    Low,
+}
+
+impl FoliageDetail {
+    const ALL: [Self; 3] = [Self::High, Self::Medium, Self::Low];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::High => "HIGH",
+            Self::Medium => "MEDIUM",
+            Self::Low => "LOW",
+        }
+    }
+
+    pub fn cycle(self, step: i32) -> Self {
+        let index = Self::ALL.iter().position(|&detail| detail == self).unwrap_or(0) as i32;
+        Self::ALL[(index + step).rem_euclid(Self::ALL.len() as i32) as usize]
+    }
+}
+
+/// Render pass a quad belongs to, derived from its vertex `material`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MaterialGroup {
+    Opaque,
+    Cutout,
+    Translucent,
+}
+
+impl MaterialGroup {
+    /// Groups in the order their indices are laid out by `MeshData::indices`.
+    pub const ALL: [MaterialGroup; 3] = [
+        MaterialGroup::Opaque,
+        MaterialGroup::Cutout,
+        MaterialGroup::Translucent,
+    ];
+
+    fn from_material(material: f32) -> Self {
+        if material >= MATERIAL_EMISSIVE - 0.5 {
+            MaterialGroup::Opaque
+        } else if material >= MATERIAL_TRANSLUCENT - 0.5 {
+            MaterialGroup::Translucent
+        } else if material >= MATERIAL_CUTOUT - 0.5 {
+            MaterialGroup::Cutout
+        } else {
+            MaterialGroup::Opaque
+        }
+    }
+
+    fn slot(self) -> usize {
+        match self {
+            MaterialGroup::Opaque => 0,
+            MaterialGroup::Cutout => 1,
+            MaterialGroup::Translucent => 2,
+        }
+    }
+}
+
+/// Vertices plus one index list per material group, so the renderer can
+/// draw each group in its own pass.
 pub struct MeshData {
     pub vertices: Vec<Vertex>,
-    pub indices: Vec<u32>,
+    groups: [Vec<u32>; 3],
+    /// Glowing blocks met while meshing, which the renderer turns into point lights.
+    pub glow_sources: Vec<[i32; 3]>,
 }
 
 impl MeshData {
     pub fn new() -> Self {
         Self {
             vertices: Vec::new(),
-            indices: Vec::new(),
+            groups: [Vec::new(), Vec::new(), Vec::new()],
+            glow_sources: Vec::new(),
         }
     }
 
+    pub fn group_indices(&self, group: MaterialGroup) -> &[u32] {
+        &self.groups[group.slot()]
+    }
+
+    pub fn index_count(&self) -> usize {
+        self.groups.iter().map(Vec::len).sum()
+    }
+
+    /// All indices, opaque first, then cutout, then translucent.
+    pub fn indices(&self) -> Vec<u32> {
+        self.groups.concat()
+    }
+
+    /// Range of each group within `indices()`, in `MaterialGroup::ALL` order.
+    pub fn group_ranges(&self) -> [Range<u32>; 3] {
+        let mut start = 0;
+        MaterialGroup::ALL.map(|group| {
+            let end = start + self.group_indices(group).len() as u32;
+            let range = start..end;
+            start = end;
+            range
+        })
+    }
+
     fn push_quad(&mut self, quad: [Vertex; 4]) {
         let base = self.vertices.len() as u32;
         self.vertices.extend_from_slice(&quad);
-        self.indices
+        let group = MaterialGroup::from_material(quad[0].material);
+        self.groups[group.slot()]
             .extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
     }
 
@@ -120,7 +222,7 @@ fn component_textures(component: ElectricalComponent, block: BlockType) -> Compo
     let base_side = block.atlas_coords(BlockFace::North);
     let base_top = block.atlas_coords(BlockFace::Top);
     match component {
-        ElectricalComponent::Wire => ComponentTextures {
+        ElectricalComponent::Wire | ElectricalComponent::Switch => ComponentTextures {
             base_side,
             base_top,
             side_connected: TILE_WIRE_SIDE_CONNECTED,
@@ -128,7 +230,11 @@ fn component_textures(component: ElectricalComponent, block: BlockType) -> Compo
             top_connected: TILE_WIRE_TOP_CONNECTED,
             top_unconnected: TILE_WIRE_TOP_UNCONNECTED,
         },
-        ElectricalComponent::Resistor => ComponentTextures {
+        ElectricalComponent::Resistor
+        | ElectricalComponent::Capacitor
+        | ElectricalComponent::Led
+        | ElectricalComponent::Diode
+        | ElectricalComponent::Potentiometer => ComponentTextures {
             base_side,
             base_top,
             side_connected: TILE_RESISTOR_SIDE_CONNECTED,
@@ -253,6 +359,25 @@ fn emit_connection_plate(
 
     mesh.push_quad_double_sided(quad);
 }
+
+/// Live state a component's mesh shows: switch position, LED brightness and wiper travel.
+#[derive(Clone, Copy, Default)]
+struct ComponentLook {
+    closed: bool,
+    brightness: u8,
+    wiper: f32,
+}
+
+impl ComponentLook {
+    fn of(node: &ElectricalNode) -> Self {
+        Self {
+            closed: node.closed,
+            brightness: led_level(node.telemetry.current),
+            wiper: node.params.wiper(),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Default)]
 struct AxisLead {
     forward_present: bool,
@@ -280,10 +405,13 @@ impl AxisLead {
 const MATERIAL_OPAQUE: f32 = 0.0;
 const MATERIAL_CUTOUT: f32 = 1.0;
 const MATERIAL_TRANSLUCENT: f32 = 2.0;
+/// Opaque and lit by itself: the shader skips block, sun and point lighting.
+const MATERIAL_EMISSIVE: f32 = 3.0;
 
-fn material_for_block(block: BlockType) -> f32 {
+pub fn material_for_block(block: BlockType) -> f32 {
     match block {
         BlockType::Water => MATERIAL_TRANSLUCENT,
+        BlockType::Lava => MATERIAL_EMISSIVE,
         BlockType::Leaves
         | BlockType::FlowerRose
         | BlockType::FlowerTulip
@@ -293,19 +421,19 @@ fn material_for_block(block: BlockType) -> f32 {
     }
 }
 
-pub fn generate_world_mesh(world: &World) -> MeshData {
+pub fn generate_world_mesh(world: &World, foliage: FoliageDetail) -> MeshData {
     let mut combined = MeshData::new();
     let mut chunk_positions: Vec<ChunkPos> = world.chunks().keys().copied().collect();
     chunk_positions.sort_by_key(|pos| (pos.x, pos.z));
 
     for pos in chunk_positions {
         if let Some(chunk) = world.chunks().get(&pos) {
-            let chunk_mesh = generate_chunk_mesh(world, pos, chunk);
+            let chunk_mesh = generate_chunk_mesh(world, pos, chunk, foliage);
             let base = combined.vertices.len() as u32;
             combined.vertices.extend_from_slice(&chunk_mesh.vertices);
-            combined
-                .indices
-                .extend(chunk_mesh.indices.iter().map(|i| i + base));
+            for (group, indices) in combined.groups.iter_mut().zip(&chunk_mesh.groups) {
+                group.extend(indices.iter().map(|i| i + base));
+            }
         }
     }
 
@@ -354,7 +482,42 @@ pub fn generate_block_mesh(block: BlockType, origin: Vector3<f32>, scale: f32) -
     mesh
 }
 
-pub fn generate_chunk_mesh(world: &World, chunk_pos: ChunkPos, chunk: &Chunk) -> MeshData {
+/// Ghost of `component` as it would sit on `face` of `pos`, with its connection
+/// plates lit according to `connections` and every vertex tinted by `tint`.
+pub fn generate_component_preview(
+    component: ElectricalComponent,
+    pos: BlockPos3,
+    face: BlockFace,
+    axis: Axis,
+    connections: [bool; 6],
+    tint: [f32; 3],
+) -> MeshData {
+    let mut mesh = MeshData::new();
+    let center = Vector3::new(pos.x as f32, pos.y as f32, pos.z as f32);
+    append_component_mesh(
+        &mut mesh,
+        component.block_type(),
+        component,
+        center,
+        face,
+        axis,
+        1.0,
+        component.connectors(axis, face),
+        connections,
+        ComponentLook::default(),
+    );
+    for vertex in &mut mesh.vertices {
+        vertex.tint = tint;
+    }
+    mesh
+}
+
+pub fn generate_chunk_mesh(
+    world: &World,
+    chunk_pos: ChunkPos,
+    chunk: &Chunk,
+    foliage: FoliageDetail,
+) -> MeshData {
     let mut mesh = MeshData::new();
 
     for (x, y, z, block) in chunk.iter() {
@@ -369,8 +532,9 @@ pub fn generate_chunk_mesh(world: &World, chunk_pos: ChunkPos, chunk: &Chunk) ->
             }
             RenderKind::Cross => append_cross_block(&mut mesh, world_x, world_y, world_z, block),
             RenderKind::Flower => {
-                if matches!(block, BlockType::FlowerRose | BlockType::FlowerTulip) {
-                    append_flower_block(&mut mesh, world_x, world_y, world_z, block);
+                let modelled = matches!(block, BlockType::FlowerRose | BlockType::FlowerTulip);
+                if modelled && foliage != FoliageDetail::Low {
+                    append_flower_block(&mut mesh, world_x, world_y, world_z, block, foliage);
                 } else {
                     append_cross_block(&mut mesh, world_x, world_y, world_z, block)
                 }
@@ -378,6 +542,9 @@ pub fn generate_chunk_mesh(world: &World, chunk_pos: ChunkPos, chunk: &Chunk) ->
             RenderKind::Flat => append_flat_block(&mut mesh, world_x, world_y, world_z, block),
             RenderKind::Electrical(_) => {}
         }
+        if block == BlockType::GlowShroom {
+            mesh.glow_sources.push([world_x, world_y, world_z]);
+        }
 
         if let Some(faces) = world.electrical().face_nodes(world_pos) {
             for (face, node) in faces.iter() {
@@ -417,19 +584,45 @@ fn append_solid_block(
         if !world.get_block(*nx, *ny, *nz).occludes() {
             // Sample light at the block's own position
             let light = world.get_light(x, y, z);
-            let quad = build_face(
-                *face,
-                *normal,
-                block,
-                [x as f32, y as f32, z as f32],
-                HALF_BLOCK,
-                light,
-            );
+            let origin = [x as f32, y as f32, z as f32];
+            let mut quad = build_face(*face, *normal, block, origin, HALF_BLOCK, light);
+            let ao = quad.map(|vertex| {
+                let offset = [0, 1, 2].map(|axis| vertex.position[axis] - origin[axis]);
+                corner_occlusion(world, [*nx, *ny, *nz], normal.map(|n| n as i32), offset)
+            });
+            for (vertex, level) in quad.iter_mut().zip(ao) {
+                vertex.tint = vertex.tint.map(|channel| channel * AO_LEVELS[level]);
+            }
+            // Split along the brighter diagonal, or the shading creases across the face.
+            if ao[0] + ao[2] < ao[1] + ao[3] {
+                quad.rotate_left(1);
+            }
             mesh.push_quad(quad);
         }
     }
 }
 
+/// Ambient occlusion level of a face corner: how many of the three blocks around it, in
+/// the layer in front of the face (`front`), are clear. Two occluding sides count as fully
+/// tucked in whatever the diagonal holds.
+fn corner_occlusion(world: &World, front: [i32; 3], normal: [i32; 3], offset: [f32; 3]) -> usize {
+    let axis = normal.iter().position(|&n| n != 0).unwrap_or(1);
+    let (a, b) = ((axis + 1) % 3, (axis + 2) % 3);
+    let toward = |mut cell: [i32; 3], axis: usize| {
+        cell[axis] += if offset[axis] > 0.0 { 1 } else { -1 };
+        cell
+    };
+    let occludes = |cell: [i32; 3]| world.get_block(cell[0], cell[1], cell[2]).occludes();
+
+    let side_a = occludes(toward(front, a));
+    let side_b = occludes(toward(front, b));
+    if side_a && side_b {
+        return 0;
+    }
+    let corner = occludes(toward(toward(front, a), b));
+    3 - (side_a as usize + side_b as usize + corner as usize)
+}
+
 fn build_face(
     face: BlockFace,
     normal: [f32; 3],
@@ -590,9 +783,16 @@ fn append_cross_block(mesh: &mut MeshData, x: i32, y: i32, z: i32, block: BlockT
     }
 }
 
-fn append_flower_block(mesh: &mut MeshData, x: i32, y: i32, z: i32, block: BlockType) {
+fn append_flower_block(
+    mesh: &mut MeshData,
+    x: i32,
+    y: i32,
+    z: i32,
+    block: BlockType,
+    foliage: FoliageDetail,
+) {
     let center = Vector3::new(x as f32, y as f32, z as f32);
-    emit_flower_geometry(mesh, center, 1.0, block);
+    emit_flower_geometry(mesh, center, 1.0, block, foliage);
 }
 
 fn append_flower_billboard(
@@ -601,19 +801,33 @@ fn append_flower_billboard(
     origin: Vector3<f32>,
     scale: f32,
 ) {
-    emit_flower_geometry(mesh, origin, scale, block);
+    emit_flower_geometry(mesh, origin, scale, block, FoliageDetail::High);
 }
 
-fn emit_flower_geometry(mesh: &mut MeshData, center: Vector3<f32>, scale: f32, block: BlockType) {
+fn emit_flower_geometry(
+    mesh: &mut MeshData,
+    center: Vector3<f32>,
+    scale: f32,
+    block: BlockType,
+    foliage: FoliageDetail,
+) {
     let material = material_for_block(block);
+    let full = foliage == FoliageDetail::High;
     match block {
-        BlockType::FlowerRose => emit_rose_geometry(mesh, center, scale, material),
-        BlockType::FlowerTulip => emit_tulip_geometry(mesh, center, scale, material),
+        BlockType::FlowerRose => emit_rose_geometry(mesh, center, scale, material, full),
+        BlockType::FlowerTulip => emit_tulip_geometry(mesh, center, scale, material, full),
         _ => {}
     }
 }
 
-fn emit_rose_geometry(mesh: &mut MeshData, center: Vector3<f32>, scale: f32, material: f32) {
+/// `full` adds the upper leaf, sepals and inner petal ring.
+fn emit_rose_geometry(
+    mesh: &mut MeshData,
+    center: Vector3<f32>,
+    scale: f32,
+    material: f32,
+    full: bool,
+) {
     let half = HALF_BLOCK * scale;
     let base_y = center.y - half;
 
@@ -681,40 +895,42 @@ fn emit_rose_geometry(mesh: &mut MeshData, center: Vector3<f32>, scale: f32, mat
         material,
         [0.78, 0.94, 0.68],
     );
-    let upper_leaf_height = base_y + scale * 0.48;
-    push_plant_panel(
-        mesh,
-        center,
-        upper_leaf_height,
-        62.0,
-        22.0,
-        scale * 0.12,
-        -scale * 0.01,
-        scale * 0.12,
-        scale * 0.07,
-        scale * 0.018,
-        leaf_uv,
-        material,
-        [0.88, 0.98, 0.80],
-    );
-
-    let sepal_height = base_y + stem_height + scale * 0.03;
-    for angle in [0.0_f32, 90.0, 180.0, 270.0] {
+    if full {
+        let upper_leaf_height = base_y + scale * 0.48;
         push_plant_panel(
             mesh,
             center,
-            sepal_height,
-            angle + 15.0,
-            26.0,
-            scale * 0.08,
-            -scale * 0.004,
-            scale * 0.08,
-            scale * 0.05,
-            scale * 0.014,
+            upper_leaf_height,
+            62.0,
+            22.0,
+            scale * 0.12,
+            -scale * 0.01,
+            scale * 0.12,
+            scale * 0.07,
+            scale * 0.018,
             leaf_uv,
             material,
-            [0.88, 0.98, 0.74],
+            [0.88, 0.98, 0.80],
         );
+
+        let sepal_height = base_y + stem_height + scale * 0.03;
+        for angle in [0.0_f32, 90.0, 180.0, 270.0] {
+            push_plant_panel(
+                mesh,
+                center,
+                sepal_height,
+                angle + 15.0,
+                26.0,
+                scale * 0.08,
+                -scale * 0.004,
+                scale * 0.08,
+                scale * 0.05,
+                scale * 0.014,
+                leaf_uv,
+                material,
+                [0.88, 0.98, 0.74],
+            );
+        }
     }
 
     let bud_center = Vector3::new(center.x, base_y + stem_height + scale * 0.12, center.z);
@@ -763,22 +979,24 @@ fn emit_rose_geometry(mesh: &mut MeshData, center: Vector3<f32>, scale: f32, mat
     let inner_half_tangent = petal_half_tangent * 0.65;
     let inner_half_bitangent = petal_half_bitangent * 0.7;
     let inner_half_normal = petal_half_normal * 0.9;
-    for angle in [36.0_f32, 108.0, 180.0, 252.0, 324.0] {
-        push_plant_panel(
-            mesh,
-            center,
-            inner_height,
-            angle,
-            20.0,
-            scale * 0.09,
-            scale * 0.012,
-            inner_half_tangent,
-            inner_half_bitangent,
-            inner_half_normal,
-            petal_uv,
-            material,
-            [1.0, 1.0, 1.0],
-        );
+    if full {
+        for angle in [36.0_f32, 108.0, 180.0, 252.0, 324.0] {
+            push_plant_panel(
+                mesh,
+                center,
+                inner_height,
+                angle,
+                20.0,
+                scale * 0.09,
+                scale * 0.012,
+                inner_half_tangent,
+                inner_half_bitangent,
+                inner_half_normal,
+                petal_uv,
+                material,
+                [1.0, 1.0, 1.0],
+            );
+        }
     }
 
     let crown_center = Vector3::new(center.x, base_y + stem_height + scale * 0.17, center.z);
@@ -796,7 +1014,14 @@ fn emit_rose_geometry(mesh: &mut MeshData, center: Vector3<f32>, scale: f32, mat
     );
 }
 
-fn emit_tulip_geometry(mesh: &mut MeshData, center: Vector3<f32>, scale: f32, material: f32) {
+/// `full` adds the stem sheaths and inner petal ring.
+fn emit_tulip_geometry(
+    mesh: &mut MeshData,
+    center: Vector3<f32>,
+    scale: f32,
+    material: f32,
+    full: bool,
+) {
     let half = HALF_BLOCK * scale;
     let base_y = center.y - half;
 
@@ -819,37 +1044,39 @@ fn emit_tulip_geometry(mesh: &mut MeshData, center: Vector3<f32>, scale: f32, ma
         [0.76, 0.92, 0.68],
     );
 
-    let sheath_height = base_y + scale * 0.2;
-    push_plant_panel(
-        mesh,
-        center,
-        sheath_height,
-        12.0,
-        22.0,
-        scale * 0.09,
-        -scale * 0.01,
-        scale * 0.10,
-        scale * 0.05,
-        scale * 0.015,
-        leaf_uv,
-        material,
-        [0.82, 0.98, 0.74],
-    );
-    push_plant_panel(
-        mesh,
-        center,
-        sheath_height,
-        -18.0,
-        18.0,
-        scale * 0.08,
-        -scale * 0.008,
-        scale * 0.10,
-        scale * 0.05,
-        scale * 0.015,
-        leaf_uv,
-        material,
-        [0.78, 0.94, 0.70],
-    );
+    if full {
+        let sheath_height = base_y + scale * 0.2;
+        push_plant_panel(
+            mesh,
+            center,
+            sheath_height,
+            12.0,
+            22.0,
+            scale * 0.09,
+            -scale * 0.01,
+            scale * 0.10,
+            scale * 0.05,
+            scale * 0.015,
+            leaf_uv,
+            material,
+            [0.82, 0.98, 0.74],
+        );
+        push_plant_panel(
+            mesh,
+            center,
+            sheath_height,
+            -18.0,
+            18.0,
+            scale * 0.08,
+            -scale * 0.008,
+            scale * 0.10,
+            scale * 0.05,
+            scale * 0.015,
+            leaf_uv,
+            material,
+            [0.78, 0.94, 0.70],
+        );
+    }
 
     let leaf_height = base_y + scale * 0.44;
     push_plant_panel(
@@ -909,22 +1136,24 @@ fn emit_tulip_geometry(mesh: &mut MeshData, center: Vector3<f32>, scale: f32, ma
     let inner_half_tangent = outer_half_tangent * 0.7;
     let inner_half_bitangent = outer_half_bitangent * 0.75;
     let inner_half_normal = outer_half_normal * 0.9;
-    for angle in [45.0_f32, 135.0, 225.0, 315.0] {
-        push_plant_panel(
-            mesh,
-            center,
-            inner_height,
-            angle,
-            9.0,
-            scale * 0.07,
-            scale * 0.008,
-            inner_half_tangent,
-            inner_half_bitangent,
-            inner_half_normal,
-            petal_uv,
-            material,
-            [1.0, 0.99, 0.98],
-        );
+    if full {
+        for angle in [45.0_f32, 135.0, 225.0, 315.0] {
+            push_plant_panel(
+                mesh,
+                center,
+                inner_height,
+                angle,
+                9.0,
+                scale * 0.07,
+                scale * 0.008,
+                inner_half_tangent,
+                inner_half_bitangent,
+                inner_half_normal,
+                petal_uv,
+                material,
+                [1.0, 0.99, 0.98],
+            );
+        }
     }
 
     let cap_center = Vector3::new(center.x, base_y + stem_height + scale * 0.16, center.z);
@@ -1052,8 +1281,23 @@ fn append_fluid_block(mesh: &mut MeshData, world: &World, x: i32, y: i32, z: i32
         return;
     }
 
-    let material = MATERIAL_TRANSLUCENT;
-    let tint = [1.0, 1.0, 1.0];
+    let kind = world.get_fluid_kind(x, y, z);
+    let block = kind.block_type();
+    let material = material_for_block(block);
+    let tint = match kind {
+        FluidKind::Water => [1.0, 1.0, 1.0],
+        // Pushed past white so the glow survives the atlas' darker crust.
+        FluidKind::Lava => [1.25, 1.1, 1.0],
+    };
+    // The other liquid never shares a surface for long (they cool to stone), so it
+    // doesn't hide this one's faces.
+    let same_fluid_amount = |nx: i32, ny: i32, nz: i32| {
+        if world.get_fluid_kind(nx, ny, nz) == kind {
+            world.get_fluid_amount(nx, ny, nz)
+        } else {
+            0
+        }
+    };
     let cx = x as f32;
     let cy = y as f32;
     let cz = z as f32;
@@ -1066,10 +1310,10 @@ fn append_fluid_block(mesh: &mut MeshData, world: &World, x: i32, y: i32, z: i32
         top = bottom + 0.001;
     }
 
-    let (top_tile_x, top_tile_y) = BlockType::Water.atlas_coords(BlockFace::Top);
+    let (top_tile_x, top_tile_y) = block.atlas_coords(BlockFace::Top);
     let (top_u_min, top_u_max, top_v_min, top_v_max) = atlas_uv_bounds(top_tile_x, top_tile_y);
 
-    let above_amount = world.get_fluid_amount(x, y + 1, z);
+    let above_amount = same_fluid_amount(x, y + 1, z);
     let above_block = world.get_block(x, y + 1, z);
     // Only draw the surface if it is actually exposed.
     if above_amount == 0 && !above_block.occludes() {
@@ -1121,7 +1365,7 @@ fn append_fluid_block(mesh: &mut MeshData, world: &World, x: i32, y: i32, z: i32
         let offset = face.normal();
         let nx = x + offset.x;
         let nz = z + offset.z;
-        let neighbor_amount = world.get_fluid_amount(nx, y, nz);
+        let neighbor_amount = same_fluid_amount(nx, y, nz);
         let neighbor_block = world.get_block(nx, y, nz);
 
         if neighbor_amount >= amount && neighbor_amount > 0 {
@@ -1151,7 +1395,7 @@ fn append_fluid_block(mesh: &mut MeshData, world: &World, x: i32, y: i32, z: i32
             normal_vec.y as f32,
             normal_vec.z as f32,
         ];
-        let (tile_x, tile_y) = BlockType::Water.atlas_coords(face);
+        let (tile_x, tile_y) = block.atlas_coords(face);
         let (u_min, u_max, v_min, v_max) = atlas_uv_bounds(tile_x, tile_y);
 
         let (p0, p1, p2, p3) = match face {
@@ -1360,7 +1604,16 @@ fn append_electrical_preview(
     let axis = component.default_axis();
     let connectors = component.connectors(axis, face);
     append_component_mesh(
-        mesh, block, component, origin, face, axis, scale, connectors, [false; 6],
+        mesh,
+        block,
+        component,
+        origin,
+        face,
+        axis,
+        scale,
+        connectors,
+        [false; 6],
+        ComponentLook::default(),
     );
 }
 
@@ -1389,6 +1642,7 @@ fn append_electrical_attachment(
         1.0,
         connectors,
         connections,
+        ComponentLook::of(node),
     );
 }
 
@@ -1402,6 +1656,7 @@ fn append_component_mesh(
     scale: f32,
     connectors: [bool; 6],
     connections: [bool; 6],
+    look: ComponentLook,
 ) {
     if scale <= 0.0 {
         return;
@@ -1493,6 +1748,74 @@ fn append_component_mesh(
             primary_sign,
             secondary_sign,
         ),
+        ElectricalComponent::Capacitor => append_capacitor_mesh(
+            mesh,
+            material,
+            block_center,
+            block_half,
+            normal,
+            tangent,
+            bitangent,
+            &uvs,
+            scale,
+            primary_lead,
+            primary_sign,
+        ),
+        ElectricalComponent::Switch => append_switch_mesh(
+            mesh,
+            material,
+            block_center,
+            block_half,
+            normal,
+            tangent,
+            bitangent,
+            &uvs,
+            scale,
+            primary_lead,
+            primary_sign,
+            look.closed,
+        ),
+        ElectricalComponent::Led => append_led_mesh(
+            mesh,
+            material,
+            block_center,
+            block_half,
+            normal,
+            tangent,
+            bitangent,
+            &uvs,
+            scale,
+            primary_lead,
+            primary_sign,
+            look.brightness,
+        ),
+        ElectricalComponent::Potentiometer => append_potentiometer_mesh(
+            mesh,
+            material,
+            block_center,
+            block_half,
+            normal,
+            tangent,
+            bitangent,
+            &uvs,
+            scale,
+            primary_lead,
+            primary_sign,
+            look.wiper,
+        ),
+        ElectricalComponent::Diode => append_diode_mesh(
+            mesh,
+            material,
+            block_center,
+            block_half,
+            normal,
+            tangent,
+            bitangent,
+            &uvs,
+            scale,
+            primary_lead,
+            primary_sign,
+        ),
         ElectricalComponent::VoltageSource => append_voltage_source_mesh(
             mesh,
             material,
@@ -2018,7 +2341,8 @@ fn append_resistor_mesh(
     }
 }
 
-fn append_voltage_source_mesh(
+#[allow(clippy::too_many_arguments)]
+fn append_capacitor_mesh(
     mesh: &mut MeshData,
     material: f32,
     block_center: Vector3<f32>,
@@ -2029,16 +2353,14 @@ fn append_voltage_source_mesh(
     uvs: &ComponentUvs,
     scale: f32,
     primary: AxisLead,
-    secondary: AxisLead,
     primary_sign: f32,
-    secondary_sign: f32,
 ) {
     let body_half = [
-        scaled(0.24, scale),
-        scaled(0.16, scale),
-        scaled(0.18, scale),
+        scaled(0.22, scale),
+        scaled(0.14, scale),
+        scaled(0.14, scale),
     ];
-    let body_center = block_center + normal * (block_half + body_half[2] + scaled(0.02, scale));
+    let body_center = block_center + normal * (block_half + body_half[2] + scaled(0.015, scale));
     push_component_box(
         mesh,
         body_center,
@@ -2052,26 +2374,507 @@ fn append_voltage_source_mesh(
         [1.0, 1.0, 1.0],
     );
 
+    // Pale band on the negative end, as printed on electrolytic cans.
     let band_half = [
-        body_half[0] * 0.96,
-        scaled(0.028, scale),
-        body_half[2] * 0.6,
+        scaled(0.03, scale),
+        body_half[1] * 1.03,
+        body_half[2] * 1.03,
     ];
-    let band_center =
-        body_center + bitangent * (body_half[1] - band_half[1] - scaled(0.006, scale));
     push_oriented_box(
         mesh,
-        band_center,
+        body_center - tangent * (primary_sign * (body_half[0] * 0.62)),
         tangent,
         bitangent,
         normal,
         band_half,
         uvs.top_base,
         material,
-        [0.94, 0.95, 0.98],
+        [0.9, 0.94, 1.0],
     );
 
-    let base_half = [body_half[0] * 0.96, scaled(0.03, scale), body_half[2] * 0.5];
+    push_primary_leads(
+        mesh,
+        material,
+        body_center,
+        body_half[0],
+        block_half,
+        (tangent, bitangent, normal),
+        uvs,
+        scale,
+        primary,
+        primary_sign,
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+fn append_switch_mesh(
+    mesh: &mut MeshData,
+    material: f32,
+    block_center: Vector3<f32>,
+    block_half: f32,
+    normal: Vector3<f32>,
+    tangent: Vector3<f32>,
+    bitangent: Vector3<f32>,
+    uvs: &ComponentUvs,
+    scale: f32,
+    primary: AxisLead,
+    primary_sign: f32,
+    closed: bool,
+) {
+    let base_half = [
+        scaled(0.26, scale),
+        scaled(0.13, scale),
+        scaled(0.03, scale),
+    ];
+    let base_center = block_center + normal * (block_half + base_half[2] + scaled(0.01, scale));
+    push_component_box(
+        mesh,
+        base_center,
+        tangent,
+        bitangent,
+        normal,
+        base_half,
+        uvs.side_base,
+        uvs.top_base,
+        material,
+        [1.0, 1.0, 1.0],
+    );
+
+    let post_half = [
+        scaled(0.035, scale),
+        scaled(0.05, scale),
+        scaled(0.045, scale),
+    ];
+    let post_offset = base_half[0] - post_half[0] - scaled(0.03, scale);
+    let post_lift = normal * (base_half[2] + post_half[2]);
+    let hinge = base_center - tangent * (primary_sign * post_offset) + post_lift;
+    let jaw = base_center + tangent * (primary_sign * post_offset) + post_lift;
+    for post in [hinge, jaw] {
+        push_oriented_box(
+            mesh,
+            post,
+            tangent,
+            bitangent,
+            normal,
+            post_half,
+            uvs.top_base,
+            material,
+            [0.78, 0.62, 0.3],
+        );
+    }
+
+    // The blade pivots on the hinge post: flat into the jaw when closed, raised when open.
+    let blade_half = [
+        post_offset + scaled(0.02, scale),
+        scaled(0.025, scale),
+        scaled(0.014, scale),
+    ];
+    let lift = if closed { 0.0f32 } else { 55f32.to_radians() };
+    let along = tangent * (primary_sign * lift.cos()) + normal * lift.sin();
+    let up = normal * lift.cos() - tangent * (primary_sign * lift.sin());
+    let blade_tangent = along * primary_sign;
+    let pivot = hinge + normal * (post_half[2] + blade_half[2]);
+    let blade_center = pivot + along * blade_half[0];
+    let blade_uv = if closed {
+        uvs.side_connected
+    } else {
+        uvs.side_unconnected
+    };
+    push_oriented_box(
+        mesh,
+        blade_center,
+        blade_tangent,
+        bitangent,
+        up,
+        blade_half,
+        blade_uv,
+        material,
+        [0.95, 0.74, 0.46],
+    );
+
+    let knob_half = [
+        scaled(0.03, scale),
+        scaled(0.045, scale),
+        scaled(0.03, scale),
+    ];
+    push_oriented_box(
+        mesh,
+        pivot + along * (blade_half[0] * 2.0) + up * (blade_half[2] + knob_half[2]),
+        blade_tangent,
+        bitangent,
+        up,
+        knob_half,
+        uvs.top_base,
+        material,
+        if closed {
+            [0.3, 0.85, 0.35]
+        } else {
+            [0.9, 0.22, 0.18]
+        },
+    );
+
+    push_primary_leads(
+        mesh,
+        material,
+        base_center,
+        base_half[0],
+        block_half,
+        (tangent, bitangent, normal),
+        uvs,
+        scale,
+        primary,
+        primary_sign,
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+fn append_diode_mesh(
+    mesh: &mut MeshData,
+    material: f32,
+    block_center: Vector3<f32>,
+    block_half: f32,
+    normal: Vector3<f32>,
+    tangent: Vector3<f32>,
+    bitangent: Vector3<f32>,
+    uvs: &ComponentUvs,
+    scale: f32,
+    primary: AxisLead,
+    primary_sign: f32,
+) {
+    let body_half = [
+        scaled(0.2, scale),
+        scaled(0.085, scale),
+        scaled(0.085, scale),
+    ];
+    let body_center = block_center + normal * (block_half + body_half[2] + scaled(0.015, scale));
+    push_component_box(
+        mesh,
+        body_center,
+        tangent,
+        bitangent,
+        normal,
+        body_half,
+        uvs.side_base,
+        uvs.top_base,
+        material,
+        [0.35, 0.35, 0.38],
+    );
+
+    // Bright cathode band at the negative end, the way current leaves a conducting diode.
+    let band_half = [
+        scaled(0.035, scale),
+        body_half[1] * 1.06,
+        body_half[2] * 1.06,
+    ];
+    push_oriented_box(
+        mesh,
+        body_center - tangent * (primary_sign * (body_half[0] * 0.7)),
+        tangent,
+        bitangent,
+        normal,
+        band_half,
+        uvs.top_base,
+        material,
+        [1.0, 1.0, 1.05],
+    );
+
+    push_primary_leads(
+        mesh,
+        material,
+        body_center,
+        body_half[0],
+        block_half,
+        (tangent, bitangent, normal),
+        uvs,
+        scale,
+        primary,
+        primary_sign,
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+fn append_potentiometer_mesh(
+    mesh: &mut MeshData,
+    material: f32,
+    block_center: Vector3<f32>,
+    block_half: f32,
+    normal: Vector3<f32>,
+    tangent: Vector3<f32>,
+    bitangent: Vector3<f32>,
+    uvs: &ComponentUvs,
+    scale: f32,
+    primary: AxisLead,
+    primary_sign: f32,
+    wiper: f32,
+) {
+    let housing_half = [
+        scaled(0.22, scale),
+        scaled(0.18, scale),
+        scaled(0.06, scale),
+    ];
+    let housing_center =
+        block_center + normal * (block_half + housing_half[2] + scaled(0.01, scale));
+    push_component_box(
+        mesh,
+        housing_center,
+        tangent,
+        bitangent,
+        normal,
+        housing_half,
+        uvs.side_base,
+        uvs.top_base,
+        material,
+        [0.55, 0.7, 1.0],
+    );
+
+    let knob_half = [
+        scaled(0.11, scale),
+        scaled(0.11, scale),
+        scaled(0.05, scale),
+    ];
+    let knob_center = housing_center + normal * (housing_half[2] + knob_half[2]);
+    push_oriented_box(
+        mesh,
+        knob_center,
+        tangent,
+        bitangent,
+        normal,
+        knob_half,
+        uvs.top_base,
+        material,
+        [0.85, 0.85, 0.88],
+    );
+
+    // The pointer sweeps 270 degrees across the knob face as the wiper travels.
+    let angle = (-135.0f32 + wiper.clamp(0.0, 1.0) * 270.0).to_radians();
+    let pointing = bitangent * angle.cos() + tangent * (primary_sign * angle.sin());
+    let across = normal.cross(pointing);
+    let pointer_half = [
+        knob_half[0] * 0.45,
+        scaled(0.018, scale),
+        scaled(0.012, scale),
+    ];
+    push_oriented_box(
+        mesh,
+        knob_center + pointing * pointer_half[0] + normal * (knob_half[2] + pointer_half[2]),
+        pointing,
+        across,
+        normal,
+        pointer_half,
+        uvs.top_base,
+        material,
+        [0.95, 0.3, 0.2],
+    );
+
+    push_primary_leads(
+        mesh,
+        material,
+        housing_center,
+        housing_half[0],
+        block_half,
+        (tangent, bitangent, normal),
+        uvs,
+        scale,
+        primary,
+        primary_sign,
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+fn append_led_mesh(
+    mesh: &mut MeshData,
+    material: f32,
+    block_center: Vector3<f32>,
+    block_half: f32,
+    normal: Vector3<f32>,
+    tangent: Vector3<f32>,
+    bitangent: Vector3<f32>,
+    uvs: &ComponentUvs,
+    scale: f32,
+    primary: AxisLead,
+    primary_sign: f32,
+    brightness: u8,
+) {
+    let base_half = [
+        scaled(0.16, scale),
+        scaled(0.16, scale),
+        scaled(0.04, scale),
+    ];
+    let base_center = block_center + normal * (block_half + base_half[2] + scaled(0.01, scale));
+    push_component_box(
+        mesh,
+        base_center,
+        tangent,
+        bitangent,
+        normal,
+        base_half,
+        uvs.side_base,
+        uvs.top_base,
+        material,
+        [1.0, 1.0, 1.0],
+    );
+
+    // The epoxy dome runs from a dull red when dark to a hot pink-white at full current.
+    let glow = brightness as f32 / LED_BRIGHTNESS_LEVELS as f32;
+    let dark = [0.36, 0.07, 0.06];
+    let lit = [1.6, 0.55, 0.45];
+    let tint = [
+        dark[0] + (lit[0] - dark[0]) * glow,
+        dark[1] + (lit[1] - dark[1]) * glow,
+        dark[2] + (lit[2] - dark[2]) * glow,
+    ];
+    let dome_half = [
+        scaled(0.1, scale),
+        scaled(0.1, scale),
+        scaled(0.09, scale),
+    ];
+    let dome_center = base_center + normal * (base_half[2] + dome_half[2]);
+    push_oriented_box(
+        mesh,
+        dome_center,
+        tangent,
+        bitangent,
+        normal,
+        dome_half,
+        uvs.top_base,
+        material,
+        tint,
+    );
+    let cap_half = [
+        scaled(0.065, scale),
+        scaled(0.065, scale),
+        scaled(0.035, scale),
+    ];
+    push_oriented_box(
+        mesh,
+        dome_center + normal * (dome_half[2] + cap_half[2]),
+        tangent,
+        bitangent,
+        normal,
+        cap_half,
+        uvs.top_base,
+        material,
+        tint,
+    );
+
+    push_primary_leads(
+        mesh,
+        material,
+        base_center,
+        base_half[0],
+        block_half,
+        (tangent, bitangent, normal),
+        uvs,
+        scale,
+        primary,
+        primary_sign,
+    );
+}
+
+/// Straight leads from a body's ends out to the primary-axis connectors, reaching past the
+/// block edge when a neighbour is connected.
+#[allow(clippy::too_many_arguments)]
+fn push_primary_leads(
+    mesh: &mut MeshData,
+    material: f32,
+    body_center: Vector3<f32>,
+    body_reach: f32,
+    block_half: f32,
+    (tangent, bitangent, normal): (Vector3<f32>, Vector3<f32>, Vector3<f32>),
+    uvs: &ComponentUvs,
+    scale: f32,
+    primary: AxisLead,
+    primary_sign: f32,
+) {
+    let lead_radius = scaled(0.036, scale);
+    let lead_depth = scaled(0.03, scale);
+    let leads = [
+        (primary.forward_present, primary.forward_connected, primary_sign),
+        (primary.backward_present, primary.backward_connected, -primary_sign),
+    ];
+    for (present, connected, sign) in leads {
+        if !present {
+            continue;
+        }
+        let target = connector_target(block_half, connected, scale, 0.05, 0.014);
+        if target <= body_reach + 0.004 {
+            continue;
+        }
+        let lead_length = (target - body_reach).max(0.01);
+        let lead_half = [lead_length * 0.5, lead_radius, lead_depth];
+        let lead_uv = if connected {
+            uvs.side_connected
+        } else {
+            uvs.side_unconnected
+        };
+        push_oriented_box(
+            mesh,
+            body_center + tangent * (sign * (body_reach + lead_half[0])),
+            tangent,
+            bitangent,
+            normal,
+            lead_half,
+            lead_uv,
+            material,
+            [0.8, 0.8, 0.8],
+        );
+    }
+}
+
+fn append_voltage_source_mesh(
+    mesh: &mut MeshData,
+    material: f32,
+    block_center: Vector3<f32>,
+    block_half: f32,
+    normal: Vector3<f32>,
+    tangent: Vector3<f32>,
+    bitangent: Vector3<f32>,
+    uvs: &ComponentUvs,
+    scale: f32,
+    primary: AxisLead,
+    secondary: AxisLead,
+    primary_sign: f32,
+    secondary_sign: f32,
+) {
+    let body_half = [
+        scaled(0.24, scale),
+        scaled(0.16, scale),
+        scaled(0.18, scale),
+    ];
+    let body_center = block_center + normal * (block_half + body_half[2] + scaled(0.02, scale));
+    push_component_box(
+        mesh,
+        body_center,
+        tangent,
+        bitangent,
+        normal,
+        body_half,
+        uvs.side_base,
+        uvs.top_base,
+        material,
+        [1.0, 1.0, 1.0],
+    );
+
+    let band_half = [
+        body_half[0] * 0.96,
+        scaled(0.028, scale),
+        body_half[2] * 0.6,
+    ];
+    let band_center =
+        body_center + bitangent * (body_half[1] - band_half[1] - scaled(0.006, scale));
+    push_oriented_box(
+        mesh,
+        band_center,
+        tangent,
+        bitangent,
+        normal,
+        band_half,
+        uvs.top_base,
+        material,
+        [0.94, 0.95, 0.98],
+    );
+
+    let base_half = [body_half[0] * 0.96, scaled(0.03, scale), body_half[2] * 0.5];
     let base_center =
         body_center - bitangent * (body_half[1] - base_half[1] - scaled(0.004, scale));
     push_oriented_box(
@@ -2621,3 +3424,129 @@ fn safe_ratio(value: f32, extent: f32) -> f32 {
         (value / extent + 1.0) * 0.5
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::Chunk;
+    use crate::world::TEST_SEED;
+
+    #[test]
+    fn lower_foliage_detail_emits_fewer_flower_vertices() {
+        let mut world = World::with_seed(TEST_SEED);
+        let pos = ChunkPos { x: 0, z: 0 };
+        let mut chunk = Chunk::new();
+        chunk.set_block(4, 80, 4, BlockType::FlowerRose);
+        chunk.set_block(6, 80, 6, BlockType::FlowerTulip);
+        world.chunks_mut().insert(pos, chunk);
+
+        let chunk = &world.chunks()[&pos];
+        let counts: Vec<usize> = [FoliageDetail::High, FoliageDetail::Medium, FoliageDetail::Low]
+            .into_iter()
+            .map(|detail| generate_chunk_mesh(&world, pos, chunk, detail).vertices.len())
+            .collect();
+        assert!(counts[0] > counts[1] && counts[1] > counts[2], "{counts:?}");
+        assert_eq!(FoliageDetail::Low.cycle(1), FoliageDetail::High);
+    }
+
+    #[test]
+    fn chunk_mesh_reports_glow_shrooms_as_glow_sources() {
+        let mut world = World::with_seed(TEST_SEED);
+        let pos = ChunkPos { x: 1, z: 0 };
+        let mut chunk = Chunk::new();
+        chunk.set_block(3, 40, 5, BlockType::GlowShroom);
+        chunk.set_block(4, 40, 5, BlockType::FlowerRose);
+        world.chunks_mut().insert(pos, chunk);
+
+        let mesh = generate_chunk_mesh(&world, pos, &world.chunks()[&pos], FoliageDetail::High);
+        assert_eq!(mesh.glow_sources, vec![[CHUNK_SIZE as i32 + 3, 40, 5]]);
+    }
+
+    #[test]
+    fn chunk_mesh_groups_water_as_translucent_and_stone_as_opaque() {
+        let mut world = World::with_seed(TEST_SEED);
+        let pos = ChunkPos { x: 0, z: 0 };
+        let mut chunk = Chunk::new();
+        chunk.set_block(2, 80, 2, BlockType::Stone);
+        chunk.set_fluid(8, 80, 8, MAX_FLUID_LEVEL);
+        world.chunks_mut().insert(pos, chunk);
+
+        let chunk = &world.chunks()[&pos];
+        let mesh = generate_chunk_mesh(&world, pos, chunk, FoliageDetail::High);
+
+        let opaque = mesh.group_indices(MaterialGroup::Opaque);
+        let translucent = mesh.group_indices(MaterialGroup::Translucent);
+        assert!(!opaque.is_empty());
+        assert!(!translucent.is_empty());
+        assert!(opaque
+            .iter()
+            .all(|&i| (mesh.vertices[i as usize].position[0] - 2.0).abs() <= HALF_BLOCK));
+        assert!(translucent
+            .iter()
+            .all(|&i| (mesh.vertices[i as usize].position[0] - 8.0).abs() <= HALF_BLOCK));
+
+        let [opaque_range, cutout_range, translucent_range] = mesh.group_ranges();
+        assert_eq!(opaque_range.end, cutout_range.start);
+        assert_eq!(cutout_range.end, translucent_range.start);
+        assert_eq!(translucent_range.end as usize, mesh.index_count());
+        assert_eq!(&mesh.indices()[translucent_range.start as usize..], translucent);
+    }
+
+    #[test]
+    fn corners_tucked_against_blocks_darken_and_split_along_the_bright_diagonal() {
+        let mut world = World::with_seed(TEST_SEED);
+        let pos = ChunkPos { x: 0, z: 0 };
+        let mut chunk = Chunk::new();
+        chunk.set_block(4, 80, 4, BlockType::Stone);
+        chunk.set_block(5, 81, 5, BlockType::Stone);
+        world.chunks_mut().insert(pos, chunk);
+
+        let chunk = &world.chunks()[&pos];
+        let mesh = generate_chunk_mesh(&world, pos, chunk, FoliageDetail::High);
+        let top: Vec<u32> = (0..mesh.vertices.len() as u32)
+            .filter(|&i| {
+                let vertex = &mesh.vertices[i as usize];
+                vertex.normal == [0.0, 1.0, 0.0]
+                    && vertex.position[1] == 80.5
+                    && (vertex.position[0] - 4.0).abs() <= HALF_BLOCK
+                    && (vertex.position[2] - 4.0).abs() <= HALF_BLOCK
+            })
+            .collect();
+        assert_eq!(top.len(), 4);
+
+        let dark: Vec<u32> = top
+            .iter()
+            .copied()
+            .filter(|&i| mesh.vertices[i as usize].tint[0] < 1.0)
+            .collect();
+        assert_eq!(dark.len(), 1);
+        let corner = mesh.vertices[dark[0] as usize];
+        assert_eq!((corner.position[0], corner.position[2]), (4.5, 4.5));
+        assert_eq!(corner.tint[0], AO_LEVELS[2]);
+        // Off the shared diagonal, the dark corner belongs to a single triangle.
+        let uses = mesh
+            .group_indices(MaterialGroup::Opaque)
+            .iter()
+            .filter(|&&i| i == dark[0])
+            .count();
+        assert_eq!(uses, 1);
+    }
+
+    #[test]
+    fn lava_meshes_as_emissive_and_opaque() {
+        let mut world = World::with_seed(TEST_SEED);
+        let pos = ChunkPos { x: 0, z: 0 };
+        let mut chunk = Chunk::new();
+        chunk.set_fluid_with_distance(8, 80, 8, FluidKind::Lava, MAX_FLUID_LEVEL, 0);
+        world.chunks_mut().insert(pos, chunk);
+
+        let chunk = &world.chunks()[&pos];
+        let mesh = generate_chunk_mesh(&world, pos, chunk, FoliageDetail::High);
+        assert!(mesh.group_indices(MaterialGroup::Translucent).is_empty());
+        let opaque = mesh.group_indices(MaterialGroup::Opaque);
+        assert!(!opaque.is_empty());
+        assert!(opaque
+            .iter()
+            .all(|&i| mesh.vertices[i as usize].material == MATERIAL_EMISSIVE));
+    }
+}
@@ -0,0 +1,190 @@
+use cgmath::{InnerSpace, Point3, Vector3};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use crate::block::{BlockFace, BlockType};
+use crate::mesh::{self, Vertex};
+use crate::texture::atlas_uv_bounds;
+
+/// Live particles kept at once; the oldest are dropped to make room for new bursts.
+pub const MAX_PARTICLES: usize = 512;
+const BREAK_PARTICLES: usize = 24;
+const PLACE_PARTICLES: usize = 8;
+const PARTICLE_GRAVITY: f32 = -14.0;
+const PARTICLE_SIZE: f32 = 0.14;
+/// Share of the tile's width each particle samples, so a chip shows a few texels.
+const PARTICLE_TILE_FRACTION: f32 = 0.25;
+
+/// A chip of a block's texture flying off after an edit.
+#[derive(Clone, Copy, Debug)]
+struct Particle {
+    position: Point3<f32>,
+    velocity: Vector3<f32>,
+    age: f32,
+    lifetime: f32,
+    /// Atlas rectangle as `(u_min, u_max, v_min, v_max)`.
+    uv: (f32, f32, f32, f32),
+    material: f32,
+    light: f32,
+}
+
+pub struct ParticleSystem {
+    particles: Vec<Particle>,
+    rng: SmallRng,
+}
+
+impl ParticleSystem {
+    pub fn new() -> Self {
+        Self {
+            particles: Vec::new(),
+            rng: SmallRng::seed_from_u64(0x5eed),
+        }
+    }
+
+    /// Bursts `block`'s texture out of the whole cell centred on `center`.
+    pub fn emit_break(&mut self, block: BlockType, center: Point3<f32>, light: f32) {
+        let spread = Vector3::new(0.5, 0.5, 0.5);
+        self.emit(block, center, spread, false, 2.5, BREAK_PARTICLES, light);
+    }
+
+    /// A smaller puff just outside the base of a block placed at `center`.
+    pub fn emit_place(&mut self, block: BlockType, center: Point3<f32>, light: f32) {
+        let base = center - Vector3::new(0.0, 0.4, 0.0);
+        let spread = Vector3::new(0.6, 0.05, 0.6);
+        self.emit(block, base, spread, true, 1.2, PLACE_PARTICLES, light);
+    }
+
+    /// Spawns `count` particles within `spread` of `center`; `outside` pushes each one to
+    /// the nearest side of the cell so a solid block doesn't swallow it.
+    #[allow(clippy::too_many_arguments)]
+    fn emit(
+        &mut self,
+        block: BlockType,
+        center: Point3<f32>,
+        spread: Vector3<f32>,
+        outside: bool,
+        speed: f32,
+        count: usize,
+        light: f32,
+    ) {
+        let (tile_x, tile_y) = block.atlas_coords(BlockFace::North);
+        let (u_min, u_max, v_min, v_max) = atlas_uv_bounds(tile_x, tile_y);
+        let (chip_u, chip_v) = (
+            (u_max - u_min) * PARTICLE_TILE_FRACTION,
+            (v_max - v_min) * PARTICLE_TILE_FRACTION,
+        );
+        let material = mesh::material_for_block(block);
+        for _ in 0..count {
+            let mut offset = Vector3::new(
+                self.rng.gen_range(-1.0..=1.0) * spread.x,
+                self.rng.gen_range(-1.0..=1.0) * spread.y,
+                self.rng.gen_range(-1.0..=1.0) * spread.z,
+            );
+            if outside {
+                if offset.x.abs() > offset.z.abs() {
+                    offset.x = spread.x.copysign(offset.x);
+                } else {
+                    offset.z = spread.z.copysign(offset.z);
+                }
+            }
+            let outward = if offset.magnitude2() > 1e-6 {
+                offset.normalize()
+            } else {
+                Vector3::unit_y()
+            };
+            let velocity =
+                (outward + Vector3::unit_y()) * speed * self.rng.gen_range(0.5..1.0);
+            let u = u_min + self.rng.gen_range(0.0..=(u_max - u_min - chip_u));
+            let v = v_min + self.rng.gen_range(0.0..=(v_max - v_min - chip_v));
+            self.particles.push(Particle {
+                position: center + offset,
+                velocity,
+                age: 0.0,
+                lifetime: self.rng.gen_range(0.6..1.1),
+                uv: (u, u + chip_u, v, v + chip_v),
+                material,
+                light,
+            });
+        }
+        if self.particles.len() > MAX_PARTICLES {
+            let excess = self.particles.len() - MAX_PARTICLES;
+            self.particles.drain(..excess);
+        }
+    }
+
+    /// Moves every particle under gravity, resting on cells `is_solid` reports, and drops
+    /// the ones that have lived out their lifetime.
+    pub fn update(&mut self, dt: f32, is_solid: impl Fn(i32, i32, i32) -> bool) {
+        let cell = |p: Point3<f32>| (p.x.round() as i32, p.y.round() as i32, p.z.round() as i32);
+        for particle in &mut self.particles {
+            particle.age += dt;
+            particle.velocity.y += PARTICLE_GRAVITY * dt;
+            let next = particle.position + particle.velocity * dt;
+            let (x, y, z) = cell(next);
+            if is_solid(x, y, z) {
+                // Land on whatever is underneath and skid to a stop.
+                particle.velocity = Vector3::new(0.0, 0.0, 0.0);
+            } else {
+                particle.position = next;
+            }
+        }
+        self.particles.retain(|particle| particle.age < particle.lifetime);
+    }
+
+    /// Camera-facing quads spanned by `right` and `up`, shrinking away over each
+    /// particle's life.
+    pub fn build_mesh(&self, right: Vector3<f32>, up: Vector3<f32>) -> (Vec<Vertex>, Vec<u32>) {
+        let normal = right.cross(up).normalize();
+        let mut vertices = Vec::with_capacity(self.particles.len() * 4);
+        let mut indices = Vec::with_capacity(self.particles.len() * 6);
+        for particle in &self.particles {
+            let remaining = 1.0 - particle.age / particle.lifetime;
+            let half = PARTICLE_SIZE * 0.5 * remaining.clamp(0.0, 1.0);
+            let (u_min, u_max, v_min, v_max) = particle.uv;
+            let corners = [
+                (-half, -half, u_min, v_max),
+                (half, -half, u_max, v_max),
+                (half, half, u_max, v_min),
+                (-half, half, u_min, v_min),
+            ];
+            let base = vertices.len() as u32;
+            for (dx, dy, u, v) in corners {
+                let position = particle.position + right * dx + up * dy;
+                vertices.push(Vertex {
+                    position: position.into(),
+                    normal: normal.into(),
+                    uv: [u, v],
+                    material: particle.material,
+                    tint: [1.0, 1.0, 1.0],
+                    light: particle.light,
+                });
+            }
+            indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+        }
+        (vertices, indices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn particles_fall_land_and_expire_under_the_cap() {
+        let mut system = ParticleSystem::new();
+        for _ in 0..(MAX_PARTICLES / BREAK_PARTICLES + 4) {
+            system.emit_break(BlockType::Stone, Point3::new(0.0, 5.0, 0.0), 15.0);
+        }
+        assert_eq!(system.particles.len(), MAX_PARTICLES);
+
+        let floor = |_: i32, y: i32, _: i32| y <= 0;
+        for _ in 0..30 {
+            system.update(1.0 / 60.0, floor);
+        }
+        assert!(!system.particles.is_empty());
+        assert!(system.particles.iter().all(|particle| particle.position.y > 0.49));
+        for _ in 0..60 {
+            system.update(1.0 / 60.0, floor);
+        }
+        assert_eq!(system.particles.len(), 0, "everything is gone after 1.1 seconds");
+    }
+}
@@ -12,6 +12,10 @@ use std::{
 struct ProfilerInner {
     file: Mutex<File>,
     frame_counter: AtomicU64,
+    /// Sections finished so far in the frame being recorded.
+    recording: Mutex<Vec<(&'static str, Duration)>>,
+    /// Sections of the last complete frame, for the on-screen breakdown.
+    last_frame: Mutex<Vec<(&'static str, Duration)>>,
 }
 
 static PROFILER: OnceLock<Arc<ProfilerInner>> = OnceLock::new();
@@ -33,6 +37,9 @@ impl Drop for SectionGuard {
     fn drop(&mut self) {
         let duration = self.start.elapsed();
         write_line(&self.inner, &self.frame_label, self.label, duration);
+        if let Ok(mut recording) = self.inner.recording.lock() {
+            recording.push((self.label, duration));
+        }
     }
 }
 
@@ -50,11 +57,14 @@ pub fn init_session() -> std::io::Result<()> {
 
     let path = PathBuf::from("debug").join(format!("profile_{timestamp}.csv"));
     let mut file = File::create(path)?;
-    writeln!(file, "frame,section,duration_ms")?;
+    // `value` is milliseconds for timed sections and a plain number for counters.
+    writeln!(file, "frame,section,value")?;
 
     let inner = Arc::new(ProfilerInner {
         file: Mutex::new(file),
         frame_counter: AtomicU64::new(0),
+        recording: Mutex::new(Vec::new()),
+        last_frame: Mutex::new(Vec::new()),
     });
 
     let _ = PROFILER.set(inner);
@@ -62,12 +72,39 @@ pub fn init_session() -> std::io::Result<()> {
 }
 
 pub fn begin_frame() -> Option<FrameCtx> {
-    PROFILER.get().map(|inner| FrameCtx {
+    PROFILER.get().map(|inner| {
+        if let (Ok(mut recording), Ok(mut last)) = (inner.recording.lock(), inner.last_frame.lock())
+        {
+            *last = std::mem::take(&mut *recording);
+        }
+        FrameCtx {
         inner: inner.clone(),
-        frame_index: inner.frame_counter.fetch_add(1, Ordering::Relaxed),
+            frame_index: inner.frame_counter.fetch_add(1, Ordering::Relaxed),
+        }
     })
 }
 
+/// Total time per section label over the last complete frame, longest first. Sections
+/// that ran several times (one per fixed tick, say) are summed. `None` when profiling
+/// never started.
+pub fn last_frame_sections() -> Option<Vec<(&'static str, Duration)>> {
+    let inner = PROFILER.get()?;
+    let last = inner.last_frame.lock().ok()?;
+    Some(summarize(&last))
+}
+
+fn summarize(sections: &[(&'static str, Duration)]) -> Vec<(&'static str, Duration)> {
+    let mut totals: Vec<(&'static str, Duration)> = Vec::new();
+    for &(label, duration) in sections {
+        match totals.iter_mut().find(|(existing, _)| *existing == label) {
+            Some((_, total)) => *total += duration,
+            None => totals.push((label, duration)),
+        }
+    }
+    totals.sort_by_key(|&(_, duration)| std::cmp::Reverse(duration));
+    totals
+}
+
 impl FrameCtx {
     pub fn section(&self, label: &'static str) -> SectionGuard {
         SectionGuard {
@@ -100,6 +137,15 @@ where
     }
 }
 
+/// Records a per-frame counter, such as how many chunks were remeshed, next to the timings.
+pub fn count(ctx: &Option<FrameCtx>, label: &'static str, value: usize) {
+    if let Some(frame) = ctx.as_ref() {
+        if let Ok(mut file) = frame.inner.file.lock() {
+            let _ = writeln!(file, "{},{},{}", frame.frame_index, label, value);
+        }
+    }
+}
+
 pub fn record_background(label: &'static str, duration: Duration) {
     if let Some(inner) = PROFILER.get() {
         write_line(inner, "background", label, duration);
@@ -117,3 +163,18 @@ fn write_line(inner: &ProfilerInner, frame_label: &str, section: &'static str, d
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_sections_are_summed_and_sorted_longest_first() {
+        let ms = Duration::from_millis;
+        let sections = [("fluid_pump", ms(1)), ("mesh_update", ms(3)), ("fluid_pump", ms(4))];
+        assert_eq!(
+            summarize(&sections),
+            vec![("fluid_pump", ms(5)), ("mesh_update", ms(3))]
+        );
+    }
+}
@@ -1,9 +1,27 @@
-use crate::{block::RenderKind, world::World};
+use crate::{
+    block::RenderKind,
+    world::{World, MAX_FLUID_LEVEL},
+};
 use cgmath::{InnerSpace, Point3, Vector3};
 
+#[derive(Clone, Copy, Debug)]
 pub struct RaycastHit {
     pub block_pos: (i32, i32, i32),
     pub normal: Vector3<f32>,
+    /// World-space point where the ray entered the hit cell (or crossed the fluid surface).
+    pub point: Point3<f32>,
+    /// True when the hit is the top surface of a fluid cell rather than a block.
+    pub fluid: bool,
+}
+
+/// Which cells stop the ray.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RaycastMode {
+    /// Solid blocks and electrical components only.
+    Blocks,
+    /// Also stop on fluid cells: at the top surface (their fill height), or at the face the
+    /// ray entered through when that is below the surface.
+    BlocksAndFluids,
 }
 
 /// DDA (Digital Differential Analyzer) voxel traversal raycast
@@ -14,6 +32,16 @@ pub fn raycast(
     origin: Point3<f32>,
     direction: Vector3<f32>,
     max_distance: f32,
+) -> Option<RaycastHit> {
+    raycast_with_mode(world, origin, direction, max_distance, RaycastMode::Blocks)
+}
+
+pub fn raycast_with_mode(
+    world: &World,
+    origin: Point3<f32>,
+    direction: Vector3<f32>,
+    max_distance: f32,
+    mode: RaycastMode,
 ) -> Option<RaycastHit> {
     let direction = direction.normalize();
 
@@ -39,6 +67,8 @@ pub fn raycast(
 
     // Track which face we entered from (for normal calculation)
     let mut normal = Vector3::new(0.0, 0.0, 0.0);
+    // Distance along the ray at which the current voxel was entered
+    let mut t_entered = 0.0_f32;
     // Traverse voxels
     let max_steps = (max_distance * 2.0) as i32; // Safety limit
     for _ in 0..max_steps {
@@ -48,9 +78,45 @@ pub fn raycast(
             return Some(RaycastHit {
                 block_pos: (voxel_x, voxel_y, voxel_z),
                 normal,
+                point: origin + direction * t_entered,
+                fluid: false,
             });
         }
 
+        let amount = if mode == RaycastMode::BlocksAndFluids {
+            world.get_fluid_amount(voxel_x, voxel_y, voxel_z)
+        } else {
+            0
+        };
+        if amount > 0 {
+            // Fluid fills the cell from its bottom face up to amount / MAX_FLUID_LEVEL.
+            let fill = (amount as f32 / MAX_FLUID_LEVEL as f32).min(1.0);
+            let surface_y = voxel_y as f32 - 0.5 + fill;
+            // The starting cell has no entry face, so a submerged camera can see out.
+            let entered_through_face = normal != Vector3::new(0.0, 0.0, 0.0);
+            let entry = origin + direction * t_entered;
+            if entered_through_face && entry.y <= surface_y {
+                return Some(RaycastHit {
+                    block_pos: (voxel_x, voxel_y, voxel_z),
+                    normal,
+                    point: entry,
+                    fluid: true,
+                });
+            }
+            if direction.y < 0.0 {
+                let t_surface = (surface_y - origin.y) / direction.y;
+                let t_exit = t_max_x.min(t_max_y).min(t_max_z);
+                if t_surface >= t_entered && t_surface <= t_exit && t_surface <= max_distance {
+                    return Some(RaycastHit {
+                        block_pos: (voxel_x, voxel_y, voxel_z),
+                        normal: Vector3::new(0.0, 1.0, 0.0),
+                        point: origin + direction * t_surface,
+                        fluid: true,
+                    });
+                }
+            }
+        }
+
         // Step to next voxel along the axis with the smallest t_max
         if t_max_x < t_max_y {
             if t_max_x < t_max_z {
@@ -58,6 +124,7 @@ pub fn raycast(
                     break;
                 }
                 voxel_x += step_x;
+                t_entered = t_max_x;
                 t_max_x += t_delta_x;
                 normal = Vector3::new(-step_x as f32, 0.0, 0.0);
             } else {
@@ -65,6 +132,7 @@ pub fn raycast(
                     break;
                 }
                 voxel_z += step_z;
+                t_entered = t_max_z;
                 t_max_z += t_delta_z;
                 normal = Vector3::new(0.0, 0.0, -step_z as f32);
             }
@@ -73,6 +141,7 @@ pub fn raycast(
                 break;
             }
             voxel_y += step_y;
+            t_entered = t_max_y;
             t_max_y += t_delta_y;
             normal = Vector3::new(0.0, -step_y as f32, 0.0);
         } else {
@@ -80,6 +149,7 @@ pub fn raycast(
                 break;
             }
             voxel_z += step_z;
+            t_entered = t_max_z;
             t_max_z += t_delta_z;
             normal = Vector3::new(0.0, 0.0, -step_z as f32);
         }
@@ -109,7 +179,7 @@ mod tests {
     use super::*;
     use crate::block::BlockType;
     use crate::chunk::{Chunk, CHUNK_SIZE};
-    use crate::world::{ChunkPos, World};
+    use crate::world::{ChunkPos, World, TEST_SEED};
     use cgmath::{point3, vec3};
     use std::collections::HashMap;
 
@@ -130,7 +200,7 @@ mod tests {
 
     #[test]
     fn ray_hits_block_directly_ahead() {
-        let mut world = World::new();
+        let mut world = World::with_seed(TEST_SEED);
         place_block(&mut world, (0, 80, 5), BlockType::Stone);
 
         let origin = point3(0.0, 80.0, 0.0);
@@ -141,7 +211,7 @@ mod tests {
 
     #[test]
     fn tiny_horizontal_bias_does_not_skip_center_block() {
-        let mut world = World::new();
+        let mut world = World::with_seed(TEST_SEED);
         place_block(&mut world, (0, 64, 6), BlockType::Stone);
 
         let origin = point3(0.0, 64.0, 0.0);
@@ -151,4 +221,54 @@ mod tests {
         let hit = raycast(&world, origin, direction, 10.0).expect("should hit front block");
         assert_eq!(hit.block_pos, (0, 64, 6));
     }
+
+    #[test]
+    fn fluid_mode_hits_water_surface_at_fill_height() {
+        let mut world = World::with_seed(TEST_SEED);
+        place_block(&mut world, (2, 70, 2), BlockType::Air);
+        world.set_fluid_amount(2, 70, 2, MAX_FLUID_LEVEL / 2);
+
+        let origin = point3(2.0, 75.0, 2.0);
+        let direction = vec3(0.0, -1.0, 0.0);
+
+        assert!(raycast(&world, origin, direction, 8.0).is_none());
+
+        let hit = raycast_with_mode(
+            &world,
+            origin,
+            direction,
+            8.0,
+            RaycastMode::BlocksAndFluids,
+        )
+        .expect("should hit water surface");
+        assert!(hit.fluid);
+        assert_eq!(hit.block_pos, (2, 70, 2));
+        assert_eq!(hit.normal, vec3(0.0, 1.0, 0.0));
+        assert!((hit.point.y - 70.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn fluid_mode_hits_the_side_of_a_water_column_below_its_surface() {
+        let mut world = World::with_seed(TEST_SEED);
+        for y in 68..=70 {
+            place_block(&mut world, (2, y, 6), BlockType::Air);
+            world.set_fluid_amount(2, y, 6, MAX_FLUID_LEVEL);
+        }
+        place_block(&mut world, (2, 69, 2), BlockType::Air);
+
+        let origin = point3(2.0, 69.0, 2.0);
+        let direction = vec3(0.0, 0.0, 1.0);
+        let hit = raycast_with_mode(
+            &world,
+            origin,
+            direction,
+            8.0,
+            RaycastMode::BlocksAndFluids,
+        )
+        .expect("should hit the side of the column");
+        assert!(hit.fluid);
+        assert_eq!(hit.block_pos, (2, 69, 6));
+        assert_eq!(hit.normal, vec3(0.0, 0.0, -1.0));
+        assert!((hit.point.z - 5.5).abs() < 1e-4);
+    }
 }
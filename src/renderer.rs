@@ -4,32 +4,114 @@ use std::sync::Arc;
 
 use anyhow::Context;
 use cgmath::{InnerSpace, Matrix, SquareMatrix};
-use cgmath::{Matrix4, Quaternion, Rad, Rotation, Rotation3, Vector3, Vector4};
+use cgmath::{Matrix4, Point3, Quaternion, Rad, Rotation, Rotation3, Vector3, Vector4};
 use wgpu::util::DeviceExt;
 use winit::dpi::PhysicalSize;
 use winit::window::Window;
 
-use crate::block::BlockType;
+use crate::block::{BlockFace, BlockType};
 use crate::camera::{Camera, Projection};
 use crate::electric::{ComponentTelemetry, ElectricalComponent};
-use crate::chunk::{CHUNK_HEIGHT, CHUNK_SIZE};
-use crate::mesh::{self, MeshData, Vertex as BlockVertex};
-use crate::texture::TextureAtlas;
-use crate::world::{AtmosphereSample, ChunkPos, World};
+use crate::fluid_system::ActiveFluidCell;
+use crate::hand::HandPose;
+use crate::chunk::CHUNK_SIZE;
+use crate::mesh::{self, FoliageDetail, MeshData, Vertex as BlockVertex};
+use crate::particles::{ParticleSystem, MAX_PARTICLES};
+use crate::texture::{TextureAtlas, TexturePack};
+use crate::world::{AtmosphereSample, ChunkPos, World, WorldEnvironment};
 
 const SHADER_SOURCE: &str = include_str!("shader.wgsl");
 const SKY_SHADER_SOURCE: &str = include_str!("sky.wgsl");
+const CLOUD_SHADER_SOURCE: &str = include_str!("clouds.wgsl");
 const HIGHLIGHT_SHADER_SOURCE: &str = include_str!("highlight.wgsl");
 const UI_SHADER_SOURCE: &str = include_str!("ui_shader.wgsl");
+const DOF_SHADER_SOURCE: &str = include_str!("dof.wgsl");
+const SHADOW_SHADER_SOURCE: &str = include_str!("shadow.wgsl");
 
 const INITIAL_HIGHLIGHT_CAPACITY: usize = 128;
+/// Outline color of a block that is about to break.
+const HIGHLIGHT_BREAKING_COLOR: [f32; 3] = [1.0, 0.45, 0.0];
 const INITIAL_POWER_CAPACITY: usize = 512;
+const INITIAL_GRID_CAPACITY: usize = 1024;
+const BUILD_GRID_COLOR: [f32; 4] = [0.92, 0.96, 1.0, 0.22];
+const INITIAL_FLUID_DEBUG_CAPACITY: usize = 1024;
+/// Fluid debug markers: cells whose amount changed vs. cells only sitting in an active chunk.
+const FLUID_DEBUG_CHANGED_COLOR: [f32; 4] = [1.0, 0.45, 0.2, 0.7];
+const FLUID_DEBUG_SCHEDULED_COLOR: [f32; 4] = [0.3, 0.75, 1.0, 0.35];
+/// Terminal direction gizmos drawn on the inspected component.
+const TERMINAL_POSITIVE_COLOR: [f32; 4] = [1.0, 0.25, 0.2, 0.95];
+const TERMINAL_NEGATIVE_COLOR: [f32; 4] = [0.25, 0.45, 1.0, 0.95];
+const TERMINAL_ARROW_LENGTH: f32 = 0.42;
+const TERMINAL_ARROW_HEAD: f32 = 0.12;
+/// Two arrows of one shaft and four head strokes each.
+const TERMINAL_ARROW_VERTICES: usize = 2 * 5 * 2;
+const MEASURE_LINE_COLOR: [f32; 4] = [1.0, 0.86, 0.3, 0.95];
+const BOX_EDGES: [(usize, usize); 12] = [
+    (0, 1),
+    (1, 2),
+    (2, 3),
+    (3, 0),
+    (4, 5),
+    (5, 6),
+    (6, 7),
+    (7, 4),
+    (0, 4),
+    (1, 5),
+    (2, 6),
+    (3, 7),
+];
 const INITIAL_HAND_VERTEX_CAPACITY: usize = 128;
 const INITIAL_HAND_INDEX_CAPACITY: usize = 192;
+const INITIAL_PREVIEW_VERTEX_CAPACITY: usize = 256;
+const INITIAL_PREVIEW_INDEX_CAPACITY: usize = 384;
 const INITIAL_ENTITY_VERTEX_CAPACITY: usize = 2048;
 const INITIAL_ENTITY_INDEX_CAPACITY: usize = 3072;
 const INITIAL_UI_VERTEX_CAPACITY: usize = 512;
 const INITIAL_UI_INDEX_CAPACITY: usize = 1024;
+const DEFAULT_VIEW_DISTANCE_CHUNKS: i32 = 3;
+const CLOUD_ALTITUDE: f32 = 224.0;
+/// Noise value above which a cloud forms; higher means clearer skies.
+const CLOUD_COVERAGE: f32 = 0.52;
+const CLOUD_FADE_DISTANCE: f32 = 420.0;
+/// Blocks either side of the focus distance that stay fully sharp in photo mode.
+const DOF_SHARP_BAND: f32 = 1.5;
+const DOF_MAX_BLUR_PIXELS: f32 = 10.0;
+/// Blocks either side of the camera covered by the sun's shadow map.
+const SHADOW_HALF_EXTENT: f32 = 48.0;
+/// Blocks toward and away from the sun that can cast or receive shadows.
+const SHADOW_DEPTH_RANGE: f32 = 160.0;
+/// Share of sky and sun light a fully shadowed surface loses at midday.
+const SHADOW_DARKNESS: f32 = 0.45;
+/// Depth offset, in shadow map depth units, before comparing against the map.
+const SHADOW_DEPTH_BIAS: f32 = 0.0004;
+/// Shadow texels the lookup is pushed off each surface along its normal.
+const SHADOW_NORMAL_OFFSET_TEXELS: f32 = 1.5;
+/// Backends tried in order when looking for an adapter; the first that can present wins.
+const ADAPTER_BACKENDS: [(wgpu::Backends, &str); 4] = [
+    (wgpu::Backends::VULKAN, "Vulkan"),
+    (wgpu::Backends::DX12, "DX12"),
+    (wgpu::Backends::METAL, "Metal"),
+    (wgpu::Backends::GL, "GL"),
+];
+const ADAPTER_POWER_PREFERENCES: [wgpu::PowerPreference; 2] = [
+    wgpu::PowerPreference::HighPerformance,
+    wgpu::PowerPreference::LowPower,
+];
+
+/// Returned when no backend/power-preference combination yields an adapter that can
+/// present to the window. Lets callers tell "no GPU" apart from other startup failures.
+#[derive(Debug)]
+pub struct NoGpuAdapter {
+    pub attempts: Vec<String>,
+}
+
+impl std::fmt::Display for NoGpuAdapter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "no compatible GPU adapter found (tried: {})", self.attempts.join("; "))
+    }
+}
+
+impl std::error::Error for NoGpuAdapter {}
 
 #[repr(C)]
 #[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
@@ -51,6 +133,33 @@ impl CameraUniform {
     }
 }
 
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct CloudUniform {
+    inv_view_proj: [[f32; 4]; 4],
+    // [altitude, animation_time, coverage, fade_distance]
+    params: [f32; 4],
+    /// Projects the cloud plane back to depth, so terrain in front of it hides it. Kept
+    /// last: the sky shader reads only the leading `inv_view_proj`.
+    view_proj: [[f32; 4]; 4],
+}
+
+impl CloudUniform {
+    fn new(view_proj: Matrix4<f32>, animation_time: f32) -> Self {
+        let inv_view_proj = view_proj.invert().unwrap_or_else(Matrix4::identity);
+        Self {
+            inv_view_proj: inv_view_proj.into(),
+            params: [
+                CLOUD_ALTITUDE,
+                animation_time,
+                CLOUD_COVERAGE,
+                CLOUD_FADE_DISTANCE,
+            ],
+            view_proj: view_proj.into(),
+        }
+    }
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 struct EnvironmentUniform {
@@ -61,6 +170,68 @@ struct EnvironmentUniform {
     fog_params: [f32; 4],
     time_params: [f32; 4],
     screen_params: [f32; 4],
+    fog_range: [f32; 4],
+    /// Unit vector toward the light that shades block faces.
+    light_direction: [f32; 4],
+    /// x: animation time in seconds, which scrolls the water texture.
+    animation: [f32; 4],
+}
+
+/// Most point lights the world shader loops over; the caller keeps the nearest.
+pub const MAX_POINT_LIGHTS: usize = 16;
+
+/// A light the world shader adds on top of block and sun light, such as a lit LED.
+#[derive(Clone, Copy, Debug)]
+pub struct PointLight {
+    pub position: Vector3<f32>,
+    pub color: [f32; 3],
+    pub intensity: f32,
+    /// Distance at which the light has faded out completely.
+    pub radius: f32,
+}
+
+/// Where a powered component's overlay is drawn, what it is and what it carries.
+#[derive(Clone, Copy, Debug)]
+pub struct PowerOverlay {
+    pub position: Vector3<f32>,
+    pub component: ElectricalComponent,
+    pub telemetry: ComponentTelemetry,
+    /// It tripped its network by carrying more than its rating; drawn flashing red.
+    pub overloaded: bool,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct PointLightUniform {
+    /// x: number of lights in use.
+    count: [u32; 4],
+    /// xyz: world position, w: radius.
+    position: [[f32; 4]; MAX_POINT_LIGHTS],
+    /// rgb: colour premultiplied by intensity.
+    color: [[f32; 4]; MAX_POINT_LIGHTS],
+}
+
+impl PointLightUniform {
+    fn from_lights(lights: &[PointLight]) -> Self {
+        let mut uniform: Self = bytemuck::Zeroable::zeroed();
+        let count = lights.len().min(MAX_POINT_LIGHTS);
+        uniform.count[0] = count as u32;
+        for (index, light) in lights.iter().take(count).enumerate() {
+            uniform.position[index] = [
+                light.position.x,
+                light.position.y,
+                light.position.z,
+                light.radius.max(0.01),
+            ];
+            uniform.color[index] = [
+                light.color[0] * light.intensity,
+                light.color[1] * light.intensity,
+                light.color[2] * light.intensity,
+                1.0,
+            ];
+        }
+        uniform
+    }
 }
 
 impl EnvironmentUniform {
@@ -73,6 +244,9 @@ impl EnvironmentUniform {
             fog_params: [0.0; 4],
             time_params: [0.0; 4],
             screen_params: [0.0; 4],
+            fog_range: [0.0; 4],
+            light_direction: [0.0, 1.0, 0.0, 0.0],
+            animation: [0.0; 4],
         }
     }
 
@@ -80,6 +254,9 @@ impl EnvironmentUniform {
         sample: &AtmosphereSample,
         camera_pos: [f32; 3],
         size: PhysicalSize<u32>,
+        fog_range: (f32, f32),
+        light_direction: [f32; 3],
+        animation_time: f32,
     ) -> Self {
         let mut uniform = Self::new();
         uniform.sky_zenith = [
@@ -117,6 +294,14 @@ impl EnvironmentUniform {
         let width = size.width.max(1) as f32;
         let height = size.height.max(1) as f32;
         uniform.screen_params = [width, height, 1.0 / width, 1.0 / height];
+        uniform.fog_range = [fog_range.0, fog_range.1, 0.0, 0.0];
+        uniform.light_direction = [
+            light_direction[0],
+            light_direction[1],
+            light_direction[2],
+            0.0,
+        ];
+        uniform.animation = [animation_time, 0.0, 0.0, 0.0];
         uniform
     }
 }
@@ -140,7 +325,8 @@ impl DepthTexture {
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format: Self::FORMAT,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            // Sampled by the depth-of-field pass to find how far each pixel is from focus.
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
             view_formats: &[],
         });
 
@@ -149,6 +335,484 @@ impl DepthTexture {
     }
 }
 
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct DepthOfFieldUniform {
+    // [focus_distance, sharp_band, max_blur_pixels, 0]
+    focus: [f32; 4],
+    // [znear, zfar, 1 / width, 1 / height]
+    clip: [f32; 4],
+}
+
+impl DepthOfFieldUniform {
+    fn new(focus: f32, (znear, zfar): (f32, f32), config: &wgpu::SurfaceConfiguration) -> Self {
+        Self {
+            focus: [focus, DOF_SHARP_BAND, DOF_MAX_BLUR_PIXELS, 0.0],
+            clip: [
+                znear,
+                zfar,
+                1.0 / config.width.max(1) as f32,
+                1.0 / config.height.max(1) as f32,
+            ],
+        }
+    }
+}
+
+/// Post pass for photo mode: the world is drawn into `scene_view`, then blurred onto the
+/// surface by how far each pixel's depth sits from the focus distance.
+struct DepthOfField {
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    uniform_buffer: wgpu::Buffer,
+    scene_view: wgpu::TextureView,
+    bind_group: wgpu::BindGroup,
+    focus: Option<f32>,
+}
+
+impl DepthOfField {
+    fn new(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        depth: &DepthTexture,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("dof_shader"),
+            source: wgpu::ShaderSource::Wgsl(DOF_SHADER_SOURCE.into()),
+        });
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("dof_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Depth,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("dof_pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("dof_pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: config.format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("dof_sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("dof_uniform_buffer"),
+            size: mem::size_of::<DepthOfFieldUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let (scene_view, bind_group) = Self::create_targets(
+            device,
+            config,
+            depth,
+            &bind_group_layout,
+            &sampler,
+            &uniform_buffer,
+        );
+        Self {
+            pipeline,
+            bind_group_layout,
+            sampler,
+            uniform_buffer,
+            scene_view,
+            bind_group,
+            focus: None,
+        }
+    }
+
+    fn create_targets(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        depth: &DepthTexture,
+        layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+        uniform_buffer: &wgpu::Buffer,
+    ) -> (wgpu::TextureView, wgpu::BindGroup) {
+        let scene = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("dof_scene_texture"),
+            size: wgpu::Extent3d {
+                width: config.width,
+                height: config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let scene_view = scene.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("dof_bind_group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&scene_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(&depth.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+            ],
+        });
+        (scene_view, bind_group)
+    }
+
+    fn resize(
+        &mut self,
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        depth: &DepthTexture,
+    ) {
+        (self.scene_view, self.bind_group) = Self::create_targets(
+            device,
+            config,
+            depth,
+            &self.bind_group_layout,
+            &self.sampler,
+            &self.uniform_buffer,
+        );
+    }
+}
+
+/// Resolution of the sun's shadow map; `Off` skips the shadow pass altogether.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ShadowQuality {
+    Off,
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl ShadowQuality {
+    const ALL: [Self; 4] = [Self::Off, Self::Low, Self::Medium, Self::High];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Off => "OFF",
+            Self::Low => "LOW",
+            Self::Medium => "MEDIUM",
+            Self::High => "HIGH",
+        }
+    }
+
+    pub fn cycle(self, step: i32) -> Self {
+        let index = Self::ALL.iter().position(|&quality| quality == self).unwrap_or(0) as i32;
+        Self::ALL[(index + step).rem_euclid(Self::ALL.len() as i32) as usize]
+    }
+
+    fn resolution(self) -> Option<u32> {
+        match self {
+            Self::Off => None,
+            Self::Low => Some(1024),
+            Self::Medium => Some(2048),
+            Self::High => Some(4096),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct ShadowUniform {
+    light_view_proj: [[f32; 4]; 4],
+    // [strength, 1 / resolution, depth_bias, normal_offset]
+    params: [f32; 4],
+}
+
+impl ShadowUniform {
+    fn new(light_view_proj: Matrix4<f32>, strength: f32, resolution: u32) -> Self {
+        let texel_blocks = 2.0 * SHADOW_HALF_EXTENT / resolution as f32;
+        Self {
+            light_view_proj: light_view_proj.into(),
+            params: [
+                strength,
+                1.0 / resolution as f32,
+                SHADOW_DEPTH_BIAS,
+                texel_blocks * SHADOW_NORMAL_OFFSET_TEXELS,
+            ],
+        }
+    }
+}
+
+/// Orthographic view from `light_direction` onto the area around `center`, with depth in
+/// wgpu's 0..1 range. The area snaps to whole shadow texels so shadow edges stay put
+/// while the camera moves.
+fn shadow_view_proj(
+    center: Vector3<f32>,
+    light_direction: [f32; 3],
+    resolution: u32,
+) -> Matrix4<f32> {
+    let direction = Vector3::from(light_direction).normalize();
+    let up = if direction.y.abs() > 0.99 {
+        Vector3::unit_z()
+    } else {
+        Vector3::unit_y()
+    };
+    let view = Matrix4::look_to_rh(Point3::new(0.0, 0.0, 0.0), -direction, up);
+    let light_center = view * center.extend(1.0);
+    let texel = 2.0 * SHADOW_HALF_EXTENT / resolution as f32;
+    let x = (light_center.x / texel).round() * texel;
+    let y = (light_center.y / texel).round() * texel;
+    // The view looks down -Z, so the centre sits at depth -z.
+    let depth = -light_center.z;
+    let projection = cgmath::ortho(
+        x - SHADOW_HALF_EXTENT,
+        x + SHADOW_HALF_EXTENT,
+        y - SHADOW_HALF_EXTENT,
+        y + SHADOW_HALF_EXTENT,
+        depth - SHADOW_DEPTH_RANGE,
+        depth + SHADOW_DEPTH_RANGE,
+    );
+    #[rustfmt::skip]
+    let opengl_to_wgpu = Matrix4::new(
+        1.0, 0.0, 0.0, 0.0,
+        0.0, 1.0, 0.0, 0.0,
+        0.0, 0.0, 0.5, 0.0,
+        0.0, 0.0, 0.5, 1.0,
+    );
+    opengl_to_wgpu * projection * view
+}
+
+/// Depth map of the world as seen from the sun, sampled by the world shader to darken
+/// whatever sits behind something else from the sun's point of view.
+struct ShadowMap {
+    pipeline: wgpu::RenderPipeline,
+    uniform_buffer: wgpu::Buffer,
+    /// Group 0 of the shadow pass: just the light matrix.
+    light_bind_group: wgpu::BindGroup,
+    sampler: wgpu::Sampler,
+    view: wgpu::TextureView,
+    resolution: u32,
+    quality: ShadowQuality,
+    light_view_proj: Matrix4<f32>,
+    /// False while shadows are off or the sun is too low to cast them.
+    active: bool,
+}
+
+impl ShadowMap {
+    const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+    fn new(
+        device: &wgpu::Device,
+        atlas_layout: &wgpu::BindGroupLayout,
+        quality: ShadowQuality,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("shadow_shader"),
+            source: wgpu::ShaderSource::Wgsl(SHADOW_SHADER_SOURCE.into()),
+        });
+        let light_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("shadow_light_bind_group_layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("shadow_pipeline_layout"),
+            bind_group_layouts: &[&light_layout, atlas_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("shadow_pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[block_vertex_layout()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[],
+            }),
+            // Both sides cast, so single-quad foliage and thin blocks still throw a shadow.
+            primitive: wgpu::PrimitiveState {
+                cull_mode: None,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: Self::FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState {
+                    constant: 2,
+                    slope_scale: 2.0,
+                    clamp: 0.0,
+                },
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("shadow_sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            compare: Some(wgpu::CompareFunction::LessEqual),
+            ..Default::default()
+        });
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("shadow_uniform_buffer"),
+            contents: bytemuck::bytes_of(&ShadowUniform::new(Matrix4::identity(), 0.0, 1)),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let light_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("shadow_light_bind_group"),
+            layout: &light_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+        // With shadows off the world shader still needs something bound, so a single
+        // texel stands in until a real resolution is picked.
+        let resolution = quality.resolution().unwrap_or(1);
+        Self {
+            pipeline,
+            uniform_buffer,
+            light_bind_group,
+            sampler,
+            view: Self::create_view(device, resolution),
+            resolution,
+            quality,
+            light_view_proj: Matrix4::identity(),
+            active: false,
+        }
+    }
+
+    fn create_view(device: &wgpu::Device, resolution: u32) -> wgpu::TextureView {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("shadow_map_texture"),
+            size: wgpu::Extent3d {
+                width: resolution,
+                height: resolution,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        texture.create_view(&wgpu::TextureViewDescriptor::default())
+    }
+}
+
+/// Group 3 of the world pipeline: point lights plus the sun's shadow map.
+fn create_point_light_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    point_light_buffer: &wgpu::Buffer,
+    shadow_map: &ShadowMap,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("point_light_bind_group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: point_light_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: shadow_map.uniform_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: wgpu::BindingResource::TextureView(&shadow_map.view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 3,
+                resource: wgpu::BindingResource::Sampler(&shadow_map.sampler),
+            },
+        ],
+    })
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 struct HighlightVertex {
@@ -165,12 +829,35 @@ pub struct UiVertex {
     pub mode: f32,
 }
 
+/// GPU-side counters for the debug overlay.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RendererStats {
+    pub chunk_meshes: usize,
+    pub vertices: u64,
+    pub indices: u64,
+    pub chunk_buffer_bytes: u64,
+    /// Chunk vertex/index buffers created since startup; flat while remeshes reuse capacity.
+    pub chunk_buffer_allocations: u64,
+    pub draw_calls: u32,
+    /// Chunks regenerated by the most recent mesh update.
+    pub chunks_remeshed: usize,
+}
+
+/// Per-chunk GPU buffers. They are sized to a power of two and rewritten in
+/// place until a remesh outgrows them.
 struct ChunkGpuMesh {
     vertex_buffer: wgpu::Buffer,
     index_buffer: wgpu::Buffer,
+    vertex_capacity: usize,
+    index_capacity: usize,
+    vertex_count: u32,
     index_count: u32,
+    /// Indices before this are opaque or cutout; the rest are translucent.
+    translucent_start: u32,
     bounds_min: [f32; 3],
     bounds_max: [f32; 3],
+    /// Glowing blocks from the last mesh of this chunk.
+    glow_sources: Vec<[i32; 3]>,
 }
 
 #[derive(Clone, Copy)]
@@ -239,6 +926,8 @@ pub struct Renderer<'window> {
     queue: Arc<wgpu::Queue>,
     config: wgpu::SurfaceConfiguration,
     depth_texture: DepthTexture,
+    depth_of_field: DepthOfField,
+    depth_planes: (f32, f32),
     texture_atlas: TextureAtlas,
     camera_buffer: wgpu::Buffer,
     camera_bind_group: wgpu::BindGroup,
@@ -246,20 +935,50 @@ pub struct Renderer<'window> {
     environment_buffer: wgpu::Buffer,
     environment_bind_group: wgpu::BindGroup,
     _environment_bind_group_layout: wgpu::BindGroupLayout,
+    point_light_buffer: wgpu::Buffer,
+    point_light_bind_group: wgpu::BindGroup,
+    point_light_bind_group_layout: wgpu::BindGroupLayout,
+    shadow_map: ShadowMap,
     render_pipeline: wgpu::RenderPipeline,
+    /// `None` when the GPU can't rasterize polygons as lines.
+    wireframe_pipeline: Option<wgpu::RenderPipeline>,
+    wireframe: bool,
     sky_pipeline: wgpu::RenderPipeline,
+    cloud_pipeline: wgpu::RenderPipeline,
+    cloud_buffer: wgpu::Buffer,
+    cloud_bind_group: wgpu::BindGroup,
+    clouds_enabled: bool,
     highlight_pipeline: wgpu::RenderPipeline,
+    electrical_xray_pipeline: wgpu::RenderPipeline,
+    electrical_xray: bool,
+    foliage_detail: FoliageDetail,
+    fixed_light_direction: Option<[f32; 3]>,
     ui_pipeline: wgpu::RenderPipeline,
     chunk_meshes: HashMap<ChunkPos, ChunkGpuMesh>,
+    chunks_remeshed: usize,
     last_view_proj: Matrix4<f32>,
     highlight_vertex_buffer: wgpu::Buffer,
     highlight_vertex_capacity: usize,
     highlight_vertex_count: u32,
     highlight_vertices: Vec<HighlightVertex>,
+    /// The outline is around an electrical component, so x-ray draws it through terrain.
+    highlight_electrical: bool,
     power_vertex_buffer: wgpu::Buffer,
     power_vertex_capacity: usize,
     power_vertex_count: u32,
     power_vertices: Vec<HighlightVertex>,
+    grid_vertex_buffer: wgpu::Buffer,
+    grid_vertex_capacity: usize,
+    grid_vertex_count: u32,
+    grid_vertices: Vec<HighlightVertex>,
+    fluid_debug_vertex_buffer: wgpu::Buffer,
+    fluid_debug_vertex_capacity: usize,
+    fluid_debug_vertex_count: u32,
+    fluid_debug_vertices: Vec<HighlightVertex>,
+    terminal_arrow_buffer: wgpu::Buffer,
+    terminal_arrow_vertex_count: u32,
+    measure_line_buffer: wgpu::Buffer,
+    measure_line_vertex_count: u32,
     hand_vertex_buffer: wgpu::Buffer,
     hand_index_buffer: wgpu::Buffer,
     hand_vertex_capacity: usize,
@@ -270,6 +989,15 @@ pub struct Renderer<'window> {
     entity_vertex_capacity: usize,
     entity_index_capacity: usize,
     entity_index_count: u32,
+    particles: ParticleSystem,
+    particle_vertex_buffer: wgpu::Buffer,
+    particle_index_buffer: wgpu::Buffer,
+    particle_index_count: u32,
+    preview_vertex_buffer: wgpu::Buffer,
+    preview_index_buffer: wgpu::Buffer,
+    preview_vertex_capacity: usize,
+    preview_index_capacity: usize,
+    preview_index_count: u32,
     ui_vertex_buffer: wgpu::Buffer,
     ui_index_buffer: wgpu::Buffer,
     ui_vertex_capacity: usize,
@@ -278,6 +1006,9 @@ pub struct Renderer<'window> {
     ui_vertices: Vec<UiVertex>,
     ui_indices: Vec<u16>,
     clear_color: [f32; 4],
+    edge_fog_range: (f32, f32),
+    last_draw_calls: u32,
+    chunk_buffer_allocations: u64,
 }
 
 impl<'window> Renderer<'window> {
@@ -285,37 +1016,71 @@ impl<'window> Renderer<'window> {
         pollster::block_on(Self::new_async(window))
     }
 
+    /// Walks `ADAPTER_BACKENDS` x `ADAPTER_POWER_PREFERENCES` until an adapter compatible
+    /// with the window surface turns up, logging each failed attempt.
+    async fn request_adapter(
+        window: &'window Window,
+    ) -> anyhow::Result<(wgpu::Surface<'window>, wgpu::Adapter)> {
+        let mut attempts = Vec::new();
+        for (backends, backend_name) in ADAPTER_BACKENDS {
+            let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+                backends,
+                flags: wgpu::InstanceFlags::default(),
+                dx12_shader_compiler: Default::default(),
+                gles_minor_version: wgpu::Gles3MinorVersion::Automatic,
+            });
+            let surface = match instance.create_surface(window) {
+                Ok(surface) => surface,
+                Err(err) => {
+                    eprintln!("GPU: {backend_name} could not create a surface: {err}");
+                    attempts.push(format!("{backend_name}: surface failed ({err})"));
+                    continue;
+                }
+            };
+            for power_preference in ADAPTER_POWER_PREFERENCES {
+                let adapter = instance
+                    .request_adapter(&wgpu::RequestAdapterOptions {
+                        power_preference,
+                        compatible_surface: Some(&surface),
+                        force_fallback_adapter: false,
+                    })
+                    .await;
+                match adapter {
+                    Some(adapter) => {
+                        let info = adapter.get_info();
+                        println!(
+                            "GPU: using {} via {backend_name} ({power_preference:?})",
+                            info.name
+                        );
+                        return Ok((surface, adapter));
+                    }
+                    None => {
+                        eprintln!("GPU: no {backend_name} adapter for {power_preference:?}");
+                        attempts.push(format!("{backend_name}/{power_preference:?}"));
+                    }
+                }
+            }
+        }
+        Err(NoGpuAdapter { attempts }.into())
+    }
+
     async fn new_async(window: &'window Window) -> anyhow::Result<Self> {
         let size = window.inner_size();
 
-        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::all(),
-            flags: wgpu::InstanceFlags::default(),
-            dx12_shader_compiler: Default::default(),
-            gles_minor_version: wgpu::Gles3MinorVersion::Automatic,
-        });
-
-        let surface = instance.create_surface(window)?;
-
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::HighPerformance,
-                compatible_surface: Some(&surface),
-                force_fallback_adapter: false,
-            })
-            .await
-            .context("failed to find a suitable GPU adapter")?;
+        let (surface, adapter) = Self::request_adapter(window).await?;
 
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
                     label: Some("renderer_device"),
-                    required_features: wgpu::Features::empty(),
+                    // Line rasterization backs the wireframe debug view where the GPU has it.
+                    required_features: adapter.features() & wgpu::Features::POLYGON_MODE_LINE,
                     required_limits: wgpu::Limits::default(),
                 },
                 None,
             )
-            .await?;
+            .await
+            .context("failed to create GPU device")?;
 
         let device = Arc::new(device);
         let queue = Arc::new(queue);
@@ -357,7 +1122,8 @@ impl<'window> Renderer<'window> {
         };
         surface.configure(device.as_ref(), &config);
 
-        let texture_atlas = TextureAtlas::new(device.as_ref(), queue.as_ref());
+        let texture_atlas =
+            TextureAtlas::new(device.as_ref(), queue.as_ref(), &TexturePack::Default);
 
         let camera_bind_group_layout =
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
@@ -419,6 +1185,67 @@ impl<'window> Renderer<'window> {
             }],
         });
 
+        let point_light_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("point_light_bind_group_layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            multisampled: false,
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            sample_type: wgpu::TextureSampleType::Depth,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 3,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison),
+                        count: None,
+                    },
+                ],
+            });
+
+        let point_light_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("point_light_buffer"),
+            contents: bytemuck::bytes_of(&PointLightUniform::from_lights(&[])),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let shadow_map = ShadowMap::new(
+            device.as_ref(),
+            &texture_atlas.bind_group_layout,
+            ShadowQuality::default(),
+        );
+        let point_light_bind_group = create_point_light_bind_group(
+            device.as_ref(),
+            &point_light_bind_group_layout,
+            &point_light_buffer,
+            &shadow_map,
+        );
+
         let world_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("world_shader"),
             source: wgpu::ShaderSource::Wgsl(SHADER_SOURCE.into()),
@@ -427,6 +1254,10 @@ impl<'window> Renderer<'window> {
             label: Some("sky_shader"),
             source: wgpu::ShaderSource::Wgsl(SKY_SHADER_SOURCE.into()),
         });
+        let cloud_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("cloud_shader"),
+            source: wgpu::ShaderSource::Wgsl(CLOUD_SHADER_SOURCE.into()),
+        });
         let highlight_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("highlight_shader"),
             source: wgpu::ShaderSource::Wgsl(HIGHLIGHT_SHADER_SOURCE.into()),
@@ -443,50 +1274,67 @@ impl<'window> Renderer<'window> {
                     &camera_bind_group_layout,
                     &texture_atlas.bind_group_layout,
                     &environment_bind_group_layout,
+                    &point_light_bind_group_layout,
                 ],
                 push_constant_ranges: &[],
             });
 
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("world_pipeline"),
-            layout: Some(&world_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &world_shader,
-                entry_point: "vs_main",
-                buffers: &[block_vertex_layout()],
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &world_shader,
-                entry_point: "fs_main",
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: config.format,
-                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
-                unclipped_depth: false,
-                polygon_mode: wgpu::PolygonMode::Fill,
-                conservative: false,
-            },
-            depth_stencil: Some(wgpu::DepthStencilState {
-                format: DepthTexture::FORMAT,
-                depth_write_enabled: true,
-                depth_compare: wgpu::CompareFunction::LessEqual,
-                stencil: wgpu::StencilState::default(),
-                bias: wgpu::DepthBiasState::default(),
-            }),
-            multisample: wgpu::MultisampleState::default(),
-            multiview: None,
-        });
+        let world_pipeline = |label, cull_mode, polygon_mode| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&world_pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &world_shader,
+                    entry_point: "vs_main",
+                    buffers: &[block_vertex_layout()],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &world_shader,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: config.format,
+                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode,
+                    unclipped_depth: false,
+                    polygon_mode,
+                    conservative: false,
+                },
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: DepthTexture::FORMAT,
+                    depth_write_enabled: true,
+                    depth_compare: wgpu::CompareFunction::LessEqual,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+            })
+        };
+        let render_pipeline = world_pipeline(
+            "world_pipeline",
+            Some(wgpu::Face::Back),
+            wgpu::PolygonMode::Fill,
+        );
+        // Back faces stay in so the edges behind a surface show through it.
+        let wireframe_pipeline = device
+            .features()
+            .contains(wgpu::Features::POLYGON_MODE_LINE)
+            .then(|| world_pipeline("wireframe_pipeline", None, wgpu::PolygonMode::Line));
 
         let sky_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("sky_pipeline_layout"),
-            bind_group_layouts: &[&environment_bind_group_layout],
+            // Group 1 is the cloud uniform, read for its inverse view-projection.
+            bind_group_layouts: &[
+                &environment_bind_group_layout,
+                &environment_bind_group_layout,
+            ],
             push_constant_ranges: &[],
         });
 
@@ -527,23 +1375,39 @@ impl<'window> Renderer<'window> {
             multiview: None,
         });
 
-        let highlight_pipeline_layout =
+        // The cloud uniform is a single buffer binding, so it shares the environment layout.
+        let cloud_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("cloud_buffer"),
+            contents: bytemuck::bytes_of(&CloudUniform::new(Matrix4::identity(), 0.0)),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let cloud_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("cloud_bind_group"),
+            layout: &environment_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: cloud_buffer.as_entire_binding(),
+            }],
+        });
+        let cloud_pipeline_layout =
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                label: Some("highlight_pipeline_layout"),
-                bind_group_layouts: &[&camera_bind_group_layout],
+                label: Some("cloud_pipeline_layout"),
+                bind_group_layouts: &[
+                    &environment_bind_group_layout,
+                    &environment_bind_group_layout,
+                ],
                 push_constant_ranges: &[],
             });
-
-        let highlight_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("highlight_pipeline"),
-            layout: Some(&highlight_pipeline_layout),
+        let cloud_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("cloud_pipeline"),
+            layout: Some(&cloud_pipeline_layout),
             vertex: wgpu::VertexState {
-                module: &highlight_shader,
+                module: &cloud_shader,
                 entry_point: "vs_main",
-                buffers: &[highlight_vertex_layout()],
+                buffers: &[],
             },
             fragment: Some(wgpu::FragmentState {
-                module: &highlight_shader,
+                module: &cloud_shader,
                 entry_point: "fs_main",
                 targets: &[Some(wgpu::ColorTargetState {
                     format: config.format,
@@ -552,7 +1416,7 @@ impl<'window> Renderer<'window> {
                 })],
             }),
             primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::LineList,
+                topology: wgpu::PrimitiveTopology::TriangleList,
                 strip_index_format: None,
                 front_face: wgpu::FrontFace::Ccw,
                 cull_mode: None,
@@ -560,6 +1424,7 @@ impl<'window> Renderer<'window> {
                 polygon_mode: wgpu::PolygonMode::Fill,
                 conservative: false,
             },
+            // The shader writes the plane's depth, so opaque terrain nearer than it wins.
             depth_stencil: Some(wgpu::DepthStencilState {
                 format: DepthTexture::FORMAT,
                 depth_write_enabled: false,
@@ -571,6 +1436,31 @@ impl<'window> Renderer<'window> {
             multiview: None,
         });
 
+        let highlight_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("highlight_pipeline_layout"),
+                bind_group_layouts: &[&camera_bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let highlight_pipeline = line_overlay_pipeline(
+            &device,
+            "highlight_pipeline",
+            &highlight_pipeline_layout,
+            &highlight_shader,
+            config.format,
+            wgpu::CompareFunction::LessEqual,
+        );
+        // Same lines with the depth test off, so electrical overlays show through terrain.
+        let electrical_xray_pipeline = line_overlay_pipeline(
+            &device,
+            "electrical_xray_pipeline",
+            &highlight_pipeline_layout,
+            &highlight_shader,
+            config.format,
+            wgpu::CompareFunction::Always,
+        );
+
         let ui_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("ui_pipeline_layout"),
             bind_group_layouts: &[&texture_atlas.bind_group_layout],
@@ -620,6 +1510,43 @@ impl<'window> Renderer<'window> {
             usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
+        let grid_vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("grid_vertex_buffer"),
+            size: (INITIAL_GRID_CAPACITY.max(1) * mem::size_of::<HighlightVertex>()) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let fluid_debug_vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("fluid_debug_vertex_buffer"),
+            size: (INITIAL_FLUID_DEBUG_CAPACITY * mem::size_of::<HighlightVertex>()) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let terminal_arrow_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("terminal_arrow_buffer"),
+            size: (TERMINAL_ARROW_VERTICES * mem::size_of::<HighlightVertex>()) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let measure_line_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("measure_line_buffer"),
+            size: (2 * mem::size_of::<HighlightVertex>()) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let preview_vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("preview_vertex_buffer"),
+            size: (INITIAL_PREVIEW_VERTEX_CAPACITY * mem::size_of::<BlockVertex>()) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let preview_index_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("preview_index_buffer"),
+            size: (INITIAL_PREVIEW_INDEX_CAPACITY * mem::size_of::<u32>()) as u64,
+            usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
 
         let hand_vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("hand_vertex_buffer"),
@@ -640,9 +1567,23 @@ impl<'window> Renderer<'window> {
             usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
-        let entity_index_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("entity_index_buffer"),
-            size: (INITIAL_ENTITY_INDEX_CAPACITY.max(1) * mem::size_of::<u32>()) as u64,
+        let entity_index_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("entity_index_buffer"),
+            size: (INITIAL_ENTITY_INDEX_CAPACITY.max(1) * mem::size_of::<u32>()) as u64,
+            usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        // Particles are capped, so their buffers are sized once for a full set of quads.
+        let particle_vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("particle_vertex_buffer"),
+            size: (MAX_PARTICLES * 4 * mem::size_of::<BlockVertex>()) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let particle_index_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("particle_index_buffer"),
+            size: (MAX_PARTICLES * 6 * mem::size_of::<u32>()) as u64,
             usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
@@ -661,6 +1602,7 @@ impl<'window> Renderer<'window> {
         });
 
         let depth_texture = DepthTexture::create(device.as_ref(), &config);
+        let depth_of_field = DepthOfField::new(device.as_ref(), &config, &depth_texture);
 
         Ok(Self {
             size,
@@ -669,6 +1611,8 @@ impl<'window> Renderer<'window> {
             queue,
             config,
             depth_texture,
+            depth_of_field,
+            depth_planes: (0.1, 1000.0),
             texture_atlas,
             camera_buffer,
             camera_bind_group,
@@ -676,20 +1620,48 @@ impl<'window> Renderer<'window> {
             environment_buffer,
             environment_bind_group,
             _environment_bind_group_layout: environment_bind_group_layout,
+            point_light_buffer,
+            point_light_bind_group,
+            point_light_bind_group_layout,
+            shadow_map,
             render_pipeline,
+            wireframe_pipeline,
+            wireframe: false,
             sky_pipeline,
+            cloud_pipeline,
+            cloud_buffer,
+            cloud_bind_group,
+            clouds_enabled: true,
             highlight_pipeline,
+            electrical_xray_pipeline,
+            electrical_xray: false,
+            foliage_detail: FoliageDetail::default(),
+            fixed_light_direction: None,
             ui_pipeline,
             chunk_meshes: HashMap::new(),
+            chunks_remeshed: 0,
             last_view_proj: Matrix4::identity(),
             highlight_vertex_buffer,
             highlight_vertex_capacity: INITIAL_HIGHLIGHT_CAPACITY.max(1),
             highlight_vertex_count: 0,
             highlight_vertices: Vec::new(),
+            highlight_electrical: false,
             power_vertex_buffer,
             power_vertex_capacity: INITIAL_POWER_CAPACITY.max(1),
             power_vertex_count: 0,
             power_vertices: Vec::new(),
+            grid_vertex_buffer,
+            grid_vertex_capacity: INITIAL_GRID_CAPACITY.max(1),
+            grid_vertex_count: 0,
+            grid_vertices: Vec::new(),
+            fluid_debug_vertex_buffer,
+            fluid_debug_vertex_capacity: INITIAL_FLUID_DEBUG_CAPACITY,
+            fluid_debug_vertex_count: 0,
+            fluid_debug_vertices: Vec::new(),
+            terminal_arrow_buffer,
+            terminal_arrow_vertex_count: 0,
+            measure_line_buffer,
+            measure_line_vertex_count: 0,
             hand_vertex_buffer,
             hand_index_buffer,
             hand_vertex_capacity: INITIAL_HAND_VERTEX_CAPACITY.max(1),
@@ -700,6 +1672,15 @@ impl<'window> Renderer<'window> {
             entity_vertex_capacity: INITIAL_ENTITY_VERTEX_CAPACITY.max(1),
             entity_index_capacity: INITIAL_ENTITY_INDEX_CAPACITY.max(1),
             entity_index_count: 0,
+            particles: ParticleSystem::new(),
+            particle_vertex_buffer,
+            particle_index_buffer,
+            particle_index_count: 0,
+            preview_vertex_buffer,
+            preview_index_buffer,
+            preview_vertex_capacity: INITIAL_PREVIEW_VERTEX_CAPACITY,
+            preview_index_capacity: INITIAL_PREVIEW_INDEX_CAPACITY,
+            preview_index_count: 0,
             ui_vertex_buffer,
             ui_index_buffer,
             ui_vertex_capacity: INITIAL_UI_VERTEX_CAPACITY.max(1),
@@ -708,6 +1689,9 @@ impl<'window> Renderer<'window> {
             ui_vertices: Vec::new(),
             ui_indices: Vec::new(),
             clear_color: [0.52, 0.73, 0.86, 1.0],
+            edge_fog_range: edge_fog_range(DEFAULT_VIEW_DISTANCE_CHUNKS),
+            last_draw_calls: 0,
+            chunk_buffer_allocations: 0,
         })
     }
 
@@ -729,12 +1713,24 @@ impl<'window> Renderer<'window> {
         self.config.height = new_size.height;
         projection.resize(new_size.width, new_size.height);
         self.surface.configure(self.device.as_ref(), &self.config);
-        self.depth_texture = DepthTexture::create(self.device.as_ref(), &self.config);
+        self.recreate_depth_targets();
     }
 
     fn reconfigure_surface(&mut self) {
         self.surface.configure(self.device.as_ref(), &self.config);
+        self.recreate_depth_targets();
+    }
+
+    fn recreate_depth_targets(&mut self) {
         self.depth_texture = DepthTexture::create(self.device.as_ref(), &self.config);
+        self.depth_of_field
+            .resize(self.device.as_ref(), &self.config, &self.depth_texture);
+    }
+
+    /// Blurs everything away from `focus` blocks from the camera; `None` renders straight to
+    /// the surface as usual.
+    pub fn set_depth_of_field(&mut self, focus: Option<f32>) {
+        self.depth_of_field.focus = focus;
     }
 
     pub fn update_camera(&mut self, camera: &Camera, projection: &Projection) {
@@ -743,101 +1739,321 @@ impl<'window> Renderer<'window> {
         self.queue
             .write_buffer(&self.camera_buffer, 0, bytemuck::bytes_of(&uniform));
         self.last_view_proj = matrix;
+        self.depth_planes = projection.clip_planes();
     }
 
-    pub fn update_environment(&mut self, atmosphere: &AtmosphereSample, camera_position: [f32; 3]) {
-        let uniform = EnvironmentUniform::from_sample(atmosphere, camera_position, self.size);
+    pub fn update_environment(
+        &mut self,
+        atmosphere: &AtmosphereSample,
+        camera_position: [f32; 3],
+        animation_time: f32,
+    ) {
+        let light_direction = self
+            .fixed_light_direction
+            .unwrap_or_else(|| WorldEnvironment::light_direction(atmosphere.time_of_day));
+        let uniform = EnvironmentUniform::from_sample(
+            atmosphere,
+            camera_position,
+            self.size,
+            self.edge_fog_range,
+            light_direction,
+            animation_time,
+        );
         self.queue
             .write_buffer(&self.environment_buffer, 0, bytemuck::bytes_of(&uniform));
+
+        // Shadows fade out with the daylight and as the sun sinks toward the horizon.
+        let shadow = &mut self.shadow_map;
+        let elevation = Vector3::from(light_direction).normalize().y;
+        let strength = if shadow.quality == ShadowQuality::Off {
+            0.0
+        } else {
+            let t = ((elevation - 0.05) / 0.2).clamp(0.0, 1.0);
+            SHADOW_DARKNESS * atmosphere.daylight.clamp(0.0, 1.0) * t * t * (3.0 - 2.0 * t)
+        };
+        shadow.active = strength > 0.0;
+        if shadow.active {
+            let center = Vector3::from(camera_position);
+            shadow.light_view_proj = shadow_view_proj(center, light_direction, shadow.resolution);
+        }
+        let uniform = ShadowUniform::new(shadow.light_view_proj, strength, shadow.resolution);
+        self.queue
+            .write_buffer(&shadow.uniform_buffer, 0, bytemuck::bytes_of(&uniform));
+    }
+
+    /// Picks the shadow map resolution, or turns the shadow pass off.
+    pub fn set_shadow_quality(&mut self, quality: ShadowQuality) {
+        self.shadow_map.quality = quality;
+        let Some(resolution) = quality.resolution() else {
+            // Keep the old map bound; the zero strength tells the shader to ignore it.
+            return;
+        };
+        if resolution != self.shadow_map.resolution {
+            self.shadow_map.view = ShadowMap::create_view(self.device.as_ref(), resolution);
+            self.shadow_map.resolution = resolution;
+            self.point_light_bind_group = create_point_light_bind_group(
+                self.device.as_ref(),
+                &self.point_light_bind_group_layout,
+                &self.point_light_buffer,
+                &self.shadow_map,
+            );
+        }
+    }
+
+    /// Replaces the lights the world shader adds; anything past `MAX_POINT_LIGHTS` is dropped.
+    pub fn update_point_lights(&mut self, lights: &[PointLight]) {
+        let uniform = PointLightUniform::from_lights(lights);
+        self.queue
+            .write_buffer(&self.point_light_buffer, 0, bytemuck::bytes_of(&uniform));
+    }
+
+    /// Links the edge fog to the chunk load radius so the outermost ring of
+    /// chunks blends into the fog colour instead of popping in.
+    pub fn set_view_distance(&mut self, render_distance: i32) {
+        self.edge_fog_range = edge_fog_range(render_distance);
+    }
+
+    /// Scrolls the cloud layer and re-projects it for the latest camera.
+    /// Also feeds the sky pass its view rays, so it runs even with clouds disabled.
+    pub fn update_clouds(&mut self, animation_time: f32) {
+        let uniform = CloudUniform::new(self.last_view_proj, animation_time);
+        self.queue
+            .write_buffer(&self.cloud_buffer, 0, bytemuck::bytes_of(&uniform));
+    }
+
+    /// Draws power overlays and terminal arrows on top of all geometry when enabled.
+    pub fn set_electrical_xray(&mut self, enabled: bool) {
+        self.electrical_xray = enabled;
+    }
+
+    /// Draws chunks, entities and the hand as triangle edges instead of filled faces.
+    /// Ignored when `supports_wireframe` is false.
+    pub fn set_wireframe(&mut self, enabled: bool) {
+        self.wireframe = enabled;
+    }
+
+    pub fn supports_wireframe(&self) -> bool {
+        self.wireframe_pipeline.is_some()
+    }
+
+    /// Pins face shading to `direction` instead of following the sun; `None` restores it.
+    pub fn set_fixed_light_direction(&mut self, direction: Option<[f32; 3]>) {
+        self.fixed_light_direction = direction;
+    }
+
+    /// Takes effect on the next chunk remesh; callers rebuild the world mesh to apply it.
+    pub fn set_foliage_detail(&mut self, detail: FoliageDetail) {
+        self.foliage_detail = detail;
+    }
+
+    /// Swaps the terrain and UI atlas for `pack`, falling back to the default tiles.
+    pub fn reload_atlas(&mut self, pack: &TexturePack) {
+        self.texture_atlas.reload(self.queue.as_ref(), pack);
+    }
+
+    pub fn set_clouds_enabled(&mut self, enabled: bool) {
+        self.clouds_enabled = enabled;
     }
 
     pub fn set_clear_color(&mut self, color: [f32; 3]) {
         self.clear_color = [color[0], color[1], color[2], 1.0];
     }
 
-    pub fn rebuild_world_mesh(&mut self, world: &World) {
-        self.chunk_meshes.clear();
+    /// Remeshes every loaded chunk; returns how many that was.
+    pub fn rebuild_world_mesh(&mut self, world: &World) -> usize {
+        // Keep existing buffers around so the upload can overwrite them in place.
+        self.chunk_meshes.retain(|pos, _| world.chunks().contains_key(pos));
         for (&pos, chunk) in world.chunks() {
-            let mesh = mesh::generate_chunk_mesh(world, pos, chunk);
-            self.upload_chunk_mesh(pos, mesh);
+            let mesh = mesh::generate_chunk_mesh(world, pos, chunk, self.foliage_detail);
+            self.upload_chunk_mesh(pos, chunk.height(), mesh);
         }
+        self.chunks_remeshed = world.chunks().len();
+        self.chunks_remeshed
     }
 
-    pub fn update_chunks(&mut self, world: &World, dirty_chunks: &HashSet<ChunkPos>) {
-        if dirty_chunks.is_empty() {
-            return;
+    /// Drops the GPU buffers of chunks that left the world.
+    pub fn unload_chunks(&mut self, unloaded: &[ChunkPos]) {
+        for pos in unloaded {
+            self.chunk_meshes.remove(pos);
         }
+    }
 
+    /// Remeshes only `dirty_chunks`, leaving every other chunk's buffers untouched; returns
+    /// how many were regenerated.
+    pub fn update_chunks(&mut self, world: &World, dirty_chunks: &HashSet<ChunkPos>) -> usize {
+        // Catch any meshes whose chunk was unloaded without an explicit notification.
+        self.chunk_meshes.retain(|pos, _| world.chunks().contains_key(pos));
+
+        self.chunks_remeshed = 0;
         for pos in dirty_chunks {
             if let Some(chunk) = world.chunks().get(pos) {
-                let mesh = mesh::generate_chunk_mesh(world, *pos, chunk);
-                self.upload_chunk_mesh(*pos, mesh);
+                let mesh = mesh::generate_chunk_mesh(world, *pos, chunk, self.foliage_detail);
+                self.upload_chunk_mesh(*pos, chunk.height(), mesh);
+                self.chunks_remeshed += 1;
             } else {
                 self.chunk_meshes.remove(pos);
             }
         }
+        self.chunks_remeshed
     }
 
-    fn upload_chunk_mesh(&mut self, pos: ChunkPos, mesh: MeshData) {
-        if mesh.vertices.is_empty() || mesh.indices.is_empty() {
-            self.chunk_meshes.remove(&pos);
+    fn upload_chunk_mesh(&mut self, pos: ChunkPos, height: usize, mesh: MeshData) {
+        if mesh.vertices.is_empty() || mesh.index_count() == 0 {
+            // Keep the buffers for when the chunk gains geometry again.
+            if let Some(existing) = self.chunk_meshes.get_mut(&pos) {
+                existing.vertex_count = 0;
+                existing.index_count = 0;
+                existing.translucent_start = 0;
+                existing.glow_sources.clear();
+            }
             return;
         }
 
-        let vertex_buffer = self
-            .device
-            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("chunk_vertex_buffer"),
-                contents: bytemuck::cast_slice(&mesh.vertices),
-                usage: wgpu::BufferUsages::VERTEX,
-            });
-        let index_buffer = self
-            .device
-            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("chunk_index_buffer"),
-                contents: bytemuck::cast_slice(&mesh.indices),
-                usage: wgpu::BufferUsages::INDEX,
-            });
+        let indices = mesh.indices();
+        let vertex_bytes: &[u8] = bytemuck::cast_slice(&mesh.vertices);
+        let index_bytes: &[u8] = bytemuck::cast_slice(&indices);
+        let [_, _, translucent] = mesh.group_ranges();
+
+        if let Some(existing) = self.chunk_meshes.get_mut(&pos) {
+            if mesh.vertices.len() <= existing.vertex_capacity
+                && indices.len() <= existing.index_capacity
+            {
+                self.queue.write_buffer(&existing.vertex_buffer, 0, vertex_bytes);
+                self.queue.write_buffer(&existing.index_buffer, 0, index_bytes);
+                existing.vertex_count = mesh.vertices.len() as u32;
+                existing.index_count = indices.len() as u32;
+                existing.translucent_start = translucent.start;
+                existing.glow_sources = mesh.glow_sources;
+                return;
+            }
+        }
+
+        let vertex_capacity = mesh.vertices.len().next_power_of_two();
+        let index_capacity = indices.len().next_power_of_two();
+        let vertex_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("chunk_vertex_buffer"),
+            size: (vertex_capacity * mem::size_of::<BlockVertex>()) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let index_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("chunk_index_buffer"),
+            size: (index_capacity * mem::size_of::<u32>()) as u64,
+            usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        self.queue.write_buffer(&vertex_buffer, 0, vertex_bytes);
+        self.queue.write_buffer(&index_buffer, 0, index_bytes);
+        self.chunk_buffer_allocations += 1;
 
         let base_x = (pos.x * CHUNK_SIZE as i32) as f32;
         let base_z = (pos.z * CHUNK_SIZE as i32) as f32;
         let bounds_min = [base_x - 0.5, -0.5, base_z - 0.5];
         let bounds_max = [
             base_x + CHUNK_SIZE as f32 - 0.5,
-            CHUNK_HEIGHT as f32 - 0.5,
+            height as f32 - 0.5,
             base_z + CHUNK_SIZE as f32 - 0.5,
         ];
 
         let gpu_mesh = ChunkGpuMesh {
             vertex_buffer,
             index_buffer,
-            index_count: mesh.indices.len() as u32,
+            vertex_capacity,
+            index_capacity,
+            vertex_count: mesh.vertices.len() as u32,
+            index_count: indices.len() as u32,
+            translucent_start: translucent.start,
             bounds_min,
             bounds_max,
+            glow_sources: mesh.glow_sources,
         };
         self.chunk_meshes.insert(pos, gpu_mesh);
     }
 
+    /// Centres of the glowing blocks in meshed chunks within `range` of `eye`.
+    pub fn glow_sources_near(&self, eye: Vector3<f32>, range: f32) -> Vec<Vector3<f32>> {
+        let range2 = range * range;
+        self.chunk_meshes
+            .values()
+            .flat_map(|mesh| mesh.glow_sources.iter())
+            .map(|&[x, y, z]| Vector3::new(x as f32, y as f32, z as f32))
+            .filter(|pos| (pos - eye).magnitude2() <= range2)
+            .collect()
+    }
+
+    pub fn stats(&self) -> RendererStats {
+        let mut stats = RendererStats {
+            chunk_meshes: self.chunk_meshes.len(),
+            draw_calls: self.last_draw_calls,
+            chunk_buffer_allocations: self.chunk_buffer_allocations,
+            chunks_remeshed: self.chunks_remeshed,
+            ..RendererStats::default()
+        };
+        for mesh in self.chunk_meshes.values() {
+            stats.vertices += mesh.vertex_count as u64;
+            stats.indices += mesh.index_count as u64;
+            stats.chunk_buffer_bytes += mesh.vertex_buffer.size() + mesh.index_buffer.size();
+        }
+        stats
+    }
+
+    /// Returns the number of chunk draw calls issued.
+    /// Draws either the opaque and cutout part of every visible chunk or its translucent
+    /// part. Opaque goes first so translucent faces blend over a complete scene.
     fn draw_world_chunks<'a>(
         &'a self,
         pass: &mut wgpu::RenderPass<'a>,
         frustum: &Frustum,
-    ) {
+        translucent: bool,
+    ) -> u32 {
+        let mut draws = 0;
         for mesh in self.chunk_meshes.values() {
-            if mesh.index_count == 0 {
+            let range = if translucent {
+                mesh.translucent_start..mesh.index_count
+            } else {
+                0..mesh.translucent_start
+            };
+            if range.is_empty() || !frustum.intersects_aabb(mesh.bounds_min, mesh.bounds_max) {
                 continue;
             }
-            if !frustum.intersects_aabb(mesh.bounds_min, mesh.bounds_max) {
+            pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+            pass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            pass.draw_indexed(range, 0, 0..1);
+            draws += 1;
+        }
+        draws
+    }
+
+    /// Draws the opaque and cutout geometry inside the sun's view; water casts no shadow.
+    /// Returns the number of draw calls issued.
+    fn draw_shadow_casters<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>) -> u32 {
+        let frustum = Frustum::from_matrix(self.shadow_map.light_view_proj);
+        let mut draws = 0;
+        for mesh in self.chunk_meshes.values() {
+            if mesh.translucent_start == 0
+                || !frustum.intersects_aabb(mesh.bounds_min, mesh.bounds_max)
+            {
                 continue;
             }
             pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
             pass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
-            pass.draw_indexed(0..mesh.index_count, 0, 0..1);
+            pass.draw_indexed(0..mesh.translucent_start, 0, 0..1);
+            draws += 1;
         }
+        draws
     }
 
-    pub fn update_highlight(&mut self, bounds: Option<([f32; 3], [f32; 3])>, breaking_progress: f32) {
+    /// Outlines `bounds` in `base_color`, shifting toward red as `breaking_progress` nears 1.
+    /// `electrical` outlines count as electrical overlays for the x-ray view.
+    pub fn update_highlight(
+        &mut self,
+        bounds: Option<([f32; 3], [f32; 3])>,
+        base_color: [f32; 3],
+        breaking_progress: f32,
+        electrical: bool,
+    ) {
         self.highlight_vertices.clear();
+        self.highlight_electrical = electrical;
 
         if let Some((min, max)) = bounds {
             let corners = [
@@ -850,28 +2066,15 @@ impl<'window> Renderer<'window> {
                 [max[0], max[1], max[2]],
                 [min[0], max[1], max[2]],
             ];
-            const EDGES: [(usize, usize); 12] = [
-                (0, 1),
-                (1, 2),
-                (2, 3),
-                (3, 0),
-                (4, 5),
-                (5, 6),
-                (6, 7),
-                (7, 4),
-                (0, 4),
-                (1, 5),
-                (2, 6),
-                (3, 7),
-            ];
-            // Color transitions from yellow (no breaking) to red (almost broken)
+            // Color transitions from the base color (no breaking) to red (almost broken)
             let progress = breaking_progress.clamp(0.0, 1.0);
-            let red = 1.0;
-            let green = 0.95 - progress * 0.5; // 0.95 -> 0.45
-            let blue = 0.45 - progress * 0.45; // 0.45 -> 0.0
+            let lerp = |from: f32, to: f32| from + (to - from) * progress;
+            let red = lerp(base_color[0], HIGHLIGHT_BREAKING_COLOR[0]);
+            let green = lerp(base_color[1], HIGHLIGHT_BREAKING_COLOR[1]);
+            let blue = lerp(base_color[2], HIGHLIGHT_BREAKING_COLOR[2]);
             let alpha = 0.85 + progress * 0.15; // 0.85 -> 1.0 (more visible as breaking)
             let color = [red, green, blue, alpha];
-            for &(a, b) in &EDGES {
+            for &(a, b) in &BOX_EDGES {
                 self.highlight_vertices.push(HighlightVertex {
                     position: corners[a],
                     color,
@@ -896,27 +2099,39 @@ impl<'window> Renderer<'window> {
 
     pub fn update_power_overlays(
         &mut self,
-        overlays: &[(Vector3<f32>, ElectricalComponent, ComponentTelemetry)],
+        overlays: &[PowerOverlay],
         animation_time: f32,
     ) {
         self.power_vertices.clear();
 
-        for (index, (pos, component, telemetry)) in overlays.iter().enumerate() {
-            let base_color = component_color(*component);
+        for (index, overlay) in overlays.iter().enumerate() {
+            let pos = overlay.position;
+            let telemetry = overlay.telemetry;
+            let base_color = component_color(overlay.component);
             let current_strength = telemetry.current.abs();
             let voltage_strength = telemetry.voltage_local.abs();
             let intensity = (current_strength * 0.4 + voltage_strength * 0.05).min(3.0);
             let pulse = (animation_time * 4.0 + index as f32 * 0.7).sin() * 0.5 + 0.5;
             let brightness = (0.6 + intensity * 0.25 + pulse * 0.2).clamp(0.0, 2.0);
-            let color = [
-                (base_color[0] * brightness).clamp(0.0, 1.0),
-                (base_color[1] * brightness).clamp(0.0, 1.0),
-                (base_color[2] * brightness).clamp(0.0, 1.0),
-                (base_color[3] * (0.6 + pulse * 0.4)).clamp(0.2, 1.0),
-            ];
+            let color = if overlay.overloaded {
+                // A hard on/off flash so a tripped part stands out from the gentle pulse.
+                let flash = if (animation_time * 10.0).sin() > 0.0 { 1.0 } else { 0.35 };
+                [flash, 0.08 * flash, 0.05 * flash, 1.0]
+            } else {
+                [
+                    (base_color[0] * brightness).clamp(0.0, 1.0),
+                    (base_color[1] * brightness).clamp(0.0, 1.0),
+                    (base_color[2] * brightness).clamp(0.0, 1.0),
+                    (base_color[3] * (0.6 + pulse * 0.4)).clamp(0.2, 1.0),
+                ]
+            };
 
             let center = Vector3::new(pos.x, pos.y, pos.z) + Vector3::new(0.5, 0.5, 0.5);
-            let radius = 0.16 + 0.08 * intensity.min(1.5);
+            let radius = if overlay.overloaded {
+                0.32
+            } else {
+                0.16 + 0.08 * intensity.min(1.5)
+            };
             let axes = [
                 Vector3::new(radius, 0.0, 0.0),
                 Vector3::new(0.0, radius, 0.0),
@@ -947,13 +2162,154 @@ impl<'window> Renderer<'window> {
         }
     }
 
+    pub fn update_build_grid(&mut self, segments: &[([f32; 3], [f32; 3])]) {
+        self.grid_vertices.clear();
+        for (a, b) in segments {
+            self.grid_vertices.push(HighlightVertex {
+                position: *a,
+                color: BUILD_GRID_COLOR,
+            });
+            self.grid_vertices.push(HighlightVertex {
+                position: *b,
+                color: BUILD_GRID_COLOR,
+            });
+        }
+
+        self.grid_vertex_count = self.grid_vertices.len() as u32;
+        self.ensure_grid_capacity(self.grid_vertices.len());
+        if self.grid_vertex_count > 0 {
+            self.queue.write_buffer(
+                &self.grid_vertex_buffer,
+                0,
+                bytemuck::cast_slice(&self.grid_vertices),
+            );
+        }
+    }
+
+    /// Outlines each active fluid cell with a small inset box, colored by why it was active.
+    pub fn update_fluid_debug(&mut self, cells: &[ActiveFluidCell]) {
+        const INSET: f32 = 0.3;
+        self.fluid_debug_vertices.clear();
+        for cell in cells {
+            let color = if cell.changed {
+                FLUID_DEBUG_CHANGED_COLOR
+            } else {
+                FLUID_DEBUG_SCHEDULED_COLOR
+            };
+            let min = [
+                cell.pos.0 as f32 + INSET,
+                cell.pos.1 as f32 + INSET,
+                cell.pos.2 as f32 + INSET,
+            ];
+            let size = 1.0 - 2.0 * INSET;
+            let max = [min[0] + size, min[1] + size, min[2] + size];
+            let corners = [
+                [min[0], min[1], min[2]],
+                [max[0], min[1], min[2]],
+                [max[0], max[1], min[2]],
+                [min[0], max[1], min[2]],
+                [min[0], min[1], max[2]],
+                [max[0], min[1], max[2]],
+                [max[0], max[1], max[2]],
+                [min[0], max[1], max[2]],
+            ];
+            for &(a, b) in &BOX_EDGES {
+                self.fluid_debug_vertices.push(HighlightVertex {
+                    position: corners[a],
+                    color,
+                });
+                self.fluid_debug_vertices.push(HighlightVertex {
+                    position: corners[b],
+                    color,
+                });
+            }
+        }
+
+        self.fluid_debug_vertex_count = self.fluid_debug_vertices.len() as u32;
+        self.ensure_fluid_debug_capacity(self.fluid_debug_vertices.len());
+        if self.fluid_debug_vertex_count > 0 {
+            self.queue.write_buffer(
+                &self.fluid_debug_vertex_buffer,
+                0,
+                bytemuck::cast_slice(&self.fluid_debug_vertices),
+            );
+        }
+    }
+
+    /// Points arrows from `origin` toward the positive and negative terminal faces of the
+    /// inspected component. A single-terminal component (both faces equal) gets one arrow.
+    pub fn update_terminal_arrows(
+        &mut self,
+        terminals: Option<(Vector3<f32>, BlockFace, BlockFace)>,
+    ) {
+        let Some((origin, positive, negative)) = terminals else {
+            self.terminal_arrow_vertex_count = 0;
+            return;
+        };
+        let mut arrows = vec![(negative, TERMINAL_NEGATIVE_COLOR)];
+        if positive != negative {
+            arrows.push((positive, TERMINAL_POSITIVE_COLOR));
+        }
+
+        let mut vertices = Vec::with_capacity(TERMINAL_ARROW_VERTICES);
+        for (face, color) in arrows {
+            let normal = face.normal();
+            let dir = Vector3::new(normal.x as f32, normal.y as f32, normal.z as f32);
+            // Faces are axis-aligned, so the other two axes span the arrowhead.
+            let side_a = Vector3::new(dir.y.abs(), dir.z.abs(), dir.x.abs());
+            let side_b = dir.cross(side_a);
+            let tip = origin + dir * TERMINAL_ARROW_LENGTH;
+            let back = tip - dir * TERMINAL_ARROW_HEAD;
+            let spread = TERMINAL_ARROW_HEAD * 0.6;
+            let mut segments = vec![(origin, tip)];
+            for side in [side_a, -side_a, side_b, -side_b] {
+                segments.push((tip, back + side * spread));
+            }
+            for (a, b) in segments {
+                vertices.push(HighlightVertex {
+                    position: [a.x, a.y, a.z],
+                    color,
+                });
+                vertices.push(HighlightVertex {
+                    position: [b.x, b.y, b.z],
+                    color,
+                });
+            }
+        }
+
+        self.terminal_arrow_vertex_count = vertices.len() as u32;
+        self.queue
+            .write_buffer(&self.terminal_arrow_buffer, 0, bytemuck::cast_slice(&vertices));
+    }
+
+    /// Shows the measuring tape segment between its two anchors; `None` hides it.
+    pub fn update_measure_line(&mut self, segment: Option<([f32; 3], [f32; 3])>) {
+        let Some((a, b)) = segment else {
+            self.measure_line_vertex_count = 0;
+            return;
+        };
+        let vertices = [
+            HighlightVertex {
+                position: a,
+                color: MEASURE_LINE_COLOR,
+            },
+            HighlightVertex {
+                position: b,
+                color: MEASURE_LINE_COLOR,
+            },
+        ];
+        self.queue
+            .write_buffer(&self.measure_line_buffer, 0, bytemuck::cast_slice(&vertices));
+        self.measure_line_vertex_count = vertices.len() as u32;
+    }
+
     pub fn update_hand(
         &mut self,
         block_type: Option<BlockType>,
         camera: &Camera,
         animation_time: f32,
         breaking_progress: f32,
-        placement_progress: f32,
+        pose: HandPose,
     ) {
         let Some(block_type) = block_type else {
             self.hand_index_count = 0;
@@ -981,13 +2337,10 @@ impl<'window> Renderer<'window> {
             hand_offset += Vector3::new(shake_x, shake_y, 0.0);
         }
 
-        // Placement animation (forward thrust that decays)
-        if placement_progress > 0.0 {
-            // Quick forward motion that eases out
-            let thrust = (1.0 - placement_progress).powi(2) * 0.15;
-            hand_offset += camera.direction() * thrust;
-            hand_offset -= Vector3::new(0.0, (1.0 - placement_progress).powi(2) * 0.05, 0.0);
-        }
+        // Swing or place motion, in camera space
+        let up = camera.right().cross(camera.direction());
+        hand_offset +=
+            camera.right() * pose.right + up * pose.up + camera.direction() * pose.forward;
 
         let hand_pos = Vector3::new(
             camera.position.x + hand_offset.x,
@@ -995,8 +2348,10 @@ impl<'window> Renderer<'window> {
             camera.position.z + hand_offset.z,
         );
 
-        let rotation =
-            Quaternion::from_angle_y(camera.yaw) * Quaternion::from_angle_x(Rad(-camera.pitch.0));
+        let rotation = Quaternion::from_axis_angle(camera.right(), pose.pitch)
+            * Quaternion::from_axis_angle(camera.direction(), pose.roll)
+            * Quaternion::from_angle_y(camera.yaw)
+            * Quaternion::from_angle_x(Rad(-camera.pitch.0));
 
         for vertex in &mut mesh.vertices {
             let v = Vector3::new(vertex.position[0], vertex.position[1], vertex.position[2]);
@@ -1006,7 +2361,8 @@ impl<'window> Renderer<'window> {
             vertex.tint = [1.0, 1.0, 1.0];
         }
 
-        self.ensure_hand_capacity(mesh.vertices.len(), mesh.indices.len());
+        let indices = mesh.indices();
+        self.ensure_hand_capacity(mesh.vertices.len(), indices.len());
         if !mesh.vertices.is_empty() {
             self.queue.write_buffer(
                 &self.hand_vertex_buffer,
@@ -1014,14 +2370,32 @@ impl<'window> Renderer<'window> {
                 bytemuck::cast_slice(&mesh.vertices),
             );
         }
-        if !mesh.indices.is_empty() {
+        if !indices.is_empty() {
             self.queue.write_buffer(
                 &self.hand_index_buffer,
                 0,
-                bytemuck::cast_slice(&mesh.indices),
+                bytemuck::cast_slice(&indices),
             );
         }
-        self.hand_index_count = mesh.indices.len() as u32;
+        self.hand_index_count = indices.len() as u32;
+    }
+
+    /// Uploads the placement ghost for the held component; `None` hides it.
+    pub fn update_placement_preview(&mut self, mesh: Option<&MeshData>) {
+        let Some(mesh) = mesh.filter(|mesh| !mesh.vertices.is_empty()) else {
+            self.preview_index_count = 0;
+            return;
+        };
+        let indices = mesh.indices();
+        self.ensure_preview_capacity(mesh.vertices.len(), indices.len());
+        self.queue.write_buffer(
+            &self.preview_vertex_buffer,
+            0,
+            bytemuck::cast_slice(&mesh.vertices),
+        );
+        self.queue
+            .write_buffer(&self.preview_index_buffer, 0, bytemuck::cast_slice(&indices));
+        self.preview_index_count = indices.len() as u32;
     }
 
     pub fn update_entities(&mut self, entities: &[crate::entity::ItemEntity]) {
@@ -1040,6 +2414,9 @@ impl<'window> Renderer<'window> {
                 crate::item::ItemType::Block(block) => block,
                 crate::item::ItemType::Tool(_, _) => crate::block::BlockType::Stone, // TODO: Tool models
                 crate::item::ItemType::Material(_) => crate::block::BlockType::Wood, // TODO: Material models
+                crate::item::ItemType::Bucket(state) => state
+                    .fluid()
+                    .map_or(crate::block::BlockType::Stone, |kind| kind.block_type()),
             };
             let mut item_mesh = mesh::generate_block_mesh(block_to_render, origin, scale);
 
@@ -1062,7 +2439,7 @@ impl<'window> Renderer<'window> {
                 combined_vertices.push(*vertex);
             }
 
-            for &index in &item_mesh.indices {
+            for index in item_mesh.indices() {
                 combined_indices.push(base_index + index);
             }
         }
@@ -1086,6 +2463,34 @@ impl<'window> Renderer<'window> {
         self.entity_index_count = combined_indices.len() as u32;
     }
 
+    pub fn emit_break_particles(&mut self, block: BlockType, center: Point3<f32>, light: f32) {
+        self.particles.emit_break(block, center, light);
+    }
+
+    pub fn emit_place_particles(&mut self, block: BlockType, center: Point3<f32>, light: f32) {
+        self.particles.emit_place(block, center, light);
+    }
+
+    /// Steps the particles and rebuilds their quads to face `camera`.
+    pub fn update_particles(
+        &mut self,
+        dt: f32,
+        camera: &Camera,
+        is_solid: impl Fn(i32, i32, i32) -> bool,
+    ) {
+        self.particles.update(dt, is_solid);
+        let right = camera.right();
+        let up = right.cross(camera.direction()).normalize();
+        let (vertices, indices) = self.particles.build_mesh(right, up);
+        if !vertices.is_empty() {
+            self.queue
+                .write_buffer(&self.particle_vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+            self.queue
+                .write_buffer(&self.particle_index_buffer, 0, bytemuck::cast_slice(&indices));
+        }
+        self.particle_index_count = indices.len() as u32;
+    }
+
     pub fn update_ui(&mut self, vertices: &[UiVertex], indices: &[u16]) {
         self.ui_vertices.clear();
         self.ui_vertices.extend_from_slice(vertices);
@@ -1144,12 +2549,43 @@ impl<'window> Renderer<'window> {
         };
 
         let frustum = Frustum::from_matrix(self.last_view_proj);
+        let world_pipeline = match &self.wireframe_pipeline {
+            Some(wireframe) if self.wireframe => wireframe,
+            _ => &self.render_pipeline,
+        };
+        let mut draw_calls = 0;
+        let world_view = if self.depth_of_field.focus.is_some() {
+            &self.depth_of_field.scene_view
+        } else {
+            &view
+        };
+
+        if self.shadow_map.active {
+            let mut shadow_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("shadow_pass"),
+                color_attachments: &[],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.shadow_map.view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }),
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+            shadow_pass.set_pipeline(&self.shadow_map.pipeline);
+            shadow_pass.set_bind_group(0, &self.shadow_map.light_bind_group, &[]);
+            shadow_pass.set_bind_group(1, &self.texture_atlas.bind_group, &[]);
+            draw_calls += self.draw_shadow_casters(&mut shadow_pass);
+        }
 
         {
             let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("world_pass"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
+                    view: world_view,
                     resolve_target: None,
                     ops: wgpu::Operations {
                         load: wgpu::LoadOp::Clear(clear_color),
@@ -1170,46 +2606,152 @@ impl<'window> Renderer<'window> {
 
             pass.set_pipeline(&self.sky_pipeline);
             pass.set_bind_group(0, &self.environment_bind_group, &[]);
+            pass.set_bind_group(1, &self.cloud_bind_group, &[]);
             pass.draw(0..3, 0..1);
+            draw_calls += 1;
 
-            pass.set_pipeline(&self.render_pipeline);
+            pass.set_pipeline(world_pipeline);
             pass.set_bind_group(0, &self.camera_bind_group, &[]);
             pass.set_bind_group(1, &self.texture_atlas.bind_group, &[]);
             pass.set_bind_group(2, &self.environment_bind_group, &[]);
-            self.draw_world_chunks(&mut pass, &frustum);
+            pass.set_bind_group(3, &self.point_light_bind_group, &[]);
+            draw_calls += self.draw_world_chunks(&mut pass, &frustum, false);
+
+            // Clouds depth-test against the opaque terrain, then water blends over both.
+            if self.clouds_enabled {
+                pass.set_pipeline(&self.cloud_pipeline);
+                pass.set_bind_group(0, &self.environment_bind_group, &[]);
+                pass.set_bind_group(1, &self.cloud_bind_group, &[]);
+                pass.draw(0..3, 0..1);
+                draw_calls += 1;
+
+                pass.set_pipeline(world_pipeline);
+                pass.set_bind_group(0, &self.camera_bind_group, &[]);
+                pass.set_bind_group(1, &self.texture_atlas.bind_group, &[]);
+                pass.set_bind_group(2, &self.environment_bind_group, &[]);
+                pass.set_bind_group(3, &self.point_light_bind_group, &[]);
+            }
+            draw_calls += self.draw_world_chunks(&mut pass, &frustum, true);
 
             // Draw item entities
             if self.entity_index_count > 0 {
                 pass.set_vertex_buffer(0, self.entity_vertex_buffer.slice(..));
                 pass.set_index_buffer(self.entity_index_buffer.slice(..), wgpu::IndexFormat::Uint32);
                 pass.draw_indexed(0..self.entity_index_count, 0, 0..1);
+                draw_calls += 1;
+            }
+
+            if self.particle_index_count > 0 {
+                pass.set_vertex_buffer(0, self.particle_vertex_buffer.slice(..));
+                pass.set_index_buffer(
+                    self.particle_index_buffer.slice(..),
+                    wgpu::IndexFormat::Uint32,
+                );
+                pass.draw_indexed(0..self.particle_index_count, 0, 0..1);
+                draw_calls += 1;
             }
 
-            if self.highlight_vertex_count > 0 || self.power_vertex_count > 0 {
+            if self.preview_index_count > 0 {
+                pass.set_vertex_buffer(0, self.preview_vertex_buffer.slice(..));
+                pass.set_index_buffer(
+                    self.preview_index_buffer.slice(..),
+                    wgpu::IndexFormat::Uint32,
+                );
+                pass.draw_indexed(0..self.preview_index_count, 0, 0..1);
+                draw_calls += 1;
+            }
+
+            if self.highlight_vertex_count > 0
+                || self.power_vertex_count > 0
+                || self.grid_vertex_count > 0
+                || self.fluid_debug_vertex_count > 0
+                || self.terminal_arrow_vertex_count > 0
+                || self.measure_line_vertex_count > 0
+            {
+                let highlight_xray = self.electrical_xray && self.highlight_electrical;
                 pass.set_pipeline(&self.highlight_pipeline);
                 pass.set_bind_group(0, &self.camera_bind_group, &[]);
-                if self.highlight_vertex_count > 0 {
+                if self.grid_vertex_count > 0 {
+                    pass.set_vertex_buffer(0, self.grid_vertex_buffer.slice(..));
+                    pass.draw(0..self.grid_vertex_count, 0..1);
+                    draw_calls += 1;
+                }
+                if self.fluid_debug_vertex_count > 0 {
+                    pass.set_vertex_buffer(0, self.fluid_debug_vertex_buffer.slice(..));
+                    pass.draw(0..self.fluid_debug_vertex_count, 0..1);
+                    draw_calls += 1;
+                }
+                if self.highlight_vertex_count > 0 && !highlight_xray {
+                    pass.set_vertex_buffer(0, self.highlight_vertex_buffer.slice(..));
+                    pass.draw(0..self.highlight_vertex_count, 0..1);
+                    draw_calls += 1;
+                }
+                if self.measure_line_vertex_count > 0 {
+                    pass.set_vertex_buffer(0, self.measure_line_buffer.slice(..));
+                    pass.draw(0..self.measure_line_vertex_count, 0..1);
+                    draw_calls += 1;
+                }
+                if self.electrical_xray {
+                    pass.set_pipeline(&self.electrical_xray_pipeline);
+                }
+                if self.highlight_vertex_count > 0 && highlight_xray {
                     pass.set_vertex_buffer(0, self.highlight_vertex_buffer.slice(..));
                     pass.draw(0..self.highlight_vertex_count, 0..1);
+                    draw_calls += 1;
                 }
                 if self.power_vertex_count > 0 {
                     pass.set_vertex_buffer(0, self.power_vertex_buffer.slice(..));
                     pass.draw(0..self.power_vertex_count, 0..1);
+                    draw_calls += 1;
+                }
+                if self.terminal_arrow_vertex_count > 0 {
+                    pass.set_vertex_buffer(0, self.terminal_arrow_buffer.slice(..));
+                    pass.draw(0..self.terminal_arrow_vertex_count, 0..1);
+                    draw_calls += 1;
                 }
 
-                pass.set_pipeline(&self.render_pipeline);
+                pass.set_pipeline(world_pipeline);
                 pass.set_bind_group(0, &self.camera_bind_group, &[]);
                 pass.set_bind_group(1, &self.texture_atlas.bind_group, &[]);
                 pass.set_bind_group(2, &self.environment_bind_group, &[]);
+                pass.set_bind_group(3, &self.point_light_bind_group, &[]);
             }
 
             if self.hand_index_count > 0 {
                 pass.set_vertex_buffer(0, self.hand_vertex_buffer.slice(..));
                 pass.set_index_buffer(self.hand_index_buffer.slice(..), wgpu::IndexFormat::Uint32);
                 pass.draw_indexed(0..self.hand_index_count, 0, 0..1);
+                draw_calls += 1;
             }
         }
 
+        if let Some(focus) = self.depth_of_field.focus {
+            let uniform = DepthOfFieldUniform::new(focus, self.depth_planes, &self.config);
+            self.queue.write_buffer(
+                &self.depth_of_field.uniform_buffer,
+                0,
+                bytemuck::bytes_of(&uniform),
+            );
+            let mut dof_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("dof_pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+            dof_pass.set_pipeline(&self.depth_of_field.pipeline);
+            dof_pass.set_bind_group(0, &self.depth_of_field.bind_group, &[]);
+            dof_pass.draw(0..3, 0..1);
+            draw_calls += 1;
+        }
+
         if self.ui_index_count > 0 {
             let mut ui_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("ui_pass"),
@@ -1230,7 +2772,9 @@ impl<'window> Renderer<'window> {
             ui_pass.set_vertex_buffer(0, self.ui_vertex_buffer.slice(..));
             ui_pass.set_index_buffer(self.ui_index_buffer.slice(..), wgpu::IndexFormat::Uint16);
             ui_pass.draw_indexed(0..self.ui_index_count, 0, 0..1);
+            draw_calls += 1;
         }
+        self.last_draw_calls = draw_calls;
 
         self.queue.submit(Some(encoder.finish()));
         output.present();
@@ -1263,6 +2807,32 @@ impl<'window> Renderer<'window> {
         }
     }
 
+    fn ensure_grid_capacity(&mut self, required: usize) {
+        let required = required.max(1);
+        if required > self.grid_vertex_capacity {
+            self.grid_vertex_capacity = required.next_power_of_two();
+            self.grid_vertex_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("grid_vertex_buffer"),
+                size: (self.grid_vertex_capacity * mem::size_of::<HighlightVertex>()) as u64,
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+        }
+    }
+
+    fn ensure_fluid_debug_capacity(&mut self, required: usize) {
+        let required = required.max(1);
+        if required > self.fluid_debug_vertex_capacity {
+            self.fluid_debug_vertex_capacity = required.next_power_of_two();
+            self.fluid_debug_vertex_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("fluid_debug_vertex_buffer"),
+                size: (self.fluid_debug_vertex_capacity * mem::size_of::<HighlightVertex>()) as u64,
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+        }
+    }
+
     fn ensure_hand_capacity(&mut self, vertices: usize, indices: usize) {
         let vertices = vertices.max(1);
         if vertices > self.hand_vertex_capacity {
@@ -1287,6 +2857,28 @@ impl<'window> Renderer<'window> {
         }
     }
 
+    fn ensure_preview_capacity(&mut self, vertices: usize, indices: usize) {
+        if vertices > self.preview_vertex_capacity {
+            self.preview_vertex_capacity = vertices.next_power_of_two();
+            self.preview_vertex_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("preview_vertex_buffer"),
+                size: (self.preview_vertex_capacity * mem::size_of::<BlockVertex>()) as u64,
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+        }
+
+        if indices > self.preview_index_capacity {
+            self.preview_index_capacity = indices.next_power_of_two();
+            self.preview_index_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("preview_index_buffer"),
+                size: (self.preview_index_capacity * mem::size_of::<u32>()) as u64,
+                usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+        }
+    }
+
     fn ensure_entity_capacity(&mut self, vertices: usize, indices: usize) {
         let vertices = vertices.max(1);
         if vertices > self.entity_vertex_capacity {
@@ -1336,6 +2928,15 @@ impl<'window> Renderer<'window> {
     }
 }
 
+/// Horizontal distances (in blocks) where the edge fog starts and reaches full
+/// strength. The ramp covers the last loaded ring of chunks.
+fn edge_fog_range(render_distance: i32) -> (f32, f32) {
+    let chunk = CHUNK_SIZE as f32;
+    let end = render_distance.max(1) as f32 * chunk;
+    let start = (end - chunk).max(chunk * 0.5);
+    (start, end)
+}
+
 fn block_vertex_layout() -> wgpu::VertexBufferLayout<'static> {
     wgpu::VertexBufferLayout {
         array_stride: mem::size_of::<BlockVertex>() as u64,
@@ -1375,6 +2976,53 @@ fn block_vertex_layout() -> wgpu::VertexBufferLayout<'static> {
     }
 }
 
+/// Alpha-blended line list over the highlight shader that never writes depth.
+fn line_overlay_pipeline(
+    device: &wgpu::Device,
+    label: &str,
+    layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+    depth_compare: wgpu::CompareFunction,
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(label),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: "vs_main",
+            buffers: &[highlight_vertex_layout()],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::LineList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            unclipped_depth: false,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            conservative: false,
+        },
+        depth_stencil: Some(wgpu::DepthStencilState {
+            format: DepthTexture::FORMAT,
+            depth_write_enabled: false,
+            depth_compare,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }),
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+    })
+}
+
 fn highlight_vertex_layout() -> wgpu::VertexBufferLayout<'static> {
     wgpu::VertexBufferLayout {
         array_stride: mem::size_of::<HighlightVertex>() as u64,
@@ -1429,5 +3077,50 @@ fn component_color(component: ElectricalComponent) -> [f32; 4] {
         ElectricalComponent::Resistor => [0.4, 0.8, 1.0, 0.9],
         ElectricalComponent::VoltageSource => [1.0, 0.35, 0.45, 0.95],
         ElectricalComponent::Ground => [0.6, 0.65, 0.7, 0.85],
+        ElectricalComponent::Capacitor => [0.45, 0.55, 1.0, 0.9],
+        ElectricalComponent::Switch => [0.95, 0.8, 0.35, 0.9],
+        ElectricalComponent::Led => [1.0, 0.3, 0.3, 0.9],
+        ElectricalComponent::Diode => [0.75, 0.75, 0.8, 0.9],
+        ElectricalComponent::Potentiometer => [0.35, 0.6, 1.0, 0.9],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(matrix: Matrix4<f32>, point: [f32; 3]) -> Vector3<f32> {
+        let clip = matrix * Vector3::from(point).extend(1.0);
+        clip.truncate() / clip.w
+    }
+
+    #[test]
+    fn shadow_view_keeps_the_camera_area_in_the_map() {
+        let sun = WorldEnvironment::direction_from_angles(30.0, 50.0);
+        let center = [12.3, 70.0, -40.7];
+        let matrix = shadow_view_proj(Vector3::from(center), sun, 2048);
+
+        let middle = project(matrix, center);
+        assert!(middle.x.abs() < 0.01 && middle.y.abs() < 0.01, "{middle:?}");
+        assert!((0.0..=1.0).contains(&middle.z));
+
+        let edge = [center[0] + SHADOW_HALF_EXTENT * 0.9, center[1], center[2]];
+        let edge = project(matrix, edge);
+        assert!(edge.x.abs() <= 1.0 && edge.y.abs() <= 1.0, "{edge:?}");
+
+        // A block lifted toward the sun sits in front of the ground below it.
+        let above = [
+            center[0] + sun[0] * 10.0,
+            center[1] + sun[1] * 10.0,
+            center[2] + sun[2] * 10.0,
+        ];
+        assert!(project(matrix, above).z < middle.z);
+    }
+
+    #[test]
+    fn shadow_quality_cycles_through_off() {
+        assert_eq!(ShadowQuality::High.cycle(1), ShadowQuality::Off);
+        assert_eq!(ShadowQuality::Off.cycle(-1), ShadowQuality::High);
+        assert_eq!(ShadowQuality::Off.resolution(), None);
     }
 }
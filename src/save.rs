@@ -0,0 +1,192 @@
+//! Little-endian byte encoding shared by the world save file and its parts.
+
+use anyhow::{bail, Context};
+
+/// Leading bytes of every world save, followed by `SAVE_VERSION`.
+pub const SAVE_MAGIC: &[u8; 8] = b"RCWORLD\0";
+/// Bumped whenever the layout changes; older files are rejected rather than misread.
+pub const SAVE_VERSION: u32 = 5;
+
+#[derive(Default)]
+pub struct SaveWriter {
+    bytes: Vec<u8>,
+}
+
+impl SaveWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn put_u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    pub fn put_bool(&mut self, value: bool) {
+        self.put_u8(value as u8);
+    }
+
+    pub fn put_u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn put_i32(&mut self, value: i32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn put_u64(&mut self, value: u64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn put_f32(&mut self, value: f32) {
+        self.put_u32(value.to_bits());
+    }
+
+    pub fn put_f64(&mut self, value: f64) {
+        self.put_u64(value.to_bits());
+    }
+
+    /// A presence byte, then the value when there is one.
+    pub fn put_opt_f32(&mut self, value: Option<f32>) {
+        self.put_bool(value.is_some());
+        if let Some(value) = value {
+            self.put_f32(value);
+        }
+    }
+
+    /// Raw bytes with no length prefix; the reader must already know how many follow.
+    pub fn put_bytes(&mut self, bytes: &[u8]) {
+        self.bytes.extend_from_slice(bytes);
+    }
+}
+
+pub struct SaveReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> SaveReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, offset: 0 }
+    }
+
+    pub fn is_at_end(&self) -> bool {
+        self.offset == self.bytes.len()
+    }
+
+    pub fn take(&mut self, len: usize) -> anyhow::Result<&'a [u8]> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .with_context(|| format!("save data ends early at byte {}", self.offset))?;
+        let slice = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(slice)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> anyhow::Result<[u8; N]> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    pub fn u8(&mut self) -> anyhow::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn bool(&mut self) -> anyhow::Result<bool> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            other => bail!("invalid flag byte {other} in save data"),
+        }
+    }
+
+    pub fn u32(&mut self) -> anyhow::Result<u32> {
+        Ok(u32::from_le_bytes(self.take_array()?))
+    }
+
+    pub fn i32(&mut self) -> anyhow::Result<i32> {
+        Ok(i32::from_le_bytes(self.take_array()?))
+    }
+
+    pub fn u64(&mut self) -> anyhow::Result<u64> {
+        Ok(u64::from_le_bytes(self.take_array()?))
+    }
+
+    pub fn f32(&mut self) -> anyhow::Result<f32> {
+        Ok(f32::from_bits(self.u32()?))
+    }
+
+    pub fn f64(&mut self) -> anyhow::Result<f64> {
+        Ok(f64::from_bits(self.u64()?))
+    }
+
+    pub fn opt_f32(&mut self) -> anyhow::Result<Option<f32>> {
+        Ok(if self.bool()? { Some(self.f32()?) } else { None })
+    }
+}
+
+/// 64-bit FNV-1a digest fed the same fixed-width little-endian values as `SaveWriter`, so
+/// equal states hash equally on every platform and Rust release.
+pub struct StateHasher {
+    hash: u64,
+}
+
+impl StateHasher {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    pub fn new() -> Self {
+        Self {
+            hash: Self::OFFSET_BASIS,
+        }
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.hash
+    }
+
+    pub fn put_u8(&mut self, value: u8) {
+        self.hash = (self.hash ^ value as u64).wrapping_mul(Self::PRIME);
+    }
+
+    pub fn put_u32(&mut self, value: u32) {
+        self.put_bytes(&value.to_le_bytes());
+    }
+
+    pub fn put_i32(&mut self, value: i32) {
+        self.put_bytes(&value.to_le_bytes());
+    }
+
+    pub fn put_bytes(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.put_u8(byte);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_hasher_matches_reference_fnv1a() {
+        let digest = |bytes: &[u8]| {
+            let mut hasher = StateHasher::new();
+            hasher.put_bytes(bytes);
+            hasher.finish()
+        };
+        assert_eq!(digest(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(digest(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(digest(b"foobar"), 0x8594_4171_f739_67e8);
+
+        let mut hasher = StateHasher::new();
+        hasher.put_i32(-2);
+        assert_eq!(hasher.finish(), digest(&[0xfe, 0xff, 0xff, 0xff]));
+    }
+}
@@ -38,11 +38,84 @@ struct Environment {
     fog_params: vec4<f32>,
     time_params: vec4<f32>,
     screen_params: vec4<f32>,
+    fog_range: vec4<f32>,
+    light_direction: vec4<f32>,
+    // x: animation time in seconds
+    animation: vec4<f32>,
 };
 
 @group(2) @binding(0)
 var<uniform> environment: Environment;
 
+const MAX_POINT_LIGHTS: u32 = 16u;
+
+struct PointLights {
+    // x: number of lights in use
+    count: vec4<u32>,
+    // xyz: world position, w: radius
+    position: array<vec4<f32>, MAX_POINT_LIGHTS>,
+    // rgb: colour already scaled by intensity
+    color: array<vec4<f32>, MAX_POINT_LIGHTS>,
+};
+
+@group(3) @binding(0)
+var<uniform> point_lights: PointLights;
+
+struct Shadow {
+    light_view_proj: mat4x4<f32>,
+    // x: strength (0 when off), y: size of one shadow texel in uv, z: depth bias,
+    // w: normal offset in blocks
+    params: vec4<f32>,
+};
+
+@group(3) @binding(1)
+var<uniform> shadow: Shadow;
+
+@group(3) @binding(2)
+var shadow_map: texture_depth_2d;
+
+@group(3) @binding(3)
+var shadow_sampler: sampler_comparison;
+
+// Fraction of sunlight reaching a surface, filtered over 3x3 shadow texels. Anything
+// outside the shadow map counts as lit.
+fn sun_visibility(world_pos: vec3<f32>, normal: vec3<f32>) -> f32 {
+    if (shadow.params.x <= 0.0) {
+        return 1.0;
+    }
+    // Pushing the lookup off the surface keeps faces from shadowing themselves.
+    let light_clip = shadow.light_view_proj * vec4<f32>(world_pos + normal * shadow.params.w, 1.0);
+    let ndc = light_clip.xyz / light_clip.w;
+    let uv = ndc.xy * vec2<f32>(0.5, -0.5) + vec2<f32>(0.5, 0.5);
+    if (any(uv < vec2<f32>(0.0)) || any(uv > vec2<f32>(1.0)) || ndc.z > 1.0) {
+        return 1.0;
+    }
+    let depth = ndc.z - shadow.params.z;
+    var lit = 0.0;
+    for (var y = -1; y <= 1; y = y + 1) {
+        for (var x = -1; x <= 1; x = x + 1) {
+            let offset = vec2<f32>(f32(x), f32(y)) * shadow.params.y;
+            lit += textureSampleCompareLevel(shadow_map, shadow_sampler, uv + offset, depth);
+        }
+    }
+    return lit / 9.0;
+}
+
+// Sum of the point lights reaching a surface, fading out quadratically toward each radius.
+fn point_lighting(world_pos: vec3<f32>, normal: vec3<f32>) -> vec3<f32> {
+    var total = vec3<f32>(0.0);
+    let count = min(point_lights.count.x, MAX_POINT_LIGHTS);
+    for (var i = 0u; i < count; i = i + 1u) {
+        let offset = point_lights.position[i].xyz - world_pos;
+        let distance = length(offset);
+        let falloff = clamp(1.0 - distance / point_lights.position[i].w, 0.0, 1.0);
+        // Faces turned away still catch a little, so the light doesn't end in a hard edge.
+        let facing = clamp(dot(normal, offset / max(distance, 1e-4)), 0.0, 1.0) * 0.75 + 0.25;
+        total += point_lights.color[i].rgb * falloff * falloff * facing;
+    }
+    return total;
+}
+
 @vertex
 fn vs_main(input: VertexInput) -> VertexOutput {
     var output: VertexOutput;
@@ -56,16 +129,39 @@ fn vs_main(input: VertexInput) -> VertexOutput {
     return output;
 }
 
+// Atlas tiles are 16 texels square.
+const TILE_TEXELS: f32 = 16.0;
+// Tile widths per second: water tops drift, water sides fall.
+const WATER_DRIFT: vec2<f32> = vec2<f32>(0.03, 0.02);
+const WATER_FALL_SPEED: f32 = 0.25;
+
+// Scrolls `uv` within its own atlas tile, clamped half a texel from the edges so linear
+// filtering never reaches into the neighbouring tile.
+fn scroll_in_tile(uv: vec2<f32>, offset: vec2<f32>) -> vec2<f32> {
+    let tile = TILE_TEXELS / vec2<f32>(textureDimensions(atlas_texture));
+    let cell = floor(uv / tile);
+    let local = fract(uv / tile - cell + offset);
+    let margin = 0.5 / TILE_TEXELS;
+    return (cell + clamp(local, vec2<f32>(margin), vec2<f32>(1.0 - margin))) * tile;
+}
+
 @fragment
 fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
-    let albedo = textureSample(atlas_texture, atlas_sampler, input.uv);
+    // Only water is translucent. Side quads run u from bottom to top, so a growing u
+    // offset carries the texture downward.
+    let time = environment.animation.x;
+    let is_water = input.material > 1.5 && input.material < 2.5;
+    let is_top = abs(input.normal.y) > 0.5;
+    let flow = select(vec2<f32>(time * WATER_FALL_SPEED, 0.0), time * WATER_DRIFT, is_top);
+    let albedo_uv = select(input.uv, scroll_in_tile(input.uv, flow), is_water);
+    let albedo = textureSample(atlas_texture, atlas_sampler, albedo_uv);
     if (albedo.a < 0.01) {
         discard;
     }
 
     let base = clamp(albedo.rgb * input.tint, vec3<f32>(0.0), vec3<f32>(1.0));
     let normal = normalize(input.normal);
-    let light_dir = normalize(vec3<f32>(0.5, 1.0, 0.3));
+    let light_dir = normalize(environment.light_direction.xyz);
     let daylight = environment.time_params.x;
 
     // Per-block lighting (0-15 converted to 0.0-1.0)
@@ -74,13 +170,19 @@ fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
     // Directional lighting for visual depth
     let directional = clamp(dot(normal, light_dir), 0.0, 1.0) * 0.3;
 
-    // Combine block light with directional shading
+    // Combine block light with directional shading; shadowed surfaces lose part of both
     let ambient = environment.fog_params.y;
-    let light = (block_light * (0.8 + 0.2 * daylight)) + directional + ambient * 0.2;
+    let shade = 1.0 - (1.0 - sun_visibility(input.world_pos, normal)) * shadow.params.x;
+    let light = ((block_light * (0.8 + 0.2 * daylight)) + directional) * shade + ambient * 0.2;
     var color = base * clamp(light, 0.0, 1.0);
+    color += base * point_lighting(input.world_pos, normal);
 
     var alpha = albedo.a;
-    if (input.material < 1.5) {
+    if (input.material > 2.5) {
+        // Emissive (lava): glows at full strength whatever the light around it.
+        color = albedo.rgb * input.tint;
+        alpha = 1.0;
+    } else if (input.material < 1.5) {
         if (input.material > 0.5 && albedo.a < 0.4) {
             discard;
         }
@@ -95,7 +197,11 @@ fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
     let fog_density = environment.fog_params.x;
     let height_falloff = environment.fog_params.w;
     let height = max(input.world_pos.y - camera_pos.y, 0.0);
-    let fog_factor = clamp(1.0 - exp(-distance * fog_density) * exp(-height * height_falloff), 0.0, 1.0);
+    let distance_fog = clamp(1.0 - exp(-distance * fog_density) * exp(-height * height_falloff), 0.0, 1.0);
+    // Ramp to full fog over the outermost loaded ring so new chunks fade in.
+    let horizontal_distance = length(to_camera.xz);
+    let edge_fog = smoothstep(environment.fog_range.x, environment.fog_range.y, horizontal_distance);
+    let fog_factor = max(distance_fog, edge_fog);
     color = mix(color, environment.fog_color.rgb, fog_factor);
 
     let ndc = input.position.xy / input.position.w;
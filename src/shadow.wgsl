@@ -0,0 +1,47 @@
+// Depth-only pass that draws the world as seen from the sun into the shadow map.
+struct Shadow {
+    light_view_proj: mat4x4<f32>,
+    // x: strength, y: size of one shadow texel in uv, z: depth bias, w: normal offset in blocks
+    params: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> shadow: Shadow;
+
+@group(1) @binding(0)
+var atlas_texture: texture_2d<f32>;
+
+@group(1) @binding(1)
+var atlas_sampler: sampler;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+    @location(3) material: f32,
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) material: f32,
+};
+
+@vertex
+fn vs_main(input: VertexInput) -> VertexOutput {
+    var output: VertexOutput;
+    output.position = shadow.light_view_proj * vec4<f32>(input.position, 1.0);
+    output.uv = input.uv;
+    output.material = input.material;
+    return output;
+}
+
+@fragment
+fn fs_main(input: VertexOutput) {
+    // Cutout texels (leaves, flowers) let the sun through where the world shader drops them.
+    if (input.material > 0.5 && input.material < 1.5) {
+        let alpha = textureSampleLevel(atlas_texture, atlas_sampler, input.uv, 0.0).a;
+        if (alpha < 0.4) {
+            discard;
+        }
+    }
+}
@@ -6,14 +6,26 @@ struct Environment {
     fog_params: vec4<f32>,
     time_params: vec4<f32>,
     screen_params: vec4<f32>,
+    fog_range: vec4<f32>,
+    light_direction: vec4<f32>,
+    animation: vec4<f32>,
 };
 
 @group(0) @binding(0)
 var<uniform> environment: Environment;
 
+// Shares the cloud uniform; only the inverse view-projection is read here.
+struct SkyView {
+    inv_view_proj: mat4x4<f32>,
+};
+
+@group(1) @binding(0)
+var<uniform> view: SkyView;
+
 struct VertexOutput {
     @builtin(position) position: vec4<f32>,
     @location(0) uv: vec2<f32>,
+    @location(1) ndc: vec2<f32>,
 };
 
 @vertex
@@ -28,63 +40,88 @@ fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
     var output: VertexOutput;
     output.position = vec4<f32>(pos, 0.0, 1.0);
     output.uv = vec2<f32>(0.5 * (pos.x + 1.0), 0.5 * (pos.y + 1.0));
+    output.ndc = pos;
     return output;
 }
 
+const TAU: f32 = 6.2831853;
+// Grid cells per unit of view direction; higher packs the stars closer.
+const STAR_GRID: f32 = 50.0;
+// Fraction of grid cells that hold a star.
+const STAR_CHANCE: f32 = 0.05;
+const STAR_RADIUS: f32 = 0.15;
+// Angular radius of the moon disc in radians.
+const MOON_RADIUS: f32 = 0.035;
+const MOON_COLOR: vec3<f32> = vec3<f32>(0.92, 0.93, 0.86);
+
+fn hash3(p: vec3<f32>) -> f32 {
+    return fract(sin(dot(p, vec3<f32>(127.1, 311.7, 74.7))) * 43758.5453);
+}
+
+// The sun's direction for a time of day, matching `WorldEnvironment::light_direction`
+// by day; the moon sits opposite it.
+fn sun_direction(time_of_day: f32) -> vec3<f32> {
+    let phase = time_of_day * TAU;
+    return normalize(vec3<f32>(cos(phase), sin(phase), 0.3));
+}
+
+// Rotates `dir` about the sun's axis so the starfield turns with the sky.
+fn celestial(dir: vec3<f32>, time_of_day: f32) -> vec3<f32> {
+    let angle = -time_of_day * TAU;
+    let c = cos(angle);
+    let s = sin(angle);
+    return vec3<f32>(c * dir.x - s * dir.y, s * dir.x + c * dir.y, dir.z);
+}
+
+// Brightness of the star nearest `dir`: a few grid cells hold one at a hashed offset.
+fn star_field(dir: vec3<f32>) -> f32 {
+    let p = dir * STAR_GRID;
+    let cell = floor(p);
+    let chance = hash3(cell);
+    if (chance > STAR_CHANCE) {
+        return 0.0;
+    }
+    let jitter = vec3<f32>(
+        hash3(cell + vec3<f32>(1.0, 0.0, 0.0)),
+        hash3(cell + vec3<f32>(0.0, 1.0, 0.0)),
+        hash3(cell + vec3<f32>(0.0, 0.0, 1.0)),
+    );
+    let center = cell + 0.2 + jitter * 0.6;
+    let falloff = 1.0 - smoothstep(0.0, STAR_RADIUS, length(p - center));
+    return falloff * (0.4 + 0.6 * chance / STAR_CHANCE);
+}
+
 @fragment
 fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
     let t = clamp(input.uv.y, 0.0, 1.0);
     var sky = mix(environment.sky_horizon.rgb, environment.sky_zenith.rgb, t);
 
+    let near = view.inv_view_proj * vec4<f32>(input.ndc, 0.0, 1.0);
+    let far = view.inv_view_proj * vec4<f32>(input.ndc, 1.0, 1.0);
+    let dir = normalize(far.xyz / far.w - near.xyz / near.w);
+
     // time_params: [daylight, sun_elevation, twilight, time_of_day]
-    let time_of_day = environment.time_params.w;
     let daylight = environment.time_params.x;
+    let twilight = environment.time_params.z;
+    let time_of_day = environment.time_params.w;
+    // Daylight is 0.5 with the sun on the horizon, so stars are gone well before then.
+    let night = 1.0 - smoothstep(0.15, 0.5, daylight);
+    let above_horizon = smoothstep(-0.02, 0.15, dir.y);
 
-    // Stars visibility (fade in at dusk, fade out at dawn)
-    // Night is roughly 0.0-0.25 and 0.75-1.0
-    var star_visibility = 0.0;
-    if (time_of_day < 0.25) {
-        star_visibility = (0.25 - time_of_day) / 0.25;
-    } else if (time_of_day > 0.75) {
-        star_visibility = (time_of_day - 0.75) / 0.25;
-    }
-    star_visibility = clamp(star_visibility * (1.0 - daylight), 0.0, 1.0);
-
-    // Generate stars using noise-like pattern
-    if (star_visibility > 0.01 && input.uv.y > 0.3) {
-        let star_coord = input.uv * environment.screen_params.xy * 0.5;
-        let star_hash = fract(sin(dot(floor(star_coord), vec2<f32>(12.9898, 78.233))) * 43758.5453);
-
-        if (star_hash > 0.998) {
-            let local_pos = fract(star_coord);
-            let dist = length(local_pos - vec2<f32>(0.5, 0.5));
-            let star_intensity = (1.0 - smoothstep(0.0, 0.15, dist)) * star_visibility;
-
-            // Twinkling effect (use time_of_day for animation)
-            let twinkle = 0.7 + 0.3 * sin(time_of_day * 50.0 + star_hash * 6.28);
-            sky += vec3<f32>(star_intensity * twinkle);
-        }
+    let star_visibility = night * (1.0 - 0.6 * twilight) * above_horizon;
+    if (star_visibility > 0.001) {
+        let seed = dot(dir, vec3<f32>(91.0, 57.0, 23.0));
+        let twinkle = 0.8 + 0.2 * sin(environment.animation.x * 3.0 + seed);
+        sky += vec3<f32>(star_field(celestial(dir, time_of_day)) * twinkle * star_visibility);
     }
 
-    // Moon rendering
-    if (star_visibility > 0.01) {
-        // Moon position based on time (opposite to sun)
-        let moon_angle = (time_of_day + 0.5) * 3.14159 * 2.0;
-        let moon_x = 0.5 + cos(moon_angle) * 0.35;
-        let moon_y = 0.5 + sin(moon_angle) * 0.35;
-
-        // Only render moon when it's above horizon
-        if (moon_y > 0.3) {
-            let moon_center = vec2<f32>(moon_x, moon_y);
-            let moon_dist = length(input.uv - moon_center);
-            let moon_radius = 0.04;
-
-            if (moon_dist < moon_radius) {
-                let moon_intensity = (1.0 - smoothstep(moon_radius * 0.7, moon_radius, moon_dist));
-                let moon_color = vec3<f32>(0.95, 0.95, 0.85) * moon_intensity * star_visibility;
-                sky = mix(sky, moon_color, moon_intensity * star_visibility);
-            }
-        }
+    let moon_visibility = night * above_horizon;
+    if (moon_visibility > 0.001) {
+        let moon_cos = dot(dir, -sun_direction(time_of_day));
+        let disc = smoothstep(cos(MOON_RADIUS), cos(MOON_RADIUS * 0.85), moon_cos);
+        let halo = pow(max(moon_cos, 0.0), 400.0) * 0.25;
+        sky = mix(sky, MOON_COLOR, disc * moon_visibility);
+        sky += MOON_COLOR * halo * moon_visibility;
     }
 
     return vec4<f32>(sky, 1.0);
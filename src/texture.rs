@@ -1,7 +1,11 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Result};
 use wgpu::util::DeviceExt;
 
 pub const TILE_SIZE: u32 = 16;
-pub const ATLAS_COLS: u32 = 39;
+pub const ATLAS_COLS: u32 = 47;
 pub const ATLAS_ROWS: u32 = 1;
 pub const ATLAS_WIDTH: u32 = TILE_SIZE * ATLAS_COLS;
 pub const ATLAS_HEIGHT: u32 = TILE_SIZE * ATLAS_ROWS;
@@ -31,6 +35,57 @@ pub const TILE_FLOWER_STEM: TileCoord = (36, 0);
 pub const TILE_FLOWER_LEAF: TileCoord = (37, 0);
 pub const TILE_GLOW_SHROOM_CAP: TileCoord = (38, 0);
 
+/// File stems looked up in a tile directory pack, e.g. `grass_top.png`.
+pub const TILE_NAMES: [(&str, TileCoord); ATLAS_COLS as usize] = [
+    ("grass_top", (0, 0)),
+    ("grass_side", (1, 0)),
+    ("dirt", (2, 0)),
+    ("stone", (3, 0)),
+    ("wood_side", (4, 0)),
+    ("wood_top", (5, 0)),
+    ("sand", (6, 0)),
+    ("leaves", (7, 0)),
+    ("coal_ore", (8, 0)),
+    ("iron_ore", (9, 0)),
+    ("water", (10, 0)),
+    ("rose_petal", TILE_FLOWER_ROSE_PETAL),
+    ("tulip_petal", TILE_FLOWER_TULIP_PETAL),
+    ("terracotta", (13, 0)),
+    ("lily_pad", (14, 0)),
+    ("snow", (15, 0)),
+    ("copper_wire", (16, 0)),
+    ("resistor", (17, 0)),
+    ("voltage_source", (18, 0)),
+    ("ground", (19, 0)),
+    ("wire_top_connected", TILE_WIRE_TOP_CONNECTED),
+    ("wire_top_unconnected", TILE_WIRE_TOP_UNCONNECTED),
+    ("wire_side_connected", TILE_WIRE_SIDE_CONNECTED),
+    ("wire_side_unconnected", TILE_WIRE_SIDE_UNCONNECTED),
+    ("resistor_top_connected", TILE_RESISTOR_TOP_CONNECTED),
+    ("resistor_top_unconnected", TILE_RESISTOR_TOP_UNCONNECTED),
+    ("resistor_side_connected", TILE_RESISTOR_SIDE_CONNECTED),
+    ("resistor_side_unconnected", TILE_RESISTOR_SIDE_UNCONNECTED),
+    ("voltage_top_connected", TILE_VOLTAGE_TOP_CONNECTED),
+    ("voltage_top_unconnected", TILE_VOLTAGE_TOP_UNCONNECTED),
+    ("voltage_side_connected", TILE_VOLTAGE_SIDE_CONNECTED),
+    ("voltage_side_unconnected", TILE_VOLTAGE_SIDE_UNCONNECTED),
+    ("ground_top_connected", TILE_GROUND_TOP_CONNECTED),
+    ("ground_top_unconnected", TILE_GROUND_TOP_UNCONNECTED),
+    ("ground_side_connected", TILE_GROUND_SIDE_CONNECTED),
+    ("ground_side_unconnected", TILE_GROUND_SIDE_UNCONNECTED),
+    ("flower_stem", TILE_FLOWER_STEM),
+    ("flower_leaf", TILE_FLOWER_LEAF),
+    ("glow_shroom_cap", TILE_GLOW_SHROOM_CAP),
+    ("capacitor", (39, 0)),
+    ("switch", (40, 0)),
+    ("led", (41, 0)),
+    ("diode", (42, 0)),
+    ("potentiometer", (43, 0)),
+    ("lava", (44, 0)),
+    ("sponge", (45, 0)),
+    ("wet_sponge", (46, 0)),
+];
+
 pub fn atlas_uv_bounds(tile_x: u32, tile_y: u32) -> (f32, f32, f32, f32) {
     let tile_width = 1.0 / ATLAS_COLS as f32;
     let tile_height = 1.0 / ATLAS_ROWS as f32;
@@ -45,8 +100,248 @@ pub fn atlas_uv_bounds(tile_x: u32, tile_y: u32) -> (f32, f32, f32, f32) {
     (u_min, u_max, v_min, v_max)
 }
 
+/// Directory scanned for extra texture packs at startup.
+pub const TEXTURE_PACK_DIR: &str = "textures";
+
+/// A look for the terrain atlas. Every pack shares the generated tile layout, so
+/// `atlas_uv_bounds` and the tile constants stay valid whichever one is active.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum TexturePack {
+    #[default]
+    Default,
+    Grayscale,
+    /// Oversaturated colours on darkened shadows.
+    Neon,
+    /// A binary PPM (P6) the size of the atlas; alpha is kept from the default tiles.
+    File(PathBuf),
+    /// A folder of 16x16 PNGs named after `TILE_NAMES`; absent tiles keep the default pixels.
+    Directory(PathBuf),
+}
+
+impl TexturePack {
+    /// The built-in packs followed by every `.ppm` and subdirectory in `dir`, sorted by name.
+    pub fn discover(dir: &Path) -> Vec<TexturePack> {
+        let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|path| {
+                        path.is_dir() || path.extension().is_some_and(|ext| ext == "ppm")
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        paths.sort();
+        let mut packs = vec![Self::Default, Self::Grayscale, Self::Neon];
+        packs.extend(paths.into_iter().map(|path| {
+            if path.is_dir() {
+                Self::Directory(path)
+            } else {
+                Self::File(path)
+            }
+        }));
+        packs
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Self::Default => "DEFAULT".to_string(),
+            Self::Grayscale => "GRAYSCALE".to_string(),
+            Self::Neon => "NEON".to_string(),
+            Self::File(path) | Self::Directory(path) => path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_uppercase().replace('_', " "))
+                .unwrap_or_default(),
+        }
+    }
+
+    fn pixels(&self) -> Result<Vec<u8>> {
+        let mut pixels = vec![0u8; (ATLAS_WIDTH * ATLAS_HEIGHT * 4) as usize];
+        generate_tiles(&mut pixels);
+        match self {
+            Self::Default => {}
+            Self::Grayscale => remap_colors(&mut pixels, |[r, g, b]| {
+                let luma = luma(r, g, b);
+                [luma, luma, luma]
+            }),
+            Self::Neon => remap_colors(&mut pixels, |[r, g, b]| {
+                let luma = luma(r, g, b);
+                [r, g, b].map(|c| ((c - luma) * 2.5 + luma * luma * 1.4).clamp(0.0, 1.0))
+            }),
+            Self::File(path) => {
+                let rgb = parse_ppm(&fs::read(path)?, ATLAS_WIDTH, ATLAS_HEIGHT)?;
+                for (pixel, rgb) in pixels.chunks_exact_mut(4).zip(rgb.chunks_exact(3)) {
+                    pixel[..3].copy_from_slice(rgb);
+                }
+            }
+            Self::Directory(dir) => {
+                if !dir.is_dir() {
+                    bail!("{} is not a directory", dir.display());
+                }
+                for (name, (tile_x, tile_y)) in TILE_NAMES {
+                    let path = dir.join(format!("{name}.png"));
+                    let Ok(bytes) = fs::read(&path) else {
+                        continue;
+                    };
+                    match decode_tile_png(&bytes) {
+                        Ok(rgba) => copy_tile(&mut pixels, tile_x, tile_y, &rgba),
+                        Err(err) => eprintln!("Skipping {} ({err})", path.display()),
+                    }
+                }
+            }
+        }
+        Ok(pixels)
+    }
+}
+
+/// Decodes a `TILE_SIZE` square PNG of any 8-bit-or-less colour type into RGBA.
+fn decode_tile_png(bytes: &[u8]) -> Result<Vec<u8>> {
+    let mut decoder = png::Decoder::new(bytes);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer)?;
+    if (info.width, info.height) != (TILE_SIZE, TILE_SIZE) {
+        bail!(
+            "expected {TILE_SIZE}x{TILE_SIZE}, found {}x{}",
+            info.width,
+            info.height
+        );
+    }
+    let data = &buffer[..info.buffer_size()];
+    let rgba = match info.color_type {
+        png::ColorType::Rgba => data.to_vec(),
+        png::ColorType::Rgb => data
+            .chunks_exact(3)
+            .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => data
+            .chunks_exact(2)
+            .flat_map(|ga| [ga[0], ga[0], ga[0], ga[1]])
+            .collect(),
+        png::ColorType::Grayscale => data.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+        png::ColorType::Indexed => bail!("palette was not expanded"),
+    };
+    Ok(rgba)
+}
+
+/// Writes one tile's worth of RGBA rows into the atlas at `(tile_x, tile_y)`.
+fn copy_tile(pixels: &mut [u8], tile_x: u32, tile_y: u32, rgba: &[u8]) {
+    let row_len = (TILE_SIZE * 4) as usize;
+    for (ly, row) in rgba.chunks_exact(row_len).enumerate() {
+        let gy = tile_y * TILE_SIZE + ly as u32;
+        let idx = ((gy * ATLAS_WIDTH + tile_x * TILE_SIZE) * 4) as usize;
+        pixels[idx..idx + row_len].copy_from_slice(row);
+    }
+}
+
+fn luma(r: f32, g: f32, b: f32) -> f32 {
+    0.299 * r + 0.587 * g + 0.114 * b
+}
+
+fn remap_colors(pixels: &mut [u8], f: impl Fn([f32; 3]) -> [f32; 3]) {
+    for pixel in pixels.chunks_exact_mut(4) {
+        let rgb = f([0, 1, 2].map(|i| pixel[i] as f32 / 255.0));
+        for (channel, value) in pixel.iter_mut().zip(rgb) {
+            *channel = (value * 255.0).round() as u8;
+        }
+    }
+}
+
+/// Reads the RGB payload of a binary PPM, rejecting anything but `width` x `height` at 8 bits.
+fn parse_ppm(bytes: &[u8], width: u32, height: u32) -> Result<Vec<u8>> {
+    let mut cursor = 0;
+    let mut fields = Vec::new();
+    while fields.len() < 4 {
+        match bytes.get(cursor) {
+            None => bail!("truncated header"),
+            Some(b'#') => {
+                while bytes.get(cursor).is_some_and(|&byte| byte != b'\n') {
+                    cursor += 1;
+                }
+            }
+            Some(byte) if byte.is_ascii_whitespace() => cursor += 1,
+            Some(_) => {
+                let start = cursor;
+                while bytes
+                    .get(cursor)
+                    .is_some_and(|byte| !byte.is_ascii_whitespace())
+                {
+                    cursor += 1;
+                }
+                fields.push(String::from_utf8_lossy(&bytes[start..cursor]).into_owned());
+            }
+        }
+    }
+    // Exactly one whitespace byte separates the header from the pixels.
+    cursor += 1;
+    if fields[0] != "P6" {
+        bail!("not a binary PPM (magic {:?})", fields[0]);
+    }
+    let dims: Vec<u32> = fields[1..]
+        .iter()
+        .map(|field| {
+            field
+                .parse()
+                .map_err(|_| anyhow!("bad header field {field:?}"))
+        })
+        .collect::<Result<_>>()?;
+    if dims != [width, height, 255] {
+        bail!(
+            "expected {width}x{height} with max value 255, found {}x{} with {}",
+            dims[0],
+            dims[1],
+            dims[2]
+        );
+    }
+    let len = (width * height * 3) as usize;
+    match bytes.get(cursor..cursor + len) {
+        Some(rgb) => Ok(rgb.to_vec()),
+        None => bail!("pixel data is shorter than {len} bytes"),
+    }
+}
+
+/// Pixels for `pack`, or the default atlas when the pack cannot be loaded.
+fn atlas_pixels(pack: &TexturePack) -> Vec<u8> {
+    pack.pixels().unwrap_or_else(|err| {
+        eprintln!(
+            "Texture pack {} is unusable ({err}); using the default atlas",
+            pack.label()
+        );
+        TexturePack::Default
+            .pixels()
+            .expect("the default atlas is generated")
+    })
+}
+
+/// Mean colour of each of `tiles` as `pack` draws them, weighting pixels by alpha so the
+/// holes in cutout tiles don't grey the result.
+pub fn average_tile_colors(pack: &TexturePack, tiles: &[(u32, u32)]) -> Vec<[f32; 3]> {
+    let pixels = atlas_pixels(pack);
+    tiles
+        .iter()
+        .map(|&(tile_x, tile_y)| {
+            let mut sum = [0.0f32; 3];
+            let mut weight = 0.0;
+            for ly in 0..TILE_SIZE {
+                for lx in 0..TILE_SIZE {
+                    let gx = tile_x * TILE_SIZE + lx;
+                    let gy = tile_y * TILE_SIZE + ly;
+                    let idx = ((gy * ATLAS_WIDTH + gx) * 4) as usize;
+                    let alpha = pixels[idx + 3] as f32 / 255.0;
+                    for (channel, total) in sum.iter_mut().enumerate() {
+                        *total += pixels[idx + channel] as f32 / 255.0 * alpha;
+                    }
+                    weight += alpha;
+                }
+            }
+            sum.map(|total| if weight > 0.0 { total / weight } else { 0.0 })
+        })
+        .collect()
+}
+
 pub struct TextureAtlas {
-    _texture: wgpu::Texture,
+    texture: wgpu::Texture,
     _view: wgpu::TextureView,
     _sampler: wgpu::Sampler,
     pub bind_group_layout: wgpu::BindGroupLayout,
@@ -54,10 +349,8 @@ pub struct TextureAtlas {
 }
 
 impl TextureAtlas {
-    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
-        let mut pixels = vec![0u8; (ATLAS_WIDTH * ATLAS_HEIGHT * 4) as usize];
-
-        generate_tiles(&mut pixels);
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue, pack: &TexturePack) -> Self {
+        let pixels = atlas_pixels(pack);
 
         let texture = device.create_texture_with_data(
             queue,
@@ -129,13 +422,37 @@ impl TextureAtlas {
         });
 
         Self {
-            _texture: texture,
+            texture,
             _view: view,
             _sampler: sampler,
             bind_group_layout,
             bind_group,
         }
     }
+
+    /// Rewrites the atlas in place with another pack; the bind group keeps pointing at it.
+    pub fn reload(&self, queue: &wgpu::Queue, pack: &TexturePack) {
+        let pixels = atlas_pixels(pack);
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &pixels,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(ATLAS_WIDTH * 4),
+                rows_per_image: Some(ATLAS_HEIGHT),
+            },
+            wgpu::Extent3d {
+                width: ATLAS_WIDTH,
+                height: ATLAS_HEIGHT,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
 }
 
 fn generate_tiles(pixels: &mut [u8]) {
@@ -191,6 +508,14 @@ fn generate_tiles(pixels: &mut [u8]) {
     fill_tile(pixels, 17, 0, resistor_pattern);
     fill_tile(pixels, 18, 0, voltage_source_pattern);
     fill_tile(pixels, 19, 0, ground_pattern);
+    fill_tile(pixels, 39, 0, capacitor_pattern);
+    fill_tile(pixels, 40, 0, switch_pattern);
+    fill_tile(pixels, 41, 0, led_pattern);
+    fill_tile(pixels, 42, 0, diode_pattern);
+    fill_tile(pixels, 43, 0, potentiometer_pattern);
+    fill_tile(pixels, 44, 0, lava_pattern);
+    fill_tile(pixels, 45, 0, |gx, gy, lx, ly| sponge_pattern(gx, gy, lx, ly, false));
+    fill_tile(pixels, 46, 0, |gx, gy, lx, ly| sponge_pattern(gx, gy, lx, ly, true));
     fill_tile(
         pixels,
         TILE_WIRE_TOP_CONNECTED.0,
@@ -691,6 +1016,41 @@ fn water_pattern(gx: u32, gy: u32, lx: u32, ly: u32) -> [f32; 4] {
     [color[0], color[1], color[2], alpha]
 }
 
+fn lava_pattern(gx: u32, gy: u32, lx: u32, ly: u32) -> [f32; 3] {
+    // Dark crust broken by glowing cracks; the mesh draws it emissive so it reads at night.
+    let crust = noise(gx + 613, gy + 127, 733);
+    let vein = noise(gx + ly * 5 + 59, gy + lx * 3 + 881, 419);
+    let crack = (1.0 - (vein - 0.5).abs() * 8.0).clamp(0.0, 1.0);
+    let heat = (crust * 0.5 + crack * 0.6).clamp(0.0, 1.0);
+    [
+        (0.6 + heat * 0.4).clamp(0.0, 1.0),
+        (0.12 + heat * 0.55).clamp(0.0, 1.0),
+        (0.02 + crack * 0.18).clamp(0.0, 1.0),
+    ]
+}
+
+/// Yellow foam full of dark pores; a soaked sponge turns a darker, greener olive.
+fn sponge_pattern(gx: u32, gy: u32, lx: u32, ly: u32, wet: bool) -> [f32; 3] {
+    let base = if wet {
+        [0.62, 0.6, 0.24]
+    } else {
+        [0.86, 0.78, 0.3]
+    };
+    let variation = (noise(gx + 271, gy + 53, 557) - 0.5) * 0.12;
+    let pore = noise(gx / 2 + lx % 2, gy / 2 + 409, 631) > 0.78;
+    let shade = if pore { -0.28 } else { variation };
+    let sheen = if wet && (lx + ly * 3).is_multiple_of(7) {
+        0.06
+    } else {
+        0.0
+    };
+    [
+        (base[0] + shade + sheen).clamp(0.0, 1.0),
+        (base[1] + shade * 0.9 + sheen).clamp(0.0, 1.0),
+        (base[2] + shade * 0.5 + sheen * 1.4).clamp(0.0, 1.0),
+    ]
+}
+
 fn rose_petal_pattern(gx: u32, gy: u32, lx: u32, ly: u32) -> [f32; 3] {
     let center = (TILE_SIZE as f32 - 1.0) * 0.5;
     let dx = lx as f32 - center;
@@ -921,6 +1281,121 @@ fn resistor_pattern(gx: u32, gy: u32, lx: u32, ly: u32) -> [f32; 3] {
     ]
 }
 
+fn capacitor_pattern(gx: u32, gy: u32, lx: u32, ly: u32) -> [f32; 3] {
+    let u = (lx as f32 + 0.5) / TILE_SIZE as f32;
+    let v = (ly as f32 + 0.5) / TILE_SIZE as f32;
+
+    // Electrolytic can: a rounded blue sleeve with a pale polarity stripe near one end.
+    let curvature = (v - 0.5).abs();
+    let shading = (1.0 - curvature.powf(1.6) * 1.8).clamp(0.35, 1.0);
+    let sleeve = [0.16, 0.3, 0.62];
+    let mut color = [sleeve[0] * shading, sleeve[1] * shading, sleeve[2] * shading];
+
+    if (0.72..0.86).contains(&u) {
+        let stripe = [0.78, 0.84, 0.9];
+        color = [stripe[0] * shading, stripe[1] * shading, stripe[2] * shading];
+    }
+    if !(0.08..0.92).contains(&u) {
+        let rim = 0.62 + (1.0 - curvature * 2.0) * 0.2;
+        color = [rim, rim, rim * 1.04];
+    }
+
+    let grain = (noise(gx + 53, gy + 389, lx + ly) - 0.5) * 0.05;
+    [
+        (color[0] + grain).clamp(0.0, 1.0),
+        (color[1] + grain).clamp(0.0, 1.0),
+        (color[2] + grain).clamp(0.0, 1.0),
+    ]
+}
+
+fn switch_pattern(gx: u32, gy: u32, lx: u32, ly: u32) -> [f32; 3] {
+    let u = (lx as f32 + 0.5) / TILE_SIZE as f32;
+    let v = (ly as f32 + 0.5) / TILE_SIZE as f32;
+
+    // Slate base with a bevelled edge and a brass contact pad at each end.
+    let edge = u.min(1.0 - u).min(v).min(1.0 - v);
+    let slate = if edge < 0.1 { 0.34 } else { 0.26 };
+    let mut color = [slate, slate * 1.04, slate * 1.12];
+    for pad_u in [0.2, 0.8] {
+        let du = (u - pad_u) / 0.1;
+        let dv = (v - 0.5) / 0.16;
+        if du * du + dv * dv < 1.0 {
+            color = [0.78, 0.62, 0.3];
+        }
+    }
+
+    let grain = (noise(gx + 71, gy + 613, lx * ly) - 0.5) * 0.05;
+    [
+        (color[0] + grain).clamp(0.0, 1.0),
+        (color[1] + grain).clamp(0.0, 1.0),
+        (color[2] + grain).clamp(0.0, 1.0),
+    ]
+}
+
+fn led_pattern(gx: u32, gy: u32, lx: u32, ly: u32) -> [f32; 3] {
+    let u = (lx as f32 + 0.5) / TILE_SIZE as f32;
+    let v = (ly as f32 + 0.5) / TILE_SIZE as f32;
+
+    // Near-white epoxy so the mesh tint sets the colour, with a specular spot up top.
+    let du = u - 0.5;
+    let dv = v - 0.5;
+    let radial = (du * du + dv * dv).sqrt() * 2.0;
+    let body = 0.92 - radial * 0.3;
+    let mut color = [body, body * 0.96, body * 0.94];
+    let spot = ((u - 0.36).powi(2) + (v - 0.32).powi(2)).sqrt();
+    if spot < 0.1 {
+        color = [1.0, 1.0, 1.0];
+    }
+
+    let grain = (noise(gx + 29, gy + 157, lx + ly * 3) - 0.5) * 0.03;
+    [
+        (color[0] + grain).clamp(0.0, 1.0),
+        (color[1] + grain).clamp(0.0, 1.0),
+        (color[2] + grain).clamp(0.0, 1.0),
+    ]
+}
+
+fn diode_pattern(gx: u32, gy: u32, lx: u32, ly: u32) -> [f32; 3] {
+    let u = (lx as f32 + 0.5) / TILE_SIZE as f32;
+    let v = (ly as f32 + 0.5) / TILE_SIZE as f32;
+
+    // Matte black glass body with a silver band printed at the cathode end.
+    let shade = 0.12 + (1.0 - (v - 0.5).abs() * 2.0) * 0.06;
+    let mut color = [shade, shade, shade * 1.08];
+    if (0.7..0.82).contains(&u) {
+        color = [0.78, 0.8, 0.84];
+    }
+
+    let grain = (noise(gx + 53, gy + 389, lx * 5 + ly) - 0.5) * 0.04;
+    [
+        (color[0] + grain).clamp(0.0, 1.0),
+        (color[1] + grain).clamp(0.0, 1.0),
+        (color[2] + grain).clamp(0.0, 1.0),
+    ]
+}
+
+fn potentiometer_pattern(gx: u32, gy: u32, lx: u32, ly: u32) -> [f32; 3] {
+    let u = (lx as f32 + 0.5) / TILE_SIZE as f32;
+    let v = (ly as f32 + 0.5) / TILE_SIZE as f32;
+
+    // Blue trimmer housing around a knurled silver dial.
+    let du = u - 0.5;
+    let dv = v - 0.5;
+    let radius = (du * du + dv * dv).sqrt();
+    let mut color = [0.16, 0.3, 0.62];
+    if radius < 0.34 {
+        let knurl = if (dv.atan2(du) * 6.0).sin() > 0.0 { 0.06 } else { 0.0 };
+        color = [0.66 + knurl, 0.68 + knurl, 0.72 + knurl];
+    }
+
+    let grain = (noise(gx + 97, gy + 421, lx * 3 + ly * 7) - 0.5) * 0.04;
+    [
+        (color[0] + grain).clamp(0.0, 1.0),
+        (color[1] + grain).clamp(0.0, 1.0),
+        (color[2] + grain).clamp(0.0, 1.0),
+    ]
+}
+
 fn voltage_source_pattern(gx: u32, gy: u32, lx: u32, ly: u32) -> [f32; 3] {
     let u = (lx as f32 + 0.5) / TILE_SIZE as f32;
     let v = (ly as f32 + 0.5) / TILE_SIZE as f32;
@@ -1222,3 +1697,88 @@ fn ground_connection_side_pattern(gx: u32, gy: u32, lx: u32, ly: u32, connected:
         389,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::{BlockFace, BlockType};
+
+    #[test]
+    fn average_tile_colors_follow_the_pack() {
+        let tiles = [
+            BlockType::Grass.atlas_coords(BlockFace::Top),
+            BlockType::Stone.atlas_coords(BlockFace::Top),
+        ];
+        let [grass, stone] = average_tile_colors(&TexturePack::Default, &tiles)[..] else {
+            panic!("one colour per tile");
+        };
+        assert!(grass[1] > grass[0] && grass[1] > grass[2], "grass is green: {grass:?}");
+        let spread = stone.iter().fold(0.0f32, |acc, &c| acc.max(c))
+            - stone.iter().fold(1.0f32, |acc, &c| acc.min(c));
+        assert!(spread < 0.15, "stone is grey: {stone:?}");
+
+        let gray = average_tile_colors(&TexturePack::Grayscale, &tiles[..1])[0];
+        assert!((gray[0] - gray[1]).abs() < 0.01 && (gray[1] - gray[2]).abs() < 0.01);
+    }
+
+    #[test]
+    fn grayscale_pack_keeps_alpha_and_drops_colour() {
+        let default = TexturePack::Default.pixels().unwrap();
+        let gray = TexturePack::Grayscale.pixels().unwrap();
+        for (base, pixel) in default.chunks_exact(4).zip(gray.chunks_exact(4)) {
+            assert_eq!(pixel[0], pixel[1]);
+            assert_eq!(pixel[1], pixel[2]);
+            assert_eq!(base[3], pixel[3]);
+        }
+    }
+
+    #[test]
+    fn ppm_packs_must_match_the_atlas_size() {
+        let mut ppm = b"P6\n# comment\n2 1\n255\n".to_vec();
+        ppm.extend_from_slice(&[1, 2, 3, 4, 5, 6]);
+        assert_eq!(parse_ppm(&ppm, 2, 1).unwrap(), vec![1, 2, 3, 4, 5, 6]);
+        assert!(parse_ppm(&ppm, 4, 1).is_err());
+        assert!(parse_ppm(&ppm[..ppm.len() - 1], 2, 1).is_err());
+        assert!(parse_ppm(b"P3\n2 1\n255\n", 2, 1).is_err());
+    }
+
+    fn write_png(path: &Path, size: u32, rgb: [u8; 3]) {
+        let file = fs::File::create(path).unwrap();
+        let mut encoder = png::Encoder::new(file, size, size);
+        encoder.set_color(png::ColorType::Rgb);
+        let mut writer = encoder.write_header().unwrap();
+        let data: Vec<u8> = (0..size * size).flat_map(|_| rgb).collect();
+        writer.write_image_data(&data).unwrap();
+    }
+
+    #[test]
+    fn directory_packs_replace_only_the_tiles_they_provide() {
+        let dir = std::env::temp_dir()
+            .join(format!("rustcraft_texture_pack_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        write_png(&dir.join("dirt.png"), TILE_SIZE, [255, 0, 0]);
+        write_png(&dir.join("stone.png"), TILE_SIZE * 2, [0, 0, 255]);
+        let pixels = TexturePack::Directory(dir.clone()).pixels();
+        let _ = fs::remove_dir_all(&dir);
+        let pixels = pixels.unwrap();
+        let default = TexturePack::Default.pixels().unwrap();
+
+        let pixel_at = |pixels: &[u8], tile_x: u32, lx: u32, ly: u32| {
+            let idx = ((ly * ATLAS_WIDTH + tile_x * TILE_SIZE + lx) * 4) as usize;
+            pixels[idx..idx + 4].to_vec()
+        };
+        let dirt = TILE_NAMES.iter().find(|(name, _)| *name == "dirt").unwrap().1;
+        let stone = TILE_NAMES.iter().find(|(name, _)| *name == "stone").unwrap().1;
+        for (lx, ly) in [(0, 0), (7, 9), (15, 15)] {
+            assert_eq!(pixel_at(&pixels, dirt.0, lx, ly), vec![255, 0, 0, 255]);
+            assert_eq!(
+                pixel_at(&pixels, stone.0, lx, ly),
+                pixel_at(&default, stone.0, lx, ly)
+            );
+        }
+        assert_eq!(
+            pixels[..(dirt.0 * TILE_SIZE * 4) as usize],
+            default[..(dirt.0 * TILE_SIZE * 4) as usize]
+        );
+    }
+}
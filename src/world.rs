@@ -1,11 +1,15 @@
 use std::collections::{hash_map::DefaultHasher, HashMap, HashSet};
 use std::f32::consts::TAU;
 use std::hash::{Hash, Hasher};
+use std::path::Path;
 use std::sync::Arc;
 
-use crate::block::{Axis, BlockFace, BlockType};
-use crate::chunk::{Chunk, CHUNK_HEIGHT, CHUNK_SIZE};
-use crate::electric::{BlockPos3, ElectricalSystem};
+use anyhow::Context;
+
+use crate::block::{Axis, BlockFace, BlockType, FluidKind};
+use crate::chunk::{Chunk, CHUNK_HEIGHT, CHUNK_SIZE, MAX_CHUNK_HEIGHT};
+use crate::electric::{BlockPos3, ComponentParams, ElectricalComponent, ElectricalSystem};
+use crate::save::{SaveReader, SaveWriter, StateHasher, SAVE_MAGIC, SAVE_VERSION};
 use cgmath::Point3;
 use noise::{NoiseFn, Perlin};
 use rand::{rngs::SmallRng, Rng, SeedableRng};
@@ -45,9 +49,23 @@ pub const FLUID_LATERAL_FLOW_CAP: u8 = if (MAX_FLUID_LEVEL + 2) / 3 == 0 {
 } else {
     (MAX_FLUID_LEVEL + 2) / 3
 };
+/// Spread limit used by `--fluid-spread` when no distance is given (Minecraft-like).
+pub const DEFAULT_FLUID_SPREAD_LIMIT: u8 = 8;
 pub const WATER_LEVEL: i32 = 84;
+/// Seed every terrain-backed test builds its world from, so runs see the same ground.
+#[cfg(test)]
+pub const TEST_SEED: u64 = 7;
 const GLOBAL_TERRAIN_BASE: f64 = 156.0;
 const MAX_WATER_FILL_DEPTH: i32 = 6;
+/// Cells within this many blocks (on every axis) of a sponge get drained.
+pub const SPONGE_RADIUS: i32 = 2;
+/// Water a sponge takes from each cell per fluid tick, so a full cell empties in a few.
+const SPONGE_DRAIN_PER_TICK: u8 = MAX_FLUID_LEVEL / 3;
+/// Total water a sponge holds before it turns wet: enough to empty its whole reach once.
+const SPONGE_CAPACITY: u32 =
+    ((2 * SPONGE_RADIUS + 1).pow(3) as u32 - 1) * MAX_FLUID_LEVEL as u32;
+/// How far (in columns) `find_spawn` searches around the requested point.
+const SPAWN_SEARCH_RADIUS: i32 = 24;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct ChunkPos {
@@ -69,6 +87,117 @@ pub struct AtmosphereSample {
     pub vignette_strength: f32,
 }
 
+/// Murky blue look used when the camera is underwater.
+const UNDERWATER_FOG_COLOR: [f32; 3] = [0.06, 0.22, 0.42];
+const UNDERWATER_FOG_DENSITY: f32 = 0.32;
+/// Lava is close to opaque: a bright orange wall a block or two away.
+const LAVA_FOG_COLOR: [f32; 3] = [0.82, 0.3, 0.04];
+const LAVA_FOG_DENSITY: f32 = 1.6;
+
+impl AtmosphereSample {
+    /// Blends the sample toward the look from inside `fluid`; `amount` 0 is unchanged,
+    /// 1 fully submerged.
+    pub fn submerged(mut self, fluid: FluidKind, amount: f32) -> Self {
+        let t = clamp01(amount);
+        if t <= 0.0 {
+            return self;
+        }
+        let (fog_color, fog_density) = match fluid {
+            FluidKind::Water => (UNDERWATER_FOG_COLOR, UNDERWATER_FOG_DENSITY),
+            FluidKind::Lava => (LAVA_FOG_COLOR, LAVA_FOG_DENSITY),
+        };
+        self.fog_color = lerp3(self.fog_color, fog_color, t);
+        self.sky_zenith = lerp3(self.sky_zenith, fog_color, t);
+        self.sky_horizon = lerp3(self.sky_horizon, fog_color, t);
+        self.fog_density = lerp(self.fog_density, fog_density, t);
+        self.vignette_strength = lerp(self.vignette_strength, 0.55, t);
+        self
+    }
+}
+
+/// Named looks that pin the atmosphere instead of following the day cycle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum AtmospherePreset {
+    #[default]
+    Dynamic,
+    Sunset,
+    Overcast,
+    Alien,
+}
+
+impl AtmospherePreset {
+    pub const ALL: [AtmospherePreset; 4] = [
+        AtmospherePreset::Dynamic,
+        AtmospherePreset::Sunset,
+        AtmospherePreset::Overcast,
+        AtmospherePreset::Alien,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AtmospherePreset::Dynamic => "DYNAMIC",
+            AtmospherePreset::Sunset => "SUNSET",
+            AtmospherePreset::Overcast => "OVERCAST",
+            AtmospherePreset::Alien => "ALIEN",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|preset| preset.label().eq_ignore_ascii_case(name))
+    }
+
+    pub fn cycle(self, delta: i32) -> Self {
+        let index = Self::ALL.iter().position(|&preset| preset == self).unwrap_or(0) as i32;
+        Self::ALL[(index + delta).rem_euclid(Self::ALL.len() as i32) as usize]
+    }
+
+    /// Fixed sample for the preset, or `None` for the time-of-day driven default.
+    pub fn sample(self) -> Option<AtmosphereSample> {
+        let sample = match self {
+            AtmospherePreset::Dynamic => return None,
+            AtmospherePreset::Sunset => AtmosphereSample {
+                time_of_day: 0.485,
+                sun_elevation: 0.09,
+                daylight: 0.55,
+                twilight: 0.46,
+                sky_zenith: [0.32, 0.34, 0.58],
+                sky_horizon: [0.98, 0.56, 0.30],
+                fog_color: [0.92, 0.58, 0.40],
+                fog_density: 0.05,
+                ambient_strength: 0.5,
+                vignette_strength: 0.3,
+            },
+            AtmospherePreset::Overcast => AtmosphereSample {
+                time_of_day: 0.25,
+                sun_elevation: 1.0,
+                daylight: 0.7,
+                twilight: 0.0,
+                sky_zenith: [0.56, 0.58, 0.62],
+                sky_horizon: [0.70, 0.72, 0.74],
+                fog_color: [0.66, 0.68, 0.70],
+                fog_density: 0.09,
+                ambient_strength: 0.52,
+                vignette_strength: 0.26,
+            },
+            AtmospherePreset::Alien => AtmosphereSample {
+                time_of_day: 0.32,
+                sun_elevation: 0.9,
+                daylight: 0.8,
+                twilight: 0.2,
+                sky_zenith: [0.16, 0.52, 0.46],
+                sky_horizon: [0.78, 0.36, 0.72],
+                fog_color: [0.52, 0.30, 0.58],
+                fog_density: 0.06,
+                ambient_strength: 0.6,
+                vignette_strength: 0.32,
+            },
+        };
+        Some(sample)
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default)]
 pub struct BiomeTints {
     pub grass: [f32; 3],
@@ -76,6 +205,29 @@ pub struct BiomeTints {
     pub water: [f32; 3],
 }
 
+/// Chunks generated and dropped by a single `update_loaded_chunks` call.
+#[derive(Clone, Debug, Default)]
+pub struct ChunkLoadChanges {
+    pub loaded: Vec<ChunkPos>,
+    pub unloaded: Vec<ChunkPos>,
+}
+
+impl ChunkLoadChanges {
+    pub fn is_empty(&self) -> bool {
+        self.loaded.is_empty() && self.unloaded.is_empty()
+    }
+}
+
+/// Aggregate counters for diagnosing memory growth while exploring.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WorldStats {
+    pub loaded_chunks: usize,
+    pub non_air_blocks: u64,
+    pub fluid_cells: u64,
+    pub electrical_nodes: usize,
+    pub electrical_rebuilds: u64,
+}
+
 #[derive(Clone, Debug)]
 pub struct WorldEnvironment {
     time_of_day: f32,
@@ -84,6 +236,11 @@ pub struct WorldEnvironment {
 
 impl WorldEnvironment {
     pub const DEFAULT_DAY_LENGTH: f32 = 720.0;
+    /// Clock hour at `time_of_day` 0.0. The sun's elevation is `sin(time_of_day * TAU)`, so
+    /// 0.0 is sunrise, 0.25 noon, 0.5 sunset and 0.75 midnight on a 06:00 dawn.
+    pub const DAWN_HOUR: f32 = 6.0;
+    /// Day lengths in seconds that `cycle_day_length` steps through, default first.
+    pub const DAY_LENGTH_PRESETS: [f32; 4] = [Self::DEFAULT_DAY_LENGTH, 120.0, 30.0, 2400.0];
 
     pub fn new() -> Self {
         Self {
@@ -100,12 +257,65 @@ impl WorldEnvironment {
         self.time_of_day = value.rem_euclid(1.0);
     }
 
+    /// Current `(hour, minute)` on a 24-hour clock, following `DAWN_HOUR`.
+    pub fn clock_time(&self) -> (u32, u32) {
+        let minutes = ((self.time_of_day * 24.0 + Self::DAWN_HOUR) * 60.0).floor() as u32;
+        let minutes = minutes % (24 * 60);
+        (minutes / 60, minutes % 60)
+    }
+
+    /// Jumps to `hour:minute` on the 24-hour clock that `clock_time` reads.
+    pub fn set_clock_time(&mut self, hour: u32, minute: u32) {
+        let hours = hour as f32 + minute as f32 / 60.0;
+        self.set_time_of_day((hours - Self::DAWN_HOUR) / 24.0);
+    }
+
+    /// True while the sun is above the horizon.
+    pub fn is_daytime(&self) -> bool {
+        self.time_of_day < 0.5
+    }
+
+    pub fn day_length(&self) -> f32 {
+        self.day_length_seconds
+    }
+
     pub fn set_day_length(&mut self, seconds: f32) {
         if seconds > 1.0 {
             self.day_length_seconds = seconds;
         }
     }
 
+    /// Moves to the preset after the current day length, or the first one when a custom
+    /// length is set, and returns it.
+    pub fn cycle_day_length(&mut self) -> f32 {
+        let presets = Self::DAY_LENGTH_PRESETS;
+        let next = presets
+            .iter()
+            .position(|&preset| preset == self.day_length_seconds)
+            .map_or(0, |index| (index + 1) % presets.len());
+        self.day_length_seconds = presets[next];
+        self.day_length_seconds
+    }
+
+    /// Unit vector toward the light that shades block faces: the sun crossing from +X
+    /// to -X by day, tilted toward +Z, and the moon opposite it by night.
+    pub fn light_direction(time_of_day: f32) -> [f32; 3] {
+        let (sin, cos) = (time_of_day * TAU).sin_cos();
+        let (x, y) = if sin >= 0.0 { (cos, sin) } else { (-cos, -sin) };
+        normalize3([x, y, 0.3])
+    }
+
+    /// Unit vector toward a light `elevation_deg` above the horizon, turned
+    /// `azimuth_deg` from +X toward +Z.
+    pub fn direction_from_angles(azimuth_deg: f32, elevation_deg: f32) -> [f32; 3] {
+        let (azimuth, elevation) = (azimuth_deg.to_radians(), elevation_deg.to_radians());
+        [
+            elevation.cos() * azimuth.cos(),
+            elevation.sin(),
+            elevation.cos() * azimuth.sin(),
+        ]
+    }
+
     pub fn advance(&mut self, delta_seconds: f32) {
         if self.day_length_seconds <= 0.0 {
             return;
@@ -135,6 +345,11 @@ fn lerp3(a: [f32; 3], b: [f32; 3], t: f32) -> [f32; 3] {
     ]
 }
 
+fn normalize3(v: [f32; 3]) -> [f32; 3] {
+    let length = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    [v[0] / length, v[1] / length, v[2] / length]
+}
+
 fn mul3(color: [f32; 3], scalar: f32) -> [f32; 3] {
     [color[0] * scalar, color[1] * scalar, color[2] * scalar]
 }
@@ -159,6 +374,29 @@ pub enum BiomeType {
     Meadow,
 }
 
+impl BiomeType {
+    pub const ALL: [BiomeType; 11] = [
+        BiomeType::Plains,
+        BiomeType::Desert,
+        BiomeType::Forest,
+        BiomeType::Mountain,
+        BiomeType::Swamp,
+        BiomeType::Tundra,
+        BiomeType::Jungle,
+        BiomeType::Mesa,
+        BiomeType::Savanna,
+        BiomeType::Taiga,
+        BiomeType::Meadow,
+    ];
+
+    /// Case-insensitive lookup by variant name, e.g. `"desert"`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|biome| format!("{biome:?}").eq_ignore_ascii_case(name))
+    }
+}
+
 fn biome_fog_density(biome: BiomeType) -> f32 {
     match biome {
         BiomeType::Plains => 0.048,
@@ -304,6 +542,174 @@ impl CaveChunkInfo {
     }
 }
 
+/// Layers stamped from y = 0 upward by the superflat preset.
+pub const DEFAULT_SUPERFLAT_LAYERS: [(BlockType, u8); 3] = [
+    (BlockType::Stone, 80),
+    (BlockType::Dirt, 3),
+    (BlockType::Grass, 1),
+];
+
+/// Multipliers applied to a column's relief when the amplified preset is on.
+const AMPLIFIED_HEIGHT_SCALE: f64 = 3.0;
+const AMPLIFIED_DETAIL_AMPLITUDE: f64 = 2.5;
+const AMPLIFIED_MOUNTAIN_CONTINENTAL: f64 = 2.5;
+
+const MAX_CAVE_DENSITY: f64 = 2.0;
+const MAX_VEGETATION_DENSITY: f64 = 4.0;
+/// Shortest column `--height` accepts; anything lower would clip the generated terrain.
+const MIN_WORLD_HEIGHT: usize = CHUNK_HEIGHT;
+
+/// Terrain generator settings. `Default` is the full procedural generator.
+#[derive(Clone, Debug)]
+pub struct WorldGenConfig {
+    /// When set, every column is this stack of `(block, thickness)` layers,
+    /// bottom first, with no noise, trees, flowers, caves, or rivers.
+    pub superflat: Option<Vec<(BlockType, u8)>>,
+    /// Exaggerates relief for dramatic peaks and valleys.
+    pub amplified: bool,
+    /// Scales the cave carving threshold; 0.0 disables caves, 1.0 is the stock density.
+    pub cave_density: f64,
+    /// Forces every column to this biome instead of the climate-driven selection.
+    pub locked_biome: Option<BiomeType>,
+    /// Column height of every chunk; terrain keeps its shape and the extra room is sky.
+    pub height: usize,
+    /// Scales every biome's tree and flower density; 0.0 grows no vegetation at all.
+    pub vegetation_density: f64,
+}
+
+impl Default for WorldGenConfig {
+    fn default() -> Self {
+        Self {
+            superflat: None,
+            amplified: false,
+            cave_density: 1.0,
+            locked_biome: None,
+            height: CHUNK_HEIGHT,
+            vegetation_density: 1.0,
+        }
+    }
+}
+
+impl WorldGenConfig {
+    /// Builds a config from command-line flags: `--flat`, `--amplified`, `--tall`,
+    /// `--no-caves`, `--cave-density=<0.0-2.0>`, `--height=<256-1024>`, `--no-vegetation`,
+    /// `--vegetation=<0.0-4.0>`, and `--biome=<name>`.
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Self {
+        let mut config = Self::default();
+        for arg in args {
+            match arg.as_str() {
+                "--flat" => config.superflat = Some(DEFAULT_SUPERFLAT_LAYERS.to_vec()),
+                "--amplified" => config.amplified = true,
+                "--no-caves" => config.cave_density = 0.0,
+                "--tall" => config.height = CHUNK_HEIGHT * 2,
+                "--no-vegetation" => config.vegetation_density = 0.0,
+                other => {
+                    if let Some(value) = other.strip_prefix("--cave-density=") {
+                        match value.parse::<f64>() {
+                            Ok(density) if density.is_finite() => {
+                                config.cave_density = density.clamp(0.0, MAX_CAVE_DENSITY);
+                            }
+                            _ => eprintln!("Ignoring invalid cave density '{value}'"),
+                        }
+                    } else if let Some(value) = other.strip_prefix("--vegetation=") {
+                        match value.parse::<f64>() {
+                            Ok(density) if density.is_finite() => {
+                                config.vegetation_density =
+                                    density.clamp(0.0, MAX_VEGETATION_DENSITY);
+                            }
+                            _ => eprintln!("Ignoring invalid vegetation density '{value}'"),
+                        }
+                    } else if let Some(value) = other.strip_prefix("--height=") {
+                        match value.parse::<usize>() {
+                            Ok(height) => {
+                                config.height = height.clamp(MIN_WORLD_HEIGHT, MAX_CHUNK_HEIGHT);
+                            }
+                            _ => eprintln!("Ignoring invalid world height '{value}'"),
+                        }
+                    } else if let Some(name) = other.strip_prefix("--biome=") {
+                        match BiomeType::from_name(name) {
+                            Some(biome) => config.locked_biome = Some(biome),
+                            None => eprintln!("Ignoring unknown biome '{name}'"),
+                        }
+                    }
+                }
+            }
+        }
+        config
+    }
+
+    fn write_save(&self, out: &mut SaveWriter) {
+        out.put_u32(self.height as u32);
+        out.put_bool(self.amplified);
+        out.put_f64(self.cave_density);
+        out.put_f64(self.vegetation_density);
+        let biome = self
+            .locked_biome
+            .and_then(|biome| BiomeType::ALL.iter().position(|&other| other == biome));
+        out.put_u8(biome.map_or(u8::MAX, |index| index as u8));
+        out.put_bool(self.superflat.is_some());
+        if let Some(layers) = &self.superflat {
+            out.put_u32(layers.len() as u32);
+            for &(block, thickness) in layers {
+                out.put_u8(block as u8);
+                out.put_u8(thickness);
+            }
+        }
+    }
+
+    fn read_save(input: &mut SaveReader) -> anyhow::Result<Self> {
+        let height = input.u32()? as usize;
+        anyhow::ensure!(
+            (1..=MAX_CHUNK_HEIGHT).contains(&height),
+            "saved world height {height} is out of range"
+        );
+        let amplified = input.bool()?;
+        let cave_density = input.f64()?;
+        let vegetation_density = input.f64()?;
+        let biome = input.u8()?;
+        let locked_biome = if biome == u8::MAX {
+            None
+        } else {
+            Some(
+                *BiomeType::ALL
+                    .get(biome as usize)
+                    .with_context(|| format!("unknown biome {biome} in save"))?,
+            )
+        };
+        let superflat = if input.bool()? {
+            let mut layers = Vec::new();
+            for _ in 0..input.u32()? {
+                let id = input.u8()?;
+                let block = BlockType::from_id(id)
+                    .with_context(|| format!("unknown superflat block id {id}"))?;
+                layers.push((block, input.u8()?));
+            }
+            Some(layers)
+        } else {
+            None
+        };
+        Ok(Self {
+            superflat,
+            amplified,
+            cave_density,
+            locked_biome,
+            height,
+            vegetation_density,
+        })
+    }
+
+    pub fn caves_enabled(&self) -> bool {
+        self.superflat.is_none() && self.cave_density > 0.0
+    }
+
+    /// Topmost solid y of the superflat stack, if the preset is active.
+    fn superflat_height(&self) -> Option<i32> {
+        let layers = self.superflat.as_ref()?;
+        let total: i32 = layers.iter().map(|&(_, thickness)| thickness as i32).sum();
+        Some((total - 1).clamp(0, self.height as i32 - 1))
+    }
+}
+
 pub struct GeneratedChunk {
     pub chunk: Chunk,
     pub cave_info: CaveChunkInfo,
@@ -313,6 +719,7 @@ pub struct GeneratedChunk {
 #[derive(Clone)]
 struct WorldGenContext {
     seed: u64,
+    config: WorldGenConfig,
     detail_noise: Perlin,
     temperature_noise: Perlin,
     moisture_noise: Perlin,
@@ -328,23 +735,34 @@ struct WorldGenContext {
     cave_humidity_noise: Perlin,
 }
 
+/// Perlin seed for one noise layer of a world. The `salt` keeps layers from lining up
+/// with each other; the mix is SplitMix64's finalizer, so nearby seeds diverge fully.
+fn layer_seed(seed: u64, salt: u32) -> u32 {
+    let mut z = seed ^ (salt as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    (z ^ (z >> 31)) as u32
+}
+
 impl WorldGenContext {
-    fn new(seed: u64) -> Self {
+    fn new(seed: u64, config: WorldGenConfig) -> Self {
+        let noise = |salt| Perlin::new(layer_seed(seed, salt));
         Self {
             seed,
-            detail_noise: Perlin::new(42),
-            temperature_noise: Perlin::new(21),
-            moisture_noise: Perlin::new(144),
-            elevation_noise: Perlin::new(7),
-            continental_noise: Perlin::new(313),
-            macro_biome_noise: Perlin::new(487),
-            river_noise: Perlin::new(999),
-            cave_noise: Perlin::new(771),
-            cave_detail_noise: Perlin::new(133),
-            cave_mask_noise: Perlin::new(1973),
-            cave_cluster_noise: Perlin::new(409),
-            cave_biome_noise: Perlin::new(1801),
-            cave_humidity_noise: Perlin::new(2903),
+            config,
+            detail_noise: noise(42),
+            temperature_noise: noise(21),
+            moisture_noise: noise(144),
+            elevation_noise: noise(7),
+            continental_noise: noise(313),
+            macro_biome_noise: noise(487),
+            river_noise: noise(999),
+            cave_noise: noise(771),
+            cave_detail_noise: noise(133),
+            cave_mask_noise: noise(1973),
+            cave_cluster_noise: noise(409),
+            cave_biome_noise: noise(1801),
+            cave_humidity_noise: noise(2903),
         }
     }
 
@@ -369,6 +787,22 @@ impl WorldGenContext {
     }
 
     fn sample_column(&self, x: i32, z: i32) -> ColumnInfo {
+        if let Some(height) = self.config.superflat_height() {
+            let biome = self.config.locked_biome.unwrap_or(BiomeType::Plains);
+            let mut config = self.biome_config(biome);
+            config.tree_density_multiplier = 0.0;
+            config.flower_density = 0.0;
+            return ColumnInfo {
+                biome,
+                config,
+                height,
+                is_river: false,
+                river_strength: 0.0,
+                river_bank: 0.0,
+                altitude: 0.5,
+            };
+        }
+
         let fx = x as f64;
         let fz = z as f64;
 
@@ -396,13 +830,15 @@ impl WorldGenContext {
             + 1.0)
             * 0.5;
 
-        let biome = self.select_biome(
-            continental_norm,
-            temperature.clamp(0.0, 1.0),
-            moisture.clamp(0.0, 1.0),
-            elevation_norm,
-            macro_variation,
-        );
+        let biome = self.config.locked_biome.unwrap_or_else(|| {
+            self.select_biome(
+                continental_norm,
+                temperature.clamp(0.0, 1.0),
+                moisture.clamp(0.0, 1.0),
+                elevation_norm,
+                macro_variation,
+            )
+        });
         let config = self.biome_config(biome);
 
         let mut height_offset = config.height_offset;
@@ -434,6 +870,14 @@ impl WorldGenContext {
             }
         }
 
+        if self.config.amplified {
+            height_scale *= AMPLIFIED_HEIGHT_SCALE;
+            detail_amplitude *= AMPLIFIED_DETAIL_AMPLITUDE;
+            if biome == BiomeType::Mountain {
+                continental_influence *= AMPLIFIED_MOUNTAIN_CONTINENTAL;
+            }
+        }
+
         let elevation_variation = (elevation_norm - 0.5) * height_scale;
 
         let mut height_f = GLOBAL_TERRAIN_BASE
@@ -464,13 +908,14 @@ impl WorldGenContext {
 
         let mut height = height_f.round() as i32;
 
+        let ceiling = self.config.height as i32 - 5;
         if is_river {
-            let max_floor = (WATER_LEVEL - 1).clamp(6, CHUNK_HEIGHT as i32 - 5);
+            let max_floor = (WATER_LEVEL - 1).clamp(6, ceiling);
             let min_floor = (WATER_LEVEL - 3).clamp(6, max_floor);
             height = height.clamp(min_floor, max_floor);
         }
 
-        height = height.clamp(6, CHUNK_HEIGHT as i32 - 5);
+        height = height.clamp(6, ceiling);
 
         ColumnInfo {
             biome,
@@ -776,13 +1221,69 @@ impl WorldGenContext {
     }
 }
 
+/// An electrical part lifted by `World::copy_region`, relative to the copied box's corner.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CopiedComponent {
+    pub offset: (i32, i32, i32),
+    pub block: BlockType,
+    pub axis: Axis,
+    pub face: BlockFace,
+    pub params: ComponentParams,
+    /// Switches keep whether they were open or closed.
+    pub closed: bool,
+}
+
+/// A box of blocks and the electrical parts attached inside it, ready to paste elsewhere.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Schematic {
+    size: (i32, i32, i32),
+    /// One entry per cell, x fastest, then z, then y. Cells hosting parts hold air.
+    blocks: Vec<BlockType>,
+    components: Vec<CopiedComponent>,
+}
+
+impl Schematic {
+    /// Extent of the box in blocks along x, y and z.
+    pub fn size(&self) -> (i32, i32, i32) {
+        self.size
+    }
+
+    pub fn components(&self) -> &[CopiedComponent] {
+        &self.components
+    }
+
+    fn cells(&self) -> impl Iterator<Item = ((i32, i32, i32), BlockType)> + '_ {
+        let (size_x, _, size_z) = self.size;
+        self.blocks.iter().enumerate().map(move |(index, &block)| {
+            let index = index as i32;
+            let offset = (
+                index % size_x,
+                index / (size_x * size_z),
+                index / size_x % size_z,
+            );
+            (offset, block)
+        })
+    }
+}
+
 pub struct World {
     chunks: HashMap<ChunkPos, Chunk>,
     active_fluid_chunks: HashSet<ChunkPos>,
+    /// Chunks whose lava may still move; `step_lava` drops them once it settles.
+    active_lava_chunks: HashSet<ChunkPos>,
+    /// Chunks whose fluid moved since the renderer last took them; see `take_fluid_remesh`.
+    fluid_remesh: HashSet<ChunkPos>,
     cave_chunk_info: HashMap<ChunkPos, CaveChunkInfo>,
     gen: Arc<WorldGenContext>,
     electrical: ElectricalSystem,
     environment: WorldEnvironment,
+    /// Max horizontal cells fluid may travel from its source; `None` spreads freely.
+    fluid_spread_limit: Option<u8>,
+    atmosphere_preset: AtmospherePreset,
+    /// Chunks touched by the running `edit_batch`; `None` outside a batch.
+    edit_batch: Option<HashSet<ChunkPos>>,
+    /// Water soaked up so far by each dry sponge that has started absorbing.
+    sponge_soak: HashMap<(i32, i32, i32), u32>,
 }
 
 impl World {
@@ -792,8 +1293,8 @@ impl World {
 
     /// Get light level at world coordinates (0-15)
     pub fn get_light(&self, x: i32, y: i32, z: i32) -> u8 {
-        if y < 0 || y >= CHUNK_HEIGHT as i32 {
-            return if y >= CHUNK_HEIGHT as i32 { 15 } else { 0 };
+        if y < 0 || y >= self.height() as i32 {
+            return if y >= self.height() as i32 { 15 } else { 0 };
         }
 
         let chunk_x = x.div_euclid(CHUNK_SIZE as i32);
@@ -809,10 +1310,29 @@ impl World {
         }
     }
 
+    /// Column height shared by every chunk in this world.
+    pub fn height(&self) -> usize {
+        self.gen.config.height
+    }
+
     pub fn electrical(&self) -> &ElectricalSystem {
         &self.electrical
     }
 
+    pub fn stats(&self) -> WorldStats {
+        let mut stats = WorldStats {
+            loaded_chunks: self.chunks.len(),
+            electrical_nodes: self.electrical.node_count(),
+            electrical_rebuilds: self.electrical.rebuild_count(),
+            ..WorldStats::default()
+        };
+        for chunk in self.chunks.values() {
+            stats.non_air_blocks += chunk.non_air_count() as u64;
+            stats.fluid_cells += chunk.fluid_count() as u64;
+        }
+        stats
+    }
+
     pub fn electrical_mut(&mut self) -> &mut ElectricalSystem {
         &mut self.electrical
     }
@@ -829,8 +1349,140 @@ impl World {
         self.environment.advance(delta_seconds);
     }
 
-    pub fn tick_electrical(&mut self) {
-        self.electrical.tick();
+    /// Advances the circuits by `dt_seconds`, the span capacitors charge over.
+    pub fn tick_electrical(&mut self, dt_seconds: f32) {
+        if self.electrical.water_leakage() {
+            let wet_cells = self
+                .electrical
+                .exposed_cells()
+                .into_iter()
+                .filter(|cell| self.get_fluid_amount(cell.x, cell.y, cell.z) > 0)
+                .collect();
+            self.electrical.set_wet_cells(&wet_cells);
+        }
+        self.electrical.tick(dt_seconds);
+    }
+
+    /// Runs `edits` as one batch: fluid wake-ups are deferred until the end and the
+    /// electrical networks are rebuilt once, instead of per edited block. Returns every
+    /// chunk whose mesh the batch invalidated, including neighbours of edited border cells.
+    /// Batches don't nest.
+    pub fn edit_batch<F: FnOnce(&mut World)>(&mut self, edits: F) -> HashSet<ChunkPos> {
+        debug_assert!(self.edit_batch.is_none(), "edit batches don't nest");
+        self.edit_batch = Some(HashSet::new());
+        edits(self);
+        let touched = self.edit_batch.take().unwrap_or_default();
+        for &pos in &touched {
+            if self.chunks.contains_key(&pos) {
+                self.queue_fluid_chunk_with_neighbors(pos);
+            }
+        }
+        self.electrical.tick(0.0);
+        touched
+    }
+
+    /// Copies the box spanned by two opposite corners, in either order, including the
+    /// orientation, parameters and switch state of every electrical part inside it.
+    pub fn copy_region(&self, a: (i32, i32, i32), b: (i32, i32, i32)) -> Schematic {
+        let min = (a.0.min(b.0), a.1.min(b.1), a.2.min(b.2));
+        let max = (a.0.max(b.0), a.1.max(b.1), a.2.max(b.2));
+        let size = (max.0 - min.0 + 1, max.1 - min.1 + 1, max.2 - min.2 + 1);
+        let mut blocks = Vec::with_capacity((size.0 * size.1 * size.2) as usize);
+        let mut components = Vec::new();
+        for y in min.1..=max.1 {
+            for z in min.2..=max.2 {
+                for x in min.0..=max.0 {
+                    let offset = (x - min.0, y - min.1, z - min.2);
+                    let world_pos = BlockPos3::new(x, y, z);
+                    let Some(faces) = self.electrical.face_nodes(world_pos) else {
+                        blocks.push(self.get_block(x, y, z));
+                        continue;
+                    };
+                    blocks.push(BlockType::Air);
+                    components.extend(faces.iter().map(|(face, node)| CopiedComponent {
+                        offset,
+                        block: node.component.block_type(),
+                        axis: node.axis,
+                        face,
+                        params: node.params,
+                        closed: node.closed,
+                    }));
+                }
+            }
+        }
+        Schematic {
+            size,
+            blocks,
+            components,
+        }
+    }
+
+    /// Writes `schematic` with its lowest corner at `origin`, replacing everything in the
+    /// box, air included. Returns the chunks whose meshes need rebuilding.
+    pub fn paste_region(
+        &mut self,
+        origin: (i32, i32, i32),
+        schematic: &Schematic,
+    ) -> HashSet<ChunkPos> {
+        let at = |offset: (i32, i32, i32)| {
+            (
+                origin.0 + offset.0,
+                origin.1 + offset.1,
+                origin.2 + offset.2,
+            )
+        };
+        self.edit_batch(|world| {
+            for (offset, block) in schematic.cells() {
+                let (x, y, z) = at(offset);
+                world.set_block(x, y, z, block);
+            }
+            for component in &schematic.components {
+                let (x, y, z) = at(component.offset);
+                world.set_block_with_axis(
+                    x,
+                    y,
+                    z,
+                    component.block,
+                    Some(component.axis),
+                    Some(component.face),
+                );
+                let world_pos = BlockPos3::new(x, y, z);
+                world
+                    .electrical
+                    .set_params(world_pos, component.face, component.params);
+                if component.closed {
+                    world.electrical.toggle_switch(world_pos, component.face);
+                }
+            }
+        })
+    }
+
+    /// Records an edit at `(x, z)` in the running batch. Returns `false` outside a batch,
+    /// where the caller should wake fluids immediately.
+    fn note_batch_edit(&mut self, x: i32, z: i32) -> bool {
+        let Some(touched) = self.edit_batch.as_mut() else {
+            return false;
+        };
+        let size = CHUNK_SIZE as i32;
+        let chunk = ChunkPos {
+            x: x.div_euclid(size),
+            z: z.div_euclid(size),
+        };
+        touched.insert(chunk);
+        let (local_x, local_z) = (x.rem_euclid(size), z.rem_euclid(size));
+        if local_x == 0 {
+            touched.insert(ChunkPos { x: chunk.x - 1, ..chunk });
+        }
+        if local_x == size - 1 {
+            touched.insert(ChunkPos { x: chunk.x + 1, ..chunk });
+        }
+        if local_z == 0 {
+            touched.insert(ChunkPos { z: chunk.z - 1, ..chunk });
+        }
+        if local_z == size - 1 {
+            touched.insert(ChunkPos { z: chunk.z + 1, ..chunk });
+        }
+        true
     }
 
     pub fn chunks_mut(&mut self) -> &mut HashMap<ChunkPos, Chunk> {
@@ -843,6 +1495,7 @@ impl World {
 
     pub fn queue_fluid_chunk(&mut self, pos: ChunkPos) {
         self.active_fluid_chunks.insert(pos);
+        self.active_lava_chunks.insert(pos);
     }
 
     pub fn queue_fluid_chunk_with_neighbors(&mut self, pos: ChunkPos) {
@@ -850,114 +1503,330 @@ impl World {
         self.queue_loaded_neighbors(pos);
     }
 
+    /// Steps water in every chunk with fluid activity: the CPU fallback for the GPU kernel.
     pub fn step_fluids(&mut self) -> bool {
-        // CPU-based fluid simulation fallback
-        // This is a simple cellular automaton approach for water flow
-
         let active_chunks: Vec<ChunkPos> = self.active_fluid_chunks.iter().copied().collect();
-        if active_chunks.is_empty() {
-            return false;
+        let mut any_changed = false;
+        for chunk_pos in active_chunks {
+            any_changed |= self.step_fluid_chunk(chunk_pos, FluidKind::Water);
         }
+        any_changed
+    }
 
-        let mut any_changed = false;
+    /// Refills water cells flanked by two or more full horizontal neighbours, so pools of
+    /// sources never drain. A cell also needs a solid block or full water under it; the
+    /// edge of a waterfall stays finite. Covers every chunk with fluid activity and runs
+    /// before a water step, whichever engine takes it.
+    pub fn regenerate_water_sources(&mut self) -> bool {
+        let is_source = |world: &World, x: i32, y: i32, z: i32| {
+            world.get_fluid_amount(x, y, z) >= MAX_FLUID_LEVEL
+                && world.get_fluid_kind(x, y, z) == FluidKind::Water
+        };
+        let horizontal = |x: i32, y: i32, z: i32| {
+            [(x + 1, y, z), (x - 1, y, z), (x, y, z + 1), (x, y, z - 1)]
+        };
 
-        // Process each active chunk
-        for chunk_pos in active_chunks {
-            let mut updates: Vec<(usize, usize, usize, u8)> = Vec::new();
+        let mut candidates = HashSet::new();
+        for chunk_pos in &self.active_fluid_chunks {
+            let Some(chunk) = self.chunks.get(chunk_pos) else {
+                continue;
+            };
+            for (x, y, z, amount) in chunk.fluids_iter() {
+                if amount < MAX_FLUID_LEVEL || chunk.fluid_kind(x, y, z) != FluidKind::Water {
+                    continue;
+                }
+                let world_x = chunk_pos.x * CHUNK_SIZE as i32 + x as i32;
+                let world_z = chunk_pos.z * CHUNK_SIZE as i32 + z as i32;
+                candidates.extend(horizontal(world_x, y as i32, world_z));
+            }
+        }
 
-            if let Some(chunk) = self.chunks.get(&chunk_pos) {
-                // Collect all fluid positions and their amounts
-                let fluid_cells: Vec<(usize, usize, usize, u8)> =
-                    chunk.fluids_iter().collect();
+        let refills: Vec<(i32, i32, i32)> = candidates
+            .into_iter()
+            .filter(|&(x, y, z)| {
+                let loaded = self.chunks.contains_key(&ChunkPos {
+                    x: x.div_euclid(CHUNK_SIZE as i32),
+                    z: z.div_euclid(CHUNK_SIZE as i32),
+                });
+                let has_room = matches!(
+                    self.fluid_room(x, y, z, FluidKind::Water),
+                    Some(amount) if amount < MAX_FLUID_LEVEL
+                );
+                let supported = y > 0
+                    && (self.get_block(x, y - 1, z).is_solid() || is_source(self, x, y - 1, z));
+                let flanking = horizontal(x, y, z)
+                    .into_iter()
+                    .filter(|&(nx, ny, nz)| is_source(self, nx, ny, nz))
+                    .count();
+                loaded && has_room && supported && flanking >= 2
+            })
+            .collect();
 
-                for (x, y, z, amount) in fluid_cells {
-                    if amount == 0 {
-                        continue;
+        for &(x, y, z) in &refills {
+            self.set_fluid(x, y, z, FluidKind::Water, MAX_FLUID_LEVEL);
+            self.fluid_remesh.insert(ChunkPos {
+                x: x.div_euclid(CHUNK_SIZE as i32),
+                z: z.div_euclid(CHUNK_SIZE as i32),
+            });
+        }
+        !refills.is_empty()
+    }
+
+    /// Drains water around every dry sponge a little each tick. A sponge turns into
+    /// `WetSponge` once it is full or has nothing left in reach, and stops absorbing.
+    pub fn absorb_into_sponges(&mut self) -> bool {
+        let mut sponges = Vec::new();
+        for (chunk_pos, chunk) in &self.chunks {
+            if chunk.sponge_count() == 0 {
+                continue;
+            }
+            for (x, y, z, block) in chunk.iter() {
+                if block == BlockType::Sponge {
+                    sponges.push((
+                        chunk_pos.x * CHUNK_SIZE as i32 + x as i32,
+                        y as i32,
+                        chunk_pos.z * CHUNK_SIZE as i32 + z as i32,
+                    ));
+                }
+            }
+        }
+        // Forget sponges that were broken or picked up since the last tick.
+        self.sponge_soak.retain(|pos, _| sponges.contains(pos));
+
+        let mut changed = false;
+        for (sx, sy, sz) in sponges {
+            let soaked = self.sponge_soak.get(&(sx, sy, sz)).copied().unwrap_or(0);
+            let mut taken = 0;
+            for dy in -SPONGE_RADIUS..=SPONGE_RADIUS {
+                for dz in -SPONGE_RADIUS..=SPONGE_RADIUS {
+                    for dx in -SPONGE_RADIUS..=SPONGE_RADIUS {
+                        let (x, y, z) = (sx + dx, sy + dy, sz + dz);
+                        if soaked + taken >= SPONGE_CAPACITY
+                            || self.get_fluid_kind(x, y, z) != FluidKind::Water
+                        {
+                            continue;
+                        }
+                        let room = (SPONGE_CAPACITY - soaked - taken).min(u8::MAX as u32) as u8;
+                        let removed = self.remove_fluid(x, y, z, SPONGE_DRAIN_PER_TICK.min(room));
+                        if removed > 0 {
+                            taken += removed as u32;
+                            self.fluid_remesh.insert(ChunkPos {
+                                x: x.div_euclid(CHUNK_SIZE as i32),
+                                z: z.div_euclid(CHUNK_SIZE as i32),
+                            });
+                        }
                     }
+                }
+            }
 
-                    let world_x = chunk_pos.x * CHUNK_SIZE as i32 + x as i32;
-                    let world_y = y as i32;
-                    let world_z = chunk_pos.z * CHUNK_SIZE as i32 + z as i32;
+            let soaked = soaked + taken;
+            if soaked >= SPONGE_CAPACITY || (soaked > 0 && taken == 0) {
+                self.sponge_soak.remove(&(sx, sy, sz));
+                self.set_block(sx, sy, sz, BlockType::WetSponge);
+                self.fluid_remesh.insert(ChunkPos {
+                    x: sx.div_euclid(CHUNK_SIZE as i32),
+                    z: sz.div_euclid(CHUNK_SIZE as i32),
+                });
+                changed = true;
+            } else if taken > 0 {
+                self.sponge_soak.insert((sx, sy, sz), soaked);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Steps lava, which only ever simulates here; callers run it less often than water so
+    /// it creeps. Chunks whose lava didn't move drop out until something wakes them.
+    pub fn step_lava(&mut self) -> bool {
+        let active_chunks: Vec<ChunkPos> = self.active_lava_chunks.iter().copied().collect();
+        let mut any_changed = false;
+        for chunk_pos in active_chunks {
+            if self.step_fluid_chunk(chunk_pos, FluidKind::Lava) {
+                any_changed = true;
+            } else {
+                self.active_lava_chunks.remove(&chunk_pos);
+            }
+        }
+        any_changed
+    }
 
-                    // Check if block below is air or has room for fluid
-                    if world_y > 0 {
-                        let below_block = self.get_block(world_x, world_y - 1, world_z);
-                        let below_fluid = self.get_fluid_amount(world_x, world_y - 1, world_z);
+    /// One cellular-automaton step for the `kind` cells of a chunk: fall first, then level
+    /// out sideways. Where lava touches water the lava cools into stone.
+    fn step_fluid_chunk(&mut self, chunk_pos: ChunkPos, kind: FluidKind) -> bool {
+        let mut any_changed = false;
+        let mut updates: Vec<(usize, usize, usize, u8)> = Vec::new();
+        let mut cooled: Vec<(i32, i32, i32)> = Vec::new();
+
+        if let Some(chunk) = self.chunks.get(&chunk_pos) {
+            // Collect all fluid positions and their amounts
+            let fluid_cells: Vec<(usize, usize, usize, u8, u8)> = chunk
+                .fluids_iter()
+                .filter(|&(x, y, z, _)| chunk.fluid_kind(x, y, z) == kind)
+                .map(|(x, y, z, amount)| (x, y, z, amount, chunk.fluid_distance(x, y, z)))
+                .collect();
+
+            for (x, y, z, amount, distance) in fluid_cells {
+                if amount == 0 {
+                    continue;
+                }
+
+                let world_x = chunk_pos.x * CHUNK_SIZE as i32 + x as i32;
+                let world_y = y as i32;
+                let world_z = chunk_pos.z * CHUNK_SIZE as i32 + z as i32;
+
+                let touching = [
+                    (world_x + 1, world_y, world_z),
+                    (world_x - 1, world_y, world_z),
+                    (world_x, world_y + 1, world_z),
+                    (world_x, world_y - 1, world_z),
+                    (world_x, world_y, world_z + 1),
+                    (world_x, world_y, world_z - 1),
+                ]
+                .into_iter()
+                .filter(|&(nx, ny, nz)| {
+                    self.get_fluid_amount(nx, ny, nz) > 0 && self.get_fluid_kind(nx, ny, nz) != kind
+                });
+                match kind {
+                    FluidKind::Lava => {
+                        if touching.count() > 0 {
+                            cooled.push((world_x, world_y, world_z));
+                            continue;
+                        }
+                    }
+                    FluidKind::Water => cooled.extend(touching),
+                }
 
-                        if !below_block.is_solid() && below_fluid < MAX_FLUID_LEVEL {
+                // Check if block below is air or has room for fluid
+                if world_y > 0 {
+                    if let Some(below_fluid) = self.fluid_room(world_x, world_y - 1, world_z, kind)
+                    {
+                        if below_fluid < MAX_FLUID_LEVEL {
                             // Flow downward (gravity)
                             let flow_amount = amount.min(MAX_FLUID_LEVEL - below_fluid).min(FLUID_MIN_FLOW * 3);
                             if flow_amount > 0 {
                                 updates.push((x, y, z, amount.saturating_sub(flow_amount)));
                                 let new_below = (below_fluid as u16 + flow_amount as u16).min(MAX_FLUID_LEVEL as u16) as u8;
-                                self.set_fluid_amount(world_x, world_y - 1, world_z, new_below);
+                                // Falling doesn't count towards the horizontal spread distance.
+                                self.set_spread_fluid(
+                                    world_x,
+                                    world_y - 1,
+                                    world_z,
+                                    kind,
+                                    new_below,
+                                    distance,
+                                );
                                 any_changed = true;
                                 continue; // Prioritize downward flow
                             }
                         }
                     }
+                }
 
-                    // If can't flow down and has enough fluid, try lateral flow
-                    if amount > FLUID_MIN_SOURCE_LEVEL {
-                        let neighbors = [
-                            (world_x + 1, world_y, world_z),
-                            (world_x - 1, world_y, world_z),
-                            (world_x, world_y, world_z + 1),
-                            (world_x, world_y, world_z - 1),
-                        ];
-
-                        let mut total_flow = 0u8;
-                        for &(nx, ny, nz) in &neighbors {
-                            let neighbor_block = self.get_block(nx, ny, nz);
-                            let neighbor_fluid = self.get_fluid_amount(nx, ny, nz);
-
-                            if !neighbor_block.is_solid() {
-                                let level_diff = amount.saturating_sub(neighbor_fluid);
-                                if level_diff > FLUID_FLOW_THRESHOLD as u8 {
-                                    let flow = (level_diff / 4).max(FLUID_MIN_FLOW).min(FLUID_LATERAL_FLOW_CAP);
-                                    let actual_flow = flow.min(amount.saturating_sub(total_flow));
-
-                                    if actual_flow > 0 {
-                                        total_flow = total_flow.saturating_add(actual_flow);
-                                        let new_neighbor = (neighbor_fluid as u16 + actual_flow as u16).min(MAX_FLUID_LEVEL as u16) as u8;
-                                        self.set_fluid_amount(nx, ny, nz, new_neighbor);
-                                        any_changed = true;
-                                    }
+                // If can't flow down and has enough fluid, try lateral flow
+                let next_distance = distance.saturating_add(1);
+                let within_limit =
+                    !matches!(self.fluid_spread_limit, Some(limit) if next_distance > limit);
+                if amount > FLUID_MIN_SOURCE_LEVEL && within_limit {
+                    let neighbors = [
+                        (world_x + 1, world_y, world_z),
+                        (world_x - 1, world_y, world_z),
+                        (world_x, world_y, world_z + 1),
+                        (world_x, world_y, world_z - 1),
+                    ];
+
+                    let mut total_flow = 0u8;
+                    for &(nx, ny, nz) in &neighbors {
+                        if let Some(neighbor_fluid) = self.fluid_room(nx, ny, nz, kind) {
+                            let level_diff = amount.saturating_sub(neighbor_fluid);
+                            if level_diff > FLUID_FLOW_THRESHOLD as u8 {
+                                let flow = (level_diff / 4)
+                                    .max(FLUID_MIN_FLOW)
+                                    .min(FLUID_LATERAL_FLOW_CAP);
+                                let actual_flow = flow.min(amount.saturating_sub(total_flow));
+
+                                if actual_flow > 0 {
+                                    total_flow = total_flow.saturating_add(actual_flow);
+                                    let new_neighbor = (neighbor_fluid as u16
+                                        + actual_flow as u16)
+                                        .min(MAX_FLUID_LEVEL as u16)
+                                        as u8;
+                                    self.set_spread_fluid(
+                                        nx,
+                                        ny,
+                                        nz,
+                                        kind,
+                                        new_neighbor,
+                                        next_distance,
+                                    );
+                                    any_changed = true;
                                 }
                             }
                         }
+                    }
 
-                        if total_flow > 0 {
-                            updates.push((x, y, z, amount.saturating_sub(total_flow)));
-                        }
+                    if total_flow > 0 {
+                        updates.push((x, y, z, amount.saturating_sub(total_flow)));
                     }
+                }
 
-                    // Evaporate very small amounts
-                    if amount <= FLUID_MIN_FLOW && y > 0 {
-                        let below_block = self.get_block(world_x, world_y - 1, world_z);
-                        if !below_block.is_solid() || self.get_fluid_amount(world_x, world_y - 1, world_z) == 0 {
-                            updates.push((x, y, z, 0));
-                            any_changed = true;
-                        }
+                // Evaporate very small amounts
+                if amount <= FLUID_MIN_FLOW && y > 0 {
+                    let below_block = self.get_block(world_x, world_y - 1, world_z);
+                    if !below_block.is_solid()
+                        || self.get_fluid_amount(world_x, world_y - 1, world_z) == 0
+                    {
+                        updates.push((x, y, z, 0));
+                        any_changed = true;
                     }
                 }
             }
+        }
 
-            // Apply updates to this chunk
-            if let Some(chunk) = self.chunks.get_mut(&chunk_pos) {
-                for (x, y, z, new_amount) in updates {
-                    chunk.set_fluid(x, y, z, new_amount);
-                }
+        // Apply updates to this chunk
+        if let Some(chunk) = self.chunks.get_mut(&chunk_pos) {
+            for (x, y, z, new_amount) in updates {
+                chunk.set_fluid(x, y, z, new_amount);
+            }
+        }
+        for (x, y, z) in cooled {
+            if self.get_fluid_kind(x, y, z) == FluidKind::Lava {
+                self.set_block(x, y, z, BlockType::Stone);
+                any_changed = true;
             }
         }
 
+        if any_changed {
+            self.fluid_remesh.insert(chunk_pos);
+        }
         any_changed
     }
 
+    /// Chunks whose fluid moved in simulation steps since the last call. Flow and cooling
+    /// can reach one cell past a chunk's edge, so callers remesh their neighbours as well.
+    pub fn take_fluid_remesh(&mut self) -> HashSet<ChunkPos> {
+        std::mem::take(&mut self.fluid_remesh)
+    }
+
+    /// Fluid already in a cell that `kind` could flow into, or `None` if a block or another
+    /// liquid fills it.
+    fn fluid_room(&self, x: i32, y: i32, z: i32, kind: FluidKind) -> Option<u8> {
+        let amount = self.get_fluid_amount(x, y, z);
+        let blocked = if amount > 0 {
+            self.get_fluid_kind(x, y, z) != kind
+        } else {
+            self.get_block(x, y, z).is_solid()
+        };
+        (!blocked).then_some(amount)
+    }
+
     pub fn finalize_fluid_chunk_state(&mut self, pos: ChunkPos, changed: bool, has_fluid: bool) {
         if changed {
             // Chunk has active fluid simulation - keep it in the active set and queue neighbors
             self.active_fluid_chunks.insert(pos);
+            self.fluid_remesh.insert(pos);
+            // Water that moved may now touch lava, which only the lava step cools.
+            self.active_lava_chunks.insert(pos);
             self.queue_loaded_neighbors(pos);
         } else {
             // Chunk is stable (no changes this tick) - remove from active simulation
@@ -975,7 +1844,7 @@ impl World {
                 z: pos.z + dz,
             };
             if self.chunks.contains_key(&neighbor) {
-                self.active_fluid_chunks.insert(neighbor);
+                self.queue_fluid_chunk(neighbor);
             }
         }
     }
@@ -1002,6 +1871,9 @@ impl World {
     }
 
     fn atmosphere_from_column(&self, column: &ColumnInfo) -> AtmosphereSample {
+        if let Some(sample) = self.atmosphere_preset.sample() {
+            return sample;
+        }
         let time = self.environment.time_of_day();
         let sun_phase = time * TAU;
         let sun_elevation = sun_phase.sin();
@@ -1107,6 +1979,25 @@ impl World {
     }
 
     pub fn new() -> Self {
+        Self::with_config(WorldGenConfig::default())
+    }
+
+    /// A world with a fresh seed; `seed()` reports it so the terrain can be shared.
+    pub fn with_config(config: WorldGenConfig) -> Self {
+        Self::with_seed_and_config(Self::random_seed(), config)
+    }
+
+    /// Default terrain that is identical for every world built from the same `seed`.
+    #[allow(dead_code)]
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_seed_and_config(seed, WorldGenConfig::default())
+    }
+
+    pub fn with_seed_and_config(seed: u64, config: WorldGenConfig) -> Self {
+        Self::from_gen(WorldGenContext::new(seed, config))
+    }
+
+    fn random_seed() -> u64 {
         // Generate a random seed based on system time and random source
         use std::time::{SystemTime, UNIX_EPOCH};
         let time_seed = SystemTime::now()
@@ -1114,65 +2005,249 @@ impl World {
             .unwrap_or_default()
             .as_secs();
         let random_component = rand::random::<u32>() as u64;
-        let seed = time_seed.wrapping_mul(0x5DEECE66D).wrapping_add(random_component);
+        time_seed.wrapping_mul(0x5DEECE66D).wrapping_add(random_component)
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.gen.seed
+    }
 
-        let gen = Arc::new(WorldGenContext::new(seed));
+    fn from_gen(gen: WorldGenContext) -> Self {
+        let gen = Arc::new(gen);
         Self {
             chunks: HashMap::new(),
             active_fluid_chunks: HashSet::new(),
+            active_lava_chunks: HashSet::new(),
+            fluid_remesh: HashSet::new(),
             cave_chunk_info: HashMap::new(),
             gen,
             electrical: ElectricalSystem::new(),
             environment: WorldEnvironment::new(),
+            fluid_spread_limit: None,
+            sponge_soak: HashMap::new(),
+            atmosphere_preset: AtmospherePreset::Dynamic,
+            edit_batch: None,
+        }
+    }
+
+    pub fn atmosphere_preset(&self) -> AtmospherePreset {
+        self.atmosphere_preset
+    }
+
+    pub fn set_atmosphere_preset(&mut self, preset: AtmospherePreset) {
+        self.atmosphere_preset = preset;
+    }
+
+    /// Stable digest of loaded blocks, fluid amounts and electrical components. Identical
+    /// worlds hash identically regardless of load order, platform or Rust release; any
+    /// edit changes the value.
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = StateHasher::new();
+        let mut positions: Vec<ChunkPos> = self.chunks.keys().copied().collect();
+        positions.sort_by_key(|pos| (pos.x, pos.z));
+        for pos in positions {
+            hasher.put_i32(pos.x);
+            hasher.put_i32(pos.z);
+            self.chunks[&pos].hash_contents(&mut hasher);
+        }
+        self.electrical.hash_state(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Writes the seed, generator settings, every loaded chunk (blocks, fluids, lighting)
+    /// and all electrical components to `path`. Chunks that aren't loaded regenerate
+    /// from the seed after a load, so only edits near the player need to be in memory.
+    pub fn save_to_path(&self, path: &Path) -> anyhow::Result<()> {
+        let mut out = SaveWriter::new();
+        out.put_bytes(SAVE_MAGIC);
+        out.put_u32(SAVE_VERSION);
+        out.put_u64(self.gen.seed);
+        self.gen.config.write_save(&mut out);
+
+        let mut positions: Vec<ChunkPos> = self.chunks.keys().copied().collect();
+        positions.sort_by_key(|pos| (pos.x, pos.z));
+        out.put_u32(positions.len() as u32);
+        for pos in positions {
+            out.put_i32(pos.x);
+            out.put_i32(pos.z);
+            out.put_bool(self.active_fluid_chunks.contains(&pos));
+            out.put_bool(self.active_lava_chunks.contains(&pos));
+            self.chunks[&pos].write_save(&mut out);
+        }
+        self.electrical.write_save(&mut out);
+
+        std::fs::write(path, out.into_bytes())
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// Reads a world written by `save_to_path`. Runtime options such as the fluid spread
+    /// limit and atmosphere preset start at their defaults, and circuit telemetry is
+    /// recomputed on the first `tick_electrical`.
+    pub fn load_from_path(path: &Path) -> anyhow::Result<Self> {
+        let bytes =
+            std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+        let mut input = SaveReader::new(&bytes);
+        anyhow::ensure!(
+            input.take(SAVE_MAGIC.len())? == SAVE_MAGIC,
+            "{} is not a world save",
+            path.display()
+        );
+        let version = input.u32()?;
+        anyhow::ensure!(
+            version == SAVE_VERSION,
+            "unsupported save version {version} (expected {SAVE_VERSION})"
+        );
+        let seed = input.u64()?;
+        let config = WorldGenConfig::read_save(&mut input)?;
+        let mut world = Self::from_gen(WorldGenContext::new(seed, config));
+
+        for _ in 0..input.u32()? {
+            let pos = ChunkPos {
+                x: input.i32()?,
+                z: input.i32()?,
+            };
+            let active = input.bool()?;
+            let lava_active = input.bool()?;
+            let chunk = Chunk::read_save(&mut input)
+                .with_context(|| format!("chunk ({}, {})", pos.x, pos.z))?;
+            anyhow::ensure!(
+                chunk.height() == world.height(),
+                "chunk ({}, {}) is {} cells tall but the world is {}",
+                pos.x,
+                pos.z,
+                chunk.height(),
+                world.height()
+            );
+            world.chunks.insert(pos, chunk);
+            if active {
+                world.active_fluid_chunks.insert(pos);
+            }
+            if lava_active {
+                world.active_lava_chunks.insert(pos);
+            }
         }
+        world.electrical.read_save(&mut input)?;
+        anyhow::ensure!(input.is_at_end(), "unexpected data after the end of the save");
+        Ok(world)
     }
 
-    pub fn update_loaded_chunks(&mut self, camera_pos: Point3<f32>, render_distance: i32) -> bool {
+    pub fn fluid_spread_limit(&self) -> Option<u8> {
+        self.fluid_spread_limit
+    }
+
+    pub fn set_fluid_spread_limit(&mut self, limit: Option<u8>) {
+        self.fluid_spread_limit = limit;
+    }
+
+    pub fn update_loaded_chunks(
+        &mut self,
+        camera_pos: Point3<f32>,
+        render_distance: i32,
+    ) -> ChunkLoadChanges {
+        self.update_loaded_chunks_within_budget(camera_pos, render_distance, usize::MAX)
+    }
+
+    /// Like `update_loaded_chunks`, but generates at most `max_new` missing chunks, nearest
+    /// the player first, so the ground underfoot appears before the outer ring. Call it again
+    /// on later frames to fill in the rest.
+    pub fn update_loaded_chunks_within_budget(
+        &mut self,
+        camera_pos: Point3<f32>,
+        render_distance: i32,
+        max_new: usize,
+    ) -> ChunkLoadChanges {
         let player_chunk_x = (camera_pos.x / CHUNK_SIZE as f32).floor() as i32;
         let player_chunk_z = (camera_pos.z / CHUNK_SIZE as f32).floor() as i32;
-        let mut changed = false;
-        let mut new_chunks = Vec::new();
 
+        let mut missing = Vec::new();
         for cz in (player_chunk_z - render_distance)..=(player_chunk_z + render_distance) {
             for cx in (player_chunk_x - render_distance)..=(player_chunk_x + render_distance) {
                 let pos = ChunkPos { x: cx, z: cz };
                 if !self.chunks.contains_key(&pos) {
-                    let chunk = self.generate_chunk(pos);
-                    let has_fluid = chunk.fluids_iter().next().is_some();
-                    self.chunks.insert(pos, chunk);
-                    if has_fluid {
-                        self.queue_fluid_chunk(pos);
-                    }
-                    new_chunks.push(pos);
-                    changed = true;
+                    missing.push(pos);
                 }
             }
         }
+        missing.sort_by_key(|pos| {
+            let (dx, dz) = (pos.x - player_chunk_x, pos.z - player_chunk_z);
+            dx * dx + dz * dz
+        });
+        missing.truncate(max_new);
+
+        let mut new_chunks = Vec::with_capacity(missing.len());
+        for pos in missing {
+            // Generated rivers and lakes are real fluid cells laid down settled, so the
+            // chunk isn't queued here; an edit nearby wakes it into the simulation.
+            let chunk = self.generate_chunk(pos);
+            self.chunks.insert(pos, chunk);
+            new_chunks.push(pos);
+        }
 
         // Calculate lighting for newly generated chunks
         use crate::lighting::LightingSystem;
-        for pos in new_chunks {
+        for &pos in &new_chunks {
             LightingSystem::calculate_skylight(self, pos);
             LightingSystem::calculate_blocklight(self, pos);
         }
 
         let unload_distance = render_distance + 2;
+        let mut unloaded = Vec::new();
         self.chunks.retain(|pos, _| {
             let dx = (pos.x - player_chunk_x).abs();
             let dz = (pos.z - player_chunk_z).abs();
             let keep = dx <= unload_distance && dz <= unload_distance;
             if !keep {
-                changed = true;
                 self.active_fluid_chunks.remove(pos);
+                self.active_lava_chunks.remove(pos);
+                unloaded.push(*pos);
             }
             keep
         });
 
-        changed
+        ChunkLoadChanges {
+            loaded: new_chunks,
+            unloaded,
+        }
+    }
+
+    /// Throws away a loaded chunk (edits, fluids and the components hosted in it) and
+    /// lays the procedural terrain back down from the seed. Returns the loaded chunks
+    /// whose meshes are now stale, `pos` and its neighbours; empty if `pos` isn't loaded.
+    pub fn regenerate_chunk(&mut self, pos: ChunkPos) -> Vec<ChunkPos> {
+        if !self.chunks.contains_key(&pos) {
+            return Vec::new();
+        }
+        let chunk = self.generate_chunk(pos);
+        self.chunks.insert(pos, chunk);
+        self.active_fluid_chunks.remove(&pos);
+        self.active_lava_chunks.remove(&pos);
+        // Player water left in a neighbour may now flow into the fresh terrain.
+        self.queue_loaded_neighbors(pos);
+        self.electrical.remove_chunk_components(pos);
+
+        let stale: Vec<ChunkPos> = [(0, 0), (1, 0), (-1, 0), (0, 1), (0, -1)]
+            .into_iter()
+            .map(|(dx, dz)| ChunkPos {
+                x: pos.x + dx,
+                z: pos.z + dz,
+            })
+            .filter(|chunk| self.chunks.contains_key(chunk))
+            .collect();
+        use crate::lighting::LightingSystem;
+        LightingSystem::calculate_skylight(self, pos);
+        // Removed light sources near the border may have lit the neighbours too.
+        for &chunk in &stale {
+            LightingSystem::calculate_blocklight(self, chunk);
+        }
+        stale
     }
 
     fn generate_chunk(&self, pos: ChunkPos) -> Chunk {
-        let mut chunk = Chunk::new();
+        if let Some(layers) = &self.gen.config.superflat {
+            return Self::generate_superflat_chunk(layers, self.height());
+        }
+
+        let mut chunk = Chunk::with_height(self.height());
         let mut rng = self.gen.chunk_rng(pos);
 
         for x in 0..CHUNK_SIZE {
@@ -1182,7 +2257,7 @@ impl World {
                 let column = self.gen.sample_column(world_x, world_z);
                 let biome_cfg = column.config;
                 let height = column.height;
-                for y in 0..CHUNK_HEIGHT {
+                for y in 0..chunk.height() {
                     let world_y = y as i32;
                     let mut block_type = if world_y > height {
                         BlockType::Air
@@ -1205,7 +2280,12 @@ impl World {
                     }
                 }
 
-                if biome_cfg.tree_density_multiplier > 0.0 {
+                let tree_density =
+                    biome_cfg.tree_density_multiplier * self.gen.config.vegetation_density;
+                let flower_density =
+                    (biome_cfg.flower_density * self.gen.config.vegetation_density).min(1.0);
+
+                if tree_density > 0.0 {
                     let canopy_radius = biome_cfg.tree_canopy_radius as usize;
                     let canopy_layers = biome_cfg.tree_canopy_layers.max(1) as usize;
                     let (min_height, max_height) = biome_cfg.tree_height_range;
@@ -1221,7 +2301,7 @@ impl World {
                         x,
                         z,
                         height,
-                        biome_cfg.tree_density_multiplier,
+                        tree_density,
                         canopy_radius,
                     ) {
                         let base_y = ground_y + 1;
@@ -1247,14 +2327,14 @@ impl World {
                     }
                 }
 
-                if biome_cfg.flower_density > 0.0 && height >= 0 {
-                    let ground_y = height.min((CHUNK_HEIGHT - 1) as i32) as usize;
+                if flower_density > 0.0 && height >= 0 {
+                    let ground_y = height.min(chunk.height() as i32 - 1) as usize;
                     let flower_y = height + 1;
-                    if flower_y >= 0 && flower_y < CHUNK_HEIGHT as i32 {
+                    if flower_y >= 0 && flower_y < chunk.height() as i32 {
                         let flower_usize = flower_y as usize;
                         if chunk.get_block(x, ground_y, z) == biome_cfg.surface
                             && chunk.get_block(x, flower_usize, z) == BlockType::Air
-                            && rng.gen_bool(biome_cfg.flower_density)
+                            && rng.gen_bool(flower_density)
                         {
                             let flower = self.random_flower(&mut rng);
                             chunk.set_block(x, flower_usize, z, flower);
@@ -1262,15 +2342,16 @@ impl World {
                     }
                 }
 
-                let ground_index = height.clamp(0, CHUNK_HEIGHT as i32 - 1) as usize;
+                let top_y = chunk.height() as i32 - 1;
+                let ground_index = height.clamp(0, top_y) as usize;
                 let has_ground = chunk.get_block(x, ground_index, z).is_solid();
-                let bed_index = (height - 1).clamp(0, CHUNK_HEIGHT as i32 - 1) as usize;
+                let bed_index = (height - 1).clamp(0, top_y) as usize;
                 let has_bed = chunk.get_block(x, bed_index, z).is_solid();
 
                 if column.river_bank > 0.18 && has_ground && height <= WATER_LEVEL + 6 {
                     let layers = ((column.river_bank * 3.0).ceil() as i32).max(0);
                     for step in 0..=layers {
-                        let carve_y = (height - step).clamp(0, CHUNK_HEIGHT as i32 - 1) as usize;
+                        let carve_y = (height - step).clamp(0, top_y) as usize;
                         if chunk.get_block(x, carve_y, z).is_solid() {
                             chunk.set_block(x, carve_y, z, biome_cfg.river_bed);
                         }
@@ -1282,7 +2363,7 @@ impl World {
                         continue;
                     }
                     if has_ground && has_bed {
-                        let fluid_y = (height + 1).clamp(0, CHUNK_HEIGHT as i32 - 1) as usize;
+                        let fluid_y = (height + 1).clamp(0, top_y) as usize;
                         let mut target_level =
                             (MAX_FLUID_LEVEL as f64 * column.river_strength.clamp(0.35, 1.0)) as u8;
                         target_level = target_level
@@ -1295,7 +2376,7 @@ impl World {
                     && has_bed
                     && height <= WATER_LEVEL + 2
                 {
-                    let fluid_y = (height + 1).clamp(0, CHUNK_HEIGHT as i32 - 1) as usize;
+                    let fluid_y = (height + 1).clamp(0, top_y) as usize;
                     let fill_strength =
                         (column.river_strength * 0.6 + column.river_bank * 0.55).clamp(0.25, 0.75);
                     let mut level = (MAX_FLUID_LEVEL as f64 * fill_strength) as u8;
@@ -1307,7 +2388,7 @@ impl World {
                     && height < WATER_LEVEL - 1
                     && height >= WATER_LEVEL - MAX_WATER_FILL_DEPTH
                 {
-                    let fluid_y = (height + 1).clamp(0, CHUNK_HEIGHT as i32 - 1) as usize;
+                    let fluid_y = (height + 1).clamp(0, top_y) as usize;
                     chunk.set_fluid(x, fluid_y, z, FLUID_SURFACE_LEVEL);
                 }
 
@@ -1328,17 +2409,26 @@ impl World {
                                 && nz >= 0
                                 && nz < CHUNK_SIZE as i32
                             {
+                                // Channel columns lay their own floor and water; widening
+                                // into them would move the floor `sample_column` reports.
+                                if self
+                                    .gen
+                                    .sample_column(world_x + dx, world_z + dz)
+                                    .is_river
+                                {
+                                    continue;
+                                }
                                 let nxu = nx as usize;
                                 let nzu = nz as usize;
                                 let carve_y =
-                                    (height - 1).clamp(0, CHUNK_HEIGHT as i32 - 1) as usize;
+                                    (height - 1).clamp(0, top_y) as usize;
                                 chunk.set_block(nxu, carve_y, nzu, biome_cfg.river_bed);
                                 let bed_check = carve_y.saturating_sub(1);
                                 if !chunk.get_block(nxu, bed_check, nzu).is_solid() {
                                     continue;
                                 }
                                 let fluid_y =
-                                    (height + 1).clamp(0, CHUNK_HEIGHT as i32 - 1) as usize;
+                                    (height + 1).clamp(0, top_y) as usize;
                                 let mut target_level = (MAX_FLUID_LEVEL as f64
                                     * column.river_strength.clamp(0.35, 1.0))
                                     as u8;
@@ -1354,16 +2444,16 @@ impl World {
                 if column.biome == BiomeType::Swamp {
                     let mut target_fluid_y = None;
                     if column.is_river {
-                        target_fluid_y = Some((height + 1).clamp(0, CHUNK_HEIGHT as i32 - 1));
+                        target_fluid_y = Some((height + 1).clamp(0, top_y));
                     } else if height <= WATER_LEVEL {
-                        target_fluid_y = Some(WATER_LEVEL.clamp(0, CHUNK_HEIGHT as i32 - 1));
+                        target_fluid_y = Some(WATER_LEVEL.clamp(0, top_y));
                     }
 
                     if let Some(fluid_y) = target_fluid_y {
                         let fluid_y_usize = fluid_y as usize;
                         if chunk.get_fluid(x, fluid_y_usize, z) > 0 {
                             let lily_y = fluid_y_usize + 1;
-                            if lily_y < CHUNK_HEIGHT
+                            if lily_y < chunk.height()
                                 && chunk.get_block(x, lily_y, z) == BlockType::Air
                                 && rng.gen_bool(0.15)
                             {
@@ -1378,8 +2468,29 @@ impl World {
         chunk
     }
 
+    fn generate_superflat_chunk(layers: &[(BlockType, u8)], height: usize) -> Chunk {
+        let mut chunk = Chunk::with_height(height);
+        let mut y = 0;
+        for &(block, thickness) in layers {
+            for _ in 0..thickness {
+                if y >= chunk.height() {
+                    return chunk;
+                }
+                if block != BlockType::Air {
+                    for x in 0..CHUNK_SIZE {
+                        for z in 0..CHUNK_SIZE {
+                            chunk.set_block(x, y, z, block);
+                        }
+                    }
+                }
+                y += 1;
+            }
+        }
+        chunk
+    }
+
     pub fn get_block(&self, x: i32, y: i32, z: i32) -> BlockType {
-        if y < 0 || y >= CHUNK_HEIGHT as i32 {
+        if y < 0 || y >= self.height() as i32 {
             return BlockType::Air;
         }
 
@@ -1400,7 +2511,7 @@ impl World {
             if matches!(block, BlockType::Air) {
                 // Check if there's fluid without doing another chunk lookup
                 if chunk.get_fluid(local_x, local_y, local_z) > 0 {
-                    BlockType::Water
+                    chunk.fluid_kind(local_x, local_y, local_z).block_type()
                 } else {
                     BlockType::Air
                 }
@@ -1416,6 +2527,14 @@ impl World {
         self.set_block_with_axis(x, y, z, block_type, None, None);
     }
 
+    /// Components attached to any face of the cell at `(x, y, z)`.
+    pub fn electrical_components_at(&self, x: i32, y: i32, z: i32) -> Vec<ElectricalComponent> {
+        self.electrical
+            .face_nodes(BlockPos3::new(x, y, z))
+            .map(|faces| faces.iter().map(|(_, node)| node.component).collect())
+            .unwrap_or_default()
+    }
+
     pub fn set_block_with_axis(
         &mut self,
         x: i32,
@@ -1425,7 +2544,7 @@ impl World {
         axis: Option<Axis>,
         face: Option<BlockFace>,
     ) {
-        if y < 0 || y >= CHUNK_HEIGHT as i32 {
+        if y < 0 || y >= self.height() as i32 {
             return;
         }
 
@@ -1446,6 +2565,14 @@ impl World {
 
         let world_pos = BlockPos3::new(x, y, z);
         let is_electrical = block_type.is_electrical();
+        // A cell carrying attachments only takes more attachments, or Air, which removes
+        // them; anything else would leave the mesh and the circuit disagreeing about it.
+        if !is_electrical
+            && block_type != BlockType::Air
+            && self.electrical.face_nodes(world_pos).is_some()
+        {
+            return;
+        }
 
         if let Some(chunk) = self.chunks.get_mut(&pos) {
             if !is_electrical {
@@ -1467,7 +2594,9 @@ impl World {
         if is_electrical || block_type == BlockType::Air {
             self.refresh_electrical_block(world_pos);
         }
-        self.queue_fluid_chunk_with_neighbors(pos);
+        if !self.note_batch_edit(x, z) {
+            self.queue_fluid_chunk_with_neighbors(pos);
+        }
     }
 
     fn refresh_electrical_block(&mut self, world_pos: BlockPos3) {
@@ -1509,7 +2638,7 @@ impl World {
     }
 
     pub fn remove_electrical_face(&mut self, x: i32, y: i32, z: i32, face: BlockFace) -> bool {
-        if y < 0 || y >= CHUNK_HEIGHT as i32 {
+        if y < 0 || y >= self.height() as i32 {
             return false;
         }
 
@@ -1533,8 +2662,58 @@ impl World {
         true
     }
 
+    /// Searches a square spiral of loaded columns around `near` for one whose top
+    /// block is opaque solid ground (not leaves, water, or a river bed) with two
+    /// air blocks above it, and returns the feet position standing on it.
+    /// Falls back to the top of the column under `near` when nothing qualifies.
+    pub fn find_spawn(&self, near: Point3<f32>) -> Point3<f32> {
+        let center_x = (near.x + 0.5).floor() as i32;
+        let center_z = (near.z + 0.5).floor() as i32;
+
+        for radius in 0..=SPAWN_SEARCH_RADIUS {
+            for dx in -radius..=radius {
+                for dz in -radius..=radius {
+                    if dx.abs() != radius && dz.abs() != radius {
+                        continue;
+                    }
+                    let (x, z) = (center_x + dx, center_z + dz);
+                    if let Some(ground_y) = self.spawnable_ground(x, z) {
+                        return Point3::new(x as f32, ground_y as f32 + 0.5, z as f32);
+                    }
+                }
+            }
+        }
+
+        let top = (0..self.height() as i32)
+            .rev()
+            .find(|&y| self.get_block(center_x, y, center_z) != BlockType::Air)
+            .unwrap_or(0);
+        Point3::new(center_x as f32, top as f32 + 0.5, center_z as f32)
+    }
+
+    fn spawnable_ground(&self, x: i32, z: i32) -> Option<i32> {
+        let chunk_pos = ChunkPos {
+            x: x.div_euclid(CHUNK_SIZE as i32),
+            z: z.div_euclid(CHUNK_SIZE as i32),
+        };
+        if !self.chunks.contains_key(&chunk_pos) || self.gen.sample_column(x, z).is_river {
+            return None;
+        }
+
+        let top = (0..self.height() as i32 - 2).rev().find(|&y| {
+            self.get_block(x, y, z) != BlockType::Air || self.get_fluid_amount(x, y, z) > 0
+        })?;
+        let ground = self.get_block(x, top, z);
+        let headroom_clear = (1..=2).all(|dy| {
+            self.get_block(x, top + dy, z) == BlockType::Air
+                && self.get_fluid_amount(x, top + dy, z) == 0
+        });
+        let dry = self.get_fluid_amount(x, top, z) == 0;
+        (ground.is_solid() && ground.occludes() && dry && headroom_clear).then_some(top)
+    }
+
     pub fn get_fluid_amount(&self, x: i32, y: i32, z: i32) -> u8 {
-        if y < 0 || y >= CHUNK_HEIGHT as i32 {
+        if y < 0 || y >= self.height() as i32 {
             return 0;
         }
 
@@ -1553,8 +2732,42 @@ impl World {
             .unwrap_or(0)
     }
 
+    /// Liquid in the cell; cells without fluid read as water.
+    pub fn get_fluid_kind(&self, x: i32, y: i32, z: i32) -> FluidKind {
+        if y < 0 || y >= self.height() as i32 {
+            return FluidKind::Water;
+        }
+        self.chunks
+            .get(&ChunkPos {
+                x: x.div_euclid(CHUNK_SIZE as i32),
+                z: z.div_euclid(CHUNK_SIZE as i32),
+            })
+            .map_or(FluidKind::Water, |chunk| {
+                chunk.fluid_kind(
+                    x.rem_euclid(CHUNK_SIZE as i32) as usize,
+                    y as usize,
+                    z.rem_euclid(CHUNK_SIZE as i32) as usize,
+                )
+            })
+    }
+
+    /// Sets the level of whatever liquid the cell holds, water if it is empty.
     pub fn set_fluid_amount(&mut self, x: i32, y: i32, z: i32, amount: u8) {
-        if y < 0 || y >= CHUNK_HEIGHT as i32 {
+        let kind = self.get_fluid_kind(x, y, z);
+        self.set_fluid(x, y, z, kind, amount);
+    }
+
+    /// Fills the cell with `amount` of `kind`. Pouring one liquid into the other turns
+    /// the cell to stone instead.
+    pub fn set_fluid(&mut self, x: i32, y: i32, z: i32, kind: FluidKind, amount: u8) {
+        if y < 0 || y >= self.height() as i32 {
+            return;
+        }
+        if amount > 0
+            && self.get_fluid_amount(x, y, z) > 0
+            && self.get_fluid_kind(x, y, z) != kind
+        {
+            self.set_block(x, y, z, BlockType::Stone);
             return;
         }
 
@@ -1573,16 +2786,76 @@ impl World {
         }
 
         if let Some(chunk) = self.chunks.get_mut(&pos) {
-            chunk.set_fluid(local_x, local_y, local_z, amount.min(MAX_FLUID_LEVEL));
+            // Fluid written directly (placed, poured, scripted) acts as a new source.
+            chunk.set_fluid_with_distance(
+                local_x,
+                local_y,
+                local_z,
+                kind,
+                amount.min(MAX_FLUID_LEVEL),
+                0,
+            );
         }
 
-        self.queue_fluid_chunk_with_neighbors(pos);
+        if !self.note_batch_edit(x, z) {
+            self.queue_fluid_chunk_with_neighbors(pos);
+        }
     }
 
-    pub fn add_fluid(&mut self, x: i32, y: i32, z: i32, amount: u8) {
-        let current = self.get_fluid_amount(x, y, z);
+    /// Writes fluid that flowed in from a cell `distance` steps from its source. Cells that
+    /// already hold fluid keep the shorter of the two distances.
+    fn set_spread_fluid(
+        &mut self,
+        x: i32,
+        y: i32,
+        z: i32,
+        kind: FluidKind,
+        amount: u8,
+        distance: u8,
+    ) {
+        if y < 0 || y >= self.height() as i32 {
+            return;
+        }
+        let pos = ChunkPos {
+            x: x.div_euclid(CHUNK_SIZE as i32),
+            z: z.div_euclid(CHUNK_SIZE as i32),
+        };
+        let local_x = x.rem_euclid(CHUNK_SIZE as i32) as usize;
+        let local_z = z.rem_euclid(CHUNK_SIZE as i32) as usize;
+        let local_y = y as usize;
+        let nearest = self
+            .chunks
+            .get(&pos)
+            .filter(|chunk| chunk.get_fluid(local_x, local_y, local_z) > 0)
+            .map_or(distance, |chunk| {
+                chunk.fluid_distance(local_x, local_y, local_z).min(distance)
+            });
+
+        self.set_fluid(x, y, z, kind, amount);
+        if let Some(chunk) = self.chunks.get_mut(&pos) {
+            chunk.set_fluid_with_distance(local_x, local_y, local_z, kind, amount, nearest);
+        }
+    }
+
+    /// Tops the cell up with `amount` of `kind`; a cell of the other liquid turns to stone.
+    pub fn add_fluid(&mut self, x: i32, y: i32, z: i32, kind: FluidKind, amount: u8) {
+        let current = if self.get_fluid_kind(x, y, z) == kind {
+            self.get_fluid_amount(x, y, z)
+        } else {
+            0
+        };
         let new_amount = (current as u16 + amount as u16).min(MAX_FLUID_LEVEL as u16) as u8;
-        self.set_fluid_amount(x, y, z, new_amount);
+        self.set_fluid(x, y, z, kind, new_amount);
+    }
+
+    /// Takes up to `amount` fluid out of a cell and returns how much was actually removed.
+    pub fn remove_fluid(&mut self, x: i32, y: i32, z: i32, amount: u8) -> u8 {
+        let current = self.get_fluid_amount(x, y, z);
+        let removed = current.min(amount);
+        if removed > 0 {
+            self.set_fluid_amount(x, y, z, current - removed);
+        }
+        removed
     }
 
     fn sample_subsurface_block(&self, rng: &mut SmallRng, world_y: i32) -> BlockType {
@@ -1685,7 +2958,11 @@ impl World {
     }
 
     fn should_carve_cave(&self, x: i32, y: i32, z: i32, column: &ColumnInfo) -> bool {
-        if y <= 0 || y >= CHUNK_HEIGHT as i32 {
+        if !self.gen.config.caves_enabled() {
+            return false;
+        }
+
+        if y <= 0 || y >= self.height() as i32 {
             return false;
         }
 
@@ -1774,11 +3051,14 @@ impl World {
             threshold += 0.025;
         }
 
+        let density = self.gen.config.cave_density;
+        threshold *= density;
+
         if noise_value < threshold {
             return true;
         }
 
-        if depth <= 3 && noise_value < threshold + 0.02 {
+        if depth <= 3 && noise_value < threshold + 0.02 * density {
             return true;
         }
 
@@ -1809,11 +3089,11 @@ impl World {
             return None;
         }
 
-        if height < 0 || height >= CHUNK_HEIGHT as i32 {
+        if height < 0 || height >= chunk.height() as i32 {
             return None;
         }
 
-        let ground_y = height.min((CHUNK_HEIGHT - 1) as i32) as usize;
+        let ground_y = height.min(chunk.height() as i32 - 1) as usize;
         let ground_block = chunk.get_block(x, ground_y, z);
         if ground_block != BlockType::Grass && ground_block != BlockType::Snow {
             return None;
@@ -1855,7 +3135,7 @@ impl World {
         let top = base_y + trunk_height - 1;
         let last_layer = canopy_layers.saturating_sub(1);
         let max_leaf_y = top + last_layer;
-        if max_leaf_y >= CHUNK_HEIGHT {
+        if max_leaf_y >= chunk.height() {
             return false;
         }
 
@@ -1919,3 +3199,737 @@ impl World {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::electric::{ComponentParams, ComponentTelemetry};
+
+    #[test]
+    fn blocks_never_replace_a_cell_that_carries_components() {
+        let mut world = World::with_seed_and_config(TEST_SEED, WorldGenConfig {
+            superflat: Some(DEFAULT_SUPERFLAT_LAYERS.to_vec()),
+            ..WorldGenConfig::default()
+        });
+        let _ = world.update_loaded_chunks(Point3::new(8.0, 90.0, 8.0), 0);
+        let y = world.gen.config.superflat_height().unwrap();
+
+        // A free-standing wire: stone can't be placed over it.
+        world.set_block(4, y + 1, 4, BlockType::CopperWire);
+        world.set_block(4, y + 1, 4, BlockType::Stone);
+        assert_eq!(world.get_block(4, y + 1, 4), BlockType::CopperWire);
+        assert_eq!(
+            world.electrical_components_at(4, y + 1, 4),
+            vec![ElectricalComponent::Wire]
+        );
+
+        // A resistor on top of a host block: the host can't be swapped, and breaking it
+        // takes the resistor along.
+        let host = world.get_block(6, y, 6);
+        world.set_block_with_axis(6, y, 6, BlockType::Resistor, None, Some(BlockFace::Top));
+        assert_eq!(world.get_block(6, y, 6), host);
+        world.set_block(6, y, 6, BlockType::Stone);
+        assert_eq!(world.get_block(6, y, 6), host);
+        world.set_block(6, y, 6, BlockType::Air);
+        assert_eq!(world.get_block(6, y, 6), BlockType::Air);
+        assert!(world.electrical_components_at(6, y, 6).is_empty());
+    }
+
+    #[test]
+    fn saved_world_loads_back_blocks_fluids_and_circuits() {
+        let mut world = World::with_seed_and_config(TEST_SEED, WorldGenConfig {
+            superflat: Some(DEFAULT_SUPERFLAT_LAYERS.to_vec()),
+            ..WorldGenConfig::default()
+        });
+        let _ = world.update_loaded_chunks(Point3::new(8.0, 90.0, 8.0), 1);
+        let y = world.gen.config.superflat_height().unwrap() + 1;
+
+        world.set_block(2, y, 4, BlockType::VoltageSource);
+        world.set_block(3, y, 4, BlockType::CopperWire);
+        world.set_block(4, y, 4, BlockType::Resistor);
+        world.set_block(5, y, 4, BlockType::Ground);
+        let resistor = BlockPos3::new(4, y, 4);
+        let params = ComponentParams::resistor(47.0, 5.0);
+        world
+            .electrical_mut()
+            .set_params(resistor, BlockFace::Top, params);
+        world.set_block(-3, y, 7, BlockType::Stone);
+        world.add_fluid(9, y, 9, FluidKind::Water, MAX_FLUID_LEVEL);
+        world.add_fluid(12, y, 9, FluidKind::Lava, MAX_FLUID_LEVEL);
+        world.tick_electrical(0.0);
+
+        let path = std::env::temp_dir()
+            .join(format!("rustcraft_save_test_{}.rcw", std::process::id()));
+        world.save_to_path(&path).unwrap();
+        let mut loaded = World::load_from_path(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(loaded.gen.seed, world.gen.seed);
+        assert_eq!(loaded.chunks().len(), world.chunks().len());
+        assert_eq!(loaded.state_hash(), world.state_hash());
+        assert_eq!(loaded.get_block(-3, y, 7), BlockType::Stone);
+        assert_eq!(loaded.get_fluid_amount(9, y, 9), MAX_FLUID_LEVEL);
+        assert_eq!(loaded.get_block(12, y, 9), BlockType::Lava);
+        assert!(loaded.active_lava_chunks.contains(&ChunkPos { x: 0, z: 0 }));
+        assert_eq!(loaded.get_light(9, y + 3, 9), world.get_light(9, y + 3, 9));
+        assert_eq!(
+            loaded.electrical().params_at(resistor, BlockFace::Top),
+            Some(params)
+        );
+
+        // Telemetry isn't stored; the first tick derives the same values again.
+        assert_eq!(
+            loaded.electrical().telemetry_at(resistor, BlockFace::Top),
+            Some(ComponentTelemetry::default())
+        );
+        loaded.tick_electrical(0.0);
+        let expected = world.electrical().telemetry_snapshot();
+        let recomputed = loaded.electrical().telemetry_snapshot();
+        assert_eq!(recomputed.len(), expected.len());
+        for (after, before) in recomputed.iter().zip(&expected) {
+            assert_eq!((after.0, after.1, after.2), (before.0, before.1, before.2));
+            assert!(before.3.current > 0.1);
+            assert!((after.3.current - before.3.current).abs() < 1e-4);
+            assert!((after.3.voltage_local - before.3.voltage_local).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn regenerated_chunk_matches_fresh_terrain() {
+        let mut world = World::with_seed_and_config(TEST_SEED, WorldGenConfig::default());
+        let _ = world.update_loaded_chunks(Point3::new(8.0, 90.0, 8.0), 1);
+        let pos = ChunkPos { x: 0, z: 0 };
+        let top = world.height() as i32 - 2;
+
+        for y in 0..20 {
+            world.set_block(3, y, 3, BlockType::Air);
+        }
+        world.set_block(5, top - 4, 5, BlockType::Stone);
+        world.add_fluid(9, top - 4, 9, FluidKind::Water, MAX_FLUID_LEVEL);
+        // A wire run crossing into the neighbouring chunk.
+        world.set_block(15, top, 4, BlockType::CopperWire);
+        world.set_block(16, top, 4, BlockType::CopperWire);
+        world.tick_electrical(0.0);
+        assert_eq!(world.electrical().networks()[0].elements.len(), 2);
+
+        let stale = world.regenerate_chunk(pos);
+        assert_eq!(stale.len(), 5);
+        assert!(stale.contains(&pos));
+
+        let fresh = world.generate_chunk(pos);
+        let chunk = &world.chunks()[&pos];
+        for x in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                for y in 0..chunk.height() {
+                    assert_eq!(chunk.get_block(x, y, z), fresh.get_block(x, y, z));
+                    assert_eq!(chunk.get_fluid(x, y, z), fresh.get_fluid(x, y, z));
+                }
+            }
+        }
+
+        // Only the half of the run outside the chunk survives.
+        world.tick_electrical(0.0);
+        assert!(world.electrical_components_at(15, top, 4).is_empty());
+        assert_eq!(world.electrical_components_at(16, top, 4), vec![ElectricalComponent::Wire]);
+        assert_eq!(world.electrical().networks()[0].elements.len(), 1);
+        assert!(world.regenerate_chunk(ChunkPos { x: 40, z: 40 }).is_empty());
+    }
+
+    #[test]
+    fn light_follows_the_sun_by_day_and_the_moon_by_night() {
+        let noon = WorldEnvironment::light_direction(0.25);
+        assert!(noon[1] > 0.9, "noon light should be overhead: {noon:?}");
+        let morning = WorldEnvironment::light_direction(0.1);
+        let afternoon = WorldEnvironment::light_direction(0.4);
+        assert!(morning[0] > 0.0 && afternoon[0] < 0.0);
+        let midnight = WorldEnvironment::light_direction(0.75);
+        assert!(midnight[1] > 0.9, "the moon lights from above at midnight: {midnight:?}");
+
+        let fixed = WorldEnvironment::direction_from_angles(90.0, 0.0);
+        assert!(fixed[2] > 0.999 && fixed[1].abs() < 1e-6);
+        let overhead = WorldEnvironment::direction_from_angles(30.0, 90.0);
+        assert!(overhead[1] > 0.999);
+    }
+
+    #[test]
+    fn clock_time_starts_at_dawn_and_wraps_at_midnight() {
+        let mut environment = WorldEnvironment::new();
+        for (time, clock, day) in [
+            (0.0, (6, 0), true),
+            (0.25, (12, 0), true),
+            (0.5, (18, 0), false),
+            (0.75, (0, 0), false),
+            (0.99, (5, 45), false),
+        ] {
+            environment.set_time_of_day(time);
+            assert_eq!(environment.clock_time(), clock, "time {time}");
+            assert_eq!(environment.is_daytime(), day, "time {time}");
+        }
+        for clock in [(6, 0), (12, 0), (0, 0), (23, 30), (5, 45)] {
+            environment.set_clock_time(clock.0, clock.1);
+            assert_eq!(environment.clock_time(), clock);
+        }
+    }
+
+    #[test]
+    fn day_length_cycles_through_presets() {
+        let mut environment = WorldEnvironment::new();
+        let presets = WorldEnvironment::DAY_LENGTH_PRESETS;
+        assert_eq!(environment.day_length(), presets[0]);
+        for &preset in presets[1..].iter().chain(&presets[..1]) {
+            assert_eq!(environment.cycle_day_length(), preset);
+        }
+        environment.set_day_length(45.0);
+        assert_eq!(environment.cycle_day_length(), presets[0]);
+    }
+
+    #[test]
+    fn same_seed_same_terrain_and_other_seeds_differ() {
+        let first = World::with_seed(1234);
+        let second = World::with_seed(1234);
+        let other = World::with_seed(1235);
+        assert_eq!(first.seed(), 1234);
+
+        let mut differing = 0;
+        for x in (-256..256).step_by(32) {
+            for z in (-256..256).step_by(32) {
+                let a = first.gen.sample_column(x, z);
+                let b = second.gen.sample_column(x, z);
+                assert_eq!((a.biome, a.height, a.is_river), (b.biome, b.height, b.is_river));
+                assert_eq!(a.altitude.to_bits(), b.altitude.to_bits());
+                if other.gen.sample_column(x, z).height != a.height {
+                    differing += 1;
+                }
+            }
+        }
+        assert!(differing > 64, "only {differing} of 256 columns changed height");
+    }
+
+    #[test]
+    fn amplified_preset_exaggerates_column_heights() {
+        let normal = WorldGenContext::new(7, WorldGenConfig::default());
+        let amplified = WorldGenContext::new(
+            7,
+            WorldGenConfig {
+                amplified: true,
+                ..WorldGenConfig::default()
+            },
+        );
+
+        let mut normal_range = (i32::MAX, i32::MIN);
+        let mut amplified_range = (i32::MAX, i32::MIN);
+        let mut taller_columns = 0;
+        for x in (-512..512).step_by(16) {
+            for z in (-512..512).step_by(16) {
+                let base = normal.sample_column(x, z);
+                let tall = amplified.sample_column(x, z);
+                assert!((6..=CHUNK_HEIGHT as i32 - 5).contains(&tall.height));
+                if !base.is_river && tall.height > base.height {
+                    taller_columns += 1;
+                }
+                normal_range = (
+                    normal_range.0.min(base.height),
+                    normal_range.1.max(base.height),
+                );
+                amplified_range = (
+                    amplified_range.0.min(tall.height),
+                    amplified_range.1.max(tall.height),
+                );
+            }
+        }
+
+        assert!(taller_columns > 0);
+        assert!(amplified_range.1 > normal_range.1);
+        assert!(amplified_range.1 - amplified_range.0 > normal_range.1 - normal_range.0);
+    }
+
+    fn has_air_below_surface(world: &World, pos: ChunkPos, chunk: &Chunk) -> bool {
+        for x in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                let world_x = pos.x * CHUNK_SIZE as i32 + x as i32;
+                let world_z = pos.z * CHUNK_SIZE as i32 + z as i32;
+                let height = world.gen.sample_column(world_x, world_z).height;
+                for y in 1..height.max(1) as usize {
+                    if chunk.get_block(x, y, z) == BlockType::Air && chunk.get_fluid(x, y, z) == 0
+                    {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    #[test]
+    fn vegetation_density_scales_trees_and_flowers() {
+        let count_vegetation = |density: f64| {
+            let world = World::with_seed_and_config(TEST_SEED, WorldGenConfig {
+                vegetation_density: density,
+                locked_biome: Some(BiomeType::Forest),
+                ..WorldGenConfig::default()
+            });
+            let (mut trees, mut flowers) = (0, 0);
+            for cx in -2..2 {
+                for cz in -2..2 {
+                    let chunk = world.generate_chunk(ChunkPos { x: cx, z: cz });
+                    for x in 0..CHUNK_SIZE {
+                        for z in 0..CHUNK_SIZE {
+                            for y in 0..chunk.height() {
+                                match chunk.get_block(x, y, z) {
+                                    BlockType::Wood => trees += 1,
+                                    BlockType::FlowerRose | BlockType::FlowerTulip => {
+                                        flowers += 1
+                                    }
+                                    _ => {}
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            (trees, flowers)
+        };
+
+        assert_eq!(count_vegetation(0.0), (0, 0));
+        let (stock_trees, stock_flowers) = count_vegetation(1.0);
+        let (dense_trees, dense_flowers) = count_vegetation(MAX_VEGETATION_DENSITY);
+        assert!(stock_trees > 0 && stock_flowers > 0);
+        assert!(dense_trees > stock_trees, "{dense_trees} <= {stock_trees}");
+        assert!(dense_flowers > stock_flowers, "{dense_flowers} <= {stock_flowers}");
+    }
+
+    #[test]
+    fn zero_cave_density_leaves_no_air_below_surface() {
+        let caves = World::with_seed(TEST_SEED);
+        let solid = World::with_seed_and_config(TEST_SEED, WorldGenConfig {
+            cave_density: 0.0,
+            ..WorldGenConfig::default()
+        });
+
+        let mut carved_somewhere = false;
+        for cx in -2..2 {
+            for cz in -2..2 {
+                let pos = ChunkPos { x: cx, z: cz };
+                carved_somewhere |= has_air_below_surface(&caves, pos, &caves.generate_chunk(pos));
+                assert!(!has_air_below_surface(&solid, pos, &solid.generate_chunk(pos)));
+            }
+        }
+        assert!(carved_somewhere, "expected the default generator to carve caves");
+    }
+
+    #[test]
+    fn locked_biome_applies_to_every_column() {
+        for biome in [BiomeType::Desert, BiomeType::Jungle] {
+            let gen = WorldGenContext::new(
+                7,
+                WorldGenConfig {
+                    locked_biome: Some(biome),
+                    ..WorldGenConfig::default()
+                },
+            );
+            for x in (-1024..1024).step_by(64) {
+                for z in (-1024..1024).step_by(64) {
+                    assert_eq!(gen.sample_column(x, z).biome, biome);
+                }
+            }
+        }
+        assert_eq!(BiomeType::from_name("jungle"), Some(BiomeType::Jungle));
+        assert_eq!(BiomeType::from_name("ocean"), None);
+    }
+
+    #[test]
+    fn budgeted_chunk_loading_fills_in_nearest_first() {
+        let mut world = World::with_seed_and_config(TEST_SEED, WorldGenConfig {
+            superflat: Some(DEFAULT_SUPERFLAT_LAYERS.to_vec()),
+            ..WorldGenConfig::default()
+        });
+        let camera = Point3::new(40.0, 100.0, -8.0);
+        let player = ChunkPos { x: 2, z: -1 };
+        let distance = |pos: &ChunkPos| (pos.x - player.x).pow(2) + (pos.z - player.z).pow(2);
+
+        let first = world.update_loaded_chunks_within_budget(camera, 2, 1);
+        assert_eq!(first.loaded, vec![player]);
+
+        let mut order = first.loaded;
+        loop {
+            let changes = world.update_loaded_chunks_within_budget(camera, 2, 4);
+            if changes.loaded.is_empty() {
+                break;
+            }
+            assert!(changes.loaded.len() <= 4);
+            order.extend(changes.loaded);
+        }
+        assert_eq!(order.len(), 25);
+        assert!(order.windows(2).all(|pair| distance(&pair[0]) <= distance(&pair[1])));
+    }
+
+    #[test]
+    fn find_spawn_returns_dry_solid_ground_with_headroom() {
+        let mut world = World::with_seed(TEST_SEED);
+        let near = Point3::new(0.0, 100.0, 0.0);
+        world.update_loaded_chunks(near, 1);
+
+        let spawn = world.find_spawn(near);
+        let x = spawn.x as i32;
+        let z = spawn.z as i32;
+        let ground_y = (spawn.y - 0.5).round() as i32;
+        assert_eq!(spawn.y, ground_y as f32 + 0.5);
+
+        let ground = world.get_block(x, ground_y, z);
+        assert!(ground.is_solid() && ground.occludes(), "spawned on {ground:?}");
+        assert_eq!(world.get_fluid_amount(x, ground_y, z), 0);
+        for dy in 1..=2 {
+            assert_eq!(world.get_block(x, ground_y + dy, z), BlockType::Air);
+            assert_eq!(world.get_fluid_amount(x, ground_y + dy, z), 0);
+        }
+    }
+
+    #[test]
+    fn double_height_world_keeps_terrain_and_spawns_on_the_surface() {
+        let tall_config = WorldGenConfig {
+            height: CHUNK_HEIGHT * 2,
+            ..WorldGenConfig::default()
+        };
+        let normal = WorldGenContext::new(7, WorldGenConfig::default());
+        let tall = WorldGenContext::new(7, tall_config.clone());
+        for x in (-512..512).step_by(32) {
+            for z in (-512..512).step_by(32) {
+                assert_eq!(tall.sample_column(x, z).height, normal.sample_column(x, z).height);
+            }
+        }
+
+        let mut world = World::with_seed_and_config(TEST_SEED, tall_config);
+        let near = Point3::new(0.0, 100.0, 0.0);
+        world.update_loaded_chunks(near, 1);
+        let chunk = &world.chunks()[&ChunkPos { x: 0, z: 0 }];
+        assert_eq!(chunk.height(), CHUNK_HEIGHT * 2);
+        for y in CHUNK_HEIGHT..chunk.height() {
+            assert_eq!(chunk.get_block(3, y, 3), BlockType::Air);
+            assert_eq!(chunk.get_skylight(3, y, 3), 15);
+        }
+
+        let spawn = world.find_spawn(near);
+        let (x, z) = (spawn.x as i32, spawn.z as i32);
+        let ground_y = (spawn.y - 0.5).round() as i32;
+        assert!(ground_y < CHUNK_HEIGHT as i32, "spawned in the extra sky at {ground_y}");
+        assert!(world.get_block(x, ground_y, z).is_solid());
+        for dy in 1..=2 {
+            assert_eq!(world.get_block(x, ground_y + dy, z), BlockType::Air);
+        }
+    }
+
+    #[test]
+    fn pasted_region_keeps_blocks_and_electrical_parts() {
+        let mut world = World::with_seed_and_config(TEST_SEED, WorldGenConfig {
+            superflat: Some(DEFAULT_SUPERFLAT_LAYERS.to_vec()),
+            ..WorldGenConfig::default()
+        });
+        let y = 100;
+        world.set_block(0, y, 0, BlockType::Stone);
+        world.set_block(1, y, 0, BlockType::Terracotta);
+        let resistor = BlockPos3::new(2, y, 1);
+        world.set_block_with_axis(2, y, 1, BlockType::Resistor, Some(Axis::Z), None);
+        let params = ComponentParams::resistor(47.0, 0.5);
+        world.electrical_mut().set_params(resistor, BlockFace::Top, params);
+        let switch = BlockPos3::new(0, y + 1, 1);
+        world.set_block(0, y + 1, 1, BlockType::Switch);
+        world.electrical_mut().toggle_switch(switch, BlockFace::Top);
+
+        let schematic = world.copy_region((2, y + 1, 1), (0, y, 0));
+        assert_eq!(schematic.size(), (3, 2, 2));
+        assert_eq!(schematic.components().len(), 2);
+
+        // Paste over a stone block into the next chunks over.
+        world.set_block(41, y, 40, BlockType::Stone);
+        let touched = world.paste_region((40, y, 40), &schematic);
+        assert!(touched.contains(&ChunkPos { x: 2, z: 2 }));
+        assert_eq!(world.get_block(40, y, 40), BlockType::Stone);
+        assert_eq!(world.get_block(41, y, 40), BlockType::Terracotta);
+        assert_eq!(world.get_block(42, y, 40), BlockType::Air);
+
+        let electrical = world.electrical();
+        let resistor = BlockPos3::new(42, y, 41);
+        assert_eq!(electrical.axis_at(resistor, BlockFace::Top), Some(Axis::Z));
+        assert_eq!(electrical.params_at(resistor, BlockFace::Top), Some(params));
+        let switch = BlockPos3::new(40, y + 1, 41);
+        assert_eq!(electrical.switch_closed(switch, BlockFace::Top), Some(true));
+        assert_eq!(world.copy_region((40, y, 40), (42, y + 1, 41)), schematic);
+    }
+
+    #[test]
+    fn edit_batch_rebuilds_the_circuit_once_and_reports_touched_chunks() {
+        let mut world = World::with_seed_and_config(TEST_SEED, WorldGenConfig {
+            superflat: Some(DEFAULT_SUPERFLAT_LAYERS.to_vec()),
+            ..WorldGenConfig::default()
+        });
+        let rebuilds_before = world.electrical().rebuild_count();
+
+        // A source, a wire run crossing the x = 16 chunk border, and a ground.
+        let y = 100;
+        let touched = world.edit_batch(|world| {
+            world.set_block(10, y, 4, BlockType::VoltageSource);
+            for x in 11..22 {
+                world.set_block(x, y, 4, BlockType::CopperWire);
+            }
+            world.set_block(22, y, 4, BlockType::Ground);
+        });
+
+        assert_eq!(world.electrical().rebuild_count(), rebuilds_before + 1);
+        assert_eq!(world.electrical().node_count(), 13);
+        assert!(!world.electrical().networks().is_empty());
+        for chunk_x in [0, 1] {
+            assert!(touched.contains(&ChunkPos { x: chunk_x, z: 0 }));
+        }
+        assert!(!touched.contains(&ChunkPos { x: 0, z: -1 }));
+
+        // Nothing is left dirty, so the next tick has nothing to rebuild.
+        world.tick_electrical(0.0);
+        assert_eq!(world.electrical().rebuild_count(), rebuilds_before + 1);
+    }
+
+    #[test]
+    fn generated_rivers_hold_settled_fluid_that_wakes_on_edit() {
+        // On seed 0 the first river sits beside a deeper channel column, whose widening
+        // used to flood this column's floor.
+        let mut world = World::with_seed_and_config(0, WorldGenConfig {
+            cave_density: 0.0,
+            ..WorldGenConfig::default()
+        });
+
+        // Find a river column, load around it, and check water sits on the river floor.
+        let (x, z, column) = (-2048..2048)
+            .step_by(8)
+            .flat_map(|x| (-2048..2048).step_by(8).map(move |z| (x, z)))
+            .map(|(x, z)| (x, z, world.gen.sample_column(x, z)))
+            .find(|(_, _, column)| column.is_river && column.height <= WATER_LEVEL)
+            .expect("no river within 2048 blocks of the origin");
+        world.update_loaded_chunks(Point3::new(x as f32, 100.0, z as f32), 1);
+        assert!(world.get_block(x, column.height, z).is_solid());
+        assert!(world.get_fluid_amount(x, column.height + 1, z) > 0);
+
+        // Loading doesn't start the simulation, so rivers don't flood on load.
+        assert!(world.active_fluid_chunks.is_empty());
+        assert!(!world.step_fluids());
+
+        // An edit beside the water queues its chunk like any other fluid.
+        world.set_block(x, column.height + 3, z, BlockType::Stone);
+        let chunk = ChunkPos {
+            x: x.div_euclid(CHUNK_SIZE as i32),
+            z: z.div_euclid(CHUNK_SIZE as i32),
+        };
+        assert!(world.active_fluid_chunks.contains(&chunk));
+    }
+
+    /// Largest Manhattan distance from `source` of any wet cell on the source's layer.
+    fn wet_radius(world: &World, source: (i32, i32, i32), reach: i32) -> i32 {
+        let mut radius = 0;
+        for x in source.0 - reach..=source.0 + reach {
+            for z in source.2 - reach..=source.2 + reach {
+                if world.get_fluid_amount(x, source.1, z) > 0 {
+                    radius = radius.max((x - source.0).abs() + (z - source.2).abs());
+                }
+            }
+        }
+        radius
+    }
+
+    fn flood_from_source(limit: Option<u8>) -> i32 {
+        let config = WorldGenConfig {
+            superflat: Some(DEFAULT_SUPERFLAT_LAYERS.to_vec()),
+            ..WorldGenConfig::default()
+        };
+        let mut world = World::with_seed_and_config(TEST_SEED, config);
+        world.set_fluid_spread_limit(limit);
+        let floor = world.gen.config.superflat_height().unwrap();
+        let source = (8, floor + 1, 8);
+        for _ in 0..400 {
+            world.set_fluid_amount(source.0, source.1, source.2, MAX_FLUID_LEVEL);
+            world.step_fluids();
+        }
+        wet_radius(&world, source, 24)
+    }
+
+    #[test]
+    fn fluid_spread_limit_bounds_wet_radius() {
+        let unlimited = flood_from_source(None);
+        let limited = flood_from_source(Some(1));
+        assert!(limited <= 1, "water reached {limited} blocks from the source");
+        assert!(unlimited > limited, "flood never spread past the limit ({unlimited})");
+    }
+
+    /// Superflat world with a pit dug into the top layer at each of `cells`, filled with
+    /// source water.
+    fn world_with_pool(cells: &[(i32, i32)]) -> (World, i32) {
+        let mut world = World::with_seed_and_config(TEST_SEED, WorldGenConfig {
+            superflat: Some(DEFAULT_SUPERFLAT_LAYERS.to_vec()),
+            ..WorldGenConfig::default()
+        });
+        let y = world.gen.config.superflat_height().unwrap();
+        for &(x, z) in cells {
+            world.set_block(x, y, z, BlockType::Air);
+            world.set_fluid_amount(x, y, z, MAX_FLUID_LEVEL);
+        }
+        (world, y)
+    }
+
+    #[test]
+    fn two_by_two_source_pool_refills_after_scooping() {
+        let pool = [(8, 8), (9, 8), (8, 9), (9, 9)];
+        let (mut world, y) = world_with_pool(&pool);
+        world.set_fluid_amount(8, y, 8, 0);
+        for _ in 0..20 {
+            world.regenerate_water_sources();
+            world.step_fluids();
+        }
+        for (x, z) in pool {
+            assert_eq!(world.get_fluid_amount(x, y, z), MAX_FLUID_LEVEL, "({x}, {z})");
+        }
+    }
+
+    #[test]
+    fn single_source_in_a_trench_stays_finite() {
+        let trench = [(7, 8), (8, 8), (9, 8), (10, 8)];
+        let (mut world, y) = world_with_pool(&trench);
+        for (x, z) in [(7, 8), (9, 8), (10, 8)] {
+            world.set_fluid_amount(x, y, z, 0);
+        }
+        for _ in 0..40 {
+            assert!(!world.regenerate_water_sources());
+            world.step_fluids();
+        }
+        let total: u32 = trench
+            .iter()
+            .map(|&(x, z)| world.get_fluid_amount(x, y, z) as u32)
+            .sum();
+        assert!(total <= MAX_FLUID_LEVEL as u32, "water grew to {total}");
+    }
+
+    #[test]
+    fn sponge_drains_a_pool_then_turns_wet() {
+        let pool: Vec<(i32, i32)> = (6..=10)
+            .flat_map(|x| (6..=10).map(move |z| (x, z)))
+            .collect();
+        let (mut world, y) = world_with_pool(&pool);
+        world.set_block(8, y, 8, BlockType::Sponge);
+        for _ in 0..10 {
+            world.regenerate_water_sources();
+            world.absorb_into_sponges();
+            world.step_fluids();
+        }
+        for &(x, z) in &pool {
+            assert_eq!(world.get_fluid_amount(x, y, z), 0, "({x}, {z})");
+        }
+        assert_eq!(world.get_block(8, y, 8), BlockType::WetSponge);
+        assert!(!world.absorb_into_sponges());
+    }
+
+    #[test]
+    fn lava_steps_apart_from_water_and_cools_where_they_meet() {
+        let mut world = World::with_seed_and_config(TEST_SEED, WorldGenConfig {
+            superflat: Some(DEFAULT_SUPERFLAT_LAYERS.to_vec()),
+            ..WorldGenConfig::default()
+        });
+        let y = world.gen.config.superflat_height().unwrap() + 1;
+        world.set_fluid(8, y, 8, FluidKind::Lava, MAX_FLUID_LEVEL);
+        assert!(!world.step_fluids(), "the water step moved lava");
+        assert_eq!(world.get_fluid_amount(8, y, 9), 0);
+
+        assert!(world.step_lava());
+        assert_eq!(world.get_block(8, y, 9), BlockType::Lava);
+        let moved = world.take_fluid_remesh();
+        assert!(moved.contains(&ChunkPos { x: 0, z: 0 }), "{moved:?}");
+        assert!(world.take_fluid_remesh().is_empty());
+
+        world.set_fluid(8, y, 10, FluidKind::Water, MAX_FLUID_LEVEL);
+        world.step_lava();
+        assert_eq!(world.get_block(8, y, 9), BlockType::Stone);
+        assert_eq!(world.get_block(8, y, 10), BlockType::Water);
+
+        // Pouring lava straight into water hardens the cell too.
+        world.set_fluid(8, y, 10, FluidKind::Lava, MAX_FLUID_LEVEL);
+        assert_eq!(world.get_block(8, y, 10), BlockType::Stone);
+        assert_eq!(world.get_fluid_amount(8, y, 10), 0);
+    }
+
+    #[test]
+    fn remove_fluid_takes_at_most_what_the_cell_holds() {
+        let mut world = World::with_seed_and_config(TEST_SEED, WorldGenConfig {
+            superflat: Some(DEFAULT_SUPERFLAT_LAYERS.to_vec()),
+            ..WorldGenConfig::default()
+        });
+        let y = world.gen.config.superflat_height().unwrap() + 1;
+        world.set_fluid_amount(3, y, 3, MAX_FLUID_LEVEL);
+
+        assert_eq!(world.remove_fluid(3, y, 3, MAX_FLUID_LEVEL), MAX_FLUID_LEVEL);
+        assert_eq!(world.get_fluid_amount(3, y, 3), 0);
+        assert_eq!(world.remove_fluid(3, y, 3, MAX_FLUID_LEVEL), 0);
+
+        world.set_fluid_amount(4, y, 4, 5);
+        assert_eq!(world.remove_fluid(4, y, 4, 2), 2);
+        assert_eq!(world.get_fluid_amount(4, y, 4), 3);
+    }
+
+    #[test]
+    fn state_hash_matches_identical_worlds_and_survives_edit_undo() {
+        let superflat = || {
+            let mut world = World::with_seed_and_config(TEST_SEED, WorldGenConfig {
+                superflat: Some(DEFAULT_SUPERFLAT_LAYERS.to_vec()),
+                ..WorldGenConfig::default()
+            });
+            let _ = world.update_loaded_chunks(Point3::new(8.0, 90.0, 8.0), 0);
+            world
+        };
+        let mut world = superflat();
+        let original = world.state_hash();
+        assert_eq!(original, superflat().state_hash());
+
+        let y = world.gen.config.superflat_height().unwrap();
+        let surface = world.get_block(4, y, 4);
+        world.set_block(4, y, 4, BlockType::Stone);
+        assert_ne!(world.state_hash(), original);
+        world.set_block(4, y, 4, surface);
+        assert_eq!(world.state_hash(), original);
+
+        world.set_block(5, y + 1, 5, BlockType::Resistor);
+        assert_ne!(world.state_hash(), original);
+        world.set_block(5, y + 1, 5, BlockType::Air);
+        assert_eq!(world.state_hash(), original);
+
+        world.set_fluid_amount(6, y + 1, 6, MAX_FLUID_LEVEL);
+        assert_ne!(world.state_hash(), original);
+        world.set_fluid_amount(6, y + 1, 6, 0);
+        assert_eq!(world.state_hash(), original);
+    }
+
+    #[test]
+    fn atmosphere_preset_ignores_time_of_day() {
+        let mut world = World::with_seed_and_config(TEST_SEED, WorldGenConfig::default());
+        world.set_atmosphere_preset(AtmospherePreset::Sunset);
+        world.environment_mut().set_time_of_day(0.1);
+        let morning = world.atmosphere_at(0, 0);
+        world.environment_mut().set_time_of_day(0.8);
+        let night = world.atmosphere_at(40, -40);
+        assert_eq!(morning.sky_horizon, night.sky_horizon);
+        assert_eq!(morning.fog_color, night.fog_color);
+
+        world.set_atmosphere_preset(AtmospherePreset::Dynamic);
+        let dynamic_night = world.atmosphere_at(0, 0);
+        assert!(dynamic_night.daylight < morning.daylight);
+    }
+
+    #[test]
+    fn submerged_atmosphere_blends_toward_underwater_fog() {
+        let surface = AtmospherePreset::Overcast.sample().unwrap();
+        assert_eq!(surface.submerged(FluidKind::Water, 0.0).fog_color, surface.fog_color);
+
+        let underwater = surface.submerged(FluidKind::Water, 1.0);
+        for (channel, target) in underwater.fog_color.iter().zip(UNDERWATER_FOG_COLOR) {
+            assert!((channel - target).abs() < 1e-6);
+        }
+        assert!(underwater.fog_density > surface.fog_density);
+
+        let halfway = surface.submerged(FluidKind::Water, 0.5);
+        assert!(halfway.fog_density > surface.fog_density);
+        assert!(halfway.fog_density < underwater.fog_density);
+    }
+}